@@ -26,6 +26,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+const SHADER_DIR: &str = "src/core/gfx/shaders";
+
+// Sources pull in shared code (`*.glsl`) with `#include "<file>"`, resolved next to them.
+fn resolve_include(name: &str, _: shaderc::IncludeType, _: &str, _: usize) -> shaderc::IncludeCallbackResult {
+    let path = Path::new(SHADER_DIR).join(name);
+    let content = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(shaderc::ResolvedInclude { resolved_name: path.to_string_lossy().into_owned(), content })
+}
+
 fn compile_vulkan_shaders(compiler: &mut Compiler, out_dir: &Path) -> Result<(), Box<dyn Error>> {
     use std::{fmt::Write as _, time::SystemTime};
 
@@ -41,17 +50,34 @@ fn compile_vulkan_shaders(compiler: &mut Compiler, out_dir: &Path) -> Result<(),
         }
     }
 
-    let mut opts = shaderc::CompileOptions::new()?;
+    // Sources also built with a macro defined, as `<stem>_<suffix>.<ext>.spv`.
+    const VARIANTS: &[(&str, &str, &str)] = &[("vulkan_shader.frag", "BINDLESS", "bindless")];
+
     let profile = std::env::var("PROFILE").unwrap_or_else(|_| "debug".to_string());
-    if profile == "release" {
-        opts.set_optimization_level(shaderc::OptimizationLevel::Performance);
-    } else {
-        opts.set_optimization_level(shaderc::OptimizationLevel::Zero);
-        opts.set_generate_debug_info();
-    }
+    let options_for = |define: Option<&str>| -> Result<shaderc::CompileOptions, Box<dyn Error>> {
+        let mut opts = shaderc::CompileOptions::new()?;
+        if profile == "release" {
+            opts.set_optimization_level(shaderc::OptimizationLevel::Performance);
+        } else {
+            opts.set_optimization_level(shaderc::OptimizationLevel::Zero);
+            opts.set_generate_debug_info();
+        }
+        if let Some(define) = define {
+            opts.add_macro_definition(define, None);
+        }
+        opts.set_include_callback(resolve_include);
+        Ok(opts)
+    };
+
+    // Any shader may include the shared files, so their newest change dates them all.
+    let include_mtime = glob::glob(&format!("{SHADER_DIR}/*.glsl"))?
+        .filter_map(Result::ok)
+        .filter_map(|path| fs::metadata(path).ok()?.modified().ok())
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH);
 
     // Gather candidates deterministically
-    let mut paths: Vec<_> = glob::glob("src/core/gfx/shaders/vulkan_*.*")?
+    let mut paths: Vec<_> = glob::glob(&format!("{SHADER_DIR}/vulkan_*.*"))?
         .filter_map(Result::ok)
         .collect();
     paths.sort();
@@ -68,50 +94,59 @@ fn compile_vulkan_shaders(compiler: &mut Compiler, out_dir: &Path) -> Result<(),
         };
 
         let src_meta = fs::metadata(&path)?;
-        let src_mtime = src_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let src_mtime = src_meta.modified().unwrap_or(SystemTime::UNIX_EPOCH).max(include_mtime);
 
         let file_name = path.file_name().unwrap().to_string_lossy().to_string();
-        let dest_path = out_dir.join(format!("{file_name}.spv"));
-
-        // Timestamp short-circuit (fast path)
-        if let Ok(dest_meta) = fs::metadata(&dest_path) {
-            if let Ok(dest_mtime) = dest_meta.modified() {
-                if dest_mtime >= src_mtime {
-                    // .spv is up-to-date for this profile/options — skip
-                    continue;
-                }
-            }
+        let mut builds = vec![(format!("{file_name}.spv"), None)];
+        for &(source_name, define, suffix) in VARIANTS.iter().filter(|v| v.0 == file_name) {
+            let (stem, ext) = source_name.rsplit_once('.').unwrap();
+            builds.push((format!("{stem}_{suffix}.{ext}.spv"), Some(define)));
         }
 
-        let source = fs::read_to_string(&path)?;
-        let src_name = path.to_string_lossy();
-
-        let spirv = match compiler.compile_into_spirv(&source, kind, &src_name, "main", Some(&opts))
-        {
-            Ok(ok) => ok,
-            Err(e) => {
-                // Pretty error with annotated source
-                let mut msg = String::new();
-                writeln!(&mut msg, "Shader compile failed: {}", src_name)?;
-                for (i, line) in source.lines().enumerate() {
-                    writeln!(&mut msg, "{:4} | {}", i + 1, line)?;
+        for (dest_name, define) in builds {
+            let dest_path = out_dir.join(dest_name);
+
+            // Timestamp short-circuit (fast path)
+            if let Ok(dest_meta) = fs::metadata(&dest_path) {
+                if let Ok(dest_mtime) = dest_meta.modified() {
+                    if dest_mtime >= src_mtime {
+                        // .spv is up-to-date for this profile/options — skip
+                        continue;
+                    }
                 }
-                writeln!(&mut msg, "\nError: {e}")?;
-                return Err(msg.into());
             }
-        };
 
-        // Byte-compare to avoid touching mtime if unchanged
-        let new_bytes = spirv.as_binary_u8();
-        let needs_write = match fs::read(&dest_path) {
-            Ok(old_bytes) => old_bytes != new_bytes,
-            Err(_) => true,
-        };
-        if needs_write {
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?;
+            let source = fs::read_to_string(&path)?;
+            let src_name = path.to_string_lossy();
+            let opts = options_for(define)?;
+
+            let spirv = match compiler.compile_into_spirv(&source, kind, &src_name, "main", Some(&opts))
+            {
+                Ok(ok) => ok,
+                Err(e) => {
+                    // Pretty error with annotated source
+                    let mut msg = String::new();
+                    writeln!(&mut msg, "Shader compile failed: {}", src_name)?;
+                    for (i, line) in source.lines().enumerate() {
+                        writeln!(&mut msg, "{:4} | {}", i + 1, line)?;
+                    }
+                    writeln!(&mut msg, "\nError: {e}")?;
+                    return Err(msg.into());
+                }
+            };
+
+            // Byte-compare to avoid touching mtime if unchanged
+            let new_bytes = spirv.as_binary_u8();
+            let needs_write = match fs::read(&dest_path) {
+                Ok(old_bytes) => old_bytes != new_bytes,
+                Err(_) => true,
+            };
+            if needs_write {
+                if let Some(parent) = dest_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&dest_path, new_bytes)?;
             }
-            fs::write(&dest_path, new_bytes)?;
         }
     }
     Ok(())
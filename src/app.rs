@@ -1,23 +1,31 @@
-use crate::core::gfx::{self as renderer, create_backend, BackendType, RenderList};
-use crate::core::input::{self, InputSource, InputState, Lane};
+use crate::core::gfx::{self as renderer, create_backend, BackendType, FrameStats, PresentSettings, RenderList, DebugLabels};
+use crate::core::gfx::frame_limiter::FrameLimiter;
+use crate::core::input::{self, InputSource, InputState};
+use crate::core::display;
+use crate::core::instance;
+use crate::core::profiler;
+use crate::core::screenshot;
+use crate::game::install;
 use crate::core::space::{self as space, Metrics};
-use crate::game::{profile, scores, scroll::ScrollSpeedSetting};
+use crate::game::{judgment, profile, scores, scroll::ScrollSpeedSetting};
 use crate::assets::AssetManager;
 use crate::ui::color;
-use crate::screens::{gameplay, menu, options, init, select_color, select_music, sandbox, evaluation, player_options, Screen as CurrentScreen, ScreenAction, Screen};
+use crate::ui::components::screen_bar;
+use crate::screens::{gameplay, menu, options, init, select_color, select_music, sandbox, evaluation, player_options, sync_test, Screen as CurrentScreen, ScreenAction, Screen};
 use crate::game::parsing::simfile as song_loading;
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalSize,
     event::WindowEvent,
-    event_loop::{ActiveEventLoop, EventLoop},
-    window::Window,
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    window::{Window, WindowLevel},
 };
 
 use log::{error, warn, info};
-use std::{error::Error, sync::Arc, time::Instant};
+use std::{error::Error, path::PathBuf, sync::Arc, time::Instant};
 
 use crate::ui::actors::Actor;
+use crate::ui::compose::EffectClocks;
 /* -------------------- gamepad -------------------- */
 use crate::core::gamepad;
 use crate::core::gamepad::{GpSystemEvent, PadEvent, PadDir, PadButton, FaceBtn};
@@ -40,6 +48,9 @@ enum TransitionState {
 pub struct App {
     window: Option<Arc<Window>>,
     backend: Option<renderer::Backend>,
+    /// Optional now-playing window (`MarqueeWindow` in the config) and its backend surface.
+    marquee_window: Option<Arc<Window>>,
+    marquee: Option<renderer::SecondaryWindow>,
     backend_type: BackendType,
     asset_manager: AssetManager,
     current_screen: CurrentScreen,
@@ -53,37 +64,58 @@ pub struct App {
     last_frame_time: Instant,
     start_time: Instant,
     vsync_enabled: bool,
-    fullscreen_enabled: bool,
+    frame_limiter: FrameLimiter,
     metrics: Metrics,
     last_fps: f32,
     last_vpf: u32,
-    current_frame_vpf: u32,
+    last_present_latency: Option<f32>,
+    last_frame_stats: FrameStats,
+    current_frame_stats: FrameStats,
     show_overlay: bool,
+    /// Render statistics and GPU time (F2); GPU timing only runs while it's shown.
+    show_perf_overlay: bool,
+    /// F12 was pressed; the next drawn frame is saved as a screenshot.
+    screenshot_requested: bool,
     transition: TransitionState,
     init_state: init::State,
     select_color_state: select_color::State,
     select_music_state: select_music::State,
     preferred_difficulty_index: usize,
     sandbox_state: sandbox::State,
+    sync_test_state: sync_test::State,
     evaluation_state: evaluation::State,
     session_start_time: Option<Instant>,
-    display_width: u32,
-    display_height: u32,
+    /// Song asked for by another launch; selected once the wheel is showing.
+    pending_open_song: Option<PathBuf>,
+    /// Set when dropped songs were installed; the wheel is rebuilt before it is next shown.
+    song_list_changed: bool,
 
     /* gamepad */
     gilrs: Option<Gilrs>,
     active_gamepad_id: Option<GamepadId>,
     gamepad_state: gamepad::GamepadState,
-    gamepad_overlay_state: Option<(String, Instant)>,
+    system_message_state: Option<(String, Instant)>,
+    /// The window was closed mid-song; waiting for the player to confirm.
+    exit_confirm_open: bool,
+    /// When the first frame was drawn; the launch counts as started
+    /// (`core::safe_mode`) a while after, or on a clean exit.
+    first_frame_at: Option<Instant>,
+    launch_confirmed: bool,
+    /// Session data has been written out and audio stopped; set once.
+    session_flushed: bool,
+    /// When the renderer was last rebuilt after losing its device.
+    backend_recovered_at: Option<Instant>,
+    /// Set by `--benchmark`: the run being measured (`core::benchmark`).
+    benchmark: Option<crate::core::benchmark::Run>,
 }
 
 impl App {
     fn new(
         backend_type: BackendType,
         vsync_enabled: bool,
-        fullscreen_enabled: bool,
         show_overlay: bool,
         color_index: i32,
+        benchmark: bool,
     ) -> Self {
         let config = crate::config::get();
         let display_width = config.display_width;
@@ -114,19 +146,28 @@ impl App {
             window: None, backend: None, backend_type, asset_manager: AssetManager::new(),
             current_screen: CurrentScreen::Init, init_state, menu_state, gameplay_state: None, options_state,
             player_options_state: None,
-            select_color_state, select_music_state, sandbox_state: sandbox::init(), evaluation_state,
+            select_color_state, select_music_state, sandbox_state: sandbox::init(), sync_test_state: sync_test::init(), evaluation_state,
             input_state: input::init_state(), frame_count: 0, last_title_update: Instant::now(), last_frame_time: Instant::now(),
             start_time: Instant::now(), metrics: space::metrics_for_window(display_width, display_height), preferred_difficulty_index: 2, // Default to Medium
-            vsync_enabled, fullscreen_enabled, show_overlay, last_fps: 0.0, last_vpf: 0, 
-            current_frame_vpf: 0, transition: TransitionState::Idle,
+            vsync_enabled, show_overlay, frame_limiter: FrameLimiter::new(crate::config::get().max_fps), last_fps: 0.0, last_vpf: 0, last_present_latency: None,
+            last_frame_stats: FrameStats::default(), current_frame_stats: FrameStats::default(), show_perf_overlay: false, screenshot_requested: false,
+            transition: TransitionState::Idle,
             session_start_time: None,
-            display_width,
-            display_height,
+            pending_open_song: None,
+            song_list_changed: false,
 
             gilrs: gamepad::try_init(),
             active_gamepad_id: None,
             gamepad_state: gamepad::GamepadState::default(),
-            gamepad_overlay_state: None,
+            system_message_state: None,
+            exit_confirm_open: false,
+            first_frame_at: None,
+            launch_confirmed: false,
+            session_flushed: false,
+            backend_recovered_at: None,
+            benchmark: benchmark.then(crate::core::benchmark::Run::start),
+            marquee_window: None,
+            marquee: None,
         }
     }
 
@@ -135,6 +176,9 @@ impl App {
             ScreenAction::Navigate(screen) => {
                 let from = self.current_screen;
                 let to = screen;
+                // The quit prompt belongs to the song; once the screen moves on
+                // (the song ended or was left), Enter must not quit from it.
+                self.exit_confirm_open = false;
 
                 if from == CurrentScreen::Init && to == CurrentScreen::Menu {
                     info!("Instant navigation Init→Menu (out-transition handled by Init screen)");
@@ -166,10 +210,13 @@ impl App {
             }
             ScreenAction::Exit => {
                 info!("Exit action received. Shutting down.");
-                event_loop.exit();
+                self.shutdown(event_loop);
+            }
+            ScreenAction::ShowMessage(message) => {
+                self.system_message_state = Some((message, Instant::now()));
             }
             ScreenAction::RequestBanner(_) => {}
-            ScreenAction::RequestDensityGraph(_) => {}
+            ScreenAction::RequestPreviewVideo(_) => {}
             ScreenAction::FetchOnlineGrade(hash) => {
                 info!("Fetching online grade for chart hash: {}", hash);
                 let profile = profile::get();
@@ -179,15 +226,229 @@ impl App {
                     }
                 });
             }
+            ScreenAction::ApplyDisplayMode => {
+                if let Some(window) = &self.window {
+                    display::apply(window, &crate::config::get());
+                }
+            }
+            ScreenAction::ApplyPresentSettings => {
+                self.frame_limiter.set_max_fps(crate::config::get().max_fps);
+                let present = self.present_settings();
+                if let Some(backend) = &mut self.backend {
+                    if let Err(e) = backend.set_present_mode(present) {
+                        warn!("Failed to switch present mode: {}", e);
+                    }
+                }
+            }
             ScreenAction::None => {}
         }
         Ok(())
     }
 
+    fn present_settings(&self) -> PresentSettings {
+        let cfg = crate::config::get();
+        PresentSettings {
+            vsync: self.vsync_enabled,
+            mode: cfg.present_mode,
+            frames_in_flight: cfg.frames_in_flight as usize,
+            msaa_samples: cfg.msaa_samples,
+            srgb: cfg.srgb_color,
+            render_scale: cfg.render_scale,
+            upscale_filter: cfg.upscale_filter,
+            hdr: cfg.hdr_output,
+            hdr_paper_white: cfg.hdr_paper_white,
+            transparent: cfg.overlay_window,
+        }
+    }
+
     fn build_screen(&self, actors: &[Actor], clear_color: [f32; 4], total_elapsed: f32) -> RenderList {
-        self.asset_manager.with_fonts(|fonts| {
-            crate::ui::compose::build_screen(actors, clear_color, &self.metrics, fonts, total_elapsed)
-        })
+        // Beat/music effect clocks follow the song during gameplay; elsewhere they run on wall time.
+        let clocks = match (&self.current_screen, &self.gameplay_state) {
+            (CurrentScreen::Gameplay, Some(gs)) => EffectClocks {
+                timer: total_elapsed,
+                beat: gs.visual_clock.beat,
+                music: gs.current_music_time,
+            },
+            _ => EffectClocks::timer_only(total_elapsed),
+        };
+        // Screens clear to opaque colors; an overlay clears to nothing instead.
+        let clear_color = match crate::config::get().overlay_window {
+            true => [0.0; 4],
+            false => clear_color,
+        };
+        let mut screen = self.asset_manager.with_fonts(|fonts| {
+            crate::ui::compose::build_screen(actors, clear_color, &self.metrics, fonts, clocks)
+        });
+        if crate::config::get().gpu_debug_labels {
+            screen.labels = Some(DebugLabels {
+                screen: format!("{:?}", self.current_screen),
+                groups: crate::ui::compose::group_labels(actors),
+            });
+        }
+        screen
+    }
+
+    // A song is being played, so quitting would throw it away.
+    fn session_in_progress(&self) -> bool {
+        self.current_screen == CurrentScreen::Gameplay && self.gameplay_state.is_some()
+    }
+
+    /// The one way out: flushes the session and asks the loop to stop; teardown
+    /// of the window and renderer follows in `exiting`.
+    fn shutdown(&mut self, event_loop: &ActiveEventLoop) {
+        self.exit_confirm_open = false;
+        self.flush_session();
+        event_loop.exit();
+    }
+
+    // Writes out whatever the session still holds and stops audio. Safe to call
+    // more than once; only the first call does anything.
+    fn flush_session(&mut self) {
+        if std::mem::replace(&mut self.session_flushed, true) {
+            return;
+        }
+        if self.first_frame_at.is_some() {
+            crate::core::safe_mode::launch_succeeded();
+        }
+        // The benchmark chart isn't a play; it stays out of the profile.
+        if self.session_in_progress() && self.benchmark.is_none() {
+            if let Some(gs) = &mut self.gameplay_state {
+                crate::game::gameplay::abandon(gs);
+            }
+        }
+        crate::core::audio::shutdown();
+        crate::core::screenshot::wait_for_pending();
+        crate::game::sync::sync_before_exit();
+        if profiler::is_capturing() {
+            if let Err(e) = profiler::stop_capture() {
+                warn!("Profile capture not saved: {}", e);
+            }
+        }
+    }
+
+    // Keys while the quit prompt is up: Enter quits, Escape goes back to the
+    // song. Returns false for every other key, which still reaches the song
+    // underneath (it keeps playing), releases included, so no lane stays held.
+    fn handle_exit_confirm_key(&mut self, event_loop: &ActiveEventLoop, key_event: &winit::event::KeyEvent) -> bool {
+        if key_event.state != winit::event::ElementState::Pressed {
+            return false;
+        }
+        match key_event.physical_key {
+            winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Enter) => {
+                if !key_event.repeat {
+                    info!("Exit confirmed. Shutting down.");
+                    self.shutdown(event_loop);
+                }
+                true
+            }
+            winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Escape) => {
+                self.exit_confirm_open = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Saves a captured frame, named after the song and score on screen, and
+    /// says where it went.
+    fn save_screenshot(&mut self, frame: Option<image::RgbaImage>) {
+        let Some(image) = frame else {
+            self.system_message_state = Some(("Screenshot failed.".to_string(), Instant::now()));
+            return;
+        };
+        let played = match (&self.current_screen, &self.gameplay_state) {
+            (CurrentScreen::Gameplay, Some(gs)) => Some((gs.song.clone(), gs.chart.clone(), Some(judgment::calculate_itg_score_percent(
+                &gs.scoring_counts, gs.holds_held_for_score, gs.rolls_held_for_score, gs.mines_hit_for_score, gs.possible_grade_points,
+            )))),
+            (CurrentScreen::Evaluation, _) => self.evaluation_state.score_info.as_ref()
+                .map(|si| (si.song.clone(), si.chart.clone(), Some(si.score_percent))),
+            _ => None,
+        };
+        let prefer_translit = crate::config::get().prefer_translit;
+        let title = played.as_ref().map(|(song, _, _)| song.display_full_title(prefer_translit));
+        let label = played.as_ref().zip(title.as_deref()).map(|((song, chart, score), title)| screenshot::Label {
+            title,
+            artist: song.display_artist(prefer_translit),
+            difficulty: &chart.difficulty,
+            meter: chart.meter,
+            score: *score,
+        });
+        let message = match screenshot::save(image, label.as_ref()) {
+            Ok(path) => format!("Screenshot saved to {}", path.display()),
+            Err(e) => format!("Screenshot not saved: {}", e),
+        };
+        self.system_message_state = Some((message, Instant::now()));
+    }
+
+    /// The marquee window's frame: the song being played, or the one under the
+    /// wheel cursor on the music select screen.
+    fn build_marquee_screen(&self, size: PhysicalSize<u32>, total_elapsed: f32) -> RenderList {
+        let song = match (&self.current_screen, &self.gameplay_state) {
+            (CurrentScreen::Gameplay, Some(gs)) => Some(gs.song.clone()),
+            (CurrentScreen::SelectMusic, _) => match self.select_music_state.entries.get(self.select_music_state.selected_index) {
+                Some(select_music::MusicWheelEntry::Song(song)) => Some(song.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let prefer_translit = crate::config::get().prefer_translit;
+        let title = song.as_ref().map(|s| s.display_full_title(prefer_translit));
+        let now_playing = song.as_ref().zip(title.as_deref()).map(|(s, t)| (t, s.display_artist(prefer_translit)));
+        let metrics = space::metrics_for_window(size.width, size.height);
+        let actors = crate::ui::components::marquee::build(
+            crate::ui::components::marquee::Params {
+                now_playing,
+                accent_color_index: self.select_music_state.active_color_index,
+            },
+            &metrics,
+        );
+        let mut screen = self.asset_manager.with_fonts(|fonts| {
+            crate::ui::compose::build_screen(&actors, [0.0, 0.0, 0.0, 1.0], &metrics, fonts, EffectClocks::timer_only(total_elapsed))
+        });
+        if crate::config::get().gpu_debug_labels {
+            screen.labels = Some(DebugLabels {
+                screen: "Marquee".to_string(),
+                groups: crate::ui::compose::group_labels(&actors),
+            });
+        }
+        screen
+    }
+
+    fn open_marquee_window(&mut self, event_loop: &ActiveEventLoop, backend: &mut renderer::Backend) {
+        let attributes = Window::default_attributes()
+            .with_title("DeadSync Marquee")
+            .with_resizable(true)
+            .with_inner_size(PhysicalSize::new(1280, 360));
+        let window = match event_loop.create_window(attributes) {
+            Ok(window) => Arc::new(window),
+            Err(e) => {
+                warn!("Failed to open the marquee window: {}", e);
+                return;
+            }
+        };
+        match backend.create_secondary_window(&window) {
+            Ok(marquee) => {
+                self.marquee_window = Some(window);
+                self.marquee = Some(marquee);
+            }
+            Err(e) => warn!("Marquee window unavailable on {:?}: {}", self.backend_type, e),
+        }
+    }
+
+    fn close_marquee_window(&mut self) {
+        if let (Some(backend), Some(marquee)) = (&mut self.backend, self.marquee.take()) {
+            backend.destroy_secondary_window(marquee);
+        }
+        self.marquee_window = None;
+    }
+
+    /// Fullscreen effects the current screen wants over its frame.
+    fn screen_post_effects(&self) -> Vec<renderer::PostEffect> {
+        match (&self.current_screen, &self.gameplay_state) {
+            (CurrentScreen::Gameplay, Some(gs)) => gameplay::post_effects(gs),
+            (CurrentScreen::Sandbox, _) => sandbox::post_effects(&self.sandbox_state),
+            _ => Vec::new(),
+        }
     }
 
     fn get_current_actors(&self) -> (Vec<Actor>, [f32; 4]) {
@@ -224,20 +485,28 @@ impl App {
             CurrentScreen::SelectColor => select_color::get_actors(&self.select_color_state, screen_alpha_multiplier),
             CurrentScreen::SelectMusic => select_music::get_actors(&self.select_music_state, &self.asset_manager),
             CurrentScreen::Sandbox  => sandbox::get_actors(&self.sandbox_state),
+            CurrentScreen::SyncTest => sync_test::get_actors(&self.sync_test_state),
             CurrentScreen::Init     => init::get_actors(&self.init_state),
             CurrentScreen::Evaluation => evaluation::get_actors(&self.evaluation_state, &self.asset_manager),
         };
 
         if self.show_overlay {
-            let overlay = crate::ui::components::stats_overlay::build(self.backend_type, self.last_fps, self.last_vpf);
+            let overlay = crate::ui::components::stats_overlay::build(self.backend_type, self.last_fps, self.last_vpf, self.last_present_latency);
             actors.extend(overlay);
         }
+        if self.show_perf_overlay {
+            let stages = profiler::stage_ms();
+            actors.extend(crate::ui::components::perf_overlay::build(&self.last_frame_stats, self.last_fps, &stages));
+        }
 
-        // Gamepad connection overlay (always on top of screen, but below transitions)
-        if let Some((msg, _)) = &self.gamepad_overlay_state {
+        // System message overlay: gamepad connections, song errors (on top of screen, below transitions)
+        if let Some((msg, _)) = &self.system_message_state {
             let params = crate::ui::components::gamepad_overlay::Params { message: msg };
             actors.extend(crate::ui::components::gamepad_overlay::build(params));
         }
+        if self.exit_confirm_open {
+            actors.extend(crate::ui::components::exit_overlay::build());
+        }
 
         match &self.transition {
             TransitionState::FadingOut { actors: out_actors, .. } => {
@@ -261,6 +530,7 @@ impl App {
             CurrentScreen::SelectColor => select_color::out_transition(),
             CurrentScreen::SelectMusic => select_music::out_transition(),
             CurrentScreen::Sandbox => sandbox::out_transition(),
+            CurrentScreen::SyncTest => sync_test::out_transition(),
             CurrentScreen::Init => init::out_transition(),
             CurrentScreen::Evaluation => evaluation::out_transition(),
         }
@@ -275,11 +545,78 @@ impl App {
             CurrentScreen::SelectColor => select_color::in_transition(),
             CurrentScreen::SelectMusic => select_music::in_transition(),
             CurrentScreen::Sandbox => sandbox::in_transition(),
+            CurrentScreen::SyncTest => sync_test::in_transition(),
             CurrentScreen::Evaluation => evaluation::in_transition(),
             CurrentScreen::Init => (vec![], 0.0),
         }
     }
 
+    /// Handles launches passed on by `core::instance`: raises the window and queues the
+    /// song for the wheel. From the title menu it goes straight to the wheel; anywhere
+    /// else the song waits until the player gets there, so a run is never interrupted.
+    fn handle_open_requests(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(request) = instance::take_request() {
+            if let Some(window) = &self.window {
+                window.set_minimized(false);
+                window.focus_window();
+            }
+            if request.simfile.is_some() {
+                self.pending_open_song = request.simfile;
+            }
+        }
+        if self.pending_open_song.is_some()
+            && self.current_screen == CurrentScreen::Menu
+            && matches!(self.transition, TransitionState::Idle)
+        {
+            let _ = self.handle_action(ScreenAction::Navigate(Screen::SelectMusic), event_loop);
+        }
+    }
+
+    /// Reports finished drag-and-drop installs and queues the first new song for the wheel.
+    fn handle_finished_installs(&mut self) {
+        for result in install::take_finished() {
+            let message = match result {
+                Ok(installed) => {
+                    let message = format!("Installed {} song(s) into {}.", installed.pack.song_count(), installed.pack.name());
+                    crate::game::parsing::simfile::add_scanned_pack(installed.pack);
+                    self.song_list_changed = true;
+                    if installed.song_dir.is_some() {
+                        self.pending_open_song = installed.song_dir;
+                    }
+                    message
+                }
+                Err(e) => e,
+            };
+            self.system_message_state = Some((message, Instant::now()));
+        }
+    }
+
+    /// Reports diagnostic bundles that finished saving (F10).
+    fn handle_finished_diagnostics(&mut self) {
+        for result in crate::core::diagnostics::take_finished() {
+            let message = match result {
+                Ok(path) => format!("Diagnostics saved to {}", path.display()),
+                Err(e) => format!("Diagnostics not saved: {}", e),
+            };
+            self.system_message_state = Some((message, Instant::now()));
+        }
+    }
+
+    /// Refreshes the values behind the screen bar's status widgets.
+    fn update_screen_bar_status(&self, now: Instant) {
+        use crate::core::network::{self, ConnectionStatus};
+        let groovestats = match network::get_status() {
+            ConnectionStatus::Pending => screen_bar::Connectivity::Pending,
+            ConnectionStatus::Connected(_) => screen_bar::Connectivity::Online,
+            ConnectionStatus::Error(_) => screen_bar::Connectivity::Offline,
+        };
+        screen_bar::set_status(screen_bar::Status {
+            session_elapsed: self.session_start_time.map(|start| now.duration_since(start).as_secs_f32()),
+            groovestats,
+            profile_source: screen_bar::ProfileSource::Local,
+        });
+    }
+
     #[inline(always)]
     fn update_fps_title(&mut self, window: &Window, now: Instant) {
         self.frame_count += 1;
@@ -287,50 +624,134 @@ impl App {
         if elapsed.as_secs_f32() >= 1.0 {
             let fps = self.frame_count as f32 / elapsed.as_secs_f32();
             self.last_fps = fps;
-            self.last_vpf = self.current_frame_vpf;
+            self.last_vpf = self.current_frame_stats.vertices;
+            self.last_frame_stats = self.current_frame_stats;
+            self.last_present_latency = self.backend.as_ref().and_then(|b| b.present_latency());
+            profiler::roll_stats();
+            if let Some(run) = &mut self.benchmark {
+                run.record_stages(&profiler::stage_ms());
+            }
             let screen_name = format!("{:?}", self.current_screen);
-            window.set_title(&format!("DeadSync - {:?} | {} | {:.2} FPS", self.backend_type, screen_name, fps));
+            let safe = if crate::core::safe_mode::is_active() { " (Safe Mode)" } else { "" };
+            window.set_title(&format!("DeadSync{} - {:?} | {} | {:.2} FPS", safe, self.backend_type, screen_name, fps));
             self.frame_count = 0;
             self.last_title_update = now;
         }
     }
 
+    // Replaces a backend whose device is gone (a driver reset, an unplugged eGPU)
+    // with a new one on the same window, uploading the textures again from the
+    // asset manager's copies. Screens, gameplay and audio carry on untouched.
+    fn recover_backend(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
+        const RETRY_SECONDS: f32 = 10.0;
+        let now = Instant::now();
+        if self.backend_recovered_at.is_some_and(|at| now.duration_since(at).as_secs_f32() < RETRY_SECONDS) {
+            return Err("the device was lost again right after the last restart".into());
+        }
+        self.backend_recovered_at = Some(now);
+        let window = self.window.clone().ok_or("no window")?;
+
+        let had_marquee = self.marquee_window.is_some();
+        self.close_marquee_window();
+        if let Some(mut backend) = self.backend.take() {
+            self.asset_manager.release_textures(&mut backend);
+            backend.cleanup();
+        }
+
+        let mut backend = create_backend(self.backend_type, window, self.present_settings())?;
+        self.backend_type = backend.backend_type();
+        crate::config::set_active_video_renderer(self.backend_type);
+        backend.set_gpu_timing(self.show_perf_overlay);
+        self.asset_manager.reupload_textures(&mut backend);
+        if had_marquee {
+            self.open_marquee_window(event_loop, &mut backend);
+        }
+        self.backend = Some(backend);
+        info!("Renderer restarted on {}.", self.backend_type);
+        self.system_message_state = Some(("The graphics device was reset; the renderer was restarted.".to_string(), now));
+        Ok(())
+    }
+
+    // Goes straight into the benchmark chart on autoplay, skipping the menus.
+    fn start_benchmark(&mut self, backend: &mut renderer::Backend) {
+        let (song, chart) = crate::core::benchmark::song();
+        let mut gs = gameplay::init(song, chart, self.menu_state.active_color_index, crate::game::mods::ChartMods::default());
+        gs.autoplay = true;
+        gs.frame_budget_seconds = crate::game::gameplay::frame_budget_seconds(self.window.as_deref().and_then(display::window_refresh_hz));
+        gs.background_texture_key = self.asset_manager.set_dynamic_background(backend, None);
+        self.gameplay_state = Some(gs);
+        self.current_screen = CurrentScreen::Gameplay;
+    }
+
+    fn finish_benchmark(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(run) = self.benchmark.take() {
+            if !run.finished() {
+                warn!("Benchmark left gameplay early; the results cover a partial run.");
+            }
+            let adapter = self.backend.as_ref().map_or("none".to_string(), |b| b.adapter_info());
+            if let Err(e) = crate::core::benchmark::publish(&run.report(&adapter)) {
+                warn!("Failed to save the benchmark report: {}", e);
+            }
+        }
+        self.shutdown(event_loop);
+    }
+
+    // Recomputes the screen space and resizes the backend for a new pixel size.
+    fn apply_window_size(&mut self, size: PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.metrics = space::set_window_size(size.width, size.height);
+        if let Some(backend) = &mut self.backend {
+            backend.resize(size.width, size.height);
+        }
+    }
+
     fn init_graphics(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
         let mut window_attributes = Window::default_attributes()
             .with_title(format!("DeadSync - {:?}", self.backend_type))
             .with_resizable(true);
 
-        let window_width = self.display_width;
-        let window_height = self.display_height;
-
-        if self.fullscreen_enabled {
-            let fullscreen = if let Some(mon) = event_loop.primary_monitor() {
-                let best_mode = mon.video_modes()
-                    .filter(|m| { let sz = m.size(); sz.width == window_width && sz.height == window_height })
-                    .max_by_key(|m| m.refresh_rate_millihertz());
-                if let Some(mode) = best_mode {
-                    log::info!("Fullscreen: using EXCLUSIVE {}x{} @ {} mHz", window_width, window_height, mode.refresh_rate_millihertz());
-                    Some(winit::window::Fullscreen::Exclusive(mode))
-                } else {
-                    log::warn!("No exact EXCLUSIVE mode {}x{}; using BORDERLESS.", window_width, window_height);
-                    Some(winit::window::Fullscreen::Borderless(Some(mon)))
-                }
-            } else {
-                log::warn!("No primary monitor reported; using BORDERLESS fullscreen.");
-                Some(winit::window::Fullscreen::Borderless(None))
-            };
-            window_attributes = window_attributes.with_fullscreen(fullscreen);
-        } else {
-            window_attributes = window_attributes.with_inner_size(PhysicalSize::new(window_width, window_height));
+        let cfg = crate::config::get();
+        display::refresh(event_loop);
+        // An overlay is always a window: fullscreen would cover what it's meant to sit on.
+        let fullscreen = match cfg.overlay_window {
+            true => None,
+            false => display::fullscreen_for(event_loop.available_monitors(), event_loop.primary_monitor(), &cfg),
+        };
+        match fullscreen {
+            Some(fullscreen) => window_attributes = window_attributes.with_fullscreen(Some(fullscreen)),
+            None => window_attributes = window_attributes.with_inner_size(PhysicalSize::new(cfg.display_width, cfg.display_height)),
+        }
+        if cfg.overlay_window {
+            window_attributes = window_attributes
+                .with_transparent(true)
+                .with_decorations(false)
+                .with_window_level(WindowLevel::AlwaysOnTop);
+            info!("Overlay window: borderless, transparent, always on top.");
         }
 
         let window = Arc::new(event_loop.create_window(window_attributes)?);
         let sz = window.inner_size();
-        self.metrics = crate::core::space::metrics_for_window(sz.width, sz.height);
-        crate::core::space::set_current_metrics(self.metrics);
-        let mut backend = create_backend(self.backend_type, window.clone(), self.vsync_enabled)?;
+        self.metrics = space::set_window_size(sz.width, sz.height);
+        info!("Window is {}x{} pixels at {:.2}x scale.", sz.width, sz.height, window.scale_factor());
+        let present = self.present_settings();
+        let mut backend = create_backend(self.backend_type, window.clone(), present)?;
+        // With `Auto` (or nothing else left to try) this is what actually came up.
+        self.backend_type = backend.backend_type();
+        crate::config::set_active_video_renderer(self.backend_type);
+        window.set_title(&format!("DeadSync - {:?}", self.backend_type));
+        if crate::core::safe_mode::is_active() {
+            self.system_message_state = Some((crate::core::safe_mode::message(), Instant::now()));
+        }
         
         self.asset_manager.load_initial_assets(&mut backend)?;
+        if crate::config::get().marquee_window {
+            self.open_marquee_window(event_loop, &mut backend);
+        }
+        if self.benchmark.is_some() {
+            self.start_benchmark(&mut backend);
+        }
 
         self.window = Some(window);
         self.backend = Some(backend);
@@ -346,6 +767,9 @@ impl App {
         event_loop: &ActiveEventLoop,
         key_event: winit::event::KeyEvent,
     ) {
+        if self.exit_confirm_open && self.handle_exit_confirm_key(event_loop, &key_event) {
+            return;
+        }
         let is_transitioning = !matches!(self.transition, TransitionState::Idle);
         let event_timestamp = Instant::now();
 
@@ -360,6 +784,37 @@ impl App {
                 self.show_overlay = !self.show_overlay;
                 log::info!("Overlay {}", if self.show_overlay { "ON" } else { "OFF" });
             }
+            if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::F2) = key_event.physical_key {
+                self.show_perf_overlay = !self.show_perf_overlay;
+                if let Some(backend) = &mut self.backend {
+                    backend.set_gpu_timing(self.show_perf_overlay);
+                }
+                log::info!("Performance overlay {}", if self.show_perf_overlay { "ON" } else { "OFF" });
+            }
+            if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::F12) = key_event.physical_key {
+                if let Some(backend) = &mut self.backend {
+                    backend.request_capture();
+                    self.screenshot_requested = true;
+                }
+            }
+            if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::F9) = key_event.physical_key {
+                let message = if profiler::is_capturing() {
+                    match profiler::stop_capture() {
+                        Ok(path) => format!("Profile saved to {}", path.display()),
+                        Err(e) => format!("Profile not saved: {}", e),
+                    }
+                } else {
+                    profiler::start_capture();
+                    "Profiling... press F9 again to save.".to_string()
+                };
+                self.system_message_state = Some((message, Instant::now()));
+            }
+            if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::F10) = key_event.physical_key {
+                let adapter = self.backend.as_ref().map_or("none".to_string(), |b| b.adapter_info());
+                let present_latency = self.backend.as_ref().and_then(|b| b.present_latency());
+                crate::core::diagnostics::start_export(adapter, present_latency);
+                self.system_message_state = Some(("Saving diagnostics...".to_string(), Instant::now()));
+            }
             if let winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::F4) = key_event.physical_key {
                 if self.current_screen == CurrentScreen::Menu {
                     let _ = self.handle_action(ScreenAction::Navigate(CurrentScreen::Sandbox), event_loop);
@@ -370,7 +825,7 @@ impl App {
                 if self.current_screen == CurrentScreen::Menu {
                     if let Err(e) = self.handle_action(ScreenAction::Exit, event_loop) {
                         log::error!("Failed to handle exit action: {}", e);
-                        event_loop.exit();
+                        self.shutdown(event_loop);
                     }
                     return;
                 }
@@ -382,11 +837,7 @@ impl App {
                     {
                         let difficulty_name =
                             color::FILE_DIFFICULTY_NAMES[self.select_music_state.selected_difficulty_index];
-                        if let Some(chart) = song
-                            .charts
-                            .iter()
-                            .find(|c| c.difficulty.eq_ignore_ascii_case(difficulty_name))
-                        {
+                        if let Some(chart) = song.chart_for(crate::config::get().game_type, difficulty_name) {
                             let action = ScreenAction::FetchOnlineGrade(chart.short_hash.clone());
                             if let Err(e) = self.handle_action(action, event_loop) {
                                 log::error!("Failed to handle FetchOnlineGrade action: {}", e);
@@ -421,13 +872,14 @@ impl App {
             }
             CurrentScreen::SelectColor => select_color::handle_key_press(&mut self.select_color_state, &key_event),
             CurrentScreen::Sandbox => sandbox::handle_key_press(&mut self.sandbox_state, &key_event),
+            CurrentScreen::SyncTest => sync_test::handle_key_press(&mut self.sync_test_state, &key_event),
             CurrentScreen::SelectMusic => select_music::handle_key_press(&mut self.select_music_state, &key_event),
             CurrentScreen::Init => init::handle_key_press(&mut self.init_state, &key_event),
             CurrentScreen::Evaluation => evaluation::handle_key_press(&mut self.evaluation_state, &key_event),
         };
         if let Err(e) = self.handle_action(action.clone(), event_loop) {
             log::error!("Failed to handle action: {}", e);
-            event_loop.exit();
+            self.shutdown(event_loop);
         }
     }
 
@@ -436,12 +888,6 @@ impl App {
     #[inline(always)]
     fn apply_dir_from_pad(&mut self, event_loop: &ActiveEventLoop, dir: PadDir, pressed: bool) {
         let timestamp = Instant::now();
-        let lane = match dir {
-            PadDir::Left => Lane::Left,
-            PadDir::Down => Lane::Down,
-            PadDir::Up => Lane::Up,
-            PadDir::Right => Lane::Right,
-        };
 
         // 1) always update InputState so gameplay can read it for arrow hits
         match dir {
@@ -449,11 +895,13 @@ impl App {
             PadDir::Down  => self.input_state.down = pressed,
             PadDir::Left  => self.input_state.left = pressed,
             PadDir::Right => self.input_state.right = pressed,
+            PadDir::UpLeft | PadDir::UpRight => {}
         }
 
+        // Gameplay maps the lane to a column through the chart's game type.
         if self.current_screen == CurrentScreen::Gameplay {
             if let Some(gs) = &mut self.gameplay_state {
-                crate::game::gameplay::queue_input_edge(gs, InputSource::Gamepad, lane, pressed, timestamp);
+                crate::game::gameplay::queue_input_edge(gs, InputSource::Gamepad, dir.lane(), pressed, timestamp);
             }
         }
 
@@ -462,13 +910,28 @@ impl App {
             let action = select_music::handle_pad_dir(&mut self.select_music_state, dir, pressed);
             if let Err(e) = self.handle_action(action, event_loop) {
                 error!("Failed to handle pad-dir action: {}", e);
-                event_loop.exit();
+                self.shutdown(event_loop);
             }
         }
     }
 
     #[inline(always)]
     fn handle_pad_event(&mut self, event_loop: &ActiveEventLoop, ev: PadEvent) {
+        // Same as the keys: only presses of Confirm and Back answer the quit
+        // prompt; everything else still plays the song.
+        if self.exit_confirm_open {
+            match ev {
+                PadEvent::Button { btn: PadButton::Confirm, pressed: true } => {
+                    self.shutdown(event_loop);
+                    return;
+                }
+                PadEvent::Button { btn: PadButton::Back, pressed: true } => {
+                    self.exit_confirm_open = false;
+                    return;
+                }
+                _ => {}
+            }
+        }
         let is_transitioning = !matches!(self.transition, TransitionState::Idle);
         if is_transitioning || self.current_screen == CurrentScreen::Init {
             return;
@@ -532,6 +995,8 @@ impl App {
                         FaceBtn::SouthA => PadDir::Down,
                     };
                     self.apply_dir_from_pad(event_loop, dir, pressed);
+                } else if pressed && btn == FaceBtn::WestX && self.current_screen == CurrentScreen::SelectMusic {
+                    select_music::handle_pad_filter_button(&mut self.select_music_state);
                 } else if pressed && btn == FaceBtn::SouthA {
                     // A button acts as Confirm/Enter in menus
                     let mut play_sound = true;
@@ -658,12 +1123,16 @@ impl App {
                             CurrentScreen::Evaluation => ScreenAction::Navigate(Screen::SelectMusic),
                             CurrentScreen::Gameplay => ScreenAction::Navigate(Screen::SelectMusic),
                             CurrentScreen::PlayerOptions => ScreenAction::Navigate(Screen::SelectMusic),
+                            CurrentScreen::SyncTest => ScreenAction::Navigate(Screen::Options),
+                            CurrentScreen::SelectMusic if select_music::is_filter_open(&self.select_music_state) => {
+                                select_music::handle_pad_button(&mut self.select_music_state, PadButton::Back, true)
+                            }
                             // Default for Options, SelectColor, SelectMusic, Sandbox is to go back to Menu
                             _ => ScreenAction::Navigate(CurrentScreen::Menu),
                         };
                         if let Err(e) = self.handle_action(action, event_loop) {
                                  error!("Failed to handle back button action: {}", e);
-                                 event_loop.exit();
+                                 self.shutdown(event_loop);
                         }
                     }
                     PadButton::F7 if pressed => {
@@ -672,7 +1141,7 @@ impl App {
                                 self.select_music_state.entries.get(self.select_music_state.selected_index)
                             {
                                 let difficulty_name = color::FILE_DIFFICULTY_NAMES[self.select_music_state.selected_difficulty_index];
-                                if let Some(chart) = song.charts.iter().find(|c| c.difficulty.eq_ignore_ascii_case(difficulty_name)) {
+                                if let Some(chart) = song.chart_for(crate::config::get().game_type, difficulty_name) {
                                     let action = ScreenAction::FetchOnlineGrade(chart.short_hash.clone());
                                     if let Err(e) = self.handle_action(action, event_loop) {
                                         error!("Failed to fetch online grade: {}", e);
@@ -706,7 +1175,7 @@ impl App {
                     format!("Disconnected: {} (ID: {})", name, usize::from(id))
                 }
             };
-            self.gamepad_overlay_state = Some((msg, Instant::now()));
+            self.system_message_state = Some((msg, Instant::now()));
         }
 
         for ev in pad_events {
@@ -720,7 +1189,7 @@ impl ApplicationHandler for App {
         if self.window.is_none() {
             if let Err(e) = self.init_graphics(event_loop) {
                 error!("Failed to initialize graphics: {}", e);
-                event_loop.exit();
+                self.shutdown(event_loop);
             }
         }
     }
@@ -732,40 +1201,66 @@ impl ApplicationHandler for App {
         event: WindowEvent,
     ) {
         let Some(window) = self.window.as_ref().cloned() else { return; };
+        if self.marquee_window.as_ref().is_some_and(|w| w.id() == window_id) {
+            match event {
+                WindowEvent::CloseRequested => self.close_marquee_window(),
+                WindowEvent::Resized(new_size) if new_size.width > 0 && new_size.height > 0 => {
+                    if let (Some(backend), Some(marquee)) = (&mut self.backend, &mut self.marquee) {
+                        backend.resize_secondary_window(marquee, new_size.width, new_size.height);
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
         if window_id != window.id() { return; }
 
         match event {
             WindowEvent::CloseRequested => {
-                info!("Close requested. Shutting down.");
-                event_loop.exit();
-            }
-            WindowEvent::Resized(new_size) => {
-                if new_size.width > 0 && new_size.height > 0 {
-                    self.metrics = space::metrics_for_window(new_size.width, new_size.height);
-                    space::set_current_metrics(self.metrics);
-                    if let Some(backend) = &mut self.backend {
-                        backend.resize(new_size.width, new_size.height);
-                    }
+                if self.exit_confirm_open || !self.session_in_progress() {
+                    info!("Close requested. Shutting down.");
+                    self.shutdown(event_loop);
+                } else {
+                    info!("Close requested mid-song; asking for confirmation.");
+                    self.exit_confirm_open = true;
                 }
             }
+            WindowEvent::Resized(new_size) => self.apply_window_size(new_size),
+            // Moved to a monitor with another DPI scale. The OS picks the new pixel
+            // size; not every platform follows up with `Resized`, so lay out again
+            // for the size the window has now, and again when `Resized` does arrive.
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                info!("Display scale changed to {:.2}x.", scale_factor);
+                self.asset_manager.forget_decoded_banners();
+                self.apply_window_size(window.inner_size());
+            }
             WindowEvent::KeyboardInput { event: key_event, .. } => {
                 self.handle_virtual_key_event(event_loop, key_event);
             }
+            WindowEvent::DroppedFile(path) => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                self.system_message_state = Some((format!("Installing {}...", name), Instant::now()));
+                install::start(path);
+            }
             WindowEvent::RedrawRequested => {
                 let now = Instant::now();
+                self.frame_limiter.begin_frame(now);
                 let delta_time = now.duration_since(self.last_frame_time).as_secs_f32();
                 self.last_frame_time = now;
                 let total_elapsed = now.duration_since(self.start_time).as_secs_f32();
+                profiler::set_screen(&format!("{:?}", self.current_screen));
+                let update_scope = profiler::scope("update");
                 crate::ui::runtime::tick(delta_time);
+                self.update_screen_bar_status(now);
 
-                // --- Manage gamepad overlay lifetime ---
-                if let Some((_, start_time)) = self.gamepad_overlay_state {
+                // --- Manage system message overlay lifetime ---
+                if let Some((_, start_time)) = self.system_message_state {
                     // Corresponds to the animation durations in gamepad_overlay.rs
                     const HOLD_DURATION: f32 = 3.33;
                     const FADE_OUT_DURATION: f32 = 0.25;
                     const TOTAL_DURATION: f32 = HOLD_DURATION + FADE_OUT_DURATION;
                     if now.duration_since(start_time).as_secs_f32() > TOTAL_DURATION {
-                        self.gamepad_overlay_state = None;
+                        self.system_message_state = None;
                     }
                 }
 
@@ -825,6 +1320,13 @@ impl ApplicationHandler for App {
                         match self.current_screen {
                             CurrentScreen::Gameplay => if let Some(gs) = &mut self.gameplay_state {
                                 let action = gameplay::update(gs, delta_time);
+                                // Left alone once quality drops the background, so decoding stops too.
+                                if let Some(backend) = self.backend.as_mut().filter(|_| gs.quality.shows_background()) {
+                                    let video_time = (gs.current_music_time - gs.background_video_start) as f64;
+                                    if let Some(key) = self.asset_manager.update_background_video(backend, video_time) {
+                                        gs.background_video_key = Some(key);
+                                    }
+                                }
                                 if let ScreenAction::Navigate(_) | ScreenAction::Exit = action.clone() {
                                     if self.handle_action(action, event_loop).is_err() {}
                                 }
@@ -836,7 +1338,10 @@ impl ApplicationHandler for App {
                                 }
                             }
                             CurrentScreen::Options => {
-                                options::update(&mut self.options_state, delta_time);
+                                let action = options::update(&mut self.options_state, delta_time);
+                                if let ScreenAction::ApplyDisplayMode = action {
+                                    let _ = self.handle_action(action, event_loop);
+                                }
                             }
                             CurrentScreen::PlayerOptions => {
                                 if let Some(pos) = &mut self.player_options_state {
@@ -844,6 +1349,7 @@ impl ApplicationHandler for App {
                                 }
                             }
                             CurrentScreen::Sandbox => sandbox::update(&mut self.sandbox_state, delta_time),
+                            CurrentScreen::SyncTest => sync_test::update(&mut self.sync_test_state, delta_time),
                             CurrentScreen::SelectColor => select_color::update(&mut self.select_color_state, delta_time),
                             CurrentScreen::Evaluation => {
                                 if let Some(start) = self.session_start_time {
@@ -855,48 +1361,65 @@ impl ApplicationHandler for App {
                                 if let Some(start) = self.session_start_time {
                                     self.select_music_state.session_elapsed = now.duration_since(start).as_secs_f32();
                                 }
+                                if std::mem::take(&mut self.song_list_changed) {
+                                    let current_color_index = self.select_music_state.active_color_index;
+                                    self.select_music_state = select_music::init();
+                                    self.select_music_state.active_color_index = current_color_index;
+                                    self.select_music_state.selected_difficulty_index = self.preferred_difficulty_index;
+                                    self.select_music_state.preferred_difficulty_index = self.preferred_difficulty_index;
+                                }
+                                if let Some(path) = self.pending_open_song.take() {
+                                    if !select_music::select_song_by_path(&mut self.select_music_state, &path) {
+                                        self.system_message_state = Some((format!("{} isn't in the song list.", path.display()), Instant::now()));
+                                    }
+                                }
                                 let action = select_music::update(&mut self.select_music_state, delta_time);
                                 if let Some(backend) = self.backend.as_mut() {
+                                    if let Some(key) = self.asset_manager.poll_banner_decodes(backend) {
+                                        self.select_music_state.current_banner_key = key;
+                                    }
                                     match action {
                                         ScreenAction::RequestBanner(path_opt) => {
                                             if let Some(path) = path_opt {
-                                                let key = self.asset_manager.set_dynamic_banner(backend, Some(path));
-                                                self.select_music_state.current_banner_key = key;
+                                                // Still decoding: keep the old banner up until it's ready.
+                                                if let Some(key) = self.asset_manager.set_dynamic_banner(backend, Some(path)) {
+                                                    self.select_music_state.current_banner_key = key;
+                                                }
                                             } else {
+                                                // Takes the preview video down with it.
                                                 self.asset_manager.destroy_dynamic_assets(backend);
+                                                select_music::stop_preview_video(&mut self.select_music_state);
                                                 let color_index = self.select_music_state.active_color_index;
                                                 let banner_num = color_index.rem_euclid(12) + 1;
                                                 let key = format!("banner{}.png", banner_num);
                                                 self.select_music_state.current_banner_key = key;
                                             }
                                         }
-                                        ScreenAction::RequestDensityGraph(chart_opt) => {
-                                            let graph_request = if let Some(chart) = chart_opt {
-                                                let graph_width = 1024;
-                                                let graph_height = 256;
-                                                let bottom_color = [0, 184, 204];
-                                                let top_color    = [130, 0, 161];
-                                                let bg_color     = [30, 40, 47];
-
-                                                let graph_data = rssp::graph::generate_density_graph_rgba_data(
-                                                    &chart.measure_nps_vec,
-                                                    chart.max_nps,
-                                                    graph_width, graph_height,
-                                                    bottom_color,
-                                                    top_color,
-                                                    bg_color,
-                                                ).ok();
-                                                
-                                                graph_data.map(|data| (chart.short_hash, data))
-                                            } else {
-                                                None
-                                            };
-
-                                            let key = self.asset_manager.set_density_graph(backend, graph_request);
-                                            self.select_music_state.current_graph_key = key;
+                                        ScreenAction::RequestPreviewVideo(path_opt) => {
+                                            self.asset_manager.set_preview_video(backend, path_opt);
                                         }
                                         _ => { let _ = self.handle_action(action, event_loop); },
                                     }
+                                    // After the request above, so a video just stopped or
+                                    // replaced can't hand back a key to its freed texture.
+                                    if let Some(backend) = self.backend.as_mut() {
+                                        let video_time = self.select_music_state.preview_video_time as f64;
+                                        if let Some(key) = self.asset_manager.update_preview_video(backend, video_time) {
+                                            self.select_music_state.preview_video_key = Some(key);
+                                        }
+                                        let key = select_music::density_graph_key(&self.select_music_state);
+                                        self.select_music_state.density_graph_texture = self.asset_manager.update_density_graph(
+                                            backend,
+                                            key,
+                                            select_music::density_graph_px_size(),
+                                            || select_music::density_graph_screen(&self.select_music_state),
+                                            &self.metrics,
+                                        );
+                                    }
+                                    // After the request above, so the banner being waited on isn't cancelled.
+                                    if let Some(paths) = select_music::take_banner_prefetch(&mut self.select_music_state) {
+                                        self.asset_manager.prefetch_banners(&paths);
+                                    }
                                 }
                             }
                             _ => {}
@@ -913,6 +1436,7 @@ impl ApplicationHandler for App {
                         crate::core::audio::stop_music();
                         if let Some(backend) = self.backend.as_mut() {
                             self.asset_manager.set_dynamic_background(backend, None);
+                            self.asset_manager.set_background_video(backend, None);
                         }
                     }
 
@@ -938,6 +1462,12 @@ impl ApplicationHandler for App {
                                     );
                                 }
 
+                                player_options::save_mods(po_state);
+                                profile::update_judgment_position(po_state.judgment_position);
+                                profile::update_column_spacing(po_state.column_spacing);
+                                profile::update_rate_scaled_effects(po_state.rate_scaled_effects);
+                                profile::update_fa_plus(po_state.fa_plus);
+
                                 // Reflect difficulty changes back to SelectMusic
                                 self.preferred_difficulty_index = po_state.chart_difficulty_index;
                                 info!("Updated preferred difficulty index to {} from PlayerOptions", self.preferred_difficulty_index);
@@ -948,6 +1478,10 @@ impl ApplicationHandler for App {
 
                     if prev == CurrentScreen::SelectMusic {
                         self.preferred_difficulty_index = self.select_music_state.preferred_difficulty_index;
+                        select_music::stop_preview_video(&mut self.select_music_state);
+                        if let Some(backend) = self.backend.as_mut() {
+                            self.asset_manager.set_preview_video(backend, None);
+                        }
                     }
 
                     if prev == CurrentScreen::SelectColor {
@@ -969,7 +1503,11 @@ impl ApplicationHandler for App {
                         let current_color_index = self.options_state.active_color_index;
                         self.options_state = options::init();
                         self.options_state.active_color_index = current_color_index;
-                    } else if target == CurrentScreen::PlayerOptions {
+                    } else if target == CurrentScreen::PlayerOptions
+                        || (target == CurrentScreen::Gameplay && prev == CurrentScreen::SelectMusic)
+                    {
+                        // Tournament mode goes straight from SelectMusic to Gameplay; the
+                        // PlayerOptions state is still built, just never shown or edited.
                         let (song_arc, chart_difficulty_index) = {
                             let sm_state = &self.select_music_state;
                             let entry = sm_state.entries.get(sm_state.selected_index).unwrap();
@@ -986,23 +1524,31 @@ impl ApplicationHandler for App {
 
                     if target == CurrentScreen::Gameplay {
                         if let Some(po_state) = self.player_options_state.take() {
+                            let chart_mods = po_state.mods;
                             let song_arc = po_state.song;
                             let chart_difficulty_index = po_state.chart_difficulty_index;
                             let difficulty_name = color::FILE_DIFFICULTY_NAMES[chart_difficulty_index];
-                            // Prefer a dance-single chart for the selected difficulty; fall back to any matching difficulty.
+                            // Prefer the wheel's game type for the selected difficulty; fall back to any matching difficulty.
                             let chart_ref = song_arc
-                                .charts
-                                .iter()
-                                .find(|c| c.chart_type.eq_ignore_ascii_case("dance-single") && c.difficulty.eq_ignore_ascii_case(difficulty_name))
+                                .chart_for(crate::config::get().game_type, difficulty_name)
                                 .or_else(|| song_arc.charts.iter().find(|c| c.difficulty.eq_ignore_ascii_case(difficulty_name)))
                                 .expect("No chart found for selected difficulty");
                             let chart = Arc::new(chart_ref.clone());
 
                             let color_index = po_state.active_color_index;
-                            let mut gs = gameplay::init(song_arc, chart, color_index);
+                            let mut gs = gameplay::init(song_arc, chart, color_index, chart_mods);
+                            gs.frame_budget_seconds = crate::game::gameplay::frame_budget_seconds(self.window.as_deref().and_then(display::window_refresh_hz));
                             
                             if let Some(backend) = self.backend.as_mut() {
                                 gs.background_texture_key = self.asset_manager.set_dynamic_background(backend, gs.song.background_path.clone());
+                                self.asset_manager.set_background_video(backend, gs.song.background_video.clone());
+                            }
+                            // Pad panels already held carry in as presses, which the start dead zone ignores until released.
+                            let pad = &self.gamepad_state;
+                            for &lane in gs.game_type.panels() {
+                                if pad.is_held(lane) {
+                                    crate::game::gameplay::queue_input_edge(&mut gs, InputSource::Gamepad, lane, true, Instant::now());
+                                }
                             }
                             self.gameplay_state = Some(gs);
                         } else {
@@ -1018,37 +1564,10 @@ impl ApplicationHandler for App {
                         );
                         self.evaluation_state = evaluation::init(gameplay_results);
                         self.evaluation_state.active_color_index = color_idx;
+                    }
 
-                        if let Some(backend) = self.backend.as_mut() {
-                            let graph_request = if let Some(score_info) = &self.evaluation_state.score_info {
-                                 let graph_width = 1024;
-                                 let graph_height = 256;
-                                 let bg_color     = [16, 21, 25];
-                                 let top_color    = [54, 25, 67];
-                                 let bottom_color = [38, 84, 91];
- 
-                                 let graph_data = rssp::graph::generate_density_graph_rgba_data(
-                                     &score_info.chart.measure_nps_vec,
-                                     score_info.chart.max_nps,
-                                     graph_width, graph_height,
-                                     bottom_color,
-                                     top_color,
-                                     bg_color,
-                                 ).ok();
-                                 
-                                let key = format!("{}_eval", score_info.chart.short_hash);
-                                graph_data.map(|data| (key, data))
-                            } else {
-                                None
-                            };
-                            
-                            let key = if let Some((key, data)) = graph_request {
-                                self.asset_manager.set_density_graph(backend, Some((key, data)))
-                            } else {
-                                self.asset_manager.set_density_graph(backend, None)
-                            };
-                            self.evaluation_state.density_graph_texture_key = key;
-                        }
+                    if target == CurrentScreen::SyncTest {
+                        self.sync_test_state = sync_test::init();
                     }
 
                     if target == CurrentScreen::SelectMusic {
@@ -1102,19 +1621,86 @@ impl ApplicationHandler for App {
                     };
                     crate::ui::runtime::clear_all();
                 }
+                drop(update_scope);
 
+                let actors_scope = profiler::scope("actors");
                 let (actors, clear_color) = self.get_current_actors();
-                let screen = self.build_screen(&actors, clear_color, total_elapsed);
+                drop(actors_scope);
+                let compose_scope = profiler::scope("compose");
+                let mut screen = self.build_screen(&actors, clear_color, total_elapsed);
+                screen.post_effects = self.screen_post_effects();
+                drop(compose_scope);
                 self.update_fps_title(&window, now);
 
+                let mut screenshot = None;
+                let mut draw_failed = false;
+                let mut device_lost = false;
                 if let Some(backend) = &mut self.backend {
+                    let _draw_scope = profiler::scope("draw");
+                    #[cfg(feature = "shader-hot-reload")]
+                    backend.reload_changed_shaders();
+                    self.asset_manager.resolve_text(backend, &mut screen);
                     match backend.draw(&screen, &self.asset_manager.textures) {
-                        Ok(vpf) => self.current_frame_vpf = vpf,
+                        Ok(stats) => {
+                            self.current_frame_stats = stats;
+                            if let Some(run) = &mut self.benchmark {
+                                run.record_frame(delta_time, &stats);
+                            }
+                            let first_frame_at = *self.first_frame_at.get_or_insert(now);
+                            if !self.launch_confirmed
+                                && now.duration_since(first_frame_at).as_secs_f32() >= crate::core::safe_mode::STARTUP_SECONDS
+                            {
+                                crate::core::safe_mode::launch_succeeded();
+                                self.launch_confirmed = true;
+                            }
+                        }
+                        Err(e) if backend.is_lost(e.as_ref()) => {
+                            warn!("Lost the graphics device: {}", e);
+                            device_lost = true;
+                        }
                         Err(e) => {
                             error!("Failed to draw frame: {}", e);
-                            event_loop.exit();
+                            draw_failed = true;
                         }
                     }
+                    // A frame the backend skipped leaves the capture for the next one.
+                    if self.screenshot_requested && !backend.capture_pending() {
+                        self.screenshot_requested = false;
+                        screenshot = Some(backend.capture_frame());
+                    }
+                }
+                if let Some(frame) = screenshot {
+                    self.save_screenshot(frame);
+                }
+                if device_lost {
+                    if let Err(e) = self.recover_backend(event_loop) {
+                        error!("Couldn't restart the renderer: {}", e);
+                        draw_failed = true;
+                    }
+                }
+                if draw_failed {
+                    self.shutdown(event_loop);
+                    return;
+                }
+                if self.benchmark.as_ref().is_some_and(|run| run.finished() || self.current_screen != CurrentScreen::Gameplay) {
+                    self.finish_benchmark(event_loop);
+                    return;
+                }
+
+                if let Some(size) = self.marquee_window.as_ref().map(|w| w.inner_size()).filter(|s| s.width > 0 && s.height > 0) {
+                    let _marquee_scope = profiler::scope("marquee");
+                    let mut marquee_screen = self.build_marquee_screen(size, total_elapsed);
+                    let mut failed = false;
+                    if let (Some(backend), Some(marquee)) = (&mut self.backend, &mut self.marquee) {
+                        self.asset_manager.resolve_text(backend, &mut marquee_screen);
+                        if let Err(e) = backend.draw_secondary_window(marquee, &marquee_screen, &self.asset_manager.textures) {
+                            warn!("Failed to draw the marquee window; closing it: {}", e);
+                            failed = true;
+                        }
+                    }
+                    if failed {
+                        self.close_marquee_window();
+                    }
                 }
             }
             _ => {}
@@ -1124,14 +1710,35 @@ impl ApplicationHandler for App {
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         // Pump gamepad → pad events → handlers
         self.poll_gamepad_and_dispatch(event_loop);
+        self.handle_finished_installs();
+        self.handle_finished_diagnostics();
+        self.handle_open_requests(event_loop);
+        // A sync pulled profile files; reload once nothing is mid-play with it.
+        if !matches!(self.current_screen, CurrentScreen::Gameplay | CurrentScreen::Evaluation | CurrentScreen::PlayerOptions)
+            && crate::game::sync::take_profile_reload()
+        {
+            info!("Reloading the profile after a sync.");
+            crate::game::profile::load();
+        }
 
-        if let Some(window) = &self.window {
-            window.request_redraw();
+        match self.frame_limiter.control_flow(Instant::now()) {
+            Some(control_flow) => event_loop.set_control_flow(control_flow),
+            None => {
+                event_loop.set_control_flow(ControlFlow::Wait);
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
         }
     }
 
     fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Covers exits that didn't come through `shutdown`.
+        self.flush_session();
+        self.close_marquee_window();
         if let Some(backend) = &mut self.backend {
+            // Nothing may still be reading what's about to be destroyed.
+            backend.wait_for_idle();
             self.asset_manager.destroy_dynamic_assets(backend);
             backend.dispose_textures(&mut self.asset_manager.textures);
             backend.cleanup();
@@ -1139,18 +1746,19 @@ impl ApplicationHandler for App {
     }
 }
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let _ = env_logger::builder().filter_level(log::LevelFilter::Info).try_init();
+/// `benchmark` skips the song scan and menus and runs `core::benchmark`.
+pub fn run(benchmark: bool) -> Result<(), Box<dyn std::error::Error>> {
     let config = crate::config::get();
     let backend_type = config.video_renderer;
     let vsync_enabled = config.vsync;
-    let fullscreen_enabled = !config.windowed;
     let show_stats = config.show_stats;
     let color_index = config.simply_love_color;
 
-    song_loading::scan_and_load_songs("songs");
+    if !benchmark {
+        song_loading::scan_and_load_songs("songs");
+    }
     let event_loop = EventLoop::new()?;
-    let mut app = App::new(backend_type, vsync_enabled, fullscreen_enabled, show_stats, color_index);
+    let mut app = App::new(backend_type, vsync_enabled, show_stats, color_index, benchmark);
     event_loop.run_app(&mut app)?;
     Ok(())
 }
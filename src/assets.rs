@@ -1,14 +1,26 @@
-use crate::core::gfx::{Backend, Texture as GfxTexture};
+use crate::core::gfx::ktx2::{self, CompressedFormat, CompressedImage};
+use crate::core::gfx::{Backend, RenderTarget, Texture as GfxTexture, UploadId, MAX_FRAMES_IN_FLIGHT};
+use crate::core::space::Metrics;
+use crate::core::video::VideoStream;
 use crate::game::profile;
+use crate::ui::actors::Actor;
+use crate::ui::compose::{self, EffectClocks};
 use crate::ui::font::{self, Font, FontLoadData};
-use image::RgbaImage;
+use crate::ui::glyph_atlas::{GlyphAtlas, GLYPH_ATLAS_KEY};
+use image::codecs::{gif::GifDecoder, png::PngDecoder};
+use image::{AnimationDecoder, Frame, RgbaImage};
 use log::{info, warn};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     error::Error,
     fs,
+    io::BufReader,
     path::{Path, PathBuf},
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, RwLock,
+    },
 };
 
 // --- Texture Metadata ---
@@ -17,6 +29,17 @@ use std::{
 pub struct TexMeta {
     pub w: u32,
     pub h: u32,
+    /// Texels per logical pixel, taken from the file name (see `parse_texture_density`).
+    pub density: u32,
+}
+
+impl TexMeta {
+    /// Size in logical pixels, i.e. what the art was authored to occupy on screen.
+    #[inline(always)]
+    pub fn logical_size(&self) -> (f32, f32) {
+        let d = self.density.max(1) as f32;
+        (self.w as f32 / d, self.h as f32 / d)
+    }
 }
 
 static TEX_META: once_cell::sync::Lazy<RwLock<HashMap<String, TexMeta>>> =
@@ -24,19 +47,173 @@ static TEX_META: once_cell::sync::Lazy<RwLock<HashMap<String, TexMeta>>> =
 
 pub fn register_texture_dims(key: &str, w: u32, h: u32) {
     let mut m = TEX_META.write().unwrap();
-    m.insert(key.to_string(), TexMeta { w, h });
+    m.insert(key.to_string(), TexMeta { w, h, density: parse_texture_density(key) });
 }
 
 pub fn texture_dims(key: &str) -> Option<TexMeta> {
     TEX_META.read().unwrap().get(key).copied()
 }
 
+// --- Animated Textures ---
+
+/// How an animated GIF/APNG was packed into its texture: frames fill `grid`
+/// (cols, rows) row-major, each shown for its entry in `delays` (seconds).
+#[derive(Clone, Debug)]
+pub struct TextureAnimation {
+    pub grid: (u32, u32),
+    pub delays: Arc<[f32]>,
+}
+
+impl TextureAnimation {
+    /// The frame showing `time` seconds in, looping.
+    pub fn frame_at(&self, time: f32) -> usize {
+        frame_for_delays(&self.delays, time)
+    }
+}
+
+/// The frame of a looping animation with per-frame `delays` (seconds) that
+/// shows at `time`.
+pub fn frame_for_delays(delays: &[f32], time: f32) -> usize {
+    let total: f32 = delays.iter().sum();
+    if delays.len() <= 1 || total <= 0.0 {
+        return 0;
+    }
+    let mut t = time.rem_euclid(total);
+    for (i, delay) in delays.iter().enumerate() {
+        if t < *delay {
+            return i;
+        }
+        t -= delay;
+    }
+    delays.len() - 1
+}
+
+static TEX_ANIMATIONS: once_cell::sync::Lazy<RwLock<HashMap<String, TextureAnimation>>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Records (or clears, for None) the frame layout of the texture under `key`.
+pub fn register_texture_animation(key: &str, animation: Option<TextureAnimation>) {
+    let mut m = TEX_ANIMATIONS.write().unwrap();
+    match animation {
+        Some(animation) => m.insert(key.to_string(), animation),
+        None => m.remove(key),
+    };
+}
+
+pub fn texture_animation(key: &str) -> Option<TextureAnimation> {
+    TEX_ANIMATIONS.read().unwrap().get(key).cloned()
+}
+
+// Browsers play GIF delays under 20ms (usually 0) at 100ms, and files rely on it.
+const MIN_FRAME_DELAY: f32 = 0.02;
+const DEFAULT_FRAME_DELAY: f32 = 0.1;
+// Largest side of the sheet an animation is packed into; frames of bigger ones are shrunk.
+const MAX_ANIMATION_SHEET: u32 = 4096;
+
+/// Decodes the image at `path`. Animated GIFs and APNGs come back as a sprite
+/// sheet of their frames plus its layout. `fit` is applied to each frame first.
+fn open_image(path: &Path, fit: impl Fn(RgbaImage) -> RgbaImage) -> image::ImageResult<(RgbaImage, Option<TextureAnimation>)> {
+    if let Some(mut frames) = read_animation_frames(path)? {
+        if frames.len() > 1 {
+            return Ok(pack_animation(frames, fit));
+        }
+        if let Some(frame) = frames.pop() {
+            return Ok((fit(frame.into_buffer()), None));
+        }
+    }
+    Ok((fit(image::open(path)?.to_rgba8()), None))
+}
+
+/// Whether `path` is a GIF or an APNG, which `open_image` unpacks into frames.
+/// Only the PNG header is read; a GIF with a single frame still counts.
+pub fn is_animated_image(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match ext.as_str() {
+        "gif" => true,
+        "png" | "apng" => fs::File::open(path)
+            .ok()
+            .and_then(|file| PngDecoder::new(BufReader::new(file)).ok())
+            .and_then(|decoder| decoder.is_apng().ok())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+// All frames of a GIF or APNG, composited to full size; None for other files.
+fn read_animation_frames(path: &Path) -> image::ImageResult<Option<Vec<Frame>>> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    let frames = match ext.as_str() {
+        "gif" => GifDecoder::new(BufReader::new(fs::File::open(path)?))?.into_frames().collect_frames()?,
+        "png" | "apng" => {
+            let decoder = PngDecoder::new(BufReader::new(fs::File::open(path)?))?;
+            if !decoder.is_apng()? {
+                return Ok(None);
+            }
+            decoder.apng()?.into_frames().collect_frames()?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(frames))
+}
+
+// Lays frames out in a near-square grid, scaled down to fit MAX_ANIMATION_SHEET.
+fn pack_animation(frames: Vec<Frame>, fit: impl Fn(RgbaImage) -> RgbaImage) -> (RgbaImage, Option<TextureAnimation>) {
+    let delays: Arc<[f32]> = frames
+        .iter()
+        .map(|f| {
+            let (numer, denom) = f.delay().numer_denom_ms();
+            let seconds = numer as f32 / denom.max(1) as f32 / 1000.0;
+            if seconds < MIN_FRAME_DELAY { DEFAULT_FRAME_DELAY } else { seconds }
+        })
+        .collect();
+    let frames: Vec<RgbaImage> = frames.into_iter().map(|f| fit(f.into_buffer())).collect();
+    let (w, h) = frames[0].dimensions();
+    let count = frames.len() as u32;
+    let cols = (count as f32).sqrt().ceil() as u32;
+    let rows = count.div_ceil(cols);
+    let scale = (MAX_ANIMATION_SHEET as f32 / (w * cols) as f32)
+        .min(MAX_ANIMATION_SHEET as f32 / (h * rows) as f32)
+        .min(1.0);
+    let cell_w = ((w as f32 * scale) as u32).max(1);
+    let cell_h = ((h as f32 * scale) as u32).max(1);
+
+    let mut sheet = RgbaImage::new(cell_w * cols, cell_h * rows);
+    for (i, frame) in frames.iter().enumerate() {
+        let resized;
+        let frame = if frame.dimensions() == (cell_w, cell_h) {
+            frame
+        } else {
+            resized = image::imageops::resize(frame, cell_w, cell_h, image::imageops::FilterType::Triangle);
+            &resized
+        };
+        let (col, row) = (i as u32 % cols, i as u32 / cols);
+        image::imageops::replace(&mut sheet, frame, (col * cell_w) as i64, (row * cell_h) as i64);
+    }
+    (sheet, Some(TextureAnimation { grid: (cols, rows), delays }))
+}
+
 pub fn canonical_texture_key<P: AsRef<Path>>(p: P) -> String {
     let p = p.as_ref();
     let rel = p.strip_prefix(Path::new("assets")).unwrap_or(p);
     rel.to_string_lossy().replace('\\', "/")
 }
 
+/// Pixel density a texture was drawn at, from its file name:
+/// StepMania's "(doubleres)" tag means 2, and an "@Nx" suffix before the
+/// extension ("arrow@3x.png") means N. Anything else is 1.
+pub fn parse_texture_density(filename: &str) -> u32 {
+    let name = filename.rsplit('/').next().unwrap_or(filename).to_ascii_lowercase();
+    if name.contains("(doubleres)") {
+        return 2;
+    }
+    let stem = name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem);
+    stem.rsplit_once('@')
+        .and_then(|(_, tag)| tag.strip_suffix('x'))
+        .and_then(|n| n.parse::<u32>().ok())
+        .filter(|&n| (1..=8).contains(&n))
+        .unwrap_or(1)
+}
+
 pub fn parse_sprite_sheet_dims(filename: &str) -> (u32, u32) {
     #[inline(always)]
     fn parse_ascii_digits(bytes: &[u8]) -> Option<u32> {
@@ -129,27 +306,374 @@ pub fn parse_sprite_sheet_dims(filename: &str) -> (u32, u32) {
     dims.unwrap_or((1, 1))
 }
 
+// --- Background Processing ---
+
+/// Longest edge backgrounds are reduced to before blurring. A blurred image loses
+/// its fine detail anyway, and blur cost scales with pixel count.
+const BLUR_MAX_DIMENSION: u32 = 960;
+/// Mean luma (0..1) that bright backgrounds are scaled down to when normalization is on.
+const BACKGROUND_TARGET_LUMA: f32 = 0.3;
+
+/// Applies the configured blur and brightness normalization to a song background.
+fn process_background(mut rgba: RgbaImage) -> RgbaImage {
+    let cfg = crate::config::get();
+
+    if cfg.background_blur > 0.0 {
+        let (w, h) = rgba.dimensions();
+        let longest = w.max(h);
+        if longest > BLUR_MAX_DIMENSION {
+            let s = BLUR_MAX_DIMENSION as f32 / longest as f32;
+            let (nw, nh) = (((w as f32 * s) as u32).max(1), ((h as f32 * s) as u32).max(1));
+            rgba = image::imageops::resize(&rgba, nw, nh, image::imageops::FilterType::Triangle);
+        }
+        rgba = image::imageops::blur(&rgba, cfg.background_blur);
+    }
+
+    if cfg.background_normalize {
+        let pixels = (rgba.width() as u64 * rgba.height() as u64).max(1);
+        let luma_sum: f64 = rgba
+            .pixels()
+            .map(|p| 0.2126 * p[0] as f64 + 0.7152 * p[1] as f64 + 0.0722 * p[2] as f64)
+            .sum();
+        let mean = (luma_sum / pixels as f64 / 255.0) as f32;
+        // Only darken; dim backgrounds are left alone.
+        if mean > BACKGROUND_TARGET_LUMA {
+            let gain = BACKGROUND_TARGET_LUMA / mean;
+            for p in rgba.pixels_mut() {
+                for c in &mut p.0[..3] {
+                    *c = (*c as f32 * gain).round() as u8;
+                }
+            }
+        }
+    }
+
+    rgba
+}
 
 // --- Asset Manager ---
 
+// Decoded, display-sized banners kept in memory so scrolling back over a song
+// doesn't decode its banner again.
+const DECODED_BANNER_CACHE_SIZE: usize = 48;
+
+enum BannerDecode {
+    Done(RgbaImage),
+    Failed,
+    /// Dropped before it started because the wheel scrolled away from it.
+    Cancelled,
+}
+
+/// Off-thread banner decoding. Files are decoded and downsized on the rayon pool;
+/// the main thread only uploads the finished images.
+struct BannerDecoder {
+    sender: Sender<(PathBuf, BannerDecode)>,
+    receiver: Receiver<(PathBuf, BannerDecode)>,
+    /// Decodes in flight, with the flag that cancels them if they haven't started.
+    pending: HashMap<PathBuf, Arc<AtomicBool>>,
+    /// Most recently used last.
+    decoded: VecDeque<(PathBuf, Arc<RgbaImage>)>,
+    /// The banner the wheel is waiting on; other finished decodes are only cached.
+    wanted: Option<PathBuf>,
+}
+
+impl BannerDecoder {
+    fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self { sender, receiver, pending: HashMap::new(), decoded: VecDeque::new(), wanted: None }
+    }
+
+    fn is_cached(&self, path: &Path) -> bool {
+        self.decoded.iter().any(|(p, _)| p == path)
+    }
+
+    fn take_cached(&mut self, path: &Path) -> Option<Arc<RgbaImage>> {
+        let i = self.decoded.iter().position(|(p, _)| p == path)?;
+        let entry = self.decoded.remove(i)?;
+        let image = entry.1.clone();
+        self.decoded.push_back(entry);
+        Some(image)
+    }
+
+    fn insert(&mut self, path: PathBuf, image: Arc<RgbaImage>) {
+        self.decoded.retain(|(p, _)| p != &path);
+        self.decoded.push_back((path, image));
+        while self.decoded.len() > DECODED_BANNER_CACHE_SIZE {
+            self.decoded.pop_front();
+        }
+    }
+
+    fn request(&mut self, path: PathBuf) {
+        if let Some(cancel) = self.pending.get(&path) {
+            cancel.store(false, Ordering::Relaxed);
+            return;
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.pending.insert(path.clone(), cancel.clone());
+        // Largest size the banner is drawn at, in window pixels.
+        let scale = crate::core::space::pixels_per_unit().max(1.0);
+        let max_w = (crate::ui::components::banner::BANNER_NATIVE_WIDTH * scale).ceil() as u32;
+        let max_h = (crate::ui::components::banner::BANNER_NATIVE_HEIGHT * scale).ceil() as u32;
+        let sender = self.sender.clone();
+        rayon::spawn(move || {
+            let decoded = if cancel.load(Ordering::Relaxed) {
+                BannerDecode::Cancelled
+            } else {
+                match open_image(&path, |img| fit_to_cover(img, max_w, max_h)) {
+                    Ok((img, animation)) => {
+                        register_texture_animation(&path.to_string_lossy(), animation);
+                        BannerDecode::Done(img)
+                    }
+                    Err(e) => {
+                        warn!("Failed to open banner image {:?}: {}. Using fallback.", path, e);
+                        BannerDecode::Failed
+                    }
+                }
+            };
+            let _ = sender.send((path, decoded));
+        });
+    }
+
+    /// Decodes `paths` into the cache ahead of the wheel. Queued prefetches that
+    /// aren't in `paths` any more (the wheel turned around) are cancelled; the
+    /// banner the wheel is waiting on never is.
+    fn prefetch(&mut self, paths: &[PathBuf]) {
+        for (path, cancel) in &self.pending {
+            if !paths.contains(path) && self.wanted.as_ref() != Some(path) {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        }
+        for path in paths {
+            if !self.is_cached(path) {
+                self.request(path.clone());
+            }
+        }
+    }
+}
+
+/// Downscales (never upscales) so the image still covers a `max_w` x `max_h` box,
+/// which is all the banner component can show even when cropping.
+fn fit_to_cover(rgba: RgbaImage, max_w: u32, max_h: u32) -> RgbaImage {
+    let (w, h) = rgba.dimensions();
+    if w == 0 || h == 0 || max_w == 0 || max_h == 0 {
+        return rgba;
+    }
+    let scale = (max_w as f32 / w as f32).max(max_h as f32 / h as f32);
+    if scale >= 1.0 {
+        return rgba;
+    }
+    let new_w = ((w as f32 * scale).round() as u32).max(1);
+    let new_h = ((h as f32 * scale).round() as u32).max(1);
+    image::imageops::resize(&rgba, new_w, new_h, image::imageops::FilterType::Triangle)
+}
+
+/// Formats the backend can take pre-compressed, for `read_ktx2_sibling`.
+fn compressed_formats(backend: &Backend) -> Vec<CompressedFormat> {
+    CompressedFormat::ALL.into_iter().filter(|&f| backend.supports_compressed(f)).collect()
+}
+
+/// Reads the `.ktx2` next to `path` if there is one in a format from `supported`.
+/// Large art can ship pre-compressed this way and stays BC-compressed in VRAM;
+/// the original image is the fallback.
+fn read_ktx2_sibling(path: &Path, supported: &[CompressedFormat]) -> Option<CompressedImage> {
+    let ktx_path = path.with_extension("ktx2");
+    let bytes = fs::read(&ktx_path).ok()?;
+    match ktx2::parse(&bytes) {
+        Ok(image) if supported.contains(&image.format) => Some(image),
+        Ok(image) => {
+            info!("GPU can't sample {:?}; using the fallback for '{}'.", image.format, ktx_path.display());
+            None
+        }
+        Err(e) => {
+            warn!("Ignoring '{}': {}", ktx_path.display(), e);
+            None
+        }
+    }
+}
+
+enum DecodedTexture {
+    Rgba(Arc<RgbaImage>),
+    Compressed(CompressedImage),
+}
+
+impl DecodedTexture {
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Self::Rgba(rgba) => rgba.dimensions(),
+            Self::Compressed(image) => (image.width, image.height),
+        }
+    }
+
+    fn upload(&self, backend: &mut Backend) -> Result<GfxTexture, Box<dyn Error>> {
+        match self {
+            Self::Rgba(rgba) => backend.create_texture(rgba),
+            Self::Compressed(image) => backend.create_compressed_texture(image),
+        }
+    }
+}
+
+// Textures loaded at boot and kept resident; see the file for its format.
+const PRELOAD_MANIFEST_PATH: &str = "assets/preload.txt";
+// Built-in copy, used if the file on disk is missing.
+const PRELOAD_MANIFEST_DEFAULT: &str = include_str!("../assets/preload.txt");
+
+fn preload_asset_path(relative_path: &str) -> PathBuf {
+    if relative_path.starts_with("noteskins/") {
+        Path::new("assets").join(relative_path)
+    } else {
+        Path::new("assets/graphics").join(relative_path)
+    }
+}
+
+/// Parses the preload manifest into (texture key, file relative to its asset root).
+fn read_preload_manifest() -> Vec<(String, String)> {
+    let text = fs::read_to_string(PRELOAD_MANIFEST_PATH).unwrap_or_else(|e| {
+        warn!("Could not read '{}': {}. Using the built-in preload list.", PRELOAD_MANIFEST_PATH, e);
+        PRELOAD_MANIFEST_DEFAULT.to_string()
+    });
+
+    let mut entries = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((dir, ext)) = line.rsplit_once("/*.") {
+            let Ok(files) = fs::read_dir(preload_asset_path(dir)) else {
+                warn!("Preload folder '{}' not found.", dir);
+                continue;
+            };
+            for file in files.flatten() {
+                let name = file.file_name().to_string_lossy().into_owned();
+                let matches = Path::new(&name)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case(ext));
+                if matches {
+                    let key = format!("{}/{}", dir, name);
+                    entries.push((key.clone(), key));
+                }
+            }
+        } else if let Some((key, file)) = line.split_once('=') {
+            entries.push((key.trim().to_string(), file.trim().to_string()));
+        } else {
+            entries.push((line.to_string(), line.to_string()));
+        }
+    }
+    info!("Preload manifest lists {} textures.", entries.len());
+    entries
+}
+
 pub struct AssetManager {
     pub textures: HashMap<String, GfxTexture>,
+    /// What each texture was uploaded from, for `reupload_textures` after the
+    /// device is lost. The glyph atlas and video frames are rebuilt instead.
+    sources: HashMap<String, DecodedTexture>,
     fonts: HashMap<&'static str, Font>,
     current_dynamic_banner: Option<(String, PathBuf)>,
-    current_density_graph: Option<(String, String)>,
+    banner_decoder: BannerDecoder,
+    /// Banner texture still being uploaded, and its image. The current banner stays
+    /// up as the placeholder until it lands.
+    banner_upload: Option<(UploadId, PathBuf, Arc<RgbaImage>)>,
     current_dynamic_background: Option<(String, PathBuf)>,
+    background_video: Option<VideoPlayback>,
+    preview_video: Option<VideoPlayback>,
     current_profile_avatar: Option<(String, PathBuf)>,
+    glyph_atlas: GlyphAtlas,
+    /// Song select's density graph: its texture key and the target drawn into
+    /// it, None if drawing failed (so it isn't retried every frame).
+    density_graph: Option<(String, Option<RenderTarget>)>,
+}
+
+/// Texture key the playing background video is drawn under.
+pub const BACKGROUND_VIDEO_KEY: &str = "__background_video";
+/// Texture key song select's preview video is drawn under.
+pub const PREVIEW_VIDEO_KEY: &str = "__preview_video";
+
+/// A playing video. The first frame is uploaded into a texture under `key`
+/// and each later one written over it in place. A texture replaced because
+/// the size changed is kept until frames in flight can no longer sample it.
+struct VideoPlayback {
+    key: &'static str,
+    stream: VideoStream,
+    upload: Option<UploadId>,
+    shown: bool,
+    // Size of the texture under `key`, once the first frame has landed.
+    size: Option<(u32, u32)>,
+    // Replaced frame textures and how many more updates they're kept for.
+    retired: VecDeque<(usize, GfxTexture)>,
+}
+
+impl VideoPlayback {
+    fn open(key: &'static str, path: PathBuf) -> Self {
+        Self { key, stream: VideoStream::open(path), upload: None, shown: false, size: None, retired: VecDeque::new() }
+    }
+
+    // Hands back every texture this video owns, current frame included, for disposal.
+    fn into_textures(self, textures: &mut HashMap<String, GfxTexture>) -> HashMap<String, GfxTexture> {
+        let mut owned: HashMap<String, GfxTexture> = self.retired
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, texture))| (i.to_string(), texture))
+            .collect();
+        if let Some(texture) = textures.remove(self.key) {
+            owned.insert(self.key.to_string(), texture);
+        }
+        owned
+    }
+
+    // Frees retired frames that are out of flight and writes or queues the
+    // frame due at `time`. Returns the key once a frame is up.
+    fn advance(&mut self, backend: &mut Backend, textures: &HashMap<String, GfxTexture>, time: f64) -> Option<String> {
+        let mut expired = HashMap::new();
+        for (frames_left, _) in self.retired.iter_mut() {
+            *frames_left = frames_left.saturating_sub(1);
+        }
+        while self.retired.front().is_some_and(|(frames_left, _)| *frames_left == 0) {
+            let (_, texture) = self.retired.pop_front().unwrap();
+            expired.insert(expired.len().to_string(), texture);
+        }
+        if !expired.is_empty() {
+            backend.dispose_textures(&mut expired);
+        }
+
+        // One upload at a time; frames that come due meanwhile are skipped.
+        if self.upload.is_none() {
+            if let Some(frame) = self.stream.frame_at(time) {
+                let (w, h) = frame.dimensions();
+                if let Some(texture) = textures.get(self.key).filter(|_| self.size == Some((w, h))) {
+                    match backend.update_texture(texture, &frame, [0, 0, w, h]) {
+                        Ok(()) => return self.shown.then(|| self.key.to_string()),
+                        Err(e) => warn!("Failed to update video frame: {}", e),
+                    }
+                }
+                match backend.queue_texture_upload(&frame) {
+                    Ok(id) => {
+                        register_texture_dims(self.key, w, h);
+                        self.size = Some((w, h));
+                        self.upload = Some(id);
+                    }
+                    Err(e) => warn!("Failed to upload video frame: {}", e),
+                }
+            }
+        }
+        self.shown.then(|| self.key.to_string())
+    }
 }
 
 impl AssetManager {
     pub fn new() -> Self {
         Self {
             textures: HashMap::new(),
+            sources: HashMap::new(),
             fonts: HashMap::new(),
             current_dynamic_banner: None,
-            current_density_graph: None,
+            banner_decoder: BannerDecoder::new(),
+            banner_upload: None,
             current_dynamic_background: None,
+            background_video: None,
+            preview_video: None,
             current_profile_avatar: None,
+            glyph_atlas: GlyphAtlas::new(),
+            density_graph: None,
         }
     }
 
@@ -196,87 +720,23 @@ impl AssetManager {
         let white_img = RgbaImage::from_raw(1, 1, vec![255, 255, 255, 255]).unwrap();
         let white_tex = backend.create_texture(&white_img)?;
         self.textures.insert("__white".to_string(), white_tex);
+        self.sources.insert("__white".to_string(), DecodedTexture::Rgba(Arc::new(white_img)));
         register_texture_dims("__white", 1, 1);
         info!("Loaded built-in texture: __white");
 
-        let mut textures_to_load: Vec<(String, String)> = vec![
-            ("logo.png".to_string(), "logo.png".to_string()),
-            ("init_arrow.png".to_string(), "init_arrow.png".to_string()),
-            ("dance.png".to_string(), "dance.png".to_string()),
-            ("meter_arrow.png".to_string(), "meter_arrow.png".to_string()),
-            ("rounded-square.png".to_string(), "rounded-square.png".to_string()),
-            ("circle.png".to_string(), "circle.png".to_string()),
-            ("swoosh.png".to_string(), "swoosh.png".to_string()),
-            ("heart.png".to_string(), "heart.png".to_string()),
-            ("combo_explosion.png".to_string(), "combo_explosion.png".to_string()),
-            (
-                "combo_100milestone_splode.png".to_string(),
-                "combo_100milestone_splode.png".to_string(),
-            ),
-            (
-                "combo_100milestone_minisplode.png".to_string(),
-                "combo_100milestone_minisplode.png".to_string(),
-            ),
-            (
-                "combo_1000milestone_swoosh.png".to_string(),
-                "combo_1000milestone_swoosh.png".to_string(),
-            ),
-            (
-                "hit_mine_explosion.png".to_string(),
-                "hit_mine_explosion.png".to_string(),
-            ),
-            ("banner1.png".to_string(), "_fallback/banner1.png".to_string()),
-            ("banner2.png".to_string(), "_fallback/banner2.png".to_string()),
-            ("banner3.png".to_string(), "_fallback/banner3.png".to_string()),
-            ("banner4.png".to_string(), "_fallback/banner4.png".to_string()),
-            ("banner5.png".to_string(), "_fallback/banner5.png".to_string()),
-            ("banner6.png".to_string(), "_fallback/banner6.png".to_string()),
-            ("banner7.png".to_string(), "_fallback/banner7.png".to_string()),
-            ("banner8.png".to_string(), "_fallback/banner8.png".to_string()),
-            ("banner9.png".to_string(), "_fallback/banner9.png".to_string()),
-            ("banner10.png".to_string(), "_fallback/banner10.png".to_string()),
-            ("banner11.png".to_string(), "_fallback/banner11.png".to_string()),
-            ("banner12.png".to_string(), "_fallback/banner12.png".to_string()),
-            ("noteskins/bar/tex notes.png".to_string(), "noteskins/bar/tex notes.png".to_string()),
-            ("noteskins/bar/tex receptors.png".to_string(), "noteskins/bar/tex receptors.png".to_string()),
-            ("noteskins/bar/tex glow.png".to_string(), "noteskins/bar/tex glow.png".to_string()),
-            (
-                "judgements/Love 2x7 (doubleres).png".to_string(),
-                "judgements/Love 2x7 (doubleres).png".to_string(),
-            ),
-            (
-                "hold_judgements/Love 1x2 (doubleres).png".to_string(),
-                "hold_judgements/Love 1x2 (doubleres).png".to_string(),
-            ),
-            (
-                "grades/grades 1x19.png".to_string(),
-                "grades/grades 1x19.png".to_string(),
-            ),
-        ];
-
-        if let Ok(entries) = fs::read_dir("assets/noteskins/cel") {
-            for entry in entries.flatten() {
-                if let Some(ext) = entry.path().extension().and_then(|e| e.to_str()) {
-                    if ext.eq_ignore_ascii_case("png") {
-                        if let Ok(name) = entry.file_name().into_string() {
-                            let key = format!("noteskins/cel/{}", name);
-                            textures_to_load.push((key.clone(), key));
-                        }
-                    }
-                }
-            }
-        }
+        let textures_to_load = read_preload_manifest();
 
+        let compressed = compressed_formats(backend);
         let mut handles = Vec::with_capacity(textures_to_load.len());
         for (key, relative_path) in textures_to_load {
+            let compressed = compressed.clone();
             handles.push(std::thread::spawn(move || {
-                let path = if relative_path.starts_with("noteskins/") {
-                    Path::new("assets").join(&relative_path)
-                } else {
-                    Path::new("assets/graphics").join(&relative_path)
-                };
-                match image::open(&path) {
-                    Ok(img) => Ok::<(String, RgbaImage), (String, String)>((key, img.to_rgba8())),
+                let path = preload_asset_path(&relative_path);
+                if let Some(image) = read_ktx2_sibling(&path, &compressed) {
+                    return Ok((key, DecodedTexture::Compressed(image), None));
+                }
+                match open_image(&path, |img| img) {
+                    Ok((img, animation)) => Ok::<_, (String, String)>((key, DecodedTexture::Rgba(Arc::new(img)), animation)),
                     Err(e) => Err((key, e.to_string())),
                 }
             }));
@@ -285,17 +745,21 @@ impl AssetManager {
         let fallback_image = Arc::new(fallback_rgba());
         for h in handles {
             match h.join().expect("texture decode thread panicked") {
-                Ok((key, rgba)) => {
-                    let texture = backend.create_texture(&rgba)?;
-                    register_texture_dims(&key, rgba.width(), rgba.height());
+                Ok((key, decoded, animation)) => {
+                    let texture = decoded.upload(backend)?;
+                    let (w, h) = decoded.dimensions();
+                    register_texture_dims(&key, w, h);
+                    register_texture_animation(&key, animation);
                     info!("Loaded texture: {}", key);
-                    self.textures.insert(key, texture);
+                    self.textures.insert(key.clone(), texture);
+                    self.sources.insert(key, decoded);
                 }
                 Err((key, msg)) => {
                     warn!("Failed to load texture for key '{}': {}. Using fallback.", key, msg);
                     let texture = backend.create_texture(&fallback_image)?;
                     register_texture_dims(&key, fallback_image.width(), fallback_image.height());
-                    self.textures.insert(key, texture);
+                    self.textures.insert(key.clone(), texture);
+                    self.sources.insert(key, DecodedTexture::Rgba(fallback_image.clone()));
                 }
             }
         }
@@ -335,11 +799,13 @@ impl AssetManager {
 
             for tex_path in &required_textures {
                 let key = canonical_texture_key(tex_path);
+                self.glyph_atlas.register_sheet(key.clone(), tex_path.clone());
                 if !self.textures.contains_key(&key) {
                     let image_data = image::open(tex_path)?.to_rgba8();
                     let texture = backend.create_texture(&image_data)?;
                     register_texture_dims(&key, image_data.width(), image_data.height());
                     self.textures.insert(key.clone(), texture);
+                    self.sources.insert(key.clone(), DecodedTexture::Rgba(Arc::new(image_data)));
                     info!("Loaded font texture: {}", key);
                 }
             }
@@ -349,91 +815,239 @@ impl AssetManager {
         Ok(())
     }
 
+    /// Expands `ObjectType::Text` objects into glyph-atlas sprites, uploading the
+    /// part of the atlas packed since the last list.
+    pub fn resolve_text(&mut self, backend: &mut Backend, render_list: &mut crate::core::gfx::RenderList) {
+        if !self.glyph_atlas.resolve(render_list, &self.fonts) {
+            return;
+        }
+        let Some((image, region)) = self.glyph_atlas.take_upload() else { return };
+        // Lists already drawn keep what they sampled; the copy is ordered after them.
+        if let Some(texture) = self.textures.get(GLYPH_ATLAS_KEY) {
+            if let Err(e) = backend.update_texture(texture, image, region) {
+                warn!("Failed to update glyph atlas: {}", e);
+            }
+            return;
+        }
+        match backend.create_texture(image) {
+            Ok(texture) => {
+                register_texture_dims(GLYPH_ATLAS_KEY, image.width(), image.height());
+                self.textures.insert(GLYPH_ATLAS_KEY.to_string(), texture);
+            }
+            Err(e) => warn!("Failed to upload glyph atlas: {}", e),
+        }
+    }
+
     // --- Dynamic Asset Management ---
 
     pub fn destroy_dynamic_assets(&mut self, backend: &mut Backend) {
-        if self.current_dynamic_banner.is_some() || self.current_density_graph.is_some() || self.current_dynamic_background.is_some() {
+        self.banner_decoder.wanted = None;
+        self.banner_upload = None;
+        if self.current_dynamic_banner.is_some() || self.current_dynamic_background.is_some() {
             backend.wait_for_idle(); // Wait for GPU to finish using old textures
-            if let Some((key, _)) = self.current_dynamic_banner.take() { self.textures.remove(&key); }
-            if let Some((key, _)) = self.current_density_graph.take() { self.textures.remove(&key); }
-            if let Some((key, _)) = self.current_dynamic_background.take() { self.textures.remove(&key); }
+            if let Some((key, _)) = self.current_dynamic_banner.take() { self.forget_texture(&key); }
+            if let Some((key, _)) = self.current_dynamic_background.take() { self.forget_texture(&key); }
         }
+        self.set_background_video(backend, None);
+        self.set_preview_video(backend, None);
         self.destroy_current_profile_avatar(backend);
+        self.destroy_density_graph(backend);
     }
 
-    pub fn set_dynamic_banner(&mut self, backend: &mut Backend, path_opt: Option<PathBuf>) -> String {
-        if let Some(path) = path_opt {
-            if self.current_dynamic_banner.as_ref().map_or(false, |(_, p)| p == &path) {
-                return self.current_dynamic_banner.as_ref().unwrap().0.clone();
+    /// Lets go of every GPU texture and pending upload before `backend` is torn
+    /// down after losing its device. The CPU copies stay for `reupload_textures`.
+    pub fn release_textures(&mut self, backend: &mut Backend) {
+        self.destroy_density_graph(backend);
+        for video in [&mut self.background_video, &mut self.preview_video].into_iter().flatten() {
+            video.upload = None;
+            video.shown = false;
+            video.size = None;
+            let mut retired: HashMap<String, GfxTexture> = video.retired
+                .drain(..)
+                .enumerate()
+                .map(|(i, (_, texture))| (i.to_string(), texture))
+                .collect();
+            backend.dispose_textures(&mut retired);
+        }
+        backend.dispose_textures(&mut self.textures);
+    }
+
+    /// Uploads everything `release_textures` let go of into a new `backend`.
+    /// The glyph atlas follows on the next text draw and videos with their next
+    /// frame; a banner that was mid-upload starts over.
+    pub fn reupload_textures(&mut self, backend: &mut Backend) {
+        for (key, source) in &self.sources {
+            match source.upload(backend) {
+                Ok(texture) => {
+                    self.textures.insert(key.clone(), texture);
+                }
+                Err(e) => warn!("Failed to upload texture '{}' again: {}", key, e),
             }
+        }
+        self.glyph_atlas.invalidate();
+        if let Some((_, path, image)) = self.banner_upload.take() {
+            // An inline upload lands in `current_dynamic_banner` right away.
+            let _ = self.queue_dynamic_banner(backend, path, image);
+        }
+        info!("Uploaded {} textures to the new renderer.", self.textures.len());
+    }
 
+    /// Drops decoded banners sized for the old pixel density, after the window
+    /// moved to a monitor with another scale. The banner on screen stays up.
+    pub fn forget_decoded_banners(&mut self) {
+        self.banner_decoder.decoded.clear();
+    }
+
+    /// Shows the banner at `path`, or the fallback for `None`. Returns the texture key
+    /// to draw, or `None` while the image is still decoding or uploading; the key then
+    /// arrives through `poll_banner_decodes`.
+    pub fn set_dynamic_banner(&mut self, backend: &mut Backend, path_opt: Option<PathBuf>) -> Option<String> {
+        let Some(path) = path_opt else {
+            self.banner_decoder.wanted = None;
+            self.banner_upload = None;
             self.destroy_current_dynamic_banner(backend);
+            return Some("banner1.png".to_string());
+        };
 
-            match image::open(&path) {
-                Ok(img) => {
-                    let rgba = img.to_rgba8();
-                    match backend.create_texture(&rgba) {
-                        Ok(texture) => {
-                            let key = path.to_string_lossy().into_owned();
-                            self.textures.insert(key.clone(), texture);
-                            register_texture_dims(&key, rgba.width(), rgba.height());
-                            self.current_dynamic_banner = Some((key.clone(), path));
-                            key
-                        }
-                        Err(e) => {
-                            warn!("Failed to create GPU texture for {:?}: {}. Using fallback.", path, e);
-                            "banner1.png".to_string()
-                        }
+        if let Some((key, p)) = &self.current_dynamic_banner {
+            if p == &path {
+                self.banner_decoder.wanted = None;
+                self.banner_upload = None;
+                return Some(key.clone());
+            }
+        }
+        if self.banner_upload.as_ref().is_some_and(|(_, p, _)| p == &path) {
+            self.banner_decoder.wanted = None;
+            return None;
+        }
+        self.banner_upload = None;
+
+        if let Some(image) = self.banner_decoder.take_cached(&path) {
+            self.banner_decoder.wanted = None;
+            return self.queue_dynamic_banner(backend, path, image);
+        }
+
+        // Compressed banners are small enough to read here and need no decoding.
+        if let Some(image) = read_ktx2_sibling(&path, &compressed_formats(backend)) {
+            self.banner_decoder.wanted = None;
+            return Some(self.upload_dynamic_banner(backend, path, DecodedTexture::Compressed(image)));
+        }
+
+        self.banner_decoder.wanted = Some(path.clone());
+        self.banner_decoder.request(path);
+        None
+    }
+
+    /// Starts decoding the banners at `paths` in the background so they're ready
+    /// when the wheel gets there. Pre-compressed banners load inline and are skipped.
+    pub fn prefetch_banners(&mut self, paths: &[PathBuf]) {
+        let paths: Vec<PathBuf> = paths.iter()
+            .filter(|p| !p.with_extension("ktx2").is_file())
+            .cloned()
+            .collect();
+        self.banner_decoder.prefetch(&paths);
+    }
+
+    /// Collects finished banner decodes and uploads. Returns the texture key once the
+    /// banner last asked for by `set_dynamic_banner` is ready to draw.
+    pub fn poll_banner_decodes(&mut self, backend: &mut Backend) -> Option<String> {
+        let mut ready = None;
+        while let Ok((path, decoded)) = self.banner_decoder.receiver.try_recv() {
+            self.banner_decoder.pending.remove(&path);
+            let is_wanted = self.banner_decoder.wanted.as_ref() == Some(&path);
+            match decoded {
+                BannerDecode::Done(image) => {
+                    let image = Arc::new(image);
+                    self.banner_decoder.insert(path.clone(), image.clone());
+                    if is_wanted {
+                        self.banner_decoder.wanted = None;
+                        ready = self.queue_dynamic_banner(backend, path, image);
                     }
                 }
-                Err(e) => {
-                    warn!("Failed to open banner image {:?}: {}. Using fallback.", path, e);
-                    "banner1.png".to_string()
+                BannerDecode::Failed if is_wanted => {
+                    self.banner_decoder.wanted = None;
+                    self.destroy_current_dynamic_banner(backend);
+                    ready = Some("banner1.png".to_string());
                 }
+                // Cancelled as a prefetch just before the wheel landed on it.
+                BannerDecode::Cancelled if is_wanted => self.banner_decoder.request(path),
+                BannerDecode::Failed | BannerDecode::Cancelled => {}
             }
-        } else {
-            self.destroy_current_dynamic_banner(backend);
-            "banner1.png".to_string()
         }
+        let uploaded = self.collect_uploads(backend);
+        ready.or(uploaded)
     }
 
-    pub fn set_density_graph(
-        &mut self,
-        backend: &mut Backend,
-        data: Option<(String, rssp::graph::GraphImageData)>,
-    ) -> String {
-        const FALLBACK_KEY: &str = "__white";
-
-        if let Some((key, graph_data)) = data {
-            if self.current_density_graph.as_ref().map_or(false, |(_, cache_key)| cache_key == &key) {
-                return self.current_density_graph.as_ref().unwrap().0.clone();
+    /// Starts uploading a decoded banner. Returns None while it uploads (its key then
+    /// comes from `poll_banner_decodes`), or the key right away if it had to be done inline.
+    fn queue_dynamic_banner(&mut self, backend: &mut Backend, path: PathBuf, image: Arc<RgbaImage>) -> Option<String> {
+        match backend.queue_texture_upload(&image) {
+            Ok(id) => {
+                self.banner_upload = Some((id, path, image));
+                None
             }
+            Err(e) => {
+                warn!("Async upload of banner {:?} failed: {}. Uploading inline.", path, e);
+                Some(self.upload_dynamic_banner(backend, path, DecodedTexture::Rgba(image)))
+            }
+        }
+    }
 
-            self.destroy_current_density_graph(backend);
-
-            let rgba_image = match RgbaImage::from_raw(graph_data.width, graph_data.height, graph_data.data) {
-                Some(img) => img,
+    // Swaps in banner and video frame textures whose upload finished; stale ones
+    // are freed. Returns the banner's key if one landed.
+    fn collect_uploads(&mut self, backend: &mut Backend) -> Option<String> {
+        let mut ready = None;
+        let mut stale = HashMap::new();
+        for (id, texture) in backend.poll_texture_uploads() {
+            if let Some((_, path, image)) = self.banner_upload.take_if(|(wanted, _, _)| *wanted == id) {
+                self.destroy_current_dynamic_banner(backend);
+                let key = path.to_string_lossy().into_owned();
+                self.textures.insert(key.clone(), texture);
+                register_texture_dims(&key, image.width(), image.height());
+                self.sources.insert(key.clone(), DecodedTexture::Rgba(image));
+                self.current_dynamic_banner = Some((key.clone(), path));
+                ready = Some(key);
+                continue;
+            }
+            let video = [&mut self.background_video, &mut self.preview_video]
+                .into_iter()
+                .flatten()
+                .find(|v| v.upload == Some(id));
+            match video {
+                Some(video) => {
+                    video.upload = None;
+                    video.shown = true;
+                    if let Some(old) = self.textures.insert(video.key.to_string(), texture) {
+                        video.retired.push_back((MAX_FRAMES_IN_FLIGHT + 1, old));
+                    }
+                }
                 None => {
-                    warn!("Failed to create RgbaImage from raw graph data for key '{}'.", key);
-                    return FALLBACK_KEY.to_string();
+                    stale.insert(format!("{:?}", id), texture);
                 }
-            };
+            }
+        }
+        if !stale.is_empty() {
+            backend.dispose_textures(&mut stale);
+        }
+        ready
+    }
 
-            match backend.create_texture(&rgba_image) {
-                Ok(texture) => {
-                    self.textures.insert(key.clone(), texture);
-                    register_texture_dims(&key, rgba_image.width(), rgba_image.height());
-                    self.current_density_graph = Some((key.clone(), key.clone()));
-                    key
-                }
-                Err(e) => {
-                    warn!("Failed to create GPU texture for density graph ('{}'): {}.", key, e);
-                    FALLBACK_KEY.to_string()
-                }
+    fn upload_dynamic_banner(&mut self, backend: &mut Backend, path: PathBuf, image: DecodedTexture) -> String {
+        self.destroy_current_dynamic_banner(backend);
+        match image.upload(backend) {
+            Ok(texture) => {
+                let key = path.to_string_lossy().into_owned();
+                let (w, h) = image.dimensions();
+                self.textures.insert(key.clone(), texture);
+                self.sources.insert(key.clone(), image);
+                register_texture_dims(&key, w, h);
+                self.current_dynamic_banner = Some((key.clone(), path));
+                key
+            }
+            Err(e) => {
+                warn!("Failed to create GPU texture for {:?}: {}. Using fallback.", path, e);
+                "banner1.png".to_string()
             }
-        } else {
-            self.destroy_current_density_graph(backend);
-            FALLBACK_KEY.to_string()
         }
     }
 
@@ -447,14 +1061,27 @@ impl AssetManager {
 
             self.destroy_current_dynamic_background(backend);
 
-            match image::open(&path) {
-                Ok(img) => {
-                    let rgba = img.to_rgba8();
-                    match backend.create_texture(&rgba) {
+            // Blur and normalization work on pixels, so they need the decoded image.
+            let cfg = crate::config::get();
+            let compressed = if cfg.background_blur > 0.0 || cfg.background_normalize {
+                None
+            } else {
+                read_ktx2_sibling(&path, &compressed_formats(backend))
+            };
+            let decoded = match compressed {
+                Some(image) => Ok(DecodedTexture::Compressed(image)),
+                None => image::open(&path).map(|img| DecodedTexture::Rgba(Arc::new(process_background(img.to_rgba8())))),
+            };
+
+            match decoded {
+                Ok(image) => {
+                    match image.upload(backend) {
                         Ok(texture) => {
                             let key = path.to_string_lossy().into_owned();
+                            let (w, h) = image.dimensions();
                             self.textures.insert(key.clone(), texture);
-                            register_texture_dims(&key, rgba.width(), rgba.height());
+                            self.sources.insert(key.clone(), image);
+                            register_texture_dims(&key, w, h);
                             self.current_dynamic_background = Some((key.clone(), path));
                             key
                         }
@@ -475,6 +1102,100 @@ impl AssetManager {
         }
     }
 
+    /// Starts playing the video at `path` as the background, or stops it for None.
+    /// Frames arrive through `update_background_video`.
+    pub fn set_background_video(&mut self, backend: &mut Backend, path_opt: Option<PathBuf>) {
+        if let Some(video) = self.background_video.take() {
+            backend.wait_for_idle();
+            backend.dispose_textures(&mut video.into_textures(&mut self.textures));
+        }
+        self.background_video = path_opt.map(|path| VideoPlayback::open(BACKGROUND_VIDEO_KEY, path));
+    }
+
+    /// Starts playing the video at `path` muted and looping in song select's info
+    /// pane, or stops it for None. Frames arrive through `update_preview_video`.
+    pub fn set_preview_video(&mut self, backend: &mut Backend, path_opt: Option<PathBuf>) {
+        if let Some(video) = self.preview_video.take() {
+            backend.wait_for_idle();
+            backend.dispose_textures(&mut video.into_textures(&mut self.textures));
+        }
+        self.preview_video = path_opt.map(|path| VideoPlayback::open(PREVIEW_VIDEO_KEY, path));
+    }
+
+    /// Advances the background video to `time` seconds in and uploads the frame
+    /// due there. Call once per frame. Returns `BACKGROUND_VIDEO_KEY` once a frame
+    /// is ready to draw; until then the still background should stay up.
+    pub fn update_background_video(&mut self, backend: &mut Backend, time: f64) -> Option<String> {
+        self.background_video.as_ref()?;
+        self.collect_uploads(backend);
+        self.background_video.as_mut()?.advance(backend, &self.textures, time)
+    }
+
+    /// `update_background_video` for the preview video: returns `PREVIEW_VIDEO_KEY`
+    /// once a frame is ready, until then the banner should stay up. Finished frame
+    /// uploads are collected by `poll_banner_decodes`, so call this after it.
+    pub fn update_preview_video(&mut self, backend: &mut Backend, time: f64) -> Option<String> {
+        self.preview_video.as_mut()?.advance(backend, &self.textures, time)
+    }
+
+    /// Draws song select's density graph into a `size` pixel texture under `key`,
+    /// once per key; `build` lays the graph out over the whole screen space. A
+    /// new key replaces the old texture, None takes it down. Returns the key once
+    /// its texture is drawn.
+    pub fn update_density_graph(
+        &mut self,
+        backend: &mut Backend,
+        key: Option<String>,
+        size: (u32, u32),
+        build: impl FnOnce() -> Option<Actor>,
+        metrics: &Metrics,
+    ) -> Option<String> {
+        if self.density_graph.as_ref().map(|(k, _)| k) != key.as_ref() {
+            self.destroy_density_graph(backend);
+            if let Some(key) = key {
+                let target = build().and_then(|graph| self.draw_density_graph(backend, &key, size, graph, metrics));
+                self.density_graph = Some((key, target));
+            }
+        }
+        self.density_graph.as_ref().filter(|(_, target)| target.is_some()).map(|(key, _)| key.clone())
+    }
+
+    fn draw_density_graph(
+        &mut self,
+        backend: &mut Backend,
+        key: &str,
+        (width, height): (u32, u32),
+        graph: Actor,
+        metrics: &Metrics,
+    ) -> Option<RenderTarget> {
+        let list = compose::build_screen(&[graph], [0.0; 4], metrics, &self.fonts, EffectClocks::timer_only(0.0));
+        let (target, texture) = match backend.create_render_target(width, height) {
+            Ok(created) => created,
+            Err(e) => {
+                warn!("Failed to create the density graph's render target: {}", e);
+                return None;
+            }
+        };
+        if let Err(e) = backend.draw_to_target(&target, &list, &self.textures) {
+            warn!("Failed to draw the density graph: {}", e);
+            backend.destroy_render_target(target);
+            backend.dispose_textures(&mut HashMap::from([(key.to_string(), texture)]));
+            return None;
+        }
+        register_texture_dims(key, width, height);
+        self.textures.insert(key.to_string(), texture);
+        Some(target)
+    }
+
+    fn destroy_density_graph(&mut self, backend: &mut Backend) {
+        let Some((key, Some(target))) = self.density_graph.take() else { return };
+        backend.wait_for_idle();
+        backend.destroy_render_target(target);
+        if let Some(texture) = self.textures.remove(&key) {
+            backend.dispose_textures(&mut HashMap::from([(key, texture)]));
+        }
+    }
+
     pub fn set_profile_avatar(&mut self, backend: &mut Backend, path_opt: Option<PathBuf>) {
         if let Some(path) = path_opt {
             if self.current_profile_avatar.as_ref().map_or(false, |(_, p)| p == &path) {
@@ -494,6 +1215,7 @@ impl AssetManager {
                             let key = path.to_string_lossy().into_owned();
                             self.textures.insert(key.clone(), texture);
                             register_texture_dims(&key, rgba.width(), rgba.height());
+                            self.sources.insert(key.clone(), DecodedTexture::Rgba(Arc::new(rgba)));
                             self.current_profile_avatar = Some((key.clone(), path));
                             profile::set_avatar_texture_key(Some(key));
                         }
@@ -513,31 +1235,29 @@ impl AssetManager {
         }
     }
 
-    fn destroy_current_dynamic_banner(&mut self, backend: &mut Backend) {
-        if let Some((key, _)) = self.current_dynamic_banner.take() {
-            backend.wait_for_idle();
-            self.textures.remove(&key);
-        }
+    fn forget_texture(&mut self, key: &str) {
+        self.textures.remove(key);
+        self.sources.remove(key);
     }
 
-    fn destroy_current_density_graph(&mut self, backend: &mut Backend) {
-        if let Some((key, _)) = self.current_density_graph.take() {
+    fn destroy_current_dynamic_banner(&mut self, backend: &mut Backend) {
+        if let Some((key, _)) = self.current_dynamic_banner.take() {
             backend.wait_for_idle();
-            self.textures.remove(&key);
+            self.forget_texture(&key);
         }
     }
 
     fn destroy_current_dynamic_background(&mut self, backend: &mut Backend) {
         if let Some((key, _)) = self.current_dynamic_background.take() {
             backend.wait_for_idle();
-            self.textures.remove(&key);
+            self.forget_texture(&key);
         }
     }
 
     fn destroy_current_profile_avatar(&mut self, backend: &mut Backend) {
         if let Some((key, _)) = self.current_profile_avatar.take() {
             backend.wait_for_idle();
-            self.textures.remove(&key);
+            self.forget_texture(&key);
         }
         profile::set_avatar_texture_key(None);
     }
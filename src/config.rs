@@ -1,39 +1,162 @@
-use crate::core::gfx::BackendType;
+use crate::core::display::DisplayMode;
+use crate::core::gfx::{BackendType, HdrOutput, PresentMode, UpscaleFilter, DEFAULT_HDR_PAPER_WHITE, MAX_FRAMES_IN_FLIGHT, MSAA_SAMPLE_CHOICES, RENDER_SCALE_CHOICES};
+use crate::core::led_marquee::LedMarqueeOutput;
+use crate::game::game_type::GameType;
+use crate::game::gameplay::{AssistTick, FailType, HoldComboMode, HoldJudging};
+use crate::game::judgment::ScoreRounding;
+use crate::ui::components::banner::BannerFit;
 use configparser::ini::Ini;
 use log::{info, warn};
 use once_cell::sync::Lazy;
 use std::str::FromStr;
 use std::sync::Mutex;
 
-const CONFIG_PATH: &str = "deadsync.ini";
+pub const CONFIG_PATH: &str = "deadsync.ini";
 
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     pub vsync: bool,
-    pub windowed: bool,
+    pub display_mode: DisplayMode,
     pub show_stats: bool,
+    /// Window size, and the exclusive fullscreen resolution.
     pub display_width: u32,
     pub display_height: u32,
+    /// Monitor to go fullscreen on: 0 is the primary one, 1.. count the monitors in listing order.
+    pub display_monitor: u32,
+    /// Exclusive fullscreen refresh rate in Hz; 0 takes the highest the resolution offers.
+    pub refresh_rate: u32,
     pub video_renderer: BackendType,
+    /// Swap behavior; `Auto` follows `vsync`.
+    pub present_mode: PresentMode,
+    /// Frames queued ahead of the display, 1..=3. 1 is the lowest latency.
+    pub frames_in_flight: u8,
+    /// MSAA samples per pixel: 1 (off), 2, 4 or 8.
+    pub msaa_samples: u32,
+    /// Blend in linear light through sRGB textures and framebuffers. Off is
+    /// the old gamma-space blending.
+    pub srgb_color: bool,
+    /// Internal render resolution in percent of the window; one of `RENDER_SCALE_CHOICES`.
+    pub render_scale: u32,
+    /// How a render scale below 100 is stretched back to the window.
+    pub upscale_filter: UpscaleFilter,
+    /// HDR swapchain color space (Vulkan only); Off is SDR.
+    pub hdr_output: HdrOutput,
+    /// Nits SDR white is shown at under `hdr_output`, 80..=1000.
+    pub hdr_paper_white: u32,
+    /// Frame rate cap, independent of the present mode; 0 is uncapped.
+    pub max_fps: u32,
     pub simply_love_color: i32,
     pub global_offset_seconds: f32,
     pub fastload: bool,
     pub cachesongs: bool,
+    pub profile_backups: usize,
+    pub banner_fit: BannerFit,
+    pub background_blur: f32,
+    pub background_normalize: bool,
+    /// Event lockdown: players go straight from song select to gameplay with the
+    /// fixed modifier set, and Evaluation is watermarked.
+    pub tournament_mode: bool,
+    /// Show #TITLETRANSLIT/#ARTISTTRANSLIT instead of the native text when a simfile has them.
+    pub prefer_translit: bool,
+    /// Whether held hold/roll bodies add to the combo (ITG: no, DDR: per beat).
+    pub hold_combo: HoldComboMode,
+    /// One result per hold (ITG) or Pump-style checkpoints along the body.
+    pub hold_judging: HoldJudging,
+    /// Pad layout the wheel lists charts for. Read at startup.
+    pub game_type: GameType,
+    /// Whether emptying the life meter ends the song (Immediate) or just marks
+    /// it failed and plays on.
+    pub fail_type: FailType,
+    /// How score percents are settled to 0.01%: ITG truncates, so 99.996% shows
+    /// as 99.99% everywhere rather than 100.00%.
+    pub score_rounding: ScoreRounding,
+    /// Tournament rulesets: remove the Decent and/or WayOff windows, so those
+    /// steps count as misses instead.
+    pub disable_decents: bool,
+    pub disable_way_offs: bool,
+    /// Public-install helper for first-time players: foot hints, forgiving
+    /// WayOffs, and a scroll speed cap.
+    pub beginner_assist: bool,
+    /// Drop costly gameplay visuals (glow, mine layers, background) for the
+    /// rest of the song when frames keep running long.
+    pub auto_quality: bool,
+    /// "Ready?" and a 3-2-1-GO countdown to the first step; off starts instantly.
+    pub song_countdown: bool,
+    /// Open a second window showing the now-playing song, for a stream layout
+    /// or a cabinet's marquee display.
+    pub marquee_window: bool,
+    /// Borderless, always-on-top window with a see-through background, so the
+    /// notefield can be played over other programs. Read at startup.
+    pub overlay_window: bool,
+    /// Apply each song's ReplayGain/R128 correction to previews and gameplay music.
+    pub normalize_music_volume: bool,
+    /// What the assist tick clicks on in gameplay; F1 there cycles it.
+    pub assist_tick: AssistTick,
+    /// Assist tick volume, 0-100, separate from the music.
+    pub assist_tick_volume: u8,
+    /// Where "Now Playing" text goes for an LED marquee board.
+    pub led_marquee: LedMarqueeOutput,
+    /// UDP port the marquee text is broadcast on when `led_marquee` is Network.
+    pub led_marquee_udp_port: u16,
+    /// Localhost TCP port gameplay events are streamed on for external tools; 0 is off.
+    pub event_server_port: u16,
+    /// Look for a newer release on GitHub at startup. Cabinets may want this off.
+    pub check_for_updates: bool,
+    /// Name GPU passes after the screen and each top-level actor, so frames
+    /// captured in RenderDoc or Nsight can be navigated. Costs a little CPU per draw.
+    pub gpu_debug_labels: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             vsync: false,
-            windowed: true,
+            display_mode: DisplayMode::Windowed,
             show_stats: false,
             display_width: 1600,
             display_height: 900,
-            video_renderer: BackendType::OpenGL,
+            display_monitor: 0,
+            refresh_rate: 0,
+            video_renderer: BackendType::Auto,
+            present_mode: PresentMode::Auto,
+            frames_in_flight: 3,
+            msaa_samples: 1,
+            srgb_color: true,
+            render_scale: 100,
+            upscale_filter: UpscaleFilter::Linear,
+            hdr_output: HdrOutput::Off,
+            hdr_paper_white: DEFAULT_HDR_PAPER_WHITE,
+            max_fps: 0,
             simply_love_color: 2, // Corresponds to DEFAULT_COLOR_INDEX
             global_offset_seconds: -0.008,
             fastload: true,
             cachesongs: true,
+            profile_backups: 5,
+            banner_fit: BannerFit::default(),
+            background_blur: 0.0,
+            background_normalize: false,
+            tournament_mode: false,
+            prefer_translit: false,
+            hold_combo: HoldComboMode::default(),
+            hold_judging: HoldJudging::default(),
+            game_type: GameType::default(),
+            fail_type: FailType::default(),
+            score_rounding: ScoreRounding::default(),
+            disable_decents: false,
+            disable_way_offs: false,
+            beginner_assist: false,
+            auto_quality: true,
+            song_countdown: true,
+            marquee_window: false,
+            overlay_window: false,
+            normalize_music_volume: true,
+            assist_tick: AssistTick::Off,
+            assist_tick_volume: 70,
+            led_marquee: LedMarqueeOutput::Off,
+            led_marquee_udp_port: 7000,
+            event_server_port: 0,
+            check_for_updates: true,
+            gpu_debug_labels: false,
         }
     }
 }
@@ -41,6 +164,14 @@ impl Default for Config {
 // Global, mutable configuration instance.
 static CONFIG: Lazy<Mutex<Config>> = Lazy::new(|| Mutex::new(Config::default()));
 
+// The renderer actually running this session. Not saved: with `Auto` in the
+// file, the choice is made again on every launch.
+static ACTIVE_VIDEO_RENDERER: Lazy<Mutex<Option<BackendType>>> = Lazy::new(|| Mutex::new(None));
+
+// Set by `apply_safe_mode`: the settings as loaded, and as safe mode left them.
+// `save` writes the loaded value back for any override the player hasn't changed.
+static SAFE_MODE_OVERRIDES: Lazy<Mutex<Option<(Config, Config)>>> = Lazy::new(|| Mutex::new(None));
+
 
 // --- File I/O ---
 
@@ -50,15 +181,52 @@ fn create_default_config_file() -> Result<(), std::io::Error> {
     let default = Config::default();
 
     conf.set("Options", "Vsync", Some((if default.vsync { "1" } else { "0" }).to_string()));
-    conf.set("Options", "Windowed", Some((if default.windowed { "1" } else { "0" }).to_string()));
+    conf.set("Options", "DisplayMode", Some(default.display_mode.to_string()));
     conf.set("Options", "ShowStats", Some((if default.show_stats { "1" } else { "0" }).to_string()));
     conf.set("Options", "DisplayWidth", Some(default.display_width.to_string()));
     conf.set("Options", "DisplayHeight", Some(default.display_height.to_string()));
+    conf.set("Options", "DisplayMonitor", Some(default.display_monitor.to_string()));
+    conf.set("Options", "RefreshRate", Some(default.refresh_rate.to_string()));
     conf.set("Options", "VideoRenderer", Some(default.video_renderer.to_string()));
+    conf.set("Options", "PresentMode", Some(default.present_mode.to_string()));
+    conf.set("Options", "FramesInFlight", Some(default.frames_in_flight.to_string()));
+    conf.set("Options", "MsaaSamples", Some(default.msaa_samples.to_string()));
+    conf.set("Options", "SrgbColor", Some((if default.srgb_color { "1" } else { "0" }).to_string()));
+    conf.set("Options", "RenderScale", Some(default.render_scale.to_string()));
+    conf.set("Options", "UpscaleFilter", Some(default.upscale_filter.to_string()));
+    conf.set("Options", "HdrOutput", Some(default.hdr_output.to_string()));
+    conf.set("Options", "HdrPaperWhite", Some(default.hdr_paper_white.to_string()));
+    conf.set("Options", "MaxFps", Some(default.max_fps.to_string()));
     conf.set("Options", "GlobalOffsetSeconds", Some(default.global_offset_seconds.to_string()));
     conf.set("Options", "FastLoad", Some((if default.fastload { "1" } else { "0" }).to_string()));
     conf.set("Options", "CacheSongs", Some((if default.cachesongs { "1" } else { "0" }).to_string()));
+    conf.set("Options", "ProfileBackups", Some(default.profile_backups.to_string()));
+    conf.set("Options", "BackgroundBlur", Some(default.background_blur.to_string()));
+    conf.set("Options", "BackgroundNormalize", Some((if default.background_normalize { "1" } else { "0" }).to_string()));
+    conf.set("Options", "TournamentMode", Some((if default.tournament_mode { "1" } else { "0" }).to_string()));
+    conf.set("Options", "HoldCombo", Some(default.hold_combo.to_string()));
+    conf.set("Options", "HoldJudging", Some(default.hold_judging.to_string()));
+    conf.set("Options", "GameType", Some(default.game_type.to_string()));
+    conf.set("Options", "FailType", Some(default.fail_type.to_string()));
+    conf.set("Options", "ScoreRounding", Some(default.score_rounding.to_string()));
+    conf.set("Options", "DisableDecents", Some((if default.disable_decents { "1" } else { "0" }).to_string()));
+    conf.set("Options", "DisableWayOffs", Some((if default.disable_way_offs { "1" } else { "0" }).to_string()));
+    conf.set("Options", "BeginnerAssist", Some((if default.beginner_assist { "1" } else { "0" }).to_string()));
+    conf.set("Options", "AutoQuality", Some((if default.auto_quality { "1" } else { "0" }).to_string()));
+    conf.set("Options", "SongCountdown", Some((if default.song_countdown { "1" } else { "0" }).to_string()));
+    conf.set("Options", "MarqueeWindow", Some((if default.marquee_window { "1" } else { "0" }).to_string()));
+    conf.set("Options", "OverlayWindow", Some((if default.overlay_window { "1" } else { "0" }).to_string()));
+    conf.set("Options", "NormalizeMusicVolume", Some((if default.normalize_music_volume { "1" } else { "0" }).to_string()));
+    conf.set("Options", "AssistTick", Some(default.assist_tick.to_string()));
+    conf.set("Options", "AssistTickVolume", Some(default.assist_tick_volume.to_string()));
+    conf.set("Options", "LedMarquee", Some(default.led_marquee.to_string()));
+    conf.set("Options", "LedMarqueeUdpPort", Some(default.led_marquee_udp_port.to_string()));
+    conf.set("Options", "EventServerPort", Some(default.event_server_port.to_string()));
+    conf.set("Options", "CheckForUpdates", Some((if default.check_for_updates { "1" } else { "0" }).to_string()));
+    conf.set("Options", "GpuDebugLabels", Some((if default.gpu_debug_labels { "1" } else { "0" }).to_string()));
     conf.set("Theme", "SimplyLoveColor", Some(default.simply_love_color.to_string()));
+    conf.set("Theme", "BannerFit", Some(default.banner_fit.to_string()));
+    conf.set("Theme", "PreferTranslit", Some((if default.prefer_translit { "1" } else { "0" }).to_string()));
 
     conf.write(CONFIG_PATH)
 }
@@ -81,17 +249,90 @@ pub fn load() {
                 let default = Config::default();
                 
                 cfg.vsync = conf.get("Options", "Vsync").and_then(|v| v.parse::<u8>().ok()).map_or(default.vsync, |v| v != 0);
-                cfg.windowed = conf.get("Options", "Windowed").and_then(|v| v.parse::<u8>().ok()).map_or(default.windowed, |v| v != 0);
+                // Older configs only have `Windowed`; fullscreen there meant exclusive.
+                let legacy_mode = conf.get("Options", "Windowed").and_then(|v| v.parse::<u8>().ok())
+                    .map(|v| if v != 0 { DisplayMode::Windowed } else { DisplayMode::Exclusive });
+                cfg.display_mode = conf.get("Options", "DisplayMode")
+                    .and_then(|s| DisplayMode::from_str(&s).ok())
+                    .or(legacy_mode)
+                    .unwrap_or(default.display_mode);
                 cfg.show_stats = conf.get("Options", "ShowStats").and_then(|v| v.parse::<u8>().ok()).map_or(default.show_stats, |v| v != 0);
                 cfg.display_width = conf.get("Options", "DisplayWidth").and_then(|v| v.parse().ok()).unwrap_or(default.display_width);
                 cfg.display_height = conf.get("Options", "DisplayHeight").and_then(|v| v.parse().ok()).unwrap_or(default.display_height);
+                cfg.display_monitor = conf.get("Options", "DisplayMonitor").and_then(|v| v.parse().ok()).unwrap_or(default.display_monitor);
+                cfg.refresh_rate = conf.get("Options", "RefreshRate").and_then(|v| v.parse().ok()).unwrap_or(default.refresh_rate);
                 cfg.video_renderer = conf.get("Options", "VideoRenderer")
                     .and_then(|s| BackendType::from_str(&s).ok())
                     .unwrap_or(default.video_renderer);
+                cfg.present_mode = conf.get("Options", "PresentMode")
+                    .and_then(|s| PresentMode::from_str(&s).ok())
+                    .unwrap_or(default.present_mode);
+                cfg.frames_in_flight = conf.get("Options", "FramesInFlight").and_then(|v| v.parse::<u8>().ok())
+                    .map_or(default.frames_in_flight, |v| v.clamp(1, MAX_FRAMES_IN_FLIGHT as u8));
+                cfg.msaa_samples = conf.get("Options", "MsaaSamples").and_then(|v| v.parse::<u32>().ok())
+                    .filter(|v| MSAA_SAMPLE_CHOICES.contains(v))
+                    .unwrap_or(default.msaa_samples);
+                cfg.srgb_color = conf.get("Options", "SrgbColor").and_then(|v| v.parse::<u8>().ok()).map_or(default.srgb_color, |v| v != 0);
+                cfg.render_scale = conf.get("Options", "RenderScale").and_then(|v| v.parse::<u32>().ok())
+                    .filter(|v| RENDER_SCALE_CHOICES.contains(v))
+                    .unwrap_or(default.render_scale);
+                cfg.upscale_filter = conf.get("Options", "UpscaleFilter")
+                    .and_then(|s| UpscaleFilter::from_str(&s).ok())
+                    .unwrap_or(default.upscale_filter);
+                cfg.hdr_output = conf.get("Options", "HdrOutput")
+                    .and_then(|s| HdrOutput::from_str(&s).ok())
+                    .unwrap_or(default.hdr_output);
+                cfg.hdr_paper_white = conf.get("Options", "HdrPaperWhite").and_then(|v| v.parse::<u32>().ok())
+                    .map_or(default.hdr_paper_white, |v| v.clamp(80, 1000));
+                cfg.max_fps = conf.get("Options", "MaxFps").and_then(|v| v.parse::<u32>().ok())
+                    .unwrap_or(default.max_fps);
                 cfg.global_offset_seconds = conf.get("Options", "GlobalOffsetSeconds").and_then(|v| v.parse().ok()).unwrap_or(default.global_offset_seconds);
                 cfg.fastload = conf.get("Options", "FastLoad").and_then(|v| v.parse::<u8>().ok()).map_or(default.fastload, |v| v != 0);
                 cfg.cachesongs = conf.get("Options", "CacheSongs").and_then(|v| v.parse::<u8>().ok()).map_or(default.cachesongs, |v| v != 0);
+                cfg.profile_backups = conf.get("Options", "ProfileBackups").and_then(|v| v.parse().ok()).unwrap_or(default.profile_backups);
+                cfg.background_blur = conf.get("Options", "BackgroundBlur").and_then(|v| v.parse::<f32>().ok()).map_or(default.background_blur, |v| v.max(0.0));
+                cfg.background_normalize = conf.get("Options", "BackgroundNormalize").and_then(|v| v.parse::<u8>().ok()).map_or(default.background_normalize, |v| v != 0);
+                cfg.tournament_mode = conf.get("Options", "TournamentMode").and_then(|v| v.parse::<u8>().ok()).map_or(default.tournament_mode, |v| v != 0);
+                cfg.hold_combo = conf.get("Options", "HoldCombo")
+                    .and_then(|s| HoldComboMode::from_str(&s).ok())
+                    .unwrap_or(default.hold_combo);
+                cfg.hold_judging = conf.get("Options", "HoldJudging")
+                    .and_then(|s| HoldJudging::from_str(&s).ok())
+                    .unwrap_or(default.hold_judging);
+                cfg.game_type = conf.get("Options", "GameType")
+                    .and_then(|s| GameType::from_str(&s).ok())
+                    .unwrap_or(default.game_type);
+                cfg.fail_type = conf.get("Options", "FailType")
+                    .and_then(|s| FailType::from_str(&s).ok())
+                    .unwrap_or(default.fail_type);
+                cfg.score_rounding = conf.get("Options", "ScoreRounding")
+                    .and_then(|s| ScoreRounding::from_str(&s).ok())
+                    .unwrap_or(default.score_rounding);
+                cfg.disable_decents = conf.get("Options", "DisableDecents").and_then(|v| v.parse::<u8>().ok()).map_or(default.disable_decents, |v| v != 0);
+                cfg.disable_way_offs = conf.get("Options", "DisableWayOffs").and_then(|v| v.parse::<u8>().ok()).map_or(default.disable_way_offs, |v| v != 0);
+                cfg.beginner_assist = conf.get("Options", "BeginnerAssist").and_then(|v| v.parse::<u8>().ok()).map_or(default.beginner_assist, |v| v != 0);
+                cfg.auto_quality = conf.get("Options", "AutoQuality").and_then(|v| v.parse::<u8>().ok()).map_or(default.auto_quality, |v| v != 0);
+                cfg.song_countdown = conf.get("Options", "SongCountdown").and_then(|v| v.parse::<u8>().ok()).map_or(default.song_countdown, |v| v != 0);
+                cfg.marquee_window = conf.get("Options", "MarqueeWindow").and_then(|v| v.parse::<u8>().ok()).map_or(default.marquee_window, |v| v != 0);
+                cfg.overlay_window = conf.get("Options", "OverlayWindow").and_then(|v| v.parse::<u8>().ok()).map_or(default.overlay_window, |v| v != 0);
+                cfg.normalize_music_volume = conf.get("Options", "NormalizeMusicVolume").and_then(|v| v.parse::<u8>().ok()).map_or(default.normalize_music_volume, |v| v != 0);
+                cfg.assist_tick = conf.get("Options", "AssistTick")
+                    .and_then(|s| AssistTick::from_str(&s).ok())
+                    .unwrap_or(default.assist_tick);
+                cfg.assist_tick_volume = conf.get("Options", "AssistTickVolume").and_then(|v| v.parse::<u8>().ok())
+                    .map_or(default.assist_tick_volume, |v| v.min(100));
+                cfg.led_marquee = conf.get("Options", "LedMarquee")
+                    .and_then(|s| LedMarqueeOutput::from_str(&s).ok())
+                    .unwrap_or(default.led_marquee);
+                cfg.led_marquee_udp_port = conf.get("Options", "LedMarqueeUdpPort").and_then(|v| v.parse().ok()).unwrap_or(default.led_marquee_udp_port);
+                cfg.event_server_port = conf.get("Options", "EventServerPort").and_then(|v| v.parse().ok()).unwrap_or(default.event_server_port);
+                cfg.check_for_updates = conf.get("Options", "CheckForUpdates").and_then(|v| v.parse::<u8>().ok()).map_or(default.check_for_updates, |v| v != 0);
+                cfg.gpu_debug_labels = conf.get("Options", "GpuDebugLabels").and_then(|v| v.parse::<u8>().ok()).map_or(default.gpu_debug_labels, |v| v != 0);
                 cfg.simply_love_color = conf.get("Theme", "SimplyLoveColor").and_then(|v| v.parse().ok()).unwrap_or(default.simply_love_color);
+                cfg.banner_fit = conf.get("Theme", "BannerFit")
+                    .and_then(|s| BannerFit::from_str(&s).ok())
+                    .unwrap_or(default.banner_fit);
+                cfg.prefer_translit = conf.get("Theme", "PreferTranslit").and_then(|v| v.parse::<u8>().ok()).map_or(default.prefer_translit, |v| v != 0);
                 
                 info!("Configuration loaded from '{}'.", CONFIG_PATH);
             } // Lock on CONFIG is released here.
@@ -107,20 +348,75 @@ pub fn load() {
     }
 }
 
+/// `cfg` as it belongs on disk: safe-mode stand-ins still in place are swapped
+/// back for the values they replaced.
+fn without_safe_mode(mut cfg: Config) -> Config {
+    let Some((loaded, safe)) = *SAFE_MODE_OVERRIDES.lock().unwrap() else {
+        return cfg;
+    };
+    macro_rules! restore {
+        ($($field:ident),*) => {
+            $(if cfg.$field == safe.$field { cfg.$field = loaded.$field; })*
+        };
+    }
+    restore!(
+        video_renderer, display_mode, display_width, display_height, msaa_samples,
+        render_scale, hdr_output, overlay_window, marquee_window, simply_love_color
+    );
+    cfg
+}
+
 fn save() {
-    let cfg = CONFIG.lock().unwrap();
+    let cfg = without_safe_mode(*CONFIG.lock().unwrap());
     let mut conf = Ini::new();
 
     conf.set("Options", "Vsync", Some((if cfg.vsync { "1" } else { "0" }).to_string()));
-    conf.set("Options", "Windowed", Some((if cfg.windowed { "1" } else { "0" }).to_string()));
+    conf.set("Options", "DisplayMode", Some(cfg.display_mode.to_string()));
     conf.set("Options", "ShowStats", Some((if cfg.show_stats { "1" } else { "0" }).to_string()));
     conf.set("Options", "DisplayWidth", Some(cfg.display_width.to_string()));
     conf.set("Options", "DisplayHeight", Some(cfg.display_height.to_string()));
+    conf.set("Options", "DisplayMonitor", Some(cfg.display_monitor.to_string()));
+    conf.set("Options", "RefreshRate", Some(cfg.refresh_rate.to_string()));
     conf.set("Options", "VideoRenderer", Some(cfg.video_renderer.to_string()));
+    conf.set("Options", "PresentMode", Some(cfg.present_mode.to_string()));
+    conf.set("Options", "FramesInFlight", Some(cfg.frames_in_flight.to_string()));
+    conf.set("Options", "MsaaSamples", Some(cfg.msaa_samples.to_string()));
+    conf.set("Options", "SrgbColor", Some((if cfg.srgb_color { "1" } else { "0" }).to_string()));
+    conf.set("Options", "RenderScale", Some(cfg.render_scale.to_string()));
+    conf.set("Options", "UpscaleFilter", Some(cfg.upscale_filter.to_string()));
+    conf.set("Options", "HdrOutput", Some(cfg.hdr_output.to_string()));
+    conf.set("Options", "HdrPaperWhite", Some(cfg.hdr_paper_white.to_string()));
+    conf.set("Options", "MaxFps", Some(cfg.max_fps.to_string()));
     conf.set("Options", "GlobalOffsetSeconds", Some(cfg.global_offset_seconds.to_string()));
     conf.set("Options", "FastLoad", Some((if cfg.fastload { "1" } else { "0" }).to_string()));
     conf.set("Options", "CacheSongs", Some((if cfg.cachesongs { "1" } else { "0" }).to_string()));
+    conf.set("Options", "ProfileBackups", Some(cfg.profile_backups.to_string()));
+    conf.set("Options", "BackgroundBlur", Some(cfg.background_blur.to_string()));
+    conf.set("Options", "BackgroundNormalize", Some((if cfg.background_normalize { "1" } else { "0" }).to_string()));
+    conf.set("Options", "TournamentMode", Some((if cfg.tournament_mode { "1" } else { "0" }).to_string()));
+    conf.set("Options", "HoldCombo", Some(cfg.hold_combo.to_string()));
+    conf.set("Options", "HoldJudging", Some(cfg.hold_judging.to_string()));
+    conf.set("Options", "GameType", Some(cfg.game_type.to_string()));
+    conf.set("Options", "FailType", Some(cfg.fail_type.to_string()));
+    conf.set("Options", "ScoreRounding", Some(cfg.score_rounding.to_string()));
+    conf.set("Options", "DisableDecents", Some((if cfg.disable_decents { "1" } else { "0" }).to_string()));
+    conf.set("Options", "DisableWayOffs", Some((if cfg.disable_way_offs { "1" } else { "0" }).to_string()));
+    conf.set("Options", "BeginnerAssist", Some((if cfg.beginner_assist { "1" } else { "0" }).to_string()));
+    conf.set("Options", "AutoQuality", Some((if cfg.auto_quality { "1" } else { "0" }).to_string()));
+    conf.set("Options", "SongCountdown", Some((if cfg.song_countdown { "1" } else { "0" }).to_string()));
+    conf.set("Options", "MarqueeWindow", Some((if cfg.marquee_window { "1" } else { "0" }).to_string()));
+    conf.set("Options", "OverlayWindow", Some((if cfg.overlay_window { "1" } else { "0" }).to_string()));
+    conf.set("Options", "NormalizeMusicVolume", Some((if cfg.normalize_music_volume { "1" } else { "0" }).to_string()));
+    conf.set("Options", "AssistTick", Some(cfg.assist_tick.to_string()));
+    conf.set("Options", "AssistTickVolume", Some(cfg.assist_tick_volume.to_string()));
+    conf.set("Options", "LedMarquee", Some(cfg.led_marquee.to_string()));
+    conf.set("Options", "LedMarqueeUdpPort", Some(cfg.led_marquee_udp_port.to_string()));
+    conf.set("Options", "EventServerPort", Some(cfg.event_server_port.to_string()));
+    conf.set("Options", "CheckForUpdates", Some((if cfg.check_for_updates { "1" } else { "0" }).to_string()));
+    conf.set("Options", "GpuDebugLabels", Some((if cfg.gpu_debug_labels { "1" } else { "0" }).to_string()));
     conf.set("Theme", "SimplyLoveColor", Some(cfg.simply_love_color.to_string()));
+    conf.set("Theme", "BannerFit", Some(cfg.banner_fit.to_string()));
+    conf.set("Theme", "PreferTranslit", Some((if cfg.prefer_translit { "1" } else { "0" }).to_string()));
     
     if let Err(e) = conf.write(CONFIG_PATH) {
         warn!("Failed to save config file: {}", e);
@@ -131,6 +427,25 @@ pub fn get() -> Config {
     *CONFIG.lock().unwrap()
 }
 
+/// Swaps in the settings least likely to crash (`core::safe_mode`) for this
+/// session only; saving other options keeps the stored values.
+pub fn apply_safe_mode() {
+    let default = Config::default();
+    let mut cfg = CONFIG.lock().unwrap();
+    let loaded = *cfg;
+    cfg.video_renderer = BackendType::OpenGL;
+    cfg.display_mode = DisplayMode::Windowed;
+    cfg.display_width = default.display_width;
+    cfg.display_height = default.display_height;
+    cfg.msaa_samples = 1;
+    cfg.render_scale = 100;
+    cfg.hdr_output = HdrOutput::Off;
+    cfg.overlay_window = false;
+    cfg.marquee_window = false;
+    cfg.simply_love_color = default.simply_love_color;
+    *SAFE_MODE_OVERRIDES.lock().unwrap() = Some((loaded, *cfg));
+}
+
 pub fn update_simply_love_color(index: i32) {
     {
         let mut cfg = CONFIG.lock().unwrap();
@@ -141,7 +456,170 @@ pub fn update_simply_love_color(index: i32) {
     save();
 }
 
-#[allow(dead_code)]
+pub fn update_video_renderer(renderer: BackendType) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.video_renderer == renderer { return; }
+        cfg.video_renderer = renderer;
+    }
+    save();
+}
+
+pub fn update_present_mode(mode: PresentMode) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.present_mode == mode { return; }
+        cfg.present_mode = mode;
+    }
+    save();
+}
+
+pub fn update_frames_in_flight(frames: u8) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        let frames = frames.clamp(1, MAX_FRAMES_IN_FLIGHT as u8);
+        if cfg.frames_in_flight == frames { return; }
+        cfg.frames_in_flight = frames;
+    }
+    save();
+}
+
+pub fn update_max_fps(max_fps: u32) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.max_fps == max_fps { return; }
+        cfg.max_fps = max_fps;
+    }
+    save();
+}
+
+pub fn update_msaa_samples(samples: u32) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.msaa_samples == samples || !MSAA_SAMPLE_CHOICES.contains(&samples) { return; }
+        cfg.msaa_samples = samples;
+    }
+    save();
+}
+
+pub fn update_display_mode(mode: DisplayMode) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.display_mode == mode { return; }
+        cfg.display_mode = mode;
+    }
+    save();
+}
+
+pub fn update_display_resolution(width: u32, height: u32) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if (cfg.display_width, cfg.display_height) == (width, height) || width == 0 || height == 0 { return; }
+        cfg.display_width = width;
+        cfg.display_height = height;
+    }
+    save();
+}
+
+pub fn update_display_monitor(monitor: u32) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.display_monitor == monitor { return; }
+        cfg.display_monitor = monitor;
+    }
+    save();
+}
+
+pub fn update_refresh_rate(hz: u32) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.refresh_rate == hz { return; }
+        cfg.refresh_rate = hz;
+    }
+    save();
+}
+
+pub fn update_song_countdown(enabled: bool) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.song_countdown == enabled { return; }
+        cfg.song_countdown = enabled;
+    }
+    save();
+}
+
+pub fn update_render_scale(scale: u32) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.render_scale == scale || !RENDER_SCALE_CHOICES.contains(&scale) { return; }
+        cfg.render_scale = scale;
+    }
+    save();
+}
+
+pub fn update_upscale_filter(filter: UpscaleFilter) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.upscale_filter == filter { return; }
+        cfg.upscale_filter = filter;
+    }
+    save();
+}
+
+pub fn update_hdr_output(hdr: HdrOutput) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.hdr_output == hdr { return; }
+        cfg.hdr_output = hdr;
+    }
+    save();
+}
+
+pub fn update_assist_tick(tick: AssistTick) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.assist_tick == tick { return; }
+        cfg.assist_tick = tick;
+    }
+    save();
+}
+
+pub fn update_assist_tick_volume(volume: u8) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        let volume = volume.min(100);
+        if cfg.assist_tick_volume == volume { return; }
+        cfg.assist_tick_volume = volume;
+    }
+    save();
+}
+
+pub fn update_led_marquee(output: LedMarqueeOutput) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.led_marquee == output { return; }
+        cfg.led_marquee = output;
+    }
+    save();
+}
+
+pub fn set_active_video_renderer(renderer: BackendType) {
+    *ACTIVE_VIDEO_RENDERER.lock().unwrap() = Some(renderer);
+}
+
+pub fn active_video_renderer() -> Option<BackendType> {
+    *ACTIVE_VIDEO_RENDERER.lock().unwrap()
+}
+
+pub fn update_prefer_translit(prefer: bool) {
+    {
+        let mut cfg = CONFIG.lock().unwrap();
+        if cfg.prefer_translit == prefer { return; }
+        cfg.prefer_translit = prefer;
+    }
+    save();
+}
+
 pub fn update_global_offset(offset: f32) {
     {
         let mut cfg = CONFIG.lock().unwrap();
@@ -8,6 +8,7 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
@@ -19,6 +20,10 @@ pub struct Cut {
     pub length_sec: f64,
     pub fade_in_sec: f64,
     pub fade_out_sec: f64,
+    /// Loudness correction applied to the whole track, in dB.
+    pub gain_db: f32,
+    /// Playback speed (1.0 = as recorded); the pitch moves with it.
+    pub rate: f64,
 }
 impl Default for Cut {
     fn default() -> Self {
@@ -27,6 +32,8 @@ impl Default for Cut {
             length_sec: f64::INFINITY,
             fade_in_sec: 0.0,
             fade_out_sec: 0.0,
+            gain_db: 0.0,
+            rate: 1.0,
         }
     }
 }
@@ -36,11 +43,28 @@ enum AudioCommand {
     PlaySfx(Arc<Vec<i16>>),
     PlayMusic(PathBuf, Cut, bool), // bool is for looping
     StopMusic,
+    TapeStopMusic(f64),
+    // Times into the playing music and the volume, or None to stop.
+    AssistTicks(Option<(Vec<f64>, f32)>),
+    // Times from now on the output clock and the volume, or None to stop.
+    Clicks(Option<(Vec<f64>, f32)>),
+    Shutdown(Sender<()>),
 }
 
 // Global engine (initialized once)
 static ENGINE: Lazy<AudioEngine> = Lazy::new(init_engine_and_thread);
 
+// Set by `disable`: the engine is never built and playback calls do nothing.
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+// The assist tick; the same click the A/V sync test uses.
+const ASSIST_TICK_SOUND: &str = "assets/sounds/change_value.ogg";
+// Ticks that can ring over each other; more are dropped rather than allocated for.
+const MAX_TICK_VOICES: usize = 16;
+
+// How long `shutdown` waits for the manager thread before giving up on it.
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
 struct AudioEngine {
     command_sender: Sender<AudioCommand>,
     sfx_cache: Mutex<HashMap<String, Arc<Vec<i16>>>>,
@@ -62,24 +86,23 @@ pub fn init() -> Result<(), String> {
     Ok(())
 }
 
+/// Keeps the game silent without touching an output device (safe mode).
+/// Call instead of `init`.
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+    info!("Audio disabled.");
+}
+
 /// Plays a sound effect from the given path (cached after first load).
 pub fn play_sfx(path: &str) {
-    let sound_data = {
-        let mut cache = ENGINE.sfx_cache.lock().unwrap();
-        if let Some(data) = cache.get(path) {
-            data.clone()
-        } else {
-            match load_and_resample_sfx(path) {
-                Ok(data) => {
-                    cache.insert(path.to_string(), data.clone());
-                    info!("Cached SFX: {}", path);
-                    data
-                }
-                Err(e) => {
-                    warn!("Failed to load SFX '{}': {}", path, e);
-                    return;
-                }
-            }
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let sound_data = match cached_sfx(path) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to load SFX '{}': {}", path, e);
+            return;
         }
     };
     let _ = ENGINE.command_sender.send(AudioCommand::PlaySfx(sound_data));
@@ -87,14 +110,77 @@ pub fn play_sfx(path: &str) {
 
 /// Plays a music track from a file path.
 pub fn play_music(path: PathBuf, cut: Cut, looping: bool) {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
     let _ = ENGINE.command_sender.send(AudioCommand::PlayMusic(path, cut, looping));
 }
 
 /// Stops the currently playing music track.
 pub fn stop_music() {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
     let _ = ENGINE.command_sender.send(AudioCommand::StopMusic);
 }
 
+/// Slows the music to a halt over `seconds`, pitch dropping and volume fading
+/// with it, like a tape losing power. Silent afterwards until the next
+/// `play_music` or `stop_music`.
+pub fn tape_stop_music(seconds: f64) {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = ENGINE.command_sender.send(AudioCommand::TapeStopMusic(seconds));
+}
+
+/// Ticks at each of `times` (seconds into the playing music, in file time) at
+/// `volume` (0..1). They're mixed into the music where those times land in the
+/// stream, so they stay sample-accurate whatever the frame rate. Replaces any
+/// earlier ticks; a new `play_music` or `stop_music` drops them.
+pub fn set_assist_ticks(times: Vec<f64>, volume: f32) {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = ENGINE.command_sender.send(AudioCommand::AssistTicks(Some((times, volume))));
+}
+
+/// Silences the ticks from `set_assist_ticks`.
+pub fn clear_assist_ticks() {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = ENGINE.command_sender.send(AudioCommand::AssistTicks(None));
+}
+
+/// The assist tick at each of `times` (seconds from now) at `volume` (0..1),
+/// placed to the sample on the output stream like `set_assist_ticks`, but
+/// with no music needed. Replaces any earlier clicks.
+pub fn set_clicks(times: Vec<f64>, volume: f32) {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = ENGINE.command_sender.send(AudioCommand::Clicks(Some((times, volume))));
+}
+
+/// Silences the clicks from `set_clicks`.
+pub fn clear_clicks() {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = ENGINE.command_sender.send(AudioCommand::Clicks(None));
+}
+
+/// Stops the music and closes the output stream, waiting (briefly) until the
+/// device has let go. Called once on the way out; later commands are ignored.
+pub fn shutdown() {
+    let Some(engine) = Lazy::get(&ENGINE) else { return };
+    let (done_sender, done_receiver) = channel();
+    if engine.command_sender.send(AudioCommand::Shutdown(done_sender)).is_ok() {
+        let _ = done_receiver.recv_timeout(SHUTDOWN_TIMEOUT);
+    }
+}
+
 /* ============================ Engine internals ============================ */
 
 fn init_engine_and_thread() -> AudioEngine {
@@ -127,6 +213,15 @@ fn audio_manager_thread(command_receiver: Receiver<AudioCommand>) {
     let mut music_stream: Option<MusicStream> = None;
     let music_ring = internal::ring_new(internal::RING_CAP_SAMPLES);
     let (sfx_sender, sfx_receiver) = channel::<Arc<Vec<i16>>>();
+    // Built here so the callback never allocates; None cancels.
+    let (tape_sender, tape_receiver) = channel::<Option<TapeStop>>();
+    let (ticks_sender, ticks_receiver) = channel::<Option<AssistTicks>>();
+    let (clicks_sender, clicks_receiver) = channel::<Option<AssistTicks>>();
+    // Samples written to the device so far (wrapping); the clock clicks are placed on.
+    let output_pos = Arc::new(AtomicUsize::new(0));
+    let output_pos_for_callback = output_pos.clone();
+    // Ring position the playing music starts at, and how it was cut.
+    let mut music_origin: Option<(usize, Cut)> = None;
 
     let host = cpal::default_host();
     let device = host.default_output_device().expect("no audio output device");
@@ -138,6 +233,10 @@ fn audio_manager_thread(command_receiver: Receiver<AudioCommand>) {
 
     // Reusable buffers captured by the callback to avoid allocations
     let mut mix_i16: Vec<i16> = Vec::new();
+    let mut tape_stop: Option<TapeStop> = None;
+    let mut assist_ticks: Option<AssistTicks> = None;
+    let mut clicks: Option<AssistTicks> = None;
+    let channels = stream_config.channels as usize;
     let mut active_sfx_for_callback: Vec<(Arc<Vec<i16>>, usize)> = Vec::new();
 
     // Build the output stream matching device sample format (like v1)
@@ -147,8 +246,13 @@ fn audio_manager_thread(command_receiver: Receiver<AudioCommand>) {
             move |out: &mut [i16], _| {
                 if mix_i16.len() != out.len() { mix_i16.resize(out.len(), 0); }
 
-                // Pull music samples
-                internal::callback_fill_from_ring_i16(&music_ring_for_callback, &mut mix_i16[..]);
+                // Pull music samples, with the assist ticks that fall on them
+                let music_pos = internal::ring_tail(&music_ring_for_callback);
+                fill_music(&music_ring_for_callback, &mut mix_i16[..], &tape_receiver, &mut tape_stop, channels);
+                mix_assist_ticks(&mut mix_i16[..], music_pos, &ticks_receiver, &mut assist_ticks);
+                let played = output_pos_for_callback.load(Ordering::Relaxed);
+                mix_assist_ticks(&mut mix_i16[..], played, &clicks_receiver, &mut clicks);
+                output_pos_for_callback.store(played.wrapping_add(mix_i16.len()), Ordering::Relaxed);
 
                 // Ingest any new SFX references without allocating in RT
                 for new_sfx in sfx_receiver.try_iter() {
@@ -176,7 +280,12 @@ fn audio_manager_thread(command_receiver: Receiver<AudioCommand>) {
             move |out: &mut [u16], _| {
                 if mix_i16.len() != out.len() { mix_i16.resize(out.len(), 0); }
 
-                internal::callback_fill_from_ring_i16(&music_ring_for_callback, &mut mix_i16[..]);
+                let music_pos = internal::ring_tail(&music_ring_for_callback);
+                fill_music(&music_ring_for_callback, &mut mix_i16[..], &tape_receiver, &mut tape_stop, channels);
+                mix_assist_ticks(&mut mix_i16[..], music_pos, &ticks_receiver, &mut assist_ticks);
+                let played = output_pos_for_callback.load(Ordering::Relaxed);
+                mix_assist_ticks(&mut mix_i16[..], played, &clicks_receiver, &mut clicks);
+                output_pos_for_callback.store(played.wrapping_add(mix_i16.len()), Ordering::Relaxed);
 
                 for new_sfx in sfx_receiver.try_iter() {
                     active_sfx_for_callback.push((new_sfx, 0));
@@ -203,7 +312,12 @@ fn audio_manager_thread(command_receiver: Receiver<AudioCommand>) {
             move |out: &mut [f32], _| {
                 if mix_i16.len() != out.len() { mix_i16.resize(out.len(), 0); }
 
-                internal::callback_fill_from_ring_i16(&music_ring_for_callback, &mut mix_i16[..]);
+                let music_pos = internal::ring_tail(&music_ring_for_callback);
+                fill_music(&music_ring_for_callback, &mut mix_i16[..], &tape_receiver, &mut tape_stop, channels);
+                mix_assist_ticks(&mut mix_i16[..], music_pos, &ticks_receiver, &mut assist_ticks);
+                let played = output_pos_for_callback.load(Ordering::Relaxed);
+                mix_assist_ticks(&mut mix_i16[..], played, &clicks_receiver, &mut clicks);
+                output_pos_for_callback.store(played.wrapping_add(mix_i16.len()), Ordering::Relaxed);
 
                 for new_sfx in sfx_receiver.try_iter() {
                     active_sfx_for_callback.push((new_sfx, 0));
@@ -235,25 +349,209 @@ fn audio_manager_thread(command_receiver: Receiver<AudioCommand>) {
         match command_receiver.recv() {
             Ok(AudioCommand::PlaySfx(data)) => { let _ = sfx_sender.send(data); },
             Ok(AudioCommand::PlayMusic(path, cut, looping)) => {
+                let _ = tape_sender.send(None);
+                let _ = ticks_sender.send(None);
                 if let Some(old) = music_stream.take() {
                     old.stop_signal.store(true, std::sync::atomic::Ordering::Relaxed);
                     let _ = old.thread.join();
                 }
                 internal::ring_clear(&music_ring);
+                // The decoder is stopped, so the new track's first sample lands here.
+                music_origin = Some((internal::ring_head(&music_ring), cut));
                 music_stream = Some(spawn_music_decoder_thread(path, cut, looping, music_ring.clone()));
             }
             Ok(AudioCommand::StopMusic) => {
+                let _ = tape_sender.send(None);
+                let _ = ticks_sender.send(None);
+                music_origin = None;
                 if let Some(old) = music_stream.take() {
                     old.stop_signal.store(true, std::sync::atomic::Ordering::Relaxed);
                     let _ = old.thread.join();
                 }
                 internal::ring_clear(&music_ring);
             }
+            Ok(AudioCommand::TapeStopMusic(seconds)) => {
+                let frames = secs_to_frames(seconds, ENGINE.device_sample_rate).max(1);
+                let _ = tape_sender.send(Some(TapeStop::new(frames, ENGINE.device_channels)));
+            }
+            Ok(AudioCommand::AssistTicks(None)) => { let _ = ticks_sender.send(None); },
+            Ok(AudioCommand::AssistTicks(Some((times, volume)))) => {
+                let Some((origin, cut)) = music_origin else { continue };
+                match AssistTicks::new(&times, volume, origin, &cut) {
+                    Ok(ticks) => { let _ = ticks_sender.send(Some(ticks)); },
+                    Err(e) => warn!("Failed to load assist tick '{}': {}", ASSIST_TICK_SOUND, e),
+                }
+            }
+            Ok(AudioCommand::Clicks(None)) => { let _ = clicks_sender.send(None); },
+            Ok(AudioCommand::Clicks(Some((times, volume)))) => {
+                // The callback may move past this before the clicks arrive; only the
+                // ones inside that gap are dropped.
+                let origin = output_pos.load(Ordering::Relaxed);
+                match AssistTicks::new(&times, volume, origin, &Cut::default()) {
+                    Ok(clicks) => { let _ = clicks_sender.send(Some(clicks)); },
+                    Err(e) => warn!("Failed to load assist tick '{}': {}", ASSIST_TICK_SOUND, e),
+                }
+            }
+            Ok(AudioCommand::Shutdown(done)) => {
+                if let Some(old) = music_stream.take() {
+                    old.stop_signal.store(true, std::sync::atomic::Ordering::Relaxed);
+                    let _ = old.thread.join();
+                }
+                let _ = stream.pause();
+                drop(stream);
+                info!("Audio stream closed.");
+                let _ = done.send(());
+                break;
+            }
             Err(_) => break, // main dropped; exit thread
         }
     }
 }
 
+/// Music slowing to a stop: the ring is read at a rate falling from 1 to 0
+/// (linear interpolation between frames) while the volume follows it down.
+struct TapeStop {
+    total_frames: u64,
+    done_frames: u64,
+    channels: usize,
+    // The two frames being interpolated between, popped from the ring; sized
+    // here so the callback never grows it. `filled` samples are valid.
+    window: Vec<i16>,
+    filled: usize,
+    // Position between the two frames, in [0, 1).
+    pos: f64,
+}
+
+impl TapeStop {
+    fn new(total_frames: u64, channels: usize) -> Self {
+        Self { total_frames, done_frames: 0, channels, window: vec![0; 2 * channels], filled: 0, pos: 0.0 }
+    }
+
+    fn fill(&mut self, ring: &internal::SpscRingI16, dst: &mut [i16]) {
+        let ch = self.channels;
+        for frame in dst.chunks_exact_mut(ch) {
+            let progress = (self.done_frames as f64 / self.total_frames as f64).min(1.0);
+            self.done_frames += 1;
+            if self.filled < self.window.len() {
+                self.filled += internal::ring_pop(ring, &mut self.window[self.filled..]);
+            }
+            if progress >= 1.0 || self.filled < self.window.len() {
+                frame.fill(0);
+                continue;
+            }
+            let frac = self.pos as f32;
+            let volume = (1.0 - progress) as f32;
+            for (c, out) in frame.iter_mut().enumerate() {
+                let a = self.window[c] as f32;
+                let b = self.window[ch + c] as f32;
+                *out = ((a + (b - a) * frac) * volume) as i16;
+            }
+            // The rate never exceeds 1, so at most one frame is passed per output frame.
+            self.pos += 1.0 - progress;
+            if self.pos >= 1.0 {
+                self.window.copy_within(ch.., 0);
+                self.filled -= ch;
+                self.pos -= 1.0;
+            }
+        }
+    }
+}
+
+// The callback's music source: straight from the ring, or through a tape stop.
+#[inline(always)]
+fn fill_music(
+    ring: &internal::SpscRingI16,
+    dst: &mut [i16],
+    tape_receiver: &Receiver<Option<TapeStop>>,
+    tape_stop: &mut Option<TapeStop>,
+    channels: usize,
+) {
+    for update in tape_receiver.try_iter() {
+        *tape_stop = update.filter(|t| t.channels == channels);
+    }
+    match tape_stop {
+        Some(tape) => tape.fill(ring, dst),
+        None => internal::callback_fill_from_ring_i16(ring, dst),
+    }
+}
+
+/// Ticks scheduled against the music: each one starts on the ring position
+/// where its time is played, so it's placed to the sample.
+struct AssistTicks {
+    sample: Arc<Vec<i16>>,
+    volume: f32,
+    // Ring positions (in samples, wrapping) the ticks start at, in order.
+    positions: Vec<usize>,
+    next: usize,
+    // Ringing ticks: read position in `sample` and where in the buffer they pick up.
+    voices: Vec<(usize, usize)>,
+}
+
+impl AssistTicks {
+    fn new(times: &[f64], volume: f32, origin: usize, cut: &Cut) -> Result<Self, Box<dyn std::error::Error>> {
+        let sample = cached_sfx(ASSIST_TICK_SOUND)?;
+        let rate = if cut.rate.is_finite() && cut.rate > 0.0 { cut.rate } else { 1.0 };
+        let out_hz = ENGINE.device_sample_rate as f64;
+        let channels = ENGINE.device_channels;
+        // Mirrors the decoder: silence for a negative start, then the file from
+        // `start_sec` (or 0) onward at `rate`.
+        let preroll_sec = (-cut.start_sec).max(0.0);
+        let file_start_sec = cut.start_sec.max(0.0);
+        let mut positions: Vec<usize> = times
+            .iter()
+            .filter(|&&t| t >= file_start_sec)
+            .map(|&t| {
+                let frame = ((preroll_sec + (t - file_start_sec) / rate) * out_hz).round() as usize;
+                origin.wrapping_add(frame * channels)
+            })
+            .collect();
+        positions.sort_by_key(|&p| p.wrapping_sub(origin));
+        Ok(Self { sample, volume: volume.clamp(0.0, 1.0), positions, next: 0, voices: Vec::with_capacity(MAX_TICK_VOICES) })
+    }
+
+    // Mixes the ticks into `dst`, the music read from ring position `pos`.
+    fn mix(&mut self, dst: &mut [i16], pos: usize) {
+        while let Some(&at) = self.positions.get(self.next) {
+            let offset = at.wrapping_sub(pos) as isize;
+            if offset >= dst.len() as isize {
+                break;
+            }
+            self.next += 1;
+            // Already played past (set mid-song); skipped rather than bunched up.
+            if offset >= 0 && self.voices.len() < MAX_TICK_VOICES {
+                self.voices.push((0, offset as usize));
+            }
+        }
+        let (sample, volume) = (&self.sample, self.volume);
+        self.voices.retain_mut(|(cursor, start)| {
+            let n = (sample.len() - *cursor).min(dst.len().saturating_sub(*start));
+            for i in 0..n {
+                let tick = (sample[*cursor + i] as f32 * volume) as i16;
+                dst[*start + i] = dst[*start + i].saturating_add(tick);
+            }
+            *cursor += n;
+            *start = 0;
+            *cursor < sample.len()
+        });
+    }
+}
+
+// The callback's assist ticks: picks up new schedules and mixes the current one.
+#[inline(always)]
+fn mix_assist_ticks(
+    dst: &mut [i16],
+    pos: usize,
+    ticks_receiver: &Receiver<Option<AssistTicks>>,
+    assist_ticks: &mut Option<AssistTicks>,
+) {
+    for update in ticks_receiver.try_iter() {
+        *assist_ticks = update;
+    }
+    if let Some(ticks) = assist_ticks {
+        ticks.mix(dst, pos);
+    }
+}
+
 /* ========================= Music decode + resample ========================= */
 
 /// Spawn a thread to decode & resample one music file into the ring buffer.
@@ -334,6 +632,15 @@ fn apply_fade_envelope(samples: &mut [i16], channels: usize, start_frame: u64, f
     }
 }
 
+fn apply_gain(samples: &mut [i16], gain: f32) {
+    if (gain - 1.0).abs() < 0.0001 {
+        return;
+    }
+    for s in samples.iter_mut() {
+        *s = (*s as f32 * gain).round().clamp(-32768.0, 32767.0) as i16;
+    }
+}
+
 /// The decoder loop, mirrored from v1 (seek+preroll, cut capping, flush).
 fn music_decoder_thread_loop(
     path: PathBuf, cut: Cut, looping: bool, ring: Arc<internal::SpscRingI16>, stop: Arc<std::sync::atomic::AtomicBool>
@@ -345,6 +652,7 @@ fn music_decoder_thread_loop(
 
     let out_ch = ENGINE.device_channels;
     let out_hz = ENGINE.device_sample_rate;
+    let gain = crate::core::audio_analysis::db_to_linear(cut.gain_db);
 
     // --- Handle negative start time as preroll silence ---
     if cut.start_sec < 0.0 {
@@ -361,8 +669,13 @@ fn music_decoder_thread_loop(
         }
     }
 
+    // Resampling from a claimed rate other than the real one plays the track
+    // faster or slower; positions in the file still count in real input frames.
+    let rate = if cut.rate.is_finite() && cut.rate > 0.0 { cut.rate } else { 1.0 };
+    let play_hz = ((in_hz as f64 * rate).round() as u32).max(1);
+
     'main_loop: loop {
-        let mut st = internal::poly_init(in_hz, out_hz, in_ch, out_ch, internal::BASE_TAPS, internal::BETA);
+        let mut st = internal::poly_init(play_hz, out_hz, in_ch, out_ch, internal::BASE_TAPS, internal::BETA);
 
         // --- v1-style start & pre-roll ---
         let start_frame_f = (cut.start_sec * in_hz as f64).max(0.0);
@@ -384,7 +697,7 @@ fn music_decoder_thread_loop(
         }
 
         // How many output frames to throw away to finish pre-roll?
-        let ratio = out_hz as f64 / in_hz as f64;
+        let ratio = out_hz as f64 / play_hz as f64;
         let mut preroll_out_frames: u64 =
             if seek_ok && start_floor > 0 {
                 (internal::PREROLL_IN_FRAMES as f64 * ratio).ceil() as u64
@@ -475,6 +788,7 @@ fn music_decoder_thread_loop(
             let finished = cap_out_frames(&mut out_tmp, out_ch, &mut frames_left_out);
 
             if !out_tmp.is_empty() {
+                apply_gain(&mut out_tmp, gain);
                 apply_fade_envelope(&mut out_tmp, out_ch, frames_emitted_total, fade_spec);
                 frames_emitted_total = frames_emitted_total.saturating_add((out_tmp.len() / out_ch) as u64);
             }
@@ -504,6 +818,7 @@ fn music_decoder_thread_loop(
         let _ = cap_out_frames(&mut out_tmp, out_ch, &mut frames_left_out);
 
         if !out_tmp.is_empty() {
+            apply_gain(&mut out_tmp, gain);
             apply_fade_envelope(&mut out_tmp, out_ch, frames_emitted_total, fade_spec);
         }
 
@@ -544,6 +859,18 @@ fn music_decoder_thread_loop(
     Ok(())
 }
 
+/// The sound at `path`, decoded for the device once and cached after.
+fn cached_sfx(path: &str) -> Result<Arc<Vec<i16>>, Box<dyn std::error::Error>> {
+    let mut cache = ENGINE.sfx_cache.lock().unwrap();
+    if let Some(data) = cache.get(path) {
+        return Ok(data.clone());
+    }
+    let data = load_and_resample_sfx(path)?;
+    cache.insert(path.to_string(), data.clone());
+    info!("Cached SFX: {}", path);
+    Ok(data)
+}
+
 /// Loads an Ogg file fully and resamples it to the device rate for SFX (cached).
 fn load_and_resample_sfx(path: &str) -> Result<Arc<Vec<i16>>, Box<dyn std::error::Error>> {
     let file = File::open(Path::new(path))?;
@@ -634,6 +961,12 @@ mod internal {
         r.tail.store(t.wrapping_add(n), Ordering::Release); n
     }
 
+    /// Write position: where the next pushed sample goes.
+    pub fn ring_head(r: &SpscRingI16) -> usize { r.head.load(Ordering::Acquire) }
+
+    /// Read position: where the next popped sample comes from.
+    pub fn ring_tail(r: &SpscRingI16) -> usize { r.tail.load(Ordering::Acquire) }
+
     pub fn ring_clear(r: &SpscRingI16) {
         // This is called from the manager thread when the producer (decoder) is stopped.
         // It makes the buffer appear empty to the consumer (audio callback).
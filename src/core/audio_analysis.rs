@@ -0,0 +1,220 @@
+use lewton::inside_ogg::OggStreamReader;
+use log::{info, warn};
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/* ============================== Public API ============================== */
+
+/// ReplayGain 2.0 reference level; every track is brought to this loudness.
+pub const REFERENCE_LUFS: f64 = -18.0;
+
+// Keep the correction sane for near-silent or brickwalled masters.
+const MAX_BOOST_DB: f32 = 6.0;
+const MAX_CUT_DB: f32 = 15.0;
+
+// Samples quieter than this (about -60 dBFS) count as silence when trimming the tail.
+const SILENCE_THRESHOLD: u16 = 33;
+
+/// Per-song results of scanning the music file once at cache-build time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TrackAnalysis {
+    /// Gain (dB) that brings the track to `REFERENCE_LUFS`, from the file's
+    /// REPLAYGAIN_TRACK_GAIN tag or, failing that, an EBU R128 measurement.
+    pub gain_db: Option<f32>,
+    /// Decoded length of the file, in seconds.
+    pub duration_sec: Option<f32>,
+    /// Time of the last audible sample; anything after it is trailing silence.
+    pub audible_end_sec: Option<f32>,
+}
+
+/// Scans a music file. Never fails hard: anything unreadable just yields no data.
+pub fn analyze(path: &Path) -> TrackAnalysis {
+    match analyze_ogg(path) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            warn!("Could not analyze '{}': {}", path.display(), e);
+            TrackAnalysis::default()
+        }
+    }
+}
+
+/// Decibels to a linear amplitude factor.
+#[inline(always)]
+pub fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/* ============================ Implementation ============================ */
+
+fn analyze_ogg(path: &Path) -> Result<TrackAnalysis, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let mut ogg = OggStreamReader::new(BufReader::new(file))?;
+    let channels = (ogg.ident_hdr.audio_channels as usize).max(1);
+    let rate = ogg.ident_hdr.audio_sample_rate;
+
+    // A tagged file already carries the gain, so only the length needs decoding.
+    let tagged_gain = replaygain_tag(&ogg.comment_hdr.comment_list);
+    let mut meter = tagged_gain.is_none().then(|| LoudnessMeter::new(rate, channels));
+
+    let mut total_frames: u64 = 0;
+    let mut last_audible_frame: Option<u64> = None;
+    while let Some(pkt) = ogg.read_dec_packet_itl()? {
+        if let Some(meter) = meter.as_mut() {
+            meter.push(&pkt);
+        }
+        if let Some(i) = pkt.iter().rposition(|s| s.unsigned_abs() > SILENCE_THRESHOLD) {
+            last_audible_frame = Some(total_frames + (i / channels) as u64);
+        }
+        total_frames += (pkt.len() / channels) as u64;
+    }
+
+    let to_secs = |frames: u64| (rate > 0).then(|| frames as f32 / rate as f32);
+    let duration_sec = to_secs(total_frames);
+    let audible_end_sec = last_audible_frame.and_then(|f| to_secs(f + 1));
+
+    let gain_db = match (tagged_gain, meter) {
+        (Some(gain), _) => {
+            info!("Using REPLAYGAIN_TRACK_GAIN {:+.2} dB for '{}'", gain, path.display());
+            Some(clamp_gain(gain))
+        }
+        (None, Some(meter)) => meter.integrated_lufs().map(|lufs| {
+            let gain = (REFERENCE_LUFS - lufs) as f32;
+            info!("Measured {:.1} LUFS ({:+.2} dB) for '{}'", lufs, gain, path.display());
+            clamp_gain(gain)
+        }),
+        (None, None) => None,
+    };
+    Ok(TrackAnalysis { gain_db, duration_sec, audible_end_sec })
+}
+
+fn clamp_gain(db: f32) -> f32 {
+    db.clamp(-MAX_CUT_DB, MAX_BOOST_DB)
+}
+
+/// Parses "REPLAYGAIN_TRACK_GAIN=-6.54 dB" from the Vorbis comments.
+fn replaygain_tag(comments: &[(String, String)]) -> Option<f32> {
+    comments
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("REPLAYGAIN_TRACK_GAIN"))
+        .and_then(|(_, value)| {
+            let number = value.trim().trim_end_matches(|c: char| c.is_alphabetic()).trim();
+            number.parse::<f32>().ok()
+        })
+        .filter(|g| g.is_finite())
+}
+
+/// Direct-form I biquad, run per channel.
+#[derive(Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self { b, a, x: [0.0; 2], y: [0.0; 2] }
+    }
+
+    #[inline(always)]
+    fn process(&mut self, input: f64) -> f64 {
+        let out = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[1] * self.y[0]
+            - self.a[2] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [out, self.y[0]];
+        out
+    }
+}
+
+/// BS.1770 K-weighting (high shelf + high pass), with coefficients derived for
+/// any sample rate the same way libebur128 does.
+fn k_weighting(rate: u32) -> (Biquad, Biquad) {
+    let rate = rate as f64;
+
+    let f0 = 1681.974450955533;
+    let gain = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    (shelf, high_pass)
+}
+
+/// EBU R128 integrated loudness: 400 ms blocks with 75% overlap, an absolute
+/// gate at -70 LUFS and a relative gate 10 LU under the ungated mean.
+struct LoudnessMeter {
+    channels: usize,
+    filters: Vec<(Biquad, Biquad)>,
+    /// Samples per 100 ms step (per channel).
+    step_len: usize,
+    step_pos: usize,
+    step_energy: f64,
+    /// Mean-square energy of each finished 100 ms step.
+    steps: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    fn new(rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        Self {
+            channels,
+            filters: vec![k_weighting(rate); channels],
+            step_len: (rate as usize / 10).max(1),
+            step_pos: 0,
+            step_energy: 0.0,
+            steps: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, interleaved: &[i16]) {
+        for frame in interleaved.chunks_exact(self.channels) {
+            for (c, sample) in frame.iter().enumerate() {
+                let (shelf, high_pass) = &mut self.filters[c];
+                let weighted = high_pass.process(shelf.process(*sample as f64 / 32768.0));
+                self.step_energy += weighted * weighted;
+            }
+            self.step_pos += 1;
+            if self.step_pos == self.step_len {
+                self.steps.push(self.step_energy / self.step_len as f64);
+                self.step_pos = 0;
+                self.step_energy = 0.0;
+            }
+        }
+    }
+
+    fn integrated_lufs(&self) -> Option<f64> {
+        let blocks: Vec<f64> = self.steps.windows(4).map(|w| w.iter().sum::<f64>() / 4.0).collect();
+        let loudness = |energy: f64| -0.691 + 10.0 * energy.log10();
+
+        let absolute: Vec<f64> = blocks.into_iter().filter(|&e| e > 0.0 && loudness(e) > -70.0).collect();
+        if absolute.is_empty() {
+            return None;
+        }
+        let relative_gate = loudness(absolute.iter().sum::<f64>() / absolute.len() as f64) - 10.0;
+
+        let gated: Vec<f64> = absolute.into_iter().filter(|&e| loudness(e) > relative_gate).collect();
+        if gated.is_empty() {
+            return None;
+        }
+        Some(loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+    }
+}
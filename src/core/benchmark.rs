@@ -0,0 +1,283 @@
+//! `--benchmark`: autoplay through a fixed, dense synthetic chart for
+//! `DURATION_SECONDS`, then report frame times, draw counts and CPU time per
+//! frame stage, so runs compare across backends and machines.
+//! - The chart is built here (no simfile, no music) and is the same every run
+//! - The first `WARMUP_SECONDS` (texture uploads, pipeline builds) aren't counted
+//! - The report goes to stdout, the log and `cache/benchmarks`
+
+use crate::config;
+use crate::core::gfx::FrameStats;
+use crate::game::chart::ChartData;
+use crate::game::song::SongData;
+use chrono::Local;
+use log::info;
+use rssp::stats::ArrowStats;
+use rssp::TechCounts;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const DURATION_SECONDS: f32 = 60.0;
+const WARMUP_SECONDS: f32 = 2.0;
+const REPORT_DIR: &str = "cache/benchmarks";
+
+const BPM: f32 = 180.0;
+// Comfortably longer than the run at `BPM`, preroll included.
+const MEASURES: usize = 56;
+
+// One measure of 16ths each; the chart cycles through them in order.
+const STREAM: [&str; 16] = [
+    "1000", "0100", "0010", "0001", "0010", "0100", "1000", "0001",
+    "1000", "0100", "0010", "0001", "0010", "0100", "1000", "0001",
+];
+const JUMPS: [&str; 16] = [
+    "1001", "0100", "0010", "0100", "0110", "1000", "0001", "1000",
+    "1001", "0100", "0010", "0100", "0110", "1000", "0001", "1000",
+];
+// A hold on the left, then a roll on the right, streamed around.
+const FREEZES: [&str; 16] = [
+    "2100", "0010", "0100", "0010", "0100", "0010", "0100", "0010",
+    "3104", "1000", "0100", "0010", "0100", "1000", "0010", "0003",
+];
+// Mines sit at least four rows ahead of the next step in their column.
+const MINES: [&str; 16] = [
+    "1M00", "0001", "0010", "000M", "0100", "1000", "0010", "0001",
+    "1M00", "0001", "0010", "000M", "0100", "1000", "0010", "0001",
+];
+
+// Counted from the rows, since the chart never goes through the parser.
+fn arrow_stats(notes: &str) -> ArrowStats {
+    let mut columns = [0u32; 4];
+    let (mut steps, mut jumps, mut mines, mut holds, mut rolls) = (0, 0, 0, 0, 0);
+    for row in notes.lines().filter(|row| row.len() == 4) {
+        let mut heads = 0;
+        for (col, c) in row.bytes().enumerate() {
+            match c {
+                b'1' | b'2' | b'4' => {
+                    heads += 1;
+                    columns[col] += 1;
+                }
+                b'M' => mines += 1,
+                _ => {}
+            }
+            match c {
+                b'2' => holds += 1,
+                b'4' => rolls += 1,
+                _ => {}
+            }
+        }
+        if heads > 0 {
+            steps += 1;
+        }
+        if heads == 2 {
+            jumps += 1;
+        }
+    }
+    ArrowStats {
+        total_arrows: columns.iter().sum(),
+        left: columns[0],
+        down: columns[1],
+        up: columns[2],
+        right: columns[3],
+        total_steps: steps,
+        jumps,
+        hands: 0,
+        mines,
+        holds,
+        rolls,
+        lifts: 0,
+        fakes: 0,
+        holding: 0,
+    }
+}
+
+/// The benchmark song and its one chart.
+pub fn song() -> (Arc<SongData>, Arc<ChartData>) {
+    let patterns = [STREAM, JUMPS, FREEZES, MINES];
+    let notes = (0..MEASURES)
+        .map(|m| patterns[m % patterns.len()].join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n,\n");
+    let bpms = format!("0.000={:.3}", BPM);
+    let nps = (BPM / 60.0 * 4.0) as f64;
+
+    let chart = ChartData {
+        chart_type: "dance-single".to_string(),
+        difficulty: "Challenge".to_string(),
+        meter: 12,
+        step_artist: "DeadSync".to_string(),
+        stats: arrow_stats(&notes),
+        notes: notes.into_bytes(),
+        short_hash: "benchmark".to_string(),
+        tech_counts: TechCounts {
+            crossovers: 0,
+            half_crossovers: 0,
+            full_crossovers: 0,
+            footswitches: 0,
+            up_footswitches: 0,
+            down_footswitches: 0,
+            sideswitches: 0,
+            jacks: 0,
+            brackets: 0,
+            doublesteps: 0,
+        },
+        total_streams: MEASURES as u32,
+        max_nps: nps,
+        detailed_breakdown: format!("{}", MEASURES),
+        partial_breakdown: format!("{}", MEASURES),
+        simple_breakdown: format!("{}", MEASURES),
+        total_measures: MEASURES,
+        measure_nps_vec: vec![nps; MEASURES],
+        chart_bpms: None,
+        chart_stops: None,
+        chart_delays: None,
+        chart_warps: None,
+        chart_speeds: None,
+        chart_scrolls: None,
+    };
+
+    let song = SongData {
+        simfile_path: PathBuf::from("benchmark"),
+        title: "Benchmark".to_string(),
+        subtitle: String::new(),
+        artist: "DeadSync".to_string(),
+        title_translit: String::new(),
+        subtitle_translit: String::new(),
+        artist_translit: String::new(),
+        banner_path: None,
+        background_path: None,
+        background_video: None,
+        background_video_beat: 0.0,
+        preview_path: None,
+        music_path: None,
+        display_bpm: format!("{}", BPM),
+        offset: 0.0,
+        sample_start: None,
+        sample_length: None,
+        min_bpm: BPM as f64,
+        max_bpm: BPM as f64,
+        normalized_bpms: bpms,
+        normalized_stops: String::new(),
+        normalized_delays: String::new(),
+        normalized_warps: String::new(),
+        normalized_speeds: String::new(),
+        normalized_scrolls: String::new(),
+        total_length_seconds: (MEASURES as f32 * 4.0 * 60.0 / BPM) as i32,
+        music_gain_db: None,
+        asset_issues: Vec::new(),
+        disambiguator: None,
+        charts: vec![chart.clone()],
+    };
+    (Arc::new(song), Arc::new(chart))
+}
+
+/// Samples gathered over a run.
+pub struct Run {
+    elapsed: f32,
+    frame_ms: Vec<f32>,
+    totals: FrameStats,
+    // Per stage, in first-seen order: summed per-second averages and samples.
+    stages: Vec<(&'static str, f32, u32)>,
+}
+
+impl Run {
+    pub fn start() -> Self {
+        info!("Benchmark started: {} s after {} s of warmup.", DURATION_SECONDS, WARMUP_SECONDS);
+        Self { elapsed: 0.0, frame_ms: Vec::new(), totals: FrameStats::default(), stages: Vec::new() }
+    }
+
+    fn measuring(&self) -> bool {
+        self.elapsed >= WARMUP_SECONDS
+    }
+
+    /// Whether the run has gone on long enough to report.
+    pub fn finished(&self) -> bool {
+        self.elapsed >= WARMUP_SECONDS + DURATION_SECONDS
+    }
+
+    /// Counts one drawn frame that took `delta_time` seconds.
+    pub fn record_frame(&mut self, delta_time: f32, stats: &FrameStats) {
+        self.elapsed += delta_time;
+        if !self.measuring() {
+            return;
+        }
+        self.frame_ms.push(delta_time * 1000.0);
+        self.totals.vertices += stats.vertices;
+        self.totals.draw_calls += stats.draw_calls;
+        self.totals.instances += stats.instances;
+        self.totals.texture_binds += stats.texture_binds;
+    }
+
+    /// Adds the profiler's per-stage averages (`profiler::stage_ms`), rolled once a second.
+    pub fn record_stages(&mut self, stages: &[(&'static str, f32)]) {
+        if !self.measuring() {
+            return;
+        }
+        for &(name, ms) in stages {
+            match self.stages.iter_mut().find(|(n, _, _)| *n == name) {
+                Some((_, total, samples)) => {
+                    *total += ms;
+                    *samples += 1;
+                }
+                None => self.stages.push((name, ms, 1)),
+            }
+        }
+    }
+
+    /// The results as text.
+    pub fn report(&self, adapter: &str) -> String {
+        let cfg = config::get();
+        let renderer = config::active_video_renderer().map_or("none".to_string(), |r| r.to_string());
+        let frames = self.frame_ms.len().max(1);
+        let seconds: f32 = self.frame_ms.iter().sum::<f32>() / 1000.0;
+        let average = self.frame_ms.iter().sum::<f32>() / frames as f32;
+        let mut sorted = self.frame_ms.clone();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let slowest = &sorted[..sorted.len().div_ceil(100).min(sorted.len())];
+        let one_percent_low = slowest.iter().sum::<f32>() / slowest.len().max(1) as f32;
+        let per_frame = |total: u32| total as f64 / frames as f64;
+
+        let mut out = String::new();
+        let _ = writeln!(out, "DeadSync {} benchmark, {}", env!("CARGO_PKG_VERSION"), Local::now().format("%Y-%m-%d %H:%M:%S"));
+        let _ = writeln!(out, "Renderer: {}, GPU: {}", renderer, adapter);
+        let _ = writeln!(
+            out,
+            "Display: {} {}x{}, present {} (vsync {}), MSAA {}x, render scale {}%, max FPS {}",
+            cfg.display_mode, cfg.display_width, cfg.display_height, cfg.present_mode,
+            if cfg.vsync { "on" } else { "off" }, cfg.msaa_samples, cfg.render_scale, cfg.max_fps,
+        );
+        let _ = writeln!(out, "Frames: {} in {:.1} s", self.frame_ms.len(), seconds);
+        let _ = writeln!(
+            out,
+            "Frame time: average {:.2} ms ({:.1} FPS), 1% low {:.2} ms ({:.1} FPS), worst {:.2} ms",
+            average, 1000.0 / average.max(f32::EPSILON),
+            one_percent_low, 1000.0 / one_percent_low.max(f32::EPSILON),
+            sorted.first().copied().unwrap_or(0.0),
+        );
+        let _ = writeln!(
+            out,
+            "Per frame: {:.1} draw calls, {:.1} instances, {:.0} vertices, {:.1} texture binds",
+            per_frame(self.totals.draw_calls), per_frame(self.totals.instances),
+            per_frame(self.totals.vertices), per_frame(self.totals.texture_binds),
+        );
+        let _ = writeln!(out, "CPU per frame:");
+        for &(name, total, samples) in &self.stages {
+            let _ = writeln!(out, "  {:<10} {:.3} ms", name, total / samples.max(1) as f32);
+        }
+        out
+    }
+}
+
+/// Prints and logs `report`, and saves it under `cache/benchmarks`.
+pub fn publish(report: &str) -> Result<PathBuf, String> {
+    println!("{}", report);
+    for line in report.lines() {
+        info!("{}", line);
+    }
+    fs::create_dir_all(REPORT_DIR).map_err(|e| format!("Failed to create '{}': {}", REPORT_DIR, e))?;
+    let path = Path::new(REPORT_DIR).join(format!("benchmark-{}.txt", Local::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, report).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    info!("Benchmark report saved to '{}'.", path.display());
+    Ok(path)
+}
@@ -0,0 +1,124 @@
+//! The F10 diagnostic bundle: one ZIP with what a bug report needs, saved to
+//! the desktop so players can attach it without digging through folders.
+//! - `logs/`: every log file (`logging::log_files`)
+//! - `deadsync.ini`: the settings (profiles stay out; they hold API keys)
+//! - `system.txt`: version, OS, renderer, GPU and measured display latency
+//! - `songs.txt`: the song cache by pack, and any files that failed validation
+//! - Built on a background thread; the app collects the result with `take_finished`
+
+use crate::config;
+use crate::core::logging;
+use crate::game::parsing::zip;
+use crate::game::song;
+use chrono::Local;
+use log::{info, warn};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+// Where bundles go when there's no desktop folder to find.
+const FALLBACK_DIR: &str = "cache/diagnostics";
+const SONG_CACHE_DIR: &str = "cache/songs";
+
+static FINISHED: Mutex<Vec<Result<PathBuf, String>>> = Mutex::new(Vec::new());
+
+/// Builds the bundle in the background. `adapter` describes the GPU
+/// (`Backend::adapter_info`); `present_latency` is `Backend::present_latency`.
+pub fn start_export(adapter: String, present_latency: Option<f32>) {
+    thread::spawn(move || {
+        let result = export_bundle(&adapter, present_latency);
+        if let Err(e) = &result {
+            warn!("Diagnostic bundle not saved: {}", e);
+        }
+        FINISHED.lock().unwrap().push(result);
+    });
+}
+
+/// Exports that finished since the last call, with the bundle's path.
+pub fn take_finished() -> Vec<Result<PathBuf, String>> {
+    std::mem::take(&mut *FINISHED.lock().unwrap())
+}
+
+fn desktop_dir() -> Option<PathBuf> {
+    let home = std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })?;
+    let desktop = PathBuf::from(home).join("Desktop");
+    desktop.is_dir().then_some(desktop)
+}
+
+fn dir_size(dir: &Path) -> (usize, u64) {
+    let Ok(entries) = fs::read_dir(dir) else { return (0, 0) };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .fold((0, 0), |(count, bytes), m| (count + 1, bytes + m.len()))
+}
+
+fn system_report(adapter: &str, present_latency: Option<f32>) -> String {
+    let cfg = config::get();
+    let mut out = String::new();
+    let _ = writeln!(out, "DeadSync {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "Exported: {}", Local::now().format("%Y-%m-%d %H:%M:%S %z"));
+    let _ = writeln!(out, "OS: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(out, "CPU threads: {}", std::thread::available_parallelism().map_or(0, |n| n.get()));
+    let running = config::active_video_renderer().map_or("none".to_string(), |r| r.to_string());
+    let _ = writeln!(out, "Renderer: {} (configured {})", running, cfg.video_renderer);
+    let _ = writeln!(out, "GPU: {}", adapter);
+    let latency = present_latency.map_or("not measured".to_string(), |s| format!("{:.1} ms", s * 1000.0));
+    let _ = writeln!(out, "Queue-to-display: {}", latency);
+    let _ = writeln!(
+        out,
+        "Display: {} {}x{} @ {} Hz, present {}, {} frames in flight",
+        cfg.display_mode, cfg.display_width, cfg.display_height, cfg.refresh_rate, cfg.present_mode, cfg.frames_in_flight,
+    );
+    out
+}
+
+fn song_report() -> String {
+    let packs = song::get_song_cache();
+    let songs: usize = packs.iter().map(|p| p.songs.len()).sum();
+    let charts: usize = packs.iter().flat_map(|p| &p.songs).map(|s| s.charts.len()).sum();
+    let (cache_files, cache_bytes) = dir_size(Path::new(SONG_CACHE_DIR));
+    let mut out = String::new();
+    let _ = writeln!(out, "{} packs, {} songs, {} charts", packs.len(), songs, charts);
+    let _ = writeln!(out, "Cache: {} files, {:.1} MiB in {}", cache_files, cache_bytes as f64 / (1024.0 * 1024.0), SONG_CACHE_DIR);
+    let _ = writeln!(out);
+    for pack in packs.iter() {
+        let _ = writeln!(out, "{} ({} songs)", pack.name, pack.songs.len());
+        for song in pack.songs.iter().filter(|s| !s.asset_issues.is_empty()) {
+            let issues: Vec<&str> = song.asset_issues.iter().map(|i| i.message()).collect();
+            let _ = writeln!(out, "  {}: {}", song.title, issues.join("; "));
+        }
+    }
+    out
+}
+
+/// Zips the bundle to the desktop (or `cache/diagnostics`) and returns its path.
+fn export_bundle(adapter: &str, present_latency: Option<f32>) -> Result<PathBuf, String> {
+    let mut files: Vec<(String, Vec<u8>)> = logging::log_files()
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            Some((format!("logs/{}", name), fs::read(&path).ok()?))
+        })
+        .collect();
+    if let Ok(ini) = fs::read(config::CONFIG_PATH) {
+        files.push((config::CONFIG_PATH.to_string(), ini));
+    }
+    files.push(("system.txt".to_string(), system_report(adapter, present_latency).into_bytes()));
+    files.push(("songs.txt".to_string(), song_report().into_bytes()));
+
+    let dir = match desktop_dir() {
+        Some(dir) => dir,
+        None => {
+            fs::create_dir_all(FALLBACK_DIR).map_err(|e| e.to_string())?;
+            PathBuf::from(FALLBACK_DIR)
+        }
+    };
+    let path = dir.join(format!("deadsync-diagnostics-{}.zip", Local::now().format("%Y%m%d-%H%M%S")));
+    zip::create(&path, &files)?;
+    info!("Diagnostic bundle saved to '{}'.", path.display());
+    Ok(path)
+}
@@ -0,0 +1,170 @@
+//! Monitors, their video modes, and how the main window sits on them.
+//! - `refresh` snapshots what winit reports so screens can list choices
+//! - `fullscreen_for` turns the configured mode into a winit `Fullscreen`
+//! - `apply` switches the live window; the backend only sees a resize
+//!
+//! Monitors are numbered like the config's `DisplayMonitor`: 0 is the primary
+//! monitor, 1.. are the monitors in the order the windowing system lists them.
+
+use crate::config::Config;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use std::str::FromStr;
+use std::sync::Mutex;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::ActiveEventLoop;
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// A borderless window covering the monitor at its desktop mode.
+    Borderless,
+    /// Takes over the monitor at the configured resolution and refresh rate.
+    Exclusive,
+}
+
+impl core::fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Windowed => write!(f, "Windowed"),
+            Self::Borderless => write!(f, "Borderless"),
+            Self::Exclusive => write!(f, "Exclusive"),
+        }
+    }
+}
+
+impl FromStr for DisplayMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "windowed" => Ok(DisplayMode::Windowed),
+            "borderless" => Ok(DisplayMode::Borderless),
+            "exclusive" | "fullscreen" => Ok(DisplayMode::Exclusive),
+            _ => Err(format!("'{}' is not a valid display mode", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub is_primary: bool,
+    /// Distinct resolutions with their refresh rates in Hz, largest first.
+    pub modes: Vec<(u32, u32, Vec<u32>)>,
+}
+
+static MONITORS: Lazy<Mutex<Vec<MonitorInfo>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn monitor_name(monitor: &MonitorHandle) -> String {
+    monitor.name().unwrap_or_else(|| "Unknown Monitor".to_string())
+}
+
+fn refresh_hz(millihertz: u32) -> u32 {
+    (millihertz + 500) / 1000
+}
+
+/// Re-reads the monitors and their modes from the windowing system.
+pub fn refresh(event_loop: &ActiveEventLoop) {
+    let primary = event_loop.primary_monitor().map(|m| monitor_name(&m));
+    let monitors: Vec<MonitorInfo> = event_loop.available_monitors().map(|monitor| {
+        let mut modes: Vec<(u32, u32, Vec<u32>)> = Vec::new();
+        for mode in monitor.video_modes() {
+            let size = mode.size();
+            let hz = refresh_hz(mode.refresh_rate_millihertz());
+            match modes.iter_mut().find(|(w, h, _)| *w == size.width && *h == size.height) {
+                Some((_, _, rates)) if !rates.contains(&hz) => rates.push(hz),
+                Some(_) => {}
+                None => modes.push((size.width, size.height, vec![hz])),
+            }
+        }
+        modes.sort_by(|a, b| (b.0 * b.1, b.0).cmp(&(a.0 * a.1, a.0)));
+        for (_, _, rates) in &mut modes {
+            rates.sort_unstable_by(|a, b| b.cmp(a));
+        }
+        let name = monitor_name(&monitor);
+        MonitorInfo { is_primary: primary.as_deref() == Some(name.as_str()), name, modes }
+    }).collect();
+    info!("Found {} monitor(s): {}", monitors.len(),
+        monitors.iter().map(|m| format!("{} ({} modes)", m.name, m.modes.len())).collect::<Vec<_>>().join(", "));
+    *MONITORS.lock().unwrap() = monitors;
+}
+
+/// Monitors as of the last `refresh`, in listing order (numbered from 1).
+pub fn monitors() -> Vec<MonitorInfo> {
+    MONITORS.lock().unwrap().clone()
+}
+
+/// Monitor `index` (0 = primary) as of the last `refresh`, else the primary one.
+pub fn monitor_info(index: u32) -> Option<MonitorInfo> {
+    let monitors = MONITORS.lock().unwrap();
+    (index as usize).checked_sub(1).and_then(|i| monitors.get(i))
+        .or_else(|| monitors.iter().find(|m| m.is_primary))
+        .or_else(|| monitors.first())
+        .cloned()
+}
+
+/// Refresh rate in Hz of the monitor `window` is on, when winit knows it.
+pub fn window_refresh_hz(window: &Window) -> Option<u32> {
+    window.current_monitor()?.refresh_rate_millihertz().map(refresh_hz)
+}
+
+fn find_monitor(
+    available: impl Iterator<Item = MonitorHandle>,
+    primary: Option<MonitorHandle>,
+    index: u32,
+) -> Option<MonitorHandle> {
+    let listed: Vec<MonitorHandle> = available.collect();
+    (index as usize).checked_sub(1).and_then(|i| listed.get(i).cloned())
+        .or(primary)
+        .or_else(|| listed.into_iter().next())
+}
+
+/// What the window should be given the config: `None` for windowed. Exclusive
+/// picks the configured refresh rate (0 = highest) at the configured size and
+/// falls back to borderless when the monitor has no such mode.
+pub fn fullscreen_for(
+    available: impl Iterator<Item = MonitorHandle>,
+    primary: Option<MonitorHandle>,
+    cfg: &Config,
+) -> Option<Fullscreen> {
+    if cfg.display_mode == DisplayMode::Windowed {
+        return None;
+    }
+    let Some(monitor) = find_monitor(available, primary, cfg.display_monitor) else {
+        warn!("No monitor reported; using BORDERLESS fullscreen.");
+        return Some(Fullscreen::Borderless(None));
+    };
+    if cfg.display_mode == DisplayMode::Borderless {
+        return Some(Fullscreen::Borderless(Some(monitor)));
+    }
+    let (width, height) = (cfg.display_width, cfg.display_height);
+    let best_mode = monitor.video_modes()
+        .filter(|m| { let sz = m.size(); sz.width == width && sz.height == height })
+        .filter(|m| cfg.refresh_rate == 0 || refresh_hz(m.refresh_rate_millihertz()) == cfg.refresh_rate)
+        .max_by_key(|m| m.refresh_rate_millihertz());
+    match best_mode {
+        Some(mode) => {
+            info!("Fullscreen: using EXCLUSIVE {}x{} @ {} mHz on {}", width, height, mode.refresh_rate_millihertz(), monitor_name(&monitor));
+            Some(Fullscreen::Exclusive(mode))
+        }
+        None => {
+            warn!("No EXCLUSIVE mode {}x{} @ {} Hz on {}; using BORDERLESS.", width, height, cfg.refresh_rate, monitor_name(&monitor));
+            Some(Fullscreen::Borderless(Some(monitor)))
+        }
+    }
+}
+
+/// Moves the live window to the configured mode. The surface and swapchain
+/// follow through the `Resized` event, so the backend keeps running.
+pub fn apply(window: &Window, cfg: &Config) {
+    let fullscreen = fullscreen_for(window.available_monitors(), window.primary_monitor(), cfg);
+    let windowed = fullscreen.is_none();
+    window.set_fullscreen(fullscreen);
+    if windowed {
+        let _ = window.request_inner_size(PhysicalSize::new(cfg.display_width, cfg.display_height));
+    }
+    info!("Display mode set to {} ({}x{}).", cfg.display_mode, cfg.display_width, cfg.display_height);
+}
@@ -1,7 +1,24 @@
+use crate::core::input::Lane;
 use gilrs::{Axis, Button, Event, EventType, GamepadId, Gilrs};
 
+/// A pad panel. `UpLeft` and `UpRight` are the corners of solo and three-panel
+/// pads, which adapters report on the shoulder buttons; menus ignore them.
 #[derive(Clone, Copy, Debug)]
-pub enum PadDir { Up, Down, Left, Right }
+pub enum PadDir { Up, Down, Left, Right, UpLeft, UpRight }
+
+impl PadDir {
+    /// The input lane this panel plays; the game type decides its column.
+    pub fn lane(self) -> Lane {
+        match self {
+            PadDir::Up => Lane::Up,
+            PadDir::Down => Lane::Down,
+            PadDir::Left => Lane::Left,
+            PadDir::Right => Lane::Right,
+            PadDir::UpLeft => Lane::UpLeft,
+            PadDir::UpRight => Lane::UpRight,
+        }
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 pub enum PadButton { Confirm, Back, F7 }
@@ -29,6 +46,8 @@ pub struct GamepadState {
     pub down: bool,
     pub left: bool,
     pub right: bool,
+    pub up_left: bool,
+    pub up_right: bool,
 
     dpad_up: bool,
     dpad_down: bool,
@@ -39,6 +58,20 @@ pub struct GamepadState {
     ly: f32,
 }
 
+impl GamepadState {
+    /// Whether the panel playing `lane` is held down.
+    pub fn is_held(&self, lane: Lane) -> bool {
+        match lane {
+            Lane::Up => self.up,
+            Lane::Down => self.down,
+            Lane::Left => self.left,
+            Lane::Right => self.right,
+            Lane::UpLeft => self.up_left,
+            Lane::UpRight => self.up_right,
+        }
+    }
+}
+
 #[inline(always)]
 const fn deadzone() -> f32 { 0.35 }
 
@@ -85,6 +118,8 @@ pub fn poll_and_collect(
                     if state.down  { out.push(PadEvent::Dir { dir: PadDir::Down,  pressed: false }); }
                     if state.left  { out.push(PadEvent::Dir { dir: PadDir::Left,  pressed: false }); }
                     if state.right { out.push(PadEvent::Dir { dir: PadDir::Right, pressed: false }); }
+                    if state.up_left  { out.push(PadEvent::Dir { dir: PadDir::UpLeft,  pressed: false }); }
+                    if state.up_right { out.push(PadEvent::Dir { dir: PadDir::UpRight, pressed: false }); }
                     *state = GamepadState::default();
                 }
                 continue; // Don't process this event as an input.
@@ -129,6 +164,16 @@ pub fn poll_and_collect(
                     Button::DPadDown  => { state.dpad_down  = true; }
                     Button::DPadLeft  => { state.dpad_left  = true; }
                     Button::DPadRight => { state.dpad_right = true; }
+
+                    // Corner panels
+                    Button::LeftTrigger => {
+                        state.up_left = true;
+                        out.push(PadEvent::Dir { dir: PadDir::UpLeft, pressed: true });
+                    }
+                    Button::RightTrigger => {
+                        state.up_right = true;
+                        out.push(PadEvent::Dir { dir: PadDir::UpRight, pressed: true });
+                    }
                     _ => {}
                 }
             }
@@ -154,6 +199,15 @@ pub fn poll_and_collect(
                     Button::DPadDown  => { state.dpad_down  = false; }
                     Button::DPadLeft  => { state.dpad_left  = false; }
                     Button::DPadRight => { state.dpad_right = false; }
+
+                    Button::LeftTrigger => {
+                        state.up_left = false;
+                        out.push(PadEvent::Dir { dir: PadDir::UpLeft, pressed: false });
+                    }
+                    Button::RightTrigger => {
+                        state.up_right = false;
+                        out.push(PadEvent::Dir { dir: PadDir::UpRight, pressed: false });
+                    }
                     _ => {}
                 }
             }
@@ -1,2 +1,3 @@
 pub mod opengl;
+pub mod software;
 pub mod vulkan;
\ No newline at end of file
@@ -1,24 +1,43 @@
-use crate::core::gfx::{BlendMode, ObjectType, RenderList, Texture as RendererTexture};
-use crate::core::space::ortho_for_window;
+use crate::core::gfx::{
+    clip_rect_px, expand_mesh, render_size, tessellate_polyline, BlendMode, ColorVertex, FrameStats, HdrOutput, Material, ObjectType, PostEffect,
+    PresentSettings, RenderList, MSAA_SAMPLE_CHOICES, Texture as RendererTexture, UpscaleFilter, POST_COPY,
+    srgb_to_linear,
+};
+use crate::core::gfx::batch::{self, BatchKind};
+use crate::core::gfx::ktx2::{CompressedFormat, CompressedImage};
+use crate::core::space::{ortho_for_current_screen, ortho_for_size, ortho_for_window};
 use cgmath::Matrix4;
-use glow::{HasContext, PixelUnpackData, UniformLocation};
+use glow::{HasContext, PixelPackData, PixelUnpackData, UniformLocation};
 use glutin::{
-    config::ConfigTemplateBuilder,
-    context::{ContextAttributesBuilder, PossiblyCurrentContext},
-    display::{Display, DisplayApiPreference},
+    config::{ConfigTemplateBuilder, GetGlConfig},
+    context::{ContextApi, ContextAttributesBuilder, PossiblyCurrentContext, Version},
+    display::{Display, DisplayApiPreference, GetGlDisplay},
     prelude::*,
     surface::{Surface, SurfaceAttributesBuilder, WindowSurface},
 };
 use image::RgbaImage;
 use log::{info, warn};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
-use std::{collections::HashMap, error::Error, ffi::CStr, mem, num::NonZeroU32, sync::Arc};
+use std::{collections::{HashMap, VecDeque}, error::Error, ffi::CStr, mem, num::NonZeroU32, sync::Arc};
 use winit::window::Window;
 
 // A handle to an OpenGL texture on the GPU.
 #[derive(Debug, Clone, Copy)]
 pub struct Texture(pub glow::Texture);
 
+// An offscreen framebuffer drawing into a texture; see `create_render_target`.
+pub struct RenderTarget {
+    framebuffer: glow::Framebuffer,
+    stencil: glow::Renderbuffer,
+    size: (i32, i32),
+}
+
+// A second window drawn with the main context; see `create_secondary_window`.
+pub struct SecondaryWindow {
+    surface: Surface<WindowSurface>,
+    size: (u32, u32),
+}
+
 pub struct State {
     pub gl: glow::Context,
     gl_surface: Surface<WindowSurface>,
@@ -37,13 +56,85 @@ pub struct State {
     uv_scale_location: UniformLocation,
     uv_offset_location: UniformLocation,
     edge_fade_location: UniformLocation,
-    instanced_location: UniformLocation,
+    corner_tint_location: UniformLocation,
+    // `Material::index` and `Material::params`; GL branches on a uniform
+    // instead of keeping a program per material.
+    material_location: UniformLocation,
+    material_params_location: UniformLocation,
+    glow_location: UniformLocation,
+    // Untextured program + streaming buffer for tessellated polylines.
+    color_program: glow::Program,
+    color_proj_location: UniformLocation,
+    color_vao: glow::VertexArray,
+    color_vbo: glow::Buffer,
+    // Fullscreen program for `RenderList::post_effects`, drawn with an empty VAO.
+    post_program: PostProgram,
+    post_vao: glow::VertexArray,
+    // Targets at the render resolution the post chain ping-pongs between;
+    // made on first use and remade when that size changes.
+    post_targets: Vec<(RenderTarget, Texture)>,
+    // `PresentSettings::render_scale` and `upscale_filter`.
+    render_scale: u32,
+    upscale_filter: UpscaleFilter,
+    // GL has no frame queue to size; with one frame in flight, block on the
+    // GPU after each swap so the driver can't run ahead.
+    finish_after_swap: bool,
+    // sRGB textures and framebuffers (`PresentSettings::srgb`), when the
+    // window surface supports them.
+    srgb: bool,
+    uploads: Uploads,
+    gpu_timer: Option<GpuTimer>,
+    // KHR_debug (or GL 4.3), for the debug groups in `RenderList::labels`.
+    debug_groups: bool,
+    // Set by `request_capture`; the next frame is read back for `capture_frame`.
+    capture_requested: bool,
+    captured_frame: Option<RgbaImage>,
+}
+
+struct PostProgram {
+    program: glow::Program,
+    texture_location: UniformLocation,
+    params_location: UniformLocation,
+    effect_location: UniformLocation,
+}
+
+/// Async texture uploads: pixels go through a pixel unpack buffer and a fence
+/// marks when the GPU has finished copying them into the texture.
+#[derive(Default)]
+struct Uploads {
+    next_id: u64,
+    in_flight: VecDeque<(u64, glow::Fence, glow::Buffer, Texture)>,
+}
+
+/// TIME_ELAPSED queries around each frame's drawing. Results arrive a few
+/// frames late, so a small ring of queries is cycled and read as they finish.
+struct GpuTimer {
+    queries: Vec<glow::Query>,
+    // Ring slots begun but not yet read, oldest first.
+    in_flight: VecDeque<usize>,
+    next: usize,
+    enabled: bool,
+    // Smoothed GPU time per frame, in milliseconds.
+    gpu_ms: Option<f32>,
 }
 
-pub fn init(window: Arc<Window>, vsync_enabled: bool) -> Result<State, Box<dyn Error>> {
+const GPU_TIMER_QUERIES: usize = 4;
+// Weight of each new sample in the smoothed GPU time.
+const GPU_TIME_SMOOTHING: f32 = 0.1;
+
+pub fn init(window: Arc<Window>, present: PresentSettings) -> Result<State, Box<dyn Error>> {
     info!("Initializing OpenGL backend...");
 
-    let (gl_surface, gl_context, gl) = create_opengl_context(&window, vsync_enabled)?;
+    let (gl_surface, gl_context, gl, srgb) =
+        create_opengl_context(&window, present.waits_for_vblank(), present.msaa_samples, present.srgb, present.transparent)?;
+    // GLES has no switch for it: writes to sRGB attachments are always encoded.
+    if srgb && !gl.version().is_embedded {
+        unsafe { gl.enable(glow::FRAMEBUFFER_SRGB) };
+    }
+    info!("OpenGL color: {}.", if srgb { "sRGB (linear blending)" } else { "RGBA8 (gamma blending)" });
+    if present.hdr != HdrOutput::Off {
+        warn!("{} output needs the Vulkan renderer; staying SDR.", present.hdr);
+    }
     let (
         program,
         mvp_location,
@@ -52,8 +143,16 @@ pub fn init(window: Arc<Window>, vsync_enabled: bool) -> Result<State, Box<dyn E
         uv_scale_location,
         uv_offset_location,
         edge_fade_location,
-        instanced_location,
-    ) = create_graphics_program(&gl)?;
+        corner_tint_location,
+        material_location,
+        material_params_location,
+        glow_location,
+    ) = create_graphics_program(
+        &gl,
+        include_str!("../shaders/opengl_shader.vert"),
+        &with_sprite_material(include_str!("../shaders/opengl_shader.frag"), include_str!("../shaders/sprite_material.glsl")),
+        srgb,
+    )?;
 
     // Create shared static unit quad + index buffer.
     let (shared_vao, _shared_vbo, _shared_ibo, index_count) = unsafe {
@@ -97,6 +196,37 @@ pub fn init(window: Arc<Window>, vsync_enabled: bool) -> Result<State, Box<dyn E
         (vao, vbo, ibo, QUAD_INDICES.len() as i32)
     };
 
+    let (color_program, color_proj_location) = create_color_program(
+        &gl,
+        include_str!("../shaders/opengl_color.vert"),
+        include_str!("../shaders/opengl_color.frag"),
+        srgb,
+    )?;
+    let (color_vao, color_vbo) = unsafe {
+        let vao = gl.create_vertex_array()?;
+        let vbo = gl.create_buffer()?;
+        gl.bind_vertex_array(Some(vao));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+
+        // a_pos (location 0), a_color (location 1)
+        let stride = mem::size_of::<ColorVertex>() as i32;
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(1);
+        gl.vertex_attrib_pointer_f32(1, 4, glow::FLOAT, false, stride, (2 * mem::size_of::<f32>()) as i32);
+
+        gl.bind_vertex_array(None);
+        (vao, vbo)
+    };
+
+    let post_program = create_post_program(
+        &gl,
+        include_str!("../shaders/opengl_post.vert"),
+        include_str!("../shaders/opengl_post.frag"),
+    )?;
+    // Core profile needs a VAO bound even when no attributes are read.
+    let post_vao = unsafe { gl.create_vertex_array()? };
+
     let initial_size = window.inner_size();
     let projection = ortho_for_window(initial_size.width, initial_size.height);
 
@@ -105,15 +235,20 @@ pub fn init(window: Arc<Window>, vsync_enabled: bool) -> Result<State, Box<dyn E
         gl.use_program(Some(program));
         gl.active_texture(glow::TEXTURE0);
         gl.uniform_1_i32(Some(&texture_location), 0);
-        gl.uniform_1_i32(Some(&instanced_location), 0);
 
         // Set default values for uniforms
         gl.uniform_2_f32(Some(&uv_scale_location), 1.0, 1.0);
         gl.uniform_2_f32(Some(&uv_offset_location), 0.0, 0.0);
         gl.uniform_4_f32(Some(&edge_fade_location), 0.0, 0.0, 0.0, 0.0);
+        gl.uniform_4_f32_slice(Some(&corner_tint_location), &[1.0; 16]);
+        gl.uniform_1_i32(Some(&material_location), 0);
+        gl.uniform_4_f32(Some(&material_params_location), 0.0, 0.0, 0.0, 0.0);
+        gl.uniform_4_f32(Some(&glow_location), 0.0, 0.0, 0.0, 0.0);
         gl.use_program(None);
     }
 
+    let gpu_timer = create_gpu_timer(&gl);
+    let debug_groups = gl.supports_debug();
     let state = State {
         gl,
         gl_surface,
@@ -131,14 +266,34 @@ pub fn init(window: Arc<Window>, vsync_enabled: bool) -> Result<State, Box<dyn E
         uv_scale_location,
         uv_offset_location,
         edge_fade_location,
-        instanced_location,
+        corner_tint_location,
+        material_location,
+        material_params_location,
+        glow_location,
+        color_program,
+        color_proj_location,
+        color_vao,
+        color_vbo,
+        post_program,
+        post_vao,
+        post_targets: Vec::new(),
+        render_scale: present.render_scale,
+        upscale_filter: present.upscale_filter,
+        finish_after_swap: present.frames_in_flight == 1,
+        srgb,
+        uploads: Uploads::default(),
+        gpu_timer,
+        debug_groups,
+        capture_requested: false,
+        captured_frame: None,
     };
 
     info!("OpenGL backend initialized successfully.");
     Ok(state)
 }
 
-pub fn create_texture(gl: &glow::Context, image: &RgbaImage) -> Result<Texture, String> {
+// Creates and binds an empty, linearly filtered, single-level texture.
+unsafe fn new_texture_object(gl: &glow::Context) -> Result<glow::Texture, String> {
     unsafe {
         let t = gl.create_texture()?;
         gl.bind_texture(glow::TEXTURE_2D, Some(t));
@@ -162,22 +317,156 @@ pub fn create_texture(gl: &glow::Context, image: &RgbaImage) -> Result<Texture,
         gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
         gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_BASE_LEVEL, 0);
         gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAX_LEVEL, 0);
+        Ok(t)
+    }
+}
+
+pub fn create_texture(state: &State, image: &RgbaImage) -> Result<Texture, String> {
+    let gl = &state.gl;
+    unsafe {
+        let t = new_texture_object(gl)?;
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            rgba_format(state) as i32,
+            image.width() as i32,
+            image.height() as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            PixelUnpackData::Slice(Some(image.as_raw())),
+        );
+
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        Ok(Texture(t))
+    }
+}
+
+/// Writes the `[x, y, width, height]` region of `image` into `texture`, which is
+/// `image`'s size. GL orders it after draws already issued that sample it.
+pub fn update_texture(state: &State, texture: &Texture, image: &RgbaImage, region: [u32; 4]) {
+    let gl = &state.gl;
+    let [x, y, width, height] = region;
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture.0));
+        gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, image.width() as i32);
+        gl.pixel_store_i32(glow::UNPACK_SKIP_PIXELS, x as i32);
+        gl.pixel_store_i32(glow::UNPACK_SKIP_ROWS, y as i32);
+        gl.tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            x as i32,
+            y as i32,
+            width as i32,
+            height as i32,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            PixelUnpackData::Slice(Some(image.as_raw())),
+        );
+        gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
+        gl.pixel_store_i32(glow::UNPACK_SKIP_PIXELS, 0);
+        gl.pixel_store_i32(glow::UNPACK_SKIP_ROWS, 0);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+}
 
-        let internal = glow::RGBA8;
-        let w = image.width() as i32;
-        let h = image.height() as i32;
-        let raw = image.as_raw();
+/// Starts uploading `image` without waiting for it; collect it with `poll_texture_uploads`.
+pub fn queue_texture_upload(state: &mut State, image: &RgbaImage) -> Result<u64, String> {
+    let gl = &state.gl;
+    let id = state.uploads.next_id;
+    state.uploads.next_id += 1;
+    unsafe {
+        let pbo = gl.create_buffer()?;
+        gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, Some(pbo));
+        gl.buffer_data_u8_slice(glow::PIXEL_UNPACK_BUFFER, image.as_raw(), glow::STREAM_DRAW);
 
+        let t = new_texture_object(gl)?;
+        // Sourced from the bound unpack buffer, so the call returns before the copy is done.
         gl.tex_image_2d(
             glow::TEXTURE_2D,
             0,
-            internal as i32,
-            w,
-            h,
+            rgba_format(state) as i32,
+            image.width() as i32,
+            image.height() as i32,
             0,
             glow::RGBA,
             glow::UNSIGNED_BYTE,
-            PixelUnpackData::Slice(Some(raw)),
+            PixelUnpackData::BufferOffset(0),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, None);
+
+        let fence = gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0)?;
+        gl.flush();
+        state.uploads.in_flight.push_back((id, fence, pbo, Texture(t)));
+    }
+    Ok(id)
+}
+
+// Images are sRGB-encoded; with an sRGB framebuffer they're sampled as such.
+fn rgba_format(state: &State) -> u32 {
+    if state.srgb { glow::SRGB8_ALPHA8 } else { glow::RGBA8 }
+}
+
+/// Uploads whose copy has finished, oldest first.
+pub fn poll_texture_uploads(state: &mut State) -> Vec<(u64, Texture)> {
+    let gl = &state.gl;
+    let mut done = Vec::new();
+    while let Some((_, fence, _, _)) = state.uploads.in_flight.front() {
+        if unsafe { gl.get_sync_status(*fence) } != glow::SIGNALED {
+            break;
+        }
+        let (id, fence, pbo, texture) = state.uploads.in_flight.pop_front().unwrap();
+        unsafe {
+            gl.delete_sync(fence);
+            gl.delete_buffer(pbo);
+        }
+        done.push((id, texture));
+    }
+    done
+}
+
+pub fn supports_compressed(state: &State, format: CompressedFormat) -> bool {
+    let exts = state.gl.supported_extensions();
+    match format {
+        CompressedFormat::Bc1Rgb | CompressedFormat::Bc1Rgba | CompressedFormat::Bc3 => {
+            exts.contains("GL_EXT_texture_compression_s3tc")
+                && (!state.srgb
+                    || exts.contains("GL_EXT_texture_sRGB")
+                    || exts.contains("GL_EXT_texture_compression_s3tc_srgb"))
+        }
+        CompressedFormat::Bc7 => {
+            let v = state.gl.version();
+            (!v.is_embedded && (v.major, v.minor) >= (4, 2))
+                || exts.contains("GL_ARB_texture_compression_bptc")
+                || exts.contains("GL_EXT_texture_compression_bptc")
+        }
+    }
+}
+
+pub fn create_compressed_texture(state: &State, image: &CompressedImage) -> Result<Texture, String> {
+    let internal = match (image.format, state.srgb) {
+        (CompressedFormat::Bc1Rgb, false) => glow::COMPRESSED_RGB_S3TC_DXT1_EXT,
+        (CompressedFormat::Bc1Rgba, false) => glow::COMPRESSED_RGBA_S3TC_DXT1_EXT,
+        (CompressedFormat::Bc3, false) => glow::COMPRESSED_RGBA_S3TC_DXT5_EXT,
+        (CompressedFormat::Bc7, false) => glow::COMPRESSED_RGBA_BPTC_UNORM,
+        (CompressedFormat::Bc1Rgb, true) => glow::COMPRESSED_SRGB_S3TC_DXT1_EXT,
+        (CompressedFormat::Bc1Rgba, true) => glow::COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+        (CompressedFormat::Bc3, true) => glow::COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+        (CompressedFormat::Bc7, true) => glow::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+    };
+    let gl = &state.gl;
+    unsafe {
+        let t = new_texture_object(gl)?;
+        gl.compressed_tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            internal as i32,
+            image.width as i32,
+            image.height as i32,
+            0,
+            image.data.len() as i32,
+            &image.data,
         );
 
         gl.bind_texture(glow::TEXTURE_2D, None);
@@ -189,56 +478,527 @@ pub fn draw(
     state: &mut State,
     render_list: &RenderList,
     textures: &HashMap<String, RendererTexture>,
-) -> Result<u32, Box<dyn Error>> {
+) -> Result<FrameStats, Box<dyn Error>> {
     let (width, height) = state.window_size;
     if width == 0 || height == 0 {
-        return Ok(0);
+        return Ok(FrameStats::default());
+    }
+
+    // Frames with effects, or drawn below window resolution, draw the scene
+    // into a post target instead of the window.
+    let post = !render_list.post_effects.is_empty() || state.render_scale < 100;
+    if post {
+        ensure_post_targets(state)?;
+    }
+    let scene_size = match post {
+        true => (state.post_targets[0].0.size.0 as u32, state.post_targets[0].0.size.1 as u32),
+        false => (width, height),
+    };
+
+    let mut stats = unsafe {
+        let timed = begin_gpu_timer(state);
+        if post {
+            state.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(state.post_targets[0].0.framebuffer));
+            state.gl.viewport(0, 0, scene_size.0 as i32, scene_size.1 as i32);
+        }
+        clear(state, render_list.clear_color);
+        let mut stats = draw_objects(state, render_list, textures, state.projection, scene_size);
+        if post {
+            let mut passes: Vec<_> = render_list.post_effects.iter().map(PostEffect::shader_params).collect();
+            if passes.is_empty() {
+                passes.push(POST_COPY);
+            }
+            let passes = draw_post_chain(state, &passes);
+            stats.draw_calls += passes;
+            stats.vertices += passes * 3;
+        }
+        if timed {
+            state.gl.end_query(glow::TIME_ELAPSED);
+        }
+        stats
+    };
+
+    if std::mem::take(&mut state.capture_requested) {
+        state.captured_frame = Some(unsafe { read_back_buffer(&state.gl, width, height) });
+    }
+    state.gl_surface.swap_buffers(&state.gl_context)?;
+    if state.finish_after_swap {
+        unsafe { state.gl.finish(); }
     }
+    stats.gpu_time_ms = state.gpu_timer.as_ref().filter(|t| t.enabled).and_then(|t| t.gpu_ms);
+    Ok(stats)
+}
+
+/// Has the next frame read back for `capture_frame`.
+pub fn request_capture(state: &mut State) {
+    state.capture_requested = true;
+}
+
+/// True from `request_capture` until a frame takes the request.
+pub fn capture_pending(state: &State) -> bool {
+    state.capture_requested
+}
+
+/// The image the last captured frame presented, once per `request_capture`.
+pub fn capture_frame(state: &mut State) -> Option<RgbaImage> {
+    state.captured_frame.take()
+}
+
+// Reads the window's back buffer (the frame about to be swapped in) as RGBA,
+// top row first.
+unsafe fn read_back_buffer(gl: &glow::Context, width: u32, height: u32) -> RgbaImage {
+    let row = width as usize * 4;
+    let mut pixels = vec![0u8; row * height as usize];
+    unsafe {
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        gl.read_pixels(
+            0, 0, width as i32, height as i32, glow::RGBA, glow::UNSIGNED_BYTE,
+            PixelPackData::Slice(Some(&mut pixels)),
+        );
+    }
+    // GL's rows run bottom-up.
+    let mut flipped = Vec::with_capacity(pixels.len());
+    for line in pixels.chunks_exact(row).rev() {
+        flipped.extend_from_slice(line);
+    }
+    // The back buffer's alpha is whatever blending left behind; the window shows it opaque.
+    for px in flipped.chunks_exact_mut(4) {
+        px[3] = 255;
+    }
+    RgbaImage::from_raw(width, height, flipped).expect("buffer sized to the image")
+}
+
+/// Keeps two post targets at the render resolution, remaking them after a
+/// resize or a change of render scale. They're single-sampled, so MSAA doesn't
+/// apply to frames with effects or a scaled resolution.
+fn ensure_post_targets(state: &mut State) -> Result<(), String> {
+    let (width, height) = render_size(state.window_size.0, state.window_size.1, state.render_scale);
+    if state.post_targets.len() == 2 && state.post_targets[0].0.size == (width as i32, height as i32) {
+        return Ok(());
+    }
+    for (target, texture) in state.post_targets.drain(..) {
+        destroy_render_target(&state.gl, target);
+        unsafe { state.gl.delete_texture(texture.0) };
+    }
+    for _ in 0..2 {
+        let target = create_render_target(state, width, height)?;
+        state.post_targets.push(target);
+    }
+    Ok(())
+}
 
+/// Runs `passes` (`PostEffect::shader_params`) over the scene drawn into the
+/// first post target, ping-ponging between the two targets; the last pass
+/// draws into the window, upscaling to it. Returns the passes drawn.
+unsafe fn draw_post_chain(state: &State, passes: &[(u32, [f32; 4])]) -> u32 {
+    let gl = &state.gl;
+    let post = &state.post_program;
+    let filter = match state.upscale_filter {
+        UpscaleFilter::Linear => glow::LINEAR,
+        UpscaleFilter::Nearest => glow::NEAREST,
+    } as i32;
+    unsafe {
+        gl.disable(glow::BLEND);
+        gl.use_program(Some(post.program));
+        gl.uniform_1_i32(Some(&post.texture_location), 0);
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_vertex_array(Some(state.post_vao));
+        for (i, &(effect, params)) in passes.iter().enumerate() {
+            let (framebuffer, size) = match i + 1 == passes.len() {
+                true => (None, (state.window_size.0 as i32, state.window_size.1 as i32)),
+                false => {
+                    let target = &state.post_targets[(i + 1) % 2].0;
+                    (Some(target.framebuffer), target.size)
+                }
+            };
+            gl.bind_framebuffer(glow::FRAMEBUFFER, framebuffer);
+            gl.viewport(0, 0, size.0, size.1);
+            gl.bind_texture(glow::TEXTURE_2D, Some(state.post_targets[i % 2].1.0));
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter);
+            gl.uniform_1_i32(Some(&post.effect_location), effect as i32);
+            gl.uniform_4_f32_slice(Some(&post.params_location), &params);
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+        }
+        gl.bind_vertex_array(None);
+        gl.use_program(None);
+    }
+    passes.len() as u32
+}
+
+fn create_gpu_timer(gl: &glow::Context) -> Option<GpuTimer> {
+    // GLES only has timer queries through GL_EXT_disjoint_timer_query, whose
+    // results can be thrown away mid-frame; don't bother.
+    if gl.version().is_embedded {
+        info!("GPU frame timing is unavailable on OpenGL ES.");
+        return None;
+    }
+    let mut queries = Vec::with_capacity(GPU_TIMER_QUERIES);
+    for _ in 0..GPU_TIMER_QUERIES {
+        match unsafe { gl.create_query() } {
+            Ok(query) => queries.push(query),
+            Err(e) => {
+                warn!("Failed to create timer query: {}", e);
+                for query in queries {
+                    unsafe { gl.delete_query(query) };
+                }
+                return None;
+            }
+        }
+    }
+    Some(GpuTimer { queries, in_flight: VecDeque::new(), next: 0, enabled: false, gpu_ms: None })
+}
+
+/// glGetQueryObjectui64v into client memory (no query buffer is ever bound).
+unsafe fn query_u64(gl: &glow::Context, query: glow::Query, parameter: u32) -> u64 {
+    let mut value: u64 = 0;
+    unsafe { gl.get_query_parameter_u64_with_offset(query, parameter, &mut value as *mut u64 as usize) };
+    value
+}
+
+/// Folds in any finished queries, then starts timing this frame if a query is
+/// free. Returns whether one was begun; end it with `end_query(TIME_ELAPSED)`.
+unsafe fn begin_gpu_timer(state: &mut State) -> bool {
+    let gl = &state.gl;
+    let Some(timer) = &mut state.gpu_timer else { return false };
+    if !timer.enabled {
+        return false;
+    }
+    unsafe {
+        while let Some(&slot) = timer.in_flight.front() {
+            let query = timer.queries[slot];
+            if query_u64(gl, query, glow::QUERY_RESULT_AVAILABLE) == 0 {
+                break;
+            }
+            timer.in_flight.pop_front();
+            let sample = query_u64(gl, query, glow::QUERY_RESULT) as f32 * 1e-6;
+            timer.gpu_ms = Some(match timer.gpu_ms {
+                Some(prev) => prev + (sample - prev) * GPU_TIME_SMOOTHING,
+                None => sample,
+            });
+        }
+        // Every query still pending: skip timing this frame rather than stall.
+        if timer.in_flight.len() == timer.queries.len() {
+            return false;
+        }
+        let slot = timer.next;
+        timer.next = (slot + 1) % timer.queries.len();
+        gl.begin_query(glow::TIME_ELAPSED, timer.queries[slot]);
+        timer.in_flight.push_back(slot);
+    }
+    true
+}
+
+/// Relinks every program from the shader sources on disk. The old programs
+/// stay in use unless all the new ones link.
+#[cfg(feature = "shader-hot-reload")]
+pub fn reload_shaders(state: &mut State) -> Result<(), Box<dyn Error>> {
+    use crate::core::gfx::shader_reload::read_source;
+
+    let gl = &state.gl;
+    let graphics = create_graphics_program(
+        gl,
+        &read_source("opengl_shader.vert")?,
+        &with_sprite_material(&read_source("opengl_shader.frag")?, &read_source("sprite_material.glsl")?),
+        state.srgb,
+    )?;
+    let color = create_color_program(gl, &read_source("opengl_color.vert")?, &read_source("opengl_color.frag")?, state.srgb)
+        .inspect_err(|_| unsafe { gl.delete_program(graphics.0) })?;
+    let post = create_post_program(gl, &read_source("opengl_post.vert")?, &read_source("opengl_post.frag")?)
+        .inspect_err(|_| unsafe {
+            gl.delete_program(graphics.0);
+            gl.delete_program(color.0);
+        })?;
+
+    unsafe {
+        gl.delete_program(state.program);
+        gl.delete_program(state.color_program);
+        gl.delete_program(state.post_program.program);
+    }
+    (
+        state.program,
+        state.mvp_location,
+        state.color_location,
+        state.texture_location,
+        state.uv_scale_location,
+        state.uv_offset_location,
+        state.edge_fade_location,
+        state.corner_tint_location,
+        state.material_location,
+        state.material_params_location,
+        state.glow_location,
+    ) = graphics;
+    (state.color_program, state.color_proj_location) = color;
+    state.post_program = post;
+    Ok(())
+}
+
+pub fn adapter_info(state: &State) -> String {
+    let gl = &state.gl;
+    let (renderer, vendor, version) = unsafe {
+        (
+            gl.get_parameter_string(glow::RENDERER),
+            gl.get_parameter_string(glow::VENDOR),
+            gl.get_parameter_string(glow::VERSION),
+        )
+    };
+    format!("OpenGL {}: {} ({})", version, renderer, vendor)
+}
+
+/// Whether `error` is the driver reporting the context lost (a GPU reset).
+pub fn is_lost(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<glutin::error::Error>()
+        .is_some_and(|e| e.error_kind() == glutin::error::ErrorKind::ContextLost)
+}
+
+pub fn set_gpu_timing(state: &mut State, enabled: bool) {
+    let Some(timer) = &mut state.gpu_timer else { return };
+    timer.enabled = enabled;
+    if !enabled {
+        timer.gpu_ms = None;
+    }
+}
+
+/// Creates an offscreen target and the texture it draws into. The texture is
+/// sampled like any other once it is in the texture map; its contents are
+/// undefined until the first `draw_to_target`.
+pub fn create_render_target(
+    state: &State,
+    width: u32,
+    height: u32,
+) -> Result<(RenderTarget, Texture), String> {
+    let gl = &state.gl;
+    let (w, h) = (width.max(1) as i32, height.max(1) as i32);
+    unsafe {
+        let tex = gl.create_texture()?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAX_LEVEL, 0);
+        gl.tex_image_2d(
+            glow::TEXTURE_2D, 0, rgba_format(state) as i32, w, h, 0,
+            glow::RGBA, glow::UNSIGNED_BYTE, PixelUnpackData::Slice(None),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        // Packed depth/stencil is the one stencil format every GL 3.3 and GLES 3.0 driver renders to.
+        let stencil = gl.create_renderbuffer()?;
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(stencil));
+        gl.renderbuffer_storage(glow::RENDERBUFFER, glow::DEPTH24_STENCIL8, w, h);
+        gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+
+        let framebuffer = gl.create_framebuffer()?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+        gl.framebuffer_texture_2d(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0, glow::TEXTURE_2D, Some(tex), 0);
+        gl.framebuffer_renderbuffer(glow::FRAMEBUFFER, glow::DEPTH_STENCIL_ATTACHMENT, glow::RENDERBUFFER, Some(stencil));
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            gl.delete_framebuffer(framebuffer);
+            gl.delete_renderbuffer(stencil);
+            gl.delete_texture(tex);
+            return Err(format!("Render target framebuffer incomplete (0x{:X})", status));
+        }
+        Ok((RenderTarget { framebuffer, stencil, size: (w, h) }, Texture(tex)))
+    }
+}
+
+/// Draws `render_list` into `target`, mapping the current screen space onto the whole target.
+pub fn draw_to_target(
+    state: &mut State,
+    target: &RenderTarget,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+) -> FrameStats {
+    // GL's framebuffer origin is bottom-left; flip so row 0 is the top, the
+    // same as uploaded images, and the target samples upright.
+    let projection = Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0) * ortho_for_current_screen();
+    unsafe {
+        let gl = &state.gl;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target.framebuffer));
+        gl.viewport(0, 0, target.size.0, target.size.1);
+        clear(state, render_list.clear_color);
+        let size = (target.size.0 as u32, target.size.1 as u32);
+        let stats = draw_objects(state, render_list, textures, projection, size);
+
+        let gl = &state.gl;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.viewport(0, 0, state.window_size.0 as i32, state.window_size.1 as i32);
+        stats
+    }
+}
+
+/// Opens a surface on `window` for `draw_secondary_window` with the main
+/// context's config, so programs, buffers and textures are all shared. On X11
+/// the window needs a visual that config can render to.
+pub fn create_secondary_window(state: &mut State, window: &Window) -> Result<SecondaryWindow, Box<dyn Error>> {
+    use glutin::surface::SwapInterval;
+    let (width, height): (u32, u32) = window.inner_size().into();
+    let attributes = SurfaceAttributesBuilder::<WindowSurface>::new().with_srgb(Some(state.srgb)).build(
+        window.window_handle()?.as_raw(),
+        NonZeroU32::new(width.max(1)).unwrap(),
+        NonZeroU32::new(height.max(1)).unwrap(),
+    );
+    let config = state.gl_context.config();
+    let surface = unsafe { state.gl_context.display().create_window_surface(&config, &attributes)? };
+
+    // The swap interval belongs to the surface; never let a second display pace the main one.
+    state.gl_context.make_current(&surface)?;
+    if let Err(e) = surface.set_swap_interval(&state.gl_context, SwapInterval::DontWait) {
+        warn!("Failed to turn off the secondary window's swap interval: {}", e);
+    }
+    state.gl_context.make_current(&state.gl_surface)?;
+    info!("Secondary window ready ({}x{}).", width, height);
+    Ok(SecondaryWindow { surface, size: (width, height) })
+}
+
+/// Draws `render_list` into a secondary window, in that window's own screen
+/// space (`ortho_for_size`). Post effects are ignored. The main surface is
+/// current again afterwards.
+pub fn draw_secondary_window(
+    state: &mut State,
+    window: &mut SecondaryWindow,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+) -> Result<FrameStats, Box<dyn Error>> {
+    let (width, height) = window.size;
+    if width == 0 || height == 0 {
+        return Ok(FrameStats::default());
+    }
+    state.gl_context.make_current(&window.surface)?;
+    let stats = unsafe {
+        state.gl.viewport(0, 0, width as i32, height as i32);
+        clear(state, render_list.clear_color);
+        draw_objects(state, render_list, textures, ortho_for_size(width, height), (width, height))
+    };
+    let swapped = window.surface.swap_buffers(&state.gl_context);
+    state.gl_context.make_current(&state.gl_surface)?;
+    unsafe {
+        state.gl.viewport(0, 0, state.window_size.0 as i32, state.window_size.1 as i32);
+    }
+    swapped?;
+    Ok(stats)
+}
+
+pub fn resize_secondary_window(state: &mut State, window: &mut SecondaryWindow, width: u32, height: u32) {
+    window.size = (width, height);
+    if let (Some(w), Some(h)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+        window.surface.resize(&state.gl_context, w, h);
+    }
+}
+
+/// Frees a target's framebuffer. Its texture is freed with the other textures.
+pub fn destroy_render_target(gl: &glow::Context, target: RenderTarget) {
+    unsafe {
+        gl.delete_framebuffer(target.framebuffer);
+        gl.delete_renderbuffer(target.stencil);
+    }
+}
+
+unsafe fn clear(state: &State, c: [f32; 4]) {
+    // Clears skip the shaders, so they're linearized here instead.
+    let c = if state.srgb { srgb_to_linear(c) } else { c };
+    let gl = &state.gl;
+    unsafe {
+        gl.clear_color(c[0], c[1], c[2], c[3]);
+        gl.clear_stencil(0);
+        gl.stencil_mask(0xFF);
+        gl.clear(glow::COLOR_BUFFER_BIT | glow::STENCIL_BUFFER_BIT);
+        gl.disable(glow::STENCIL_TEST);
+    }
+}
+
+/// Draws the objects into the bound framebuffer (`size` pixels). Returns what was drawn.
+unsafe fn draw_objects(
+    state: &State,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+    projection: Matrix4<f32>,
+    size: (u32, u32),
+) -> FrameStats {
     #[inline(always)]
     fn apply_blend(gl: &glow::Context, want: BlendMode, last: &mut Option<BlendMode>) {
         if *last == Some(want) { return; }
         unsafe {
             gl.enable(glow::BLEND);
+            // Alpha accumulates coverage and the other modes leave it alone, as on Vulkan.
             match want {
                 BlendMode::Alpha => {
                     gl.blend_equation(glow::FUNC_ADD);
-                    gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+                    gl.blend_func_separate(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA, glow::ONE, glow::ONE_MINUS_SRC_ALPHA);
                 }
                 BlendMode::Add => {
                     gl.blend_equation(glow::FUNC_ADD);
-                    gl.blend_func(glow::SRC_ALPHA, glow::ONE);
+                    gl.blend_func_separate(glow::SRC_ALPHA, glow::ONE, glow::ZERO, glow::ONE);
                 }
                 BlendMode::Multiply => {
                     gl.blend_equation(glow::FUNC_ADD);
-                    gl.blend_func(glow::DST_COLOR, glow::ZERO);
+                    gl.blend_func_separate(glow::DST_COLOR, glow::ZERO, glow::ZERO, glow::ONE);
                 }
                 BlendMode::Subtract => {
-                    gl.blend_equation(glow::FUNC_REVERSE_SUBTRACT);
-                    gl.blend_func(glow::ONE, glow::ONE);
+                    gl.blend_equation_separate(glow::FUNC_REVERSE_SUBTRACT, glow::FUNC_ADD);
+                    gl.blend_func_separate(glow::ONE, glow::ONE, glow::ZERO, glow::ONE);
                 }
             }
         }
         *last = Some(want);
     }
 
-    let mut vertices: u32 = 0;
+    // Objects with a mask only pass where the stencil holds that id; 0 disables the test.
+    #[inline(always)]
+    fn apply_stencil(gl: &glow::Context, mask: u8, last: &mut Option<u8>) {
+        if *last == Some(mask) { return; }
+        unsafe {
+            if mask == 0 {
+                gl.disable(glow::STENCIL_TEST);
+            } else {
+                gl.enable(glow::STENCIL_TEST);
+                gl.stencil_func(glow::EQUAL, mask as i32, 0xFF);
+                gl.stencil_op(glow::KEEP, glow::KEEP, glow::KEEP);
+            }
+        }
+        *last = Some(mask);
+    }
+
+    #[inline(always)]
+    fn apply_clip(
+        gl: &glow::Context,
+        clip: Option<[f32; 4]>,
+        last: &mut Option<[f32; 4]>,
+        projection: &Matrix4<f32>,
+        size: (u32, u32),
+    ) {
+        if *last == clip { return; }
+        unsafe {
+            match clip {
+                Some(c) => {
+                    let [x, y, w, h] = clip_rect_px(c, projection, size.0, size.1, false);
+                    gl.enable(glow::SCISSOR_TEST);
+                    gl.scissor(x, y, w, h);
+                }
+                None => gl.disable(glow::SCISSOR_TEST),
+            }
+        }
+        *last = clip;
+    }
+
+    let mut stats = FrameStats::default();
 
     unsafe {
         let gl = &state.gl;
 
-        let c = render_list.clear_color;
-        gl.clear_color(c[0], c[1], c[2], c[3]);
-        gl.clear(glow::COLOR_BUFFER_BIT);
-
         gl.use_program(Some(state.program));
         gl.bind_vertex_array(Some(state.shared_vao));
 
-        gl.uniform_1_i32(Some(&state.instanced_location), 0);
 
         gl.enable(glow::BLEND);
         gl.blend_equation(glow::FUNC_ADD);
-        gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+        gl.blend_func_separate(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA, glow::ONE, glow::ONE_MINUS_SRC_ALPHA);
 
         gl.active_texture(glow::TEXTURE0);
         gl.uniform_1_i32(Some(&state.texture_location), 0);
@@ -249,20 +1009,63 @@ pub fn draw(
         let mut last_uv_offset: Option<[f32; 2]> = None;
         let mut last_color: Option<[f32; 4]> = None;
         let mut last_edge_fade: Option<[f32; 4]> = None;
+        let mut last_corner_tint: Option<[[f32; 4]; 4]> = None;
+        let mut last_material: Option<Material> = None;
+        let mut last_glow: Option<[f32; 4]> = None;
+        let mut last_stencil: Option<u8> = Some(0);
+        let mut last_clip: Option<[f32; 4]> = None;
+        let mut sprite_program_bound = true;
+        let mut line_vertices: Vec<ColorVertex> = Vec::new();
 
-        for obj in &render_list.objects {
-            apply_blend(gl, obj.blend, &mut last_blend);
-
-            let mvp_array: [[f32; 4]; 4] = (state.projection * obj.transform).into();
-            gl.uniform_matrix_4_f32_slice(Some(&state.mvp_location), false, bytemuck::cast_slice(&mvp_array));
+        let batches = batch::batches(&render_list.objects, |id| match textures.get(id) {
+            Some(RendererTexture::OpenGL(gl_tex)) => Some(gl_tex.0),
+            _ => None,
+        });
+        let labels = render_list.labels.as_ref().filter(|_| state.debug_groups);
+        if let Some(labels) = labels {
+            gl.push_debug_group(glow::DEBUG_SOURCE_APPLICATION, 0, &labels.screen);
+        }
+        let mut open_group: Option<u32> = None;
+        for b in batches {
+            if let Some(labels) = labels {
+                let group = render_list.objects.get(b.objects.start).map_or(0, |o| o.group);
+                if open_group != Some(group) {
+                    if open_group.is_some() {
+                        gl.pop_debug_group();
+                    }
+                    gl.push_debug_group(glow::DEBUG_SOURCE_APPLICATION, 0, labels.group(group));
+                    open_group = Some(group);
+                }
+            }
+            apply_blend(gl, b.blend, &mut last_blend);
+            if !matches!(b.kind, BatchKind::Mask { .. }) {
+                apply_stencil(gl, b.mask, &mut last_stencil);
+            }
+            apply_clip(gl, b.clip, &mut last_clip, &projection, size);
+            let objects = &render_list.objects[b.objects];
 
-            match &obj.object_type {
-                ObjectType::Sprite { texture_id, tint, uv_scale, uv_offset, edge_fade } => {
-                    if let Some(RendererTexture::OpenGL(gl_tex)) = textures.get(texture_id) {
-                        if last_bound_tex != Some(gl_tex.0) {
-                            gl.bind_texture(glow::TEXTURE_2D, Some(gl_tex.0));
-                            last_bound_tex = Some(gl_tex.0);
+            match b.kind {
+                BatchKind::Sprites { texture, .. } => {
+                    if !sprite_program_bound {
+                        gl.use_program(Some(state.program));
+                        gl.bind_vertex_array(Some(state.shared_vao));
+                        sprite_program_bound = true;
+                    }
+                    if last_bound_tex != Some(texture) {
+                        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                        last_bound_tex = Some(texture);
+                        stats.texture_binds += 1;
+                    }
+                    for obj in objects {
+                        let ObjectType::Sprite { texture_id, tint, uv_scale, uv_offset, edge_fade, corner_tint, material, glow } = &obj.object_type else {
+                            continue;
+                        };
+                        // Sprites whose texture isn't loaded ride along in the batch; skip them.
+                        if !textures.contains_key(texture_id) {
+                            continue;
                         }
+                        let mvp_array: [[f32; 4]; 4] = (projection * obj.transform).into();
+                        gl.uniform_matrix_4_f32_slice(Some(&state.mvp_location), false, bytemuck::cast_slice(&mvp_array));
                         if last_uv_scale != Some(*uv_scale) {
                             gl.uniform_2_f32(Some(&state.uv_scale_location), uv_scale[0], uv_scale[1]);
                             last_uv_scale = Some(*uv_scale);
@@ -279,17 +1082,104 @@ pub fn draw(
                             gl.uniform_4_f32_slice(Some(&state.edge_fade_location), edge_fade);
                             last_edge_fade = Some(*edge_fade);
                         }
+                        if last_corner_tint != Some(*corner_tint) {
+                            gl.uniform_4_f32_slice(Some(&state.corner_tint_location), bytemuck::cast_slice(corner_tint));
+                            last_corner_tint = Some(*corner_tint);
+                        }
+                        if last_material != Some(*material) {
+                            gl.uniform_1_i32(Some(&state.material_location), material.index() as i32);
+                            gl.uniform_4_f32_slice(Some(&state.material_params_location), &material.params());
+                            last_material = Some(*material);
+                        }
+                        if last_glow != Some(*glow) {
+                            gl.uniform_4_f32_slice(Some(&state.glow_location), glow);
+                            last_glow = Some(*glow);
+                        }
                         gl.draw_elements(glow::TRIANGLES, state.index_count, glow::UNSIGNED_SHORT, 0);
-                        vertices += 4;
+                        stats.vertices += 4;
+                        stats.draw_calls += 1;
+                        stats.instances += 1;
                     }
                 }
+                BatchKind::Shapes => {
+                    // The whole batch goes up in one buffer and one draw.
+                    line_vertices.clear();
+                    for obj in objects {
+                        if let ObjectType::Polyline { points, colors, thickness } = &obj.object_type {
+                            tessellate_polyline(points, colors, *thickness, &obj.transform, &mut line_vertices);
+                        } else if let ObjectType::Mesh { vertices: points, indices, colors } = &obj.object_type {
+                            expand_mesh(points, indices, colors, &obj.transform, &mut line_vertices);
+                        }
+                    }
+                    if line_vertices.is_empty() {
+                        continue;
+                    }
+                    if sprite_program_bound {
+                        gl.use_program(Some(state.color_program));
+                        gl.bind_vertex_array(Some(state.color_vao));
+                        let proj_array: [[f32; 4]; 4] = projection.into();
+                        gl.uniform_matrix_4_f32_slice(Some(&state.color_proj_location), false, bytemuck::cast_slice(&proj_array));
+                        sprite_program_bound = false;
+                    }
+                    gl.bind_buffer(glow::ARRAY_BUFFER, Some(state.color_vbo));
+                    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, bytemuck::cast_slice(&line_vertices), glow::STREAM_DRAW);
+                    gl.draw_arrays(glow::TRIANGLES, 0, line_vertices.len() as i32);
+                    stats.vertices += line_vertices.len() as u32;
+                    stats.draw_calls += 1;
+                }
+                BatchKind::Mask { id } => {
+                    let Some(obj) = objects.iter().find(|o| matches!(o.object_type, ObjectType::Mask { .. })) else {
+                        continue;
+                    };
+                    if !sprite_program_bound {
+                        gl.use_program(Some(state.program));
+                        gl.bind_vertex_array(Some(state.shared_vao));
+                        sprite_program_bound = true;
+                    }
+                    let mvp_array: [[f32; 4]; 4] = (projection * obj.transform).into();
+                    gl.uniform_matrix_4_f32_slice(Some(&state.mvp_location), false, bytemuck::cast_slice(&mvp_array));
+
+                    gl.enable(glow::STENCIL_TEST);
+                    gl.stencil_func(glow::ALWAYS, id as i32, 0xFF);
+                    gl.stencil_op(glow::KEEP, glow::KEEP, glow::REPLACE);
+                    gl.color_mask(false, false, false, false);
+                    gl.draw_elements(glow::TRIANGLES, state.index_count, glow::UNSIGNED_SHORT, 0);
+                    gl.color_mask(true, true, true, true);
+                    last_stencil = None;
+                    stats.vertices += 4;
+                    stats.draw_calls += 1;
+                    stats.instances += 1;
+                }
+            }
+        }
+        if labels.is_some() {
+            if open_group.is_some() {
+                gl.pop_debug_group();
             }
+            gl.pop_debug_group();
         }
+        gl.disable(glow::STENCIL_TEST);
+        // Scissoring also limits glClear, so never leave it on for the next frame.
+        gl.disable(glow::SCISSOR_TEST);
         gl.bind_vertex_array(None);
     }
+    stats
+}
 
-    state.gl_surface.swap_buffers(&state.gl_context)?;
-    Ok(vertices)
+/// GL only has a swap interval: FIFO waits for vblank, every other mode doesn't.
+pub fn set_present_mode(state: &mut State, present: PresentSettings) -> Result<(), Box<dyn Error>> {
+    use glutin::surface::SwapInterval;
+    // Picked up by `ensure_post_targets` and `draw_post_chain` on the next frame.
+    state.render_scale = present.render_scale;
+    state.upscale_filter = present.upscale_filter;
+    let interval = if present.waits_for_vblank() {
+        SwapInterval::Wait(NonZeroU32::new(1).unwrap())
+    } else {
+        SwapInterval::DontWait
+    };
+    state.gl_surface.set_swap_interval(&state.gl_context, interval)?;
+    info!("Set swap interval for present mode {} (vsync {}).", present.mode, present.vsync);
+    Ok(())
 }
 
 pub fn resize(state: &mut State, width: u32, height: u32) {
@@ -311,10 +1201,29 @@ pub fn resize(state: &mut State, width: u32, height: u32) {
 pub fn cleanup(state: &mut State) {
     info!("Cleaning up OpenGL resources...");
     unsafe {
+        for (_, fence, pbo, texture) in state.uploads.in_flight.drain(..) {
+            state.gl.delete_sync(fence);
+            state.gl.delete_buffer(pbo);
+            state.gl.delete_texture(texture.0);
+        }
         state.gl.delete_program(state.program);
         state.gl.delete_vertex_array(state.shared_vao);
         state.gl.delete_buffer(state._shared_vbo);
         state.gl.delete_buffer(state._shared_ibo);
+        state.gl.delete_program(state.color_program);
+        state.gl.delete_vertex_array(state.color_vao);
+        state.gl.delete_buffer(state.color_vbo);
+        state.gl.delete_program(state.post_program.program);
+        state.gl.delete_vertex_array(state.post_vao);
+        for (target, texture) in state.post_targets.drain(..) {
+            destroy_render_target(&state.gl, target);
+            state.gl.delete_texture(texture.0);
+        }
+        if let Some(timer) = state.gpu_timer.take() {
+            for query in timer.queries {
+                state.gl.delete_query(query);
+            }
+        }
     }
     info!("OpenGL resources cleaned up.");
 }
@@ -322,7 +1231,10 @@ pub fn cleanup(state: &mut State) {
 fn create_opengl_context(
     window: &Window,
     vsync_enabled: bool,
-) -> Result<(Surface<WindowSurface>, PossiblyCurrentContext, glow::Context), Box<dyn Error>> {
+    msaa_samples: u32,
+    srgb: bool,
+    transparent: bool,
+) -> Result<(Surface<WindowSurface>, PossiblyCurrentContext, glow::Context, bool), Box<dyn Error>> {
     let display_handle = window.display_handle()?.as_raw();
 
     #[cfg(target_os = "windows")]
@@ -353,8 +1265,17 @@ fn create_opengl_context(
     
     #[cfg(not(target_os = "windows"))]
     let (display, vsync_logic) = {
-        info!("Using EGL for OpenGL context.");
-        let preference = DisplayApiPreference::Egl;
+        // macOS has no EGL; CGL is its native GL API.
+        #[cfg(target_os = "macos")]
+        let preference = {
+            info!("Using CGL for OpenGL context.");
+            DisplayApiPreference::Cgl
+        };
+        #[cfg(not(target_os = "macos"))]
+        let preference = {
+            info!("Using EGL for OpenGL context.");
+            DisplayApiPreference::Egl
+        };
         let display = unsafe { Display::new(display_handle, preference)? };
         
         let vsync_logic = move |_display: &Display, surface: &Surface<WindowSurface>, context: &PossiblyCurrentContext| {
@@ -374,47 +1295,188 @@ fn create_opengl_context(
         (display, vsync_logic)
     };
 
-    let template = ConfigTemplateBuilder::new()
-        .with_alpha_size(8)
-        .with_stencil_size(8)
-        .build();
-
-    let config = unsafe { display.find_configs(template)?.next() }
-        .ok_or("Failed to find a suitable GL config")?;
+    // Ask for MSAA first, then settle for whatever the display offers. A
+    // transparent window falls back to an opaque config rather than failing.
+    let template = |samples: u8, transparent: bool| {
+        let builder = ConfigTemplateBuilder::new().with_alpha_size(8).with_stencil_size(8).with_transparency(transparent);
+        if samples > 1 { builder.with_multisampling(samples) } else { builder }.build()
+    };
+    let find_config = |transparent: bool| -> Result<_, glutin::error::Error> {
+        for samples in MSAA_SAMPLE_CHOICES.into_iter().rev().filter(|&s| s <= msaa_samples.max(1)) {
+            if let Some(config) = unsafe { display.find_configs(template(samples as u8, transparent))?.next() } {
+                if samples < msaa_samples {
+                    warn!("{}x MSAA unsupported; using {}x.", msaa_samples, samples);
+                }
+                return Ok(Some(config));
+            }
+        }
+        Ok(None)
+    };
+    let mut config = find_config(transparent)?;
+    if config.is_none() && transparent {
+        warn!("No transparent GL config; the overlay window will be opaque.");
+        config = find_config(false)?;
+    }
+    let config = config.ok_or("Failed to find a suitable GL config")?;
+    if config.num_samples() > 1 {
+        info!("OpenGL MSAA: {}x.", config.num_samples());
+    }
 
     let (width, height): (u32, u32) = window.inner_size().into();
     let raw_window_handle = window.window_handle()?.as_raw();
-    let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::new().build(
+    let srgb = srgb && config.srgb_capable();
+    let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::new().with_srgb(Some(srgb)).build(
         raw_window_handle,
         NonZeroU32::new(width).unwrap(),
         NonZeroU32::new(height).unwrap(),
     );
     let surface = unsafe { display.create_window_surface(&config, &surface_attributes)? };
 
-    let context_attributes =
-        ContextAttributesBuilder::new().build(Some(raw_window_handle));
-    let context = unsafe { display.create_context(&config, &context_attributes)? }
-        .make_current(&surface)?;
+    // CGL hands out a legacy 2.1 context unless a core profile is asked for,
+    // and the shaders are GLSL 330. It has no GLES.
+    #[cfg(target_os = "macos")]
+    let context_apis = [(Some(glutin::context::GlProfile::Core), ContextApi::OpenGl(Some(Version::new(3, 3))))];
+    // Elsewhere take the driver's default desktop context, and fall back to
+    // GLES 3.0 (ARM boards, ANGLE) when that is missing or older than 3.3.
+    // The shaders are rewritten for GLSL ES when compiled; see `glsl_source`.
+    #[cfg(not(target_os = "macos"))]
+    let context_apis = [(None, ContextApi::OpenGl(None)), (None, ContextApi::Gles(Some(Version::new(3, 0))))];
 
-    #[cfg(target_os = "windows")]
-    vsync_logic(&display);
-    #[cfg(not(target_os = "windows"))]
-    vsync_logic(&display, &surface, &context);
+    let mut last_error: Box<dyn Error> = "No OpenGL context available".into();
+    for (profile, api) in context_apis {
+        let mut attributes = ContextAttributesBuilder::new().with_context_api(api);
+        if let Some(profile) = profile {
+            attributes = attributes.with_profile(profile);
+        }
+        let attributes = attributes.build(Some(raw_window_handle));
+        let context = match unsafe { display.create_context(&config, &attributes) }.and_then(|c| c.make_current(&surface)) {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Couldn't create a {:?} context: {}", api, e);
+                last_error = e.into();
+                continue;
+            }
+        };
+        let gl = unsafe { glow::Context::from_loader_function_cstr(|s: &CStr| display.get_proc_address(s)) };
+        let version = gl.version();
+        if !version.is_embedded && (version.major, version.minor) < (3, 3) {
+            warn!("OpenGL {}.{} is too old for the GLSL 330 shaders.", version.major, version.minor);
+            last_error = format!("OpenGL {}.{} is older than 3.3", version.major, version.minor).into();
+            continue;
+        }
+        info!(
+            "OpenGL{} {}.{} context ({}).",
+            if version.is_embedded { " ES" } else { "" },
+            version.major,
+            version.minor,
+            version.vendor_info
+        );
+
+        #[cfg(target_os = "windows")]
+        vsync_logic(&display);
+        #[cfg(not(target_os = "windows"))]
+        vsync_logic(&display, &surface, &context);
+
+        return Ok((surface, context, gl, srgb));
+    }
+    Err(last_error)
+}
+
+/// Adapts a GLSL 330 source to the context: GLES 3.0 gets `#version 300 es`
+/// and default precisions, which the desktop sources don't declare.
+fn glsl_source(gl: &glow::Context, src: &str) -> String {
+    if !gl.version().is_embedded {
+        return src.to_string();
+    }
+    let body = match src.trim_start().strip_prefix("#version") {
+        Some(rest) => rest.split_once('\n').map_or("", |(_, body)| body),
+        None => src,
+    };
+    format!("#version 300 es\nprecision highp float;\nprecision highp int;\nprecision highp sampler2D;\n{}", body)
+}
+
+type GraphicsProgram = (
+    glow::Program, UniformLocation, UniformLocation, UniformLocation, UniformLocation,
+    UniformLocation, UniformLocation, UniformLocation, UniformLocation, UniformLocation, UniformLocation,
+);
+
+// GLSL 330 has no #include, so the shared sprite code is spliced in by hand.
+fn with_sprite_material(frag_src: &str, material_src: &str) -> String {
+    frag_src.replace("#include \"sprite_material.glsl\"", material_src)
+}
+
+fn create_graphics_program(gl: &glow::Context, vert_src: &str, frag_src: &str, srgb: bool) -> Result<GraphicsProgram, String> {
+    let program = link_program(gl, vert_src, frag_src)?;
+    unsafe {
+        set_srgb_uniform(gl, program, srgb);
+        let get = |name: &str| gl.get_uniform_location(program, name).ok_or_else(|| name.to_string());
+        let locations = || -> Result<GraphicsProgram, String> {
+            let mvp_location = get("u_model_view_proj")?;
+            let color_location = get("u_color")?;
+            let texture_location = get("u_texture")?;
+            let uv_scale_location = get("u_uv_scale")?;
+            let uv_offset_location = get("u_uv_offset")?;
+            let edge_fade_location = get("u_edge_fade")?;
+            let corner_tint_location = get("u_corner_tint")?;
+            let material_location = get("u_material")?;
+            let material_params_location = get("u_material_params")?;
+            let glow_location = get("u_glow")?;
 
+            Ok((program, mvp_location, color_location, texture_location, uv_scale_location, uv_offset_location, edge_fade_location, corner_tint_location, material_location, material_params_location, glow_location))
+        };
+        // A reloaded shader may have dropped a uniform; don't leak the program.
+        locations().inspect_err(|_| gl.delete_program(program))
+    }
+}
+
+fn create_color_program(gl: &glow::Context, vert_src: &str, frag_src: &str, srgb: bool) -> Result<(glow::Program, UniformLocation), String> {
+    let program = link_program(gl, vert_src, frag_src)?;
     unsafe {
-        let gl = glow::Context::from_loader_function_cstr(|s: &CStr| display.get_proc_address(s));
-        Ok((surface, context, gl))
+        set_srgb_uniform(gl, program, srgb);
+        match gl.get_uniform_location(program, "u_proj") {
+            Some(proj_location) => Ok((program, proj_location)),
+            None => {
+                gl.delete_program(program);
+                Err("u_proj".into())
+            }
+        }
     }
 }
 
-fn create_graphics_program(
-    gl: &glow::Context,
-) -> Result<(glow::Program, UniformLocation, UniformLocation, UniformLocation, UniformLocation, UniformLocation, UniformLocation, UniformLocation), String> {
+// `u_srgb` is fixed for the program's lifetime, so it's set once at link time.
+// A shader that doesn't use it simply has no such uniform.
+unsafe fn set_srgb_uniform(gl: &glow::Context, program: glow::Program, srgb: bool) {
+    unsafe {
+        if let Some(location) = gl.get_uniform_location(program, "u_srgb") {
+            gl.use_program(Some(program));
+            gl.uniform_1_i32(Some(&location), srgb as i32);
+            gl.use_program(None);
+        }
+    }
+}
+
+fn create_post_program(gl: &glow::Context, vert_src: &str, frag_src: &str) -> Result<PostProgram, String> {
+    let program = link_program(gl, vert_src, frag_src)?;
+    unsafe {
+        let get = |name: &str| gl.get_uniform_location(program, name).ok_or_else(|| name.to_string());
+        let locations = || -> Result<PostProgram, String> {
+            Ok(PostProgram {
+                program,
+                texture_location: get("u_texture")?,
+                params_location: get("u_params")?,
+                effect_location: get("u_effect")?,
+            })
+        };
+        locations().inspect_err(|_| gl.delete_program(program))
+    }
+}
+
+fn link_program(gl: &glow::Context, vert_src: &str, frag_src: &str) -> Result<glow::Program, String> {
     unsafe {
         let program = gl.create_program()?;
         let compile = |ty, src: &str| -> Result<glow::Shader, String> {
             let sh = gl.create_shader(ty)?;
-            gl.shader_source(sh, src);
+            gl.shader_source(sh, &glsl_source(gl, src));
             gl.compile_shader(sh);
             if !gl.get_shader_compile_status(sh) {
                 let log = gl.get_shader_info_log(sh);
@@ -424,8 +1486,8 @@ fn create_graphics_program(
             Ok(sh)
         };
 
-        let vert = compile(glow::VERTEX_SHADER, include_str!("../shaders/opengl_shader.vert"))?;
-        let frag = compile(glow::FRAGMENT_SHADER, include_str!("../shaders/opengl_shader.frag"))?;
+        let vert = compile(glow::VERTEX_SHADER, vert_src)?;
+        let frag = compile(glow::FRAGMENT_SHADER, frag_src)?;
 
         gl.attach_shader(program, vert);
         gl.attach_shader(program, frag);
@@ -439,17 +1501,7 @@ fn create_graphics_program(
         }
         gl.detach_shader(program, vert); gl.detach_shader(program, frag);
         gl.delete_shader(vert); gl.delete_shader(frag);
-
-        let get = |name: &str| gl.get_uniform_location(program, name).ok_or_else(|| name.to_string());
-        let mvp_location = get("u_model_view_proj")?;
-        let color_location = get("u_color")?;
-        let texture_location = get("u_texture")?;
-        let uv_scale_location = get("u_uv_scale")?;
-        let uv_offset_location = get("u_uv_offset")?;
-        let edge_fade_location = get("u_edge_fade")?;
-        let instanced_location = get("u_instanced")?;
-
-        Ok((program, mvp_location, color_location, texture_location, uv_scale_location, uv_offset_location, edge_fade_location, instanced_location))
+        Ok(program)
     }
 }
 
@@ -0,0 +1,649 @@
+//! A CPU rasterizer for render lists, with no GPU or window behind it. Frames
+//! land in an `RgbaImage`, so screens and gameplay can be golden-image tested in CI.
+//! - Sprites sample their texture bilinearly with clamp-to-edge like the GPU
+//!   backends, with edge fade, corner tint, materials, blend modes, stencil masks
+//!   and clip rects
+//! - Polylines and meshes go through the same tessellation as the GPU backends
+//! - Post effects run over the finished frame; render scale, MSAA and sRGB
+//!   blending are ignored, so frames match an 8-bit non-sRGB framebuffer
+//!
+//! Start one with `gfx::create_headless_backend` and read frames back with
+//! `Backend::request_capture` / `Backend::capture_frame`.
+
+use crate::core::gfx::{
+    clip_rect_px, expand_mesh, tessellate_polyline, BlendMode, ColorVertex, FrameStats, Material, ObjectType, PostEffect,
+    RenderList, Texture as RendererTexture,
+};
+use crate::core::space::{ortho_for_current_screen, ortho_for_size, ortho_for_window};
+use cgmath::{Matrix4, Vector4};
+use image::{Rgba, RgbaImage};
+use log::{info, warn};
+use std::{collections::HashMap, error::Error, sync::{Arc, RwLock}};
+
+// An image in memory. A render target's texture shares its image with the target.
+#[derive(Clone)]
+pub struct Texture(pub Arc<RwLock<RgbaImage>>);
+
+// An offscreen image drawn into by `draw_to_target`; see `create_render_target`.
+pub struct RenderTarget {
+    image: Arc<RwLock<RgbaImage>>,
+}
+
+pub struct State {
+    projection: Matrix4<f32>,
+    frame: RgbaImage,
+    stencil: Vec<u8>,
+    next_upload_id: u64,
+    ready_uploads: Vec<(u64, Texture)>,
+    capture_requested: bool,
+    captured_frame: Option<RgbaImage>,
+}
+
+/// Scissor bounds in pixels, `[x0, y0, x1, y1)` from the top-left.
+type Bounds = [i32; 4];
+
+/// Leaves the current screen space alone; `create_headless_backend` makes it current.
+pub fn init(width: u32, height: u32) -> Result<State, Box<dyn Error>> {
+    if width == 0 || height == 0 {
+        return Err(format!("Software renderer can't draw a {}x{} frame", width, height).into());
+    }
+    info!("Software renderer drawing {}x{} frames.", width, height);
+    Ok(State {
+        projection: ortho_for_size(width, height),
+        frame: RgbaImage::new(width, height),
+        stencil: vec![0; width as usize * height as usize],
+        next_upload_id: 0,
+        ready_uploads: Vec::new(),
+        capture_requested: false,
+        captured_frame: None,
+    })
+}
+
+pub fn create_texture(image: &RgbaImage) -> Texture {
+    Texture(Arc::new(RwLock::new(image.clone())))
+}
+
+/// Copies the `[x, y, width, height]` region of `image` into `texture`, which is `image`'s size.
+pub fn update_texture(texture: &Texture, image: &RgbaImage, region: [u32; 4]) {
+    let [x, y, width, height] = region;
+    let mut target = texture.0.write().unwrap();
+    let target: &mut [u8] = &mut target;
+    let row_bytes = image.width() as usize * 4;
+    for row in y as usize..(y + height) as usize {
+        let span = row * row_bytes + x as usize * 4..row * row_bytes + (x + width) as usize * 4;
+        target[span.clone()].copy_from_slice(&image.as_raw()[span]);
+    }
+}
+
+/// There is nothing to wait for: the texture is ready on the next `poll_texture_uploads`.
+pub fn queue_texture_upload(state: &mut State, image: &RgbaImage) -> u64 {
+    let id = state.next_upload_id;
+    state.next_upload_id += 1;
+    state.ready_uploads.push((id, create_texture(image)));
+    id
+}
+
+pub fn poll_texture_uploads(state: &mut State) -> Vec<(u64, Texture)> {
+    std::mem::take(&mut state.ready_uploads)
+}
+
+pub fn create_render_target(width: u32, height: u32) -> Result<(RenderTarget, Texture), String> {
+    if width == 0 || height == 0 {
+        return Err(format!("Can't create a {}x{} render target", width, height));
+    }
+    let image = Arc::new(RwLock::new(RgbaImage::new(width, height)));
+    Ok((RenderTarget { image: image.clone() }, Texture(image)))
+}
+
+pub fn draw(state: &mut State, render_list: &RenderList, textures: &HashMap<String, RendererTexture>) -> FrameStats {
+    clear(&mut state.frame, &mut state.stencil, render_list.clear_color);
+    let mut stats = draw_objects(&mut state.frame, &mut state.stencil, render_list, textures, &state.projection);
+    for effect in &render_list.post_effects {
+        state.frame = apply_post_effect(&state.frame, *effect);
+        stats.draw_calls += 1;
+        stats.vertices += 3;
+    }
+
+    if std::mem::take(&mut state.capture_requested) {
+        let mut frame = state.frame.clone();
+        // Alpha is whatever blending left behind; a window would show the frame opaque.
+        for px in frame.pixels_mut() {
+            px[3] = 255;
+        }
+        state.captured_frame = Some(frame);
+    }
+    stats
+}
+
+/// Has the next frame kept for `capture_frame`.
+pub fn request_capture(state: &mut State) {
+    state.capture_requested = true;
+}
+
+/// True from `request_capture` until a frame takes the request.
+pub fn capture_pending(state: &State) -> bool {
+    state.capture_requested
+}
+
+/// The image the last captured frame drew, once per `request_capture`.
+pub fn capture_frame(state: &mut State) -> Option<RgbaImage> {
+    state.captured_frame.take()
+}
+
+/// Draws `render_list` into `target`, mapping the current screen space onto the whole target.
+pub fn draw_to_target(
+    target: &RenderTarget,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+) -> Result<FrameStats, Box<dyn Error>> {
+    let (width, height) = target.image.read().map_err(|_| "Render target image is poisoned")?.dimensions();
+    let mut image = RgbaImage::new(width, height);
+    let mut stencil = vec![0; width as usize * height as usize];
+    clear(&mut image, &mut stencil, render_list.clear_color);
+    // The target's own texture may be in `textures`, so nothing is locked for writing until the list is drawn.
+    let stats = draw_objects(&mut image, &mut stencil, render_list, textures, &ortho_for_current_screen());
+    *target.image.write().map_err(|_| "Render target image is poisoned")? = image;
+    Ok(stats)
+}
+
+pub fn resize(state: &mut State, width: u32, height: u32) {
+    if width == 0 || height == 0 {
+        warn!("Ignoring resize to zero dimensions.");
+        return;
+    }
+    state.projection = ortho_for_window(width, height);
+    state.frame = RgbaImage::new(width, height);
+    state.stencil = vec![0; width as usize * height as usize];
+}
+
+pub fn cleanup(state: &mut State) {
+    info!("Cleaning up software renderer...");
+    state.ready_uploads.clear();
+    state.captured_frame = None;
+}
+
+fn clear(frame: &mut RgbaImage, stencil: &mut [u8], c: [f32; 4]) {
+    let px = Rgba(c.map(to_byte));
+    for p in frame.pixels_mut() {
+        *p = px;
+    }
+    stencil.fill(0);
+}
+
+/// Draws the objects in list order (already sorted by `batch::sort`). Every
+/// drawn object counts as one draw call; nothing is batched.
+fn draw_objects(
+    frame: &mut RgbaImage,
+    stencil: &mut [u8],
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+    projection: &Matrix4<f32>,
+) -> FrameStats {
+    let (width, height) = frame.dimensions();
+    let mut stats = FrameStats::default();
+    let mut last_texture: Option<*const RwLock<RgbaImage>> = None;
+    let mut shape_vertices: Vec<ColorVertex> = Vec::new();
+
+    for obj in &render_list.objects {
+        let bounds: Bounds = match obj.clip {
+            Some(c) => {
+                let [x, y, w, h] = clip_rect_px(c, projection, width, height, true);
+                [x, y, x + w, y + h]
+            }
+            None => [0, 0, width as i32, height as i32],
+        };
+        let (mask, blend_mode) = (obj.mask, obj.blend);
+        let mut plot = |x: u32, y: u32, color: [f32; 4]| {
+            let i = y as usize * width as usize + x as usize;
+            if mask != 0 && stencil[i] != mask {
+                return;
+            }
+            let dst = frame.get_pixel_mut(x, y);
+            *dst = Rgba(blend(blend_mode, color, dst.0.map(from_byte)).map(to_byte));
+        };
+
+        match &obj.object_type {
+            ObjectType::Sprite { texture_id, tint, uv_scale, uv_offset, edge_fade, corner_tint, material, glow } => {
+                // Sprites whose texture isn't loaded are skipped, as on the GPU.
+                let Some(RendererTexture::Software(texture)) = textures.get(texture_id) else {
+                    continue;
+                };
+                let Ok(image) = texture.0.read() else {
+                    continue;
+                };
+                if last_texture != Some(Arc::as_ptr(&texture.0)) {
+                    last_texture = Some(Arc::as_ptr(&texture.0));
+                    stats.texture_binds += 1;
+                }
+                let params = material.params();
+                let mvp = projection * obj.transform;
+                cover_quad(&mvp, (width, height), bounds, |x, y, q| {
+                    let uv = [q[0] * uv_scale[0] + uv_offset[0], q[1] * uv_scale[1] + uv_offset[1]];
+                    let s = sample(&image, uv);
+                    let corner = lerp4(lerp4(corner_tint[0], corner_tint[1], q[0]), lerp4(corner_tint[2], corner_tint[3], q[0]), q[1]);
+                    let color = std::array::from_fn(|c| s[c] * tint[c] * corner[c]);
+                    let mut color = apply_glow(apply_material(material, color, params), s[3], *glow);
+                    color[3] *= edge_fade_factor(q, *edge_fade);
+                    plot(x, y, color);
+                });
+                stats.vertices += 4;
+                stats.draw_calls += 1;
+                stats.instances += 1;
+            }
+            ObjectType::Polyline { .. } | ObjectType::Mesh { .. } => {
+                shape_vertices.clear();
+                if let ObjectType::Polyline { points, colors, thickness } = &obj.object_type {
+                    tessellate_polyline(points, colors, *thickness, &obj.transform, &mut shape_vertices);
+                } else if let ObjectType::Mesh { vertices, indices, colors } = &obj.object_type {
+                    expand_mesh(vertices, indices, colors, &obj.transform, &mut shape_vertices);
+                }
+                if shape_vertices.is_empty() {
+                    continue;
+                }
+                for tri in shape_vertices.chunks_exact(3) {
+                    let px = [0, 1, 2].map(|i| to_pixel(projection, tri[i].pos, (width, height)));
+                    cover_triangle(px, bounds, |x, y, w| {
+                        let color = std::array::from_fn(|c| tri[0].color[c] * w[0] + tri[1].color[c] * w[1] + tri[2].color[c] * w[2]);
+                        plot(x, y, color);
+                    });
+                }
+                stats.vertices += shape_vertices.len() as u32;
+                stats.draw_calls += 1;
+            }
+            ObjectType::Mask { id } => {
+                let mvp = projection * obj.transform;
+                cover_quad(&mvp, (width, height), bounds, |x, y, _| {
+                    stencil[y as usize * width as usize + x as usize] = *id;
+                });
+                stats.vertices += 4;
+                stats.draw_calls += 1;
+                stats.instances += 1;
+            }
+            // Resolved into glyph sprites before the list reaches a backend.
+            ObjectType::Text { .. } => {}
+        }
+    }
+    stats
+}
+
+fn to_pixel(mvp: &Matrix4<f32>, p: [f32; 2], size: (u32, u32)) -> [f32; 2] {
+    let c = mvp * Vector4::new(p[0], p[1], 0.0, 1.0);
+    [(c.x / c.w + 1.0) * 0.5 * size.0 as f32, (1.0 - c.y / c.w) * 0.5 * size.1 as f32]
+}
+
+/// Calls `plot` with the quad coordinates (0..1, top-left origin, as the
+/// sprite shader's `v_quad`) of every pixel center inside the unit quad mapped
+/// by `mvp`. Edges are half-open so quads sharing an edge don't overlap.
+fn cover_quad(mvp: &Matrix4<f32>, size: (u32, u32), bounds: Bounds, mut plot: impl FnMut(u32, u32, [f32; 2])) {
+    let o = to_pixel(mvp, [0.0, 0.0], size);
+    let (px, py) = (to_pixel(mvp, [1.0, 0.0], size), to_pixel(mvp, [0.0, 1.0], size));
+    let (ax, ay) = ([px[0] - o[0], px[1] - o[1]], [py[0] - o[0], py[1] - o[1]]);
+    let det = ax[0] * ay[1] - ax[1] * ay[0];
+    if det.abs() < 1e-6 {
+        return;
+    }
+    let corners = [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]].map(|c| to_pixel(mvp, c, size));
+    let Some([x0, y0, x1, y1]) = pixel_span(&corners, bounds) else {
+        return;
+    };
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let d = [x as f32 + 0.5 - o[0], y as f32 + 0.5 - o[1]];
+            let lx = (d[0] * ay[1] - d[1] * ay[0]) / det;
+            let ly = (ax[0] * d[1] - ax[1] * d[0]) / det;
+            if lx < -0.5 || lx >= 0.5 || ly <= -0.5 || ly > 0.5 {
+                continue;
+            }
+            plot(x as u32, y as u32, [lx + 0.5, 0.5 - ly]);
+        }
+    }
+}
+
+/// Calls `plot` with the barycentric weights of every pixel center inside the
+/// triangle `v` (pixel space). Shared edges go to exactly one of the two
+/// triangles, so triangle lists blend without seams.
+fn cover_triangle(v: [[f32; 2]; 3], bounds: Bounds, mut plot: impl FnMut(u32, u32, [f32; 3])) {
+    let edge = |a: [f32; 2], b: [f32; 2], p: [f32; 2]| (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0]);
+    let area = edge(v[0], v[1], v[2]);
+    if area.abs() < 1e-8 {
+        return;
+    }
+    // Ties go to "top-left" edges, judged in a consistent winding.
+    let owns = |a: [f32; 2], b: [f32; 2]| {
+        let (dx, dy) = if area > 0.0 { (b[0] - a[0], b[1] - a[1]) } else { (a[0] - b[0], a[1] - b[1]) };
+        dy < 0.0 || (dy == 0.0 && dx > 0.0)
+    };
+    let owned = [owns(v[1], v[2]), owns(v[2], v[0]), owns(v[0], v[1])];
+    let Some([x0, y0, x1, y1]) = pixel_span(&v, bounds) else {
+        return;
+    };
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let w = [edge(v[1], v[2], p) / area, edge(v[2], v[0], p) / area, edge(v[0], v[1], p) / area];
+            if (0..3).all(|i| w[i] > 0.0 || (w[i] == 0.0 && owned[i])) {
+                plot(x as u32, y as u32, w);
+            }
+        }
+    }
+}
+
+/// The pixel rows and columns the bounding box of `points` touches, within `bounds`.
+fn pixel_span(points: &[[f32; 2]], bounds: Bounds) -> Option<Bounds> {
+    let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+    for p in points {
+        if !p[0].is_finite() || !p[1].is_finite() {
+            return None;
+        }
+        for a in 0..2 {
+            min[a] = min[a].min(p[a]);
+            max[a] = max[a].max(p[a]);
+        }
+    }
+    let span = [
+        (min[0].floor() as i32).max(bounds[0]),
+        (min[1].floor() as i32).max(bounds[1]),
+        (max[0].ceil() as i32).min(bounds[2]),
+        (max[1].ceil() as i32).min(bounds[3]),
+    ];
+    (span[0] < span[2] && span[1] < span[3]).then_some(span)
+}
+
+/// Bilinear sample with clamp-to-edge addressing; `uv` (0, 0) is the top-left texel corner.
+fn sample(image: &RgbaImage, uv: [f32; 2]) -> [f32; 4] {
+    let (w, h) = image.dimensions();
+    if w == 0 || h == 0 {
+        return [0.0; 4];
+    }
+    let (x, y) = (uv[0] * w as f32 - 0.5, uv[1] * h as f32 - 0.5);
+    let (fx, fy) = (x - x.floor(), y - y.floor());
+    let texel = |tx: f32, ty: f32| {
+        let px = image.get_pixel(tx.clamp(0.0, (w - 1) as f32) as u32, ty.clamp(0.0, (h - 1) as f32) as u32);
+        px.0.map(from_byte)
+    };
+    let (x0, y0) = (x.floor(), y.floor());
+    let top = lerp4(texel(x0, y0), texel(x0 + 1.0, y0), fx);
+    let bottom = lerp4(texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0), fx);
+    lerp4(top, bottom, fy)
+}
+
+/// `q` in quad space (0 = left/top, 1 = right/bottom); `e` is (left, right, top, bottom).
+fn edge_fade_factor(q: [f32; 2], e: [f32; 4]) -> f32 {
+    let mut f = 1.0;
+    if e[0] > 0.0 { f *= (q[0] / e[0]).clamp(0.0, 1.0); }
+    if e[1] > 0.0 { f *= ((1.0 - q[0]) / e[1]).clamp(0.0, 1.0); }
+    if e[2] > 0.0 { f *= (q[1] / e[2]).clamp(0.0, 1.0); }
+    if e[3] > 0.0 { f *= ((1.0 - q[1]) / e[3]).clamp(0.0, 1.0); }
+    f
+}
+
+// Mirrors `applyGlow` in sprite_material.glsl: the glow color over `color`
+// through the texel's alpha.
+fn apply_glow(color: [f32; 4], texel_alpha: f32, glow: [f32; 4]) -> [f32; 4] {
+    let g = texel_alpha * glow[3];
+    let a = g + color[3] * (1.0 - g);
+    if a <= 0.0 {
+        return color;
+    }
+    std::array::from_fn(|c| match c {
+        3 => a,
+        _ => (glow[c] * g + color[c] * color[3] * (1.0 - g)) / a,
+    })
+}
+
+// Mirrors `applyMaterial` in sprite_material.glsl.
+fn apply_material(material: &Material, mut color: [f32; 4], params: [f32; 4]) -> [f32; 4] {
+    match material {
+        Material::Standard => {}
+        Material::HsvShift { .. } => {
+            let [h, s, v] = rgb_to_hsv([color[0], color[1], color[2]]);
+            let h = (h + params[0]).rem_euclid(1.0);
+            let s = (s * params[1]).clamp(0.0, 1.0);
+            let [r, g, b] = hsv_to_rgb([h, s, v * params[2]]);
+            color = [r, g, b, color[3]];
+        }
+        Material::Grayscale { .. } => {
+            let luma = luma([color[0], color[1], color[2]]);
+            for c in &mut color[..3] {
+                *c += (luma - *c) * params[0];
+            }
+        }
+        Material::Glow { .. } => {
+            for (c, p) in color[..3].iter_mut().zip(params) {
+                *c += p * params[3];
+            }
+        }
+    }
+    color
+}
+
+fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let d = max - r.min(g).min(b);
+    if d <= 0.0 {
+        return [0.0, 0.0, max];
+    }
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    [h / 6.0, d / max, max]
+}
+
+fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let channel = |k: f32| {
+        let p = ((h + k).fract() * 6.0 - 3.0).abs();
+        v * (1.0 + s * ((p - 1.0).clamp(0.0, 1.0) - 1.0))
+    };
+    [channel(1.0), channel(2.0 / 3.0), channel(1.0 / 3.0)]
+}
+
+fn luma([r, g, b]: [f32; 3]) -> f32 {
+    r * 0.299 + g * 0.587 + b * 0.114
+}
+
+/// Blends straight-alpha `src` onto `dst` with the GPU backends' blend equations.
+fn blend(mode: BlendMode, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+    let a = src[3];
+    let out: [f32; 4] = std::array::from_fn(|c| match mode {
+        BlendMode::Alpha => src[c] * a + dst[c] * (1.0 - a),
+        BlendMode::Add => src[c] * a + dst[c],
+        BlendMode::Multiply => src[c] * dst[c],
+        BlendMode::Subtract => dst[c] - src[c],
+    });
+    out.map(|c| c.clamp(0.0, 1.0))
+}
+
+// Mirrors the post shaders; see `PostEffect`.
+fn apply_post_effect(frame: &RgbaImage, effect: PostEffect) -> RgbaImage {
+    let (w, h) = frame.dimensions();
+    let (_, params) = effect.shader_params();
+    RgbaImage::from_fn(w, h, |x, y| {
+        // uv runs bottom-up as in GL; every effect but the scanlines is symmetric.
+        let screen_uv = [(x as f32 + 0.5) / w as f32, 1.0 - (y as f32 + 0.5) / h as f32];
+        let mut uv = screen_uv;
+        if let PostEffect::Crt { .. } = effect {
+            let c = [uv[0] * 2.0 - 1.0, uv[1] * 2.0 - 1.0];
+            let k = 1.0 + params[1] * (c[0] * c[0] + c[1] * c[1]);
+            uv = [c[0] * k * 0.5 + 0.5, c[1] * k * 0.5 + 0.5];
+            if uv.iter().any(|v| !(0.0..=1.0).contains(v)) {
+                return Rgba([0, 0, 0, 255]);
+            }
+        }
+        let s = sample(frame, [uv[0], 1.0 - uv[1]]);
+        let mut color = [s[0], s[1], s[2]];
+        match effect {
+            PostEffect::Vignette { .. } => {
+                let (dx, dy) = (screen_uv[0] - 0.5, screen_uv[1] - 0.5);
+                let d = (dx * dx + dy * dy).sqrt() * std::f32::consts::SQRT_2;
+                let t = ((d - 0.3) / 0.7).clamp(0.0, 1.0);
+                let fade = 1.0 - params[0] * t * t * (3.0 - 2.0 * t);
+                color = color.map(|c| c * fade);
+            }
+            PostEffect::Crt { .. } => {
+                // GL counts rows from the bottom.
+                if (h - 1 - y) % 2 == 1 {
+                    color = color.map(|c| c * (1.0 - params[0]));
+                }
+            }
+            PostEffect::Gamma { .. } => color = color.map(|c| c.powf(1.0 / params[0])),
+            PostEffect::Desaturate { .. } => {
+                let luma = luma(color);
+                color = color.map(|c| c + (luma - c) * params[0]);
+            }
+        }
+        Rgba([to_byte(color[0]), to_byte(color[1]), to_byte(color[2]), 255])
+    })
+}
+
+fn lerp4(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+fn from_byte(c: u8) -> f32 {
+    c as f32 / 255.0
+}
+
+fn to_byte(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::gfx::{create_headless_backend, BlendMode, Material, ObjectType, PostEffect, RenderList, RenderObject};
+    use cgmath::{Matrix4, Vector3};
+    use image::{Rgba, RgbaImage};
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    // 4:3, so the screen space is 640x480 units and each pixel is 4x4 of them.
+    const WIDTH: u32 = 160;
+    const HEIGHT: u32 = 120;
+    // Rewritten by running the tests with `UPDATE_GOLDEN=1` after an intended change.
+    const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/core/gfx/backends/golden/software_scene.png");
+    // Per channel, for float differences between targets.
+    const TOLERANCE: u8 = 2;
+
+    fn at(x: f32, y: f32, w: f32, h: f32) -> Matrix4<f32> {
+        Matrix4::from_translation(Vector3::new(x, y, 0.0)) * Matrix4::from_nonuniform_scale(w, h, 1.0)
+    }
+
+    fn sprite(transform: Matrix4<f32>) -> RenderObject {
+        RenderObject {
+            object_type: ObjectType::Sprite {
+                texture_id: "checker".to_string(),
+                tint: [1.0; 4],
+                uv_scale: [1.0, 1.0],
+                uv_offset: [0.0, 0.0],
+                edge_fade: [0.0; 4],
+                corner_tint: [[1.0; 4]; 4],
+                material: Material::Standard,
+                glow: [0.0; 4],
+            },
+            transform,
+            blend: BlendMode::Alpha,
+            z: 0,
+            order: 0,
+            mask: 0,
+            clip: None,
+            group: 0,
+        }
+    }
+
+    fn with_sprite(mut obj: RenderObject, f: impl FnOnce(&mut [f32; 4], &mut [[f32; 4]; 4], &mut Material, &mut [f32; 4])) -> RenderObject {
+        if let ObjectType::Sprite { tint, corner_tint, material, glow, .. } = &mut obj.object_type {
+            f(tint, corner_tint, material, glow);
+        }
+        obj
+    }
+
+    // Every object type and sprite feature the software renderer draws, laid out so none hides another.
+    fn scene() -> RenderList {
+        let objects = vec![
+            sprite(at(-220.0, 130.0, 160.0, 160.0)),
+            with_sprite(RenderObject { blend: BlendMode::Add, ..sprite(at(-150.0, 80.0, 120.0, 120.0)) }, |tint, corners, _, _| {
+                *tint = [1.0, 0.6, 0.2, 0.7];
+                corners[1] = [0.2, 0.2, 1.0, 1.0];
+                corners[2] = [1.0, 1.0, 1.0, 0.0];
+            }),
+            with_sprite(sprite(at(20.0, 130.0, 140.0, 140.0)), |_, _, material, _| {
+                *material = Material::HsvShift { hue: 0.33, saturation: 1.0, value: 0.9 };
+            }),
+            with_sprite(RenderObject { clip: Some([150.0, 60.0, 300.0, 220.0]), ..sprite(at(200.0, 130.0, 180.0, 180.0)) }, |_, _, material, glow| {
+                *material = Material::Grayscale { amount: 1.0 };
+                *glow = [1.0, 0.0, 0.0, 0.4];
+            }),
+            RenderObject { object_type: ObjectType::Mask { id: 1 }, ..sprite(at(-180.0, -120.0, 120.0, 80.0)) },
+            RenderObject { mask: 1, blend: BlendMode::Multiply, ..sprite(at(-180.0, -120.0, 200.0, 200.0)) },
+            RenderObject {
+                object_type: ObjectType::Polyline {
+                    points: vec![[-0.5, -0.5], [-0.2, 0.5], [0.1, -0.5], [0.5, 0.3]],
+                    colors: vec![[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 0.8]],
+                    thickness: 12.0,
+                },
+                ..sprite(at(40.0, -110.0, 200.0, 160.0))
+            },
+            RenderObject {
+                object_type: ObjectType::Mesh {
+                    vertices: vec![[0.0, 0.5], [-0.5, -0.5], [0.5, -0.5], [0.5, 0.5]],
+                    indices: vec![0, 1, 2, 0, 2, 3],
+                    colors: vec![[1.0, 1.0, 0.0, 1.0], [0.0, 1.0, 1.0, 0.6], [1.0, 0.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.3]],
+                },
+                blend: BlendMode::Subtract,
+                ..sprite(at(230.0, -120.0, 140.0, 140.0))
+            },
+        ];
+        RenderList {
+            clear_color: [0.1, 0.12, 0.2, 1.0],
+            objects,
+            post_effects: vec![PostEffect::Vignette { strength: 0.5 }],
+            labels: None,
+        }
+    }
+
+    // 8x8 cells of two colors, translucent in one corner so blending shows.
+    fn checker() -> RgbaImage {
+        RgbaImage::from_fn(32, 32, |x, y| match ((x / 8 + y / 8) % 2, x < 8 && y < 8) {
+            (_, true) => Rgba([255, 255, 255, 96]),
+            (0, _) => Rgba([230, 200, 40, 255]),
+            _ => Rgba([40, 90, 200, 255]),
+        })
+    }
+
+    fn render() -> RgbaImage {
+        let mut backend = create_headless_backend(WIDTH, HEIGHT).unwrap();
+        let mut textures = HashMap::new();
+        textures.insert("checker".to_string(), backend.create_texture(&checker()).unwrap());
+        backend.request_capture();
+        backend.draw(&scene(), &textures).unwrap();
+        backend.capture_frame().unwrap()
+    }
+
+    #[test]
+    fn scene_matches_golden_image() {
+        let frame = render();
+        let golden = Path::new(GOLDEN);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(golden.parent().unwrap()).unwrap();
+            frame.save(golden).unwrap();
+            return;
+        }
+        let expected = image::open(golden).expect("missing golden image; run with UPDATE_GOLDEN=1").to_rgba8();
+        assert_eq!(frame.dimensions(), expected.dimensions());
+        let off = frame
+            .enumerate_pixels()
+            .filter(|&(x, y, p)| p.0.iter().zip(expected.get_pixel(x, y).0).any(|(a, b)| a.abs_diff(b) > TOLERANCE))
+            .count();
+        assert_eq!(off, 0, "{} pixels differ from {}", off, GOLDEN);
+    }
+
+    #[test]
+    fn headless_backend_sets_screen_space() {
+        let _backend = create_headless_backend(WIDTH, HEIGHT).unwrap();
+        assert_eq!(crate::core::space::screen_width(), 640.0);
+        assert_eq!(crate::core::space::screen_height(), 480.0);
+    }
+}
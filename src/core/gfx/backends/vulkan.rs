@@ -1,5 +1,11 @@
-use crate::core::gfx::{BlendMode, ObjectType, RenderList, Texture as RendererTexture};
-use crate::core::space::ortho_for_window;
+use crate::core::gfx::{
+    clip_rect_px, expand_mesh, hdr_output_color, hdr_output_pass, render_size, tessellate_polyline, BlendMode, ColorVertex,
+    DebugLabels, FrameStats, HdrOutput, ObjectType, PostEffect, PresentMode, PresentSettings, RenderList, Texture as RendererTexture,
+    UpscaleFilter, BLEND_MODES, MATERIAL_COUNT, MAX_FRAMES_IN_FLIGHT, POST_COPY, srgb_to_linear,
+};
+use crate::core::gfx::batch::{self, BatchKind};
+use crate::core::gfx::ktx2::{CompressedFormat, CompressedImage};
+use crate::core::space::{ortho_for_current_screen, ortho_for_size, ortho_for_window};
 use ash::{
     khr::{surface, swapchain},
     vk, Device, Entry, Instance,
@@ -7,7 +13,7 @@ use ash::{
 use cgmath::Matrix4;
 use image::RgbaImage;
 use log::{debug, error, info, warn};
-use std::{collections::HashMap, error::Error, ffi, mem, sync::Arc};
+use std::{collections::{HashMap, VecDeque}, error::Error, ffi, fs, mem, path::Path, sync::{Arc, Mutex}};
 use winit::{
     dpi::PhysicalSize,
     raw_window_handle::{HasDisplayHandle, HasWindowHandle},
@@ -15,7 +21,7 @@ use winit::{
 };
 
 // --- Constants ---
-const MAX_FRAMES_IN_FLIGHT: usize = 3;
+const PIPELINE_CACHE_PATH: &str = "cache/vulkan_pipeline_cache.bin";
 
 // --- Structs ---
 
@@ -27,14 +33,16 @@ struct ProjPush {
 #[repr(C)]
 #[derive(Clone, Copy)]
 struct InstanceData {
-    // 72 bytes total
-    center:     [f32; 2], // offset 0
-    size:       [f32; 2], // offset 8
-    rot_sin_cos:[f32; 2], // offset 16  (sin, cos)
-    tint:       [f32; 4], // offset 24
-    uv_scale:   [f32; 2], // offset 40
-    uv_offset:  [f32; 2], // offset 48
-    edge_fade:  [f32; 4], // offset 56
+    // 152 bytes total
+    model:      [[f32; 4]; 4], // offset 0   (column-major model matrix)
+    tint:       [f32; 4], // offset 64
+    uv_scale:   [f32; 2], // offset 80
+    uv_offset:  [f32; 2], // offset 88
+    edge_fade:  [f32; 4], // offset 96
+    corner_tint:[u32; 4], // offset 112 (TL, TR, BL, BR as packed RGBA8 unorm)
+    material:   [f32; 4], // offset 128 (Material::params)
+    texture:    u32,      // offset 144 (bindless slot; unused by the per-texture pipelines)
+    glow:       u32,      // offset 148 (packed RGBA8 unorm)
 }
 
 struct PipelinePair {
@@ -42,6 +50,38 @@ struct PipelinePair {
     pipe: vk::Pipeline,
 }
 
+// SPIR-V for a pipeline's vertex and fragment stages.
+struct ShaderCode<'a> {
+    vert: &'a [u8],
+    frag: &'a [u8],
+}
+
+// Compiled by build.rs.
+const SPRITE_SHADERS: ShaderCode<'static> = ShaderCode {
+    vert: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_shader.vert.spv")),
+    frag: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_shader.frag.spv")),
+};
+// The sprite fragment shader built with BINDLESS: samples the instance's slot.
+const BINDLESS_SPRITE_SHADERS: ShaderCode<'static> = ShaderCode {
+    vert: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_shader.vert.spv")),
+    frag: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_shader_bindless.frag.spv")),
+};
+const COLOR_SHADERS: ShaderCode<'static> = ShaderCode {
+    vert: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_color.vert.spv")),
+    frag: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_color.frag.spv")),
+};
+const POST_SHADERS: ShaderCode<'static> = ShaderCode {
+    vert: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_post.vert.spv")),
+    frag: include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_post.frag.spv")),
+};
+
+// Push constants for one post effect; see `PostEffect::shader_params`.
+#[repr(C)]
+struct PostPush {
+    params: [f32; 4],
+    effect: u32,
+}
+
 // A handle to a Vulkan texture on the GPU.
 pub struct Texture {
     device: Arc<Device>,
@@ -50,6 +90,8 @@ pub struct Texture {
     view: vk::ImageView,
     pub descriptor_set: vk::DescriptorSet,
     pool: vk::DescriptorPool,
+    // None without descriptor indexing, or once every slot is taken.
+    bindless_slot: Option<BindlessSlot>,
 }
 
 impl Drop for Texture {
@@ -63,19 +105,94 @@ impl Drop for Texture {
     }
 }
 
+// A texture's index in `Bindless::set`, handed back to the free list on drop.
+struct BindlessSlot {
+    index: u32,
+    free: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Drop for BindlessSlot {
+    fn drop(&mut self) {
+        self.free.lock().unwrap().push(self.index);
+    }
+}
+
+// Most slots any device gets; plenty for a noteskin, fonts and the UI.
+const MAX_BINDLESS_TEXTURES: u32 = 4096;
+
+/// Every texture also written into one partially bound descriptor array, with
+/// each instance carrying its slot, so sprites drawn with different textures
+/// still share a draw call. Needs Vulkan 1.2 descriptor indexing.
+struct Bindless {
+    set_layout: vk::DescriptorSetLayout,
+    pool: vk::DescriptorPool,
+    set: vk::DescriptorSet,
+    pipelines: Vec<PipelinePair>,
+    free: Arc<Mutex<Vec<u32>>>,
+}
+
 struct BufferResource {
     buffer: vk::Buffer,
     memory: vk::DeviceMemory,
 }
 
+// An image that only lives as a framebuffer attachment: the stencil buffer used
+// for clip masks, or the multisampled color buffer that is resolved at the end
+// of the pass. Sized to its framebuffer.
+struct Attachment {
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    view: vk::ImageView,
+}
+
+// An offscreen framebuffer drawing into a texture's image; see `create_render_target`.
+pub struct RenderTarget {
+    device: Arc<Device>,
+    framebuffer: vk::Framebuffer,
+    stencil: Attachment,
+    msaa_color: Option<Attachment>,
+    extent: vk::Extent2D,
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_framebuffer(self.framebuffer, None);
+        }
+        destroy_attachment(&self.device, &self.stencil);
+        if let Some(color) = &self.msaa_color {
+            destroy_attachment(&self.device, color);
+        }
+    }
+}
+
+// A second window sharing the device, pipelines and textures; see
+// `create_secondary_window`. Only one of its frames is in flight at a time.
+pub struct SecondaryWindow {
+    surface: vk::SurfaceKHR,
+    swapchain_resources: SwapchainResources,
+    window_size: PhysicalSize<u32>,
+    cmd: vk::CommandBuffer,
+    image_available: vk::Semaphore,
+    render_finished: vk::Semaphore,
+    fence: vk::Fence,
+    // Vertex buffers of the last frame, freed once `fence` has signaled.
+    buffers: Vec<BufferResource>,
+}
+
 struct SwapchainResources {
     swapchain_loader: swapchain::Device,
     swapchain: vk::SwapchainKHR,
-    _images: Vec<vk::Image>,
+    images: Vec<vk::Image>,
     image_views: Vec<vk::ImageView>,
     framebuffers: Vec<vk::Framebuffer>,
     extent: vk::Extent2D,
     format: vk::SurfaceFormatKHR,
+    stencil: Attachment,
+    // Drawn into instead of the swapchain image when MSAA is on.
+    msaa_color: Option<Attachment>,
+    // The surface lets images be copied out and they're 8-bit, so frames can be captured.
+    capturable: bool,
 }
 
 // The main Vulkan state struct, now simplified.
@@ -89,16 +206,38 @@ pub struct State {
     pub pdevice: vk::PhysicalDevice,
     pub device: Option<Arc<Device>>,
     pub queue: vk::Queue,
+    queue_family_index: u32,
     pub command_pool: vk::CommandPool,
     swapchain_resources: SwapchainResources,
     render_pass: vk::RenderPass,
-    sprite_pipeline_layout: vk::PipelineLayout,
-    sprite_pipeline: vk::Pipeline,
+    // Same attachments as `render_pass` (so the pipelines work in both), but
+    // the color image ends up ready for sampling instead of presenting.
+    target_render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
+    // One per blend mode and material, by `sprite_pipeline_index`.
+    sprite_pipelines: Vec<PipelinePair>,
+    // One per blend mode, by `BlendMode::index`.
+    color_pipelines: Vec<PipelinePair>,
+    mask_pipeline_layout: vk::PipelineLayout,
+    mask_pipeline: vk::Pipeline,
+    // Fullscreen pass for `RenderList::post_effects`.
+    post_pipeline_layout: vk::PipelineLayout,
+    post_pipeline: vk::Pipeline,
+    // Targets at the render resolution the post chain ping-pongs between; made
+    // on first use and remade when that size or the upscale filter changes.
+    post_targets: Vec<(RenderTarget, Texture)>,
+    post_filter: UpscaleFilter,
+    stencil_format: vk::Format,
+    // MSAA sample count shared by every pass and pipeline.
+    samples: vk::SampleCountFlags,
     vertex_buffer: Option<BufferResource>,
     index_buffer: Option<BufferResource>,
     pub descriptor_set_layout: vk::DescriptorSetLayout,
     pub descriptor_pool: vk::DescriptorPool,
+    bindless: Option<Bindless>,
     pub sampler: vk::Sampler,
+    // Samples the post targets under `UpscaleFilter::Nearest`.
+    nearest_sampler: vk::Sampler,
     command_buffers: Vec<vk::CommandBuffer>,
     image_available_semaphores: Vec<vk::Semaphore>,
     render_finished_semaphores: Vec<vk::Semaphore>,
@@ -106,16 +245,92 @@ pub struct State {
     images_in_flight: Vec<vk::Fence>,
     current_frame: usize,
     window_size: PhysicalSize<u32>,
-    vsync_enabled: bool,
+    present: PresentSettings,
     projection: Matrix4<f32>,
     instance_ring: Option<BufferResource>,       // one big VB for all frames
     instance_ring_ptr: *mut InstanceData,        // persistently mapped pointer
     instance_capacity_instances: usize,          // total instances across ring
     per_frame_stride_instances: usize,           // instances reserved per frame
+    line_ring: Option<BufferResource>,           // polyline vertices, same per-frame slicing
+    line_ring_ptr: *mut ColorVertex,
+    line_capacity_vertices: usize,
+    per_frame_stride_vertices: usize,
+    // VK_GOOGLE_display_timing, when the driver has it.
+    display_timing: Option<ash::google::display_timing::Device>,
+    // VK_EXT_debug_utils command labels, when the loader has the extension.
+    debug_utils: Option<ash::ext::debug_utils::Device>,
+    present_timing: PresentTiming,
+    // None when the queue can't write timestamps.
+    gpu_timer: Option<GpuTimer>,
+    uploads: Uploads,
+    // Set by `request_capture`; the next frame copies its image out for `capture_frame`.
+    capture_requested: bool,
+    captured_frame: Option<RgbaImage>,
+}
+
+/// Async texture uploads. Pixels are staged in a persistently mapped ring and
+/// copied by a submission of their own with a fence, so nothing waits on the queue.
+#[derive(Default)]
+struct Uploads {
+    ring: Option<(BufferResource, *mut u8)>,
+    // Bytes ever staged; `% UPLOAD_RING_SIZE` is the ring offset.
+    head: u64,
+    next_id: u64,
+    in_flight: VecDeque<InFlightUpload>,
+    // Finished (or done synchronously) but not yet collected.
+    done: Vec<(u64, Texture)>,
+}
+
+struct InFlightUpload {
+    id: u64,
+    fence: vk::Fence,
+    cmd: vk::CommandBuffer,
+    // Where its pixels start in the ring; everything from here to `head` is still in use.
+    ring_start: u64,
+    // None for an `update_texture` copy into a texture that's already out.
+    texture: Option<Texture>,
+}
+
+const UPLOAD_RING_SIZE: u64 = 32 * 1024 * 1024;
+// A safe bufferOffset for every format uploaded through the ring.
+const UPLOAD_ALIGNMENT: u64 = 16;
+
+/// Presents tagged with an id and the host time they were queued, waiting for
+/// the driver to report when they actually reached the display.
+#[derive(Default)]
+struct PresentTiming {
+    next_id: u32,
+    pending: VecDeque<(u32, u64)>,
+    // Smoothed queue-to-display time, in seconds.
+    latency: Option<f32>,
+}
+
+/// Timestamps written at the start and end of each frame's commands, two
+/// queries per frame in flight. A frame's pair is read back the next time its
+/// slot comes round, once its fence says the GPU is done with it.
+struct GpuTimer {
+    pool: vk::QueryPool,
+    // Nanoseconds per timestamp tick.
+    period_ns: f32,
+    // Timestamps wrap at this many bits.
+    valid_bits: u32,
+    enabled: bool,
+    // Frame slots with timestamps written but not yet read.
+    pending: [bool; MAX_FRAMES_IN_FLIGHT],
+    // Smoothed GPU time per frame, in milliseconds.
+    gpu_ms: Option<f32>,
 }
 
+// Weight of each new sample in the smoothed GPU time.
+const GPU_TIME_SMOOTHING: f32 = 0.1;
+
+// Presents older than this without a timing report are given up on.
+const MAX_PENDING_PRESENTS: usize = 64;
+// Weight of each new sample in the smoothed latency.
+const LATENCY_SMOOTHING: f32 = 0.1;
+
 // --- Main Procedural Functions ---
-pub fn init(window: &Window, vsync_enabled: bool) -> Result<State, Box<dyn Error>> {
+pub fn init(window: &Window, present: PresentSettings) -> Result<State, Box<dyn Error>> {
     info!("Initializing Vulkan backend...");
     let entry = Entry::linked();
     let instance = create_instance(&entry, window)?;
@@ -123,12 +338,23 @@ pub fn init(window: &Window, vsync_enabled: bool) -> Result<State, Box<dyn Error
     let surface = create_surface(&entry, &instance, window)?;
     let surface_loader = surface::Instance::new(&entry, &instance);
     let pdevice = select_physical_device(&instance, &surface_loader, surface)?;
-    let (device, queue, queue_family_index) =
+    let (device, queue, queue_family_index, bindless_slots) =
         create_logical_device(&instance, pdevice, &surface_loader, surface)?;
     let device = Some(Arc::new(device));
     let command_pool = create_command_pool(device.as_ref().unwrap(), queue_family_index)?;
+    let display_timing = (monotonic_ns().is_some()
+        && device_supports_extension(&instance, pdevice, ash::google::display_timing::NAME))
+        .then(|| ash::google::display_timing::Device::new(&instance, device.as_ref().unwrap()));
+    let debug_utils = instance_supports_extension(&entry, ash::ext::debug_utils::NAME)
+        .then(|| ash::ext::debug_utils::Device::new(&instance, device.as_ref().unwrap()));
+    if display_timing.is_some() {
+        info!("VK_GOOGLE_display_timing available; measuring queue-to-display latency.");
+    }
+    let gpu_timer = create_gpu_timer(&instance, device.as_ref().unwrap(), pdevice, queue_family_index);
 
     let initial_size = window.inner_size();
+    let stencil_format = find_stencil_format(&instance, pdevice)?;
+    let samples = pick_sample_count(&instance, pdevice, present.msaa_samples);
     let mut swapchain_resources = create_swapchain(
         &instance,
         device.as_ref().unwrap(),
@@ -137,31 +363,101 @@ pub fn init(window: &Window, vsync_enabled: bool) -> Result<State, Box<dyn Error
         &surface_loader,
         initial_size,
         None,
-        vsync_enabled,
+        present,
+        stencil_format,
+        samples,
+    )?;
+    // The swapchain settles for UNORM when the surface has no sRGB format, and
+    // for SDR when it has no format in the HDR color space asked for.
+    let hdr = match hdr_color_space(present.hdr) == Some(swapchain_resources.format.color_space) {
+        true => present.hdr,
+        false => {
+            if present.hdr != HdrOutput::Off {
+                warn!("The display offers no {} output; staying SDR.", present.hdr);
+            }
+            HdrOutput::Off
+        }
+    };
+    let present = PresentSettings { srgb: present.srgb && is_srgb(swapchain_resources.format.format), hdr, ..present };
+    match present.hdr {
+        HdrOutput::Off => info!("Vulkan color: {}.", if present.srgb { "sRGB (linear blending)" } else { "UNORM (gamma blending)" }),
+        hdr => info!("Vulkan color: {} ({:?}), SDR white at {} nits.", hdr, swapchain_resources.format.format, present.hdr_paper_white),
+    }
+    let render_pass = create_render_pass(
+        device.as_ref().unwrap(),
+        swapchain_resources.format.format,
+        stencil_format,
+        samples,
+        vk::ImageLayout::PRESENT_SRC_KHR,
+    )?;
+    let target_render_pass = create_render_pass(
+        device.as_ref().unwrap(),
+        swapchain_resources.format.format,
+        stencil_format,
+        samples,
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
     )?;
-    let render_pass =
-        create_render_pass(device.as_ref().unwrap(), swapchain_resources.format.format)?;
     recreate_framebuffers(device.as_ref().unwrap(), &mut swapchain_resources, render_pass)?;
 
-    let sampler = create_sampler(device.as_ref().unwrap())?;
+    let sampler = create_sampler(device.as_ref().unwrap(), vk::Filter::LINEAR)?;
+    let nearest_sampler = create_sampler(device.as_ref().unwrap(), vk::Filter::NEAREST)?;
     let descriptor_set_layout = create_descriptor_set_layout(device.as_ref().unwrap())?;
     let descriptor_pool = create_descriptor_pool(device.as_ref().unwrap())?;
+    let pipeline_cache = create_pipeline_cache(&instance, device.as_ref().unwrap(), pdevice)?;
 
-    let PipelinePair { layout: sprite_pipeline_layout, pipe: sprite_pipeline } =
+    let sprite_pipelines = create_sprite_pipelines(
+        device.as_ref().unwrap(),
+        render_pass,
+        pipeline_cache,
+        descriptor_set_layout,
+        samples,
+        present.srgb,
+        &SPRITE_SHADERS,
+    )?;
+    let PipelinePair { layout: mask_pipeline_layout, pipe: mask_pipeline } =
         create_sprite_pipeline(
             device.as_ref().unwrap(),
             render_pass,
+            pipeline_cache,
             descriptor_set_layout,
             BlendMode::Alpha,
+            true,
+            0,
+            present.srgb,
+            samples,
+            &SPRITE_SHADERS,
         )?;
+    let color_pipelines =
+        create_color_pipelines(device.as_ref().unwrap(), render_pass, pipeline_cache, samples, present.srgb, &COLOR_SHADERS)?;
+    let PipelinePair { layout: post_pipeline_layout, pipe: post_pipeline } = create_post_pipeline(
+        device.as_ref().unwrap(),
+        render_pass,
+        pipeline_cache,
+        descriptor_set_layout,
+        samples,
+        &POST_SHADERS,
+    )?;
+    let bindless = bindless_slots.and_then(|slots| {
+        match create_bindless(device.as_ref().unwrap(), render_pass, pipeline_cache, samples, present.srgb, slots) {
+            Ok(bindless) => {
+                info!("Vulkan descriptor indexing on; {} texture slots.", slots);
+                Some(bindless)
+            }
+            Err(e) => {
+                warn!("Descriptor indexing unavailable ({}); binding textures per draw.", e);
+                None
+            }
+        }
+    });
 
     let command_buffers =
-        create_command_buffers(device.as_ref().unwrap(), command_pool, MAX_FRAMES_IN_FLIGHT)?;
+        create_command_buffers(device.as_ref().unwrap(), command_pool, present.frames_in_flight)?;
     let (image_available_semaphores, render_finished_semaphores, in_flight_fences) =
-        create_sync_objects(device.as_ref().unwrap())?;
-    let images_in_flight = vec![vk::Fence::null(); swapchain_resources._images.len()];
+        create_sync_objects(device.as_ref().unwrap(), present.frames_in_flight)?;
+    let images_in_flight = vec![vk::Fence::null(); swapchain_resources.images.len()];
 
     let projection = ortho_for_window(initial_size.width, initial_size.height);
+    save_pipeline_cache(device.as_ref().unwrap(), pipeline_cache);
 
     let mut state = State {
         _entry: entry,
@@ -173,16 +469,29 @@ pub fn init(window: &Window, vsync_enabled: bool) -> Result<State, Box<dyn Error
         pdevice,
         device: device.clone(),
         queue,
+        queue_family_index,
         command_pool,
         swapchain_resources,
         render_pass,
-        sprite_pipeline_layout,
-        sprite_pipeline,
+        target_render_pass,
+        pipeline_cache,
+        sprite_pipelines,
+        color_pipelines,
+        mask_pipeline_layout,
+        mask_pipeline,
+        post_pipeline_layout,
+        post_pipeline,
+        post_targets: Vec::new(),
+        post_filter: present.upscale_filter,
+        stencil_format,
+        samples,
         vertex_buffer: None,
         index_buffer: None,
         descriptor_set_layout,
         descriptor_pool,
+        bindless,
         sampler,
+        nearest_sampler,
         command_buffers,
         image_available_semaphores,
         render_finished_semaphores,
@@ -190,12 +499,23 @@ pub fn init(window: &Window, vsync_enabled: bool) -> Result<State, Box<dyn Error
         images_in_flight,
         current_frame: 0,
         window_size: initial_size,
-        vsync_enabled,
+        present,
         projection,
         instance_ring: None,
         instance_ring_ptr: std::ptr::null_mut(),
         instance_capacity_instances: 0,
         per_frame_stride_instances: 0,
+        line_ring: None,
+        line_ring_ptr: std::ptr::null_mut(),
+        line_capacity_vertices: 0,
+        per_frame_stride_vertices: 0,
+        display_timing,
+        debug_utils,
+        present_timing: PresentTiming::default(),
+        gpu_timer,
+        uploads: Uploads::default(),
+        capture_requested: false,
+        captured_frame: None,
     };
 
     // Static unit quad buffers
@@ -221,10 +541,10 @@ pub fn init(window: &Window, vsync_enabled: bool) -> Result<State, Box<dyn Error
     Ok(state)
 }
 
-fn create_sampler(device: &Device) -> Result<vk::Sampler, vk::Result> {
+fn create_sampler(device: &Device, filter: vk::Filter) -> Result<vk::Sampler, vk::Result> {
     let sampler_info = vk::SamplerCreateInfo::default()
-        .mag_filter(vk::Filter::LINEAR)
-        .min_filter(vk::Filter::LINEAR)
+        .mag_filter(filter)
+        .min_filter(filter)
         .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
         .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
         .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
@@ -250,6 +570,91 @@ fn create_descriptor_set_layout(device: &Device) -> Result<vk::DescriptorSetLayo
     unsafe { device.create_descriptor_set_layout(&layout_info, None) }
 }
 
+/// The slot array, its one set and the sprite pipelines that read it. Slots are
+/// written as textures are made, possibly while frames using the set are in
+/// flight, hence update-after-bind and partially bound.
+fn create_bindless(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
+    samples: vk::SampleCountFlags,
+    srgb: bool,
+    slots: u32,
+) -> Result<Bindless, Box<dyn Error>> {
+    let binding = vk::DescriptorSetLayoutBinding::default()
+        .binding(0)
+        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+        .descriptor_count(slots)
+        .stage_flags(vk::ShaderStageFlags::FRAGMENT);
+    let binding_flags = [vk::DescriptorBindingFlags::PARTIALLY_BOUND
+        | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+        | vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING];
+    let mut flags_info = vk::DescriptorSetLayoutBindingFlagsCreateInfo::default().binding_flags(&binding_flags);
+    let layout_info = vk::DescriptorSetLayoutCreateInfo::default()
+        .flags(vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL)
+        .bindings(std::slice::from_ref(&binding))
+        .push_next(&mut flags_info);
+    let set_layout = unsafe { device.create_descriptor_set_layout(&layout_info, None)? };
+
+    let pool_size = vk::DescriptorPoolSize::default()
+        .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+        .descriptor_count(slots);
+    let pool_info = vk::DescriptorPoolCreateInfo::default()
+        .flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
+        .pool_sizes(std::slice::from_ref(&pool_size))
+        .max_sets(1);
+    let pool = match unsafe { device.create_descriptor_pool(&pool_info, None) } {
+        Ok(pool) => pool,
+        Err(e) => {
+            unsafe { device.destroy_descriptor_set_layout(set_layout, None) };
+            return Err(e.into());
+        }
+    };
+    let destroy = || unsafe {
+        device.destroy_descriptor_pool(pool, None);
+        device.destroy_descriptor_set_layout(set_layout, None);
+    };
+
+    let layouts = [set_layout];
+    let alloc_info = vk::DescriptorSetAllocateInfo::default().descriptor_pool(pool).set_layouts(&layouts);
+    let set = match unsafe { device.allocate_descriptor_sets(&alloc_info) } {
+        Ok(sets) => sets[0],
+        Err(e) => {
+            destroy();
+            return Err(e.into());
+        }
+    };
+    let pipelines = create_sprite_pipelines(
+        device, render_pass, pipeline_cache, set_layout, samples, srgb, &BINDLESS_SPRITE_SHADERS,
+    )
+    .inspect_err(|_| destroy())?;
+
+    // Handed out lowest first.
+    let free = (0..slots).rev().collect();
+    Ok(Bindless { set_layout, pool, set, pipelines, free: Arc::new(Mutex::new(free)) })
+}
+
+/// Writes `view` into a free slot of the bindless array, if there is one.
+fn bindless_slot(state: &State, view: vk::ImageView, sampler: vk::Sampler) -> Option<BindlessSlot> {
+    let bindless = state.bindless.as_ref()?;
+    let Some(index) = bindless.free.lock().unwrap().pop() else {
+        warn!("Out of bindless texture slots; this texture is drawn with its own binding.");
+        return None;
+    };
+    let image_info = vk::DescriptorImageInfo::default()
+        .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+        .image_view(view)
+        .sampler(sampler);
+    let write = vk::WriteDescriptorSet::default()
+        .dst_set(bindless.set)
+        .dst_binding(0)
+        .dst_array_element(index)
+        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+        .image_info(std::slice::from_ref(&image_info));
+    unsafe { state.device.as_ref().unwrap().update_descriptor_sets(&[write], &[]) };
+    Some(BindlessSlot { index, free: bindless.free.clone() })
+}
+
 fn create_descriptor_pool(device: &Device) -> Result<vk::DescriptorPool, vk::Result> {
     let pool_size = vk::DescriptorPoolSize::default()
         .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
@@ -263,31 +668,109 @@ fn create_descriptor_pool(device: &Device) -> Result<vk::DescriptorPool, vk::Res
     unsafe { device.create_descriptor_pool(&pool_info, None) }
 }
 
+/// Creates the pipeline cache, seeded from disk when the saved data was produced
+/// by this exact GPU and driver. Anything else starts an empty cache.
+fn create_pipeline_cache(
+    instance: &Instance,
+    device: &Device,
+    pdevice: vk::PhysicalDevice,
+) -> Result<vk::PipelineCache, vk::Result> {
+    let props = unsafe { instance.get_physical_device_properties(pdevice) };
+    let initial_data = match fs::read(PIPELINE_CACHE_PATH) {
+        Ok(data) if pipeline_cache_matches(&data, &props) => {
+            info!("Loaded Vulkan pipeline cache ({} bytes).", data.len());
+            data
+        }
+        Ok(_) => {
+            info!("Vulkan pipeline cache is from another GPU or driver; rebuilding it.");
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    let create_info = vk::PipelineCacheCreateInfo::default().initial_data(&initial_data);
+    match unsafe { device.create_pipeline_cache(&create_info, None) } {
+        Ok(cache) => Ok(cache),
+        Err(e) if !initial_data.is_empty() => {
+            warn!("Rejected saved Vulkan pipeline cache ({:?}); starting empty.", e);
+            unsafe { device.create_pipeline_cache(&vk::PipelineCacheCreateInfo::default(), None) }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks the VkPipelineCacheHeaderVersionOne header against the current device.
+fn pipeline_cache_matches(data: &[u8], props: &vk::PhysicalDeviceProperties) -> bool {
+    if data.len() < 32 {
+        return false;
+    }
+    let read_u32 = |at: usize| u32::from_ne_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+    read_u32(4) == vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32
+        && read_u32(8) == props.vendor_id
+        && read_u32(12) == props.device_id
+        && data[16..32] == props.pipeline_cache_uuid
+}
+
+fn save_pipeline_cache(device: &Device, cache: vk::PipelineCache) {
+    let data = match unsafe { device.get_pipeline_cache_data(cache) } {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to read Vulkan pipeline cache: {:?}", e);
+            return;
+        }
+    };
+    let path = Path::new(PIPELINE_CACHE_PATH);
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Err(e) = fs::write(path, &data) {
+        warn!("Failed to save Vulkan pipeline cache to '{}': {}", path.display(), e);
+    }
+}
+
 fn create_sprite_pipeline(
     device: &Device,
     render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
     set_layout: vk::DescriptorSetLayout,
     mode: BlendMode,
+    mask_write: bool,
+    material: usize,
+    srgb: bool,
+    samples: vk::SampleCountFlags,
+    shaders: &ShaderCode,
 ) -> Result<PipelinePair, Box<dyn Error>> {
-    // Shaders (recompiled SPIR-V with compact instance layout)
-    let vert_shader_code = include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_shader.vert.spv"));
-    let frag_shader_code = include_bytes!(concat!(env!("OUT_DIR"), "/vulkan_shader.frag.spv"));
-    let vert_module = create_shader_module(device, vert_shader_code)?;
-    let frag_module = create_shader_module(device, frag_shader_code)?;
+    // Shaders (SPIR-V with per-instance model matrix)
+    let vert_module = create_shader_module(device, shaders.vert)?;
+    let frag_module = create_shader_module(device, shaders.frag)?;
     let main_name = ffi::CStr::from_bytes_with_nul(b"main\0")?;
 
+    // The fragment shader's MATERIAL (constant_id 0) picks the material branch.
+    let material_id = (material as i32).to_ne_bytes();
+    let spec_entry = vk::SpecializationMapEntry::default().constant_id(0).offset(0).size(material_id.len());
+    let spec_info = vk::SpecializationInfo::default()
+        .map_entries(std::slice::from_ref(&spec_entry))
+        .data(&material_id);
+    let srgb_flag = srgb_spec_data(srgb);
+    let srgb_entry = srgb_spec_entry();
+    let vert_spec_info = vk::SpecializationInfo::default()
+        .map_entries(std::slice::from_ref(&srgb_entry))
+        .data(&srgb_flag);
+
     let shader_stages = [
         vk::PipelineShaderStageCreateInfo::default()
             .stage(vk::ShaderStageFlags::VERTEX)
             .module(vert_module)
-            .name(main_name),
+            .name(main_name)
+            .specialization_info(&vert_spec_info),
         vk::PipelineShaderStageCreateInfo::default()
             .stage(vk::ShaderStageFlags::FRAGMENT)
             .module(frag_module)
-            .name(main_name),
+            .name(main_name)
+            .specialization_info(&spec_info),
     ];
 
-    // Vertex inputs: binding 0 (unit quad), binding 1 (compact per-instance)
+    // Vertex inputs: binding 0 (unit quad), binding 1 (per-instance)
     let (binding_descriptions, attribute_descriptions) = vertex_input_descriptions_textured_instanced();
     let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::default()
         .vertex_binding_descriptions(&binding_descriptions)
@@ -299,20 +782,36 @@ fn create_sprite_pipeline(
     let viewport_state = vk::PipelineViewportStateCreateInfo::default()
         .viewport_count(1).scissor_count(1);
 
+    // No culling: X/Y rotation past 90 degrees shows the back face of a sprite.
     let rasterizer = vk::PipelineRasterizationStateCreateInfo::default()
         .polygon_mode(vk::PolygonMode::FILL)
         .line_width(1.0)
-        .cull_mode(vk::CullModeFlags::BACK)
+        .cull_mode(vk::CullModeFlags::NONE)
         .front_face(vk::FrontFace::COUNTER_CLOCKWISE);
 
     let multisampling = vk::PipelineMultisampleStateCreateInfo::default()
-        .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+        .rasterization_samples(samples);
 
-    let color_blend_attachment = color_blend_for(mode);
+    // Mask writes only touch the stencil buffer.
+    let mut color_blend_attachment = color_blend_for(mode);
+    if mask_write {
+        color_blend_attachment = color_blend_attachment.color_write_mask(vk::ColorComponentFlags::empty());
+    }
     let color_blending = vk::PipelineColorBlendStateCreateInfo::default()
         .attachments(std::slice::from_ref(&color_blend_attachment));
 
-    let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let stencil = stencil_op_for(mask_write);
+    let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::default()
+        .stencil_test_enable(true)
+        .front(stencil)
+        .back(stencil);
+
+    let dynamic_states = [
+        vk::DynamicState::VIEWPORT,
+        vk::DynamicState::SCISSOR,
+        vk::DynamicState::STENCIL_COMPARE_MASK,
+        vk::DynamicState::STENCIL_REFERENCE,
+    ];
     let dynamic_state = vk::PipelineDynamicStateCreateInfo::default()
         .dynamic_states(&dynamic_states);
 
@@ -338,6 +837,7 @@ fn create_sprite_pipeline(
         .rasterization_state(&rasterizer)
         .multisample_state(&multisampling)
         .color_blend_state(&color_blending)
+        .depth_stencil_state(&depth_stencil)
         .dynamic_state(&dynamic_state)
         .layout(layout)
         .render_pass(render_pass)
@@ -345,7 +845,7 @@ fn create_sprite_pipeline(
 
     let pipe = unsafe {
         device
-            .create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_info], None)
+            .create_graphics_pipelines(pipeline_cache, &[pipeline_info], None)
             .map_err(|e| e.1)?[0]
     };
 
@@ -357,408 +857,2071 @@ fn create_sprite_pipeline(
     Ok(PipelinePair { layout, pipe })
 }
 
-#[inline(always)]
-fn next_pow2_usize(x: usize) -> usize {
-    let mut v = if x == 0 { 1 } else { x - 1 };
-    v |= v >> 1; v |= v >> 2; v |= v >> 4; v |= v >> 8; v |= v >> 16;
-    if std::mem::size_of::<usize>() == 8 { v |= v >> 32; }
-    v + 1
+// Where the sprite pipeline for `blend` and `material` (`Material::index`) sits
+// in what `create_sprite_pipelines` returns.
+fn sprite_pipeline_index(blend: BlendMode, material: usize) -> usize {
+    blend.index() * MATERIAL_COUNT + material
 }
 
-fn ensure_instance_ring_capacity(
-    state: &mut State,
-    needed_instances: usize,
-) -> Result<u32, Box<dyn Error>> {
-    // Request at least 1 instance, round to next power of two.
-    let requested_stride = next_pow2_usize(needed_instances.max(1));
-
-    // Grow-only policy: never shrink the ring to avoid frequent realloc + stalls.
-    let stride = if state.per_frame_stride_instances == 0 {
-        requested_stride
-    } else {
-        state.per_frame_stride_instances.max(requested_stride)
-    };
-
-    let need_total_instances = stride * MAX_FRAMES_IN_FLIGHT;
-    let bytes_per_instance = std::mem::size_of::<InstanceData>() as vk::DeviceSize;
-    let need_bytes = (need_total_instances as u64) * (bytes_per_instance as u64);
-
-    let dev = state.device.as_ref().unwrap();
-
-    // Reallocate only if missing or too small.
-    if state.instance_ring.is_none() || state.instance_capacity_instances < need_total_instances {
-        // Safety: the old ring buffer may be referenced by in-flight command buffers.
-        // Wait for the GPU to finish BEFORE destroying it.
-        if let Some(old) = state.instance_ring.take() {
-            unsafe {
-                // Full idle here is fine — this path runs only when we *grow*.
-                dev.device_wait_idle()?;
-                if !state.instance_ring_ptr.is_null() {
-                    dev.unmap_memory(old.memory);
+// The sprite pipeline for every blend mode and material, by `sprite_pipeline_index`.
+fn create_sprite_pipelines(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
+    set_layout: vk::DescriptorSetLayout,
+    samples: vk::SampleCountFlags,
+    srgb: bool,
+    shaders: &ShaderCode,
+) -> Result<Vec<PipelinePair>, Box<dyn Error>> {
+    let mut pipelines = Vec::with_capacity(BLEND_MODES.len() * MATERIAL_COUNT);
+    for blend in BLEND_MODES {
+        for material in 0..MATERIAL_COUNT {
+            match create_sprite_pipeline(
+                device, render_pass, pipeline_cache, set_layout, blend, false, material, srgb, samples, shaders,
+            ) {
+                Ok(pair) => pipelines.push(pair),
+                Err(e) => {
+                    destroy_pipelines(device, &pipelines);
+                    return Err(e);
                 }
             }
-            destroy_buffer(dev, &old);
-            state.instance_ring_ptr = std::ptr::null_mut();
         }
+    }
+    Ok(pipelines)
+}
 
-        // Create a new HOST_VISIBLE | HOST_COHERENT VB and keep it persistently mapped.
-        let (buf, mem) = create_gpu_buffer(
-            &state.instance, dev, state.pdevice,
-            need_bytes,
-            vk::BufferUsageFlags::VERTEX_BUFFER,
-            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-        )?;
-
-        let mapped = unsafe { dev.map_memory(mem, 0, need_bytes, vk::MemoryMapFlags::empty())? };
-
-        state.instance_ring = Some(BufferResource { buffer: buf, memory: mem });
-        state.instance_ring_ptr = mapped as *mut InstanceData;
-        state.instance_capacity_instances = need_total_instances;
-        state.per_frame_stride_instances = stride;
-    } else if state.per_frame_stride_instances != stride {
-        // We decided to grow-only; keep the bigger existing stride.
-        state.per_frame_stride_instances = stride;
+// The color pipeline for every blend mode, by `BlendMode::index`.
+fn create_color_pipelines(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
+    samples: vk::SampleCountFlags,
+    srgb: bool,
+    shaders: &ShaderCode,
+) -> Result<Vec<PipelinePair>, Box<dyn Error>> {
+    let mut pipelines = Vec::with_capacity(BLEND_MODES.len());
+    for blend in BLEND_MODES {
+        match create_color_pipeline(device, render_pass, pipeline_cache, blend, samples, srgb, shaders) {
+            Ok(pair) => pipelines.push(pair),
+            Err(e) => {
+                destroy_pipelines(device, &pipelines);
+                return Err(e);
+            }
+        }
     }
+    Ok(pipelines)
+}
 
-    // Base "firstInstance" for this frame’s slice of the ring.
-    Ok((state.current_frame * state.per_frame_stride_instances) as u32)
+// The vertex shaders' SRGB (constant_id 1) linearizes colors for sRGB framebuffers.
+fn srgb_spec_entry() -> vk::SpecializationMapEntry {
+    vk::SpecializationMapEntry::default().constant_id(1).offset(0).size(mem::size_of::<vk::Bool32>())
 }
 
-fn transition_image_layout_cmd(
-    device: &Device,
-    cmd: vk::CommandBuffer,
-    image: vk::Image,
-    old_layout: vk::ImageLayout,
-    new_layout: vk::ImageLayout,
-) {
-    let (src_access_mask, dst_access_mask, src_stage, dst_stage) = match (old_layout, new_layout) {
-        (vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL) => (
-            vk::AccessFlags::empty(),
-            vk::AccessFlags::TRANSFER_WRITE,
-            vk::PipelineStageFlags::TOP_OF_PIPE,
-            vk::PipelineStageFlags::TRANSFER,
-        ),
-        (vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL) => (
-            vk::AccessFlags::TRANSFER_WRITE,
-            vk::AccessFlags::SHADER_READ,
-            vk::PipelineStageFlags::TRANSFER,
-            vk::PipelineStageFlags::FRAGMENT_SHADER,
-        ),
-        _ => panic!("Unsupported layout transition!"),
-    };
+fn srgb_spec_data(srgb: bool) -> [u8; 4] {
+    (if srgb { vk::TRUE } else { vk::FALSE }).to_ne_bytes()
+}
 
-    let barrier = vk::ImageMemoryBarrier::default()
-        .old_layout(old_layout)
-        .new_layout(new_layout)
-        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-        .image(image)
-        .subresource_range(
-            vk::ImageSubresourceRange::default()
-                .aspect_mask(vk::ImageAspectFlags::COLOR)
-                .base_mip_level(0)
-                .level_count(1)
-                .base_array_layer(0)
-                .layer_count(1),
-        )
-        .src_access_mask(src_access_mask)
-        .dst_access_mask(dst_access_mask);
+fn is_srgb(format: vk::Format) -> bool {
+    matches!(format, vk::Format::B8G8R8A8_SRGB | vk::Format::R8G8B8A8_SRGB)
+}
 
-    unsafe {
-        device.cmd_pipeline_barrier(cmd, src_stage, dst_stage, vk::DependencyFlags::empty(), &[], &[], &[barrier]);
+fn destroy_pipelines(device: &Device, pipelines: &[PipelinePair]) {
+    for pair in pipelines {
+        unsafe {
+            device.destroy_pipeline(pair.pipe, None);
+            device.destroy_pipeline_layout(pair.layout, None);
+        }
     }
 }
 
-pub fn create_texture(state: &mut State, image: &RgbaImage) -> Result<Texture, Box<dyn Error>> {
-    let device_arc = state.device.as_ref().unwrap().clone();
-    let device = device_arc.as_ref();
-
-    let (width, height) = image.dimensions();
-    let image_data = image.as_raw();
+// Fullscreen triangle sampling one texture (the frame so far) with no blending
+// or stencil; drawn in both `render_pass` and `target_render_pass`.
+fn create_post_pipeline(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
+    set_layout: vk::DescriptorSetLayout,
+    samples: vk::SampleCountFlags,
+    shaders: &ShaderCode,
+) -> Result<PipelinePair, Box<dyn Error>> {
+    let vert_module = create_shader_module(device, shaders.vert)?;
+    let frag_module = create_shader_module(device, shaders.frag)?;
+    let main_name = ffi::CStr::from_bytes_with_nul(b"main\0")?;
 
-    let staging = create_buffer(
-        &state.instance, device, state.pdevice, state.command_pool, state.queue,
-        vk::BufferUsageFlags::TRANSFER_SRC,
-        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
-        Some(image_data),
+    let shader_stages = [
+        vk::PipelineShaderStageCreateInfo::default()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert_module)
+            .name(main_name),
+        vk::PipelineShaderStageCreateInfo::default()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag_module)
+            .name(main_name),
+    ];
+
+    // Vertices come from gl_VertexIndex.
+    let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::default();
+    let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::default()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+    let viewport_state = vk::PipelineViewportStateCreateInfo::default()
+        .viewport_count(1).scissor_count(1);
+    let rasterizer = vk::PipelineRasterizationStateCreateInfo::default()
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE);
+    let multisampling = vk::PipelineMultisampleStateCreateInfo::default()
+        .rasterization_samples(samples);
+    let color_blend_attachment = vk::PipelineColorBlendAttachmentState::default()
+        .blend_enable(false)
+        .color_write_mask(vk::ColorComponentFlags::RGBA);
+    let color_blending = vk::PipelineColorBlendStateCreateInfo::default()
+        .attachments(std::slice::from_ref(&color_blend_attachment));
+    // The passes have a stencil attachment, so this must be given even though it's unused.
+    let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::default();
+
+    let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state = vk::PipelineDynamicStateCreateInfo::default()
+        .dynamic_states(&dynamic_states);
+
+    let push_constant_range = vk::PushConstantRange::default()
+        .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+        .offset(0)
+        .size(std::mem::size_of::<PostPush>() as u32);
+    let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default()
+        .set_layouts(std::slice::from_ref(&set_layout))
+        .push_constant_ranges(std::slice::from_ref(&push_constant_range));
+    let layout = unsafe { device.create_pipeline_layout(&pipeline_layout_info, None)? };
+
+    let pipeline_info = vk::GraphicsPipelineCreateInfo::default()
+        .stages(&shader_stages)
+        .vertex_input_state(&vertex_input_info)
+        .input_assembly_state(&input_assembly)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterizer)
+        .multisample_state(&multisampling)
+        .color_blend_state(&color_blending)
+        .depth_stencil_state(&depth_stencil)
+        .dynamic_state(&dynamic_state)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0);
+
+    let pipe = unsafe {
+        device
+            .create_graphics_pipelines(pipeline_cache, &[pipeline_info], None)
+            .map_err(|e| e.1)?[0]
+    };
+
+    unsafe {
+        device.destroy_shader_module(vert_module, None);
+        device.destroy_shader_module(frag_module, None);
+    }
+
+    Ok(PipelinePair { layout, pipe })
+}
+
+fn create_color_pipeline(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    pipeline_cache: vk::PipelineCache,
+    mode: BlendMode,
+    samples: vk::SampleCountFlags,
+    srgb: bool,
+    shaders: &ShaderCode,
+) -> Result<PipelinePair, Box<dyn Error>> {
+    // Untextured, pre-tessellated triangles (polylines)
+    let vert_module = create_shader_module(device, shaders.vert)?;
+    let frag_module = create_shader_module(device, shaders.frag)?;
+    let main_name = ffi::CStr::from_bytes_with_nul(b"main\0")?;
+
+    let srgb_flag = srgb_spec_data(srgb);
+    let srgb_entry = srgb_spec_entry();
+    let vert_spec_info = vk::SpecializationInfo::default()
+        .map_entries(std::slice::from_ref(&srgb_entry))
+        .data(&srgb_flag);
+
+    let shader_stages = [
+        vk::PipelineShaderStageCreateInfo::default()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert_module)
+            .name(main_name)
+            .specialization_info(&vert_spec_info),
+        vk::PipelineShaderStageCreateInfo::default()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag_module)
+            .name(main_name),
+    ];
+
+    // Vertex input: binding 0 (pos vec2 + color vec4)
+    let binding_description = vk::VertexInputBindingDescription::default()
+        .binding(0)
+        .stride(mem::size_of::<ColorVertex>() as u32)
+        .input_rate(vk::VertexInputRate::VERTEX);
+    let attribute_descriptions = [
+        vk::VertexInputAttributeDescription::default()
+            .binding(0).location(0).format(vk::Format::R32G32_SFLOAT).offset(0),
+        vk::VertexInputAttributeDescription::default()
+            .binding(0).location(1).format(vk::Format::R32G32B32A32_SFLOAT).offset(8),
+    ];
+    let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::default()
+        .vertex_binding_descriptions(std::slice::from_ref(&binding_description))
+        .vertex_attribute_descriptions(&attribute_descriptions);
+
+    let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::default()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo::default()
+        .viewport_count(1).scissor_count(1);
+
+    // Tessellated strokes wind either way depending on line direction.
+    let rasterizer = vk::PipelineRasterizationStateCreateInfo::default()
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE);
+
+    let multisampling = vk::PipelineMultisampleStateCreateInfo::default()
+        .rasterization_samples(samples);
+
+    let color_blend_attachment = color_blend_for(mode);
+    let color_blending = vk::PipelineColorBlendStateCreateInfo::default()
+        .attachments(std::slice::from_ref(&color_blend_attachment));
+
+    let stencil = stencil_op_for(false);
+    let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::default()
+        .stencil_test_enable(true)
+        .front(stencil)
+        .back(stencil);
+
+    let dynamic_states = [
+        vk::DynamicState::VIEWPORT,
+        vk::DynamicState::SCISSOR,
+        vk::DynamicState::STENCIL_COMPARE_MASK,
+        vk::DynamicState::STENCIL_REFERENCE,
+    ];
+    let dynamic_state = vk::PipelineDynamicStateCreateInfo::default()
+        .dynamic_states(&dynamic_states);
+
+    // Push constant: projection only (no descriptor sets)
+    let push_constant_range = vk::PushConstantRange::default()
+        .stage_flags(vk::ShaderStageFlags::VERTEX)
+        .offset(0)
+        .size(std::mem::size_of::<ProjPush>() as u32);
+
+    let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default()
+        .push_constant_ranges(std::slice::from_ref(&push_constant_range));
+
+    let layout = unsafe { device.create_pipeline_layout(&pipeline_layout_info, None)? };
+
+    let pipeline_info = vk::GraphicsPipelineCreateInfo::default()
+        .stages(&shader_stages)
+        .vertex_input_state(&vertex_input_info)
+        .input_assembly_state(&input_assembly)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterizer)
+        .multisample_state(&multisampling)
+        .color_blend_state(&color_blending)
+        .depth_stencil_state(&depth_stencil)
+        .dynamic_state(&dynamic_state)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0);
+
+    let pipe = unsafe {
+        device
+            .create_graphics_pipelines(pipeline_cache, &[pipeline_info], None)
+            .map_err(|e| e.1)?[0]
+    };
+
+    unsafe {
+        device.destroy_shader_module(vert_module, None);
+        device.destroy_shader_module(frag_module, None);
+    }
+
+    Ok(PipelinePair { layout, pipe })
+}
+
+#[inline(always)]
+fn next_pow2_usize(x: usize) -> usize {
+    let mut v = if x == 0 { 1 } else { x - 1 };
+    v |= v >> 1; v |= v >> 2; v |= v >> 4; v |= v >> 8; v |= v >> 16;
+    if std::mem::size_of::<usize>() == 8 { v |= v >> 32; }
+    v + 1
+}
+
+fn ensure_instance_ring_capacity(
+    state: &mut State,
+    needed_instances: usize,
+) -> Result<u32, Box<dyn Error>> {
+    // Request at least 1 instance, round to next power of two.
+    let requested_stride = next_pow2_usize(needed_instances.max(1));
+
+    // Grow-only policy: never shrink the ring to avoid frequent realloc + stalls.
+    let stride = if state.per_frame_stride_instances == 0 {
+        requested_stride
+    } else {
+        state.per_frame_stride_instances.max(requested_stride)
+    };
+
+    let need_total_instances = stride * state.present.frames_in_flight;
+    let bytes_per_instance = std::mem::size_of::<InstanceData>() as vk::DeviceSize;
+    let need_bytes = (need_total_instances as u64) * (bytes_per_instance as u64);
+
+    let dev = state.device.as_ref().unwrap();
+
+    // Reallocate only if missing or too small.
+    if state.instance_ring.is_none() || state.instance_capacity_instances < need_total_instances {
+        // Safety: the old ring buffer may be referenced by in-flight command buffers.
+        // Wait for the GPU to finish BEFORE destroying it.
+        if let Some(old) = state.instance_ring.take() {
+            unsafe {
+                // Full idle here is fine — this path runs only when we *grow*.
+                dev.device_wait_idle()?;
+                if !state.instance_ring_ptr.is_null() {
+                    dev.unmap_memory(old.memory);
+                }
+            }
+            destroy_buffer(dev, &old);
+            state.instance_ring_ptr = std::ptr::null_mut();
+        }
+
+        // Create a new HOST_VISIBLE | HOST_COHERENT VB and keep it persistently mapped.
+        let (buf, mem) = create_gpu_buffer(
+            &state.instance, dev, state.pdevice,
+            need_bytes,
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+
+        let mapped = unsafe { dev.map_memory(mem, 0, need_bytes, vk::MemoryMapFlags::empty())? };
+
+        state.instance_ring = Some(BufferResource { buffer: buf, memory: mem });
+        state.instance_ring_ptr = mapped as *mut InstanceData;
+        state.instance_capacity_instances = need_total_instances;
+        state.per_frame_stride_instances = stride;
+    } else if state.per_frame_stride_instances != stride {
+        // We decided to grow-only; keep the bigger existing stride.
+        state.per_frame_stride_instances = stride;
+    }
+
+    // Base "firstInstance" for this frame’s slice of the ring.
+    Ok((state.current_frame * state.per_frame_stride_instances) as u32)
+}
+
+/// Polyline counterpart of `ensure_instance_ring_capacity`: a grow-only,
+/// persistently mapped vertex ring with one slice per frame in flight.
+fn ensure_line_ring_capacity(
+    state: &mut State,
+    needed_vertices: usize,
+) -> Result<u32, Box<dyn Error>> {
+    let stride = next_pow2_usize(needed_vertices.max(1)).max(state.per_frame_stride_vertices);
+    let need_total_vertices = stride * state.present.frames_in_flight;
+    let need_bytes = (need_total_vertices * std::mem::size_of::<ColorVertex>()) as vk::DeviceSize;
+
+    let dev = state.device.as_ref().unwrap();
+
+    if state.line_ring.is_none() || state.line_capacity_vertices < need_total_vertices {
+        if let Some(old) = state.line_ring.take() {
+            unsafe {
+                // Only on growth; the old ring may still be in use by in-flight frames.
+                dev.device_wait_idle()?;
+                if !state.line_ring_ptr.is_null() {
+                    dev.unmap_memory(old.memory);
+                }
+            }
+            destroy_buffer(dev, &old);
+            state.line_ring_ptr = std::ptr::null_mut();
+        }
+
+        let (buf, mem) = create_gpu_buffer(
+            &state.instance, dev, state.pdevice,
+            need_bytes,
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+
+        let mapped = unsafe { dev.map_memory(mem, 0, need_bytes, vk::MemoryMapFlags::empty())? };
+
+        state.line_ring = Some(BufferResource { buffer: buf, memory: mem });
+        state.line_ring_ptr = mapped as *mut ColorVertex;
+        state.line_capacity_vertices = need_total_vertices;
+    }
+    state.per_frame_stride_vertices = stride;
+
+    // Base "firstVertex" for this frame’s slice of the ring.
+    Ok((state.current_frame * state.per_frame_stride_vertices) as u32)
+}
+
+fn transition_image_layout_cmd(
+    device: &Device,
+    cmd: vk::CommandBuffer,
+    image: vk::Image,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+) {
+    let (src_access_mask, dst_access_mask, src_stage, dst_stage) = match (old_layout, new_layout) {
+        (vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL) => (
+            vk::AccessFlags::empty(),
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            vk::PipelineStageFlags::TRANSFER,
+        ),
+        (vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL) => (
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::AccessFlags::SHADER_READ,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+        ),
+        (vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL, vk::ImageLayout::TRANSFER_DST_OPTIMAL) => (
+            vk::AccessFlags::SHADER_READ,
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::PipelineStageFlags::TRANSFER,
+        ),
+        (vk::ImageLayout::UNDEFINED, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL) => (
+            vk::AccessFlags::empty(),
+            vk::AccessFlags::SHADER_READ,
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+        ),
+        _ => panic!("Unsupported layout transition!"),
+    };
+
+    let barrier = vk::ImageMemoryBarrier::default()
+        .old_layout(old_layout)
+        .new_layout(new_layout)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(
+            vk::ImageSubresourceRange::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .base_mip_level(0)
+                .level_count(1)
+                .base_array_layer(0)
+                .layer_count(1),
+        )
+        .src_access_mask(src_access_mask)
+        .dst_access_mask(dst_access_mask);
+
+    unsafe {
+        device.cmd_pipeline_barrier(cmd, src_stage, dst_stage, vk::DependencyFlags::empty(), &[], &[], &[barrier]);
+    }
+}
+
+pub fn create_texture(state: &mut State, image: &RgbaImage) -> Result<Texture, Box<dyn Error>> {
+    let (width, height) = image.dimensions();
+    upload_texture(state, width, height, rgba_format(state), image.as_raw())
+}
+
+// Images are sRGB-encoded; with an sRGB swapchain they're sampled as such.
+fn rgba_format(state: &State) -> vk::Format {
+    if state.present.srgb { vk::Format::R8G8B8A8_SRGB } else { vk::Format::R8G8B8A8_UNORM }
+}
+
+// Clears skip the shaders, so they're linearized here instead.
+fn clear_color_for(state: &State, render_list: &RenderList) -> [f32; 4] {
+    if state.present.srgb { srgb_to_linear(render_list.clear_color) } else { render_list.clear_color }
+}
+
+/// Starts uploading `image` without waiting for it; collect it with `poll_texture_uploads`.
+pub fn queue_texture_upload(state: &mut State, image: &RgbaImage) -> Result<u64, Box<dyn Error>> {
+    let id = state.uploads.next_id;
+    state.uploads.next_id += 1;
+    let (width, height) = image.dimensions();
+    let bytes = image.as_raw();
+    if bytes.len() as u64 > UPLOAD_RING_SIZE {
+        // Can't be staged in the ring; do it the blocking way.
+        let texture = create_texture(state, image)?;
+        state.uploads.done.push((id, texture));
+        return Ok(id);
+    }
+
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    let (ring, start) = stage_in_ring(state, bytes)?;
+    let offset = start % UPLOAD_RING_SIZE;
+
+    let fmt = rgba_format(state);
+    let (tex_image, tex_mem) = create_image(
+        state, width, height, fmt, vk::ImageTiling::OPTIMAL,
+        vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+
+    let cmd = begin_single_time_commands(device, state.command_pool)?;
+    transition_image_layout_cmd(device, cmd, tex_image, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+    let region = vk::BufferImageCopy::default()
+        .buffer_offset(offset)
+        .image_subresource(vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        })
+        .image_extent(vk::Extent3D { width, height, depth: 1 });
+    unsafe {
+        device.cmd_copy_buffer_to_image(cmd, ring, tex_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[region]);
+    }
+    transition_image_layout_cmd(device, cmd, tex_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    let fence = submit_with_fence(device, state.queue, cmd)?;
+
+    let view = create_image_view(device, tex_image, fmt, vk::ImageAspectFlags::COLOR)?;
+    let set = create_texture_descriptor_set(state, view, state.sampler)?;
+    let texture = Texture {
+        device: device_arc.clone(),
+        image: tex_image,
+        memory: tex_mem,
+        view,
+        descriptor_set: set,
+        pool: state.descriptor_pool,
+        bindless_slot: bindless_slot(state, view, state.sampler),
+    };
+    state.uploads.in_flight.push_back(InFlightUpload { id, fence, cmd, ring_start: start, texture: Some(texture) });
+    Ok(id)
+}
+
+/// Writes the `[x, y, width, height]` region of `image` into `texture`, which is
+/// `image`'s size. The copy is ordered after every frame already submitted and
+/// before the next one, so no frame samples a half-written texture, and nothing
+/// waits on the queue.
+pub fn update_texture(state: &mut State, texture: &Texture, image: &RgbaImage, region: [u32; 4]) -> Result<(), Box<dyn Error>> {
+    let [x, y, width, height] = region;
+    // Whole rows are staged; the copy picks the columns out of them.
+    let row_bytes = image.width() as usize * 4;
+    let bytes = &image.as_raw()[y as usize * row_bytes..(y + height) as usize * row_bytes];
+    if bytes.len() as u64 > UPLOAD_RING_SIZE {
+        return Err("Texture update is too large to stage".into());
+    }
+
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    let (ring, start) = stage_in_ring(state, bytes)?;
+
+    let cmd = begin_single_time_commands(device, state.command_pool)?;
+    transition_image_layout_cmd(device, cmd, texture.image, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+    let copy = vk::BufferImageCopy::default()
+        .buffer_offset(start % UPLOAD_RING_SIZE + x as u64 * 4)
+        .buffer_row_length(image.width())
+        .image_subresource(vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        })
+        .image_offset(vk::Offset3D { x: x as i32, y: y as i32, z: 0 })
+        .image_extent(vk::Extent3D { width, height, depth: 1 });
+    unsafe {
+        device.cmd_copy_buffer_to_image(cmd, ring, texture.image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[copy]);
+    }
+    transition_image_layout_cmd(device, cmd, texture.image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    let fence = submit_with_fence(device, state.queue, cmd)?;
+
+    let id = state.uploads.next_id;
+    state.uploads.next_id += 1;
+    state.uploads.in_flight.push_back(InFlightUpload { id, fence, cmd, ring_start: start, texture: None });
+    Ok(())
+}
+
+fn submit_with_fence(device: &Device, queue: vk::Queue, cmd: vk::CommandBuffer) -> Result<vk::Fence, vk::Result> {
+    unsafe {
+        device.end_command_buffer(cmd)?;
+        let fence = device.create_fence(&vk::FenceCreateInfo::default(), None)?;
+        let submit_info = vk::SubmitInfo::default().command_buffers(std::slice::from_ref(&cmd));
+        device.queue_submit(queue, &[submit_info], fence)?;
+        Ok(fence)
+    }
+}
+
+/// Copies `bytes` into the upload ring and returns the ring buffer and where in
+/// the ring's history they start. Only waits on older uploads when it's full.
+fn stage_in_ring(state: &mut State, bytes: &[u8]) -> Result<(vk::Buffer, u64), Box<dyn Error>> {
+    let len = bytes.len() as u64;
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    if state.uploads.ring.is_none() {
+        let (buffer, memory) = create_gpu_buffer(
+            &state.instance, device, state.pdevice, UPLOAD_RING_SIZE,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+        let ptr = unsafe { device.map_memory(memory, 0, UPLOAD_RING_SIZE, vk::MemoryMapFlags::empty())? } as *mut u8;
+        state.uploads.ring = Some((BufferResource { buffer, memory }, ptr));
+    }
+
+    // Place it after the previous upload, wrapping instead of splitting an image;
+    // only wait on older uploads when the ring is actually full.
+    let start = loop {
+        retire_uploads(state)?;
+        let Some(oldest) = state.uploads.in_flight.front() else {
+            state.uploads.head = 0;
+            break 0;
+        };
+        let mut start = state.uploads.head.next_multiple_of(UPLOAD_ALIGNMENT);
+        if start % UPLOAD_RING_SIZE + len > UPLOAD_RING_SIZE {
+            start = start.next_multiple_of(UPLOAD_RING_SIZE);
+        }
+        if start + len - oldest.ring_start <= UPLOAD_RING_SIZE {
+            break start;
+        }
+        unsafe { device.wait_for_fences(&[oldest.fence], true, u64::MAX)? };
+    };
+    let (ring, ring_ptr) = state.uploads.ring.as_ref().map(|(r, p)| (r.buffer, *p)).unwrap();
+    let offset = start % UPLOAD_RING_SIZE;
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ring_ptr.add(offset as usize), bytes.len()) };
+    state.uploads.head = start + len;
+    Ok((ring, start))
+}
+
+// Moves uploads whose fence has signaled to `done`, oldest first.
+fn retire_uploads(state: &mut State) -> Result<(), vk::Result> {
+    let device = state.device.as_ref().unwrap();
+    while let Some(upload) = state.uploads.in_flight.front() {
+        if !unsafe { device.get_fence_status(upload.fence)? } {
+            break;
+        }
+        let upload = state.uploads.in_flight.pop_front().unwrap();
+        unsafe {
+            device.destroy_fence(upload.fence, None);
+            device.free_command_buffers(state.command_pool, &[upload.cmd]);
+        }
+        if let Some(texture) = upload.texture {
+            state.uploads.done.push((upload.id, texture));
+        }
+    }
+    Ok(())
+}
+
+/// Uploads whose copy has finished, oldest first.
+pub fn poll_texture_uploads(state: &mut State) -> Vec<(u64, Texture)> {
+    if let Err(e) = retire_uploads(state) {
+        warn!("Failed to check texture uploads: {:?}", e);
+    }
+    mem::take(&mut state.uploads.done)
+}
+
+fn vk_format_for(format: CompressedFormat, srgb: bool) -> vk::Format {
+    match (format, srgb) {
+        (CompressedFormat::Bc1Rgb, false) => vk::Format::BC1_RGB_UNORM_BLOCK,
+        (CompressedFormat::Bc1Rgba, false) => vk::Format::BC1_RGBA_UNORM_BLOCK,
+        (CompressedFormat::Bc3, false) => vk::Format::BC3_UNORM_BLOCK,
+        (CompressedFormat::Bc7, false) => vk::Format::BC7_UNORM_BLOCK,
+        (CompressedFormat::Bc1Rgb, true) => vk::Format::BC1_RGB_SRGB_BLOCK,
+        (CompressedFormat::Bc1Rgba, true) => vk::Format::BC1_RGBA_SRGB_BLOCK,
+        (CompressedFormat::Bc3, true) => vk::Format::BC3_SRGB_BLOCK,
+        (CompressedFormat::Bc7, true) => vk::Format::BC7_SRGB_BLOCK,
+    }
+}
+
+pub fn supports_compressed(state: &State, format: CompressedFormat) -> bool {
+    let features = unsafe { state.instance.get_physical_device_features(state.pdevice) };
+    if features.texture_compression_bc != vk::TRUE {
+        return false;
+    }
+    let props = unsafe { state.instance.get_physical_device_format_properties(state.pdevice, vk_format_for(format, state.present.srgb)) };
+    props.optimal_tiling_features.contains(
+        vk::FormatFeatureFlags::SAMPLED_IMAGE | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR,
+    )
+}
+
+pub fn create_compressed_texture(state: &mut State, image: &CompressedImage) -> Result<Texture, Box<dyn Error>> {
+    let fmt = vk_format_for(image.format, state.present.srgb);
+    upload_texture(state, image.width, image.height, fmt, &image.data)
+}
+
+fn upload_texture(
+    state: &mut State, width: u32, height: u32, fmt: vk::Format, image_data: &[u8],
+) -> Result<Texture, Box<dyn Error>> {
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+
+    let staging = create_buffer(
+        &state.instance, device, state.pdevice, state.command_pool, state.queue,
+        vk::BufferUsageFlags::TRANSFER_SRC,
+        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        Some(image_data),
+    )?;
+
+    let (tex_image, tex_mem) = create_image(
+        state, width, height, fmt, vk::ImageTiling::OPTIMAL,
+        vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
     )?;
 
-    let fmt = vk::Format::R8G8B8A8_UNORM;
-    let (tex_image, tex_mem) = create_image(
-        state, width, height, fmt, vk::ImageTiling::OPTIMAL,
-        vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
-        vk::MemoryPropertyFlags::DEVICE_LOCAL,
-    )?;
+    let cmd = begin_single_time_commands(device, state.command_pool)?;
+
+    transition_image_layout_cmd(device, cmd, tex_image, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+
+    let region = vk::BufferImageCopy::default()
+        .image_subresource(vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        })
+        .image_extent(vk::Extent3D { width, height, depth: 1 });
+
+    unsafe {
+        device.cmd_copy_buffer_to_image(cmd, staging.buffer, tex_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[region]);
+    }
+
+    transition_image_layout_cmd(device, cmd, tex_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+
+    end_single_time_commands(device, state.command_pool, state.queue, cmd)?;
+
+    destroy_buffer(device, &staging);
+    let view = create_image_view(device, tex_image, fmt, vk::ImageAspectFlags::COLOR)?;
+    let set  = create_texture_descriptor_set(state, view, state.sampler)?;
+
+    Ok(Texture {
+        device: device_arc.clone(),
+        image: tex_image,
+        memory: tex_mem,
+        view,
+        descriptor_set: set,
+        pool: state.descriptor_pool,
+        bindless_slot: bindless_slot(state, view, state.sampler),
+    })
+}
+
+#[inline(always)]
+unsafe fn bytes_of<T>(v: &T) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(
+            (v as *const T) as *const u8,
+            std::mem::size_of::<T>(),
+        )
+    }
+}
+
+pub fn draw(
+    state: &mut State,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+) -> Result<FrameStats, Box<dyn Error>> {
+    if state.window_size.width == 0 || state.window_size.height == 0 {
+        return Ok(FrameStats::default());
+    }
+    let capture_requested = std::mem::take(&mut state.capture_requested);
+
+    let needed_instances = count_instances(render_list);
+    let has_polylines = render_list.objects.iter().any(|o| {
+        matches!(&o.object_type, ObjectType::Polyline { .. } | ObjectType::Mesh { .. })
+    });
+
+    if needed_instances == 0 && !has_polylines {
+        unsafe {
+            let device = state.device.as_ref().unwrap();
+            let fence = state.in_flight_fences[state.current_frame];
+            device.wait_for_fences(&[fence], true, u64::MAX)?;
+            if let Some(timer) = &mut state.gpu_timer {
+                collect_gpu_time(timer, device, state.current_frame);
+            }
+
+            let (image_index, acquired_suboptimal) =
+                match state.swapchain_resources.swapchain_loader.acquire_next_image(
+                    state.swapchain_resources.swapchain,
+                    u64::MAX,
+                    state.image_available_semaphores[state.current_frame],
+                    vk::Fence::null(),
+                ) {
+                    Ok(pair) => pair,
+                    Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                        // Nothing was drawn, so a requested capture waits for the next frame.
+                        state.capture_requested = capture_requested;
+                        recreate_swapchain_and_dependents(state)?;
+                        return Ok(FrameStats::default());
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+
+            let in_flight = state.images_in_flight[image_index as usize];
+            if in_flight != vk::Fence::null() {
+                device.wait_for_fences(&[in_flight], true, u64::MAX)?;
+            }
+            state.images_in_flight[image_index as usize] = fence;
+
+            device.reset_fences(&[fence])?;
+            let cmd = state.command_buffers[state.current_frame];
+            device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())?;
+
+            device.begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))?;
+            if let Some(timer) = &state.gpu_timer {
+                write_frame_start(timer, device, cmd, state.current_frame);
+            }
+
+            // Straight into the swapchain image, so HDR output is applied here too.
+            let c = match state.present.hdr {
+                HdrOutput::Off => clear_color_for(state, render_list),
+                hdr => hdr_output_color(render_list.clear_color, hdr, state.present.hdr_paper_white),
+            };
+            let clear_values = [
+                vk::ClearValue { color: vk::ClearColorValue { float32: [c[0], c[1], c[2], c[3]] } },
+                vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 } },
+            ];
+            let rp_info = vk::RenderPassBeginInfo::default()
+                .render_pass(state.render_pass)
+                .framebuffer(state.swapchain_resources.framebuffers[image_index as usize])
+                .render_area(vk::Rect2D { offset: vk::Offset2D::default(), extent: state.swapchain_resources.extent })
+                .clear_values(&clear_values);
+            device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
+            device.cmd_end_render_pass(cmd);
+            let capture = if capture_requested { record_capture(state, device, cmd, image_index) } else { None };
+            if let Some(timer) = &mut state.gpu_timer {
+                write_frame_end(timer, device, cmd, state.current_frame);
+            }
+            device.end_command_buffer(cmd)?;
+
+            let wait = [state.image_available_semaphores[state.current_frame]];
+            let sig  = [state.render_finished_semaphores[state.current_frame]];
+            let stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+            let submit = vk::SubmitInfo::default()
+                .wait_semaphores(&wait).wait_dst_stage_mask(&stages)
+                .command_buffers(std::slice::from_ref(&cmd)).signal_semaphores(&sig);
+            device.queue_submit(state.queue, &[submit], fence)?;
+            if let Some(capture) = capture {
+                state.captured_frame = finish_capture(device, capture, fence);
+            }
+
+            match queue_present(state, image_index, &sig) {
+                Ok(suboptimal) if suboptimal || acquired_suboptimal => recreate_swapchain_and_dependents(state)?,
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR) => recreate_swapchain_and_dependents(state)?,
+                Ok(_) => {},
+                Err(e) => return Err(e.into()),
+            }
+            state.current_frame = (state.current_frame + 1) % state.present.frames_in_flight;
+        }
+        return Ok(FrameStats { gpu_time_ms: gpu_time_ms(state), ..FrameStats::default() });
+    }
+
+    let base_first_instance = ensure_instance_ring_capacity(state, needed_instances)?;
+    let mut line_vertices: Vec<ColorVertex> = Vec::new();
+
+    unsafe {
+        let dst_base = state.instance_ring_ptr.add(base_first_instance as usize);
+        let (runs, _) = build_runs(render_list, textures, state.bindless.as_ref().map(|b| b.set), dst_base, &mut line_vertices);
+
+        if runs.is_empty() {
+            // ... (clear-only path, same as above)
+            state.capture_requested = capture_requested;
+            return Ok(FrameStats::default());
+        }
+
+        // Frames with effects, drawn below window resolution, or mapped to HDR
+        // draw the scene into a post target instead of the swapchain image;
+        // frames with nothing but the clear color skip them.
+        let post = !render_list.post_effects.is_empty()
+            || state.present.render_scale < 100
+            || state.present.hdr != HdrOutput::Off;
+        if post {
+            ensure_post_targets(state)?;
+        }
+        let scene_extent = match post {
+            true => state.post_targets[0].0.extent,
+            false => state.swapchain_resources.extent,
+        };
+
+        let base_first_vertex = if line_vertices.is_empty() {
+            0
+        } else {
+            let base = ensure_line_ring_capacity(state, line_vertices.len())?;
+            std::ptr::copy_nonoverlapping(
+                line_vertices.as_ptr(),
+                state.line_ring_ptr.add(base as usize),
+                line_vertices.len(),
+            );
+            base
+        };
+
+        let device_arc = state.device.as_ref().unwrap().clone();
+        let device = device_arc.as_ref();
+
+        let fence = state.in_flight_fences[state.current_frame];
+        device.wait_for_fences(&[fence], true, u64::MAX)?;
+        if let Some(timer) = &mut state.gpu_timer {
+            collect_gpu_time(timer, device, state.current_frame);
+        }
+
+        let (image_index, acquired_suboptimal) =
+            match state.swapchain_resources.swapchain_loader.acquire_next_image(
+                state.swapchain_resources.swapchain,
+                u64::MAX,
+                state.image_available_semaphores[state.current_frame],
+                vk::Fence::null(),
+            ) {
+                Ok(pair) => pair,
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    // Nothing was drawn, so a requested capture waits for the next frame.
+                    state.capture_requested = capture_requested;
+                    recreate_swapchain_and_dependents(state)?;
+                    return Ok(FrameStats::default());
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+        let in_flight = state.images_in_flight[image_index as usize];
+        if in_flight != vk::Fence::null() {
+            device.wait_for_fences(&[in_flight], true, u64::MAX)?;
+        }
+        state.images_in_flight[image_index as usize] = fence;
+
+        device.reset_fences(&[fence])?;
+        let cmd = state.command_buffers[state.current_frame];
+        device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())?;
+        device.begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))?;
+        if let Some(timer) = &state.gpu_timer {
+            write_frame_start(timer, device, cmd, state.current_frame);
+        }
+
+        let c = clear_color_for(state, render_list);
+        let clear_values = [
+            vk::ClearValue { color: vk::ClearColorValue { float32: [c[0], c[1], c[2], c[3]] } },
+            vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 } },
+        ];
+        let (render_pass, framebuffer) = match post {
+            true => (state.target_render_pass, state.post_targets[0].0.framebuffer),
+            false => (state.render_pass, state.swapchain_resources.framebuffers[image_index as usize]),
+        };
+        let rp_info = vk::RenderPassBeginInfo::default()
+            .render_pass(render_pass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D { offset: vk::Offset2D::default(), extent: scene_extent })
+            .clear_values(&clear_values);
+        device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
+
+        let buffers = RunBuffers {
+            instances: state.instance_ring.as_ref().unwrap().buffer,
+            base_first_instance,
+            lines: state.line_ring.as_ref().map_or(vk::Buffer::null(), |b| b.buffer),
+            base_first_vertex,
+        };
+        let mut stats = record_runs(
+            state, device, cmd, runs, render_list.labels.as_ref(), &buffers, scene_extent, state.projection,
+        );
+
+        device.cmd_end_render_pass(cmd);
+        if post {
+            let mut passes: Vec<_> = render_list.post_effects.iter().map(PostEffect::shader_params).collect();
+            passes.extend(hdr_output_pass(state.present.hdr, state.present.hdr_paper_white));
+            if passes.is_empty() {
+                passes.push(POST_COPY);
+            }
+            let passes = record_post_chain(state, device, cmd, image_index, &passes);
+            stats.draw_calls += passes;
+            stats.vertices += passes * 3;
+        }
+        let capture = if capture_requested { record_capture(state, device, cmd, image_index) } else { None };
+        if let Some(timer) = &mut state.gpu_timer {
+            write_frame_end(timer, device, cmd, state.current_frame);
+        }
+        device.end_command_buffer(cmd)?;
+
+        let wait = [state.image_available_semaphores[state.current_frame]];
+        let sig  = [state.render_finished_semaphores[state.current_frame]];
+        let stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+        let submit = vk::SubmitInfo::default().wait_semaphores(&wait).wait_dst_stage_mask(&stages).command_buffers(std::slice::from_ref(&cmd)).signal_semaphores(&sig);
+        device.queue_submit(state.queue, &[submit], fence)?;
+        if let Some(capture) = capture {
+            state.captured_frame = finish_capture(device, capture, fence);
+        }
 
-    let cmd = begin_single_time_commands(device, state.command_pool)?;
+        match queue_present(state, image_index, &sig) {
+            Ok(suboptimal) if suboptimal || acquired_suboptimal => recreate_swapchain_and_dependents(state)?,
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR) => recreate_swapchain_and_dependents(state)?,
+            Ok(_) => {},
+            Err(e) => return Err(e.into()),
+        }
 
-    transition_image_layout_cmd(device, cmd, tex_image, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+        state.current_frame = (state.current_frame + 1) % state.present.frames_in_flight;
+        stats.gpu_time_ms = gpu_time_ms(state);
+        Ok(stats)
+    }
+}
 
-    let region = vk::BufferImageCopy::default()
-        .image_subresource(vk::ImageSubresourceLayers {
-            aspect_mask: vk::ImageAspectFlags::COLOR,
-            mip_level: 0,
-            base_array_layer: 0,
-            layer_count: 1,
-        })
-        .image_extent(vk::Extent3D { width, height, depth: 1 });
+/// Has the next frame copy its swapchain image out for `capture_frame`.
+pub fn request_capture(state: &mut State) {
+    if !state.swapchain_resources.capturable {
+        warn!("The swapchain images can't be copied out as RGBA; can't capture frames.");
+        return;
+    }
+    state.capture_requested = true;
+}
+
+/// True from `request_capture` until a frame takes the request.
+pub fn capture_pending(state: &State) -> bool {
+    state.capture_requested
+}
+
+/// The image the last captured frame presented, once per `request_capture`.
+pub fn capture_frame(state: &mut State) -> Option<RgbaImage> {
+    state.captured_frame.take()
+}
+
+// A frame's image on its way back to the CPU.
+struct FrameCapture {
+    buffer: BufferResource,
+    extent: vk::Extent2D,
+    format: vk::Format,
+}
 
+/// Copies swapchain image `image_index` into a host-visible buffer once the frame's
+/// passes are done, then hands the image back to presentation. Record after the
+/// last render pass.
+unsafe fn record_capture(state: &State, device: &Device, cmd: vk::CommandBuffer, image_index: u32) -> Option<FrameCapture> {
+    let image = state.swapchain_resources.images[image_index as usize];
+    let extent = state.swapchain_resources.extent;
+    let size = extent.width as vk::DeviceSize * extent.height as vk::DeviceSize * 4;
+    let (buffer, memory) = match create_gpu_buffer(
+        &state.instance, device, state.pdevice, size, vk::BufferUsageFlags::TRANSFER_DST,
+        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+    ) {
+        Ok(pair) => pair,
+        Err(e) => {
+            warn!("Failed to create the frame capture buffer: {}", e);
+            return None;
+        }
+    };
+    let range = vk::ImageSubresourceRange::default()
+        .aspect_mask(vk::ImageAspectFlags::COLOR).level_count(1).layer_count(1);
+    let barrier = |old, new, src_access, dst_access| vk::ImageMemoryBarrier::default()
+        .old_layout(old).new_layout(new)
+        .src_access_mask(src_access).dst_access_mask(dst_access)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED).dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image).subresource_range(range);
+    let region = vk::BufferImageCopy::default()
+        .image_subresource(vk::ImageSubresourceLayers::default().aspect_mask(vk::ImageAspectFlags::COLOR).layer_count(1))
+        .image_extent(vk::Extent3D { width: extent.width, height: extent.height, depth: 1 });
     unsafe {
-        device.cmd_copy_buffer_to_image(cmd, staging.buffer, tex_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[region]);
+        device.cmd_pipeline_barrier(
+            cmd, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT, vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(), &[], &[],
+            &[barrier(vk::ImageLayout::PRESENT_SRC_KHR, vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                vk::AccessFlags::COLOR_ATTACHMENT_WRITE, vk::AccessFlags::TRANSFER_READ)],
+        );
+        device.cmd_copy_image_to_buffer(cmd, image, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, buffer, &[region]);
+        device.cmd_pipeline_barrier(
+            cmd, vk::PipelineStageFlags::TRANSFER, vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+            vk::DependencyFlags::empty(), &[], &[],
+            &[barrier(vk::ImageLayout::TRANSFER_SRC_OPTIMAL, vk::ImageLayout::PRESENT_SRC_KHR,
+                vk::AccessFlags::TRANSFER_READ, vk::AccessFlags::empty())],
+        );
     }
+    Some(FrameCapture { buffer: BufferResource { buffer, memory }, extent, format: state.swapchain_resources.format.format })
+}
 
-    transition_image_layout_cmd(device, cmd, tex_image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+/// Waits for the frame behind `fence` and reads its captured image back as RGBA.
+unsafe fn finish_capture(device: &Device, capture: FrameCapture, fence: vk::Fence) -> Option<RgbaImage> {
+    let FrameCapture { buffer, extent, format } = capture;
+    let len = extent.width as usize * extent.height as usize * 4;
+    let pixels = unsafe {
+        device.wait_for_fences(&[fence], true, u64::MAX)
+            .and_then(|_| device.map_memory(buffer.memory, 0, len as vk::DeviceSize, vk::MemoryMapFlags::empty()))
+            .map(|mapped| {
+                let pixels = std::slice::from_raw_parts(mapped as *const u8, len).to_vec();
+                device.unmap_memory(buffer.memory);
+                pixels
+            })
+    };
+    destroy_buffer(device, &buffer);
+    let mut pixels = match pixels {
+        Ok(pixels) => pixels,
+        Err(e) => {
+            warn!("Failed to read back the captured frame: {:?}", e);
+            return None;
+        }
+    };
+    let bgra = matches!(format, vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB);
+    for px in pixels.chunks_exact_mut(4) {
+        if bgra {
+            px.swap(0, 2);
+        }
+        // The swapchain's alpha is whatever blending left behind; the window shows it opaque.
+        px[3] = 255;
+    }
+    RgbaImage::from_raw(extent.width, extent.height, pixels)
+}
 
-    end_single_time_commands(device, state.command_pool, state.queue, cmd)?;
+/// Keeps two post targets at the render resolution, remaking them after a
+/// resize or a change of render scale or upscale filter.
+fn ensure_post_targets(state: &mut State) -> Result<(), Box<dyn Error>> {
+    let window = state.swapchain_resources.extent;
+    let (width, height) = render_size(window.width, window.height, state.present.render_scale);
+    let extent = vk::Extent2D { width, height };
+    let filter = state.present.upscale_filter;
+    if state.post_targets.len() == 2 && state.post_targets[0].0.extent == extent && state.post_filter == filter {
+        return Ok(());
+    }
+    if !state.post_targets.is_empty() {
+        // Frames in flight may still be sampling the old ones.
+        unsafe { state.device.as_ref().unwrap().device_wait_idle()?; }
+        state.post_targets.clear();
+    }
+    let sampler = match filter {
+        UpscaleFilter::Linear => state.sampler,
+        UpscaleFilter::Nearest => state.nearest_sampler,
+    };
+    for _ in 0..2 {
+        let target = create_render_target_with_sampler(state, extent.width, extent.height, sampler)?;
+        state.post_targets.push(target);
+    }
+    state.post_filter = filter;
+    Ok(())
+}
 
-    destroy_buffer(device, &staging);
-    let view = create_image_view(device, tex_image, fmt)?;
-    let set  = create_texture_descriptor_set(state, view, state.sampler)?;
+/// Runs `passes` (`PostEffect::shader_params`) over the scene drawn into the
+/// first post target, ping-ponging between the two targets; the last pass
+/// draws into the swapchain image, upscaling to it. Returns the passes recorded.
+unsafe fn record_post_chain(
+    state: &State,
+    device: &Device,
+    cmd: vk::CommandBuffer,
+    image_index: u32,
+    passes: &[(u32, [f32; 4])],
+) -> u32 {
+    let clear_values = [
+        vk::ClearValue { color: vk::ClearColorValue { float32: [0.0, 0.0, 0.0, 1.0] } },
+        vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 } },
+    ];
+    unsafe {
+        for (i, &(effect, params)) in passes.iter().enumerate() {
+            let (render_pass, framebuffer, extent) = match i + 1 == passes.len() {
+                true => (
+                    state.render_pass,
+                    state.swapchain_resources.framebuffers[image_index as usize],
+                    state.swapchain_resources.extent,
+                ),
+                false => {
+                    let target = &state.post_targets[(i + 1) % 2].0;
+                    (state.target_render_pass, target.framebuffer, target.extent)
+                }
+            };
+            // Not flipped like the scene's, so the targets are read back the way they were drawn.
+            let viewport = vk::Viewport { x: 0.0, y: 0.0, width: extent.width as f32, height: extent.height as f32, min_depth: 0.0, max_depth: 1.0 };
+            let scissor = vk::Rect2D { offset: vk::Offset2D::default(), extent };
+            let rp_info = vk::RenderPassBeginInfo::default()
+                .render_pass(render_pass)
+                .framebuffer(framebuffer)
+                .render_area(scissor)
+                .clear_values(&clear_values);
+            device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
+            device.cmd_set_viewport(cmd, 0, &[viewport]);
+            device.cmd_set_scissor(cmd, 0, &[scissor]);
+            device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, state.post_pipeline);
+            device.cmd_bind_descriptor_sets(
+                cmd, vk::PipelineBindPoint::GRAPHICS, state.post_pipeline_layout, 0,
+                &[state.post_targets[i % 2].1.descriptor_set], &[],
+            );
+            device.cmd_push_constants(
+                cmd, state.post_pipeline_layout, vk::ShaderStageFlags::FRAGMENT, 0,
+                bytes_of(&PostPush { params, effect }),
+            );
+            device.cmd_draw(cmd, 3, 1, 0, 0);
+            device.cmd_end_render_pass(cmd);
+        }
+    }
+    passes.len() as u32
+}
 
-    Ok(Texture {
-        device: device_arc.clone(),
-        image: tex_image,
-        memory: tex_mem,
-        view,
-        descriptor_set: set,
-        pool: state.descriptor_pool,
-    })
+fn create_gpu_timer(
+    instance: &Instance,
+    device: &Device,
+    pdevice: vk::PhysicalDevice,
+    queue_family_index: u32,
+) -> Option<GpuTimer> {
+    let families = unsafe { instance.get_physical_device_queue_family_properties(pdevice) };
+    let valid_bits = families.get(queue_family_index as usize)?.timestamp_valid_bits;
+    let period_ns = unsafe { instance.get_physical_device_properties(pdevice) }.limits.timestamp_period;
+    if valid_bits == 0 || period_ns <= 0.0 {
+        info!("Queue can't write timestamps; GPU frame timing unavailable.");
+        return None;
+    }
+    let info = vk::QueryPoolCreateInfo::default()
+        .query_type(vk::QueryType::TIMESTAMP)
+        .query_count(2 * MAX_FRAMES_IN_FLIGHT as u32);
+    match unsafe { device.create_query_pool(&info, None) } {
+        Ok(pool) => Some(GpuTimer {
+            pool,
+            period_ns,
+            valid_bits,
+            enabled: false,
+            pending: [false; MAX_FRAMES_IN_FLIGHT],
+            gpu_ms: None,
+        }),
+        Err(e) => {
+            warn!("Failed to create timestamp query pool: {:?}", e);
+            None
+        }
+    }
 }
 
-#[inline(always)]
-unsafe fn bytes_of<T>(v: &T) -> &[u8] {
+/// Folds the timestamps `frame` wrote last time into the smoothed GPU time.
+/// Only call once that frame's fence has signaled.
+unsafe fn collect_gpu_time(timer: &mut GpuTimer, device: &Device, frame: usize) {
+    if !std::mem::take(&mut timer.pending[frame]) {
+        return;
+    }
+    let mut stamps = [0u64; 2];
+    let first = 2 * frame as u32;
+    let read = unsafe { device.get_query_pool_results(timer.pool, first, &mut stamps, vk::QueryResultFlags::TYPE_64) };
+    if read.is_err() {
+        return;
+    }
+    let mask = if timer.valid_bits >= 64 { u64::MAX } else { (1u64 << timer.valid_bits) - 1 };
+    let ticks = stamps[1].wrapping_sub(stamps[0]) & mask;
+    let sample = ticks as f32 * timer.period_ns * 1e-6;
+    timer.gpu_ms = Some(match timer.gpu_ms {
+        Some(prev) => prev + (sample - prev) * GPU_TIME_SMOOTHING,
+        None => sample,
+    });
+}
+
+/// Resets `frame`'s queries and stamps the start of its commands. Must be
+/// recorded outside a render pass.
+unsafe fn write_frame_start(timer: &GpuTimer, device: &Device, cmd: vk::CommandBuffer, frame: usize) {
+    if !timer.enabled {
+        return;
+    }
+    let first = 2 * frame as u32;
     unsafe {
-        std::slice::from_raw_parts(
-            (v as *const T) as *const u8,
-            std::mem::size_of::<T>(),
+        device.cmd_reset_query_pool(cmd, timer.pool, first, 2);
+        device.cmd_write_timestamp(cmd, vk::PipelineStageFlags::TOP_OF_PIPE, timer.pool, first);
+    }
+}
+
+unsafe fn write_frame_end(timer: &mut GpuTimer, device: &Device, cmd: vk::CommandBuffer, frame: usize) {
+    if !timer.enabled {
+        return;
+    }
+    unsafe {
+        device.cmd_write_timestamp(cmd, vk::PipelineStageFlags::BOTTOM_OF_PIPE, timer.pool, 2 * frame as u32 + 1);
+    }
+    timer.pending[frame] = true;
+}
+
+fn gpu_time_ms(state: &State) -> Option<f32> {
+    state.gpu_timer.as_ref().filter(|t| t.enabled).and_then(|t| t.gpu_ms)
+}
+
+/// Recompiles the shader sources on disk and rebuilds every pipeline from them.
+/// The old pipelines stay in use unless all the new ones build.
+#[cfg(feature = "shader-hot-reload")]
+pub fn reload_shaders(state: &mut State) -> Result<(), Box<dyn Error>> {
+    use crate::core::gfx::shader_reload::compile_spirv;
+
+    let sprite_vert = compile_spirv("vulkan_shader.vert", &[])?;
+    let sprite_frag = compile_spirv("vulkan_shader.frag", &[])?;
+    let bindless_frag = compile_spirv("vulkan_shader.frag", &["BINDLESS"])?;
+    let color_vert = compile_spirv("vulkan_color.vert", &[])?;
+    let color_frag = compile_spirv("vulkan_color.frag", &[])?;
+    let post_vert = compile_spirv("vulkan_post.vert", &[])?;
+    let post_frag = compile_spirv("vulkan_post.frag", &[])?;
+    let sprite_shaders = ShaderCode { vert: &sprite_vert, frag: &sprite_frag };
+    let bindless_shaders = ShaderCode { vert: &sprite_vert, frag: &bindless_frag };
+    let color_shaders = ShaderCode { vert: &color_vert, frag: &color_frag };
+    let post_shaders = ShaderCode { vert: &post_vert, frag: &post_frag };
+
+    let device = state.device.as_ref().unwrap().clone();
+    let destroy = |pair: &PipelinePair| unsafe {
+        device.destroy_pipeline(pair.pipe, None);
+        device.destroy_pipeline_layout(pair.layout, None);
+    };
+    let sprites = create_sprite_pipelines(
+        &device, state.render_pass, state.pipeline_cache, state.descriptor_set_layout, state.samples,
+        state.present.srgb, &sprite_shaders,
+    )?;
+    let mask = create_sprite_pipeline(
+        &device, state.render_pass, state.pipeline_cache, state.descriptor_set_layout,
+        BlendMode::Alpha, true, 0, state.present.srgb, state.samples, &sprite_shaders,
+    )
+    .inspect_err(|_| destroy_pipelines(&device, &sprites))?;
+    let color = create_color_pipelines(
+        &device, state.render_pass, state.pipeline_cache, state.samples, state.present.srgb, &color_shaders,
+    )
+    .inspect_err(|_| {
+        destroy_pipelines(&device, &sprites);
+        destroy(&mask);
+    })?;
+    let post = create_post_pipeline(
+        &device, state.render_pass, state.pipeline_cache, state.descriptor_set_layout, state.samples, &post_shaders,
+    )
+    .inspect_err(|_| {
+        destroy_pipelines(&device, &sprites);
+        destroy(&mask);
+        destroy_pipelines(&device, &color);
+    })?;
+    let bindless = match &state.bindless {
+        Some(b) => Some(create_sprite_pipelines(
+            &device, state.render_pass, state.pipeline_cache, b.set_layout, state.samples,
+            state.present.srgb, &bindless_shaders,
         )
+        .inspect_err(|_| {
+            destroy_pipelines(&device, &sprites);
+            destroy(&mask);
+            destroy_pipelines(&device, &color);
+            destroy(&post);
+        })?),
+        None => None,
+    };
+
+    // Frames in flight may still be using the old pipelines.
+    unsafe { device.device_wait_idle()? };
+    destroy_pipelines(&device, &state.sprite_pipelines);
+    if let (Some(b), Some(pipelines)) = (&mut state.bindless, bindless) {
+        destroy_pipelines(&device, &b.pipelines);
+        b.pipelines = pipelines;
     }
+    destroy(&PipelinePair { layout: state.mask_pipeline_layout, pipe: state.mask_pipeline });
+    destroy_pipelines(&device, &state.color_pipelines);
+    destroy(&PipelinePair { layout: state.post_pipeline_layout, pipe: state.post_pipeline });
+    state.sprite_pipelines = sprites;
+    PipelinePair { layout: state.mask_pipeline_layout, pipe: state.mask_pipeline } = mask;
+    state.color_pipelines = color;
+    PipelinePair { layout: state.post_pipeline_layout, pipe: state.post_pipeline } = post;
+    Ok(())
 }
 
-pub fn draw(
-    state: &mut State,
-    render_list: &RenderList,
-    textures: &HashMap<String, RendererTexture>,
-) -> Result<u32, Box<dyn Error>> {
-    if state.window_size.width == 0 || state.window_size.height == 0 {
-        return Ok(0);
+pub fn set_gpu_timing(state: &mut State, enabled: bool) {
+    let Some(timer) = &mut state.gpu_timer else { return };
+    timer.enabled = enabled;
+    if !enabled {
+        // Slots recorded before this may not have both timestamps; start clean.
+        timer.pending = [false; MAX_FRAMES_IN_FLIGHT];
+        timer.gpu_ms = None;
     }
+}
 
-    #[inline(always)]
-    fn decompose_2d(m: [[f32; 4]; 4]) -> ([f32; 2], [f32; 2], [f32; 2]) {
-        let center = [m[3][0], m[3][1]];
-        let c0 = [m[0][0], m[0][1]];
-        let c1 = [m[1][0], m[1][1]];
-        let sx = (c0[0]*c0[0] + c0[1]*c0[1]).sqrt().max(1e-12);
-        let sy = (c1[0]*c1[0] + c1[1]*c1[1]).sqrt().max(1e-12);
-        let cos_t = c0[0] / sx;
-        let sin_t = c0[1] / sx;
-        (center, [sx, sy], [sin_t, cos_t])
+/// Presents `image_index` once `wait` is signaled. With display timing, the
+/// present is tagged so `poll_present_timing` can see when it hit the screen.
+unsafe fn queue_present(state: &mut State, image_index: u32, wait: &[vk::Semaphore]) -> ash::prelude::VkResult<bool> {
+    let swapchains = [state.swapchain_resources.swapchain];
+    let indices = [image_index];
+    let mut present_info = vk::PresentInfoKHR::default()
+        .wait_semaphores(wait)
+        .swapchains(&swapchains)
+        .image_indices(&indices);
+
+    let tagged = match (&state.display_timing, monotonic_ns()) {
+        (Some(_), Some(now)) => {
+            let timing = &mut state.present_timing;
+            let id = timing.next_id;
+            timing.next_id = timing.next_id.wrapping_add(1);
+            timing.pending.push_back((id, now));
+            if timing.pending.len() > MAX_PENDING_PRESENTS {
+                timing.pending.pop_front();
+            }
+            Some([vk::PresentTimeGOOGLE { present_id: id, desired_present_time: 0 }])
+        }
+        _ => None,
+    };
+    let mut times_info = vk::PresentTimesInfoGOOGLE::default();
+    if let Some(times) = &tagged {
+        times_info = times_info.times(times);
+        present_info = present_info.push_next(&mut times_info);
     }
 
-    let needed_instances = render_list.objects.iter().filter(|o| {
-        matches!(&o.object_type, ObjectType::Sprite { .. })
-    }).count();
+    let result = unsafe { state.swapchain_resources.swapchain_loader.queue_present(state.queue, &present_info) };
+    poll_present_timing(state);
+    result
+}
 
-    if needed_instances == 0 {
-        unsafe {
-            let device = state.device.as_ref().unwrap();
-            let fence = state.in_flight_fences[state.current_frame];
-            device.wait_for_fences(&[fence], true, u64::MAX)?;
+/// Folds any presentation timings the driver has reported into the smoothed latency.
+fn poll_present_timing(state: &mut State) {
+    let Some(display_timing) = &state.display_timing else { return };
+    let swapchain = state.swapchain_resources.swapchain;
+    let Ok(reports) = (unsafe { display_timing.get_past_presentation_timing(swapchain) }) else { return };
+
+    let timing = &mut state.present_timing;
+    for report in reports {
+        while let Some(&(id, queued_at)) = timing.pending.front() {
+            // Ids are issued in order, so anything older than the report was dropped by the driver.
+            if id.wrapping_sub(report.present_id) as i32 > 0 {
+                break;
+            }
+            timing.pending.pop_front();
+            if id == report.present_id && report.actual_present_time >= queued_at {
+                let sample = (report.actual_present_time - queued_at) as f32 * 1e-9;
+                timing.latency = Some(match timing.latency {
+                    Some(prev) => prev + (sample - prev) * LATENCY_SMOOTHING,
+                    None => sample,
+                });
+                break;
+            }
+        }
+    }
+}
 
-            let (image_index, acquired_suboptimal) =
-                match state.swapchain_resources.swapchain_loader.acquire_next_image(
-                    state.swapchain_resources.swapchain,
-                    u64::MAX,
-                    state.image_available_semaphores[state.current_frame],
-                    vk::Fence::null(),
-                ) {
-                    Ok(pair) => pair,
-                    Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => { recreate_swapchain_and_dependents(state)?; return Ok(0); }
-                    Err(e) => return Err(e.into()),
-                };
+pub fn adapter_info(state: &State) -> String {
+    let props = unsafe { state.instance.get_physical_device_properties(state.pdevice) };
+    let name = props.device_name_as_c_str().map_or("unknown".into(), |n| n.to_string_lossy());
+    let api = props.api_version;
+    format!(
+        "Vulkan {}.{}.{}: {} (vendor {:#06x}, device {:#06x}, driver {:#x})",
+        vk::api_version_major(api), vk::api_version_minor(api), vk::api_version_patch(api),
+        name, props.vendor_id, props.device_id, props.driver_version,
+    )
+}
 
-            let in_flight = state.images_in_flight[image_index as usize];
-            if in_flight != vk::Fence::null() {
-                device.wait_for_fences(&[in_flight], true, u64::MAX)?;
+/// Whether `error` means the device or the window surface is gone; neither
+/// comes back without a new instance.
+pub fn is_lost(error: &(dyn Error + 'static)) -> bool {
+    matches!(
+        error.downcast_ref::<vk::Result>(),
+        Some(&vk::Result::ERROR_DEVICE_LOST | &vk::Result::ERROR_SURFACE_LOST_KHR)
+    )
+}
+
+/// Smoothed time from queueing a present to it reaching the display, when the
+/// driver reports presentation timing.
+pub fn present_latency(state: &State) -> Option<f32> {
+    state.present_timing.latency
+}
+
+/// Host time on the clock display timing reports in (CLOCK_MONOTONIC).
+#[cfg(target_os = "linux")]
+fn monotonic_ns() -> Option<u64> {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) } != 0 {
+        return None;
+    }
+    Some(ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+}
+
+/// Host time on the clock Windows drivers report display timing in: the
+/// performance counter, which DXGI frame statistics are stamped with too.
+#[cfg(windows)]
+fn monotonic_ns() -> Option<u64> {
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn QueryPerformanceCounter(count: *mut i64) -> i32;
+        fn QueryPerformanceFrequency(frequency: *mut i64) -> i32;
+    }
+    let (mut count, mut frequency) = (0i64, 0i64);
+    let ok = unsafe { QueryPerformanceCounter(&mut count) != 0 && QueryPerformanceFrequency(&mut frequency) != 0 };
+    if !ok || count < 0 || frequency <= 0 {
+        return None;
+    }
+    Some((count as u128 * 1_000_000_000 / frequency as u128) as u64)
+}
+
+// Elsewhere the clock the driver uses isn't one we can read, so timing stays off.
+#[cfg(not(any(target_os = "linux", windows)))]
+fn monotonic_ns() -> Option<u64> {
+    None
+}
+
+/// Creates an offscreen target and the texture it draws into. The texture is
+/// sampled like any other once it is in the texture map; its contents are
+/// undefined until the first `draw_to_target`.
+pub fn create_render_target(
+    state: &mut State,
+    width: u32,
+    height: u32,
+) -> Result<(RenderTarget, Texture), Box<dyn Error>> {
+    let sampler = state.sampler;
+    create_render_target_with_sampler(state, width, height, sampler)
+}
+
+fn create_render_target_with_sampler(
+    state: &mut State,
+    width: u32,
+    height: u32,
+    sampler: vk::Sampler,
+) -> Result<(RenderTarget, Texture), Box<dyn Error>> {
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    let extent = vk::Extent2D { width: width.max(1), height: height.max(1) };
+
+    // Match the swapchain so the target pass stays compatible with the pipelines.
+    let fmt = state.swapchain_resources.format.format;
+    let (image, memory) = create_image(
+        state, extent.width, extent.height, fmt, vk::ImageTiling::OPTIMAL,
+        vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    let cmd = begin_single_time_commands(device, state.command_pool)?;
+    transition_image_layout_cmd(device, cmd, image, vk::ImageLayout::UNDEFINED, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    end_single_time_commands(device, state.command_pool, state.queue, cmd)?;
+
+    let view = create_image_view(device, image, fmt, vk::ImageAspectFlags::COLOR)?;
+    let set = create_texture_descriptor_set(state, view, sampler)?;
+    let texture = Texture {
+        device: device_arc.clone(),
+        image,
+        memory,
+        view,
+        descriptor_set: set,
+        pool: state.descriptor_pool,
+        bindless_slot: bindless_slot(state, view, sampler),
+    };
+
+    let stencil = create_stencil_attachment(&state.instance, device, state.pdevice, state.stencil_format, extent, state.samples)?;
+    let msaa_color = match create_msaa_color_attachment(&state.instance, device, state.pdevice, fmt, extent, state.samples) {
+        Ok(color) => color,
+        Err(e) => {
+            destroy_attachment(device, &stencil);
+            return Err(e.into());
+        }
+    };
+    let attachments = framebuffer_attachments(view, &stencil, msaa_color.as_ref());
+    let create_info = vk::FramebufferCreateInfo::default()
+        .render_pass(state.target_render_pass)
+        .attachments(&attachments)
+        .width(extent.width)
+        .height(extent.height)
+        .layers(1);
+    let framebuffer = match unsafe { device.create_framebuffer(&create_info, None) } {
+        Ok(fb) => fb,
+        Err(e) => {
+            destroy_attachment(device, &stencil);
+            if let Some(color) = &msaa_color {
+                destroy_attachment(device, color);
             }
-            state.images_in_flight[image_index as usize] = fence;
+            return Err(e.into());
+        }
+    };
 
-            device.reset_fences(&[fence])?;
-            let cmd = state.command_buffers[state.current_frame];
-            device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())?;
+    Ok((RenderTarget { device: device_arc.clone(), framebuffer, stencil, msaa_color, extent }, texture))
+}
 
-            device.begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default()
-                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))?;
+/// Draws `render_list` into `target`, mapping the current screen space onto
+/// the whole target. Waits for the GPU first, since the target's texture may
+/// still be in use by frames in flight; meant for occasional renders, not every frame.
+pub fn draw_to_target(
+    state: &mut State,
+    target: &RenderTarget,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+) -> Result<FrameStats, Box<dyn Error>> {
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    unsafe { device.device_wait_idle()?; }
 
-            let c = render_list.clear_color;
-            let clear_value = vk::ClearValue { color: vk::ClearColorValue { float32: [c[0], c[1], c[2], c[3]] } };
-            let rp_info = vk::RenderPassBeginInfo::default()
-                .render_pass(state.render_pass)
-                .framebuffer(state.swapchain_resources.framebuffers[image_index as usize])
-                .render_area(vk::Rect2D { offset: vk::Offset2D::default(), extent: state.swapchain_resources.extent })
-                .clear_values(std::slice::from_ref(&clear_value));
-            device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
-            device.cmd_end_render_pass(cmd);
-            device.end_command_buffer(cmd)?;
+    let mut instances: Vec<InstanceData> = Vec::with_capacity(count_instances(render_list));
+    let mut line_vertices: Vec<ColorVertex> = Vec::new();
+    let runs = unsafe {
+        let (runs, written) = build_runs(render_list, textures, state.bindless.as_ref().map(|b| b.set), instances.as_mut_ptr(), &mut line_vertices);
+        instances.set_len(written as usize);
+        runs
+    };
 
-            let wait = [state.image_available_semaphores[state.current_frame]];
-            let sig  = [state.render_finished_semaphores[state.current_frame]];
-            let stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-            let submit = vk::SubmitInfo::default()
-                .wait_semaphores(&wait).wait_dst_stage_mask(&stages)
-                .command_buffers(std::slice::from_ref(&cmd)).signal_semaphores(&sig);
-            device.queue_submit(state.queue, &[submit], fence)?;
+    // One-shot device-local buffers; the ring buffers belong to the frames in flight.
+    let instance_buffer = match instances.is_empty() {
+        true => None,
+        false => Some(create_buffer(
+            &state.instance, device, state.pdevice, state.command_pool, state.queue,
+            vk::BufferUsageFlags::VERTEX_BUFFER, vk::MemoryPropertyFlags::DEVICE_LOCAL, Some(&instances[..]),
+        )?),
+    };
+    let line_buffer = match line_vertices.is_empty() {
+        true => None,
+        false => Some(create_buffer(
+            &state.instance, device, state.pdevice, state.command_pool, state.queue,
+            vk::BufferUsageFlags::VERTEX_BUFFER, vk::MemoryPropertyFlags::DEVICE_LOCAL, Some(&line_vertices[..]),
+        )?),
+    };
+
+    let cmd = begin_single_time_commands(device, state.command_pool)?;
+    let c = clear_color_for(state, render_list);
+    let clear_values = [
+        vk::ClearValue { color: vk::ClearColorValue { float32: [c[0], c[1], c[2], c[3]] } },
+        vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 } },
+    ];
+    let rp_info = vk::RenderPassBeginInfo::default()
+        .render_pass(state.target_render_pass)
+        .framebuffer(target.framebuffer)
+        .render_area(vk::Rect2D { offset: vk::Offset2D::default(), extent: target.extent })
+        .clear_values(&clear_values);
+    let buffers = RunBuffers {
+        instances: instance_buffer.as_ref().map_or(vk::Buffer::null(), |b| b.buffer),
+        base_first_instance: 0,
+        lines: line_buffer.as_ref().map_or(vk::Buffer::null(), |b| b.buffer),
+        base_first_vertex: 0,
+    };
+    let stats = unsafe {
+        device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
+        let drawn = record_runs(state, device, cmd, runs, render_list.labels.as_ref(), &buffers, target.extent, ortho_for_current_screen());
+        device.cmd_end_render_pass(cmd);
+        drawn
+    };
+    end_single_time_commands(device, state.command_pool, state.queue, cmd)?;
+
+    for buffer in instance_buffer.iter().chain(line_buffer.iter()) {
+        destroy_buffer(device, buffer);
+    }
+    Ok(stats)
+}
+
+/// Opens a swapchain on `window` for `draw_secondary_window`. The window must
+/// be presentable from the main window's queue and offer its color format.
+pub fn create_secondary_window(state: &mut State, window: &Window) -> Result<SecondaryWindow, Box<dyn Error>> {
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    let surface = create_surface(&state._entry, &state.instance, window)?;
+    let supported = unsafe {
+        state.surface_loader.get_physical_device_surface_support(state.pdevice, state.queue_family_index, surface)
+    }.unwrap_or(false);
+    if !supported {
+        unsafe { state.surface_loader.destroy_surface(surface, None) };
+        return Err("Secondary window can't be presented from the main queue".into());
+    }
+    let window_size = window.inner_size();
+    let swapchain_resources = match create_secondary_swapchain(state, surface, window_size, None) {
+        Ok(resources) => resources,
+        Err(e) => {
+            unsafe { state.surface_loader.destroy_surface(surface, None) };
+            return Err(e);
+        }
+    };
+    let cmd = create_command_buffers(device, state.command_pool, 1)?[0];
+    let (mut image_available, mut render_finished, mut fences) = create_sync_objects(device, 1)?;
+    info!(
+        "Secondary window ready ({}x{}).",
+        swapchain_resources.extent.width, swapchain_resources.extent.height
+    );
+    Ok(SecondaryWindow {
+        surface,
+        swapchain_resources,
+        window_size,
+        cmd,
+        image_available: image_available.remove(0),
+        render_finished: render_finished.remove(0),
+        fence: fences.remove(0),
+        buffers: Vec::new(),
+    })
+}
+
+// Swapchain and framebuffers for a secondary window, compatible with the main render pass.
+fn create_secondary_swapchain(
+    state: &State,
+    surface: vk::SurfaceKHR,
+    window_size: PhysicalSize<u32>,
+    old_swapchain: Option<vk::SwapchainKHR>,
+) -> Result<SwapchainResources, Box<dyn Error>> {
+    let device = state.device.as_ref().unwrap();
+    if state.present.hdr != HdrOutput::Off {
+        // Its frames skip the post chain, so nothing would map them out of SDR.
+        return Err("Secondary windows aren't supported with HDR output".into());
+    }
+    // Never wait for vblank: a second display must not pace the main one.
+    let present = PresentSettings { vsync: false, mode: PresentMode::Auto, ..state.present };
+    let mut resources = create_swapchain(
+        &state.instance, device, state.pdevice, surface, &state.surface_loader, window_size,
+        old_swapchain, present, state.stencil_format, state.samples,
+    )?;
+    if resources.format.format != state.swapchain_resources.format.format {
+        destroy_swapchain_resources(device, &resources);
+        return Err(format!(
+            "Secondary window offers {:?}, not the main window's {:?}",
+            resources.format.format, state.swapchain_resources.format.format
+        ).into());
+    }
+    if let Err(e) = recreate_framebuffers(device, &mut resources, state.render_pass) {
+        destroy_swapchain_resources(device, &resources);
+        return Err(e.into());
+    }
+    Ok(resources)
+}
+
+fn recreate_secondary_swapchain(state: &State, window: &mut SecondaryWindow) -> Result<(), Box<dyn Error>> {
+    let device = state.device.as_ref().unwrap();
+    unsafe { device.device_wait_idle()?; }
+    let old_swapchain = window.swapchain_resources.swapchain;
+    let resources = create_secondary_swapchain(state, window.surface, window.window_size, Some(old_swapchain))?;
+    let old = std::mem::replace(&mut window.swapchain_resources, resources);
+    destroy_swapchain_resources(device, &old);
+    Ok(())
+}
+
+/// Draws `render_list` into a secondary window, in that window's own screen
+/// space (`ortho_for_size`). Post effects are ignored. Waits for the window's
+/// previous frame, but not for the main window's.
+pub fn draw_secondary_window(
+    state: &mut State,
+    window: &mut SecondaryWindow,
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+) -> Result<FrameStats, Box<dyn Error>> {
+    if window.window_size.width == 0 || window.window_size.height == 0 {
+        return Ok(FrameStats::default());
+    }
+    let device_arc = state.device.as_ref().unwrap().clone();
+    let device = device_arc.as_ref();
+    unsafe { device.wait_for_fences(&[window.fence], true, u64::MAX)?; }
+    for buffer in window.buffers.drain(..) {
+        destroy_buffer(device, &buffer);
+    }
 
-            let present_info = vk::PresentInfoKHR::default()
-                .wait_semaphores(&sig)
-                .swapchains(std::slice::from_ref(&state.swapchain_resources.swapchain))
-                .image_indices(std::slice::from_ref(&image_index));
+    let mut instances: Vec<InstanceData> = Vec::with_capacity(count_instances(render_list));
+    let mut line_vertices: Vec<ColorVertex> = Vec::new();
+    let runs = unsafe {
+        let (runs, written) = build_runs(render_list, textures, state.bindless.as_ref().map(|b| b.set), instances.as_mut_ptr(), &mut line_vertices);
+        instances.set_len(written as usize);
+        runs
+    };
+    // Host-visible, so the upload doesn't stall the queue the main window is using.
+    let instance_buffer = match instances.is_empty() {
+        true => None,
+        false => Some(create_host_vertex_buffer(state, device, &instances)?),
+    };
+    let line_buffer = match line_vertices.is_empty() {
+        true => None,
+        false => Some(create_host_vertex_buffer(state, device, &line_vertices)?),
+    };
+    let buffers = RunBuffers {
+        instances: instance_buffer.as_ref().map_or(vk::Buffer::null(), |b| b.buffer),
+        base_first_instance: 0,
+        lines: line_buffer.as_ref().map_or(vk::Buffer::null(), |b| b.buffer),
+        base_first_vertex: 0,
+    };
+    window.buffers.extend(instance_buffer.into_iter().chain(line_buffer));
 
-            match state.swapchain_resources.swapchain_loader.queue_present(state.queue, &present_info) {
-                Ok(suboptimal) if suboptimal || acquired_suboptimal => recreate_swapchain_and_dependents(state)?,
-                Err(vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR) => recreate_swapchain_and_dependents(state)?,
-                Ok(_) => {},
-                Err(e) => return Err(e.into()),
+    unsafe {
+        let resources = &window.swapchain_resources;
+        let (image_index, acquired_suboptimal) = match resources.swapchain_loader.acquire_next_image(
+            resources.swapchain, u64::MAX, window.image_available, vk::Fence::null(),
+        ) {
+            Ok(pair) => pair,
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                recreate_secondary_swapchain(state, window)?;
+                return Ok(FrameStats::default());
             }
-            state.current_frame = (state.current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
+            Err(e) => return Err(e.into()),
+        };
+
+        device.reset_fences(&[window.fence])?;
+        let cmd = window.cmd;
+        device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())?;
+        device.begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))?;
+        let c = clear_color_for(state, render_list);
+        let clear_values = [
+            vk::ClearValue { color: vk::ClearColorValue { float32: [c[0], c[1], c[2], c[3]] } },
+            vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 } },
+        ];
+        let extent = resources.extent;
+        let rp_info = vk::RenderPassBeginInfo::default()
+            .render_pass(state.render_pass)
+            .framebuffer(resources.framebuffers[image_index as usize])
+            .render_area(vk::Rect2D { offset: vk::Offset2D::default(), extent })
+            .clear_values(&clear_values);
+        device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
+        let stats = record_runs(state, device, cmd, runs, render_list.labels.as_ref(), &buffers, extent, ortho_for_size(extent.width, extent.height));
+        device.cmd_end_render_pass(cmd);
+        device.end_command_buffer(cmd)?;
+
+        let wait = [window.image_available];
+        let sig = [window.render_finished];
+        let stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+        let submit = vk::SubmitInfo::default().wait_semaphores(&wait).wait_dst_stage_mask(&stages).command_buffers(std::slice::from_ref(&cmd)).signal_semaphores(&sig);
+        device.queue_submit(state.queue, &[submit], window.fence)?;
+
+        let swapchains = [resources.swapchain];
+        let indices = [image_index];
+        let present_info = vk::PresentInfoKHR::default().wait_semaphores(&sig).swapchains(&swapchains).image_indices(&indices);
+        match resources.swapchain_loader.queue_present(state.queue, &present_info) {
+            Ok(suboptimal) if suboptimal || acquired_suboptimal => recreate_secondary_swapchain(state, window)?,
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR) => recreate_secondary_swapchain(state, window)?,
+            Ok(_) => {},
+            Err(e) => return Err(e.into()),
         }
-        return Ok(0);
+        Ok(stats)
     }
+}
 
-    let base_first_instance = ensure_instance_ring_capacity(state, needed_instances)?;
-    struct Run { set: vk::DescriptorSet, start: u32, count: u32 }
-
-    let mut runs: Vec<Run> = Vec::new();
-    let mut written: u32 = 0;
+pub fn resize_secondary_window(state: &mut State, window: &mut SecondaryWindow, width: u32, height: u32) {
+    window.window_size = PhysicalSize::new(width, height);
+    if width > 0 && height > 0 {
+        if let Err(e) = recreate_secondary_swapchain(state, window) {
+            error!("Failed to recreate secondary swapchain: {}", e);
+        }
+    }
+}
 
+/// Frees a secondary window's swapchain and surface. Call before `cleanup`.
+pub fn destroy_secondary_window(state: &mut State, window: SecondaryWindow) {
+    let device = state.device.as_ref().unwrap();
     unsafe {
-        let dst_base = state.instance_ring_ptr.add(base_first_instance as usize);
-        let mut last_set = vk::DescriptorSet::null();
-
-        for obj in &render_list.objects {
-            let (texture_id, tint, uv_scale, uv_offset, edge_fade) = match &obj.object_type {
-                ObjectType::Sprite { texture_id, tint, uv_scale, uv_offset, edge_fade } => {
-                    (texture_id, tint, uv_scale, uv_offset, edge_fade)
-                }
-            };
+        let _ = device.device_wait_idle();
+        for buffer in &window.buffers {
+            destroy_buffer(device, buffer);
+        }
+        destroy_swapchain_resources(device, &window.swapchain_resources);
+        device.destroy_semaphore(window.image_available, None);
+        device.destroy_semaphore(window.render_finished, None);
+        device.destroy_fence(window.fence, None);
+        device.free_command_buffers(state.command_pool, &[window.cmd]);
+        state.surface_loader.destroy_surface(window.surface, None);
+    }
+}
 
-            let set_opt = textures.get(texture_id).and_then(|t| {
-                if let RendererTexture::Vulkan(tex) = t {
-                    Some(tex.descriptor_set)
-                } else {
-                    None
-                }
-            });
-            let set = match set_opt { Some(s) => s, None => continue };
-
-            let model: [[f32;4];4] = obj.transform.into();
-            let (center, size, sincos) = decompose_2d(model);
-            let dst_ptr = dst_base.add(written as usize);
-            std::ptr::write(dst_ptr, InstanceData { center, size, rot_sin_cos: sincos, tint: *tint, uv_scale: *uv_scale, uv_offset: *uv_offset, edge_fade: *edge_fade });
-
-            if runs.is_empty() || set != last_set {
-                runs.push(Run { set, start: written, count: 1 });
-                last_set = set;
-            } else {
-                if let Some(r) = runs.last_mut() {
-                    r.count += 1;
-                }
+// A mapped vertex buffer filled with `data`; slower to read than device-local
+// memory, but needs no staging copy.
+fn create_host_vertex_buffer<T: Copy>(state: &State, device: &Device, data: &[T]) -> Result<BufferResource, Box<dyn Error>> {
+    let size = (mem::size_of::<T>() * data.len()) as vk::DeviceSize;
+    let (buffer, memory) = create_gpu_buffer(
+        &state.instance, device, state.pdevice, size, vk::BufferUsageFlags::VERTEX_BUFFER,
+        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+    )?;
+    let resource = BufferResource { buffer, memory };
+    unsafe {
+        match device.map_memory(memory, 0, size, vk::MemoryMapFlags::empty()) {
+            Ok(mapped) => {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), mapped as *mut T, data.len());
+                device.unmap_memory(memory);
+            }
+            Err(e) => {
+                destroy_buffer(device, &resource);
+                return Err(e.into());
             }
-            written += 1;
         }
+    }
+    Ok(resource)
+}
 
-        if written == 0 {
-            // ... (clear-only path, same as above)
-            return Ok(0);
-        }
+fn count_instances(render_list: &RenderList) -> usize {
+    render_list.objects.iter().filter(|o| {
+        matches!(&o.object_type, ObjectType::Sprite { .. } | ObjectType::Mask { .. })
+    }).count()
+}
 
-        let device_arc = state.device.as_ref().unwrap().clone();
-        let device = device_arc.as_ref();
+// Consecutive objects that can share one draw call, in submission order.
+enum Run {
+    // `bindless`: `set` is the slot array and the instances say which texture to sample.
+    Sprites { set: vk::DescriptorSet, bindless: bool, material: usize, blend: BlendMode, start: u32, count: u32, mask: u8, clip: Option<[f32; 4]> },
+    Lines { start: u32, count: u32, blend: BlendMode, mask: u8, clip: Option<[f32; 4]> },
+    MaskWrite { set: vk::DescriptorSet, start: u32, id: u8 },
+}
 
-        let fence = state.in_flight_fences[state.current_frame];
-        device.wait_for_fences(&[fence], true, u64::MAX)?;
+// Vertex buffers a list of runs reads from, and where its data starts in them.
+struct RunBuffers {
+    instances: vk::Buffer,
+    base_first_instance: u32,
+    lines: vk::Buffer,
+    base_first_vertex: u32,
+}
 
-        let (image_index, acquired_suboptimal) =
-            match state.swapchain_resources.swapchain_loader.acquire_next_image(
-                state.swapchain_resources.swapchain,
-                u64::MAX,
-                state.image_available_semaphores[state.current_frame],
-                vk::Fence::null(),
-            ) {
-                Ok(pair) => pair,
-                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => { recreate_swapchain_and_dependents(state)?; return Ok(0); }
-                Err(e) => return Err(e.into()),
-            };
+/// Writes one `InstanceData` per sprite/mask to `dst_base` (room for
+/// `count_instances` entries) and tessellates polylines and meshes into `line_vertices`,
+/// one run per `batch::Batch`. With `bindless_set`, sprites whose textures have a
+/// slot share a batch key, so only material, blend, mask or clip changes split them.
+/// Returns the runs, each with the `RenderObject::group` it starts in, and the instances written.
+unsafe fn build_runs(
+    render_list: &RenderList,
+    textures: &HashMap<String, RendererTexture>,
+    bindless_set: Option<vk::DescriptorSet>,
+    dst_base: *mut InstanceData,
+    line_vertices: &mut Vec<ColorVertex>,
+) -> (Vec<(u32, Run)>, u32) {
+    let mut runs: Vec<(u32, Run)> = Vec::new();
+    let mut written: u32 = 0;
+    let batches = batch::batches(&render_list.objects, |id| match textures.get(id) {
+        Some(RendererTexture::Vulkan(tex)) => Some(match (bindless_set, &tex.bindless_slot) {
+            (Some(set), Some(_)) => (set, true),
+            _ => (tex.descriptor_set, false),
+        }),
+        _ => None,
+    });
 
-        let in_flight = state.images_in_flight[image_index as usize];
-        if in_flight != vk::Fence::null() {
-            device.wait_for_fences(&[in_flight], true, u64::MAX)?;
+    unsafe {
+        for b in batches {
+            let objects = &render_list.objects[b.objects];
+            let group = objects.first().map_or(0, |o| o.group);
+            match b.kind {
+                BatchKind::Sprites { texture: (set, bindless), material } => {
+                    let start = written;
+                    for obj in objects {
+                        let ObjectType::Sprite { texture_id, tint, uv_scale, uv_offset, edge_fade, corner_tint, material, glow } = &obj.object_type else {
+                            continue;
+                        };
+                        let Some(RendererTexture::Vulkan(tex)) = textures.get(texture_id) else { continue };
+                        let slot = if bindless { tex.bindless_slot.as_ref().map_or(0, |s| s.index) } else { 0 };
+                        std::ptr::write(dst_base.add(written as usize), InstanceData {
+                            model: obj.transform.into(), tint: *tint, uv_scale: *uv_scale, uv_offset: *uv_offset, edge_fade: *edge_fade,
+                            corner_tint: corner_tint.map(pack_unorm4x8), material: material.params(), texture: slot,
+                            glow: pack_unorm4x8(*glow),
+                        });
+                        written += 1;
+                    }
+                    if written > start {
+                        runs.push((group, Run::Sprites {
+                            set, bindless, material, blend: b.blend, start, count: written - start, mask: b.mask, clip: b.clip,
+                        }));
+                    }
+                }
+                BatchKind::Shapes => {
+                    let start = line_vertices.len() as u32;
+                    for obj in objects {
+                        if let ObjectType::Polyline { points, colors, thickness } = &obj.object_type {
+                            tessellate_polyline(points, colors, *thickness, &obj.transform, line_vertices);
+                        } else if let ObjectType::Mesh { vertices, indices, colors } = &obj.object_type {
+                            expand_mesh(vertices, indices, colors, &obj.transform, line_vertices);
+                        }
+                    }
+                    let count = line_vertices.len() as u32 - start;
+                    if count > 0 {
+                        runs.push((group, Run::Lines { start, count, blend: b.blend, mask: b.mask, clip: b.clip }));
+                    }
+                }
+                BatchKind::Mask { id } => {
+                    let Some(obj) = objects.iter().find(|o| matches!(o.object_type, ObjectType::Mask { .. })) else { continue };
+                    let Some(RendererTexture::Vulkan(white)) = textures.get("__white") else { continue };
+                    std::ptr::write(dst_base.add(written as usize), InstanceData {
+                        model: obj.transform.into(), tint: [1.0; 4], uv_scale: [1.0, 1.0], uv_offset: [0.0, 0.0],
+                        edge_fade: [0.0; 4], corner_tint: [pack_unorm4x8([1.0; 4]); 4], material: [0.0; 4], texture: 0, glow: 0,
+                    });
+                    runs.push((obj.group, Run::MaskWrite { set: white.descriptor_set, start: written, id }));
+                    written += 1;
+                }
+            }
         }
-        state.images_in_flight[image_index as usize] = fence;
-
-        device.reset_fences(&[fence])?;
-        let cmd = state.command_buffers[state.current_frame];
-        device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())?;
-        device.begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))?;
-
-        let c = render_list.clear_color;
-        let clear_value = vk::ClearValue { color: vk::ClearColorValue { float32: [c[0], c[1], c[2], c[3]] } };
-        let rp_info = vk::RenderPassBeginInfo::default()
-            .render_pass(state.render_pass)
-            .framebuffer(state.swapchain_resources.framebuffers[image_index as usize])
-            .render_area(vk::Rect2D { offset: vk::Offset2D::default(), extent: state.swapchain_resources.extent })
-            .clear_values(std::slice::from_ref(&clear_value));
-        device.cmd_begin_render_pass(cmd, &rp_info, vk::SubpassContents::INLINE);
+    }
+    (runs, written)
+}
 
-        let vp = vk::Viewport { x: 0.0, y: state.swapchain_resources.extent.height as f32, width: state.swapchain_resources.extent.width as f32, height: -(state.swapchain_resources.extent.height as f32), min_depth: 0.0, max_depth: 1.0, };
+/// Records `runs` into the render pass already begun on `cmd`, inside a label per
+/// `labels` when the instance has debug utils. Returns what was recorded.
+unsafe fn record_runs(
+    state: &State,
+    device: &Device,
+    cmd: vk::CommandBuffer,
+    runs: Vec<(u32, Run)>,
+    labels: Option<&DebugLabels>,
+    buffers: &RunBuffers,
+    extent: vk::Extent2D,
+    projection: Matrix4<f32>,
+) -> FrameStats {
+    unsafe {
+        let vp = vk::Viewport { x: 0.0, y: extent.height as f32, width: extent.width as f32, height: -(extent.height as f32), min_depth: 0.0, max_depth: 1.0, };
         device.cmd_set_viewport(cmd, 0, &[vp]);
-        let sc = vk::Rect2D { offset: vk::Offset2D::default(), extent: state.swapchain_resources.extent };
-        device.cmd_set_scissor(cmd, 0, &[sc]);
-
-        device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, state.sprite_pipeline);
-        let pc = ProjPush { proj: state.projection };
-        device.cmd_push_constants(cmd, state.sprite_pipeline_layout, vk::ShaderStageFlags::VERTEX, 0, bytes_of(&pc));
-        let vb0 = state.vertex_buffer.as_ref().unwrap().buffer;
-        let inst_buf = state.instance_ring.as_ref().unwrap().buffer;
-        device.cmd_bind_vertex_buffers(cmd, 0, &[vb0, inst_buf], &[0, 0]);
-        let ib = state.index_buffer.as_ref().unwrap().buffer;
-        device.cmd_bind_index_buffer(cmd, ib, 0, vk::IndexType::UINT16);
+        let full = vk::Rect2D { offset: vk::Offset2D::default(), extent };
+        device.cmd_set_scissor(cmd, 0, &[full]);
+        let mut last_clip: Option<[f32; 4]> = None;
+        let set_clip = |clip: Option<[f32; 4]>, last: &mut Option<[f32; 4]>| {
+            if *last == clip { return; }
+            let rect = clip.map_or(full, |c| {
+                let [x, y, w, h] = clip_rect_px(c, &projection, extent.width, extent.height, true);
+                vk::Rect2D {
+                    offset: vk::Offset2D { x, y },
+                    extent: vk::Extent2D { width: w as u32, height: h as u32 },
+                }
+            });
+            device.cmd_set_scissor(cmd, 0, &[rect]);
+            *last = clip;
+        };
 
+        let pc = ProjPush { proj: projection };
+        let mut bound_pipeline = vk::Pipeline::null();
         let mut last_set = vk::DescriptorSet::null();
-        let mut vertices_drawn: u32 = 0;
-        for run in runs {
-            if last_set != run.set {
-                device.cmd_bind_descriptor_sets(cmd, vk::PipelineBindPoint::GRAPHICS, state.sprite_pipeline_layout, 0, &[run.set], &[]);
-                last_set = run.set;
+        let mut stats = FrameStats::default();
+        // Stencil compare state as last set; None forces the next run to set it.
+        let mut last_stencil: Option<u8> = None;
+        let set_stencil = |mask: u8, last: &mut Option<u8>| {
+            if *last == Some(mask) { return; }
+            let compare_mask = if mask == 0 { 0 } else { 0xFF };
+            device.cmd_set_stencil_compare_mask(cmd, vk::StencilFaceFlags::FRONT_AND_BACK, compare_mask);
+            device.cmd_set_stencil_reference(cmd, vk::StencilFaceFlags::FRONT_AND_BACK, mask as u32);
+            *last = Some(mask);
+        };
+        let labels = state.debug_utils.as_ref().zip(labels);
+        if let Some((utils, labels)) = labels {
+            begin_label(utils, cmd, &labels.screen);
+        }
+        let mut open_group: Option<u32> = None;
+        for (group, run) in runs {
+            if let Some((utils, labels)) = labels.filter(|_| open_group != Some(group)) {
+                if open_group.is_some() {
+                    utils.cmd_end_debug_utils_label(cmd);
+                }
+                begin_label(utils, cmd, labels.group(group));
+                open_group = Some(group);
+            }
+            match run {
+                Run::MaskWrite { set, start, id } => {
+                    set_clip(None, &mut last_clip);
+                    if bound_pipeline != state.mask_pipeline {
+                        device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, state.mask_pipeline);
+                        device.cmd_push_constants(cmd, state.mask_pipeline_layout, vk::ShaderStageFlags::VERTEX, 0, bytes_of(&pc));
+                        let vb0 = state.vertex_buffer.as_ref().unwrap().buffer;
+                        let inst_buf = buffers.instances;
+                        device.cmd_bind_vertex_buffers(cmd, 0, &[vb0, inst_buf], &[0, 0]);
+                        let ib = state.index_buffer.as_ref().unwrap().buffer;
+                        device.cmd_bind_index_buffer(cmd, ib, 0, vk::IndexType::UINT16);
+                        bound_pipeline = state.mask_pipeline;
+                        last_set = vk::DescriptorSet::null();
+                    }
+                    if last_set != set {
+                        device.cmd_bind_descriptor_sets(cmd, vk::PipelineBindPoint::GRAPHICS, state.mask_pipeline_layout, 0, &[set], &[]);
+                        last_set = set;
+                        stats.texture_binds += 1;
+                    }
+                    device.cmd_set_stencil_reference(cmd, vk::StencilFaceFlags::FRONT_AND_BACK, id as u32);
+                    last_stencil = None;
+                    device.cmd_draw_indexed(cmd, 6, 1, 0, 0, buffers.base_first_instance + start);
+                    stats.vertices = stats.vertices.saturating_add(4);
+                    stats.draw_calls += 1;
+                    stats.instances += 1;
+                }
+                Run::Sprites { set, bindless, material, blend, start, count, mask, clip } => {
+                    set_stencil(mask, &mut last_stencil);
+                    set_clip(clip, &mut last_clip);
+                    let pipeline = match (bindless, &state.bindless) {
+                        (true, Some(b)) => &b.pipelines[sprite_pipeline_index(blend, material)],
+                        _ => &state.sprite_pipelines[sprite_pipeline_index(blend, material)],
+                    };
+                    if bound_pipeline != pipeline.pipe {
+                        device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, pipeline.pipe);
+                        device.cmd_push_constants(cmd, pipeline.layout, vk::ShaderStageFlags::VERTEX, 0, bytes_of(&pc));
+                        let vb0 = state.vertex_buffer.as_ref().unwrap().buffer;
+                        let inst_buf = buffers.instances;
+                        device.cmd_bind_vertex_buffers(cmd, 0, &[vb0, inst_buf], &[0, 0]);
+                        let ib = state.index_buffer.as_ref().unwrap().buffer;
+                        device.cmd_bind_index_buffer(cmd, ib, 0, vk::IndexType::UINT16);
+                        bound_pipeline = pipeline.pipe;
+                        last_set = vk::DescriptorSet::null();
+                    }
+                    if last_set != set {
+                        device.cmd_bind_descriptor_sets(cmd, vk::PipelineBindPoint::GRAPHICS, pipeline.layout, 0, &[set], &[]);
+                        last_set = set;
+                        stats.texture_binds += 1;
+                    }
+                    device.cmd_draw_indexed(cmd, 6, count, 0, 0, buffers.base_first_instance + start);
+                    stats.vertices = stats.vertices.saturating_add(4 * count);
+                    stats.draw_calls += 1;
+                    stats.instances += count;
+                }
+                Run::Lines { start, count, blend, mask, clip } => {
+                    set_stencil(mask, &mut last_stencil);
+                    set_clip(clip, &mut last_clip);
+                    let pipeline = &state.color_pipelines[blend.index()];
+                    if bound_pipeline != pipeline.pipe {
+                        device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, pipeline.pipe);
+                        device.cmd_push_constants(cmd, pipeline.layout, vk::ShaderStageFlags::VERTEX, 0, bytes_of(&pc));
+                        let line_buf = buffers.lines;
+                        device.cmd_bind_vertex_buffers(cmd, 0, &[line_buf], &[0]);
+                        bound_pipeline = pipeline.pipe;
+                    }
+                    device.cmd_draw(cmd, count, 1, buffers.base_first_vertex + start, 0);
+                    stats.vertices = stats.vertices.saturating_add(count);
+                    stats.draw_calls += 1;
+                }
             }
-            let first_instance = base_first_instance + run.start;
-            device.cmd_draw_indexed(cmd, 6, run.count, 0, 0, first_instance);
-            vertices_drawn = vertices_drawn.saturating_add(4 * run.count);
         }
-
-        device.cmd_end_render_pass(cmd);
-        device.end_command_buffer(cmd)?;
-
-        let wait = [state.image_available_semaphores[state.current_frame]];
-        let sig  = [state.render_finished_semaphores[state.current_frame]];
-        let stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-        let submit = vk::SubmitInfo::default().wait_semaphores(&wait).wait_dst_stage_mask(&stages).command_buffers(std::slice::from_ref(&cmd)).signal_semaphores(&sig);
-        device.queue_submit(state.queue, &[submit], fence)?;
-
-        let present_info = vk::PresentInfoKHR::default().wait_semaphores(&sig).swapchains(std::slice::from_ref(&state.swapchain_resources.swapchain)).image_indices(std::slice::from_ref(&image_index));
-        match state.swapchain_resources.swapchain_loader.queue_present(state.queue, &present_info) {
-            Ok(suboptimal) if suboptimal || acquired_suboptimal => recreate_swapchain_and_dependents(state)?,
-            Err(vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR) => recreate_swapchain_and_dependents(state)?,
-            Ok(_) => {},
-            Err(e) => return Err(e.into()),
+        if let Some((utils, _)) = labels {
+            if open_group.is_some() {
+                utils.cmd_end_debug_utils_label(cmd);
+            }
+            utils.cmd_end_debug_utils_label(cmd);
         }
-
-        state.current_frame = (state.current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
-        Ok(vertices_drawn)
+        stats
     }
 }
 
+unsafe fn begin_label(utils: &ash::ext::debug_utils::Device, cmd: vk::CommandBuffer, name: &str) {
+    let name = ffi::CString::new(name).unwrap_or_default();
+    unsafe { utils.cmd_begin_debug_utils_label(cmd, &vk::DebugUtilsLabelEXT::default().label_name(&name)) };
+}
+
 pub fn cleanup(state: &mut State) {
     info!("Cleaning up Vulkan resources...");
     unsafe {
@@ -767,10 +2930,16 @@ pub fn cleanup(state: &mut State) {
         }
     }
 
+    // Their Drop needs the device.
+    state.post_targets.clear();
+
     unsafe {
         cleanup_swapchain_and_dependents(state);
 
-        for i in 0..MAX_FRAMES_IN_FLIGHT {
+        if let Some(timer) = state.gpu_timer.take() {
+            state.device.as_ref().unwrap().destroy_query_pool(timer.pool, None);
+        }
+        for i in 0..state.in_flight_fences.len() {
             state.device.as_ref().unwrap().destroy_semaphore(state.render_finished_semaphores[i], None);
             state.device.as_ref().unwrap().destroy_semaphore(state.image_available_semaphores[i], None);
             state.device.as_ref().unwrap().destroy_fence(state.in_flight_fences[i], None);
@@ -791,13 +2960,42 @@ pub fn cleanup(state: &mut State) {
             }
             destroy_buffer(state.device.as_ref().unwrap(), &ring);
         }
+        if let Some(ring) = state.line_ring.take() {
+            if !state.line_ring_ptr.is_null() {
+                state.device.as_ref().unwrap().unmap_memory(ring.memory);
+                state.line_ring_ptr = std::ptr::null_mut();
+            }
+            destroy_buffer(state.device.as_ref().unwrap(), &ring);
+        }
+        for upload in state.uploads.in_flight.drain(..) {
+            state.device.as_ref().unwrap().destroy_fence(upload.fence, None);
+            state.device.as_ref().unwrap().free_command_buffers(state.command_pool, &[upload.cmd]);
+        }
+        state.uploads.done.clear();
+        if let Some((ring, _)) = state.uploads.ring.take() {
+            state.device.as_ref().unwrap().unmap_memory(ring.memory);
+            destroy_buffer(state.device.as_ref().unwrap(), &ring);
+        }
 
         state.device.as_ref().unwrap().destroy_sampler(state.sampler, None);
+        state.device.as_ref().unwrap().destroy_sampler(state.nearest_sampler, None);
         state.device.as_ref().unwrap().destroy_descriptor_pool(state.descriptor_pool, None);
         state.device.as_ref().unwrap().destroy_descriptor_set_layout(state.descriptor_set_layout, None);
-        state.device.as_ref().unwrap().destroy_pipeline(state.sprite_pipeline, None);
-        state.device.as_ref().unwrap().destroy_pipeline_layout(state.sprite_pipeline_layout, None);
+        destroy_pipelines(state.device.as_ref().unwrap(), &state.sprite_pipelines);
+        if let Some(bindless) = state.bindless.take() {
+            destroy_pipelines(state.device.as_ref().unwrap(), &bindless.pipelines);
+            state.device.as_ref().unwrap().destroy_descriptor_pool(bindless.pool, None);
+            state.device.as_ref().unwrap().destroy_descriptor_set_layout(bindless.set_layout, None);
+        }
+        destroy_pipelines(state.device.as_ref().unwrap(), &state.color_pipelines);
+        state.device.as_ref().unwrap().destroy_pipeline(state.mask_pipeline, None);
+        state.device.as_ref().unwrap().destroy_pipeline_layout(state.mask_pipeline_layout, None);
+        state.device.as_ref().unwrap().destroy_pipeline(state.post_pipeline, None);
+        state.device.as_ref().unwrap().destroy_pipeline_layout(state.post_pipeline_layout, None);
+        save_pipeline_cache(state.device.as_ref().unwrap(), state.pipeline_cache);
+        state.device.as_ref().unwrap().destroy_pipeline_cache(state.pipeline_cache, None);
         state.device.as_ref().unwrap().destroy_render_pass(state.render_pass, None);
+        state.device.as_ref().unwrap().destroy_render_pass(state.target_render_pass, None);
         state.device.as_ref().unwrap().destroy_command_pool(state.command_pool, None);
         state.surface_loader.destroy_surface(state.surface, None);
 
@@ -814,6 +3012,19 @@ pub fn cleanup(state: &mut State) {
     info!("Vulkan resources cleaned up.");
 }
 
+pub fn set_present_mode(state: &mut State, present: PresentSettings) -> Result<(), Box<dyn Error>> {
+    // Picked up by `ensure_post_targets` on the next frame.
+    state.present.render_scale = present.render_scale;
+    state.present.upscale_filter = present.upscale_filter;
+    if state.present.vsync == present.vsync && state.present.mode == present.mode {
+        return Ok(());
+    }
+    info!("Switching present mode to {} (vsync {}).", present.mode, present.vsync);
+    state.present.vsync = present.vsync;
+    state.present.mode = present.mode;
+    recreate_swapchain_and_dependents(state)
+}
+
 pub fn resize(state: &mut State, width: u32, height: u32) {
     info!("Vulkan resize requested to {}x{}", width, height);
     state.window_size = PhysicalSize::new(width, height);
@@ -827,13 +3038,15 @@ pub fn resize(state: &mut State, width: u32, height: u32) {
 
 // --- ALL HELPER FUNCTIONS ---
 
-fn create_image_view(device: &Device, image: vk::Image, format: vk::Format) -> Result<vk::ImageView, vk::Result> {
+fn create_image_view(
+    device: &Device, image: vk::Image, format: vk::Format, aspect_mask: vk::ImageAspectFlags,
+) -> Result<vk::ImageView, vk::Result> {
     let view_info = vk::ImageViewCreateInfo::default()
         .image(image)
         .view_type(vk::ImageViewType::TYPE_2D)
         .format(format)
         .subresource_range(vk::ImageSubresourceRange {
-            aspect_mask: vk::ImageAspectFlags::COLOR,
+            aspect_mask,
             base_mip_level: 0,
             level_count: 1,
             base_array_layer: 0,
@@ -871,6 +3084,8 @@ fn create_image(
     }
 }
 
+// Alpha accumulates coverage ("over") and the other modes leave it alone, so the
+// frame's alpha is right for a transparent window; opaque windows ignore it.
 fn color_blend_for(mode: BlendMode) -> vk::PipelineColorBlendAttachmentState {
     match mode {
         BlendMode::Alpha => vk::PipelineColorBlendAttachmentState::default()
@@ -880,7 +3095,7 @@ fn color_blend_for(mode: BlendMode) -> vk::PipelineColorBlendAttachmentState {
             .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
             .color_blend_op(vk::BlendOp::ADD)
             .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
             .alpha_blend_op(vk::BlendOp::ADD),
         BlendMode::Add => vk::PipelineColorBlendAttachmentState::default()
             .color_write_mask(vk::ColorComponentFlags::RGBA)
@@ -888,8 +3103,8 @@ fn color_blend_for(mode: BlendMode) -> vk::PipelineColorBlendAttachmentState {
             .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
             .dst_color_blend_factor(vk::BlendFactor::ONE)
             .color_blend_op(vk::BlendOp::ADD)
-            .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .src_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE)
             .alpha_blend_op(vk::BlendOp::ADD),
         BlendMode::Multiply => vk::PipelineColorBlendAttachmentState::default()
             .color_write_mask(vk::ColorComponentFlags::RGBA)
@@ -897,8 +3112,8 @@ fn color_blend_for(mode: BlendMode) -> vk::PipelineColorBlendAttachmentState {
             .src_color_blend_factor(vk::BlendFactor::DST_COLOR)
             .dst_color_blend_factor(vk::BlendFactor::ZERO)
             .color_blend_op(vk::BlendOp::ADD)
-            .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .src_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE)
             .alpha_blend_op(vk::BlendOp::ADD),
         BlendMode::Subtract => vk::PipelineColorBlendAttachmentState::default()
             .color_write_mask(vk::ColorComponentFlags::RGBA)
@@ -906,12 +3121,28 @@ fn color_blend_for(mode: BlendMode) -> vk::PipelineColorBlendAttachmentState {
             .src_color_blend_factor(vk::BlendFactor::ONE)
             .dst_color_blend_factor(vk::BlendFactor::ONE)
             .color_blend_op(vk::BlendOp::REVERSE_SUBTRACT)
-            .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .src_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE)
             .alpha_blend_op(vk::BlendOp::ADD),
     }
 }
 
+// Drawing tests stencil == reference under a dynamic compare mask (0 passes everything);
+// mask writes always pass and replace the stencil with the reference.
+fn stencil_op_for(mask_write: bool) -> vk::StencilOpState {
+    let (compare_op, pass_op, write_mask) = if mask_write {
+        (vk::CompareOp::ALWAYS, vk::StencilOp::REPLACE, 0xFF)
+    } else {
+        (vk::CompareOp::EQUAL, vk::StencilOp::KEEP, 0)
+    };
+    vk::StencilOpState::default()
+        .fail_op(vk::StencilOp::KEEP)
+        .pass_op(pass_op)
+        .depth_fail_op(vk::StencilOp::KEEP)
+        .compare_op(compare_op)
+        .write_mask(write_mask)
+}
+
 fn create_texture_descriptor_set(
     state: &State,
     texture_image_view: vk::ImageView,
@@ -941,10 +3172,18 @@ fn create_texture_descriptor_set(
     Ok(descriptor_set)
 }
 
+/// Packs an RGBA color into the layout GLSL's `unpackUnorm4x8` expects (R in the low byte).
+#[inline(always)]
+fn pack_unorm4x8(c: [f32; 4]) -> u32 {
+    c.iter().enumerate().fold(0u32, |acc, (i, v)| {
+        acc | (((v.clamp(0.0, 1.0) * 255.0).round() as u32) << (8 * i))
+    })
+}
+
 #[inline(always)]
 fn vertex_input_descriptions_textured_instanced() -> (
     [vk::VertexInputBindingDescription; 2],
-    [vk::VertexInputAttributeDescription; 9],
+    [vk::VertexInputAttributeDescription; 14],
 ) {
     // binding 0: unit quad [x,y,u,v]
     let b0 = vk::VertexInputBindingDescription::default()
@@ -955,7 +3194,7 @@ fn vertex_input_descriptions_textured_instanced() -> (
     // binding 1: compact per-instance payload
     let b1 = vk::VertexInputBindingDescription::default()
         .binding(1)
-        .stride(std::mem::size_of::<InstanceData>() as u32) // 72
+        .stride(std::mem::size_of::<InstanceData>() as u32) // 152
         .input_rate(vk::VertexInputRate::INSTANCE);
 
     // per-vertex
@@ -964,23 +3203,27 @@ fn vertex_input_descriptions_textured_instanced() -> (
     let a1 = vk::VertexInputAttributeDescription::default()
         .binding(0).location(1).format(vk::Format::R32G32_SFLOAT).offset(8);  // uv
 
-    // per-instance
-    let i_center = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(2).format(vk::Format::R32G32_SFLOAT).offset(0);
-    let i_size = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(3).format(vk::Format::R32G32_SFLOAT).offset(8);
-    let i_rot = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(4).format(vk::Format::R32G32_SFLOAT).offset(16);
+    // per-instance: the mat4 model occupies one location per column (2..5)
+    let i_model = |col: u32| vk::VertexInputAttributeDescription::default()
+        .binding(1).location(2 + col).format(vk::Format::R32G32B32A32_SFLOAT).offset(16 * col);
     let i_tint = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(5).format(vk::Format::R32G32B32A32_SFLOAT).offset(24);
+        .binding(1).location(6).format(vk::Format::R32G32B32A32_SFLOAT).offset(64);
     let i_uvs = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(6).format(vk::Format::R32G32_SFLOAT).offset(40);
+        .binding(1).location(7).format(vk::Format::R32G32_SFLOAT).offset(80);
     let i_uvo = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(7).format(vk::Format::R32G32_SFLOAT).offset(48);
+        .binding(1).location(8).format(vk::Format::R32G32_SFLOAT).offset(88);
     let i_fade = vk::VertexInputAttributeDescription::default()
-        .binding(1).location(8).format(vk::Format::R32G32B32A32_SFLOAT).offset(56);
-
-    ([b0, b1], [a0, a1, i_center, i_size, i_rot, i_tint, i_uvs, i_uvo, i_fade])
+        .binding(1).location(9).format(vk::Format::R32G32B32A32_SFLOAT).offset(96);
+    let i_corners = vk::VertexInputAttributeDescription::default()
+        .binding(1).location(10).format(vk::Format::R32G32B32A32_UINT).offset(112);
+    let i_material = vk::VertexInputAttributeDescription::default()
+        .binding(1).location(11).format(vk::Format::R32G32B32A32_SFLOAT).offset(128);
+    let i_texture = vk::VertexInputAttributeDescription::default()
+        .binding(1).location(12).format(vk::Format::R32_UINT).offset(144);
+    let i_glow = vk::VertexInputAttributeDescription::default()
+        .binding(1).location(13).format(vk::Format::R32_UINT).offset(148);
+
+    ([b0, b1], [a0, a1, i_model(0), i_model(1), i_model(2), i_model(3), i_tint, i_uvs, i_uvo, i_fade, i_corners, i_material, i_texture, i_glow])
 }
 
 fn begin_single_time_commands(device: &Device, pool: vk::CommandPool) -> Result<vk::CommandBuffer, vk::Result> {
@@ -1112,10 +3355,25 @@ fn create_instance(entry: &Entry, window: &Window) -> Result<Instance, Box<dyn E
         .api_version(vk::API_VERSION_1_3);
 
     let mut extension_names = ash_window::enumerate_required_extensions(window.display_handle()?.as_raw())?.to_vec();
-    if cfg!(debug_assertions) {
+    // Also wanted in release builds for the command labels capture tools show.
+    if cfg!(debug_assertions) || instance_supports_extension(entry, ash::ext::debug_utils::NAME) {
         extension_names.push(ash::ext::debug_utils::NAME.as_ptr());
     }
 
+    // Lists the HDR color spaces among the surface formats.
+    let available = unsafe { entry.enumerate_instance_extension_properties(None)? };
+    if available.iter().any(|ext| ext.extension_name_as_c_str().is_ok_and(|n| n == ash::ext::swapchain_colorspace::NAME)) {
+        extension_names.push(ash::ext::swapchain_colorspace::NAME.as_ptr());
+    }
+
+    // macOS has no native Vulkan; MoltenVK is a portability driver, which the
+    // loader only lists when the instance opts in.
+    let mut flags = vk::InstanceCreateFlags::empty();
+    if cfg!(target_os = "macos") {
+        extension_names.push(ash::khr::portability_enumeration::NAME.as_ptr());
+        flags |= vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR;
+    }
+
     let layers_names_raw: Vec<*const ffi::c_char> = if cfg!(debug_assertions) {
         vec![ffi::CStr::from_bytes_with_nul(b"VK_LAYER_KHRONOS_validation\0")?.as_ptr()]
     } else {
@@ -1123,6 +3381,7 @@ fn create_instance(entry: &Entry, window: &Window) -> Result<Instance, Box<dyn E
     };
 
     let create_info = vk::InstanceCreateInfo::default()
+        .flags(flags)
         .application_info(&app_info)
         .enabled_extension_names(&extension_names)
         .enabled_layer_names(&layers_names_raw);
@@ -1222,28 +3481,117 @@ fn find_queue_family(
     })
 }
 
+fn instance_supports_extension(entry: &Entry, name: &ffi::CStr) -> bool {
+    let Ok(extensions) = (unsafe { entry.enumerate_instance_extension_properties(None) }) else {
+        return false;
+    };
+    extensions
+        .iter()
+        .any(|ext| ext.extension_name_as_c_str().is_ok_and(|n| n == name))
+}
+
+fn device_supports_extension(instance: &Instance, pdevice: vk::PhysicalDevice, name: &ffi::CStr) -> bool {
+    let Ok(extensions) = (unsafe { instance.enumerate_device_extension_properties(pdevice) }) else {
+        return false;
+    };
+    extensions
+        .iter()
+        .any(|ext| ext.extension_name_as_c_str().is_ok_and(|n| n == name))
+}
+
+/// Texture slots for the bindless array when the device has the descriptor
+/// indexing it needs (Vulkan 1.2), capped at `MAX_BINDLESS_TEXTURES`.
+fn bindless_slot_count(instance: &Instance, pdevice: vk::PhysicalDevice) -> Option<u32> {
+    let props = unsafe { instance.get_physical_device_properties(pdevice) };
+    if props.api_version < vk::API_VERSION_1_2 {
+        return None;
+    }
+    let mut features12 = vk::PhysicalDeviceVulkan12Features::default();
+    let mut features = vk::PhysicalDeviceFeatures2::default().push_next(&mut features12);
+    unsafe { instance.get_physical_device_features2(pdevice, &mut features) };
+    let supported = features12.runtime_descriptor_array == vk::TRUE
+        && features12.descriptor_binding_partially_bound == vk::TRUE
+        && features12.shader_sampled_image_array_non_uniform_indexing == vk::TRUE
+        && features12.descriptor_binding_sampled_image_update_after_bind == vk::TRUE
+        && features12.descriptor_binding_update_unused_while_pending == vk::TRUE;
+    if !supported {
+        return None;
+    }
+    let mut props12 = vk::PhysicalDeviceVulkan12Properties::default();
+    let mut props2 = vk::PhysicalDeviceProperties2::default().push_next(&mut props12);
+    unsafe { instance.get_physical_device_properties2(pdevice, &mut props2) };
+    let limit = props12.max_per_stage_descriptor_update_after_bind_samplers
+        .min(props12.max_per_stage_descriptor_update_after_bind_sampled_images)
+        .min(props12.max_descriptor_set_update_after_bind_samplers)
+        .min(props12.max_descriptor_set_update_after_bind_sampled_images);
+    Some(limit.min(MAX_BINDLESS_TEXTURES)).filter(|&slots| slots > 0)
+}
+
+/// Also returns the bindless slot count when descriptor indexing was enabled.
 fn create_logical_device(
     instance: &Instance,
     pdevice: vk::PhysicalDevice,
     surface_loader: &surface::Instance,
     surface: vk::SurfaceKHR,
-) -> Result<(Device, vk::Queue, u32), Box<dyn Error>> {
+) -> Result<(Device, vk::Queue, u32, Option<u32>), Box<dyn Error>> {
     let queue_family_index = find_queue_family(instance, pdevice, surface_loader, surface)
         .ok_or("No suitable queue family found")?;
     let queue_priorities = [1.0];
     let queue_create_info = vk::DeviceQueueCreateInfo::default()
         .queue_family_index(queue_family_index)
         .queue_priorities(&queue_priorities);
-    let device_extensions = [swapchain::NAME.as_ptr()];
-    let features = vk::PhysicalDeviceFeatures::default();
-    let create_info = vk::DeviceCreateInfo::default()
+    let mut device_extensions = vec![swapchain::NAME.as_ptr()];
+    // Portability drivers (MoltenVK) must have their subset extension enabled.
+    if device_supports_extension(instance, pdevice, ash::khr::portability_subset::NAME) {
+        info!("Enabling VK_KHR_portability_subset (MoltenVK).");
+        device_extensions.push(ash::khr::portability_subset::NAME.as_ptr());
+    }
+    if monotonic_ns().is_some() && device_supports_extension(instance, pdevice, ash::google::display_timing::NAME) {
+        device_extensions.push(ash::google::display_timing::NAME.as_ptr());
+    }
+    // BC formats are used for pre-compressed backgrounds and banners when available.
+    let supported = unsafe { instance.get_physical_device_features(pdevice) };
+    let features = vk::PhysicalDeviceFeatures::default()
+        .texture_compression_bc(supported.texture_compression_bc == vk::TRUE);
+    let bindless_slots = bindless_slot_count(instance, pdevice);
+    let mut features12 = vk::PhysicalDeviceVulkan12Features::default()
+        .runtime_descriptor_array(true)
+        .descriptor_binding_partially_bound(true)
+        .shader_sampled_image_array_non_uniform_indexing(true)
+        .descriptor_binding_sampled_image_update_after_bind(true)
+        .descriptor_binding_update_unused_while_pending(true);
+    let mut create_info = vk::DeviceCreateInfo::default()
         .queue_create_infos(std::slice::from_ref(&queue_create_info))
         .enabled_extension_names(&device_extensions)
         .enabled_features(&features);
+    if bindless_slots.is_some() {
+        create_info = create_info.push_next(&mut features12);
+    }
 
     let device = unsafe { instance.create_device(pdevice, &create_info, None)? };
     let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
-    Ok((device, queue, queue_family_index))
+    Ok((device, queue, queue_family_index, bindless_slots))
+}
+
+// Blending leaves premultiplied color in the frame, so a transparent window wants
+// PRE_MULTIPLIED; the others are closer than showing it opaque.
+fn composite_alpha(capabilities: &vk::SurfaceCapabilitiesKHR, transparent: bool) -> vk::CompositeAlphaFlagsKHR {
+    let supported = capabilities.supported_composite_alpha;
+    let preferred = [
+        vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+        vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED,
+        vk::CompositeAlphaFlagsKHR::INHERIT,
+    ];
+    if transparent {
+        if let Some(mode) = preferred.into_iter().find(|&m| supported.contains(m)) {
+            return mode;
+        }
+        warn!("The surface can't be composited with alpha; the overlay window will be opaque.");
+    }
+    match supported.contains(vk::CompositeAlphaFlagsKHR::OPAQUE) {
+        true => vk::CompositeAlphaFlagsKHR::OPAQUE,
+        false => preferred.into_iter().find(|&m| supported.contains(m)).unwrap_or(vk::CompositeAlphaFlagsKHR::OPAQUE),
+    }
 }
 
 fn create_swapchain(
@@ -1254,25 +3602,48 @@ fn create_swapchain(
     surface_loader: &surface::Instance,
     window_size: PhysicalSize<u32>,
     old_swapchain: Option<vk::SwapchainKHR>,
-    vsync_enabled: bool,
+    present: PresentSettings,
+    stencil_format: vk::Format,
+    samples: vk::SampleCountFlags,
 ) -> Result<SwapchainResources, Box<dyn Error>> {
     let capabilities = unsafe { surface_loader.get_physical_device_surface_capabilities(pdevice, surface)? };
     let formats = unsafe { surface_loader.get_physical_device_surface_formats(pdevice, surface)? };
     let present_modes = unsafe { surface_loader.get_physical_device_surface_present_modes(pdevice, surface)? };
 
-    let format = formats.iter().find(|f| f.format == vk::Format::B8G8R8A8_UNORM)
+    let preferred: &[vk::Format] = if present.srgb {
+        &[vk::Format::B8G8R8A8_SRGB, vk::Format::R8G8B8A8_SRGB, vk::Format::B8G8R8A8_UNORM]
+    } else {
+        &[vk::Format::B8G8R8A8_UNORM]
+    };
+    let hdr_formats: &[vk::Format] = match present.hdr {
+        HdrOutput::Off => &[],
+        HdrOutput::Hdr10 => &[vk::Format::A2B10G10R10_UNORM_PACK32, vk::Format::A2R10G10B10_UNORM_PACK32],
+        HdrOutput::ScRgb => &[vk::Format::R16G16B16A16_SFLOAT],
+    };
+    let hdr_format = hdr_color_space(present.hdr).and_then(|space| {
+        hdr_formats.iter().find_map(|&want| formats.iter().find(|f| f.format == want && f.color_space == space))
+    });
+    let format = hdr_format
+        .or_else(|| preferred.iter().find_map(|&want| {
+            formats.iter().find(|f| f.format == want && f.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR)
+        }))
         .cloned()
         .unwrap_or(formats[0]);
     
-    let present_mode = if vsync_enabled {
-        vk::PresentModeKHR::FIFO
-    } else if present_modes.contains(&vk::PresentModeKHR::MAILBOX) {
-        vk::PresentModeKHR::MAILBOX
-    } else if present_modes.contains(&vk::PresentModeKHR::IMMEDIATE) {
-        vk::PresentModeKHR::IMMEDIATE
-    } else {
-        vk::PresentModeKHR::FIFO
+    // Each choice falls back towards FIFO, the only mode every driver supports.
+    let preference: &[vk::PresentModeKHR] = match present.mode {
+        PresentMode::Auto if present.vsync => &[],
+        PresentMode::Auto | PresentMode::Mailbox => &[vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::IMMEDIATE],
+        PresentMode::Immediate => &[vk::PresentModeKHR::IMMEDIATE, vk::PresentModeKHR::MAILBOX],
+        PresentMode::Fifo => &[],
     };
+    let present_mode = preference.iter()
+        .copied()
+        .find(|m| present_modes.contains(m))
+        .unwrap_or(vk::PresentModeKHR::FIFO);
+    if present.mode != PresentMode::Auto && preference.first().is_some_and(|&m| m != present_mode) {
+        warn!("Present mode {:?} unsupported; using {:?}.", preference[0], present_mode);
+    }
 
     let desired_images =
         if present_mode == vk::PresentModeKHR::MAILBOX { 3 } else { capabilities.min_image_count + 1 };
@@ -1291,19 +3662,168 @@ fn create_swapchain(
         }
     };
 
+    // Frames are read back as 8-bit RGBA, which the HDR formats aren't.
+    let capturable = capabilities.supported_usage_flags.contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        && hdr_format.is_none();
+    let usage = match capturable {
+        true => vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+        false => vk::ImageUsageFlags::COLOR_ATTACHMENT,
+    };
     let create_info = vk::SwapchainCreateInfoKHR::default()
         .surface(surface).min_image_count(image_count).image_format(format.format)
         .image_color_space(format.color_space).image_extent(extent).image_array_layers(1)
-        .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT).image_sharing_mode(vk::SharingMode::EXCLUSIVE)
-        .pre_transform(capabilities.current_transform).composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
+        .image_usage(usage).image_sharing_mode(vk::SharingMode::EXCLUSIVE)
+        .pre_transform(capabilities.current_transform).composite_alpha(composite_alpha(&capabilities, present.transparent))
         .present_mode(present_mode).clipped(true).old_swapchain(old_swapchain.unwrap_or(vk::SwapchainKHR::null()));
 
     let swapchain_loader = swapchain::Device::new(instance, device);
     let swapchain = unsafe { swapchain_loader.create_swapchain(&create_info, None)? };
     let images = unsafe { swapchain_loader.get_swapchain_images(swapchain)? };
-    let image_views = images.iter().map(|&image| create_image_view(device, image, format.format)).collect::<Result<Vec<_>, _>>()?;
+    let image_views = images.iter().map(|&image| create_image_view(device, image, format.format, vk::ImageAspectFlags::COLOR)).collect::<Result<Vec<_>, _>>()?;
+    let stencil = create_stencil_attachment(instance, device, pdevice, stencil_format, extent, samples)?;
+    let msaa_color = create_msaa_color_attachment(instance, device, pdevice, format.format, extent, samples)?;
+
+    Ok(SwapchainResources {
+        swapchain_loader, swapchain, images, image_views, framebuffers: vec![], extent, format, stencil, msaa_color, capturable,
+    })
+}
+
+/// The swapchain color space `hdr` asks for; None when it's off.
+fn hdr_color_space(hdr: HdrOutput) -> Option<vk::ColorSpaceKHR> {
+    match hdr {
+        HdrOutput::Off => None,
+        HdrOutput::Hdr10 => Some(vk::ColorSpaceKHR::HDR10_ST2084_EXT),
+        HdrOutput::ScRgb => Some(vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT),
+    }
+}
+
+fn find_stencil_format(instance: &Instance, pdevice: vk::PhysicalDevice) -> Result<vk::Format, Box<dyn Error>> {
+    [vk::Format::D24_UNORM_S8_UINT, vk::Format::D32_SFLOAT_S8_UINT, vk::Format::D16_UNORM_S8_UINT]
+        .into_iter()
+        .find(|&format| {
+            let props = unsafe { instance.get_physical_device_format_properties(pdevice, format) };
+            props.optimal_tiling_features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+        })
+        .ok_or_else(|| "No supported depth/stencil format found".into())
+}
+
+/// Highest supported MSAA sample count that doesn't exceed `requested`, for both
+/// color and stencil attachments. 1 (or 0) means no multisampling.
+fn pick_sample_count(instance: &Instance, pdevice: vk::PhysicalDevice, requested: u32) -> vk::SampleCountFlags {
+    let limits = unsafe { instance.get_physical_device_properties(pdevice) }.limits;
+    let supported = limits.framebuffer_color_sample_counts & limits.framebuffer_stencil_sample_counts;
+    let picked = [
+        (8, vk::SampleCountFlags::TYPE_8),
+        (4, vk::SampleCountFlags::TYPE_4),
+        (2, vk::SampleCountFlags::TYPE_2),
+    ]
+    .into_iter()
+    .find(|&(count, flag)| count <= requested && supported.contains(flag));
+    match picked {
+        Some((count, flag)) => {
+            if count < requested {
+                warn!("{}x MSAA unsupported; using {}x.", requested, count);
+            }
+            info!("Vulkan MSAA: {}x.", count);
+            flag
+        }
+        None => {
+            if requested > 1 {
+                warn!("MSAA unsupported on this device; drawing without it.");
+            }
+            vk::SampleCountFlags::TYPE_1
+        }
+    }
+}
+
+fn create_attachment(
+    instance: &Instance,
+    device: &Device,
+    pdevice: vk::PhysicalDevice,
+    format: vk::Format,
+    extent: vk::Extent2D,
+    samples: vk::SampleCountFlags,
+    usage: vk::ImageUsageFlags,
+    aspect: vk::ImageAspectFlags,
+) -> Result<Attachment, vk::Result> {
+    let image_info = vk::ImageCreateInfo::default()
+        .image_type(vk::ImageType::TYPE_2D)
+        .extent(vk::Extent3D { width: extent.width, height: extent.height, depth: 1 })
+        .mip_levels(1)
+        .array_layers(1)
+        .format(format)
+        .tiling(vk::ImageTiling::OPTIMAL)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .usage(usage)
+        .samples(samples)
+        .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+    unsafe {
+        let image = device.create_image(&image_info, None)?;
+        let mem_requirements = device.get_image_memory_requirements(image);
+        let mem_type_index = find_memory_type(
+            instance, pdevice, mem_requirements.memory_type_bits, vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        );
+        let alloc_info = vk::MemoryAllocateInfo::default()
+            .allocation_size(mem_requirements.size)
+            .memory_type_index(mem_type_index);
+        let memory = device.allocate_memory(&alloc_info, None)?;
+        device.bind_image_memory(image, memory, 0)?;
+        let view = create_image_view(device, image, format, aspect)?;
+        Ok(Attachment { image, memory, view })
+    }
+}
+
+fn create_stencil_attachment(
+    instance: &Instance,
+    device: &Device,
+    pdevice: vk::PhysicalDevice,
+    format: vk::Format,
+    extent: vk::Extent2D,
+    samples: vk::SampleCountFlags,
+) -> Result<Attachment, vk::Result> {
+    create_attachment(
+        instance, device, pdevice, format, extent, samples,
+        vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+    )
+}
+
+/// The multisampled color buffer for a pass, or None without MSAA. Never
+/// stored, so it can stay in tile memory where the driver allows.
+fn create_msaa_color_attachment(
+    instance: &Instance,
+    device: &Device,
+    pdevice: vk::PhysicalDevice,
+    format: vk::Format,
+    extent: vk::Extent2D,
+    samples: vk::SampleCountFlags,
+) -> Result<Option<Attachment>, vk::Result> {
+    if samples == vk::SampleCountFlags::TYPE_1 {
+        return Ok(None);
+    }
+    create_attachment(
+        instance, device, pdevice, format, extent, samples,
+        vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+        vk::ImageAspectFlags::COLOR,
+    )
+    .map(Some)
+}
+
+fn destroy_attachment(device: &Device, attachment: &Attachment) {
+    unsafe {
+        device.destroy_image_view(attachment.view, None);
+        device.destroy_image(attachment.image, None);
+        device.free_memory(attachment.memory, None);
+    }
+}
 
-    Ok(SwapchainResources { swapchain_loader, swapchain, _images: images, image_views, framebuffers: vec![], extent, format })
+/// Framebuffer attachments in the order `create_render_pass` declares them.
+fn framebuffer_attachments(target: vk::ImageView, stencil: &Attachment, msaa_color: Option<&Attachment>) -> Vec<vk::ImageView> {
+    match msaa_color {
+        Some(color) => vec![color.view, stencil.view, target],
+        None => vec![target, stencil.view],
+    }
 }
 
 fn recreate_framebuffers(
@@ -1312,7 +3832,9 @@ fn recreate_framebuffers(
     render_pass: vk::RenderPass,
 ) -> Result<(), vk::Result> {
     swapchain_resources.framebuffers = swapchain_resources.image_views.iter().map(|view| {
-        let attachments = [*view];
+        let attachments = framebuffer_attachments(
+            *view, &swapchain_resources.stencil, swapchain_resources.msaa_color.as_ref(),
+        );
         let create_info = vk::FramebufferCreateInfo::default()
             .render_pass(render_pass)
             .attachments(&attachments)
@@ -1324,26 +3846,74 @@ fn recreate_framebuffers(
     Ok(())
 }
 
-fn create_render_pass(device: &Device, format: vk::Format) -> Result<vk::RenderPass, vk::Result> {
+/// With MSAA the pass draws into a multisampled color attachment (0) and
+/// resolves it into the target image (2) at the end; otherwise it draws into
+/// the target directly (0). The stencil is always attachment 1.
+fn create_render_pass(
+    device: &Device,
+    format: vk::Format,
+    stencil_format: vk::Format,
+    samples: vk::SampleCountFlags,
+    final_layout: vk::ImageLayout,
+) -> Result<vk::RenderPass, vk::Result> {
+    let multisampled = samples != vk::SampleCountFlags::TYPE_1;
     let color_attachment = vk::AttachmentDescription::default()
-        .format(format).samples(vk::SampleCountFlags::TYPE_1).load_op(vk::AttachmentLoadOp::CLEAR)
+        .format(format).samples(samples).load_op(vk::AttachmentLoadOp::CLEAR)
+        .store_op(if multisampled { vk::AttachmentStoreOp::DONT_CARE } else { vk::AttachmentStoreOp::STORE })
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE).initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(if multisampled { vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL } else { final_layout });
+    // Stencil only lives for the frame: cleared on load, never stored.
+    let stencil_attachment = vk::AttachmentDescription::default()
+        .format(stencil_format).samples(samples).load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .store_op(vk::AttachmentStoreOp::DONT_CARE).stencil_load_op(vk::AttachmentLoadOp::CLEAR)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE).initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+    let resolve_attachment = vk::AttachmentDescription::default()
+        .format(format).samples(vk::SampleCountFlags::TYPE_1).load_op(vk::AttachmentLoadOp::DONT_CARE)
         .store_op(vk::AttachmentStoreOp::STORE).stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
         .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE).initial_layout(vk::ImageLayout::UNDEFINED)
-        .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
+        .final_layout(final_layout);
+    let attachments: &[vk::AttachmentDescription] = if multisampled {
+        &[color_attachment, stencil_attachment, resolve_attachment]
+    } else {
+        &[color_attachment, stencil_attachment]
+    };
     let color_attachment_ref = vk::AttachmentReference::default().attachment(0).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-    let subpass = vk::SubpassDescription::default()
+    let stencil_attachment_ref = vk::AttachmentReference::default().attachment(1).layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+    let resolve_attachment_ref = vk::AttachmentReference::default().attachment(2).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+    let mut subpass = vk::SubpassDescription::default()
         .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-        .color_attachments(std::slice::from_ref(&color_attachment_ref));
-    let dependency = vk::SubpassDependency::default()
-        .src_subpass(vk::SUBPASS_EXTERNAL).dst_subpass(0)
-        .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-        .src_access_mask(vk::AccessFlags::empty())
-        .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-        .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+        .color_attachments(std::slice::from_ref(&color_attachment_ref))
+        .depth_stencil_attachment(&stencil_attachment_ref);
+    if multisampled {
+        subpass = subpass.resolve_attachments(std::slice::from_ref(&resolve_attachment_ref));
+    }
+    // In: earlier passes are done with the attachments, including sampling them
+    // (post targets are drawn into again the pass after they're read).
+    // Out: the color is written before a later pass samples it.
+    let dependencies = [
+        vk::SubpassDependency::default()
+            .src_subpass(vk::SUBPASS_EXTERNAL).dst_subpass(0)
+            .src_stage_mask(
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS
+                    | vk::PipelineStageFlags::FRAGMENT_SHADER,
+            )
+            .src_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS)
+            .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE),
+        vk::SubpassDependency::default()
+            .src_subpass(0).dst_subpass(vk::SUBPASS_EXTERNAL)
+            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+            .dst_access_mask(vk::AccessFlags::SHADER_READ),
+    ];
     let create_info = vk::RenderPassCreateInfo::default()
-        .attachments(std::slice::from_ref(&color_attachment))
+        .attachments(attachments)
         .subpasses(std::slice::from_ref(&subpass))
-        .dependencies(std::slice::from_ref(&dependency));
+        .dependencies(&dependencies);
     unsafe { device.create_render_pass(&create_info, None) }
 }
 
@@ -1368,13 +3938,13 @@ fn create_shader_module(device: &Device, code: &[u8]) -> Result<vk::ShaderModule
     unsafe { device.create_shader_module(&create_info, None) }
 }
 
-fn create_sync_objects(device: &Device) -> Result<(Vec<vk::Semaphore>, Vec<vk::Semaphore>, Vec<vk::Fence>), vk::Result> {
+fn create_sync_objects(device: &Device, frames_in_flight: usize) -> Result<(Vec<vk::Semaphore>, Vec<vk::Semaphore>, Vec<vk::Fence>), vk::Result> {
     let semaphore_info = vk::SemaphoreCreateInfo::default();
     let fence_info = vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED);
     let mut image_available = vec![];
     let mut render_finished = vec![];
     let mut in_flight_fences = vec![];
-    for _ in 0..MAX_FRAMES_IN_FLIGHT {
+    for _ in 0..frames_in_flight {
         image_available.push(unsafe { device.create_semaphore(&semaphore_info, None)? });
         render_finished.push(unsafe { device.create_semaphore(&semaphore_info, None)? });
         in_flight_fences.push(unsafe { device.create_fence(&fence_info, None)? });
@@ -1383,14 +3953,22 @@ fn create_sync_objects(device: &Device) -> Result<(Vec<vk::Semaphore>, Vec<vk::S
 }
 
 fn cleanup_swapchain_and_dependents(state: &mut State) {
+    destroy_swapchain_resources(state.device.as_ref().unwrap(), &state.swapchain_resources);
+}
+
+fn destroy_swapchain_resources(device: &Device, resources: &SwapchainResources) {
     unsafe {
-        for &framebuffer in &state.swapchain_resources.framebuffers {
-            state.device.as_ref().unwrap().destroy_framebuffer(framebuffer, None);
+        for &framebuffer in &resources.framebuffers {
+            device.destroy_framebuffer(framebuffer, None);
         }
-        for &view in &state.swapchain_resources.image_views {
-            state.device.as_ref().unwrap().destroy_image_view(view, None);
+        for &view in &resources.image_views {
+            device.destroy_image_view(view, None);
+        }
+        destroy_attachment(device, &resources.stencil);
+        if let Some(color) = &resources.msaa_color {
+            destroy_attachment(device, color);
         }
-        state.swapchain_resources.swapchain_loader.destroy_swapchain(state.swapchain_resources.swapchain, None);
+        resources.swapchain_loader.destroy_swapchain(resources.swapchain, None);
     }
 }
 
@@ -1410,24 +3988,17 @@ fn recreate_swapchain_and_dependents(state: &mut State) -> Result<(), Box<dyn Er
         &state.surface_loader,
         state.window_size,
         Some(old_swapchain),
-        state.vsync_enabled,
+        state.present,
+        state.stencil_format,
+        state.samples,
     )?;
 
     let old = std::mem::replace(&mut state.swapchain_resources, new_resources);
 
     recreate_framebuffers(device, &mut state.swapchain_resources, state.render_pass)?;
+    destroy_swapchain_resources(device, &old);
 
-    unsafe {
-        for fb in old.framebuffers {
-            device.destroy_framebuffer(fb, None);
-        }
-        for view in old.image_views {
-            device.destroy_image_view(view, None);
-        }
-        old.swapchain_loader.destroy_swapchain(old.swapchain, None);
-    }
-
-    state.images_in_flight = vec![vk::Fence::null(); state.swapchain_resources._images.len()];
+    state.images_in_flight = vec![vk::Fence::null(); state.swapchain_resources.images.len()];
     debug!("Swapchain recreated.");
     Ok(())
 }
@@ -0,0 +1,237 @@
+//! The render-list compiler: draw order and draw batches, shared by every backend.
+//! - `sort` puts objects in painter's order: by `z`, then by `order` (build order)
+//! - `batches` groups neighbours that can go out with one pipeline and state setup;
+//!   a batch ends wherever the object kind, blend, stencil mask, clip rect,
+//!   material or texture changes
+//!
+//! Nothing is moved past anything else to make batches longer: objects at the
+//! same z may overlap, so layering stays exactly what `sort` produced.
+
+use crate::core::gfx::{BlendMode, ObjectType, RenderObject};
+use std::ops::Range;
+
+/// Sorts `objects` into draw order. Stable, so objects with equal `(z, order)`
+/// keep the order they were pushed in (glyphs of one text run, for example).
+pub fn sort(objects: &mut [RenderObject]) {
+    objects.sort_by_key(|o| (o.z, o.order));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BatchKind<K> {
+    /// Sprites sharing a backend texture binding `K` and a `Material::index`.
+    Sprites { texture: K, material: usize },
+    /// Polylines and meshes, drawn with the untextured color pipeline.
+    Shapes,
+    /// One `ObjectType::Mask` stencil write; never merged.
+    Mask { id: u8 },
+}
+
+/// A run of consecutive objects drawn under one setup.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Batch<K> {
+    /// Indices into the list's `objects`. May include objects with nothing to
+    /// draw (unresolved text, sprites whose texture isn't loaded); skip those.
+    pub objects: Range<usize>,
+    pub kind: BatchKind<K>,
+    pub blend: BlendMode,
+    pub mask: u8,
+    pub clip: Option<[f32; 4]>,
+}
+
+/// Splits sorted `objects` into batches. `texture_key` maps a sprite's texture
+/// id to what the backend binds for it, or None when it can't be drawn; sprites
+/// whose keys compare equal share a batch. Objects with nothing to draw join
+/// whichever batch they fall in rather than breaking it.
+pub(crate) fn batches<K: Copy + PartialEq>(
+    objects: &[RenderObject],
+    mut texture_key: impl FnMut(&str) -> Option<K>,
+) -> Vec<Batch<K>> {
+    let mut batches: Vec<Batch<K>> = Vec::new();
+    for (i, obj) in objects.iter().enumerate() {
+        let kind = match &obj.object_type {
+            ObjectType::Sprite { texture_id, material, .. } => match texture_key(texture_id) {
+                Some(texture) => BatchKind::Sprites { texture, material: material.index() },
+                None => {
+                    extend_last(&mut batches, i);
+                    continue;
+                }
+            },
+            ObjectType::Polyline { .. } | ObjectType::Mesh { .. } => BatchKind::Shapes,
+            ObjectType::Mask { id } => BatchKind::Mask { id: *id },
+            ObjectType::Text { .. } => {
+                extend_last(&mut batches, i);
+                continue;
+            }
+        };
+        // Mask writes ignore the clip rect and stencil test, so neither splits them.
+        let (mask, clip) = match kind {
+            BatchKind::Mask { .. } => (0, None),
+            _ => (obj.mask, obj.clip),
+        };
+        match batches.last_mut() {
+            Some(last)
+                if !matches!(kind, BatchKind::Mask { .. })
+                    && last.kind == kind
+                    && last.blend == obj.blend
+                    && last.mask == mask
+                    && last.clip == clip =>
+            {
+                last.objects.end = i + 1;
+            }
+            _ => batches.push(Batch { objects: i..i + 1, kind, blend: obj.blend, mask, clip }),
+        }
+    }
+    batches
+}
+
+fn extend_last<K>(batches: &mut [Batch<K>], i: usize) {
+    if let Some(last) = batches.last_mut() {
+        last.objects.end = i + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::gfx::Material;
+    use cgmath::{Matrix4, SquareMatrix};
+
+    fn sprite(texture: &str) -> RenderObject {
+        RenderObject {
+            object_type: ObjectType::Sprite {
+                texture_id: texture.to_string(),
+                tint: [1.0; 4],
+                uv_scale: [1.0, 1.0],
+                uv_offset: [0.0, 0.0],
+                edge_fade: [0.0; 4],
+                corner_tint: [[1.0; 4]; 4],
+                material: Material::Standard,
+                glow: [0.0; 4],
+            },
+            transform: Matrix4::identity(),
+            blend: BlendMode::Alpha,
+            z: 0,
+            order: 0,
+            mask: 0,
+            clip: None,
+            group: 0,
+        }
+    }
+
+    fn mask(id: u8) -> RenderObject {
+        RenderObject { object_type: ObjectType::Mask { id }, ..sprite("") }
+    }
+
+    // Every texture loaded, each its own binding.
+    fn loaded(texture_id: &str) -> Option<u64> {
+        Some(texture_id.bytes().fold(0, |h, b| h * 31 + u64::from(b)))
+    }
+
+    // Like `loaded`, but "missing" isn't.
+    fn all_but_missing(texture_id: &str) -> Option<u64> {
+        loaded(texture_id).filter(|_| texture_id != "missing")
+    }
+
+    fn ranges<K>(batches: &[Batch<K>]) -> Vec<Range<usize>> {
+        batches.iter().map(|b| b.objects.clone()).collect()
+    }
+
+    #[test]
+    fn sort_orders_by_z_then_order() {
+        let mut objects: Vec<RenderObject> = [(2, 0), (0, 5), (1, 1), (0, 2)]
+            .into_iter()
+            .map(|(z, order)| RenderObject { z, order, ..sprite("a") })
+            .collect();
+        sort(&mut objects);
+        let keys: Vec<(i16, u32)> = objects.iter().map(|o| (o.z, o.order)).collect();
+        assert_eq!(keys, [(0, 2), (0, 5), (1, 1), (2, 0)]);
+    }
+
+    #[test]
+    fn sort_is_stable_for_equal_keys() {
+        let mut objects: Vec<RenderObject> = ["first", "second", "third"]
+            .into_iter()
+            .map(|texture| RenderObject { z: 3, order: 7, ..sprite(texture) })
+            .collect();
+        objects.insert(0, RenderObject { z: 4, ..sprite("above") });
+        sort(&mut objects);
+        let textures: Vec<&str> = objects
+            .iter()
+            .map(|o| match &o.object_type {
+                ObjectType::Sprite { texture_id, .. } => texture_id.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(textures, ["first", "second", "third", "above"]);
+    }
+
+    #[test]
+    fn compatible_neighbours_share_a_batch() {
+        let objects = [sprite("a"), sprite("a"), sprite("a")];
+        assert_eq!(ranges(&batches(&objects, loaded)), [0..3]);
+    }
+
+    #[test]
+    fn each_state_change_starts_a_batch() {
+        let base = sprite("a");
+        let changes = [
+            RenderObject { blend: BlendMode::Add, ..sprite("a") },
+            RenderObject { mask: 1, ..sprite("a") },
+            RenderObject { clip: Some([0.0, 0.0, 10.0, 10.0]), ..sprite("a") },
+            sprite("b"),
+        ];
+        for changed in changes {
+            let objects = [base.clone(), changed, base.clone()];
+            assert_eq!(ranges(&batches(&objects, loaded)), [0..1, 1..2, 2..3]);
+        }
+
+        let mut glowing = sprite("a");
+        if let ObjectType::Sprite { material, .. } = &mut glowing.object_type {
+            *material = Material::Glow { color: [1.0, 1.0, 1.0], strength: 0.5 };
+        }
+        let objects = [base.clone(), glowing, base];
+        assert_eq!(ranges(&batches(&objects, loaded)), [0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn shapes_and_sprites_split() {
+        let line = RenderObject {
+            object_type: ObjectType::Polyline { points: vec![[0.0, 0.0], [1.0, 1.0]], colors: vec![[1.0; 4]], thickness: 1.0 },
+            ..sprite("")
+        };
+        let objects = [sprite("a"), line.clone(), line, sprite("a")];
+        let out = batches(&objects, loaded);
+        assert_eq!(ranges(&out), [0..1, 1..3, 3..4]);
+        assert_eq!(out[1].kind, BatchKind::Shapes);
+    }
+
+    #[test]
+    fn masks_never_merge() {
+        let objects = [mask(1), mask(1), RenderObject { mask: 1, ..sprite("a") }];
+        let out = batches(&objects, loaded);
+        assert_eq!(ranges(&out), [0..1, 1..2, 2..3]);
+        assert_eq!(out[0].kind, BatchKind::Mask { id: 1 });
+        // Stencil writes ignore clipping, so the batch doesn't carry it.
+        let clipped = RenderObject { clip: Some([0.0, 0.0, 1.0, 1.0]), mask: 2, ..mask(1) };
+        let out = batches(&[clipped], loaded);
+        assert_eq!((out[0].mask, out[0].clip), (0, None));
+    }
+
+    #[test]
+    fn unresolved_objects_extend_the_last_batch() {
+        let text = RenderObject {
+            object_type: ObjectType::Text { font: "miso", text: "hi".to_string(), size: 10.0, color: [1.0; 4], align: 0.0 },
+            ..sprite("")
+        };
+        let objects = [sprite("a"), sprite("missing"), text, sprite("a")];
+        let out = batches(&objects, all_but_missing);
+        assert_eq!(ranges(&out), [0..4]);
+    }
+
+    #[test]
+    fn unresolved_objects_before_any_batch_are_dropped() {
+        let objects = [sprite("missing"), sprite("a")];
+        let out = batches(&objects, all_but_missing);
+        assert_eq!(ranges(&out), [1..2]);
+    }
+}
@@ -0,0 +1,60 @@
+//! CPU-side frame cap for when the swap itself doesn't pace frames (vsync off,
+//! Mailbox, Immediate), so frames come at a steady, predictable interval.
+//! - The event loop waits (`ControlFlow::WaitUntil`) through most of the gap and
+//!   polls the rest, since OS timers can overshoot by a millisecond or more (far
+//!   worse with Windows' default timer); input is handled all the while
+//! - Deadlines advance by whole intervals, so one late frame doesn't shift the cadence
+
+use std::time::{Duration, Instant};
+use winit::event_loop::ControlFlow;
+
+/// Caps offered in the options; 0 is uncapped.
+pub const FRAME_LIMIT_CHOICES: [u32; 7] = [0, 60, 120, 144, 240, 360, 500];
+
+// How long before the deadline to stop waiting on a timer and start polling.
+#[cfg(windows)]
+const SPIN_WINDOW: Duration = Duration::from_millis(2);
+#[cfg(not(windows))]
+const SPIN_WINDOW: Duration = Duration::from_micros(500);
+
+pub struct FrameLimiter {
+    interval: Option<Duration>,
+    next_frame: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: u32) -> Self {
+        let mut limiter = Self { interval: None, next_frame: Instant::now() };
+        limiter.set_max_fps(max_fps);
+        limiter
+    }
+
+    /// 0 removes the cap.
+    pub fn set_max_fps(&mut self, max_fps: u32) {
+        self.interval = (max_fps > 0).then(|| Duration::from_secs_f64(1.0 / max_fps as f64));
+        self.next_frame = Instant::now();
+    }
+
+    /// How the event loop should wait at `now` for the next frame, or None when
+    /// it's due and a redraw should be requested. Call from `about_to_wait`.
+    pub fn control_flow(&self, now: Instant) -> Option<ControlFlow> {
+        self.interval?;
+        let remaining = self.next_frame.checked_duration_since(now).filter(|r| !r.is_zero())?;
+        if remaining > SPIN_WINDOW {
+            Some(ControlFlow::WaitUntil(self.next_frame - SPIN_WINDOW))
+        } else {
+            Some(ControlFlow::Poll)
+        }
+    }
+
+    /// Counts a frame started at `now`. Call once per frame, before input and update.
+    pub fn begin_frame(&mut self, now: Instant) {
+        let Some(interval) = self.interval else { return };
+        self.next_frame += interval;
+        // More than a frame behind (a hitch, a loading screen): start over from
+        // now rather than rushing out frames to catch up.
+        if self.next_frame < now {
+            self.next_frame = now + interval;
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Minimal KTX2 reader for block-compressed textures.
+//! - Only BC1/BC3/BC7 payloads without supercompression, since those go to the GPU as-is
+//! - Only the base mip level is kept; sprites are never minified far enough to need more
+
+use std::error::Error;
+
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+// Identifier + 9 header words + the dfd/kvd/sgd index.
+const LEVEL_INDEX_OFFSET: usize = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Bc1Rgb,
+    Bc1Rgba,
+    Bc3,
+    Bc7,
+}
+
+impl CompressedFormat {
+    pub const ALL: [Self; 4] = [Self::Bc1Rgb, Self::Bc1Rgba, Self::Bc3, Self::Bc7];
+
+    // sRGB-tagged data is uploaded as UNORM, the same way PNGs are, so both look identical.
+    fn from_vk_format(vk_format: u32) -> Option<Self> {
+        match vk_format {
+            131 | 132 => Some(Self::Bc1Rgb),  // VK_FORMAT_BC1_RGB_{UNORM,SRGB}_BLOCK
+            133 | 134 => Some(Self::Bc1Rgba), // VK_FORMAT_BC1_RGBA_{UNORM,SRGB}_BLOCK
+            137 | 138 => Some(Self::Bc3),     // VK_FORMAT_BC3_{UNORM,SRGB}_BLOCK
+            145 | 146 => Some(Self::Bc7),     // VK_FORMAT_BC7_{UNORM,SRGB}_BLOCK
+            _ => None,
+        }
+    }
+
+    /// Bytes per 4x4 block.
+    pub fn block_bytes(self) -> usize {
+        match self {
+            Self::Bc1Rgb | Self::Bc1Rgba => 8,
+            Self::Bc3 | Self::Bc7 => 16,
+        }
+    }
+}
+
+/// The base level of a block-compressed texture, ready for upload.
+pub struct CompressedImage {
+    pub format: CompressedFormat,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, Box<dyn Error>> {
+    let b = bytes.get(offset..offset + 4).ok_or("KTX2 file is truncated")?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, Box<dyn Error>> {
+    let lo = read_u32(bytes, offset)? as u64;
+    let hi = read_u32(bytes, offset + 4)? as u64;
+    Ok(lo | (hi << 32))
+}
+
+pub fn parse(bytes: &[u8]) -> Result<CompressedImage, Box<dyn Error>> {
+    if bytes.get(..IDENTIFIER.len()) != Some(&IDENTIFIER[..]) {
+        return Err("Not a KTX2 file".into());
+    }
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?;
+    let depth = read_u32(bytes, 28)?;
+    let layers = read_u32(bytes, 32)?;
+    let faces = read_u32(bytes, 36)?;
+    let supercompression = read_u32(bytes, 44)?;
+
+    let format = CompressedFormat::from_vk_format(vk_format)
+        .ok_or_else(|| format!("Unsupported KTX2 format (vkFormat {})", vk_format))?;
+    if supercompression != 0 {
+        return Err(format!("Unsupported KTX2 supercompression scheme {}", supercompression).into());
+    }
+    if width == 0 || height == 0 || depth > 1 || layers > 1 || faces != 1 {
+        return Err("Only single 2D KTX2 textures are supported".into());
+    }
+
+    // Level 0 is the first entry of the level index and the largest image.
+    let offset = read_u64(bytes, LEVEL_INDEX_OFFSET)? as usize;
+    let length = read_u64(bytes, LEVEL_INDEX_OFFSET + 8)? as usize;
+    let expected = width.div_ceil(4) as usize * height.div_ceil(4) as usize * format.block_bytes();
+    if length != expected {
+        return Err(format!("KTX2 level 0 is {} bytes, expected {}", length, expected).into());
+    }
+    let data = offset
+        .checked_add(length)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or("KTX2 file is truncated")?;
+
+    Ok(CompressedImage { format, width, height, data: data.to_vec() })
+}
@@ -1,9 +1,16 @@
 mod backends;
+pub mod batch;
+pub mod frame_limiter;
+pub mod ktx2;
+#[cfg(feature = "shader-hot-reload")]
+mod shader_reload;
 
-use crate::core::gfx::backends::{opengl, vulkan};
+use crate::core::gfx::backends::{opengl, software, vulkan};
+use crate::core::gfx::ktx2::{CompressedFormat, CompressedImage};
 use cgmath::Matrix4;
 use glow::HasContext;
 use image::RgbaImage;
+use log::{info, warn};
 use std::{collections::HashMap, error::Error, str::FromStr, sync::Arc};
 use winit::window::Window;
 
@@ -12,6 +19,24 @@ use winit::window::Window;
 pub struct RenderList {
     pub clear_color: [f32; 4],
     pub objects: Vec<RenderObject>,
+    /// Fullscreen effects run over the finished frame, in order, before it is
+    /// presented. Empty draws straight to the window. Ignored by `Backend::draw_to_target`.
+    pub post_effects: Vec<PostEffect>,
+    /// Names for GPU debug labels, or None to draw without them.
+    pub labels: Option<DebugLabels>,
+}
+/// What a frame capture tool shows for a frame: the pass is named after `screen`
+/// and each stretch of draws after the top-level actor it came from.
+#[derive(Clone, Default)]
+pub struct DebugLabels {
+    pub screen: String,
+    /// Indexed by `RenderObject::group`.
+    pub groups: Vec<String>,
+}
+impl DebugLabels {
+    pub fn group(&self, group: u32) -> &str {
+        self.groups.get(group as usize).map_or("", String::as_str)
+    }
 }
 #[derive(Clone)]
 pub struct RenderObject {
@@ -20,6 +45,13 @@ pub struct RenderObject {
     pub blend: BlendMode,
     pub z: i16,
     pub order: u32,
+    /// Stencil mask this object is clipped to, or 0 for none. See `ObjectType::Mask`.
+    pub mask: u8,
+    /// Axis-aligned world rect `[left, bottom, right, top]` this object is cut off at
+    /// (a scissor rect, so cheaper than a mask), or None for no clipping.
+    pub clip: Option<[f32; 4]>,
+    /// Index of the top-level actor this object was built from; see `DebugLabels`.
+    pub group: u32,
 }
 #[derive(Clone)]
 pub enum ObjectType {
@@ -29,36 +61,477 @@ pub enum ObjectType {
         uv_scale: [f32; 2],
         uv_offset: [f32; 2],
         edge_fade: [f32; 4],
+        /// Per-corner color multipliers on `tint` (TL, TR, BL, BR), interpolated across the quad.
+        corner_tint: [[f32; 4]; 4],
+        material: Material,
+        /// SM glow: this color drawn over the tinted texel through its alpha, in
+        /// the same pass. Zero alpha is no glow.
+        glow: [f32; 4],
+    },
+    /// A stroked line through `points` (in model space, mapped by `transform`).
+    /// `colors` holds one color per point, interpolated along each segment; a
+    /// shorter list repeats its last entry.
+    Polyline {
+        points: Vec<[f32; 2]>,
+        colors: Vec<[f32; 4]>,
+        thickness: f32,
+    },
+    /// An untextured triangle list: `indices` index `vertices` (model space, mapped
+    /// by `transform`) three at a time. `colors` holds one color per vertex; a
+    /// shorter list repeats its last entry.
+    Mesh {
+        vertices: Vec<[f32; 2]>,
+        indices: Vec<u16>,
+        colors: Vec<[f32; 4]>,
+    },
+    /// Writes `id` into the stencil buffer over the unit quad (mapped by `transform`)
+    /// without drawing any color. Objects with `mask == id` only draw inside it.
+    /// Must precede those objects in the list. For rectangular clipping prefer
+    /// `RenderObject::clip`. Built from sprites marked `masksource()`.
+    Mask { id: u8 },
+    /// A run of text in `font`, `size` world units tall (cap height) and centered
+    /// on the origin; `align` places each line within the block (0 = left,
+    /// 0.5 = center, 1 = right). Expanded into glyph-atlas sprites by
+    /// `AssetManager::resolve_text` before the list reaches a backend.
+    Text {
+        font: &'static str,
+        text: String,
+        size: f32,
+        color: [f32; 4],
+        align: f32,
     },
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BlendMode {
     Alpha,
     Add,
-    #[allow(dead_code)]
     Multiply,
-    #[allow(dead_code)]
     Subtract,
 }
 
+/// Every `BlendMode`, by `BlendMode::index`; backends that bake blending into
+/// pipelines build one set per entry.
+pub(crate) const BLEND_MODES: [BlendMode; 4] = [BlendMode::Alpha, BlendMode::Add, BlendMode::Multiply, BlendMode::Subtract];
+
+impl BlendMode {
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            BlendMode::Alpha => 0,
+            BlendMode::Add => 1,
+            BlendMode::Multiply => 2,
+            BlendMode::Subtract => 3,
+        }
+    }
+}
+
+/// Extra shading for a sprite after tint and edge fade. Each material is its
+/// own sprite pipeline in the backends (`Material::index`), built from the one
+/// sprite shader; a new material is a variant here and a branch there.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Material {
+    #[default]
+    Standard,
+    /// Rotates hue by `hue` turns and scales saturation and value.
+    HsvShift { hue: f32, saturation: f32, value: f32 },
+    /// Blends toward the color's luminance; `amount` 1 is fully gray.
+    Grayscale { amount: f32 },
+    /// Adds `color` times `strength` on top, where the sprite is opaque.
+    /// Animate `strength` for a pulse.
+    Glow { color: [f32; 3], strength: f32 },
+}
+
+/// Number of `Material` variants; the backends build this many sprite pipelines.
+pub(crate) const MATERIAL_COUNT: usize = 4;
+
+impl Material {
+    /// Which sprite pipeline draws this material; the `MATERIAL` the sprite shaders branch on.
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            Material::Standard => 0,
+            Material::HsvShift { .. } => 1,
+            Material::Grayscale { .. } => 2,
+            Material::Glow { .. } => 3,
+        }
+    }
+
+    /// Per-sprite parameters handed to the shader.
+    pub(crate) fn params(&self) -> [f32; 4] {
+        match *self {
+            Material::Standard => [0.0; 4],
+            Material::HsvShift { hue, saturation, value } => [hue, saturation, value, 0.0],
+            Material::Grayscale { amount } => [amount.clamp(0.0, 1.0), 0.0, 0.0, 0.0],
+            Material::Glow { color, strength } => [color[0], color[1], color[2], strength],
+        }
+    }
+}
+
+/// A fullscreen shader pass over the drawn frame; see `RenderList::post_effects`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PostEffect {
+    /// Darkens toward the corners. `strength` 0 is off, 1 is black corners.
+    Vignette { strength: f32 },
+    /// Dark scanlines every other pixel row (`scanlines` 0..1 is how dark) and
+    /// barrel distortion (`curvature`, 0 is flat).
+    Crt { scanlines: f32, curvature: f32 },
+    /// Raises colors to `1 / gamma`; 1 leaves them unchanged, higher brightens.
+    Gamma { gamma: f32 },
+    /// Fades colors toward gray; `amount` 0 is off, 1 is fully grayscale.
+    Desaturate { amount: f32 },
+}
+
+impl PostEffect {
+    /// The effect id and parameters the post shaders switch on (`u_effect`, `u_params`).
+    pub(crate) fn shader_params(&self) -> (u32, [f32; 4]) {
+        match *self {
+            PostEffect::Vignette { strength } => (0, [strength.clamp(0.0, 1.0), 0.0, 0.0, 0.0]),
+            PostEffect::Crt { scanlines, curvature } => (1, [scanlines.clamp(0.0, 1.0), curvature.max(0.0), 0.0, 0.0]),
+            PostEffect::Gamma { gamma } => (2, [gamma.max(0.01), 0.0, 0.0, 0.0]),
+            PostEffect::Desaturate { amount } => (4, [amount.clamp(0.0, 1.0), 0.0, 0.0, 0.0]),
+        }
+    }
+}
+
+/// Post shader pass that copies its input unchanged; the upscale from a
+/// scaled render resolution when no effects are on.
+pub(crate) const POST_COPY: (u32, [f32; 4]) = (3, [0.0; 4]);
+
+/// Post shader pass mapping the SDR frame into `hdr`'s color space with SDR
+/// white at `paper_white` nits. Runs last, into the swapchain image; None when off.
+pub(crate) fn hdr_output_pass(hdr: HdrOutput, paper_white: u32) -> Option<(u32, [f32; 4])> {
+    let mode = match hdr {
+        HdrOutput::Off => return None,
+        HdrOutput::Hdr10 => 0.0,
+        HdrOutput::ScRgb => 1.0,
+    };
+    Some((5, [mode, paper_white as f32, 0.0, 0.0]))
+}
+
+/// `hdr_output_pass` on the CPU, for clear colors, which don't pass through a shader.
+pub(crate) fn hdr_output_color(color: [f32; 4], hdr: HdrOutput, paper_white: u32) -> [f32; 4] {
+    let [r, g, b, a] = srgb_to_linear(color);
+    let nits = paper_white as f32;
+    match hdr {
+        HdrOutput::Off => color,
+        HdrOutput::ScRgb => {
+            let scale = nits / 80.0;
+            [r * scale, g * scale, b * scale, a]
+        }
+        HdrOutput::Hdr10 => {
+            // BT.709 to BT.2020 primaries, then the PQ curve over 10000 nits.
+            let rgb = [
+                0.6274 * r + 0.3293 * g + 0.0433 * b,
+                0.0691 * r + 0.9195 * g + 0.0114 * b,
+                0.0164 * r + 0.0880 * g + 0.8956 * b,
+            ];
+            let pq = |c: f32| {
+                let y = (c * nits / 10000.0).max(0.0).powf(0.1593017578125);
+                ((0.8359375 + 18.8515625 * y) / (1.0 + 18.6875 * y)).powf(78.84375)
+            };
+            [pq(rgb[0]), pq(rgb[1]), pq(rgb[2]), a]
+        }
+    }
+}
+
+/// Untextured vertex used by the backends' color pipelines (polylines, meshes).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ColorVertex {
+    pub pos: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Longest miter allowed at a join, as a multiple of the half-thickness.
+const MITER_LIMIT: f32 = 4.0;
+
+/// Expands a polyline into a world-space triangle list with mitered joins, so
+/// both backends can draw it as plain colored triangles. Appends to `out`.
+pub(crate) fn tessellate_polyline(
+    points: &[[f32; 2]],
+    colors: &[[f32; 4]],
+    thickness: f32,
+    transform: &Matrix4<f32>,
+    out: &mut Vec<ColorVertex>,
+) {
+    if thickness <= 0.0 {
+        return;
+    }
+    let t = transform;
+    let color_at = |i: usize| colors.get(i).or(colors.last()).copied().unwrap_or([1.0; 4]);
+
+    // Map to world space, dropping repeated points (they have no direction).
+    let mut pts: Vec<([f32; 2], [f32; 4])> = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        let w = [
+            t.x.x * p[0] + t.y.x * p[1] + t.w.x,
+            t.x.y * p[0] + t.y.y * p[1] + t.w.y,
+        ];
+        if pts.last().map_or(true, |(q, _)| (q[0] - w[0]).abs() > 1e-4 || (q[1] - w[1]).abs() > 1e-4) {
+            pts.push((w, color_at(i)));
+        }
+    }
+    if pts.len() < 2 {
+        return;
+    }
+
+    let half = 0.5 * thickness;
+    let normal = |a: [f32; 2], b: [f32; 2]| {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let len = (dx * dx + dy * dy).sqrt();
+        [-dy / len, dx / len]
+    };
+
+    // Offset from each point to the left edge of the stroke.
+    let n = pts.len();
+    let offsets: Vec<[f32; 2]> = (0..n)
+        .map(|i| {
+            if i == 0 {
+                let nn = normal(pts[0].0, pts[1].0);
+                return [nn[0] * half, nn[1] * half];
+            }
+            let np = normal(pts[i - 1].0, pts[i].0);
+            if i == n - 1 {
+                return [np[0] * half, np[1] * half];
+            }
+            let nn = normal(pts[i].0, pts[i + 1].0);
+            let (mx, my) = (np[0] + nn[0], np[1] + nn[1]);
+            let mlen = (mx * mx + my * my).sqrt();
+            if mlen < 1e-4 {
+                // The line folds back on itself; a miter is undefined.
+                return [nn[0] * half, nn[1] * half];
+            }
+            let (mx, my) = (mx / mlen, my / mlen);
+            let scale = (half / (mx * nn[0] + my * nn[1]).max(1e-4)).min(half * MITER_LIMIT);
+            [mx * scale, my * scale]
+        })
+        .collect();
+
+    out.reserve((n - 1) * 6);
+    for i in 0..n - 1 {
+        let (p0, c0) = pts[i];
+        let (p1, c1) = pts[i + 1];
+        let (o0, o1) = (offsets[i], offsets[i + 1]);
+        let l0 = ColorVertex { pos: [p0[0] + o0[0], p0[1] + o0[1]], color: c0 };
+        let r0 = ColorVertex { pos: [p0[0] - o0[0], p0[1] - o0[1]], color: c0 };
+        let l1 = ColorVertex { pos: [p1[0] + o1[0], p1[1] + o1[1]], color: c1 };
+        let r1 = ColorVertex { pos: [p1[0] - o1[0], p1[1] - o1[1]], color: c1 };
+        out.extend_from_slice(&[l0, r0, l1, l1, r0, r1]);
+    }
+}
+
+/// Flattens an indexed mesh into a world-space triangle list for the color
+/// pipelines. Out-of-range indices and a trailing partial triangle are dropped.
+pub(crate) fn expand_mesh(
+    vertices: &[[f32; 2]],
+    indices: &[u16],
+    colors: &[[f32; 4]],
+    transform: &Matrix4<f32>,
+    out: &mut Vec<ColorVertex>,
+) {
+    let t = transform;
+    let color_at = |i: usize| colors.get(i).or(colors.last()).copied().unwrap_or([1.0; 4]);
+    let vertex = |i: u16| {
+        let i = i as usize;
+        vertices.get(i).map(|p| ColorVertex {
+            pos: [t.x.x * p[0] + t.y.x * p[1] + t.w.x, t.x.y * p[0] + t.y.y * p[1] + t.w.y],
+            color: color_at(i),
+        })
+    };
+
+    out.reserve(indices.len() - indices.len() % 3);
+    for tri in indices.chunks_exact(3) {
+        if let (Some(a), Some(b), Some(c)) = (vertex(tri[0]), vertex(tri[1]), vertex(tri[2])) {
+            out.extend_from_slice(&[a, b, c]);
+        }
+    }
+}
+
+/// Maps a world-space clip rect to a framebuffer scissor `[x, y, w, h]` in pixels,
+/// clamped to the framebuffer. `y_down` selects a top-left origin (Vulkan) over
+/// GL's bottom-left one.
+pub(crate) fn clip_rect_px(clip: [f32; 4], projection: &Matrix4<f32>, width: u32, height: u32, y_down: bool) -> [i32; 4] {
+    let p0 = projection * cgmath::Vector4::new(clip[0], clip[1], 0.0, 1.0);
+    let p1 = projection * cgmath::Vector4::new(clip[2], clip[3], 0.0, 1.0);
+    let (w, h) = (width as f32, height as f32);
+    let to_px = |ndc: f32, size: f32| ((ndc + 1.0) * 0.5 * size).round().clamp(0.0, size);
+
+    let (x0, x1) = (to_px(p0.x.min(p1.x), w), to_px(p0.x.max(p1.x), w));
+    let (y0, y1) = if y_down {
+        (to_px(-p0.y.max(p1.y), h), to_px(-p0.y.min(p1.y), h))
+    } else {
+        (to_px(p0.y.min(p1.y), h), to_px(p0.y.max(p1.y), h))
+    };
+    [x0 as i32, y0 as i32, (x1 - x0) as i32, (y1 - y0) as i32]
+}
+
 // --- Public API Facade ---
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BackendType {
     Vulkan,
     OpenGL,
+    /// CPU rasterizer without a window, for tests and CI; see `create_headless_backend`.
+    Software,
+    /// Pick at startup: the first backend in `AUTO_BACKEND_ORDER` that initializes.
+    Auto,
+}
+
+/// Fallback chain for `BackendType::Auto`. The software renderer can't present
+/// to a window, so it is never picked.
+const AUTO_BACKEND_ORDER: [BackendType; 2] = [BackendType::Vulkan, BackendType::OpenGL];
+
+/// How finished frames are handed to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Follow VSync: FIFO when on, otherwise the lowest-latency mode available.
+    Auto,
+    /// Wait for vblank. Never tears.
+    Fifo,
+    /// Replace the queued frame at each vblank: no tearing, less latency than
+    /// FIFO. OpenGL can't express it and presents immediately instead.
+    Mailbox,
+    /// Present as soon as the frame is done. Lowest latency, may tear.
+    Immediate,
+}
+
+/// Upper bound for `PresentSettings::frames_in_flight`.
+pub const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+/// Anti-aliasing choices for `PresentSettings::msaa_samples`; 1 is off.
+pub const MSAA_SAMPLE_CHOICES: [u32; 4] = [1, 2, 4, 8];
+
+/// Internal render resolutions for `PresentSettings::render_scale`, in percent
+/// of the window size; 100 renders at native resolution.
+pub const RENDER_SCALE_CHOICES: [u32; 5] = [50, 67, 75, 85, 100];
+
+/// How a scaled render resolution is stretched to fill the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpscaleFilter {
+    /// Bilinear: smooth, slightly soft.
+    Linear,
+    /// Blocky but sharp; best at 50%, where each pixel becomes exactly 2x2.
+    Nearest,
+}
+
+/// Swapchain output for HDR displays. Only the Vulkan backend can ask the
+/// display for an HDR color space; OpenGL stays SDR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HdrOutput {
+    Off,
+    /// 10-bit PQ (SMPTE ST 2084) with BT.2020 primaries.
+    Hdr10,
+    /// Linear 16-bit float with sRGB primaries, 1.0 = 80 nits.
+    ScRgb,
+}
+
+/// SDR white for `PresentSettings::hdr_paper_white`, in nits (BT.2408's reference white).
+pub const DEFAULT_HDR_PAPER_WHITE: u32 = 203;
+
+/// Frame pacing, anti-aliasing and render resolution. `vsync`, `mode`,
+/// `render_scale` and `upscale_filter` can change at runtime
+/// (`Backend::set_present_mode`); the rest are fixed at startup.
+#[derive(Debug, Clone, Copy)]
+pub struct PresentSettings {
+    pub vsync: bool,
+    pub mode: PresentMode,
+    /// Frames the CPU may record ahead of the display (1..=MAX_FRAMES_IN_FLIGHT).
+    /// Fewer means less input-to-photon latency but less slack for slow frames.
+    pub frames_in_flight: usize,
+    /// MSAA samples per pixel (1 = off). Backends fall back to the highest
+    /// count the device supports.
+    pub msaa_samples: u32,
+    /// Use sRGB textures and framebuffers so blending happens in linear light;
+    /// colors in the render list stay sRGB and are linearized in the shaders.
+    /// Backends turn it off when the display offers no sRGB framebuffer.
+    pub srgb: bool,
+    /// Percent of the window size the scene is drawn at (`RENDER_SCALE_CHOICES`),
+    /// then upscaled with `upscale_filter`. Below 100 trades sharpness for fill rate.
+    pub render_scale: u32,
+    pub upscale_filter: UpscaleFilter,
+    /// HDR color space for the swapchain. Backends fall back to `Off` when the
+    /// display doesn't offer it.
+    pub hdr: HdrOutput,
+    /// Nits SDR white is shown at under `hdr`; everything the game draws is SDR,
+    /// so this sets the overall brightness.
+    pub hdr_paper_white: u32,
+    /// Keep the frame's alpha so the compositor shows what's behind the window
+    /// wherever nothing was drawn. The window must have been created transparent.
+    pub transparent: bool,
+}
+
+impl PresentSettings {
+    /// Whether the swap waits for vblank (FIFO).
+    pub fn waits_for_vblank(&self) -> bool {
+        match self.mode {
+            PresentMode::Auto => self.vsync,
+            PresentMode::Fifo => true,
+            PresentMode::Mailbox | PresentMode::Immediate => false,
+        }
+    }
+}
+
+/// The size the scene is drawn at for a `width` x `height` window at
+/// `render_scale` percent (`PresentSettings::render_scale`).
+pub(crate) fn render_size(width: u32, height: u32, render_scale: u32) -> (u32, u32) {
+    let scale = |v: u32| ((v as u64 * render_scale.clamp(1, 100) as u64 + 50) / 100).max(1) as u32;
+    (scale(width), scale(height))
+}
+
+/// sRGB-encoded color to linear, alpha untouched. For clear colors, which
+/// don't pass through a shader.
+pub(crate) fn srgb_to_linear(color: [f32; 4]) -> [f32; 4] {
+    let channel = |c: f32| {
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    [channel(color[0]), channel(color[1]), channel(color[2]), color[3]]
 }
 
+/// What went into a frame, from `Backend::draw`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    pub vertices: u32,
+    pub draw_calls: u32,
+    /// Sprite and mask quads drawn.
+    pub instances: u32,
+    /// Texture changes between draws.
+    pub texture_binds: u32,
+    /// GPU time for a recent frame, in milliseconds, while `Backend::set_gpu_timing`
+    /// is on and the device supports timer queries. Lags a few frames behind.
+    pub gpu_time_ms: Option<f32>,
+}
+
+/// Identifies a texture started with `Backend::queue_texture_upload`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UploadId(u64);
+
 // A handle to a backend-specific texture resource.
 pub enum Texture {
     Vulkan(vulkan::Texture),
     OpenGL(opengl::Texture),
+    Software(software::Texture),
+}
+
+/// An offscreen color buffer a `RenderList` can be drawn into (density graphs,
+/// blurred backgrounds, screenshots). Created together with the `Texture` it
+/// renders to; put that texture in the texture map to draw it as a sprite.
+pub enum RenderTarget {
+    Vulkan(vulkan::RenderTarget),
+    OpenGL(opengl::RenderTarget),
+    Software(software::RenderTarget),
+}
+
+/// A second window drawn from its own `RenderList` (a now-playing marquee, a
+/// stream layout), sharing the backend's textures. Vulkan gives it its own
+/// surface and swapchain; GL draws it with the main context.
+pub enum SecondaryWindow {
+    Vulkan(vulkan::SecondaryWindow),
+    OpenGL(opengl::SecondaryWindow),
 }
 
 // An internal enum to hold the state for the active rendering backend.
 enum BackendImpl {
     Vulkan(vulkan::State),
     OpenGL(opengl::State),
+    Software(software::State),
 }
 
 /// A public, opaque wrapper around the active rendering backend.
@@ -66,14 +539,105 @@ enum BackendImpl {
 pub struct Backend(BackendImpl);
 
 impl Backend {
+    /// The concrete backend that is running (never `Auto`).
+    pub fn backend_type(&self) -> BackendType {
+        match &self.0 {
+            BackendImpl::Vulkan(_) => BackendType::Vulkan,
+            BackendImpl::OpenGL(_) => BackendType::OpenGL,
+            BackendImpl::Software(_) => BackendType::Software,
+        }
+    }
+
     pub fn draw(
         &mut self,
         render_list: &RenderList,
         textures: &HashMap<String, Texture>,
-    ) -> Result<u32, Box<dyn Error>> {
+    ) -> Result<FrameStats, Box<dyn Error>> {
         match &mut self.0 {
             BackendImpl::Vulkan(state) => vulkan::draw(state, render_list, textures),
             BackendImpl::OpenGL(state) => opengl::draw(state, render_list, textures),
+            BackendImpl::Software(state) => Ok(software::draw(state, render_list, textures)),
+        }
+    }
+
+    /// Whether `error` from `draw` means the GPU is gone (a driver reset, an
+    /// unplugged eGPU, a lost surface). Only a new backend can draw after that;
+    /// textures have to be uploaded to it again.
+    pub fn is_lost(&self, error: &(dyn Error + 'static)) -> bool {
+        match &self.0 {
+            BackendImpl::Vulkan(_) => vulkan::is_lost(error),
+            BackendImpl::OpenGL(_) => opengl::is_lost(error),
+            BackendImpl::Software(_) => false,
+        }
+    }
+
+    /// Has the next `draw` keep a copy of the image it presents (a screenshot).
+    /// Collect it with `capture_frame` after that draw.
+    pub fn request_capture(&mut self) {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::request_capture(state),
+            BackendImpl::OpenGL(state) => opengl::request_capture(state),
+            BackendImpl::Software(state) => software::request_capture(state),
+        }
+    }
+
+    /// A requested capture no draw has taken yet, because frames were skipped
+    /// (a minimized window, a swapchain being rebuilt).
+    pub fn capture_pending(&self) -> bool {
+        match &self.0 {
+            BackendImpl::Vulkan(state) => vulkan::capture_pending(state),
+            BackendImpl::OpenGL(state) => opengl::capture_pending(state),
+            BackendImpl::Software(state) => software::capture_pending(state),
+        }
+    }
+
+    /// The last presented image, read back (`vkCmdCopyImageToBuffer` /
+    /// `glReadPixels`, or simply copied by the software renderer) by the draw
+    /// after `request_capture`. None if no capture was requested or the readback failed.
+    pub fn capture_frame(&mut self) -> Option<RgbaImage> {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::capture_frame(state),
+            BackendImpl::OpenGL(state) => opengl::capture_frame(state),
+            BackendImpl::Software(state) => software::capture_frame(state),
+        }
+    }
+
+    /// Rebuilds the pipelines from the shader sources on disk if any changed
+    /// since the last call. A broken edit is logged and the old shaders kept.
+    #[cfg(feature = "shader-hot-reload")]
+    pub fn reload_changed_shaders(&mut self) {
+        if !shader_reload::poll_changed() {
+            return;
+        }
+        let result = match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::reload_shaders(state),
+            BackendImpl::OpenGL(state) => opengl::reload_shaders(state),
+            BackendImpl::Software(_) => return,
+        };
+        match result {
+            Ok(()) => info!("Shaders reloaded."),
+            Err(e) => warn!("Shader reload failed, keeping the current shaders: {}", e),
+        }
+    }
+
+    /// Turns GPU frame timing (`FrameStats::gpu_time_ms`) on or off. Off by
+    /// default, since reading the queries back costs a little every frame.
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::set_gpu_timing(state, enabled),
+            BackendImpl::OpenGL(state) => opengl::set_gpu_timing(state, enabled),
+            BackendImpl::Software(_) => {}
+        }
+    }
+
+    /// Applies `present.vsync`, `present.mode` and the render scale settings
+    /// without restarting the backend; Vulkan rebuilds just the swapchain.
+    pub fn set_present_mode(&mut self, present: PresentSettings) -> Result<(), Box<dyn Error>> {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::set_present_mode(state, present),
+            BackendImpl::OpenGL(state) => opengl::set_present_mode(state, present),
+            // Nothing is presented, and frames are always drawn at full size.
+            BackendImpl::Software(_) => Ok(()),
         }
     }
 
@@ -81,6 +645,7 @@ impl Backend {
         match &mut self.0 {
             BackendImpl::Vulkan(state) => vulkan::resize(state, width, height),
             BackendImpl::OpenGL(state) => opengl::resize(state, width, height),
+            BackendImpl::Software(state) => software::resize(state, width, height),
         }
     }
 
@@ -88,6 +653,7 @@ impl Backend {
         match &mut self.0 {
             BackendImpl::Vulkan(state) => vulkan::cleanup(state),
             BackendImpl::OpenGL(state) => opengl::cleanup(state),
+            BackendImpl::Software(state) => software::cleanup(state),
         }
     }
 
@@ -98,17 +664,197 @@ impl Backend {
                 Ok(Texture::Vulkan(tex))
             }
             BackendImpl::OpenGL(state) => {
-                let tex = opengl::create_texture(&state.gl, image)?;
+                let tex = opengl::create_texture(state, image)?;
+                Ok(Texture::OpenGL(tex))
+            }
+            BackendImpl::Software(_) => Ok(Texture::Software(software::create_texture(image))),
+        }
+    }
+
+    /// Starts uploading `image` without blocking the frame. The texture can't be
+    /// drawn until `poll_texture_uploads` hands it back.
+    pub fn queue_texture_upload(&mut self, image: &RgbaImage) -> Result<UploadId, Box<dyn Error>> {
+        let id = match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::queue_texture_upload(state, image)?,
+            BackendImpl::OpenGL(state) => opengl::queue_texture_upload(state, image)?,
+            BackendImpl::Software(state) => software::queue_texture_upload(state, image),
+        };
+        Ok(UploadId(id))
+    }
+
+    /// Writes the `[x, y, width, height]` region of `image` into `texture` in
+    /// place, for textures that change every frame (video, the glyph atlas).
+    /// `texture` must be `image`'s size. Frames already submitted still see the
+    /// old pixels and later ones the new, without waiting on the GPU.
+    pub fn update_texture(&mut self, texture: &Texture, image: &RgbaImage, region: [u32; 4]) -> Result<(), Box<dyn Error>> {
+        match (&mut self.0, texture) {
+            (BackendImpl::Vulkan(state), Texture::Vulkan(tex)) => vulkan::update_texture(state, tex, image, region)?,
+            (BackendImpl::OpenGL(state), Texture::OpenGL(tex)) => opengl::update_texture(state, tex, image, region),
+            (BackendImpl::Software(_), Texture::Software(tex)) => software::update_texture(tex, image, region),
+            _ => return Err("Texture belongs to a different backend".into()),
+        }
+        Ok(())
+    }
+
+    /// Textures from `queue_texture_upload` that are ready to draw.
+    pub fn poll_texture_uploads(&mut self) -> Vec<(UploadId, Texture)> {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => vulkan::poll_texture_uploads(state)
+                .into_iter()
+                .map(|(id, tex)| (UploadId(id), Texture::Vulkan(tex)))
+                .collect(),
+            BackendImpl::OpenGL(state) => opengl::poll_texture_uploads(state)
+                .into_iter()
+                .map(|(id, tex)| (UploadId(id), Texture::OpenGL(tex)))
+                .collect(),
+            BackendImpl::Software(state) => software::poll_texture_uploads(state)
+                .into_iter()
+                .map(|(id, tex)| (UploadId(id), Texture::Software(tex)))
+                .collect(),
+        }
+    }
+
+    /// Whether `format` can be uploaded with `create_compressed_texture`.
+    pub fn supports_compressed(&self, format: CompressedFormat) -> bool {
+        match &self.0 {
+            BackendImpl::Vulkan(state) => vulkan::supports_compressed(state, format),
+            BackendImpl::OpenGL(state) => opengl::supports_compressed(state, format),
+            // Everything goes through the decoded path.
+            BackendImpl::Software(_) => false,
+        }
+    }
+
+    /// Uploads block-compressed data without decoding it. Check `supports_compressed` first.
+    pub fn create_compressed_texture(&mut self, image: &CompressedImage) -> Result<Texture, Box<dyn Error>> {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => {
+                let tex = vulkan::create_compressed_texture(state, image)?;
+                Ok(Texture::Vulkan(tex))
+            }
+            BackendImpl::OpenGL(state) => {
+                let tex = opengl::create_compressed_texture(state, image)?;
                 Ok(Texture::OpenGL(tex))
             }
+            BackendImpl::Software(_) => Err("The software renderer can't sample compressed textures".into()),
+        }
+    }
+
+    /// Creates a `width` x `height` pixel render target and the texture it draws into.
+    pub fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<(RenderTarget, Texture), Box<dyn Error>> {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => {
+                let (target, tex) = vulkan::create_render_target(state, width, height)?;
+                Ok((RenderTarget::Vulkan(target), Texture::Vulkan(tex)))
+            }
+            BackendImpl::OpenGL(state) => {
+                let (target, tex) = opengl::create_render_target(state, width, height)?;
+                Ok((RenderTarget::OpenGL(target), Texture::OpenGL(tex)))
+            }
+            BackendImpl::Software(_) => {
+                let (target, tex) = software::create_render_target(width, height)?;
+                Ok((RenderTarget::Software(target), Texture::Software(tex)))
+            }
+        }
+    }
+
+    /// Draws `render_list` into `target` instead of the window, with the current
+    /// screen space stretched over the whole target. `textures` must not hold
+    /// the target's own texture under a key the list draws.
+    pub fn draw_to_target(
+        &mut self,
+        target: &RenderTarget,
+        render_list: &RenderList,
+        textures: &HashMap<String, Texture>,
+    ) -> Result<FrameStats, Box<dyn Error>> {
+        match (&mut self.0, target) {
+            (BackendImpl::Vulkan(state), RenderTarget::Vulkan(target)) => {
+                vulkan::draw_to_target(state, target, render_list, textures)
+            }
+            (BackendImpl::OpenGL(state), RenderTarget::OpenGL(target)) => {
+                Ok(opengl::draw_to_target(state, target, render_list, textures))
+            }
+            (BackendImpl::Software(_), RenderTarget::Software(target)) => {
+                software::draw_to_target(target, render_list, textures)
+            }
+            _ => Err("Render target belongs to a different backend".into()),
+        }
+    }
+
+    /// Frees a render target. Its texture is disposed like any other.
+    pub fn destroy_render_target(&mut self, target: RenderTarget) {
+        match (&mut self.0, target) {
+            // Vulkan and software targets are cleaned up by their Drop implementation.
+            (BackendImpl::Vulkan(_), RenderTarget::Vulkan(target)) => drop(target),
+            (BackendImpl::Software(_), RenderTarget::Software(target)) => drop(target),
+            (BackendImpl::OpenGL(state), RenderTarget::OpenGL(target)) => {
+                opengl::destroy_render_target(&state.gl, target)
+            }
+            _ => warn!("Render target belongs to a different backend; leaking it."),
+        }
+    }
+
+    /// Starts drawing into `window` as well as the main one. Destroy it with
+    /// `destroy_secondary_window` before `cleanup`.
+    pub fn create_secondary_window(&mut self, window: &Window) -> Result<SecondaryWindow, Box<dyn Error>> {
+        match &mut self.0 {
+            BackendImpl::Vulkan(state) => Ok(SecondaryWindow::Vulkan(vulkan::create_secondary_window(state, window)?)),
+            BackendImpl::OpenGL(state) => Ok(SecondaryWindow::OpenGL(opengl::create_secondary_window(state, window)?)),
+            BackendImpl::Software(_) => Err("The software renderer has no windows to draw into".into()),
+        }
+    }
+
+    /// Draws and presents `render_list` in `window`, laid out in that window's
+    /// own screen space (`space::metrics_for_window` of its size). Post effects
+    /// are ignored, and it never waits for vblank.
+    pub fn draw_secondary_window(
+        &mut self,
+        window: &mut SecondaryWindow,
+        render_list: &RenderList,
+        textures: &HashMap<String, Texture>,
+    ) -> Result<FrameStats, Box<dyn Error>> {
+        match (&mut self.0, window) {
+            (BackendImpl::Vulkan(state), SecondaryWindow::Vulkan(window)) => {
+                vulkan::draw_secondary_window(state, window, render_list, textures)
+            }
+            (BackendImpl::OpenGL(state), SecondaryWindow::OpenGL(window)) => {
+                opengl::draw_secondary_window(state, window, render_list, textures)
+            }
+            _ => Err("Secondary window belongs to a different backend".into()),
+        }
+    }
+
+    pub fn resize_secondary_window(&mut self, window: &mut SecondaryWindow, width: u32, height: u32) {
+        match (&mut self.0, window) {
+            (BackendImpl::Vulkan(state), SecondaryWindow::Vulkan(window)) => {
+                vulkan::resize_secondary_window(state, window, width, height)
+            }
+            (BackendImpl::OpenGL(state), SecondaryWindow::OpenGL(window)) => {
+                opengl::resize_secondary_window(state, window, width, height)
+            }
+            _ => warn!("Secondary window belongs to a different backend; not resizing it."),
+        }
+    }
+
+    pub fn destroy_secondary_window(&mut self, window: SecondaryWindow) {
+        match (&mut self.0, window) {
+            (BackendImpl::Vulkan(state), SecondaryWindow::Vulkan(window)) => {
+                vulkan::destroy_secondary_window(state, window)
+            }
+            // The surface is freed when dropped; the context is never left current on it.
+            (BackendImpl::OpenGL(_), SecondaryWindow::OpenGL(window)) => drop(window),
+            _ => warn!("Secondary window belongs to a different backend; leaking it."),
         }
     }
 
     pub fn dispose_textures(&mut self, textures: &mut HashMap<String, Texture>) {
         let old_textures = std::mem::take(textures);
         match &mut self.0 {
-            BackendImpl::Vulkan(_) => {
-                // Vulkan textures are cleaned up by their Drop implementation.
+            BackendImpl::Vulkan(_) | BackendImpl::Software(_) => {
+                // Vulkan and software textures are cleaned up by their Drop implementation.
                 drop(old_textures);
             }
             BackendImpl::OpenGL(state) => unsafe {
@@ -121,6 +867,25 @@ impl Backend {
         }
     }
 
+    /// Measured queue-to-display time, from queueing a present to it reaching the
+    /// display, in seconds, when the driver reports it (Vulkan with
+    /// VK_GOOGLE_display_timing, on Linux and Windows).
+    pub fn present_latency(&self) -> Option<f32> {
+        match &self.0 {
+            BackendImpl::Vulkan(state) => vulkan::present_latency(state),
+            BackendImpl::OpenGL(_) | BackendImpl::Software(_) => None,
+        }
+    }
+
+    /// GPU, driver and API version, for diagnostics.
+    pub fn adapter_info(&self) -> String {
+        match &self.0 {
+            BackendImpl::Vulkan(state) => vulkan::adapter_info(state),
+            BackendImpl::OpenGL(state) => opengl::adapter_info(state),
+            BackendImpl::Software(_) => "Software renderer (CPU)".to_string(),
+        }
+    }
+
     pub fn wait_for_idle(&mut self) {
         match &mut self.0 {
             BackendImpl::Vulkan(state) => {
@@ -130,8 +895,8 @@ impl Backend {
                     }
                 }
             }
-            BackendImpl::OpenGL(_) => {
-                // This is a no-op for OpenGL.
+            BackendImpl::OpenGL(_) | BackendImpl::Software(_) => {
+                // This is a no-op for OpenGL and the software renderer.
             }
         }
     }
@@ -141,21 +906,57 @@ impl Backend {
 pub fn create_backend(
     backend_type: BackendType,
     window: Arc<Window>,
-    vsync_enabled: bool,
+    present: PresentSettings,
 ) -> Result<Backend, Box<dyn Error>> {
+    let present = PresentSettings {
+        frames_in_flight: present.frames_in_flight.clamp(1, MAX_FRAMES_IN_FLIGHT),
+        ..present
+    };
     let backend_impl = match backend_type {
-        BackendType::Vulkan => BackendImpl::Vulkan(vulkan::init(&window, vsync_enabled)?),
-        BackendType::OpenGL => BackendImpl::OpenGL(opengl::init(window, vsync_enabled)?),
+        BackendType::Vulkan => BackendImpl::Vulkan(vulkan::init(&window, present)?),
+        BackendType::OpenGL => BackendImpl::OpenGL(opengl::init(window, present)?),
+        BackendType::Software => {
+            warn!("The software renderer can't present to a window. Using Auto instead.");
+            return create_backend(BackendType::Auto, window, present);
+        }
+        BackendType::Auto => {
+            let mut last_error: Box<dyn Error> = "No graphics backend available".into();
+            for candidate in AUTO_BACKEND_ORDER {
+                match create_backend(candidate, window.clone(), present) {
+                    Ok(backend) => {
+                        info!("Auto renderer selected {}.", candidate);
+                        return Ok(backend);
+                    }
+                    Err(e) => {
+                        warn!("{} renderer failed to initialize: {}. Trying the next one.", candidate, e);
+                        last_error = e;
+                    }
+                }
+            }
+            return Err(last_error);
+        }
     };
     Ok(Backend(backend_impl))
 }
 
+/// Creates a software backend drawing `width` x `height` frames in memory, with
+/// no window or GPU. Read frames back with `request_capture` and `capture_frame`.
+/// Sets the current screen space to that size, as a window resize would.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn create_headless_backend(width: u32, height: u32) -> Result<Backend, Box<dyn Error>> {
+    let state = software::init(width, height)?;
+    crate::core::space::set_window_size(width, height);
+    Ok(Backend(BackendImpl::Software(state)))
+}
+
 // -- Boilerplate impls --
 impl core::fmt::Display for BackendType {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Vulkan => write!(f, "Vulkan"),
             Self::OpenGL => write!(f, "OpenGL"),
+            Self::Software => write!(f, "Software"),
+            Self::Auto => write!(f, "Auto"),
         }
     }
 }
@@ -165,7 +966,69 @@ impl FromStr for BackendType {
         match s.to_lowercase().as_str() {
             "vulkan" => Ok(BackendType::Vulkan),
             "opengl" => Ok(BackendType::OpenGL),
+            "software" => Ok(BackendType::Software),
+            "auto" => Ok(BackendType::Auto),
             _ => Err(format!("'{}' is not a valid video renderer", s)),
         }
     }
 }
+impl core::fmt::Display for PresentMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "Auto"),
+            Self::Fifo => write!(f, "FIFO"),
+            Self::Mailbox => write!(f, "Mailbox"),
+            Self::Immediate => write!(f, "Immediate"),
+        }
+    }
+}
+impl FromStr for PresentMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(PresentMode::Auto),
+            "fifo" => Ok(PresentMode::Fifo),
+            "mailbox" => Ok(PresentMode::Mailbox),
+            "immediate" => Ok(PresentMode::Immediate),
+            _ => Err(format!("'{}' is not a valid present mode", s)),
+        }
+    }
+}
+impl core::fmt::Display for UpscaleFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Linear => write!(f, "Linear"),
+            Self::Nearest => write!(f, "Nearest"),
+        }
+    }
+}
+impl FromStr for UpscaleFilter {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(UpscaleFilter::Linear),
+            "nearest" => Ok(UpscaleFilter::Nearest),
+            _ => Err(format!("'{}' is not a valid upscale filter", s)),
+        }
+    }
+}
+impl core::fmt::Display for HdrOutput {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Off => write!(f, "Off"),
+            Self::Hdr10 => write!(f, "HDR10"),
+            Self::ScRgb => write!(f, "scRGB"),
+        }
+    }
+}
+impl FromStr for HdrOutput {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(HdrOutput::Off),
+            "hdr10" => Ok(HdrOutput::Hdr10),
+            "scrgb" => Ok(HdrOutput::ScRgb),
+            _ => Err(format!("'{}' is not a valid HDR output", s)),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Development-only shader hot reload (`--features shader-hot-reload`).
+//! - Polls the shader sources in the checkout for changes a few times a second
+//! - Vulkan sources are compiled to SPIR-V here with shaderc, the same way build.rs does
+//! - A source that fails to compile or link is logged and the old shaders stay in use
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/core/gfx/shaders");
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+struct Watch {
+    mtimes: HashMap<PathBuf, SystemTime>,
+    last_poll: Option<Instant>,
+}
+
+static WATCH: Lazy<Mutex<Watch>> = Lazy::new(|| Mutex::new(Watch::default()));
+
+fn scan() -> HashMap<PathBuf, SystemTime> {
+    fs::read_dir(SHADER_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .collect()
+}
+
+/// Whether any shader source changed since the last call. The first call only
+/// records the current state.
+pub fn poll_changed() -> bool {
+    let mut watch = WATCH.lock().unwrap();
+    let now = Instant::now();
+    let first = watch.last_poll.is_none();
+    if watch.last_poll.is_some_and(|last| now.duration_since(last) < POLL_INTERVAL) {
+        return false;
+    }
+    watch.last_poll = Some(now);
+    let mtimes = scan();
+    let changed = mtimes != watch.mtimes;
+    watch.mtimes = mtimes;
+    changed && !first
+}
+
+pub fn read_source(name: &str) -> Result<String, String> {
+    let path = Path::new(SHADER_DIR).join(name);
+    fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Compiles the GLSL source `name` (e.g. "vulkan_shader.vert") into SPIR-V bytes,
+/// with `defines` set like build.rs does for its variants.
+pub fn compile_spirv(name: &str, defines: &[&str]) -> Result<Vec<u8>, String> {
+    let kind = match Path::new(name).extension().and_then(|e| e.to_str()) {
+        Some("vert") => shaderc::ShaderKind::Vertex,
+        Some("frag") => shaderc::ShaderKind::Fragment,
+        _ => return Err(format!("{}: unknown shader stage", name)),
+    };
+    let source = read_source(name)?;
+    let compiler = shaderc::Compiler::new().map_err(|e| e.to_string())?;
+    let mut opts = shaderc::CompileOptions::new().map_err(|e| e.to_string())?;
+    opts.set_optimization_level(shaderc::OptimizationLevel::Zero);
+    opts.set_generate_debug_info();
+    for define in defines {
+        opts.add_macro_definition(define, None);
+    }
+    opts.set_include_callback(|include, _, _, _| {
+        let content = read_source(include)?;
+        Ok(shaderc::ResolvedInclude { resolved_name: include.to_string(), content })
+    });
+    let spirv = compiler
+        .compile_into_spirv(&source, kind, name, "main", Some(&opts))
+        .map_err(|e| e.to_string())?;
+    Ok(spirv.as_binary_u8().to_vec())
+}
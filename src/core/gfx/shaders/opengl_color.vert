@@ -0,0 +1,18 @@
+#version 330 core
+layout (location = 0) in vec2 a_pos;   // world space
+layout (location = 1) in vec4 a_color;
+
+out vec4 v_color;
+
+uniform mat4 u_proj;
+uniform int  u_srgb; // 1 with an sRGB framebuffer: colors are linearized before blending
+
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), greaterThan(c, vec3(0.04045)));
+}
+
+void main() {
+    v_color = a_color;
+    if (u_srgb == 1) v_color.rgb = srgbToLinear(v_color.rgb);
+    gl_Position = u_proj * vec4(a_pos, 0.0, 1.0);
+}
@@ -0,0 +1,45 @@
+#version 330 core
+// Fullscreen effects over the drawn frame; ids match PostEffect::shader_params.
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D u_texture;
+uniform vec4 u_params;
+uniform int  u_effect;
+
+const int EFFECT_VIGNETTE = 0;
+const int EFFECT_CRT      = 1;
+const int EFFECT_GAMMA    = 2;
+// 3 is POST_COPY: no branch, the frame passes through unchanged.
+const int EFFECT_DESATURATE = 4;
+
+void main() {
+    vec2 uv = v_uv;
+    if (u_effect == EFFECT_CRT) {
+        // Barrel distortion; whatever lands outside the frame is black.
+        vec2 c = uv * 2.0 - 1.0;
+        c *= 1.0 + u_params.y * dot(c.yx, c.yx);
+        uv = c * 0.5 + 0.5;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+            FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+            return;
+        }
+    }
+
+    // Alpha passes through for transparent windows.
+    vec4 frame = texture(u_texture, uv);
+    vec3 color = frame.rgb;
+    if (u_effect == EFFECT_VIGNETTE) {
+        float d = length(v_uv - 0.5) * 1.41421356; // 0 at the center, 1 in the corners
+        color *= 1.0 - u_params.x * smoothstep(0.3, 1.0, d);
+    } else if (u_effect == EFFECT_CRT) {
+        float row = floor(gl_FragCoord.y);
+        color *= 1.0 - u_params.x * mod(row, 2.0);
+    } else if (u_effect == EFFECT_GAMMA) {
+        color = pow(color, vec3(1.0 / u_params.x));
+    } else if (u_effect == EFFECT_DESATURATE) {
+        float luma = dot(color, vec3(0.299, 0.587, 0.114));
+        color = mix(color, vec3(luma), u_params.x);
+    }
+    FragColor = vec4(color, frame.a);
+}
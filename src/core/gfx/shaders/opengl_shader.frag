@@ -1,11 +1,16 @@
 #version 330 core
 in vec2 v_tex_coord;
 in vec2 v_quad;
+in vec4 v_corner_color;
 out vec4 FragColor;
 
 uniform vec4  u_color;
 uniform sampler2D u_texture;
 uniform vec4  u_edge_fade; // (left, right, top, bottom), quad fractions
+uniform int   u_material;
+uniform vec4  u_material_params;
+uniform vec4  u_glow; // SM glow color; alpha 0 is none
+uniform int   u_srgb; // 1 with an sRGB framebuffer: the tint is linearized before blending
 
 float edge_fade_factor(vec2 q, vec4 e) {
     // q in [0,1]^2 (0=left/top, 1=right/bottom)
@@ -17,9 +22,21 @@ float edge_fade_factor(vec2 q, vec4 e) {
     return f;
 }
 
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), greaterThan(c, vec3(0.04045)));
+}
+
+#include "sprite_material.glsl"
+
 void main() {
     vec4 s = texture(u_texture, v_tex_coord);
     float f = edge_fade_factor(v_quad, u_edge_fade);
-    s.a *= f;
-    FragColor = s * u_color; // standard straight-alpha blend
+    vec4 tint = u_color * v_corner_color;
+    vec4 glow = u_glow;
+    if (u_srgb == 1) {
+        tint.rgb = srgbToLinear(tint.rgb);
+        glow.rgb = srgbToLinear(glow.rgb);
+    }
+    FragColor = applyGlow(applyMaterial(u_material, s * tint, u_material_params), s.a, glow); // standard straight-alpha blend
+    FragColor.a *= f;
 }
\ No newline at end of file
@@ -0,0 +1,45 @@
+// Shared by opengl_shader.frag and vulkan_shader.frag, which pull it in with
+// `#include "sprite_material.glsl"` (spliced in by the OpenGL backend, which
+// has no #include). Mirrored by `apply_material`/`apply_glow` in software.rs.
+
+vec3 rgb2hsv(vec3 c) {
+    vec4 K = vec4(0.0, -1.0 / 3.0, 2.0 / 3.0, -1.0);
+    vec4 p = mix(vec4(c.bg, K.wz), vec4(c.gb, K.xy), step(c.b, c.g));
+    vec4 q = mix(vec4(p.xyw, c.r), vec4(c.r, p.yzx), step(p.x, c.r));
+    float d = q.x - min(q.w, q.y);
+    float e = 1.0e-10;
+    return vec3(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x);
+}
+
+vec3 hsv2rgb(vec3 c) {
+    vec4 K = vec4(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    vec3 p = abs(fract(c.xxx + K.xyz) * 6.0 - K.www);
+    return c.z * mix(K.xxx, clamp(p - K.xxx, 0.0, 1.0), c.y);
+}
+
+// Material ids and params match gfx::Material::index / params.
+vec4 applyMaterial(int material, vec4 color, vec4 params) {
+    if (material == 1) {        // HsvShift
+        vec3 hsv = rgb2hsv(color.rgb);
+        hsv.x = fract(hsv.x + params.x);
+        hsv.y = clamp(hsv.y * params.y, 0.0, 1.0);
+        hsv.z *= params.z;
+        color.rgb = hsv2rgb(hsv);
+    } else if (material == 2) { // Grayscale
+        float luma = dot(color.rgb, vec3(0.299, 0.587, 0.114));
+        color.rgb = mix(color.rgb, vec3(luma), params.x);
+    } else if (material == 3) { // Glow
+        color.rgb += params.rgb * params.a;
+    }
+    return color;
+}
+
+// SM glow: the glow color laid over the shaded texel through the texel's alpha,
+// composited as one straight-alpha result instead of a second sprite.
+vec4 applyGlow(vec4 color, float texelAlpha, vec4 glow) {
+    float g = texelAlpha * glow.a;
+    float a = g + color.a * (1.0 - g);
+    if (a > 0.0) color.rgb = (glow.rgb * g + color.rgb * color.a * (1.0 - g)) / a;
+    color.a = a;
+    return color;
+}
@@ -0,0 +1,25 @@
+#version 450
+
+// Pre-tessellated, world-space triangles (polylines)
+layout(location = 0) in vec2 a_pos;
+layout(location = 1) in vec4 a_color;
+
+// Push constants
+layout(push_constant) uniform ProjPush {
+    mat4 proj;
+} pc;
+
+// Set with sRGB framebuffers: colors are authored sRGB but blended in linear light.
+layout(constant_id = 1) const bool SRGB = false;
+
+layout(location = 0) out vec4 v_color;
+
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), greaterThan(c, vec3(0.04045)));
+}
+
+void main() {
+    v_color = a_color;
+    if (SRGB) v_color.rgb = srgbToLinear(v_color.rgb);
+    gl_Position = pc.proj * vec4(a_pos, 0.0, 1.0);
+}
@@ -0,0 +1,73 @@
+#version 450
+
+// Fullscreen effects over the drawn frame; ids match PostEffect::shader_params.
+layout(set = 0, binding = 0) uniform sampler2D u_texture;
+
+layout(push_constant) uniform PostPush {
+    vec4 params;
+    uint effect;
+} pc;
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 out_color;
+
+const uint EFFECT_VIGNETTE = 0u;
+const uint EFFECT_CRT      = 1u;
+const uint EFFECT_GAMMA    = 2u;
+// 3 is POST_COPY: no branch, the frame passes through unchanged.
+const uint EFFECT_DESATURATE = 4u;
+// Last pass under HDR output; params.x is 0 for HDR10, 1 for scRGB, params.y SDR white in nits.
+const uint EFFECT_HDR_OUTPUT = 5u;
+
+vec3 srgb_to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(vec3(0.04045), c));
+}
+
+vec3 hdr_output(vec3 color) {
+    vec3 linear = srgb_to_linear(clamp(color, 0.0, 1.0));
+    if (pc.params.x > 0.5) {
+        // scRGB: linear sRGB primaries, 1.0 = 80 nits.
+        return linear * (pc.params.y / 80.0);
+    }
+    // HDR10: BT.709 to BT.2020 primaries, then PQ over 10000 nits.
+    const mat3 to_bt2020 = mat3(
+        0.6274, 0.0691, 0.0164,
+        0.3293, 0.9195, 0.0880,
+        0.0433, 0.0114, 0.8956
+    );
+    vec3 y = pow(max(to_bt2020 * linear * (pc.params.y / 10000.0), 0.0), vec3(0.1593017578125));
+    return pow((0.8359375 + 18.8515625 * y) / (1.0 + 18.6875 * y), vec3(78.84375));
+}
+
+void main() {
+    vec2 uv = v_uv;
+    if (pc.effect == EFFECT_CRT) {
+        // Barrel distortion; whatever lands outside the frame is black.
+        vec2 c = uv * 2.0 - 1.0;
+        c *= 1.0 + pc.params.y * dot(c.yx, c.yx);
+        uv = c * 0.5 + 0.5;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+            out_color = vec4(0.0, 0.0, 0.0, 1.0);
+            return;
+        }
+    }
+
+    // Alpha passes through for transparent windows.
+    vec4 frame = texture(u_texture, uv);
+    vec3 color = frame.rgb;
+    if (pc.effect == EFFECT_VIGNETTE) {
+        float d = length(v_uv - 0.5) * 1.41421356; // 0 at the center, 1 in the corners
+        color *= 1.0 - pc.params.x * smoothstep(0.3, 1.0, d);
+    } else if (pc.effect == EFFECT_CRT) {
+        float row = floor(gl_FragCoord.y);
+        color *= 1.0 - pc.params.x * mod(row, 2.0);
+    } else if (pc.effect == EFFECT_GAMMA) {
+        color = pow(color, vec3(1.0 / pc.params.x));
+    } else if (pc.effect == EFFECT_DESATURATE) {
+        float luma = dot(color, vec3(0.299, 0.587, 0.114));
+        color = mix(color, vec3(luma), pc.params.x);
+    } else if (pc.effect == EFFECT_HDR_OUTPUT) {
+        color = hdr_output(color);
+    }
+    out_color = vec4(color, frame.a);
+}
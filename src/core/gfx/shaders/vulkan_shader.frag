@@ -1,10 +1,25 @@
 #version 450
 
+// Built twice: plain, and with BINDLESS defined, where every texture sits in one
+// descriptor array and each instance says which slot it samples.
+#ifdef BINDLESS
+#extension GL_EXT_nonuniform_qualifier : require
+layout(set = 0, binding = 0) uniform sampler2D u_textures[];
+layout(location = 4) flat in uint v_texture;
+#define SAMPLE_TEXTURE(uv) texture(u_textures[nonuniformEXT(v_texture)], uv)
+#else
 layout(set = 0, binding = 0) uniform sampler2D u_tex;
+#define SAMPLE_TEXTURE(uv) texture(u_tex, uv)
+#endif
+
+// One pipeline per material; the unused branches compile away.
+layout(constant_id = 0) const int MATERIAL = 0;
 
 layout(location = 0) in vec2 v_uv;
-layout(location = 1) flat in vec4 v_tint;
+layout(location = 1) in vec4 v_tint;
 layout(location = 2) flat in vec4 v_edgeFade; // (left, right, bottom, top) in UV units
+layout(location = 3) flat in vec4 v_material;
+layout(location = 5) flat in vec4 v_glow;
 
 layout(location = 0) out vec4 outColor;
 
@@ -17,13 +32,15 @@ float edgeFactor1D(float t, float featherLeft, float featherRight) {
     return min(fL, fR);
 }
 
+#include "sprite_material.glsl"
+
 void main() {
-    vec4 texel = texture(u_tex, v_uv);
+    vec4 texel = SAMPLE_TEXTURE(v_uv);
 
     float fadeX = edgeFactor1D(v_uv.x, v_edgeFade.x, v_edgeFade.y);
     float fadeY = edgeFactor1D(v_uv.y, v_edgeFade.z, v_edgeFade.w);
     float fade  = min(fadeX, fadeY);
 
-    outColor = texel * v_tint;
+    outColor = applyGlow(applyMaterial(MATERIAL, texel * v_tint, v_material), texel.a, v_glow);
     outColor.a *= fade;
 }
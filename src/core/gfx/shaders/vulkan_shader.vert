@@ -4,40 +4,48 @@
 layout(location = 0) in vec2 a_pos;      // unit quad: [-0.5..0.5]
 layout(location = 1) in vec2 a_uv;
 
-// Per-instance (binding = 1) — 72 bytes total
-layout(location = 2) in vec2 i_center;      // screen/world space center
-layout(location = 3) in vec2 i_size;        // scale along X/Y (lengths of model columns)
-layout(location = 4) in vec2 i_rot_sin_cos; // (sinθ, cosθ)
-layout(location = 5) in vec4 i_tint;
-layout(location = 6) in vec2 i_uv_scale;
-layout(location = 7) in vec2 i_uv_offset;
-layout(location = 8) in vec4 i_edge_fade;   // (fadeLeft, fadeRight, fadeBottom, fadeTop), in UV units
+// Per-instance (binding = 1) — 152 bytes total
+layout(location = 2) in mat4 i_model;       // full model transform (locations 2..5)
+layout(location = 6) in vec4 i_tint;
+layout(location = 7) in vec2 i_uv_scale;
+layout(location = 8) in vec2 i_uv_offset;
+layout(location = 9) in vec4 i_edge_fade;   // (fadeLeft, fadeRight, fadeBottom, fadeTop), in UV units
+layout(location = 10) in uvec4 i_corner_tint; // TL, TR, BL, BR packed as RGBA8 unorm
+layout(location = 11) in vec4 i_material;     // material params
+layout(location = 12) in uint i_texture;      // bindless texture slot
+layout(location = 13) in uint i_glow;         // glow color, packed RGBA8 unorm
 
 // Push constants
 layout(push_constant) uniform ProjPush {
     mat4 proj;
 } pc;
 
+// Set with sRGB framebuffers: tints are authored sRGB but blended in linear light.
+layout(constant_id = 1) const bool SRGB = false;
+
 // Varyings
 layout(location = 0) out vec2 v_uv;
-layout(location = 1) flat out vec4 v_tint;
+layout(location = 1) out vec4 v_tint;
 layout(location = 2) flat out vec4 v_edgeFade;
+layout(location = 3) flat out vec4 v_material;
+layout(location = 4) flat out uint v_texture;
+layout(location = 5) flat out vec4 v_glow;
 
-void main() {
-    // Scale local quad half-extents by instance size
-    vec2 local = vec2(a_pos.x * i_size.x, a_pos.y * i_size.y);
-
-    // Rotate with sin/cos pair (R = [ cos -sin; sin cos ])
-    float s = i_rot_sin_cos.x;
-    float c = i_rot_sin_cos.y;
-    vec2 rotated = vec2(c * local.x - s * local.y,
-                        s * local.x + c * local.y);
-
-    vec2 world = i_center + rotated;
+vec3 srgbToLinear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), greaterThan(c, vec3(0.04045)));
+}
 
-    gl_Position = pc.proj * vec4(world, 0.0, 1.0);
+void main() {
+    gl_Position = pc.proj * i_model * vec4(a_pos, 0.0, 1.0);
 
     v_uv       = a_uv * i_uv_scale + i_uv_offset;
-    v_tint     = i_tint;
+    vec4 top    = mix(unpackUnorm4x8(i_corner_tint.x), unpackUnorm4x8(i_corner_tint.y), a_uv.x);
+    vec4 bottom = mix(unpackUnorm4x8(i_corner_tint.z), unpackUnorm4x8(i_corner_tint.w), a_uv.x);
+    v_tint     = i_tint * mix(top, bottom, a_uv.y);
+    if (SRGB) v_tint.rgb = srgbToLinear(v_tint.rgb);
     v_edgeFade = i_edge_fade;
+    v_material = i_material;
+    v_texture  = i_texture;
+    v_glow     = unpackUnorm4x8(i_glow);
+    if (SRGB) v_glow.rgb = srgbToLinear(v_glow.rgb);
 }
@@ -10,13 +10,13 @@ pub enum Lane {
     Down = 1,
     Up = 2,
     Right = 3,
+    /// The corner panels of solo and three-panel pads.
+    UpLeft = 4,
+    UpRight = 5,
 }
 
 impl Lane {
-    #[inline(always)]
-    pub const fn index(self) -> usize {
-        self as usize
-    }
+    pub const ALL: [Lane; 6] = [Lane::Left, Lane::Down, Lane::Up, Lane::Right, Lane::UpLeft, Lane::UpRight];
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -68,6 +68,8 @@ pub fn lane_from_keycode(code: KeyCode) -> Option<Lane> {
         KeyCode::ArrowDown | KeyCode::KeyF => Some(Lane::Down),
         KeyCode::ArrowUp | KeyCode::KeyJ => Some(Lane::Up),
         KeyCode::ArrowRight | KeyCode::KeyK => Some(Lane::Right),
+        KeyCode::Numpad7 | KeyCode::KeyE => Some(Lane::UpLeft),
+        KeyCode::Numpad9 | KeyCode::KeyI => Some(Lane::UpRight),
         _ => None,
     }
 }
@@ -0,0 +1,83 @@
+//! Single-instance handling.
+//! - The first instance listens on a fixed localhost port
+//! - Later launches send their simfile argument there and exit, so the running
+//!   window comes to the front and jumps to the song instead of a second copy starting
+//! - If the port is taken by something else, every launch just runs normally
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+const INSTANCE_PORT: u16 = 47_180;
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Sent by a later launch (or the first one's own argument): bring the window
+/// forward and, with a path, select that song.
+#[derive(Debug, Clone)]
+pub struct OpenRequest {
+    pub simfile: Option<PathBuf>,
+}
+
+static PENDING: Lazy<Mutex<Option<OpenRequest>>> = Lazy::new(|| Mutex::new(None));
+
+/// Becomes the running instance, or hands `open_path` to the one already running.
+/// Returns false when this process should exit.
+pub fn claim(open_path: Option<PathBuf>) -> bool {
+    // The running instance has its own working directory.
+    let open_path = open_path.map(|p| p.canonicalize().unwrap_or(p));
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, INSTANCE_PORT));
+
+    match TcpListener::bind(addr) {
+        Ok(listener) => {
+            if open_path.is_some() {
+                push_request(OpenRequest { simfile: open_path });
+            }
+            thread::spawn(move || listen(listener));
+            true
+        }
+        Err(_) => match forward(&addr, open_path.as_deref()) {
+            Ok(()) => {
+                info!("deadsync is already running; passed the request on to it.");
+                false
+            }
+            Err(e) => {
+                warn!("Single-instance port {} is busy but not answering ({}); starting anyway.", INSTANCE_PORT, e);
+                true
+            }
+        },
+    }
+}
+
+fn forward(addr: &SocketAddr, open_path: Option<&Path>) -> std::io::Result<()> {
+    let mut stream = TcpStream::connect_timeout(addr, CONNECT_TIMEOUT)?;
+    let line = open_path.map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+    writeln!(stream, "{}", line)
+}
+
+fn listen(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+        let mut line = String::new();
+        if BufReader::new(stream).read_line(&mut line).is_err() {
+            continue;
+        }
+        let line = line.trim();
+        info!("Open request from another launch: {:?}", line);
+        let simfile = (!line.is_empty()).then(|| PathBuf::from(line));
+        push_request(OpenRequest { simfile });
+    }
+}
+
+fn push_request(request: OpenRequest) {
+    *PENDING.lock().unwrap() = Some(request);
+}
+
+/// The latest request not yet handled, if any.
+pub fn take_request() -> Option<OpenRequest> {
+    PENDING.lock().unwrap().take()
+}
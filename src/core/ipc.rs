@@ -0,0 +1,105 @@
+//! Gameplay events for external tools (stream overlays, trackers, bots).
+//! - Clients connect to `127.0.0.1:<EventServerPort>` and read one JSON object per line
+//! - Nothing is read from clients; events published while nobody is connected are dropped
+//! - Writes happen on a background thread so a slow client can't stall a frame
+
+use crate::game::judgment::JudgeGrade;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// A client that can't take a line within this long is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IpcEvent {
+    SongStarted {
+        title: String,
+        artist: String,
+        chart_type: String,
+        difficulty: String,
+        meter: u32,
+        chart_hash: String,
+    },
+    /// A single note was judged. `offset_ms` is None for misses.
+    NoteJudged {
+        time: f32,
+        row: usize,
+        column: usize,
+        grade: JudgeGrade,
+        offset_ms: Option<f32>,
+    },
+    HoldDropped {
+        time: f32,
+        column: usize,
+    },
+    /// Gameplay was left, either at the end of the music or by the player.
+    SongEnded {
+        time: f32,
+        completed: bool,
+        score_percent: f64,
+    },
+}
+
+static SENDER: Lazy<Mutex<Option<Sender<String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Starts the event server on `port`. 0 leaves it off.
+pub fn init(port: u16) {
+    if port == 0 {
+        return;
+    }
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Event server could not listen on port {}: {}", port, e);
+            return;
+        }
+    };
+    info!("Event server listening on 127.0.0.1:{}.", port);
+
+    let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::default();
+    let accepted = clients.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                    if let Ok(addr) = stream.peer_addr() {
+                        info!("Event client connected from {}.", addr);
+                    }
+                    accepted.lock().unwrap().push(stream);
+                }
+                Err(e) => warn!("Event server accept failed: {}", e),
+            }
+        }
+    });
+
+    let (sender, receiver) = channel::<String>();
+    thread::spawn(move || {
+        for line in receiver {
+            clients.lock().unwrap().retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+        }
+    });
+    *SENDER.lock().unwrap() = Some(sender);
+}
+
+/// Sends `event` to every connected client. Does nothing when the server is off.
+pub fn publish(event: IpcEvent) {
+    let sender = SENDER.lock().unwrap();
+    let Some(sender) = sender.as_ref() else { return };
+    match serde_json::to_string(&event) {
+        Ok(mut line) => {
+            line.push('\n');
+            let _ = sender.send(line);
+        }
+        Err(e) => warn!("Failed to serialize event {:?}: {}", event, e),
+    }
+}
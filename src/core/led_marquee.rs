@@ -0,0 +1,157 @@
+//! "Now Playing" text for cabinet LED marquee boards.
+//! - Serial boards get one CR/LF-terminated line per update on the configured port
+//! - Network boards get the same line as a UDP broadcast on `LedMarqueeUdpPort`
+//! - An empty line clears the board when gameplay is left
+//!
+//! Baud rate and framing are whatever the OS has set for the port (`stty` or
+//! Device Manager); boards generally ship at 9600 8N1. Writes happen on a
+//! background thread so a stuck port can't stall a frame.
+
+use crate::config;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::UdpSocket;
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+/// Serial ports offered in the options menu.
+pub const MAX_SERIAL_PORT: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LedMarqueeOutput {
+    #[default]
+    Off,
+    /// Serial port number, 1-based: COM<n> on Windows, /dev/ttyUSB<n-1> elsewhere.
+    Serial(u8),
+    /// UDP broadcast to `LedMarqueeUdpPort` on the local network.
+    Network,
+}
+
+impl LedMarqueeOutput {
+    /// Off, then every serial port, then network.
+    pub fn choices() -> impl Iterator<Item = LedMarqueeOutput> {
+        std::iter::once(Self::Off)
+            .chain((1..=MAX_SERIAL_PORT).map(Self::Serial))
+            .chain(std::iter::once(Self::Network))
+    }
+}
+
+impl core::fmt::Display for LedMarqueeOutput {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Off => write!(f, "Off"),
+            Self::Serial(n) => write!(f, "Serial{}", n),
+            Self::Network => write!(f, "Network"),
+        }
+    }
+}
+
+impl FromStr for LedMarqueeOutput {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        match lower.as_str() {
+            "off" => Ok(Self::Off),
+            "network" | "udp" => Ok(Self::Network),
+            _ => lower.strip_prefix("serial")
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| (1..=MAX_SERIAL_PORT).contains(n))
+                .map(Self::Serial)
+                .ok_or_else(|| format!("'{}' is not a valid LED marquee output", s)),
+        }
+    }
+}
+
+/// The OS path of serial port `n` (1-based).
+pub fn serial_path(n: u8) -> String {
+    if cfg!(windows) {
+        format!(r"\\.\COM{}", n)
+    } else {
+        format!("/dev/ttyUSB{}", n.saturating_sub(1))
+    }
+}
+
+struct Line {
+    output: LedMarqueeOutput,
+    udp_port: u16,
+    text: String,
+}
+
+static SENDER: Lazy<Mutex<Option<Sender<Line>>>> = Lazy::new(|| Mutex::new(None));
+
+fn sender() -> Sender<Line> {
+    let mut slot = SENDER.lock().unwrap();
+    slot.get_or_insert_with(|| {
+        let (sender, receiver) = channel::<Line>();
+        thread::spawn(move || {
+            let mut serial: Option<(u8, File)> = None;
+            let mut socket: Option<UdpSocket> = None;
+            for line in receiver {
+                let bytes = format!("{}\r\n", line.text).into_bytes();
+                match line.output {
+                    LedMarqueeOutput::Off => {}
+                    LedMarqueeOutput::Serial(n) => {
+                        if serial.as_ref().is_none_or(|(open, _)| *open != n) {
+                            serial = match OpenOptions::new().write(true).open(serial_path(n)) {
+                                Ok(file) => {
+                                    info!("LED marquee writing to {}.", serial_path(n));
+                                    Some((n, file))
+                                }
+                                Err(e) => {
+                                    warn!("LED marquee could not open {}: {}", serial_path(n), e);
+                                    None
+                                }
+                            };
+                        }
+                        let written = serial.as_mut().map(|(_, file)| file.write_all(&bytes).and_then(|_| file.flush()));
+                        if let Some(Err(e)) = written {
+                            // Reopen on the next line; USB adapters come and go.
+                            warn!("LED marquee write to {} failed: {}", serial_path(n), e);
+                            serial = None;
+                        }
+                    }
+                    LedMarqueeOutput::Network => {
+                        if socket.is_none() {
+                            socket = UdpSocket::bind(("0.0.0.0", 0))
+                                .and_then(|s| s.set_broadcast(true).map(|_| s))
+                                .inspect_err(|e| warn!("LED marquee could not open a UDP socket: {}", e))
+                                .ok();
+                        }
+                        let sent = socket.as_ref().map(|s| s.send_to(&bytes, ("255.255.255.255", line.udp_port)));
+                        if let Some(Err(e)) = sent {
+                            warn!("LED marquee broadcast to port {} failed: {}", line.udp_port, e);
+                        }
+                    }
+                }
+            }
+        });
+        sender
+    }).clone()
+}
+
+fn send(text: String) {
+    let cfg = config::get();
+    if cfg.led_marquee == LedMarqueeOutput::Off {
+        return;
+    }
+    let _ = sender().send(Line { output: cfg.led_marquee, udp_port: cfg.led_marquee_udp_port, text });
+}
+
+/// Shows "Now Playing: artist - title" on the board, if one is configured.
+pub fn show_now_playing(title: &str, artist: &str) {
+    let text = if artist.trim().is_empty() {
+        format!("Now Playing: {}", title)
+    } else {
+        format!("Now Playing: {} - {}", artist, title)
+    };
+    send(text);
+}
+
+/// Blanks the board, if one is configured.
+pub fn clear() {
+    send(String::new());
+}
@@ -0,0 +1,149 @@
+//! Log output: every record goes to stderr and to `logs/deadsync.log`.
+//! - The file rolls over to `deadsync.1.log`, `deadsync.2.log`, ... at
+//!   `MAX_LOG_SIZE`, keeping `MAX_LOG_FILES` in all, so logs never grow unbounded
+//! - File logging stops (stderr carries on) when a write fails or the disk
+//!   drops below `MIN_FREE_SPACE`
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_DIR: &str = "logs";
+const LOG_STEM: &str = "deadsync";
+const MAX_LOG_SIZE: u64 = 4 * 1024 * 1024;
+// The live file plus rotated ones.
+const MAX_LOG_FILES: usize = 5;
+const MIN_FREE_SPACE: u64 = 256 * 1024 * 1024;
+
+fn log_path(index: usize) -> PathBuf {
+    match index {
+        0 => Path::new(LOG_DIR).join(format!("{}.log", LOG_STEM)),
+        n => Path::new(LOG_DIR).join(format!("{}.{}.log", LOG_STEM, n)),
+    }
+}
+
+/// The log files on disk, newest first.
+pub fn log_files() -> Vec<PathBuf> {
+    (0..MAX_LOG_FILES).map(log_path).filter(|p| p.is_file()).collect()
+}
+
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    match unsafe { libc::statvfs(path.as_ptr(), &mut stats) } {
+        0 => Some(stats.f_bavail as u64 * stats.f_frsize as u64),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetDiskFreeSpaceExW(dir: *const u16, available: *mut u64, total: *mut u64, total_free: *mut u64) -> i32;
+    }
+    let path: Vec<u16> = dir.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    let ok = unsafe { GetDiskFreeSpaceExW(path.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) };
+    (ok != 0).then_some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+fn low_on_space() -> bool {
+    free_space(Path::new(LOG_DIR)).is_some_and(|free| free < MIN_FREE_SPACE)
+}
+
+/// The current log file, rotated before a record would push it past `MAX_LOG_SIZE`.
+struct RotatingFile {
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open() -> Self {
+        let file = fs::create_dir_all(LOG_DIR)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(log_path(0)));
+        match file {
+            Ok(file) if !low_on_space() => {
+                let size = file.metadata().map_or(0, |m| m.len());
+                Self { file: Some(file), size }
+            }
+            Ok(_) => {
+                eprintln!("Less than {} MiB free; not writing log files.", MIN_FREE_SPACE / (1024 * 1024));
+                Self { file: None, size: 0 }
+            }
+            Err(e) => {
+                eprintln!("Can't open '{}': {}; logging to stderr only.", log_path(0).display(), e);
+                Self { file: None, size: 0 }
+            }
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        let _ = fs::remove_file(log_path(MAX_LOG_FILES - 1));
+        for index in (0..MAX_LOG_FILES - 1).rev() {
+            let from = log_path(index);
+            if from.exists() {
+                fs::rename(&from, log_path(index + 1))?;
+            }
+        }
+        if low_on_space() {
+            return Err(io::Error::new(io::ErrorKind::StorageFull, "disk space is low"));
+        }
+        self.file = Some(File::create(log_path(0))?);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_record(&mut self, buf: &[u8]) {
+        if self.file.is_none() {
+            return;
+        }
+        let result = match self.size + buf.len() as u64 > MAX_LOG_SIZE && self.size > 0 {
+            true => self.rotate(),
+            false => Ok(()),
+        };
+        let result = result.and_then(|_| match &mut self.file {
+            Some(file) => file.write_all(buf),
+            None => Ok(()),
+        });
+        match result {
+            Ok(()) => self.size += buf.len() as u64,
+            Err(e) => {
+                eprintln!("Stopped writing log files: {}", e);
+                self.file = None;
+            }
+        }
+    }
+}
+
+/// env_logger's pipe target: each formatted record goes to stderr and the file.
+struct Tee(RotatingFile);
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _ = io::stderr().write_all(buf);
+        self.0.write_record(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// Installs the logger. Call once, as early as possible.
+pub fn init() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .target(env_logger::Target::Pipe(Box::new(Tee(RotatingFile::open()))))
+        .try_init();
+}
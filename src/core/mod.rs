@@ -1,6 +1,19 @@
 pub mod gfx;
+pub mod display;
 pub mod input;
 pub mod space;
 pub mod audio;
+pub mod audio_analysis;
+pub mod benchmark;
 pub mod network;
-pub mod gamepad;
\ No newline at end of file
+pub mod ipc;
+pub mod led_marquee;
+pub mod instance;
+pub mod gamepad;
+pub mod profiler;
+pub mod logging;
+pub mod diagnostics;
+pub mod safe_mode;
+pub mod screenshot;
+pub mod updates;
+pub mod video;
@@ -0,0 +1,140 @@
+//! Scoped CPU timers around the frame stages (screen update, actor build,
+//! backend draw), tagged with the screen they ran on.
+//! - `scope("update")` times until the guard drops; a lock and two clock reads
+//! - Per-stage averages roll over once a second for the F2 overlay (`stage_ms`)
+//! - A capture (F9 to start and stop) keeps every timing and writes a
+//!   chrome-tracing JSON that chrome://tracing and Perfetto open as a flame chart
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const TRACE_DIR: &str = "cache/traces";
+
+// A forgotten capture stops growing here (roughly a few minutes of frames).
+const MAX_CAPTURE_EVENTS: usize = 1_000_000;
+
+/// One complete ("X" phase) event in the chrome-tracing format; times in microseconds.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    cat: String,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: &'a [TraceEvent],
+    display_time_unit: &'static str,
+}
+
+struct Profiler {
+    epoch: Instant,
+    screen: String,
+    // Per stage, in first-seen order: seconds summed and calls since the last roll.
+    totals: Vec<(&'static str, f64, u32)>,
+    // Average milliseconds per call over the last rolled second.
+    averages: Vec<(&'static str, f32)>,
+    capture: Option<Vec<TraceEvent>>,
+}
+
+static PROFILER: Lazy<Mutex<Profiler>> = Lazy::new(|| {
+    Mutex::new(Profiler {
+        epoch: Instant::now(),
+        screen: String::new(),
+        totals: Vec::new(),
+        averages: Vec::new(),
+        capture: None,
+    })
+});
+
+/// Times a stage until dropped. Bind it to a named variable (`let _t = ...`),
+/// since `let _ = ...` drops it at once.
+pub struct Scope {
+    name: &'static str,
+    start: Instant,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let end = Instant::now();
+        let secs = end.duration_since(self.start).as_secs_f64();
+        let mut p = PROFILER.lock().unwrap();
+        match p.totals.iter_mut().find(|(n, _, _)| *n == self.name) {
+            Some((_, total, calls)) => {
+                *total += secs;
+                *calls += 1;
+            }
+            None => p.totals.push((self.name, secs, 1)),
+        }
+        let ts = self.start.saturating_duration_since(p.epoch).as_secs_f64() * 1e6;
+        let cat = p.screen.clone();
+        if let Some(events) = p.capture.as_mut().filter(|e| e.len() < MAX_CAPTURE_EVENTS) {
+            events.push(TraceEvent { name: self.name, cat, ph: "X", ts, dur: secs * 1e6, pid: 1, tid: 1 });
+        }
+    }
+}
+
+/// Starts timing `name`; see `Scope`.
+pub fn scope(name: &'static str) -> Scope {
+    Scope { name, start: Instant::now() }
+}
+
+/// Tags the timings that follow with `screen` (the trace's category).
+pub fn set_screen(screen: &str) {
+    let mut p = PROFILER.lock().unwrap();
+    if p.screen != screen {
+        p.screen = screen.to_string();
+    }
+}
+
+/// Turns the sums since the last call into per-call averages for `stage_ms`.
+/// Call about once a second.
+pub fn roll_stats() {
+    let mut p = PROFILER.lock().unwrap();
+    let averages = p.totals.iter()
+        .map(|&(name, total, calls)| (name, (total * 1000.0 / calls.max(1) as f64) as f32))
+        .collect();
+    p.averages = averages;
+    p.totals.clear();
+}
+
+/// Average milliseconds per call of each stage over the last rolled second.
+pub fn stage_ms() -> Vec<(&'static str, f32)> {
+    PROFILER.lock().unwrap().averages.clone()
+}
+
+pub fn is_capturing() -> bool {
+    PROFILER.lock().unwrap().capture.is_some()
+}
+
+/// Starts keeping every timing for `stop_capture`. Restarts a running capture.
+pub fn start_capture() {
+    PROFILER.lock().unwrap().capture = Some(Vec::new());
+    info!("Profiler capture started.");
+}
+
+/// Ends the capture and writes it as chrome-tracing JSON; returns the file written.
+pub fn stop_capture() -> Result<PathBuf, String> {
+    let events = PROFILER.lock().unwrap().capture.take().ok_or("No profiler capture running")?;
+    if events.len() >= MAX_CAPTURE_EVENTS {
+        warn!("Profiler capture hit {} events; later timings were dropped.", MAX_CAPTURE_EVENTS);
+    }
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let path = Path::new(TRACE_DIR).join(format!("trace-{}.json", secs));
+    fs::create_dir_all(TRACE_DIR).map_err(|e| format!("Failed to create '{}': {}", TRACE_DIR, e))?;
+    let json = serde_json::to_string(&Trace { trace_events: &events, display_time_unit: "ms" })
+        .map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    info!("Profiler capture of {} events written to '{}'.", events.len(), path.display());
+    Ok(path)
+}
@@ -0,0 +1,63 @@
+//! Safe mode after repeated startup crashes.
+//! - Every launch bumps a counter in `STATE_PATH`; a launch that draws frames
+//!   for `STARTUP_SECONDS` (or quits cleanly after drawing) clears it
+//! - When `CRASH_LIMIT` launches in a row never got that far, this one starts
+//!   safe: OpenGL, windowed, no audio device, default theme
+//! - The overrides live in memory only; deadsync.ini is untouched unless an
+//!   option is changed, which saves the current settings as usual
+
+use log::{info, warn};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const STATE_PATH: &str = "cache/startup_attempts";
+/// Failed launches in a row before the next one starts in safe mode.
+const CRASH_LIMIT: u32 = 3;
+/// Seconds of drawn frames after which a launch counts as started.
+pub const STARTUP_SECONDS: f32 = 10.0;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+fn failed_launches() -> u32 {
+    fs::read_to_string(STATE_PATH).ok().and_then(|s| s.trim().parse().ok()).unwrap_or(0)
+}
+
+/// Records this launch and says whether it should run in safe mode. Call once,
+/// before the config is used.
+pub fn begin_launch() -> bool {
+    let failed = failed_launches();
+    let write = Path::new(STATE_PATH)
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(STATE_PATH, (failed + 1).to_string()));
+    if let Err(e) = write {
+        warn!("Can't record the launch in '{}': {}", STATE_PATH, e);
+    }
+    let safe = failed >= CRASH_LIMIT;
+    if safe {
+        warn!("The last {} launches didn't finish starting; entering safe mode.", failed);
+    }
+    ACTIVE.store(safe, Ordering::Relaxed);
+    safe
+}
+
+/// The launch got going; the next one starts normally.
+pub fn launch_succeeded() {
+    if fs::remove_file(STATE_PATH).is_ok() {
+        info!("Startup completed.");
+    }
+}
+
+/// Whether this session is running in safe mode.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// What the player is told when safe mode is on.
+pub fn message() -> String {
+    format!(
+        "Safe mode: {} launches in a row crashed. Running OpenGL, windowed, without sound or theme colors. Fix the settings in Options, then restart.",
+        CRASH_LIMIT,
+    )
+}
@@ -0,0 +1,76 @@
+//! Screenshots (F12): the presented frame saved as a PNG under `Screenshots/`.
+//! - The file name is the local time, then the song, chart and score when
+//!   there's one on screen, so a folder of them sorts and reads on its own
+//! - Encoding runs on a background thread; the frame is already read back.
+//!   `wait_for_pending` lets shutdown finish any still being written
+
+use chrono::Local;
+use image::RgbaImage;
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+const SCREENSHOT_DIR: &str = "Screenshots";
+// Keeps names well under path limits with long song titles.
+const MAX_LABEL_CHARS: usize = 120;
+
+// Encodes still running, so exiting doesn't cut a file off halfway.
+static PENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// What a screenshot shows, for its file name.
+pub struct Label<'a> {
+    pub title: &'a str,
+    pub artist: &'a str,
+    pub difficulty: &'a str,
+    pub meter: u32,
+    /// Score percent (0..=1), once the chart has been played at all.
+    pub score: Option<f64>,
+}
+
+// Drops characters file systems reject and collapses whitespace.
+fn sanitize(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .map(|c| if c.is_control() || r#"<>:"/\|?*"#.contains(c) { ' ' } else { c })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn file_name(label: Option<&Label>) -> String {
+    let stamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    let Some(label) = label else {
+        return format!("{}.png", stamp);
+    };
+    let mut text = format!("{} - {} [{} {}]", label.artist, label.title, label.difficulty, label.meter);
+    if let Some(score) = label.score {
+        text.push_str(&format!(" {:.2}%", (score * 100.0).max(0.0)));
+    }
+    let text: String = sanitize(&text).chars().take(MAX_LABEL_CHARS).collect();
+    format!("{} {}.png", stamp, text.trim_end_matches(['.', ' ']))
+}
+
+/// Saves `image` under `Screenshots/` in the background. Returns the path it's
+/// written to; failures are logged.
+pub fn save(image: RgbaImage, label: Option<&Label>) -> Result<PathBuf, String> {
+    fs::create_dir_all(SCREENSHOT_DIR).map_err(|e| format!("Failed to create '{}': {}", SCREENSHOT_DIR, e))?;
+    let path = Path::new(SCREENSHOT_DIR).join(file_name(label));
+    let written = path.clone();
+    let handle = thread::spawn(move || match image.save(&written) {
+        Ok(()) => info!("Screenshot saved to '{}'.", written.display()),
+        Err(e) => warn!("Failed to save screenshot '{}': {}", written.display(), e),
+    });
+    let mut pending = PENDING.lock().unwrap();
+    pending.retain(|h| !h.is_finished());
+    pending.push(handle);
+    Ok(path)
+}
+
+/// Blocks until every screenshot still being encoded is on disk.
+pub fn wait_for_pending() {
+    let handles = std::mem::take(&mut *PENDING.lock().unwrap());
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
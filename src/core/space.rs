@@ -40,6 +40,14 @@ pub fn set_current_window_px(px_w: u32, px_h: u32) {
     CURRENT_PIXEL.with(|c| c.set((px_w, px_h)));
 }
 
+/// Window pixels per logical unit, for sizing art to what will actually be drawn.
+#[inline(always)]
+pub fn pixels_per_unit() -> f32 {
+    let px_h = CURRENT_PIXEL.with(|c| c.get().1) as f32;
+    let h = screen_height();
+    if h > 0.0 { px_h / h } else { 1.0 }
+}
+
 #[allow(dead_code)]
 #[inline(always)] pub fn screen_width()  -> f32 { CURRENT_METRICS.with(|c| { let m=c.get(); m.right - m.left }) }
 #[allow(dead_code)]
@@ -89,10 +97,41 @@ pub fn metrics_for_window(px_w: u32, px_h: u32) -> Metrics {
 // -----------------------------------------------------------------------------
 #[inline(always)]
 pub fn ortho_for_window(width: u32, height: u32) -> Matrix4<f32> {
+    ortho_for_metrics(set_window_size(width, height))
+}
+
+/// Makes a `width` x `height` pixel window's screen space current and returns it.
+/// Call on every change to the window's pixel size, including the one a move to
+/// a monitor with another DPI scale brings, so `pixels_per_unit` never lags.
+#[inline(always)]
+pub fn set_window_size(width: u32, height: u32) -> Metrics {
     set_current_window_px(width, height);
     let m = metrics_for_window(width, height);
     set_current_metrics(m);
-    cgmath::ortho(m.left, m.right, m.bottom, m.top, -1.0, 1.0)
+    m
+}
+
+/// Projection of the screen space a `width` x `height` pixel window gets, without
+/// making it current. Secondary windows draw in their own space with it.
+#[inline(always)]
+pub fn ortho_for_size(width: u32, height: u32) -> Matrix4<f32> {
+    ortho_for_metrics(metrics_for_window(width, height))
+}
+
+/// Projection of the current screen space, without touching the window state.
+/// Offscreen render targets use it to draw what the screen would show.
+#[inline(always)]
+pub fn ortho_for_current_screen() -> Matrix4<f32> {
+    ortho_for_metrics(CURRENT_METRICS.with(|c| c.get()))
+}
+
+#[inline(always)]
+fn ortho_for_metrics(m: Metrics) -> Matrix4<f32> {
+    // X/Y-rotated actors leave the z=0 plane. Map world z in [-depth, depth] to
+    // NDC [0, 1], which lies inside both GL's [-1, 1] and Vulkan's [0, 1] clip
+    // range, so a tilted full-screen quad is never clipped against near/far.
+    let depth = (m.right - m.left).max(m.top - m.bottom);
+    cgmath::ortho(m.left, m.right, m.bottom, m.top, -3.0 * depth, depth)
 }
 
 // -----------------------------------------------------------------------------
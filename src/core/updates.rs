@@ -0,0 +1,108 @@
+//! Startup check for a newer release on GitHub.
+//! - Runs once in the background; nothing waits on it and failures are only logged
+//! - The Menu shows a toast with the changelog until it's dismissed
+//! - Off with `CheckForUpdates=0`, for cabinets that shouldn't phone home
+
+use crate::core::network;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::thread;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/pnn64/deadsync/releases/latest";
+// Changelog lines kept for the toast.
+const MAX_CHANGELOG_LINES: usize = 8;
+const MAX_LINE_CHARS: usize = 64;
+
+#[derive(Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    draft: bool,
+    prerelease: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub version: String,
+    pub changelog: Vec<String>,
+}
+
+#[derive(Default)]
+struct UpdateState {
+    available: Option<Release>,
+    dismissed: bool,
+}
+
+static STATE: Lazy<Mutex<UpdateState>> = Lazy::new(|| Mutex::new(UpdateState::default()));
+
+pub fn init() {
+    thread::spawn(|| match fetch_latest() {
+        Ok(Some(release)) => {
+            info!("DeadSync {} is available (running {}).", release.version, env!("CARGO_PKG_VERSION"));
+            STATE.lock().unwrap().available = Some(release);
+        }
+        Ok(None) => info!("DeadSync is up to date."),
+        Err(e) => warn!("Update check failed: {}", e),
+    });
+}
+
+/// The newer release to tell the player about, unless already dismissed.
+pub fn pending_toast() -> Option<Release> {
+    let state = STATE.lock().unwrap();
+    if state.dismissed { None } else { state.available.clone() }
+}
+
+pub fn dismiss_toast() {
+    STATE.lock().unwrap().dismissed = true;
+}
+
+fn fetch_latest() -> Result<Option<Release>, Box<dyn std::error::Error>> {
+    let agent = network::get_agent();
+    // GitHub rejects API requests without a User-Agent.
+    let resp = agent
+        .get(RELEASES_URL)
+        .header("User-Agent", concat!("deadsync/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .call()?;
+    let release: ApiRelease = resp.into_body().read_json()?;
+    if release.draft || release.prerelease {
+        return Ok(None);
+    }
+    let (Some(latest), Some(current)) = (parse_version(&release.tag_name), parse_version(env!("CARGO_PKG_VERSION")))
+    else {
+        return Err(format!("unrecognized release tag '{}'", release.tag_name).into());
+    };
+    if latest <= current {
+        return Ok(None);
+    }
+    Ok(Some(Release {
+        version: release.tag_name.trim_start_matches(['v', 'V']).to_string(),
+        changelog: changelog_lines(release.body.as_deref().unwrap_or_default()),
+    }))
+}
+
+/// "v0.1.161" -> [0, 1, 161]. Anything after a '-' or '+' is ignored.
+fn parse_version(tag: &str) -> Option<Vec<u32>> {
+    let core = tag.trim().trim_start_matches(['v', 'V']).split(['-', '+']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// The first few lines of a markdown changelog, with list and heading markers stripped.
+fn changelog_lines(body: &str) -> Vec<String> {
+    body.lines()
+        .map(|line| line.trim().trim_start_matches(['#', '-', '*', ' ']).trim())
+        .filter(|line| !line.is_empty())
+        .take(MAX_CHANGELOG_LINES)
+        .map(|line| {
+            if line.chars().count() > MAX_LINE_CHARS {
+                let cut: String = line.chars().take(MAX_LINE_CHARS - 1).collect();
+                format!("{}…", cut)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
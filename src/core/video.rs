@@ -0,0 +1,141 @@
+//! Video backgrounds (.avi/.mp4/...), decoded by an external `ffmpeg`.
+//! - `ffprobe` reads the size and frame rate, then `ffmpeg` streams raw RGBA
+//!   frames over a pipe, looping the file for as long as it's wanted
+//! - A background thread keeps a few frames ahead; `frame_at` hands out the
+//!   newest one that's due and drops any the caller was too slow for
+//!
+//! Without ffmpeg and ffprobe on PATH, videos just don't play and the song's
+//! still background stays up.
+
+use image::RgbaImage;
+use log::{info, warn};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+const VIDEO_EXTENSIONS: [&str; 11] = ["avi", "mp4", "m4v", "mpg", "mpeg", "mkv", "webm", "mov", "wmv", "flv", "ogv"];
+
+// Backgrounds sit under the notefield and a filter; more pixels than this
+// only cost upload bandwidth.
+const MAX_WIDTH: u32 = 1280;
+// Decoded frames buffered ahead of playback.
+const FRAMES_AHEAD: usize = 3;
+
+/// Whether `path` looks like a video, by extension.
+pub fn is_video_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// A video being decoded in the background.
+pub struct VideoStream {
+    receiver: Receiver<(f64, RgbaImage)>,
+    // Decoded but not due yet.
+    next: Option<(f64, RgbaImage)>,
+}
+
+impl VideoStream {
+    /// Starts decoding `path` from its first frame. Problems are logged from the
+    /// decode thread; the stream then simply never produces a frame.
+    pub fn open(path: PathBuf) -> Self {
+        let (sender, receiver) = sync_channel(FRAMES_AHEAD);
+        thread::spawn(move || {
+            let (width, height, fps) = match probe(&path) {
+                Ok(info) => info,
+                Err(e) => {
+                    warn!("Can't play video {:?}: {}", path.file_name().unwrap_or_default(), e);
+                    return;
+                }
+            };
+            let (width, height) = output_size(width, height);
+            let child = Command::new("ffmpeg")
+                .args(["-v", "error", "-nostdin", "-stream_loop", "-1", "-i"])
+                .arg(&path)
+                .args(["-an", "-vf", &format!("scale={}:{}", width, height)])
+                .args(["-pix_fmt", "rgba", "-f", "rawvideo", "-"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    warn!("Can't start ffmpeg for video backgrounds: {}", e);
+                    return;
+                }
+            };
+            info!("Playing video {:?} at {}x{}, {:.2} fps.", path.file_name().unwrap_or_default(), width, height, fps);
+            let mut stdout = child.stdout.take().unwrap();
+            for index in 0u64.. {
+                let mut pixels = vec![0u8; width as usize * height as usize * 4];
+                if stdout.read_exact(&mut pixels).is_err() {
+                    break;
+                }
+                let frame = RgbaImage::from_raw(width, height, pixels).unwrap();
+                // Fails once the stream is dropped.
+                if sender.send((index as f64 / fps, frame)).is_err() {
+                    break;
+                }
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+        Self { receiver, next: None }
+    }
+
+    /// The newest frame due `time` seconds into the video that hasn't been handed
+    /// out yet, or None when there's nothing new to show.
+    pub fn frame_at(&mut self, time: f64) -> Option<RgbaImage> {
+        let mut newest = None;
+        loop {
+            if self.next.is_none() {
+                self.next = self.receiver.try_recv().ok();
+            }
+            match &self.next {
+                Some((due, _)) if *due <= time => newest = self.next.take().map(|(_, frame)| frame),
+                _ => return newest,
+            }
+        }
+    }
+}
+
+// Width, height and frame rate of the first video stream.
+fn probe(path: &Path) -> Result<(u32, u32, f64), String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args(["-show_entries", "stream=width,height,avg_frame_rate", "-of", "csv=p=0"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("ffprobe isn't available ({})", e))?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = text.trim().split(',').collect();
+    let [width, height, rate] = fields[..] else {
+        return Err("no video stream".to_string());
+    };
+    let width: u32 = width.parse().map_err(|_| "bad width".to_string())?;
+    let height: u32 = height.parse().map_err(|_| "bad height".to_string())?;
+    // "30000/1001", or "0/0" when the container doesn't say.
+    let fps = match rate.split_once('/') {
+        Some((n, d)) => n.parse::<f64>().unwrap_or(0.0) / d.parse::<f64>().unwrap_or(0.0),
+        None => rate.parse().unwrap_or(0.0),
+    };
+    let fps = if fps.is_finite() && fps > 0.0 { fps } else { 30.0 };
+    if width == 0 || height == 0 {
+        return Err("empty video stream".to_string());
+    }
+    Ok((width, height, fps))
+}
+
+// Caps the width at MAX_WIDTH, keeping the aspect; even sizes keep scalers happy.
+fn output_size(width: u32, height: u32) -> (u32, u32) {
+    let (w, h) = if width > MAX_WIDTH {
+        (MAX_WIDTH, (height as u64 * MAX_WIDTH as u64 / width as u64) as u32)
+    } else {
+        (width, height)
+    };
+    ((w & !1).max(2), (h & !1).max(2))
+}
@@ -0,0 +1,137 @@
+use chrono::Local;
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// --- Backup Data ---
+const PROFILES_DIR: &str = "save/profiles";
+const BACKUP_DIR: &str = "save/backups";
+const BACKUP_NAME_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// Appended to the backup holding the profiles a restore replaced.
+const PRE_RESTORE_SUFFIX: &str = "-pre-restore";
+
+/// Recursively copies `src` into `dst`, creating directories as needed.
+fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns all backup directories, newest first. Backup names are timestamps,
+/// so a reverse lexical sort is also a reverse chronological one.
+pub fn list_backups() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(BACKUP_DIR) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.join("profiles").is_dir())
+        .collect();
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Copies `save/profiles` into a new timestamped backup directory, then deletes
+/// the oldest backups so that at most `keep` remain. A `keep` of 0 disables backups.
+pub fn create_startup_backup(keep: usize) {
+    if keep == 0 || !Path::new(PROFILES_DIR).is_dir() {
+        return;
+    }
+
+    let name = Local::now().format(BACKUP_NAME_FORMAT).to_string();
+    let dest = Path::new(BACKUP_DIR).join(&name);
+    if dest.exists() {
+        // Two launches within the same second; the existing backup is just as fresh.
+        return;
+    }
+
+    match copy_dir_recursive(Path::new(PROFILES_DIR), &dest.join("profiles")) {
+        Ok(()) => info!("Backed up profiles to '{}'.", dest.display()),
+        Err(e) => {
+            warn!("Failed to back up profiles to '{}': {}", dest.display(), e);
+            let _ = fs::remove_dir_all(&dest);
+            return;
+        }
+    }
+
+    for old in list_backups().into_iter().skip(keep) {
+        if let Err(e) = fs::remove_dir_all(&old) {
+            warn!("Failed to remove old backup '{}': {}", old.display(), e);
+        }
+    }
+}
+
+/// Replaces `save/profiles` with the contents of `backup`. The current profiles
+/// are moved into a new backup first and put back if the copy fails, so a bad
+/// restore never leaves the machine without any profile data, and a good one
+/// can still be undone. Returns that backup, if there were profiles to keep.
+pub fn restore_backup(backup: &Path) -> Result<Option<PathBuf>, std::io::Error> {
+    let src = backup.join("profiles");
+    if !src.is_dir() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("'{}' does not contain a profiles directory", backup.display()),
+        ));
+    }
+
+    let current = Path::new(PROFILES_DIR);
+    let kept = if current.exists() {
+        let name = format!("{}{}", Local::now().format(BACKUP_NAME_FORMAT), PRE_RESTORE_SUFFIX);
+        let dest = Path::new(BACKUP_DIR).join(name);
+        if dest.exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("'{}' already exists", dest.display()),
+            ));
+        }
+        fs::create_dir_all(&dest)?;
+        if let Err(e) = fs::rename(current, dest.join("profiles")) {
+            let _ = fs::remove_dir_all(&dest);
+            return Err(e);
+        }
+        Some(dest)
+    } else {
+        None
+    };
+
+    if let Err(e) = copy_dir_recursive(&src, current) {
+        let _ = fs::remove_dir_all(current);
+        if let Some(dest) = &kept {
+            let _ = fs::rename(dest.join("profiles"), current);
+            let _ = fs::remove_dir_all(dest);
+        }
+        return Err(e);
+    }
+
+    info!("Restored profiles from '{}'.", backup.display());
+    if let Some(dest) = &kept {
+        info!("Kept the replaced profiles in '{}'.", dest.display());
+    }
+    Ok(kept)
+}
+
+/// Human-readable label for a backup directory, e.g. "2024-05-01 18:30:12", or
+/// "2024-05-01 18:30:12 (before restore)" for profiles a restore replaced.
+pub fn backup_label(backup: &Path) -> String {
+    let name = backup
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (stamp, note) = match name.strip_suffix(PRE_RESTORE_SUFFIX) {
+        Some(stamp) => (stamp, " (before restore)"),
+        None => (name.as_str(), ""),
+    };
+    chrono::NaiveDateTime::parse_from_str(stamp, BACKUP_NAME_FORMAT)
+        .map(|dt| format!("{}{}", dt.format("%Y-%m-%d %H:%M:%S"), note))
+        .unwrap_or(name)
+}
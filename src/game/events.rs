@@ -0,0 +1,104 @@
+use crate::game::gameplay::ComboMilestoneKind;
+use crate::game::judgment::{JudgeGrade, Judgment};
+use std::time::Instant;
+
+/// Something that happened during gameplay that screens or actors may react to.
+/// Events carry what they describe as of when they were published, since
+/// listeners only run later in the frame.
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    /// A row was finalized with this (worst/latest) judgment.
+    Judgment { judgment: Judgment, judged_at: Instant },
+    ComboMilestone(ComboMilestoneKind),
+    /// Life moved to `life` at music time `time`.
+    LifeChanged { life: f32, time: f32 },
+    /// The song position crossed into this whole second.
+    SongSecond(i32),
+    SongStarted,
+    /// A single note was judged. `offset_ms` is None for misses.
+    NoteJudged { time: f32, row: usize, column: usize, grade: JudgeGrade, offset_ms: Option<f32> },
+    HoldDropped { time: f32, column: usize },
+    /// Gameplay was left, either at the end of the music or by the player.
+    SongEnded { time: f32, completed: bool, score_percent: f64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Judgment,
+    ComboMilestone,
+    LifeChanged,
+    SongSecond,
+    SongStarted,
+    NoteJudged,
+    HoldDropped,
+    SongEnded,
+}
+
+impl GameEvent {
+    #[inline(always)]
+    pub fn kind(&self) -> EventKind {
+        match self {
+            GameEvent::Judgment { .. } => EventKind::Judgment,
+            GameEvent::ComboMilestone(_) => EventKind::ComboMilestone,
+            GameEvent::LifeChanged { .. } => EventKind::LifeChanged,
+            GameEvent::SongSecond(_) => EventKind::SongSecond,
+            GameEvent::SongStarted => EventKind::SongStarted,
+            GameEvent::NoteJudged { .. } => EventKind::NoteJudged,
+            GameEvent::HoldDropped { .. } => EventKind::HoldDropped,
+            GameEvent::SongEnded { .. } => EventKind::SongEnded,
+        }
+    }
+}
+
+/// Listeners get the owning state so they can update whatever they display.
+pub type Listener<S> = fn(&mut S, &GameEvent);
+
+/// Events are queued while game logic runs and delivered together by `dispatch`,
+/// so listeners never observe a half-updated state.
+pub struct EventBus<S> {
+    queue: Vec<GameEvent>,
+    listeners: Vec<(EventKind, Listener<S>)>,
+}
+
+impl<S> Default for EventBus<S> {
+    fn default() -> Self {
+        Self { queue: Vec::new(), listeners: Vec::new() }
+    }
+}
+
+impl<S> EventBus<S> {
+    pub fn subscribe(&mut self, kind: EventKind, listener: Listener<S>) {
+        self.listeners.push((kind, listener));
+    }
+
+    #[inline(always)]
+    pub fn publish(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+}
+
+/// State that owns an event bus for its own listeners.
+pub trait Subscriber: Sized {
+    fn event_bus(&mut self) -> &mut EventBus<Self>;
+}
+
+/// Delivers queued events in publish order. Events published by listeners are
+/// delivered in the same call, up to a fixed depth to break feedback loops.
+pub fn dispatch<S: Subscriber>(state: &mut S) {
+    const MAX_ROUNDS: usize = 8;
+    for _ in 0..MAX_ROUNDS {
+        let bus = state.event_bus();
+        if bus.queue.is_empty() {
+            return;
+        }
+        let events = std::mem::take(&mut bus.queue);
+        let listeners = bus.listeners.clone();
+        for event in &events {
+            let kind = event.kind();
+            for (_, listener) in listeners.iter().filter(|(k, _)| *k == kind) {
+                listener(state, event);
+            }
+        }
+    }
+    state.event_bus().queue.clear();
+}
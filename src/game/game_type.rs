@@ -0,0 +1,108 @@
+//! Game types: the pad layouts a chart can be played on, each described once
+//! here instead of assumed to be four panels.
+//! - `chart_type` picks which charts in a simfile show up on the wheel
+//! - `panels` gives the column count and the input lane behind each column
+//! - `noteskin_file` and `style` pick the noteskin layout that draws them
+//! - The wheel follows `GameType` in deadsync.ini (read at startup); gameplay
+//!   follows the chart it was handed
+
+use crate::core::input::Lane;
+use crate::game::parsing::noteskin::Style;
+use std::fmt;
+use std::str::FromStr;
+
+/// The most columns any game type has; per-column gameplay state is sized to it.
+pub const MAX_COLS: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameType {
+    /// Four panels: Left, Down, Up, Right.
+    #[default]
+    Single,
+    /// Six panels: the four plus the up-corners.
+    Solo,
+    /// Three panels: Up-Left, Down, Up-Right.
+    ThreePanel,
+}
+
+impl GameType {
+    pub const ALL: [GameType; 3] = [GameType::Single, GameType::Solo, GameType::ThreePanel];
+
+    /// The `#NOTES` steps type charts of this game type are tagged with.
+    pub fn chart_type(self) -> &'static str {
+        match self {
+            GameType::Single => "dance-single",
+            GameType::Solo => "dance-solo",
+            GameType::ThreePanel => "dance-threepanel",
+        }
+    }
+
+    /// The game type a chart's steps type plays on, if it's one we know.
+    pub fn for_chart_type(chart_type: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.chart_type().eq_ignore_ascii_case(chart_type))
+    }
+
+    /// The panel each column is stepped on, left to right.
+    pub fn panels(self) -> &'static [Lane] {
+        match self {
+            GameType::Single => &[Lane::Left, Lane::Down, Lane::Up, Lane::Right],
+            GameType::Solo => &[Lane::Left, Lane::UpLeft, Lane::Down, Lane::Up, Lane::UpRight, Lane::Right],
+            GameType::ThreePanel => &[Lane::UpLeft, Lane::Down, Lane::UpRight],
+        }
+    }
+
+    pub fn num_cols(self) -> usize {
+        self.panels().len()
+    }
+
+    /// The column `lane` plays, if this game type has one for it. Three-panel
+    /// also takes Left and Right for its corners, so arrow keys and four-way
+    /// pads can play it; two lanes can then share a column, so track held
+    /// state per lane and call a column down while any of its lanes is.
+    pub fn column_for(self, lane: Lane) -> Option<usize> {
+        if let Some(col) = self.panels().iter().position(|p| *p == lane) {
+            return Some(col);
+        }
+        match (self, lane) {
+            (GameType::ThreePanel, Lane::Left) => Some(0),
+            (GameType::ThreePanel, Lane::Right) => Some(2),
+            _ => None,
+        }
+    }
+
+    /// The noteskin definition laid out for this game type, inside a noteskin's folder.
+    pub fn noteskin_file(self) -> &'static str {
+        match self {
+            GameType::Single => "dance-single.txt",
+            GameType::Solo => "dance-solo.txt",
+            GameType::ThreePanel => "dance-threepanel.txt",
+        }
+    }
+
+    pub fn style(self) -> Style {
+        Style { num_cols: self.num_cols(), num_players: 1 }
+    }
+}
+
+impl fmt::Display for GameType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameType::Single => write!(f, "Single"),
+            GameType::Solo => write!(f, "Solo"),
+            GameType::ThreePanel => write!(f, "ThreePanel"),
+        }
+    }
+}
+
+impl FromStr for GameType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "single" | "dance-single" => Ok(GameType::Single),
+            "solo" | "dance-solo" => Ok(GameType::Solo),
+            "threepanel" | "dance-threepanel" => Ok(GameType::ThreePanel),
+            other => Err(format!("'{}' is not a valid GameType setting", other)),
+        }
+    }
+}
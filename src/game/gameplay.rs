@@ -1,20 +1,27 @@
 // ===== PROJECT: deadsync FILE: src/game/gameplay.rs =====
 use crate::core::audio;
+use crate::core::ipc::{self, IpcEvent};
+use crate::core::led_marquee;
 use crate::core::input::{lane_from_keycode, InputEdge, InputSource, Lane};
 use crate::core::space::*;
 use crate::game::chart::ChartData;
+use crate::game::events::{self, EventBus, EventKind, GameEvent, Subscriber};
 use crate::game::judgment::{self, JudgeGrade, Judgment};
 use crate::game::note::{HoldData, HoldResult, MineResult, Note, NoteType};
 use crate::game::parsing::notes as note_parser;
-use crate::game::parsing::noteskin::{self, Noteskin, Style};
+use crate::game::parsing::noteskin::{self, Noteskin};
 use crate::game::song::SongData;
 use crate::game::timing::TimingData;
 use crate::game::{
+    game_type::{GameType, MAX_COLS},
     life::{LifeChange, REGEN_COMBO_AFTER_MISS},
-    profile,
+    mods::ChartMods,
+    offsets, profile,
+    replay::{self, Replay},
     scroll::ScrollSpeedSetting,
 };
 use crate::screens::{Screen, ScreenAction};
+use crate::ui::anim::{self, Anim, Ease};
 use crate::ui::color;
 use log::{info, warn};
 use std::collections::{HashMap, VecDeque};
@@ -34,6 +41,8 @@ const M_MOD_HIGH_CAP: f32 = 600.0;
 const TIMING_WINDOW_ADD: f32 = 0.0015;
 
 pub const BASE_FANTASTIC_WINDOW: f32 = 0.0215;
+/// The FA+ split inside the Fantastic window: 15ms with `TIMING_WINDOW_ADD`.
+pub const BASE_WHITE_FANTASTIC_WINDOW: f32 = 0.0135;
 const BASE_EXCELLENT_WINDOW: f32 = 0.0430;
 const BASE_GREAT_WINDOW: f32 = 0.1020;
 const BASE_DECENT_WINDOW: f32 = 0.1350;
@@ -48,12 +57,194 @@ pub const HOLD_JUDGMENT_TOTAL_DURATION: f32 = 0.8;
 pub const RECEPTOR_GLOW_DURATION: f32 = 0.2;
 pub const COMBO_HUNDRED_MILESTONE_DURATION: f32 = 0.6;
 pub const COMBO_THOUSAND_MILESTONE_DURATION: f32 = 0.7;
+pub const QUALITY_NOTICE_DURATION: f32 = 3.0;
+const COUNTDOWN_READY_SECONDS: f32 = 1.2;
+
+// Automatic quality downshift: frames longer than the budget count toward
+// shedding the next visual, shorter ones count back down. The budget is the
+// target frame time with some slack, and one hitch counts for at most
+// `MAX_SLOW_FRAME_SECONDS`, so a single stall can't force a downshift.
+const FRAME_BUDGET_SLACK: f32 = 1.25;
+const DEFAULT_TARGET_FPS: u32 = 60;
+const MAX_SLOW_FRAME_SECONDS: f32 = 0.1;
+const DOWNSHIFT_AFTER_SECONDS: f32 = 3.0;
 
 const MAX_HOLD_LIFE: f32 = 1.0;
 const INITIAL_HOLD_LIFE: f32 = 1.0;
 const TIMING_WINDOW_SECONDS_HOLD: f32 = 0.32;
 const TIMING_WINDOW_SECONDS_ROLL: f32 = 0.35;
 
+// Beginner assist caps the reference BPM scroll at this speed.
+const BEGINNER_MAX_SCROLL_BPM: f32 = 300.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Foot {
+    Left,
+    Right,
+}
+
+/// How held holds/rolls feed the combo counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldComboMode {
+    /// ITG: only the head counts; the body never adds combo.
+    None,
+    /// One extra combo when the hold is completed.
+    PerHold,
+    /// DDR-style checkpoints: one combo for every whole beat the hold is held.
+    PerBeat,
+}
+
+impl Default for HoldComboMode {
+    fn default() -> Self {
+        HoldComboMode::None
+    }
+}
+
+impl core::fmt::Display for HoldComboMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::PerHold => write!(f, "PerHold"),
+            Self::PerBeat => write!(f, "PerBeat"),
+        }
+    }
+}
+
+impl std::str::FromStr for HoldComboMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "perhold" => Ok(Self::PerHold),
+            "perbeat" => Ok(Self::PerBeat),
+            _ => Err(format!("'{}' is not a valid hold combo mode", s)),
+        }
+    }
+}
+
+/// Checkpoints per beat under `HoldJudging::Checkpoint` (Pump's default tick count).
+pub const CHECKPOINTS_PER_BEAT: u32 = 4;
+
+/// How a hold or roll is judged once its head is hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldJudging {
+    /// ITG: one Held or Let Go at the tail; let go too long and it's over.
+    Standard,
+    /// Pump-style: a checkpoint every `1 / CHECKPOINTS_PER_BEAT` beat adds combo
+    /// if the lane is down and is a miss if it isn't. Letting go never ends the
+    /// hold, so it can be grabbed again; it's Held only with no checkpoint missed.
+    Checkpoint,
+}
+
+impl Default for HoldJudging {
+    fn default() -> Self {
+        HoldJudging::Standard
+    }
+}
+
+impl core::fmt::Display for HoldJudging {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Standard => write!(f, "Standard"),
+            Self::Checkpoint => write!(f, "Checkpoint"),
+        }
+    }
+}
+
+impl std::str::FromStr for HoldJudging {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "standard" => Ok(Self::Standard),
+            "checkpoint" => Ok(Self::Checkpoint),
+            _ => Err(format!("'{}' is not a valid hold judging mode", s)),
+        }
+    }
+}
+
+/// What happens when the life meter empties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailType {
+    /// The song stops: a short fail sequence plays, then Evaluation.
+    Immediate,
+    /// The fail is recorded but the song plays out.
+    ImmediateContinue,
+}
+
+impl Default for FailType {
+    fn default() -> Self {
+        FailType::ImmediateContinue
+    }
+}
+
+impl core::fmt::Display for FailType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Immediate => write!(f, "Immediate"),
+            Self::ImmediateContinue => write!(f, "ImmediateContinue"),
+        }
+    }
+}
+
+impl std::str::FromStr for FailType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "immediate" => Ok(Self::Immediate),
+            "immediatecontinue" => Ok(Self::ImmediateContinue),
+            _ => Err(format!("'{}' is not a valid fail type", s)),
+        }
+    }
+}
+
+/// What the assist tick clicks on during gameplay. F1 cycles it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssistTick {
+    #[default]
+    Off,
+    /// Every row with a step in it.
+    Clap,
+    /// Every beat, as a metronome.
+    Metronome,
+}
+
+impl AssistTick {
+    fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Clap,
+            Self::Clap => Self::Metronome,
+            Self::Metronome => Self::Off,
+        }
+    }
+}
+
+impl core::fmt::Display for AssistTick {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Off => write!(f, "Off"),
+            Self::Clap => write!(f, "Clap"),
+            Self::Metronome => write!(f, "Metronome"),
+        }
+    }
+}
+
+impl std::str::FromStr for AssistTick {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "clap" => Ok(Self::Clap),
+            "metronome" => Ok(Self::Metronome),
+            _ => Err(format!("'{}' is not a valid assist tick", s)),
+        }
+    }
+}
+
+/// Length of the Immediate fail sequence, from the fail to Evaluation.
+pub const FAIL_SEQUENCE_DURATION: f32 = 3.0;
+// How long the music takes to wind down to a stop on an Immediate fail.
+const FAIL_MUSIC_STOP_SECONDS: f64 = 1.5;
+
 #[derive(Clone, Debug)]
 pub struct Arrow {
     pub beat: f32,
@@ -69,36 +260,105 @@ pub struct JudgmentRenderInfo {
     pub judged_at: Instant,
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct HoldJudgmentRenderInfo {
-    pub result: HoldResult,
-    pub triggered_at: Instant,
-}
-
 #[derive(Clone, Debug)]
 pub struct ActiveTapExplosion {
     pub window: String,
-    pub elapsed: f32,
     pub start_beat: f32,
 }
 
-#[derive(Clone, Debug)]
-pub struct ActiveMineExplosion {
-    pub elapsed: f32,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceptorEvent {
+    Press,
+    Lift,
+}
+
+/// Visuals shed one step at a time, least noticeable first, while frames keep
+/// running over budget during a song. Each level also drops everything the
+/// ones before it did. A new song starts back at `Full`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QualityLevel {
+    Full,
+    /// No glowshift overlays on receptors and holds, no receptor press glow.
+    NoGlow,
+    /// Mines drawn with one gradient layer instead of the noteskin's stack.
+    FlatMines,
+    /// No song background or its filter; the clear color shows through.
+    NoBackground,
+}
+
+impl QualityLevel {
+    fn next(self) -> Option<Self> {
+        match self {
+            Self::Full => Some(Self::NoGlow),
+            Self::NoGlow => Some(Self::FlatMines),
+            Self::FlatMines => Some(Self::NoBackground),
+            Self::NoBackground => None,
+        }
+    }
+
+    pub fn shows_glow(self) -> bool {
+        self < Self::NoGlow
+    }
+
+    /// Gradient layers to draw a mine with, given the noteskin's count.
+    pub fn mine_fill_layers(self, layers: usize) -> usize {
+        if self < Self::FlatMines { layers } else { layers.min(1) }
+    }
+
+    pub fn shows_background(self) -> bool {
+        self < Self::NoBackground
+    }
+}
+
+/// "Ready?" then 3, 2, 1 on the counts before the first step and "GO!" on it,
+/// so players can get set on the pad. Times are music seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct Countdown {
+    /// The first step; "GO!" shows from here.
+    pub go_time: f32,
+    /// One count: the beat at the first step's BPM, doubled or halved into 0.4..1.0 s.
+    pub count_seconds: f32,
+}
+
+impl Countdown {
+    fn new(first_step_time: f32, bpm: f32) -> Self {
+        let mut count_seconds = if bpm.is_finite() && bpm > 0.0 { 60.0 / bpm } else { 0.5 };
+        while count_seconds < 0.4 { count_seconds *= 2.0; }
+        while count_seconds > 1.0 { count_seconds *= 0.5; }
+        Self { go_time: first_step_time, count_seconds }
+    }
+
+    /// When "Ready?" appears.
+    pub fn start_time(&self) -> f32 {
+        self.go_time - 3.0 * self.count_seconds - COUNTDOWN_READY_SECONDS
+    }
+
+    /// The prompt showing at `music_time`, with how long it has shown and for how
+    /// long it shows in all; `None` before "Ready?" and once "GO!" is done.
+    pub fn prompt_at(&self, music_time: f32) -> Option<(&'static str, f32, f32)> {
+        let counts_start = self.go_time - 3.0 * self.count_seconds;
+        if music_time < self.start_time() {
+            None
+        } else if music_time < counts_start {
+            Some(("Ready?", music_time - self.start_time(), COUNTDOWN_READY_SECONDS))
+        } else if music_time < self.go_time {
+            let count = ((music_time - counts_start) / self.count_seconds).floor().clamp(0.0, 2.0);
+            let elapsed = music_time - counts_start - count * self.count_seconds;
+            Some((["3", "2", "1"][count as usize], elapsed, self.count_seconds))
+        } else if music_time < self.go_time + self.count_seconds {
+            Some(("GO!", music_time - self.go_time, self.count_seconds))
+        } else {
+            None
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ComboMilestoneKind {
     Hundred,
     Thousand,
 }
 
-#[derive(Clone, Debug)]
-pub struct ActiveComboMilestone {
-    pub kind: ComboMilestoneKind,
-    pub elapsed: f32,
-}
-
 #[derive(Clone, Debug)]
 pub struct ActiveHold {
     pub note_index: usize,
@@ -107,6 +367,17 @@ pub struct ActiveHold {
     pub let_go: bool,
     pub is_pressed: bool,
     pub life: f32,
+    /// Whole beats of the body already credited under `HoldComboMode::PerBeat`.
+    pub combo_ticks: u32,
+    /// Seconds since the engaged explosion last pulsed: at the head, on each
+    /// whole beat held, and on each roll step.
+    pub explosion_elapsed: f32,
+    /// Whole beats held, for spotting the next pulse.
+    pub pulse_beats: u32,
+    /// Checkpoints passed so far, and how many of them with the lane up
+    /// (`HoldJudging::Checkpoint`).
+    pub checkpoints: u32,
+    pub checkpoints_missed: u32,
 }
 
 #[inline(always)]
@@ -114,19 +385,56 @@ pub fn active_hold_is_engaged(active: &ActiveHold) -> bool {
     !active.let_go && active.life > 0.0
 }
 
+/// The one clock gameplay visuals read, so beat-synced and timed effects stay
+/// coherent with each other under rate mods.
+#[derive(Clone, Copy, Debug)]
+pub struct VisualClock {
+    /// Song beat: receptor pulse, combo color, lifebar swoosh, noteskin animations.
+    pub beat: f32,
+    /// Seconds for milestones, explosions and glows. Runs at `rate` against the wall clock.
+    pub time: f32,
+    /// The music rate with the profile's `rate_scaled_effects` on, 1.0 with it off.
+    pub rate: f32,
+}
+
+impl VisualClock {
+    fn new(rate: f32) -> Self {
+        Self { beat: 0.0, time: 0.0, rate }
+    }
+
+    /// Moves to `beat` after `delta_time` wall seconds; returns the scaled step
+    /// for timed effects.
+    fn advance(&mut self, beat: f32, delta_time: f32) -> f32 {
+        let step = delta_time * self.rate;
+        self.beat = beat;
+        self.time += step;
+        step
+    }
+}
+
 pub struct State {
     pub song: Arc<SongData>,
     pub background_texture_key: String,
+    /// Set once the song's background video has a frame up; drawn instead of the still.
+    pub background_video_key: Option<String>,
+    /// Music time the background video starts at.
+    pub background_video_start: f32,
     pub chart: Arc<ChartData>,
+    /// The chart's pad layout; columns at and past `num_cols` stay empty.
+    pub game_type: GameType,
+    pub num_cols: usize,
     pub timing: Arc<TimingData>,
     pub notes: Vec<Note>,
 
     pub song_start_instant: Instant,
+    /// Rate, mini and turn in effect; the music clock runs at `mods.rate`.
+    pub mods: ChartMods,
     pub current_beat: f32,
     pub current_music_time: f32,
+    pub visual_clock: VisualClock,
     pub note_spawn_cursor: usize,
     pub judged_row_cursor: usize,
-    pub arrows: [Vec<Arrow>; 4],
+    pub arrows: [Vec<Arrow>; MAX_COLS],
     // Cached per-note timing to avoid per-frame recomputation
     pub note_time_cache: Vec<f32>,
     pub note_display_beat_cache: Vec<f32>,
@@ -138,20 +446,62 @@ pub struct State {
     pub full_combo_grade: Option<JudgeGrade>,
     pub first_fc_attempt_broken: bool,
     pub judgment_counts: HashMap<JudgeGrade, u32>,
+    /// Shows white Fantastics apart from blue ones (the profile's `fa_plus`).
+    pub fa_plus: bool,
+    /// Rows whose Fantastic landed in the white window; part of the Fantastic
+    /// count in `judgment_counts`, counted whether or not `fa_plus` is on.
+    pub white_fantastic_count: u32,
     pub scoring_counts: HashMap<JudgeGrade, u32>,
     pub last_judgment: Option<JudgmentRenderInfo>,
-    pub hold_judgments: [Option<HoldJudgmentRenderInfo>; 4],
+    pub hold_judgments: [Option<Anim<HoldResult>>; MAX_COLS],
 
     pub life: f32,
+    /// (music time, life) after every life change, for the Evaluation life graph.
+    pub life_history: Vec<(f32, f32)>,
     pub combo_after_miss: u32,
+    /// Whole seconds of the song left, as shown; kept by `SongSecond` events.
+    pub remaining_seconds: i32,
     pub is_failing: bool,
     pub is_in_freeze: bool,
     pub is_in_delay: bool,
     pub fail_time: Option<f32>,
+    pub fail_type: FailType,
+    /// Seconds into the Immediate fail sequence; the song clock is frozen meanwhile.
+    pub fail_elapsed: f32,
 
     pub earned_grade_points: i32,
     pub possible_grade_points: i32,
+    /// (music time, earned grade points) after every change; saved as the replay on a new best.
+    pub score_history: Vec<(f32, i32)>,
+    /// The personal best run on this chart, raced against as a pacer.
+    pub ghost: Option<Replay>,
     pub song_completed_naturally: bool,
+    /// Played under tournament lockdown: rate 1.0, no transforms, stock judgment windows and hold judging.
+    pub tournament_mode: bool,
+    pub hold_combo: HoldComboMode,
+    pub hold_judging: HoldJudging,
+    /// Every step hit dead on and every hold held (`autoplay`); set by `--benchmark`.
+    pub autoplay: bool,
+    /// Tournament-style window removal: a tap in a disabled window is ignored, so
+    /// the note ends up a miss unless a later tap lands in an enabled window.
+    pub decents_enabled: bool,
+    pub way_offs_enabled: bool,
+    /// Beginner assist: foot hints on the arrows, WayOffs forgiven as Decents,
+    /// and a scroll speed cap.
+    pub beginner_assist: bool,
+    /// Suggested foot per note (indexed like `notes`); empty unless beginner assist is on.
+    pub foot_hints: Vec<Option<Foot>>,
+    /// Shed visuals when frames run long (`Config::auto_quality`).
+    auto_quality: bool,
+    pub quality: QualityLevel,
+    /// Frames longer than this count toward a downshift; see `frame_budget_seconds`.
+    pub frame_budget_seconds: f32,
+    // Net seconds of over-budget frames toward the next downshift.
+    slow_frame_seconds: f32,
+    /// Shown briefly after each downshift.
+    pub quality_notice: Option<Anim>,
+    /// "Ready?" and 3-2-1-GO ahead of the first step; `None` for an instant start.
+    pub countdown: Option<Countdown>,
 
     pub noteskin: Option<Noteskin>,
     pub active_color_index: i32,
@@ -162,12 +512,12 @@ pub struct State {
     pub scroll_travel_time: f32,
     pub draw_distance_before_targets: f32,
     pub draw_distance_after_targets: f32,
-    pub receptor_glow_timers: [f32; 4],
-    pub receptor_bop_timers: [f32; 4],
-    pub tap_explosions: [Option<ActiveTapExplosion>; 4],
-    pub mine_explosions: [Option<ActiveMineExplosion>; 4],
-    pub active_holds: [Option<ActiveHold>; 4],
-    pub combo_milestones: Vec<ActiveComboMilestone>,
+    pub receptor_glows: [Option<Anim>; MAX_COLS],
+    pub receptor_commands: [Option<Anim<ReceptorEvent>>; MAX_COLS],
+    pub tap_explosions: [Option<Anim<ActiveTapExplosion>>; MAX_COLS],
+    pub mine_explosions: [Option<Anim>; MAX_COLS],
+    pub active_holds: [Option<ActiveHold>; MAX_COLS],
+    pub combo_milestones: Vec<Anim<ComboMilestoneKind>>,
     pub hands_achieved: u32,
     pub holds_total: u32,
     pub holds_held: u32,
@@ -181,18 +531,97 @@ pub struct State {
     pub mines_avoided: u32,
     hands_holding_count_for_stats: i32,
 
-    pub total_elapsed_in_screen: f32,
-
     pub hold_to_exit_key: Option<KeyCode>,
     pub hold_to_exit_start: Option<Instant>,
-    prev_inputs: [bool; 4],
-    keyboard_lane_state: [bool; 4],
-    gamepad_lane_state: [bool; 4],
+    // Per column; `GameType::column_for` maps each input lane to one.
+    prev_inputs: [bool; MAX_COLS],
+    // Per input lane, before lanes sharing a column are folded together.
+    keyboard_input_lanes: [bool; Lane::ALL.len()],
+    gamepad_input_lanes: [bool; Lane::ALL.len()],
+    keyboard_lane_state: [bool; MAX_COLS],
+    gamepad_lane_state: [bool; MAX_COLS],
     pending_edges: VecDeque<InputEdge>,
-
+    /// Music time before which presses are never judged: the countdown up to the
+    /// first step's earliest window.
+    input_dead_zone_end: f32,
+    /// Lanes pressed in the dead zone (or held in from the previous screen). They
+    /// count as up until released, so a foot resting on a sensor can't score,
+    /// engage a hold or set off a mine.
+    stale_lanes: [bool; MAX_COLS],
+
+    pub events: EventBus<State>,
     log_timer: f32,
 }
 
+impl Subscriber for State {
+    fn event_bus(&mut self) -> &mut EventBus<State> {
+        &mut self.events
+    }
+}
+
+// --- Event listeners: presentation state driven by gameplay events ---
+
+fn on_judgment(state: &mut State, event: &GameEvent) {
+    if let GameEvent::Judgment { judgment, judged_at } = event {
+        state.last_judgment = Some(JudgmentRenderInfo {
+            judgment: judgment.clone(),
+            judged_at: *judged_at,
+        });
+    }
+}
+
+fn on_combo_milestone(state: &mut State, event: &GameEvent) {
+    if let GameEvent::ComboMilestone(kind) = event {
+        trigger_combo_milestone(state, kind.clone());
+    }
+}
+
+fn on_life_changed(state: &mut State, event: &GameEvent) {
+    if let GameEvent::LifeChanged { life, time } = event {
+        state.life_history.push((*time, *life));
+    }
+}
+
+fn on_song_second(state: &mut State, event: &GameEvent) {
+    // The clock stops where the player failed.
+    if let (GameEvent::SongSecond(second), None) = (event, state.fail_time) {
+        // Past the start of `second`, so that second is no longer remaining.
+        let total = state.song.total_length_seconds.max(0);
+        state.remaining_seconds = (total - second - 1).clamp(0, total);
+    }
+}
+
+// --- Event listeners: the event server ---
+
+fn on_song_started(state: &mut State, _event: &GameEvent) {
+    ipc::publish(IpcEvent::SongStarted {
+        title: state.song.title.clone(),
+        artist: state.song.artist.clone(),
+        chart_type: state.chart.chart_type.clone(),
+        difficulty: state.chart.difficulty.clone(),
+        meter: state.chart.meter,
+        chart_hash: state.chart.short_hash.clone(),
+    });
+}
+
+fn on_note_judged(_state: &mut State, event: &GameEvent) {
+    if let GameEvent::NoteJudged { time, row, column, grade, offset_ms } = *event {
+        ipc::publish(IpcEvent::NoteJudged { time, row, column, grade, offset_ms });
+    }
+}
+
+fn on_hold_dropped(_state: &mut State, event: &GameEvent) {
+    if let GameEvent::HoldDropped { time, column } = *event {
+        ipc::publish(IpcEvent::HoldDropped { time, column });
+    }
+}
+
+fn on_song_ended(_state: &mut State, event: &GameEvent) {
+    if let GameEvent::SongEnded { time, completed, score_percent } = *event {
+        ipc::publish(IpcEvent::SongEnded { time, completed, score_percent });
+    }
+}
+
 #[inline(always)]
 fn is_state_dead(state: &State) -> bool {
     state.is_failing || state.life <= 0.0
@@ -216,11 +645,22 @@ fn apply_life_change(state: &mut State, delta: f32) {
         state.combo_after_miss = REGEN_COMBO_AFTER_MISS;
     }
 
+    let previous_life = state.life;
     state.life = (state.life + final_delta).clamp(0.0, 1.0);
+    if state.life != previous_life {
+        state.events.publish(GameEvent::LifeChanged {
+            life: state.life,
+            time: state.current_music_time,
+        });
+    }
 
     if state.life <= 0.0 {
         if !state.is_failing {
             state.fail_time = Some(state.current_music_time);
+            if state.fail_type == FailType::Immediate {
+                audio::clear_assist_ticks();
+                audio::tape_stop_music(FAIL_MUSIC_STOP_SECONDS);
+            }
         }
         state.life = 0.0;
         state.is_failing = true;
@@ -260,24 +700,93 @@ fn get_reference_bpm_from_display_tag(display_bpm_str: &str) -> Option<f32> {
     s.parse::<f32>().ok()
 }
 
-pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32) -> State {
+/// Simple alternating-feet guess for beginners: the outermost columns take
+/// their own foot, jumps split left-most/right-most, and lone steps in between
+/// go to the foot that didn't step last.
+fn compute_foot_hints(notes: &[Note], num_cols: usize) -> Vec<Option<Foot>> {
+    let mut hints = vec![None; notes.len()];
+    let mut last_foot = Foot::Right;
+    let mut i = 0;
+    while i < notes.len() {
+        let row = notes[i].row_index;
+        let mut end = i;
+        while end < notes.len() && notes[end].row_index == row {
+            end += 1;
+        }
+        let steps: Vec<usize> = (i..end)
+            .filter(|&n| !matches!(notes[n].note_type, NoteType::Mine))
+            .collect();
+
+        match steps.as_slice() {
+            [] => {}
+            [only] => {
+                let foot = match notes[*only].column {
+                    0 => Foot::Left,
+                    c if c + 1 == num_cols => Foot::Right,
+                    _ if last_foot == Foot::Left => Foot::Right,
+                    _ => Foot::Left,
+                };
+                hints[*only] = Some(foot);
+                last_foot = foot;
+            }
+            many => {
+                let leftmost = many.iter().copied().min_by_key(|&n| notes[n].column);
+                let rightmost = many.iter().copied().max_by_key(|&n| notes[n].column);
+                if let (Some(l), Some(r)) = (leftmost, rightmost) {
+                    hints[l] = Some(Foot::Left);
+                    hints[r] = Some(Foot::Right);
+                }
+            }
+        }
+        i = end;
+    }
+    hints
+}
+
+pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32, mods: ChartMods) -> State {
     info!("Initializing Gameplay Screen...");
     info!(
         "Loaded song '{}' and chart '{}'",
         song.title, chart.difficulty
     );
 
-    let style = Style {
-        num_cols: 4,
-        num_players: 1,
-    };
-    let noteskin = noteskin::load(Path::new("assets/noteskins/cel/dance-single.txt"), &style)
+    // The chart says what it's played on; one the wheel somehow let through
+    // with an unknown steps type is read as four-panel.
+    let game_type = GameType::for_chart_type(&chart.chart_type).unwrap_or_else(|| {
+        warn!("Unknown steps type '{}'; playing it as {}.", chart.chart_type, GameType::default());
+        GameType::default()
+    });
+    let num_cols = game_type.num_cols();
+    let style = game_type.style();
+    let noteskin = noteskin::load(&Path::new("assets/noteskins/cel").join(game_type.noteskin_file()), &style)
         .ok()
         .or_else(|| noteskin::load(Path::new("assets/noteskins/fallback.txt"), &style).ok());
 
     let config = crate::config::get();
+    let mods = if config.tournament_mode { ChartMods::default() } else { mods.clamped() };
+    // Tournament play keeps the stock windows, hold judging and scroll speed, and
+    // forgives nothing, whatever the machine is set to.
+    let beginner_assist = !config.tournament_mode && config.beginner_assist;
+    let decents_enabled = config.tournament_mode || !config.disable_decents;
+    let way_offs_enabled = config.tournament_mode || !config.disable_way_offs;
+    let hold_judging = if config.tournament_mode { HoldJudging::Standard } else { config.hold_judging };
+    if config.tournament_mode {
+        info!("Tournament mode: modifiers locked to rate 1.0, no transforms, stock judgment windows and hold judging, no beginner assist.");
+    }
+    if !mods.is_default() {
+        info!("Playing with rate {}, mini {}, turn {}.", mods.rate_text(), mods.mini_text(), mods.turn);
+    }
+    let column_map = mods.turn.column_map(num_cols);
+    if !decents_enabled || !way_offs_enabled {
+        info!(
+            "Judgment windows disabled: Decents {}, WayOffs {}.",
+            if decents_enabled { "on" } else { "off" },
+            if way_offs_enabled { "on" } else { "off" }
+        );
+    }
+    let song_offset = song.offset + offsets::offset_for(&song, &chart.short_hash);
     let timing = Arc::new(TimingData::from_chart_data(
-        -song.offset, config.global_offset_seconds,
+        -song_offset, config.global_offset_seconds,
         chart.chart_bpms.as_deref(),
         &song.normalized_bpms,
 		chart.chart_stops.as_deref(),
@@ -293,7 +802,7 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         &chart.notes,
     ));
 
-    let parsed_notes = note_parser::parse_chart_notes(&chart.notes);
+    let parsed_notes = note_parser::parse_chart_notes(&chart.notes, num_cols);
     let mut notes: Vec<Note> = Vec::with_capacity(parsed_notes.len());
     let mut holds_total: u32 = 0;
     let mut rolls_total: u32 = 0;
@@ -336,7 +845,7 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
 
         notes.push(Note {
             beat,
-            column: parsed.column,
+            column: column_map.get(parsed.column).copied().unwrap_or(parsed.column),
             note_type,
             row_index,
             result: None,
@@ -344,6 +853,10 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
             mine_result: None,
         });
     }
+    // Turned columns no longer come out of the parser in order within a row.
+    if column_map.iter().enumerate().any(|(i, &c)| i != c) {
+        notes.sort_by_key(|n| (n.row_index, n.column));
+    }
     // ITG scoring counts one tap judgment per row (chords count as one).
     // Compute unique non-mine rows to determine possible tap grade points.
     let num_tap_rows = {
@@ -360,9 +873,20 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         + (holds_total as u64 * judgment::HOLD_SCORE_HELD as u64)
         + (rolls_total as u64 * judgment::HOLD_SCORE_HELD as u64);
     let possible_grade_points = possible_grade_points as i32;
+    // A replay from a different version of the chart would pace against the wrong totals.
+    let ghost = replay::load(&chart.short_hash, mods.rate).filter(|r| r.possible_grade_points == possible_grade_points);
+
+    // Marquee boards rarely have glyphs beyond ASCII, so transliterations win there.
+    led_marquee::show_now_playing(&song.display_full_title(true), song.display_artist(true));
 
     info!("Parsed {} notes from chart data.", notes.len());
 
+    let foot_hints = if beginner_assist {
+        compute_foot_hints(&notes, num_cols)
+    } else {
+        Vec::new()
+    };
+
     // Build immutable caches for timing-intensive lookups
     let note_time_cache: Vec<f32> = notes
         .iter()
@@ -387,6 +911,8 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         let cut = audio::Cut {
             start_sec: (-start_delay) as f64,
             length_sec: f64::INFINITY,
+            gain_db: song.playback_gain_db(config.normalize_music_volume),
+            rate: mods.rate as f64,
             ..Default::default()
         };
         audio::play_music(music_path.clone(), cut, false);
@@ -395,7 +921,7 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
     }
 
     let profile = profile::get();
-    let scroll_speed = profile.scroll_speed;
+    let mut scroll_speed = profile.scroll_speed;
     let initial_bpm = timing.get_bpm_for_beat(first_note_beat);
 
     // THIS IS THE KEY CHANGE: Determine the reference BPM for M-Mods.
@@ -415,6 +941,13 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         reference_bpm = initial_bpm.max(120.0);
     }
 
+    if beginner_assist {
+        let capped = scroll_speed.capped(BEGINNER_MAX_SCROLL_BPM, reference_bpm);
+        if capped != scroll_speed {
+            info!("Beginner assist: scroll speed {} capped to {}.", scroll_speed, capped);
+            scroll_speed = capped;
+        }
+    }
     let mut pixels_per_second = scroll_speed.pixels_per_second(initial_bpm, reference_bpm);
     if !pixels_per_second.is_finite() || pixels_per_second <= 0.0 {
         warn!(
@@ -426,10 +959,12 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
     }
     let draw_distance_before_targets = screen_height() * DRAW_DISTANCE_BEFORE_TARGETS_MULTIPLIER;
     let draw_distance_after_targets = DRAW_DISTANCE_AFTER_TARGETS;
+    // Mini packs the notes closer, so the same distance on screen covers more of the chart.
+    let unzoomed_draw_distance = draw_distance_before_targets / mods.field_zoom();
     let mut travel_time =
-        scroll_speed.travel_time_seconds(draw_distance_before_targets, initial_bpm, reference_bpm);
+        scroll_speed.travel_time_seconds(unzoomed_draw_distance, initial_bpm, reference_bpm);
     if !travel_time.is_finite() || travel_time <= 0.0 {
-        travel_time = draw_distance_before_targets / pixels_per_second;
+        travel_time = unzoomed_draw_distance / pixels_per_second;
     }
     info!(
         "Scroll speed set to {} (ref BPM: {:.2}, effective BPM at start: {:.2}), {:.2} px/s",
@@ -449,21 +984,39 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
             acc.max(end)
         });
     let music_end_time = last_relevant_second
-        + (BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD)
-        + TRANSITION_OUT_DURATION;
-
-    State {
+        + (BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD + TRANSITION_OUT_DURATION) * mods.rate;
+
+    let mut events = EventBus::default();
+    events.subscribe(EventKind::Judgment, on_judgment);
+    events.subscribe(EventKind::ComboMilestone, on_combo_milestone);
+    events.subscribe(EventKind::LifeChanged, on_life_changed);
+    events.subscribe(EventKind::SongSecond, on_song_second);
+    events.subscribe(EventKind::SongStarted, on_song_started);
+    events.subscribe(EventKind::NoteJudged, on_note_judged);
+    events.subscribe(EventKind::HoldDropped, on_hold_dropped);
+    events.subscribe(EventKind::SongEnded, on_song_ended);
+    events.publish(GameEvent::SongStarted);
+
+    let background_video_start = timing.get_time_for_beat(song.background_video_beat);
+    let remaining_seconds = song.total_length_seconds.max(0);
+    let state = State {
         song,
         chart,
+        game_type,
+        num_cols,
         background_texture_key: "__white".to_string(),
+        background_video_key: None,
+        background_video_start,
         timing,
         notes,
         song_start_instant,
+        mods,
         current_beat: 0.0,
-        current_music_time: -start_delay,
+        current_music_time: -start_delay * mods.rate,
+        visual_clock: VisualClock::new(if profile.rate_scaled_effects { mods.rate } else { 1.0 }),
         note_spawn_cursor: 0,
         judged_row_cursor: 0,
-        arrows: [vec![], vec![], vec![], vec![]],
+        arrows: Default::default(),
         note_time_cache,
         note_display_beat_cache,
         hold_end_time_cache,
@@ -476,6 +1029,8 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
             (JudgeGrade::WayOff, 0),
             (JudgeGrade::Miss, 0),
         ]),
+        fa_plus: profile.fa_plus,
+        white_fantastic_count: 0,
         scoring_counts: HashMap::from_iter([
             (JudgeGrade::Fantastic, 0),
             (JudgeGrade::Excellent, 0),
@@ -491,14 +1046,34 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         last_judgment: None,
         hold_judgments: Default::default(),
         life: 0.5,
+        life_history: vec![(0.0, 0.5)],
         combo_after_miss: 0,
+        remaining_seconds,
         is_failing: false,
         is_in_freeze: false,
         is_in_delay: false,
         fail_time: None,
+        fail_type: config.fail_type,
+        fail_elapsed: 0.0,
         earned_grade_points: 0,
         possible_grade_points,
+        score_history: Vec::new(),
+        ghost,
         song_completed_naturally: false,
+        tournament_mode: config.tournament_mode,
+        hold_combo: config.hold_combo,
+        hold_judging,
+        autoplay: false,
+        decents_enabled,
+        way_offs_enabled,
+        beginner_assist,
+        foot_hints,
+        auto_quality: config.auto_quality,
+        quality: QualityLevel::Full,
+        frame_budget_seconds: frame_budget_seconds(None),
+        slow_frame_seconds: 0.0,
+        quality_notice: None,
+        countdown: config.song_countdown.then(|| Countdown::new(first_second, initial_bpm)),
         noteskin,
         active_color_index,
         player_color: color::decorative_rgba(active_color_index),
@@ -508,8 +1083,8 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         scroll_travel_time: travel_time,
         draw_distance_before_targets,
         draw_distance_after_targets,
-        receptor_glow_timers: [0.0; 4],
-        receptor_bop_timers: [0.0; 4],
+        receptor_glows: Default::default(),
+        receptor_commands: Default::default(),
         tap_explosions: Default::default(),
         mine_explosions: Default::default(),
         active_holds: Default::default(),
@@ -526,24 +1101,98 @@ pub fn init(song: Arc<SongData>, chart: Arc<ChartData>, active_color_index: i32)
         mines_hit_for_score: 0,
         mines_avoided: 0,
         hands_holding_count_for_stats: 0,
-        total_elapsed_in_screen: 0.0,
         hold_to_exit_key: None,
         hold_to_exit_start: None,
-        prev_inputs: [false; 4],
-        keyboard_lane_state: [false; 4],
-        gamepad_lane_state: [false; 4],
+        prev_inputs: [false; MAX_COLS],
+        keyboard_input_lanes: [false; Lane::ALL.len()],
+        gamepad_input_lanes: [false; Lane::ALL.len()],
+        keyboard_lane_state: [false; MAX_COLS],
+        gamepad_lane_state: [false; MAX_COLS],
         pending_edges: VecDeque::new(),
+        input_dead_zone_end: first_second - (BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD) * mods.rate,
+        stale_lanes: [false; MAX_COLS],
+        events,
         log_timer: 0.0,
-    }
+    };
+    schedule_assist_ticks(&state);
+    state
+}
+
+/// Hands the audio engine the assist tick times for the configured mode, or
+/// silences it when that's Off.
+fn schedule_assist_ticks(state: &State) {
+    let config = crate::config::get();
+    // Note times have the global offset taken out; ticks go where the music plays them.
+    let offset = config.global_offset_seconds;
+    let times: Vec<f64> = match config.assist_tick {
+        AssistTick::Off => {
+            audio::clear_assist_ticks();
+            return;
+        }
+        AssistTick::Clap => {
+            let mut last_row = None;
+            state.notes
+                .iter()
+                .zip(&state.note_time_cache)
+                .filter(|(note, _)| note.note_type != NoteType::Mine)
+                .filter(|(note, _)| last_row.replace(note.row_index) != Some(note.row_index))
+                .map(|(_, &time)| (time + offset) as f64)
+                .collect()
+        }
+        AssistTick::Metronome => {
+            let last_beat = state.timing.get_beat_for_time(state.music_end_time).floor().max(0.0) as u32;
+            (0..=last_beat)
+                .map(|beat| (state.timing.get_time_for_beat(beat as f32) + offset) as f64)
+                .collect()
+        }
+    };
+    audio::set_assist_ticks(times, config.assist_tick_volume as f32 / 100.0);
 }
 
 fn update_itg_grade_totals(state: &mut State) {
-    state.earned_grade_points = judgment::calculate_itg_grade_points(
+    let earned = judgment::calculate_itg_grade_points(
         &state.scoring_counts,
         state.holds_held_for_score,
         state.rolls_held_for_score,
         state.mines_hit_for_score,
     );
+    if earned != state.earned_grade_points {
+        state.score_history.push((state.current_music_time, earned));
+    }
+    state.earned_grade_points = earned;
+}
+
+fn publish_song_ended(state: &mut State, completed: bool) {
+    let score_percent = judgment::score_percent_from_points(state.earned_grade_points, state.possible_grade_points);
+    state.events.publish(GameEvent::SongEnded { time: state.current_music_time, completed, score_percent });
+    led_marquee::clear();
+}
+
+/// Wraps up a song cut short by quitting the game: listeners hear it ended.
+/// An unfinished song isn't counted as a play.
+pub fn abandon(state: &mut State) {
+    publish_song_ended(state, false);
+    events::dispatch(state);
+}
+
+/// Seconds into the Immediate fail sequence, or None when it isn't playing.
+pub fn fail_sequence_time(state: &State) -> Option<f32> {
+    (state.fail_type == FailType::Immediate && state.fail_time.is_some()).then_some(state.fail_elapsed)
+}
+
+/// How far ahead of (+) or behind (-) the ghost the player is, in score percent.
+pub fn ghost_delta_percent(state: &State) -> Option<f64> {
+    let ghost = state.ghost.as_ref()?;
+    if state.possible_grade_points <= 0 {
+        return None;
+    }
+    let diff = state.earned_grade_points - ghost.points_at(state.current_music_time);
+    Some(diff as f64 / state.possible_grade_points as f64 * 100.0)
+}
+
+/// Whether a Fantastic `time_error` seconds off lands in the FA+ white window.
+pub fn is_white_fantastic(grade: JudgeGrade, time_error: f32) -> bool {
+    grade == JudgeGrade::Fantastic && time_error.abs() <= BASE_WHITE_FANTASTIC_WINDOW + TIMING_WINDOW_ADD
 }
 
 fn grade_to_window(grade: JudgeGrade) -> Option<&'static str> {
@@ -557,43 +1206,70 @@ fn grade_to_window(grade: JudgeGrade) -> Option<&'static str> {
     }
 }
 
-fn trigger_tap_explosion(state: &mut State, column: usize, grade: JudgeGrade) {
-    let Some(window_key) = grade_to_window(grade) else {
+// `white` picks the skin's W0 explosion under FA+; skins without one show W1.
+fn trigger_tap_explosion(state: &mut State, column: usize, grade: JudgeGrade, white: bool) {
+    let Some(mut window_key) = grade_to_window(grade) else {
         return;
     };
+    let has_w0 = state.noteskin.as_ref().is_some_and(|ns| ns.tap_explosions.contains_key("W0"));
+    if white && state.fa_plus && has_w0 {
+        window_key = "W0";
+    }
 
-    let spawn_window = state.noteskin.as_ref().and_then(|ns| {
-        if ns.tap_explosions.contains_key(window_key) {
-            Some(window_key.to_string())
-        } else {
-            None
-        }
-    });
+    let lifetime = state
+        .noteskin
+        .as_ref()
+        .and_then(|ns| ns.tap_explosions.get(window_key))
+        .map(|explosion| explosion.animation.duration());
 
-    if let Some(window) = spawn_window {
-        state.tap_explosions[column] = Some(ActiveTapExplosion {
-            window,
-            elapsed: 0.0,
+    if let Some(lifetime) = lifetime {
+        let explosion = ActiveTapExplosion {
+            window: window_key.to_string(),
             start_beat: state.current_beat,
-        });
+        };
+        state.tap_explosions[column] = Some(Anim::with(explosion, lifetime, Ease::Linear));
     }
 }
 
 fn trigger_mine_explosion(state: &mut State, column: usize) {
-    state.mine_explosions[column] = Some(ActiveMineExplosion { elapsed: 0.0 });
+    state.mine_explosions[column] = Some(Anim::new(MINE_EXPLOSION_DURATION, Ease::Linear));
+}
+
+/// The noteskin script a receptor plays for `event`.
+pub fn receptor_animation(ns: &Noteskin, event: ReceptorEvent) -> &noteskin::ExplosionAnimation {
+    match event {
+        ReceptorEvent::Press => &ns.receptor_press,
+        ReceptorEvent::Lift => &ns.receptor_lift,
+    }
+}
+
+fn start_receptor_command(state: &mut State, column: usize, event: ReceptorEvent) {
+    let lifetime = state
+        .noteskin
+        .as_ref()
+        .map(|ns| receptor_animation(ns, event).duration())
+        .unwrap_or(0.0);
+    // An empty script (the default lift) leaves a running press alone.
+    if lifetime > 0.0 {
+        state.receptor_commands[column] = Some(Anim::with(event, lifetime, Ease::Linear));
+    }
 }
 
 fn trigger_combo_milestone(state: &mut State, kind: ComboMilestoneKind) {
-    if let Some(index) = state
+    if let Some(milestone) = state
         .combo_milestones
-        .iter()
-        .position(|milestone| milestone.kind == kind)
+        .iter_mut()
+        .find(|milestone| milestone.data == kind)
     {
-        state.combo_milestones[index].elapsed = 0.0;
+        milestone.restart();
     } else {
+        let duration = match kind {
+            ComboMilestoneKind::Hundred => COMBO_HUNDRED_MILESTONE_DURATION,
+            ComboMilestoneKind::Thousand => COMBO_THOUSAND_MILESTONE_DURATION,
+        };
         state
             .combo_milestones
-            .push(ActiveComboMilestone { kind, elapsed: 0.0 });
+            .push(Anim::with(kind, duration, Ease::Linear));
     }
 }
 
@@ -638,7 +1314,7 @@ fn handle_mine_hit(
         state.first_fc_attempt_broken = true;
     }
     state.full_combo_grade = None;
-    state.receptor_glow_timers[column] = 0.0;
+    state.receptor_glows[column] = None;
     trigger_mine_explosion(state, column);
     audio::play_sfx("assets/sounds/boom.ogg");
 
@@ -670,10 +1346,35 @@ fn try_hit_mine_while_held(state: &mut State, column: usize, current_time: f32)
         return false;
     };
 
-    let time_error = current_time - note_time;
+    let time_error = real_seconds(state, current_time - note_time);
     handle_mine_hit(state, column, arrow_idx, note_index, time_error)
 }
 
+/// Extends the combo by one. `grade` is the judgment that earned it, folded into
+/// the full-combo grade; hold body credit passes `None` and leaves the grade as is.
+fn add_combo(state: &mut State, grade: Option<JudgeGrade>) {
+    state.combo += 1;
+
+    let combo = state.combo;
+    if combo > 0 && combo % 1000 == 0 {
+        state.events.publish(GameEvent::ComboMilestone(ComboMilestoneKind::Thousand));
+        state.events.publish(GameEvent::ComboMilestone(ComboMilestoneKind::Hundred));
+    } else if combo > 0 && combo % 100 == 0 {
+        state.events.publish(GameEvent::ComboMilestone(ComboMilestoneKind::Hundred));
+    }
+
+    if !state.first_fc_attempt_broken {
+        if let Some(grade) = grade {
+            let new_grade = if let Some(current_fc_grade) = &state.full_combo_grade {
+                grade.max(*current_fc_grade)
+            } else {
+                grade
+            };
+            state.full_combo_grade = Some(new_grade);
+        }
+    }
+}
+
 fn handle_hold_let_go(state: &mut State, column: usize, note_index: usize) {
     if let Some(hold) = state.notes[note_index].hold.as_mut() {
         if hold.result == Some(HoldResult::LetGo) {
@@ -689,11 +1390,13 @@ fn handle_hold_let_go(state: &mut State, column: usize, note_index: usize) {
     if state.hands_holding_count_for_stats > 0 {
         state.hands_holding_count_for_stats -= 1;
     }
+    state.events.publish(GameEvent::HoldDropped { time: state.current_music_time, column });
 
-    state.hold_judgments[column] = Some(HoldJudgmentRenderInfo {
-        result: HoldResult::LetGo,
-        triggered_at: Instant::now(),
-    });
+    state.hold_judgments[column] = Some(Anim::with(
+        HoldResult::LetGo,
+        HOLD_JUDGMENT_TOTAL_DURATION,
+        Ease::Accelerate,
+    ));
 
     apply_life_change(state, LifeChange::LET_GO);
     if !is_state_dead(state) {
@@ -705,7 +1408,7 @@ fn handle_hold_let_go(state: &mut State, column: usize, note_index: usize) {
         state.first_fc_attempt_broken = true;
     }
     state.full_combo_grade = None;
-    state.receptor_glow_timers[column] = 0.0;
+    state.receptor_glows[column] = None;
 }
 
 fn handle_hold_success(state: &mut State, column: usize, note_index: usize) {
@@ -749,13 +1452,60 @@ fn handle_hold_success(state: &mut State, column: usize, note_index: usize) {
         update_itg_grade_totals(state);
     }
     state.miss_combo = 0;
+    // Checkpoints already gave the body its combo.
+    if state.hold_combo == HoldComboMode::PerHold && state.hold_judging == HoldJudging::Standard {
+        add_combo(state, None);
+    }
 
-    trigger_tap_explosion(state, column, JudgeGrade::Excellent);
+    trigger_tap_explosion(state, column, JudgeGrade::Excellent, false);
 
-    state.hold_judgments[column] = Some(HoldJudgmentRenderInfo {
-        result: HoldResult::Held,
-        triggered_at: Instant::now(),
-    });
+    state.hold_judgments[column] = Some(Anim::with(
+        HoldResult::Held,
+        HOLD_JUDGMENT_TOTAL_DURATION,
+        Ease::Accelerate,
+    ));
+}
+
+// Checkpoints that passed with the lane up: each one is a Miss, counted and
+// scored like a missed tap.
+fn miss_checkpoints(state: &mut State, column: usize, count: u32) {
+    *state.judgment_counts.entry(JudgeGrade::Miss).or_insert(0) += count;
+    if !is_state_dead(state) {
+        *state.scoring_counts.entry(JudgeGrade::Miss).or_insert(0) += count;
+        update_itg_grade_totals(state);
+    }
+    for _ in 0..count {
+        apply_life_change(state, LifeChange::MISS);
+    }
+    state.combo = 0;
+    state.miss_combo = state.miss_combo.saturating_add(count);
+    if state.full_combo_grade.is_some() {
+        state.first_fc_attempt_broken = true;
+    }
+    state.full_combo_grade = None;
+    state.hold_judgments[column] = Some(Anim::with(
+        HoldResult::LetGo,
+        HOLD_JUDGMENT_TOTAL_DURATION,
+        Ease::Accelerate,
+    ));
+}
+
+// The tail of a checkpoint hold. Held only if every checkpoint was; misses were
+// already charged as they happened, so a Let Go costs nothing more here.
+fn finish_checkpoint_hold(state: &mut State, column: usize, note_index: usize, missed: u32) {
+    if missed == 0 {
+        handle_hold_success(state, column, note_index);
+        return;
+    }
+    if let Some(hold) = state.notes[note_index].hold.as_mut() {
+        hold.result = Some(HoldResult::LetGo);
+    }
+    if state.hands_holding_count_for_stats > 0 {
+        state.hands_holding_count_for_stats -= 1;
+    }
+    if !is_state_dead(state) {
+        update_itg_grade_totals(state);
+    }
 }
 
 fn refresh_roll_life_on_step(state: &mut State, column: usize) {
@@ -778,15 +1528,21 @@ fn refresh_roll_life_on_step(state: &mut State, column: usize) {
     }
 
     active.life = MAX_HOLD_LIFE;
+    active.explosion_elapsed = 0.0;
     hold.life = MAX_HOLD_LIFE;
     hold.let_go_started_at = None;
     hold.let_go_starting_life = 0.0;
 }
 
-fn update_active_holds(state: &mut State, inputs: &[bool; 4], current_time: f32, delta_time: f32) {
+fn update_active_holds(state: &mut State, inputs: &[bool; MAX_COLS], current_time: f32, delta_time: f32) {
     for column in 0..state.active_holds.len() {
         let mut handle_let_go = None;
         let mut handle_success = None;
+        let mut combo_ticks = 0;
+        let mut checkpoints_held = 0;
+        let mut checkpoints_missed = 0;
+        let mut checkpoint_end = None;
+        let checkpoint = state.hold_judging == HoldJudging::Checkpoint;
 
         {
             let active_opt = &mut state.active_holds[column];
@@ -823,12 +1579,37 @@ fn update_active_holds(state: &mut State, inputs: &[bool; 4], current_time: f32,
                     } else {
                         hold.last_held_beat = prev_beat.clamp(note_start_beat, hold.end_beat);
                     }
+
+                    let held_beats = (hold.last_held_beat - note_start_beat).max(0.0).floor() as u32;
+                    if state.hold_combo == HoldComboMode::PerBeat && !checkpoint {
+                        combo_ticks = held_beats.saturating_sub(active.combo_ticks);
+                        active.combo_ticks = active.combo_ticks.max(held_beats);
+                    }
+                    if held_beats > active.pulse_beats {
+                        active.pulse_beats = held_beats;
+                        active.explosion_elapsed = 0.0;
+                    } else {
+                        active.explosion_elapsed += delta_time;
+                    }
                 }
 
                 let pressed = inputs[column];
                 active.is_pressed = pressed;
 
-                if !active.let_go {
+                if checkpoint {
+                    let beat = state.current_beat.min(hold.end_beat);
+                    let passed = ((beat - note_start_beat).max(0.0) * CHECKPOINTS_PER_BEAT as f32 + 1e-3).floor() as u32;
+                    let new = passed.saturating_sub(active.checkpoints);
+                    active.checkpoints = active.checkpoints.max(passed);
+                    if pressed {
+                        checkpoints_held = new;
+                    } else {
+                        checkpoints_missed = new;
+                        active.checkpoints_missed += new;
+                    }
+                    // Engaged (and drawn so) exactly while the lane is down.
+                    active.life = if pressed { MAX_HOLD_LIFE } else { 0.0 };
+                } else if !active.let_go {
                     let window = match active.note_type {
                         NoteType::Hold => TIMING_WINDOW_SECONDS_HOLD,
                         NoteType::Roll => TIMING_WINDOW_SECONDS_ROLL,
@@ -872,12 +1653,15 @@ fn update_active_holds(state: &mut State, inputs: &[bool; 4], current_time: f32,
                 hold.let_go_started_at = None;
                 hold.let_go_starting_life = 0.0;
 
-                if !active.let_go && active.life <= 0.0 {
+                if !checkpoint && !active.let_go && active.life <= 0.0 {
                     active.let_go = true;
                     handle_let_go = Some((column, note_index));
                 }
 
-                if current_time >= active.end_time {
+                if checkpoint && current_time >= active.end_time {
+                    checkpoint_end = Some((column, note_index, active.checkpoints_missed));
+                    *active_opt = None;
+                } else if current_time >= active.end_time {
                     if !active.let_go && active.life > 0.0 {
                         handle_success = Some((column, note_index));
                     } else if !active.let_go {
@@ -891,6 +1675,20 @@ fn update_active_holds(state: &mut State, inputs: &[bool; 4], current_time: f32,
             }
         }
 
+        for _ in 0..combo_ticks {
+            add_combo(state, None);
+        }
+        for _ in 0..checkpoints_held {
+            add_combo(state, None);
+            state.miss_combo = 0;
+        }
+        if checkpoints_missed > 0 {
+            miss_checkpoints(state, column, checkpoints_missed);
+        }
+        if let Some((column, note_index, missed)) = checkpoint_end {
+            finish_checkpoint_hold(state, column, note_index, missed);
+        }
+
         if let Some((column, note_index)) = handle_let_go {
             handle_hold_let_go(state, column, note_index);
         }
@@ -901,6 +1699,45 @@ fn update_active_holds(state: &mut State, inputs: &[bool; 4], current_time: f32,
     }
 }
 
+/// Converts a span of music time to wall-clock seconds. Music runs `rate` times
+/// faster than the clock, and the timing windows are in real time.
+#[inline(always)]
+fn real_seconds(state: &State, music_seconds: f32) -> f32 {
+    music_seconds / state.mods.rate
+}
+
+/// Widest window that still produces a judgment. Taps outside it are ignored, and
+/// a note that drifts past it late is a miss.
+fn outer_hit_window(state: &State) -> f32 {
+    if state.way_offs_enabled {
+        BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD
+    } else if state.decents_enabled {
+        BASE_DECENT_WINDOW + TIMING_WINDOW_ADD
+    } else {
+        BASE_GREAT_WINDOW + TIMING_WINDOW_ADD
+    }
+}
+
+/// The grade for a tap `abs_time_error` seconds off its note, or None when that
+/// lands outside every enabled window and the tap is ignored.
+fn tap_grade(state: &State, abs_time_error: f32) -> Option<JudgeGrade> {
+    if abs_time_error <= BASE_FANTASTIC_WINDOW + TIMING_WINDOW_ADD {
+        Some(JudgeGrade::Fantastic)
+    } else if abs_time_error <= BASE_EXCELLENT_WINDOW + TIMING_WINDOW_ADD {
+        Some(JudgeGrade::Excellent)
+    } else if abs_time_error <= BASE_GREAT_WINDOW + TIMING_WINDOW_ADD {
+        Some(JudgeGrade::Great)
+    } else if abs_time_error <= BASE_DECENT_WINDOW + TIMING_WINDOW_ADD {
+        state.decents_enabled.then_some(JudgeGrade::Decent)
+    } else if abs_time_error <= BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD {
+        // Beginner assist forgives a WayOff only as far as a Decent that's allowed.
+        let forgiven = state.beginner_assist && state.decents_enabled;
+        state.way_offs_enabled.then_some(if forgiven { JudgeGrade::Decent } else { JudgeGrade::WayOff })
+    } else {
+        None
+    }
+}
+
 pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool {
     if let Some((arrow_list_index, arrow_to_judge)) = state.arrows[column]
         .iter()
@@ -912,7 +1749,7 @@ pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool
         let note_row_index = state.notes[note_index].row_index;
         let note_type = state.notes[note_index].note_type.clone();
         let note_time = state.note_time_cache[note_index];
-        let time_error = current_time - note_time;
+        let time_error = real_seconds(state, current_time - note_time);
         let abs_time_error = time_error.abs();
 
         if matches!(note_type, NoteType::Mine) {
@@ -922,25 +1759,7 @@ pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool
             return false;
         }
 
-        let fantastic_window = BASE_FANTASTIC_WINDOW + TIMING_WINDOW_ADD;
-        let excellent_window = BASE_EXCELLENT_WINDOW + TIMING_WINDOW_ADD;
-        let great_window = BASE_GREAT_WINDOW + TIMING_WINDOW_ADD;
-        let decent_window = BASE_DECENT_WINDOW + TIMING_WINDOW_ADD;
-        let way_off_window = BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD;
-
-        if abs_time_error <= way_off_window {
-            let grade = if abs_time_error <= fantastic_window {
-                JudgeGrade::Fantastic
-            } else if abs_time_error <= excellent_window {
-                JudgeGrade::Excellent
-            } else if abs_time_error <= great_window {
-                JudgeGrade::Great
-            } else if abs_time_error <= decent_window {
-                JudgeGrade::Decent
-            } else {
-                JudgeGrade::WayOff
-            };
-
+        if let Some(grade) = tap_grade(state, abs_time_error) {
             let judgment = Judgment {
                 time_error_ms: time_error * 1000.0,
                 grade,
@@ -948,6 +1767,13 @@ pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool
             };
 
             state.notes[note_index].result = Some(judgment);
+            state.events.publish(GameEvent::NoteJudged {
+                time: current_time,
+                row: note_row_index,
+                column,
+                grade,
+                offset_ms: Some(time_error * 1000.0),
+            });
             let note_type = state.notes[note_index].note_type.clone();
             let hold_end_time = state.hold_end_time_cache[note_index];
             info!(
@@ -959,8 +1785,8 @@ pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool
             );
 
             state.arrows[column].remove(arrow_list_index);
-            state.receptor_glow_timers[column] = RECEPTOR_GLOW_DURATION;
-            trigger_tap_explosion(state, column, grade);
+            state.receptor_glows[column] = Some(Anim::new(RECEPTOR_GLOW_DURATION, Ease::Accelerate));
+            trigger_tap_explosion(state, column, grade, is_white_fantastic(grade, time_error));
 
             if matches!(note_type, NoteType::Hold | NoteType::Roll) {
                 if let Some(end_time) = hold_end_time {
@@ -974,6 +1800,11 @@ pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool
                         let_go: false,
                         is_pressed: true,
                         life: MAX_HOLD_LIFE,
+                        combo_ticks: 0,
+                        explosion_elapsed: 0.0,
+                        pulse_beats: 0,
+                        checkpoints: 0,
+                        checkpoints_missed: 0,
                     });
                 }
             }
@@ -987,6 +1818,11 @@ pub fn judge_a_tap(state: &mut State, column: usize, current_time: f32) -> bool
 pub fn handle_key_press(state: &mut State, event: &KeyEvent, timestamp: Instant) -> ScreenAction {
     if let PhysicalKey::Code(key_code) = event.physical_key {
         if event.state == ElementState::Pressed && event.repeat {
+            // A key held in from the previous screen only shows up as repeats;
+            // let the dead zone see it so it stays ignored until released.
+            if let Some(lane) = lane_from_keycode(key_code).filter(|_| state.current_music_time < state.input_dead_zone_end) {
+                queue_input_edge(state, InputSource::Keyboard, lane, true, timestamp);
+            }
             return ScreenAction::None;
         }
 
@@ -997,6 +1833,15 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent, timestamp: Instant)
 
         match event.state {
             ElementState::Pressed => {
+                if key_code == KeyCode::F1 {
+                    let tick = crate::config::get().assist_tick.next();
+                    crate::config::update_assist_tick(tick);
+                    // The music is winding down after an Immediate fail; keep it quiet.
+                    if fail_sequence_time(state).is_none() {
+                        schedule_assist_ticks(state);
+                    }
+                    return ScreenAction::ShowMessage(format!("Assist Tick: {}", tick));
+                }
                 if key_code == KeyCode::Escape || key_code == KeyCode::Enter {
                     state.hold_to_exit_key = Some(key_code);
                     state.hold_to_exit_start = Some(timestamp);
@@ -1053,6 +1898,9 @@ fn finalize_row_judgment(state: &mut State, row_index: usize, judgments_in_row:
 
     // Increment counts ONCE per row (jumps/hands should not overcount).
     *state.judgment_counts.entry(final_grade).or_insert(0) += 1;
+    if is_white_fantastic(final_grade, final_judgment.time_error_ms / 1000.0) {
+        state.white_fantastic_count += 1;
+    }
     if !is_state_dead(state) {
         *state.scoring_counts.entry(final_grade).or_insert(0) += 1;
         update_itg_grade_totals(state);
@@ -1073,10 +1921,7 @@ fn finalize_row_judgment(state: &mut State, row_index: usize, judgments_in_row:
     };
     apply_life_change(state, life_delta);
 
-    state.last_judgment = Some(JudgmentRenderInfo {
-        judgment: final_judgment,
-        judged_at: Instant::now(),
-    });
+    state.events.publish(GameEvent::Judgment { judgment: final_judgment, judged_at: Instant::now() });
 
     if row_has_successful_hit {
         state.miss_combo = 0;
@@ -1092,24 +1937,7 @@ fn finalize_row_judgment(state: &mut State, row_index: usize, judgments_in_row:
         }
         state.full_combo_grade = None;
     } else {
-        state.combo += 1;
-
-        let combo = state.combo;
-        if combo > 0 && combo % 1000 == 0 {
-            trigger_combo_milestone(state, ComboMilestoneKind::Thousand);
-            trigger_combo_milestone(state, ComboMilestoneKind::Hundred);
-        } else if combo > 0 && combo % 100 == 0 {
-            trigger_combo_milestone(state, ComboMilestoneKind::Hundred);
-        }
-
-        if !state.first_fc_attempt_broken {
-            let new_grade = if let Some(current_fc_grade) = &state.full_combo_grade {
-                final_grade.max(*current_fc_grade)
-            } else {
-                final_grade
-            };
-            state.full_combo_grade = Some(new_grade);
-        }
+        add_combo(state, Some(final_grade));
     }
 
     let mut successful_steps: u32 = 0;
@@ -1182,27 +2010,71 @@ fn update_judged_rows(state: &mut State) {
 
 // get_music_end_time removed; use state.music_end_time directly
 
+/// Whether a column counts as pressed: down on either source and not held over from the dead zone.
+#[inline(always)]
+fn lane_down(state: &State, lane_idx: usize) -> bool {
+    (state.keyboard_lane_state[lane_idx] || state.gamepad_lane_state[lane_idx]) && !state.stale_lanes[lane_idx]
+}
+
 #[inline(always)]
 fn process_input_edges(state: &mut State, music_time_sec: f32, now: Instant) {
     while let Some(edge) = state.pending_edges.pop_front() {
-        let lane_idx = edge.lane.index();
-        let was_down = state.keyboard_lane_state[lane_idx] || state.gamepad_lane_state[lane_idx];
-
-        match edge.source {
-            InputSource::Keyboard => state.keyboard_lane_state[lane_idx] = edge.pressed,
-            InputSource::Gamepad => state.gamepad_lane_state[lane_idx] = edge.pressed,
+        // Panels this game type doesn't have are ignored.
+        let Some(lane_idx) = state.game_type.column_for(edge.lane) else { continue };
+        let was_down = lane_down(state, lane_idx);
+        let elapsed = now.saturating_duration_since(edge.timestamp).as_secs_f32();
+        let event_music_time = music_time_sec - elapsed * state.mods.rate;
+
+        let game_type = state.game_type;
+        let (lanes, columns) = match edge.source {
+            InputSource::Keyboard => (&mut state.keyboard_input_lanes, &mut state.keyboard_lane_state),
+            InputSource::Gamepad => (&mut state.gamepad_input_lanes, &mut state.gamepad_lane_state),
+        };
+        lanes[edge.lane as usize] = edge.pressed;
+        // Lanes sharing a column (three-panel corners) keep it down until the last lets go.
+        columns[lane_idx] = Lane::ALL
+            .iter()
+            .any(|&lane| lanes[lane as usize] && game_type.column_for(lane) == Some(lane_idx));
+        if edge.pressed && event_music_time < state.input_dead_zone_end {
+            state.stale_lanes[lane_idx] = true;
+        } else if !state.keyboard_lane_state[lane_idx] && !state.gamepad_lane_state[lane_idx] {
+            state.stale_lanes[lane_idx] = false;
         }
 
-        let is_down = state.keyboard_lane_state[lane_idx] || state.gamepad_lane_state[lane_idx];
+        let is_down = lane_down(state, lane_idx);
 
         if edge.pressed && is_down && !was_down {
-            let elapsed = now.saturating_duration_since(edge.timestamp).as_secs_f32();
-            let event_music_time = music_time_sec - elapsed;
             let hit_note = judge_a_tap(state, lane_idx, event_music_time);
             refresh_roll_life_on_step(state, lane_idx);
             if !hit_note {
-                state.receptor_bop_timers[lane_idx] = 0.11;
+                start_receptor_command(state, lane_idx, ReceptorEvent::Press);
             }
+        } else if !edge.pressed && was_down && !is_down {
+            start_receptor_command(state, lane_idx, ReceptorEvent::Lift);
+        }
+    }
+}
+
+/// Plays for the player: due steps are judged at their exact time, holds and
+/// rolls are held (rolls re-stepped) to their tails, and mines are left alone.
+fn autoplay(state: &mut State, music_time_sec: f32) {
+    for col in 0..state.arrows.len() {
+        while let Some(note_index) = state.arrows[col]
+            .iter()
+            .map(|arrow| arrow.note_index)
+            .find(|&i| state.notes[i].result.is_none())
+            .filter(|&i| !matches!(state.notes[i].note_type, NoteType::Mine))
+            .filter(|&i| state.note_time_cache[i] <= music_time_sec)
+        {
+            let note_time = state.note_time_cache[note_index];
+            if !judge_a_tap(state, col, note_time) {
+                break;
+            }
+        }
+        let holding = state.active_holds[col].as_ref().is_some_and(|active| !active.let_go);
+        state.keyboard_lane_state[col] = holding;
+        if holding {
+            refresh_roll_life_on_step(state, col);
         }
     }
 }
@@ -1223,59 +2095,55 @@ fn decay_let_go_hold_life(state: &mut State) {
         };
         if window <= 0.0 { hold.life = 0.0; continue; }
 
-        let elapsed = (state.current_music_time - start_time).max(0.0);
+        let elapsed = (state.current_music_time - start_time).max(0.0) / state.mods.rate;
         hold.life = (base_life - elapsed / window).max(0.0);
     }
 }
 
 #[inline(always)]
 fn tick_visual_effects(state: &mut State, delta_time: f32) {
-    for timer in &mut state.receptor_glow_timers {
-        *timer = (*timer - delta_time).max(0.0);
-    }
-    for timer in &mut state.receptor_bop_timers {
-        *timer = (*timer - delta_time).max(0.0);
-    }
+    anim::tick_slots(&mut state.receptor_glows, delta_time);
+    anim::tick_slots(&mut state.receptor_commands, delta_time);
+    anim::tick_slots(&mut state.tap_explosions, delta_time);
+    anim::tick_slots(&mut state.mine_explosions, delta_time);
+    anim::tick_slots(&mut state.hold_judgments, delta_time);
+    state
+        .combo_milestones
+        .retain_mut(|milestone| milestone.tick(delta_time));
+}
 
-    state.combo_milestones.retain_mut(|milestone| {
-        milestone.elapsed += delta_time;
-        let max_duration = match milestone.kind {
-            ComboMilestoneKind::Hundred => COMBO_HUNDRED_MILESTONE_DURATION,
-            ComboMilestoneKind::Thousand => COMBO_THOUSAND_MILESTONE_DURATION,
-        };
-        milestone.elapsed < max_duration
-    });
+/// Frame time auto quality holds gameplay to: the display's refresh rate (60 Hz
+/// when unknown), or the frame cap when that's lower, plus some slack.
+pub fn frame_budget_seconds(refresh_hz: Option<u32>) -> f32 {
+    let max_fps = crate::config::get().max_fps;
+    let target = refresh_hz.filter(|&hz| hz > 0).unwrap_or(DEFAULT_TARGET_FPS);
+    let target = if max_fps > 0 { target.min(max_fps) } else { target };
+    FRAME_BUDGET_SLACK / target as f32
+}
 
-    for explosion in &mut state.tap_explosions {
-        if let Some(active) = explosion {
-            active.elapsed += delta_time;
-            let lifetime = state
-                .noteskin
-                .as_ref()
-                .and_then(|ns| ns.tap_explosions.get(&active.window))
-                .map(|explosion| explosion.animation.duration())
-                .unwrap_or(0.0);
-            if lifetime <= 0.0 || active.elapsed >= lifetime {
-                *explosion = None;
-            }
-        }
+/// Steps `state.quality` down once frames have run over budget for a few
+/// seconds in total, net of the frames that made it in time.
+fn update_auto_quality(state: &mut State, delta_time: f32) {
+    if state.quality_notice.as_mut().is_some_and(|notice| !notice.tick(delta_time)) {
+        state.quality_notice = None;
     }
-
-    for explosion in &mut state.mine_explosions {
-        if let Some(active) = explosion {
-            active.elapsed += delta_time;
-            if active.elapsed >= MINE_EXPLOSION_DURATION {
-                *explosion = None;
-            }
-        }
+    // Hitches while the song is still lining up say nothing about draw cost.
+    if !state.auto_quality || state.current_music_time < 0.0 {
+        return;
     }
-
-    for slot in &mut state.hold_judgments {
-        if let Some(render_info) = slot {
-            if render_info.triggered_at.elapsed().as_secs_f32() >= HOLD_JUDGMENT_TOTAL_DURATION {
-                *slot = None;
-            }
-        }
+    state.slow_frame_seconds = if delta_time > state.frame_budget_seconds {
+        state.slow_frame_seconds + delta_time.min(MAX_SLOW_FRAME_SECONDS)
+    } else {
+        (state.slow_frame_seconds - delta_time).max(0.0)
+    };
+    if state.slow_frame_seconds < DOWNSHIFT_AFTER_SECONDS {
+        return;
+    }
+    state.slow_frame_seconds = 0.0;
+    if let Some(next) = state.quality.next() {
+        info!("Frames running over budget; lowering gameplay quality to {:?}.", next);
+        state.quality = next;
+        state.quality_notice = Some(Anim::new(QUALITY_NOTICE_DURATION, Ease::Linear));
     }
 }
 
@@ -1299,7 +2167,8 @@ fn spawn_lookahead_arrows(state: &mut State, music_time_sec: f32) {
 
 #[inline(always)]
 fn apply_passive_misses_and_mine_avoidance(state: &mut State, music_time_sec: f32) {
-    let way_off_window = BASE_WAY_OFF_WINDOW + TIMING_WINDOW_ADD;
+    let miss_window = outer_hit_window(state);
+    let rate = state.mods.rate;
     for (col_idx, col_arrows) in state.arrows.iter_mut().enumerate() {
         let Some(next_arrow_index) = col_arrows
             .iter()
@@ -1313,6 +2182,7 @@ fn apply_passive_misses_and_mine_avoidance(state: &mut State, music_time_sec: f3
             (note.row_index, note.note_type.clone())
         };
         let note_time = state.note_time_cache[note_index];
+        let late_by = (music_time_sec - note_time) / rate;
 
         if matches!(note_type, NoteType::Mine) {
             match state.notes[note_index].mine_result {
@@ -1320,7 +2190,7 @@ fn apply_passive_misses_and_mine_avoidance(state: &mut State, music_time_sec: f3
                 Some(MineResult::Avoided) => {}
                 None => {
                     let mine_window = BASE_MINE_WINDOW + TIMING_WINDOW_ADD;
-                    if music_time_sec - note_time > mine_window {
+                    if late_by > mine_window {
                         state.notes[note_index].mine_result = Some(MineResult::Avoided);
                         state.mines_avoided = state.mines_avoided.saturating_add(1);
                         info!(
@@ -1333,9 +2203,9 @@ fn apply_passive_misses_and_mine_avoidance(state: &mut State, music_time_sec: f3
             continue;
         }
 
-        if music_time_sec - note_time > way_off_window {
+        if late_by > miss_window {
             let judgment = Judgment {
-                time_error_ms: ((music_time_sec - note_time) * 1000.0),
+                time_error_ms: late_by * 1000.0,
                 grade: JudgeGrade::Miss,
                 row: note_row_index,
             };
@@ -1351,6 +2221,13 @@ fn apply_passive_misses_and_mine_avoidance(state: &mut State, music_time_sec: f3
             }
 
             state.notes[note_index].result = Some(judgment);
+            state.events.publish(GameEvent::NoteJudged {
+                time: music_time_sec,
+                row: note_row_index,
+                column: col_idx,
+                grade: JudgeGrade::Miss,
+                offset_ms: None,
+            });
             info!(
                 "MISSED (pending): Row {}, Col {}",
                 note_row_index, col_idx
@@ -1363,10 +2240,11 @@ fn apply_passive_misses_and_mine_avoidance(state: &mut State, music_time_sec: f3
 fn cull_scrolled_out_arrows(state: &mut State, music_time_sec: f32) {
     let receptor_y = screen_center_y() + RECEPTOR_Y_OFFSET_FROM_CENTER;
     let miss_cull_threshold = receptor_y - state.draw_distance_after_targets;
+    let arrow_spacing = ScrollSpeedSetting::ARROW_SPACING * state.mods.field_zoom();
 
     let (cmod_pps_opt, curr_disp_beat, beatmod_multiplier) = match state.scroll_speed {
         ScrollSpeedSetting::CMod(c_bpm) => {
-            let pps = (c_bpm / 60.0) * ScrollSpeedSetting::ARROW_SPACING;
+            let pps = (c_bpm / 60.0) * arrow_spacing;
             (Some(pps), 0.0, 0.0)
         }
         ScrollSpeedSetting::XMod(_) | ScrollSpeedSetting::MMod(_) => {
@@ -1405,10 +2283,7 @@ fn cull_scrolled_out_arrows(state: &mut State, music_time_sec: f32) {
                 ScrollSpeedSetting::XMod(_) | ScrollSpeedSetting::MMod(_) => {
                     let note_disp_beat = state.note_display_beat_cache[arrow.note_index];
                     let beat_diff_disp = note_disp_beat - curr_disp_beat;
-                    receptor_y
-                        + beat_diff_disp
-                            * ScrollSpeedSetting::ARROW_SPACING
-                            * beatmod_multiplier
+                    receptor_y + beat_diff_disp * arrow_spacing * beatmod_multiplier
                 }
             };
 
@@ -1418,10 +2293,18 @@ fn cull_scrolled_out_arrows(state: &mut State, music_time_sec: f32) {
 }
 
 pub fn update(state: &mut State, delta_time: f32) -> ScreenAction {
+    let action = update_state(state, delta_time);
+    // Also after an early return, so nothing published this frame is held over.
+    events::dispatch(state);
+    action
+}
+
+fn update_state(state: &mut State, delta_time: f32) -> ScreenAction {
     if let (Some(key), Some(start_time)) = (state.hold_to_exit_key, state.hold_to_exit_start) {
         if start_time.elapsed() >= std::time::Duration::from_secs(1) {
             state.hold_to_exit_key = None;
             state.hold_to_exit_start = None;
+            publish_song_ended(state, false);
             return match key {
                 winit::keyboard::KeyCode::Enter => ScreenAction::Navigate(Screen::Evaluation),
                 winit::keyboard::KeyCode::Escape => ScreenAction::Navigate(Screen::SelectMusic),
@@ -1430,7 +2313,15 @@ pub fn update(state: &mut State, delta_time: f32) -> ScreenAction {
         }
     }
 
-    state.total_elapsed_in_screen += delta_time;
+    // Immediate fail: everything holds where it died while the sequence plays.
+    if fail_sequence_time(state).is_some() {
+        state.fail_elapsed += delta_time;
+        if state.fail_elapsed >= FAIL_SEQUENCE_DURATION {
+            publish_song_ended(state, false);
+            return ScreenAction::Navigate(Screen::Evaluation);
+        }
+        return ScreenAction::None;
+    }
 
     let now = std::time::Instant::now();
     let music_time_sec = if now < state.song_start_instant {
@@ -1441,8 +2332,13 @@ pub fn update(state: &mut State, delta_time: f32) -> ScreenAction {
     } else {
         now.saturating_duration_since(state.song_start_instant)
             .as_secs_f32()
-    };
+    } * state.mods.rate;
+    let previous_second = state.current_music_time.floor() as i32;
     state.current_music_time = music_time_sec;
+    let current_second = music_time_sec.floor() as i32;
+    if current_second > previous_second {
+        state.events.publish(GameEvent::SongSecond(current_second));
+    }
 	let beat_info = state.timing.get_beat_info_from_time(music_time_sec);
 	state.current_beat = beat_info.beat;
 	state.is_in_freeze = beat_info.is_in_freeze;
@@ -1462,30 +2358,30 @@ pub fn update(state: &mut State, delta_time: f32) -> ScreenAction {
     let draw_distance_before_targets = screen_height() * DRAW_DISTANCE_BEFORE_TARGETS_MULTIPLIER;
     state.draw_distance_before_targets = draw_distance_before_targets;
     state.draw_distance_after_targets = DRAW_DISTANCE_AFTER_TARGETS;
+    let unzoomed_draw_distance = draw_distance_before_targets / state.mods.field_zoom();
     let mut travel_time = state.scroll_speed.travel_time_seconds(
-        draw_distance_before_targets,
+        unzoomed_draw_distance,
         current_bpm,
         state.scroll_reference_bpm,
     );
     if !travel_time.is_finite() || travel_time <= 0.0 {
-        travel_time = draw_distance_before_targets / dynamic_speed;
+        travel_time = unzoomed_draw_distance / dynamic_speed;
     }
     state.scroll_travel_time = travel_time;
 
     if state.current_music_time >= state.music_end_time {
         info!("Music end time reached. Transitioning to evaluation.");
         state.song_completed_naturally = true;
+        publish_song_ended(state, true);
         return ScreenAction::Navigate(Screen::Evaluation);
     }
 
     process_input_edges(state, music_time_sec, now);
+    if state.autoplay {
+        autoplay(state, music_time_sec);
+    }
 
-    let current_inputs = [
-        state.keyboard_lane_state[0] || state.gamepad_lane_state[0],
-        state.keyboard_lane_state[1] || state.gamepad_lane_state[1],
-        state.keyboard_lane_state[2] || state.gamepad_lane_state[2],
-        state.keyboard_lane_state[3] || state.gamepad_lane_state[3],
-    ];
+    let current_inputs: [bool; MAX_COLS] = std::array::from_fn(|col| lane_down(state, col));
     let prev_inputs = state.prev_inputs;
 
     for (col, (now_down, was_down)) in current_inputs.iter().copied().zip(prev_inputs).enumerate() {
@@ -1499,7 +2395,9 @@ pub fn update(state: &mut State, delta_time: f32) -> ScreenAction {
     update_active_holds(state, &current_inputs, music_time_sec, delta_time);
     decay_let_go_hold_life(state);
 
-    tick_visual_effects(state, delta_time);
+    let effect_delta = state.visual_clock.advance(state.current_beat, delta_time);
+    tick_visual_effects(state, effect_delta);
+    update_auto_quality(state, delta_time);
 
     spawn_lookahead_arrows(state, music_time_sec);
 
@@ -0,0 +1,182 @@
+//! Installs songs dropped onto the window.
+//! - Accepts a song folder, a pack folder, a simfile (its folder is installed) or a ZIP of any of those
+//! - Copies or extracts into `songs/`, then loads only that pack; anything already under `songs/` is loaded in place
+//! - Runs on a background thread; the app collects results with `take_finished` and adds the pack to the song cache
+
+use crate::game::parsing::simfile::{self, ScannedPack};
+use crate::game::parsing::zip;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+const SONGS_DIR: &str = "songs";
+// Pack that loose song folders are installed into.
+const LOOSE_SONGS_PACK: &str = "Installed";
+// ZIPs are unpacked here first, since their layout is only known afterwards.
+const STAGING_DIR: &str = "cache/install";
+
+#[derive(Debug, Clone)]
+pub struct Installed {
+    /// The pack the songs went into, for `simfile::add_scanned_pack`.
+    pub pack: ScannedPack,
+    /// A newly installed song folder to show on the wheel.
+    pub song_dir: Option<PathBuf>,
+}
+
+static FINISHED: Lazy<Mutex<Vec<Result<Installed, String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Installs `path` in the background.
+pub fn start(path: PathBuf) {
+    thread::spawn(move || {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        info!("Installing '{}'...", path.display());
+        let result = install(&path).map_err(|e| format!("Couldn't install {}: {}", name, e));
+        match &result {
+            Ok(installed) => info!("Installed {} song(s) into pack '{}'.", installed.pack.song_count(), installed.pack.name()),
+            Err(e) => warn!("{}", e),
+        }
+        FINISHED.lock().unwrap().push(result);
+    });
+}
+
+/// Installs that finished since the last call.
+pub fn take_finished() -> Vec<Result<Installed, String>> {
+    std::mem::take(&mut *FINISHED.lock().unwrap())
+}
+
+fn has_extension(path: &Path, exts: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| exts.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+fn install(path: &Path) -> Result<Installed, String> {
+    if path.is_dir() {
+        return install_folder(path);
+    }
+    if has_extension(path, &["sm", "ssc"]) {
+        return install_folder(path.parent().ok_or("simfile has no folder")?);
+    }
+    if has_extension(path, &["zip"]) {
+        let stem = path.file_stem().ok_or("ZIP has no name")?;
+        let staging = Path::new(STAGING_DIR).join(stem);
+        let _ = fs::remove_dir_all(&staging);
+        zip::extract(path, &staging)?;
+        let result = install_folder(&staging);
+        let _ = fs::remove_dir_all(&staging);
+        return result;
+    }
+    Err("only folders, .zip files and simfiles can be installed".into())
+}
+
+fn has_simfile(dir: &Path) -> bool {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|entry| has_extension(&entry.path(), &["sm", "ssc"]))
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        // Finder adds this to ZIPs made on macOS.
+        .filter(|p| p.is_dir() && p.file_name().is_some_and(|n| n != "__MACOSX"))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+enum Layout {
+    Song,
+    Pack,
+}
+
+/// Finds the song or pack folder, looking through wrapper folders that hold
+/// nothing but a single subfolder (common in downloads).
+fn classify(dir: &Path) -> Result<(PathBuf, Layout), String> {
+    let mut dir = dir.to_path_buf();
+    loop {
+        if has_simfile(&dir) {
+            return Ok((dir, Layout::Song));
+        }
+        let children = subdirs(&dir);
+        if children.iter().any(|d| has_simfile(d)) {
+            return Ok((dir, Layout::Pack));
+        }
+        match children.as_slice() {
+            [only] => dir = only.clone(),
+            _ => return Err("no .sm or .ssc files found".into()),
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Where `root` already sits inside `songs/`, as (pack folder, song folder).
+fn installed_in_place(root: &Path, layout: &Layout) -> Option<(PathBuf, Option<PathBuf>)> {
+    let songs_dir = fs::canonicalize(SONGS_DIR).ok()?;
+    let root = fs::canonicalize(root).ok()?;
+    // Kept relative to `songs/`, like the paths a full scan produces.
+    let dir = Path::new(SONGS_DIR).join(root.strip_prefix(&songs_dir).ok()?);
+    match (layout, dir.components().count()) {
+        (Layout::Pack, 2) => Some((dir, None)),
+        (Layout::Song, 3) => Some((dir.parent()?.to_path_buf(), Some(dir))),
+        _ => None,
+    }
+}
+
+fn install_folder(path: &Path) -> Result<Installed, String> {
+    let (root, layout) = classify(path)?;
+    // Dropping something that is already in place (say, a simfile from inside
+    // `songs/`) only rescans its pack.
+    if let Some((pack_dir, song_dir)) = installed_in_place(&root, &layout) {
+        return scanned(&pack_dir, song_dir);
+    }
+    let name = root.file_name().ok_or("folder has no name")?.to_owned();
+    let songs_dir = Path::new(SONGS_DIR);
+
+    let (pack_dir, song_dir) = match layout {
+        Layout::Pack => {
+            let pack_dir = songs_dir.join(&name);
+            let first_song = subdirs(&root).into_iter().find(|d| has_simfile(d));
+            let song_dir = first_song.and_then(|d| d.file_name().map(|n| pack_dir.join(n)));
+            (pack_dir, song_dir)
+        }
+        Layout::Song => {
+            let pack_dir = songs_dir.join(LOOSE_SONGS_PACK);
+            let song_dir = pack_dir.join(&name);
+            (pack_dir, Some(song_dir))
+        }
+    };
+    let target = match layout {
+        Layout::Pack => pack_dir.clone(),
+        Layout::Song => song_dir.clone().unwrap_or_else(|| pack_dir.clone()),
+    };
+
+    copy_dir(&root, &target).map_err(|e| e.to_string())?;
+    scanned(&pack_dir, song_dir)
+}
+
+fn scanned(pack_dir: &Path, song_dir: Option<PathBuf>) -> Result<Installed, String> {
+    let pack = simfile::scan_pack(pack_dir).ok_or("none of its songs could be loaded")?;
+    Ok(Installed { pack, song_dir })
+}
@@ -1,6 +1,40 @@
 use std::collections::HashMap;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Score percents are kept in hundredths of a percent: 10000 is 100.00%.
+pub const SCORE_SCALE: i64 = 10000;
+
+/// How a score between two hundredths is settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreRounding {
+    /// ITG: drop anything past 0.01%, so 99.996% is 99.99% and only a full
+    /// score is 100.00%.
+    #[default]
+    Truncate,
+    /// Nearest hundredth, halves up.
+    Round,
+}
+
+impl core::fmt::Display for ScoreRounding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncate => write!(f, "Truncate"),
+            Self::Round => write!(f, "Round"),
+        }
+    }
+}
+
+impl std::str::FromStr for ScoreRounding {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "truncate" => Ok(Self::Truncate),
+            "round" => Ok(Self::Round),
+            _ => Err(format!("'{}' is not a valid score rounding", s)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
 pub enum JudgeGrade {
     Fantastic, // W1
     Excellent, // W2
@@ -66,5 +100,39 @@ pub fn calculate_itg_score_percent(
         mines_hit_for_score,
     );
 
-    (total_points as f64 / possible_grade_points as f64).max(0.0)
+    score_percent_from_points(total_points, possible_grade_points)
+}
+
+/// `points / possible` in hundredths of a percent, never below zero. Integer
+/// math, so the same points always land on the same hundredth.
+pub fn score_hundredths(points: i32, possible: i32, rounding: ScoreRounding) -> u32 {
+    if possible <= 0 || points <= 0 {
+        return 0;
+    }
+    let (points, possible) = (points as i64, possible as i64);
+    let hundredths = match rounding {
+        ScoreRounding::Truncate => points * SCORE_SCALE / possible,
+        ScoreRounding::Round => (points * SCORE_SCALE * 2 + possible) / (possible * 2),
+    };
+    hundredths as u32
+}
+
+/// The score percent (0.0 to 1.0) for `points` out of `possible`, settled to a
+/// hundredth of a percent under the configured `ScoreRounding`. Everything that
+/// shows, compares or stores a score goes through here or `quantize_score_percent`.
+pub fn score_percent_from_points(points: i32, possible: i32) -> f64 {
+    let rounding = crate::config::get().score_rounding;
+    score_hundredths(points, possible, rounding) as f64 / SCORE_SCALE as f64
+}
+
+/// Settles an already-computed percent (0.0 to 1.0), such as one read back from
+/// disk, to a hundredth under the configured `ScoreRounding`.
+pub fn quantize_score_percent(percent: f64) -> f64 {
+    let scaled = (percent * SCORE_SCALE as f64).max(0.0);
+    // Values written as decimals come back a hair under their hundredth.
+    let hundredths = match crate::config::get().score_rounding {
+        ScoreRounding::Truncate => (scaled + 1e-6).floor(),
+        ScoreRounding::Round => scaled.round(),
+    };
+    hundredths / SCORE_SCALE as f64
 }
@@ -1,11 +1,19 @@
+pub mod backup;
 pub mod chart;
+pub mod events;
+pub mod game_type;
 pub mod gameplay;
+pub mod install;
 pub mod judgment;
 pub mod life;
+pub mod mods;
 pub mod note;
+pub mod offsets;
 pub mod parsing;
 pub mod profile;
+pub mod replay;
 pub mod scores;
 pub mod scroll;
 pub mod song;
+pub mod sync;
 pub mod timing;
@@ -0,0 +1,120 @@
+use rand::seq::SliceRandom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Music rates offered on the options screen, in steps of 0.05.
+pub const MIN_RATE: f32 = 0.5;
+pub const MAX_RATE: f32 = 2.0;
+pub const RATE_STEP: f32 = 0.05;
+/// Mini steps of 5%, from 0% (full size) to 100% (half size).
+pub const MINI_STEP: f32 = 0.05;
+
+/// Rearranges the columns of a chart. Left and Right turn a four-panel pad;
+/// other layouts have no quarter turn and keep their columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Turn {
+    #[default]
+    None,
+    Mirror,
+    Left,
+    Right,
+    Shuffle,
+}
+
+impl Turn {
+    pub const ALL: [Turn; 5] = [Turn::None, Turn::Mirror, Turn::Left, Turn::Right, Turn::Shuffle];
+
+    /// Where each of `num_cols` chart columns ends up (for four, Left, Down, Up,
+    /// Right). Shuffle draws a fresh arrangement every call, never the unshuffled one.
+    pub fn column_map(self, num_cols: usize) -> Vec<usize> {
+        let identity: Vec<usize> = (0..num_cols).collect();
+        match self {
+            Turn::None => identity,
+            Turn::Mirror => identity.into_iter().rev().collect(),
+            // The pad turned a quarter to the left: Left steps become Down, and so on.
+            Turn::Left if num_cols == 4 => vec![1, 3, 0, 2],
+            Turn::Right if num_cols == 4 => vec![2, 0, 3, 1],
+            Turn::Left | Turn::Right => identity,
+            Turn::Shuffle => {
+                let mut map = identity.clone();
+                let mut rng = rand::rng();
+                while num_cols > 1 && map == identity {
+                    map.shuffle(&mut rng);
+                }
+                map
+            }
+        }
+    }
+}
+
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Turn::None => write!(f, "None"),
+            Turn::Mirror => write!(f, "Mirror"),
+            Turn::Left => write!(f, "Left"),
+            Turn::Right => write!(f, "Right"),
+            Turn::Shuffle => write!(f, "Shuffle"),
+        }
+    }
+}
+
+impl FromStr for Turn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "off" => Ok(Turn::None),
+            "mirror" => Ok(Turn::Mirror),
+            "left" => Ok(Turn::Left),
+            "right" => Ok(Turn::Right),
+            "shuffle" => Ok(Turn::Shuffle),
+            other => Err(format!("'{}' is not a valid Turn setting", other)),
+        }
+    }
+}
+
+/// The modifiers a chart can carry as a sticky preset: music rate, mini and turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartMods {
+    pub rate: f32,
+    /// 0.0 (full size) to 1.0 (half size).
+    pub mini: f32,
+    pub turn: Turn,
+}
+
+impl Default for ChartMods {
+    fn default() -> Self {
+        Self { rate: 1.0, mini: 0.0, turn: Turn::None }
+    }
+}
+
+impl ChartMods {
+    /// Keeps values inside what the options screen can show.
+    pub fn clamped(self) -> Self {
+        let rate = if self.rate.is_finite() { self.rate } else { 1.0 };
+        let mini = if self.mini.is_finite() { self.mini } else { 0.0 };
+        Self {
+            rate: ((rate / RATE_STEP).round() * RATE_STEP).clamp(MIN_RATE, MAX_RATE),
+            mini: ((mini / MINI_STEP).round() * MINI_STEP).clamp(0.0, 1.0),
+            turn: self.turn,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        (self.rate - 1.0).abs() < 0.001 && self.mini.abs() < 0.001 && self.turn == Turn::None
+    }
+
+    /// Notefield zoom for the mini setting; 100% mini draws at half size.
+    pub fn field_zoom(&self) -> f32 {
+        1.0 - self.mini * 0.5
+    }
+
+    pub fn rate_text(&self) -> String {
+        format!("{:.2}x", self.rate)
+    }
+
+    pub fn mini_text(&self) -> String {
+        format!("{}%", (self.mini * 100.0).round() as i32)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::game::song::{get_song_cache, SongData};
+use log::{info, warn};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+// User sync corrections, kept apart from the simfiles so packs stay untouched
+// and the file can be passed around between players.
+const OFFSETS_JSON_PATH: &str = "save/offsets.json";
+
+/// Layout of `offsets.json`. Values are seconds added to the simfile's #OFFSET;
+/// a pack entry applies to every chart in that pack folder, and a chart entry
+/// (keyed by chart hash) stacks on top of it.
+///
+/// ```json
+/// { "packs": { "Some Pack": -0.009 }, "charts": { "0123abcd4567ef89": 0.004 } }
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct OffsetsFile {
+    packs: HashMap<String, f32>,
+    charts: HashMap<String, f32>,
+}
+
+/// Reads the file fresh each time so edits apply on the next song without a restart.
+fn load_file() -> Option<OffsetsFile> {
+    let path = Path::new(OFFSETS_JSON_PATH);
+    if !path.exists() {
+        return None;
+    }
+    let text = match fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) => {
+            warn!("Failed to read '{}': {}", OFFSETS_JSON_PATH, e);
+            return None;
+        }
+    };
+    match serde_json::from_str(&text) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            warn!("Ignoring malformed '{}': {}", OFFSETS_JSON_PATH, e);
+            None
+        }
+    }
+}
+
+/// Name of the pack folder the song was loaded from, if it is still in the cache.
+fn pack_name_of(song: &Arc<SongData>) -> Option<String> {
+    get_song_cache()
+        .iter()
+        .find(|pack| pack.songs.iter().any(|s| Arc::ptr_eq(s, song)))
+        .map(|pack| pack.name.clone())
+}
+
+/// Total correction in seconds for a chart: its pack's entry plus its own.
+pub fn offset_for(song: &Arc<SongData>, chart_hash: &str) -> f32 {
+    let Some(file) = load_file() else { return 0.0; };
+
+    let pack = pack_name_of(song)
+        .and_then(|name| file.packs.get(&name).copied())
+        .unwrap_or(0.0);
+    let chart = file
+        .charts
+        .iter()
+        .find(|(hash, _)| hash.eq_ignore_ascii_case(chart_hash))
+        .map_or(0.0, |(_, v)| *v);
+
+    let total = pack + chart;
+    if total != 0.0 {
+        info!(
+            "Applying custom offset {:+.3}s (pack {:+.3}s, chart {:+.3}s) to chart {}",
+            total, pack, chart, chart_hash
+        );
+    }
+    if total.is_finite() { total } else { 0.0 }
+}
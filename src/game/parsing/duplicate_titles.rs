@@ -0,0 +1,150 @@
+use crate::game::song::{Disambiguator, SongData, SongPack};
+use log::info;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+// Songs whose titles are the same, or one edit apart once case, spacing and
+// punctuation are ignored ("PARANOiA" / "Paranoia", "MAX 300" / "MAX300"), get
+// a disambiguator so the wheel and gameplay can tell them apart. This runs over
+// the whole song list after every scan, since a new pack can collide with any
+// pack already loaded.
+
+// Shorter titles only collide when identical; one edit is too loose for them.
+const FUZZY_MIN_LEN: usize = 6;
+
+/// Title folded for comparison: transliterated, lowercase, letters and digits only.
+fn normalize(song: &SongData) -> Vec<char> {
+    song.display_full_title(true)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Levenshtein distance of at most one, without building the full table.
+fn within_one_edit(a: &[char], b: &[char]) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let prefix = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+    if prefix == short.len() {
+        return true;
+    }
+    if short.len() == long.len() {
+        short[prefix + 1..] == long[prefix + 1..]
+    } else {
+        short[prefix..] == long[prefix + 1..]
+    }
+}
+
+fn titles_collide(a: &[char], b: &[char]) -> bool {
+    a == b || (a.len().min(b.len()) >= FUZZY_MIN_LEN && within_one_edit(a, b))
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+fn artist_key(song: &SongData) -> String {
+    song.display_artist(true).trim().to_lowercase()
+}
+
+/// The folder the song's simfile sits in, e.g. "MAX 300" for ".../Pack/MAX 300/max.sm".
+fn folder_name(song: &SongData) -> String {
+    song.simfile_path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+/// How every song in `group` (all sharing a title) should be told apart: the
+/// artist when that differs for each, else the pack, else both, else the pack
+/// and song folder (which no two songs share).
+fn pick_disambiguator(group: &[(usize, usize)], packs: &[SongPack]) -> fn(&SongPack, &SongData) -> Disambiguator {
+    let all_distinct = |key: &dyn Fn(&SongPack, &SongData) -> String| {
+        let mut seen = HashSet::new();
+        group.iter().all(|&(p, s)| seen.insert(key(&packs[p], &packs[p].songs[s])))
+    };
+    if all_distinct(&|_, song| artist_key(song)) {
+        |_, _| Disambiguator::Artist
+    } else if all_distinct(&|pack, _| pack.display_name.to_lowercase()) {
+        |pack, _| Disambiguator::Pack(pack.display_name.clone())
+    } else if all_distinct(&|pack, song| format!("{}\n{}", artist_key(song), pack.display_name.to_lowercase())) {
+        |pack, _| Disambiguator::ArtistAndPack(pack.display_name.clone())
+    } else {
+        |pack, song| Disambiguator::Folder(format!("{}/{}", pack.display_name, folder_name(song)))
+    }
+}
+
+/// Marks every song whose title collides with another's, and unmarks songs that
+/// no longer collide (a pack was replaced).
+pub fn disambiguate(packs: &mut [SongPack]) {
+    let songs: Vec<(usize, usize)> = packs
+        .iter()
+        .enumerate()
+        .flat_map(|(p, pack)| (0..pack.songs.len()).map(move |s| (p, s)))
+        .collect();
+    let titles: Vec<Vec<char>> = songs.iter().map(|&(p, s)| normalize(&packs[p].songs[s])).collect();
+
+    // Two titles within one edit share either the title itself or a copy with
+    // one character deleted, so only songs sharing one of those get compared.
+    let mut buckets: HashMap<Vec<char>, Vec<usize>> = HashMap::new();
+    for (i, title) in titles.iter().enumerate() {
+        if title.is_empty() {
+            continue;
+        }
+        buckets.entry(title.clone()).or_default().push(i);
+        if title.len() >= FUZZY_MIN_LEN {
+            for skip in 0..title.len() {
+                let mut variant = title.clone();
+                variant.remove(skip);
+                buckets.entry(variant).or_default().push(i);
+            }
+        }
+    }
+
+    let mut parents: Vec<usize> = (0..songs.len()).collect();
+    for bucket in buckets.values().filter(|b| b.len() > 1) {
+        for (n, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[n + 1..] {
+                if a != b && titles_collide(&titles[a], &titles[b]) {
+                    let (root_a, root_b) = (find(&mut parents, a), find(&mut parents, b));
+                    parents[root_a] = root_b;
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for (i, &song) in songs.iter().enumerate() {
+        groups.entry(find(&mut parents, i)).or_default().push(song);
+    }
+
+    let mut wanted: HashMap<(usize, usize), Disambiguator> = HashMap::new();
+    for group in groups.values().filter(|g| g.len() > 1) {
+        let make = pick_disambiguator(group, packs);
+        for &(p, s) in group {
+            wanted.insert((p, s), make(&packs[p], &packs[p].songs[s]));
+        }
+    }
+
+    let mut marked = 0;
+    for (p, pack) in packs.iter_mut().enumerate() {
+        for (s, song) in pack.songs.iter_mut().enumerate() {
+            let disambiguator = wanted.remove(&(p, s));
+            marked += usize::from(disambiguator.is_some());
+            // Only touch songs that change; others may be shared with the wheel.
+            if song.disambiguator != disambiguator {
+                Arc::make_mut(song).disambiguator = disambiguator;
+            }
+        }
+    }
+    if marked > 0 {
+        info!("Added disambiguators to {} songs sharing a title with another.", marked);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod duplicate_titles;
 pub mod notes;
+pub mod pack_ini;
 pub mod noteskin;
-pub mod simfile;
\ No newline at end of file
+pub mod simfile;
+pub mod zip;
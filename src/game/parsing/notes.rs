@@ -13,11 +13,11 @@ pub struct ParsedNote {
 ///
 /// This function converts the byte representation of notes into a vector of
 /// `(row_index, column, NoteType)`, leaving the conversion from row to beat
-/// up to the `TimingData` module.
-pub fn parse_chart_notes(raw_note_bytes: &[u8]) -> Vec<ParsedNote> {
+/// up to the `TimingData` module. Rows are `num_cols` wide (the game type's).
+pub fn parse_chart_notes(raw_note_bytes: &[u8], num_cols: usize) -> Vec<ParsedNote> {
     let mut notes = Vec::new();
     let mut row_index = 0;
-    let mut hold_heads: [Option<usize>; 4] = [None; 4];
+    let mut hold_heads: Vec<Option<usize>> = vec![None; num_cols];
 
     // Split by lines, also handling potential commas on their own lines
     for line in raw_note_bytes.split(|&b| b == b'\n') {
@@ -26,8 +26,8 @@ pub fn parse_chart_notes(raw_note_bytes: &[u8]) -> Vec<ParsedNote> {
             continue;
         }
 
-        if trimmed_line.len() >= 4 {
-            for (col_index, &ch) in trimmed_line.iter().take(4).enumerate() {
+        if trimmed_line.len() >= num_cols {
+            for (col_index, &ch) in trimmed_line.iter().take(num_cols).enumerate() {
                 match ch {
                     b'1' => {
                         notes.push(ParsedNote {
@@ -1,4 +1,5 @@
 use crate::assets;
+use crate::core::gfx::Material;
 use image::image_dimensions;
 use log::{info, warn};
 use std::collections::HashMap;
@@ -46,12 +47,15 @@ pub struct SpriteDefinition {
     pub rotation_deg: i32,
     pub mirror_h: bool,
     pub mirror_v: bool,
+    pub material: Material,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum AnimationRate {
     FramesPerSecond(f32),
     FramesPerBeat(f32),
+    /// Each frame for its own time in seconds, as stored in an animated GIF/APNG.
+    FrameDelays(Arc<[f32]>),
 }
 
 #[derive(Debug)]
@@ -134,6 +138,9 @@ impl SpriteSlot {
             SpriteSource::Atlas { .. } => 0,
             SpriteSource::Animated { rate, .. } => {
                 let frame = match rate {
+                    AnimationRate::FrameDelays(delays) => {
+                        return assets::frame_for_delays(delays, time) % frames;
+                    }
                     AnimationRate::FramesPerSecond(fps) if *fps > 0.0 => {
                         (time * fps).floor() as isize
                     }
@@ -147,6 +154,13 @@ impl SpriteSlot {
         }
     }
 
+    // `mirror` in the noteskin reverses the rect, which flips the sampled image.
+    fn mirrored(&self, [u0, v0, u1, v1]: [f32; 4]) -> [f32; 4] {
+        let (u0, u1) = if self.def.mirror_h { (u1, u0) } else { (u0, u1) };
+        let (v0, v1) = if self.def.mirror_v { (v1, v0) } else { (v0, v1) };
+        [u0, v0, u1, v1]
+    }
+
     pub fn uv_for_frame(&self, frame_index: usize) -> [f32; 4] {
         match self.source.as_ref() {
             SpriteSource::Atlas { tex_dims, .. } => {
@@ -169,7 +183,7 @@ impl SpriteSlot {
                     v1 -= 0.5;
                 }
 
-                [u0 / tw, v0 / th, u1 / tw, v1 / th]
+                self.mirrored([u0 / tw, v0 / th, u1 / tw, v1 / th])
             }
             SpriteSource::Animated {
                 tex_dims,
@@ -202,7 +216,7 @@ impl SpriteSlot {
                     v1 -= 0.5;
                 }
 
-                [u0 / tw, v0 / th, u1 / tw, v1 / th]
+                self.mirrored([u0 / tw, v0 / th, u1 / tw, v1 / th])
             }
         }
     }
@@ -400,6 +414,9 @@ pub struct HoldVisuals {
     pub bottomcap_inactive: Option<SpriteSlot>,
     pub bottomcap_active: Option<SpriteSlot>,
     pub explosion: Option<SpriteSlot>,
+    /// Played on the engaged explosion from each tick, from an `ExplosionCommand`
+    /// with `window=Hold` or `window=Roll`. None draws the static frame.
+    pub explosion_animation: Option<ExplosionAnimation>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -415,14 +432,51 @@ pub struct Noteskin {
     pub receptor_glow: Vec<Option<SpriteSlot>>,
     pub mines: Vec<Option<SpriteSlot>>,
     pub mine_frames: Vec<Option<SpriteSlot>>,
+    pub mine: MineVisuals,
     pub column_xs: Vec<i32>,
     pub tap_explosions: HashMap<String, TapExplosion>,
     pub receptor_pulse: ReceptorPulse,
+    /// Played on a receptor when its lane is pressed without hitting a note,
+    /// from a `ReceptorCommand` with `event=Press`.
+    pub receptor_press: ExplosionAnimation,
+    /// Played on a receptor when its lane is released, from `event=Lift`.
+    /// Empty by default, so releasing leaves the receptor alone.
+    pub receptor_lift: ExplosionAnimation,
     pub hold_let_go_gray_percent: f32,
     pub hold: HoldVisuals,
     pub roll: HoldVisuals,
 }
 
+/// How mines are drawn, from the `MineStyle` block.
+#[derive(Debug, Clone, Copy)]
+pub struct MineVisuals {
+    /// Fill spin in degrees per second; negative spins the other way. Only
+    /// used when the fill is drawn as a sprite rather than a gradient.
+    pub fill_spin: f32,
+    /// Frame spin in degrees per second.
+    pub frame_spin: f32,
+    /// Concentric circles the gradient fill is built from.
+    pub fill_layers: usize,
+    /// Gradient fill diameter as a fraction of the frame's.
+    pub core_ratio: f32,
+}
+
+impl Default for MineVisuals {
+    fn default() -> Self {
+        Self {
+            fill_spin: -45.0,
+            frame_spin: 120.0,
+            fill_layers: 32,
+            core_ratio: 0.45,
+        }
+    }
+}
+
+const MAX_MINE_FILL_LAYERS: usize = 128;
+
+// The bop receptors did before noteskins could script it.
+const DEFAULT_RECEPTOR_PRESS_COMMANDS: &str = "zoom,0.75;linear,0.11;zoom,1.0";
+
 #[derive(Debug, Clone, Copy)]
 pub struct ReceptorPulse {
     pub effect_color1: [f32; 4],
@@ -534,17 +588,22 @@ struct NoteskinBuilder {
     default_sources: HashMap<String, Arc<SpriteSource>>,
     tap_explosions: HashMap<String, ExplosionBuilder>,
     receptor_pulse: ReceptorPulse,
+    receptor_press: Option<ExplosionAnimation>,
+    receptor_lift: Option<ExplosionAnimation>,
+    mine: MineVisuals,
     hold_let_go_gray_percent: f32,
     hold_body_inactive: Option<SlotBuilder>,
     hold_body_active: Option<SlotBuilder>,
     hold_bottomcap_inactive: Option<SlotBuilder>,
     hold_bottomcap_active: Option<SlotBuilder>,
     hold_explosion: Option<SlotBuilder>,
+    hold_explosion_animation: Option<ExplosionAnimation>,
     roll_body_inactive: Option<SlotBuilder>,
     roll_body_active: Option<SlotBuilder>,
     roll_bottomcap_inactive: Option<SlotBuilder>,
     roll_bottomcap_active: Option<SlotBuilder>,
     roll_explosion: Option<SlotBuilder>,
+    roll_explosion_animation: Option<ExplosionAnimation>,
 }
 
 impl NoteskinBuilder {
@@ -564,17 +623,22 @@ impl NoteskinBuilder {
             default_sources: HashMap::new(),
             tap_explosions: HashMap::new(),
             receptor_pulse: ReceptorPulse::default(),
+            receptor_press: None,
+            receptor_lift: None,
+            mine: MineVisuals::default(),
             hold_let_go_gray_percent: 0.25,
             hold_body_inactive: None,
             hold_body_active: None,
             hold_bottomcap_inactive: None,
             hold_bottomcap_active: None,
             hold_explosion: None,
+            hold_explosion_animation: None,
             roll_body_inactive: None,
             roll_body_active: None,
             roll_bottomcap_inactive: None,
             roll_bottomcap_active: None,
             roll_explosion: None,
+            roll_explosion_animation: None,
         }
     }
 
@@ -753,6 +817,7 @@ impl NoteskinBuilder {
             bottomcap_inactive: hold_bottomcap_inactive.clone(),
             bottomcap_active: hold_bottomcap_active.clone(),
             explosion: hold_explosion.clone(),
+            explosion_animation: self.hold_explosion_animation.clone(),
         };
 
         let roll_visuals = HoldVisuals {
@@ -761,6 +826,7 @@ impl NoteskinBuilder {
             bottomcap_inactive: roll_bottomcap_inactive.clone(),
             bottomcap_active: roll_bottomcap_active.clone(),
             explosion: roll_explosion.clone(),
+            explosion_animation: self.roll_explosion_animation.or(self.hold_explosion_animation),
         };
 
         let tap_explosions = self
@@ -817,9 +883,18 @@ impl NoteskinBuilder {
             receptor_glow,
             mines,
             mine_frames,
+            mine: self.mine,
             column_xs,
             tap_explosions,
             receptor_pulse: self.receptor_pulse,
+            receptor_press: self
+                .receptor_press
+                .unwrap_or_else(|| parse_explosion_animation(DEFAULT_RECEPTOR_PRESS_COMMANDS)),
+            receptor_lift: self.receptor_lift.unwrap_or_else(|| ExplosionAnimation {
+                initial: ExplosionState::default(),
+                segments: Vec::new(),
+                glow: None,
+            }),
             hold_let_go_gray_percent: self.hold_let_go_gray_percent,
             hold: hold_visuals,
             roll: roll_visuals,
@@ -862,6 +937,8 @@ pub fn load(path: &Path, style: &Style) -> Result<Noteskin, String> {
                     "ExplosionSheet" => parse_explosion_sheet(&noteskin_dir, &mut builder, &props),
                     "ExplosionCommand" => parse_explosion_command(&mut builder, &props),
                     "ReceptorPulse" => parse_receptor_pulse(&mut builder, &props),
+                    "ReceptorCommand" => parse_receptor_command(&mut builder, &props),
+                    "MineStyle" => parse_mine_style(&mut builder, &props),
                     "HoldBody" | "Hold-body" | "HoldHead" | "HoldBodyActive"
                     | "HoldBodyInactive" => parse_hold_component(
                         &noteskin_dir,
@@ -1357,7 +1434,34 @@ fn parse_explosion_command(builder: &mut NoteskinBuilder, props: &HashMap<&str,
     };
 
     let animation = parse_explosion_animation(commands);
-    builder.tap_explosions.entry(window).or_default().animation = Some(animation);
+    // Hold and roll scripts restart on every tick while the note is held.
+    match window.as_str() {
+        "HOLD" => builder.hold_explosion_animation = Some(animation),
+        "ROLL" => builder.roll_explosion_animation = Some(animation),
+        _ => builder.tap_explosions.entry(window).or_default().animation = Some(animation),
+    }
+}
+
+fn parse_receptor_command(builder: &mut NoteskinBuilder, props: &HashMap<&str, &str>) {
+    let Some(event) = props.get("event").map(|s| s.trim().to_ascii_uppercase()) else {
+        warn!("ReceptorCommand missing event attribute");
+        return;
+    };
+
+    let Some(commands) = props.get("commands").map(|s| s.trim().trim_matches('"')) else {
+        warn!(
+            "ReceptorCommand missing commands attribute for event '{}'",
+            event
+        );
+        return;
+    };
+
+    let animation = parse_explosion_animation(commands);
+    match event.as_str() {
+        "PRESS" => builder.receptor_press = Some(animation),
+        "LIFT" => builder.receptor_lift = Some(animation),
+        _ => warn!("Unknown ReceptorCommand event '{}'", event),
+    }
 }
 
 struct PendingSegment {
@@ -1634,6 +1738,34 @@ fn apply_basic_sprite_properties(slot: &mut SlotBuilder, props: &HashMap<&str, &
         slot.def.mirror_h = mirror_str.contains('h');
         slot.def.mirror_v = mirror_str.contains('v');
     }
+    apply_material(&mut slot.def, props);
+}
+
+fn apply_material(def: &mut SpriteDefinition, props: &HashMap<&str, &str>) {
+    let Some(material_str) = props.get("material") else { return };
+    match parse_material(material_str) {
+        Some(material) => def.material = material,
+        None => warn!("Unrecognized noteskin material '{}'", material_str),
+    }
+}
+
+/// `material=hsv,<hue turns>,<saturation>,<value>`, `grayscale,<amount>`,
+/// `glow,<r>,<g>,<b>,<strength>` or `standard`.
+fn parse_material(input: &str) -> Option<Material> {
+    let mut parts = input
+        .trim()
+        .trim_matches('"')
+        .split([',', ' '])
+        .filter(|p| !p.is_empty());
+    let name = parts.next()?.to_ascii_lowercase();
+    let args: Vec<f32> = parts.map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    match (name.as_str(), args.as_slice()) {
+        ("standard", []) => Some(Material::Standard),
+        ("hsv", &[hue, saturation, value]) => Some(Material::HsvShift { hue, saturation, value }),
+        ("grayscale", &[amount]) => Some(Material::Grayscale { amount }),
+        ("glow", &[r, g, b, strength]) => Some(Material::Glow { color: [r, g, b], strength }),
+        _ => None,
+    }
 }
 
 fn parse_sprite_rule(
@@ -1665,6 +1797,7 @@ fn parse_sprite_rule(
             def.mirror_h = mirror_str.contains('h');
             def.mirror_v = mirror_str.contains('v');
         }
+        apply_material(&mut def, props);
         if tag == "Mine" {
             builder
                 .defaults
@@ -1777,13 +1910,14 @@ fn build_sheet_source(
 ) -> Option<Arc<SpriteSource>> {
     let key = resolve_texture_key(noteskin_dir, texture);
     let dims = texture_dimensions(&key)?;
-    let fps = props
-        .get("fps")
-        .and_then(|v| v.parse::<f32>().ok())
-        .unwrap_or(default_fps);
+    let fps_prop = props.get("fps").and_then(|v| v.parse::<f32>().ok());
+    let fps = fps_prop.unwrap_or(default_fps);
+    // GIF/APNG textures were packed into a sheet on load and know their own layout.
+    let animation = assets::texture_animation(&key);
     let grid = props
         .get("grid")
         .and_then(|g| parse_pair_usize(g))
+        .or(animation.as_ref().map(|a| (a.grid.0 as usize, a.grid.1 as usize)))
         .unwrap_or_else(|| infer_grid(&key));
     let frame_size = props
         .get("frame_size")
@@ -1792,6 +1926,7 @@ fn build_sheet_source(
     let frames = props
         .get("frames")
         .and_then(|s| s.parse::<usize>().ok())
+        .or(animation.as_ref().map(|a| a.delays.len()))
         .unwrap_or_else(|| grid.0 * grid.1);
     let beats_per_loop = props
         .get("beats_per_loop")
@@ -1820,6 +1955,8 @@ fn build_sheet_source(
             .unwrap_or(fps)
             .max(0.0);
         AnimationRate::FramesPerBeat(frames_per_beat)
+    } else if let Some(animation) = animation.filter(|_| fps_prop.is_none()) {
+        AnimationRate::FrameDelays(animation.delays)
     } else {
         AnimationRate::FramesPerSecond(fps.max(0.0))
     };
@@ -1911,6 +2048,33 @@ fn parse_color_rgba(input: &str) -> Option<[f32; 4]> {
     Some(color)
 }
 
+fn parse_mine_style(builder: &mut NoteskinBuilder, props: &HashMap<&str, &str>) {
+    let parse = |key: &str| {
+        let value = props.get(key)?.trim().trim_matches('"');
+        match value.parse::<f32>() {
+            Ok(parsed) if parsed.is_finite() => Some(parsed),
+            _ => {
+                warn!("Failed to parse MineStyle {} '{}'", key, value);
+                None
+            }
+        }
+    };
+
+    let mine = &mut builder.mine;
+    if let Some(value) = parse("fill_spin") {
+        mine.fill_spin = value;
+    }
+    if let Some(value) = parse("frame_spin") {
+        mine.frame_spin = value;
+    }
+    if let Some(value) = parse("fill_layers") {
+        mine.fill_layers = (value.round().max(1.0) as usize).min(MAX_MINE_FILL_LAYERS);
+    }
+    if let Some(value) = parse("core_ratio") {
+        mine.core_ratio = value.clamp(0.0, 1.0);
+    }
+}
+
 fn parse_receptor_pulse(builder: &mut NoteskinBuilder, props: &HashMap<&str, &str>) {
     fn parse_non_negative(value: Option<&&str>) -> Option<f32> {
         value
@@ -0,0 +1,70 @@
+use configparser::ini::Ini;
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+
+// Optional per-pack metadata, read from Group.ini (or ITGmania's Pack.ini) in the
+// pack folder. Everything is optional; missing keys fall back to the folder name.
+const PACK_INI_NAMES: &[&str] = &["Group.ini", "Pack.ini"];
+const PACK_INI_SECTIONS: &[&str] = &["Group", "Pack"];
+
+#[derive(Debug, Clone, Default)]
+pub struct PackMeta {
+    pub display_name: Option<String>,
+    pub sort_title: Option<String>,
+    /// Explicit position; packs with one come before packs without, lowest first.
+    pub sort_order: Option<i32>,
+    pub banner_path: Option<PathBuf>,
+    pub color: Option<[f32; 4]>,
+}
+
+/// Accepts "#RGB", "#RRGGBB" or "#RRGGBBAA" (the '#' is optional).
+fn parse_color(s: &str) -> Option<[f32; 4]> {
+    let hex = s.trim().trim_start_matches('#');
+    let valid = matches!(hex.len(), 3 | 4 | 6 | 8) && hex.bytes().all(|b| b.is_ascii_hexdigit());
+    valid.then(|| crate::ui::color::rgba_hex(hex))
+}
+
+pub fn load(pack_path: &Path) -> PackMeta {
+    let Some(ini_path) = PACK_INI_NAMES
+        .iter()
+        .map(|name| pack_path.join(name))
+        .find(|p| p.is_file())
+    else {
+        return PackMeta::default();
+    };
+
+    let mut conf = Ini::new();
+    if let Err(e) = conf.load(&ini_path) {
+        warn!("Failed to load '{}': {}", ini_path.display(), e);
+        return PackMeta::default();
+    }
+
+    let get = |key: &str| {
+        PACK_INI_SECTIONS
+            .iter()
+            .find_map(|section| conf.get(section, key))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let color = get("Color").and_then(|c| {
+        let parsed = parse_color(&c);
+        if parsed.is_none() {
+            warn!("Ignoring invalid Color '{}' in '{}'.", c, ini_path.display());
+        }
+        parsed
+    });
+    let banner_path = get("Banner")
+        .map(|b| pack_path.join(b))
+        .filter(|p| p.is_file());
+
+    let meta = PackMeta {
+        display_name: get("DisplayTitle").or_else(|| get("DisplayName")),
+        sort_title: get("SortTitle"),
+        sort_order: get("SortOrder").and_then(|v| v.parse().ok()),
+        banner_path,
+        color,
+    };
+    info!("Loaded pack metadata from '{}': {:?}", ini_path.display(), meta);
+    meta
+}
@@ -1,8 +1,13 @@
+use crate::core::audio_analysis;
+use crate::core::video;
+use crate::game::parsing::{duplicate_titles, pack_ini};
 use crate::game::{
     chart::ChartData,
-    song::{set_song_cache, SongData, SongPack},
+    song::{get_song_cache, set_song_cache, AssetIssue, SongData, SongPack},
 };
+use chardetng::EncodingDetector;
 use log::{info, warn};
+use rayon::prelude::*;
 use rssp::{analyze, AnalysisOptions};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -210,8 +215,14 @@ struct SerializableSongData {
     title: String,
     subtitle: String,
     artist: String,
+    title_translit: String,
+    subtitle_translit: String,
+    artist_translit: String,
     banner_path: Option<String>,
     background_path: Option<String>,
+    background_video: Option<String>,
+    background_video_beat: f32,
+    preview_path: Option<String>,
     music_path: Option<String>,
     display_bpm: String,
     offset: f32,
@@ -226,6 +237,8 @@ struct SerializableSongData {
     normalized_speeds: String,
     normalized_scrolls: String,
     total_length_seconds: i32,
+    music_gain_db: Option<f32>,
+    asset_issues: Vec<u8>,
     charts: Vec<SerializableChartData>,
 }
 
@@ -235,8 +248,14 @@ impl From<&SongData> for SerializableSongData {
             title: song.title.clone(),
             subtitle: song.subtitle.clone(),
             artist: song.artist.clone(),
+            title_translit: song.title_translit.clone(),
+            subtitle_translit: song.subtitle_translit.clone(),
+            artist_translit: song.artist_translit.clone(),
             banner_path: song.banner_path.as_ref().map(|p| p.to_string_lossy().into_owned()),
             background_path: song.background_path.as_ref().map(|p| p.to_string_lossy().into_owned()),
+            background_video: song.background_video.as_ref().map(|p| p.to_string_lossy().into_owned()),
+            background_video_beat: song.background_video_beat,
+            preview_path: song.preview_path.as_ref().map(|p| p.to_string_lossy().into_owned()),
             music_path: song.music_path.as_ref().map(|p| p.to_string_lossy().into_owned()),
             display_bpm: song.display_bpm.clone(),
             offset: song.offset,
@@ -251,6 +270,8 @@ impl From<&SongData> for SerializableSongData {
             normalized_speeds: song.normalized_speeds.clone(),
             normalized_scrolls: song.normalized_scrolls.clone(),
             total_length_seconds: song.total_length_seconds,
+            music_gain_db: song.music_gain_db,
+            asset_issues: song.asset_issues.iter().map(|i| i.to_u8()).collect(),
             charts: song.charts.iter().map(SerializableChartData::from).collect(),
         }
     }
@@ -259,11 +280,19 @@ impl From<&SongData> for SerializableSongData {
 impl From<SerializableSongData> for SongData {
     fn from(song: SerializableSongData) -> Self {
         Self {
+            // The cache is keyed by the simfile path; the loader fills it in.
+            simfile_path: PathBuf::new(),
             title: song.title,
             subtitle: song.subtitle,
             artist: song.artist,
+            title_translit: song.title_translit,
+            subtitle_translit: song.subtitle_translit,
+            artist_translit: song.artist_translit,
             banner_path: song.banner_path.map(PathBuf::from),
             background_path: song.background_path.map(PathBuf::from),
+            background_video: song.background_video.map(PathBuf::from),
+            background_video_beat: song.background_video_beat,
+            preview_path: song.preview_path.map(PathBuf::from),
             music_path: song.music_path.map(PathBuf::from),
             display_bpm: song.display_bpm,
             offset: song.offset,
@@ -278,13 +307,21 @@ impl From<SerializableSongData> for SongData {
             normalized_speeds: song.normalized_speeds,
             normalized_scrolls: song.normalized_scrolls,
             total_length_seconds: song.total_length_seconds,
+            music_gain_db: song.music_gain_db,
+            asset_issues: song.asset_issues.into_iter().filter_map(AssetIssue::from_u8).collect(),
+            disambiguator: None,
             charts: song.charts.into_iter().map(ChartData::from).collect(),
         }
     }
 }
 
+/// Bumped whenever the cached song layout or its text decoding changes,
+/// so stale entries get re-parsed.
+const CACHE_REVISION: u32 = 6;
+
 #[derive(Serialize, Deserialize, Encode, Decode)]
 struct CachedSong {
+    cache_revision: u32,
     rssp_version: String,
     source_hash: u64,
     data: SerializableSongData,
@@ -346,72 +383,148 @@ pub fn scan_and_load_songs(root_path_str: &'static str) {
         if !pack_path.is_dir() {
             continue;
         }
+        if let Some(loaded) = load_pack(&pack_path, config.fastload, config.cachesongs) {
+            loaded_packs.push(loaded);
+        }
+    }
+
+    // Packs with a Group.ini SortOrder come first, the rest alphabetically
+    // (by SortTitle when given, otherwise the folder name).
+    loaded_packs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut loaded_packs: Vec<SongPack> = loaded_packs.into_iter().map(|(_, pack)| pack).collect();
+    duplicate_titles::disambiguate(&mut loaded_packs);
+
+    info!("Finished scan. Found {} packs.", loaded_packs.len());
+    set_song_cache(loaded_packs);
+}
 
-        let pack_name = pack_path.file_name().unwrap_or_default().to_string_lossy().to_string();
-        let mut current_pack = SongPack { name: pack_name, songs: Vec::new() };
-        info!("Scanning pack: {}", current_pack.name);
+// Where a pack goes in the song list; see the ordering in `scan_and_load_songs`.
+fn pack_sort_key(pack_name: &str, meta: &pack_ini::PackMeta) -> (i32, String) {
+    (
+        meta.sort_order.unwrap_or(i32::MAX),
+        meta.sort_title.clone().unwrap_or_else(|| pack_name.to_string()).to_lowercase(),
+    )
+}
 
-        // Each subdirectory in a pack is a song folder
-        for song_dir_entry in fs::read_dir(pack_path).into_iter().flatten().flatten() {
-            let song_path = song_dir_entry.path();
-            if !song_path.is_dir() {
-                continue;
+/// Loads every song in one pack folder. None when it has no loadable songs.
+fn load_pack(pack_path: &Path, fastload: bool, cachesongs: bool) -> Option<((i32, String), SongPack)> {
+    let pack_name = pack_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let meta = pack_ini::load(pack_path);
+    let sort_key = pack_sort_key(&pack_name, &meta);
+    let mut current_pack = SongPack {
+        display_name: meta.display_name.unwrap_or_else(|| pack_name.clone()),
+        name: pack_name,
+        banner_path: meta.banner_path,
+        color: meta.color,
+        songs: Vec::new(),
+    };
+    info!("Scanning pack: {}", current_pack.name);
+
+    // Each subdirectory in a pack is a song folder; find its .sm or .ssc file.
+    let simfile_paths: Vec<PathBuf> = fs::read_dir(pack_path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|song_path| song_path.is_dir())
+        .filter_map(|song_path| {
+            fs::read_dir(&song_path).ok()?.flatten().map(|file| file.path()).find(|file_path| {
+                file_path
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("sm") || ext.eq_ignore_ascii_case("ssc"))
+            })
+        })
+        .collect();
+
+    // Parsing, image checks and the music scan are independent per song, so
+    // a cold cache build spreads them over the rayon pool.
+    current_pack.songs = simfile_paths
+        .par_iter()
+        .filter_map(|file_path| match load_song_from_file(file_path, fastload, cachesongs) {
+            Ok(song_data) => Some(Arc::new(song_data)),
+            Err(e) => {
+                warn!("Failed to load '{:?}': {}", file_path, e);
+                None
             }
+        })
+        .collect();
 
-            // Find the .sm or .ssc file within the song folder
-            if let Ok(files) = fs::read_dir(&song_path) {
-                for file in files.flatten() {
-                    let file_path = file.path();
-                    if let Some(ext) = file_path.extension().and_then(|s| s.to_str()) {
-                        if ext.eq_ignore_ascii_case("sm") || ext.eq_ignore_ascii_case("ssc") {
-                            match load_song_from_file(&file_path, config.fastload, config.cachesongs) {
-                                Ok(song_data) => {
-                                    current_pack.songs.push(Arc::new(song_data));
-                                }
-                                Err(e) => warn!("Failed to load '{:?}': {}", file_path, e),
-                            }
-                            // Found the simfile, move to the next song directory
-                            break;
-                        }
-                    }
-                }
+    if !current_pack.songs.is_empty() {
+        // Sort songs within the pack with a more natural order, grouping songs
+        // that start with non-alphanumeric characters (like '[Marathon]') at the end.
+        // Titles are compared by transliteration when the simfile provides one.
+        current_pack.songs.sort_by(|a, b| {
+            let a_title = a.sort_title().to_lowercase();
+            let b_title = b.sort_title().to_lowercase();
+
+            let a_first_char = a_title.chars().next();
+            let b_first_char = b_title.chars().next();
+
+            // Treat a title as "special" if it starts with a non-alphanumeric character.
+            let a_is_special = a_first_char.map_or(false, |c| !c.is_alphanumeric());
+            let b_is_special = b_first_char.map_or(false, |c| !c.is_alphanumeric());
+
+            if a_is_special == b_is_special {
+                // If both are special or both are not, sort them alphabetically.
+                a_title.cmp(&b_title)
+            } else if a_is_special {
+                // `a` is special and `b` is not, so `b` should come first.
+                std::cmp::Ordering::Greater
+            } else {
+                // `b` is special and `a` is not, so `a` should come first.
+                std::cmp::Ordering::Less
             }
-        }
+        });
+        Some((sort_key, current_pack))
+    } else {
+        None
+    }
+}
 
-        if !current_pack.songs.is_empty() {
-            // Sort songs within the pack with a more natural order, grouping songs
-            // that start with non-alphanumeric characters (like '[Marathon]') at the end.
-            current_pack.songs.sort_by(|a, b| {
-                let a_title = a.title.to_lowercase();
-                let b_title = b.title.to_lowercase();
-
-                let a_first_char = a_title.chars().next();
-                let b_first_char = b_title.chars().next();
-
-                // Treat a title as "special" if it starts with a non-alphanumeric character.
-                let a_is_special = a_first_char.map_or(false, |c| !c.is_alphanumeric());
-                let b_is_special = b_first_char.map_or(false, |c| !c.is_alphanumeric());
-
-                if a_is_special == b_is_special {
-                    // If both are special or both are not, sort them alphabetically.
-                    a_title.cmp(&b_title)
-                } else if a_is_special {
-                    // `a` is special and `b` is not, so `b` should come first.
-                    std::cmp::Ordering::Greater
-                } else {
-                    // `b` is special and `a` is not, so `a` should come first.
-                    std::cmp::Ordering::Less
-                }
-            });
-            loaded_packs.push(current_pack);
-        }
+/// One pack folder loaded on its own, e.g. after installing it, ready for
+/// `add_scanned_pack` to put in the song cache.
+#[derive(Clone, Debug)]
+pub struct ScannedPack {
+    sort_key: (i32, String),
+    pack: SongPack,
+    root: PathBuf,
+}
+
+impl ScannedPack {
+    pub fn name(&self) -> &str {
+        &self.pack.name
     }
 
-    // Sort the packs themselves alphabetically by name for consistent ordering.
-    loaded_packs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    pub fn song_count(&self) -> usize {
+        self.pack.songs.len()
+    }
+}
 
-    info!("Finished scan. Found {} packs.", loaded_packs.len());
-    set_song_cache(loaded_packs);
+/// Loads a single pack folder without rescanning everything. Safe off the main
+/// thread; None when it has no loadable songs.
+pub fn scan_pack(pack_path: &Path) -> Option<ScannedPack> {
+    let config = crate::config::get();
+    let (sort_key, pack) = load_pack(pack_path, config.fastload, config.cachesongs)?;
+    let root = pack_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    Some(ScannedPack { sort_key, pack, root })
+}
+
+/// Adds (or replaces) a pack from `scan_pack` in the song cache. Call it from
+/// the main thread, so screens never see the cache change under them.
+pub fn add_scanned_pack(scanned: ScannedPack) {
+    let ScannedPack { sort_key, pack, root } = scanned;
+    let mut cache = get_song_cache();
+    if let Some(existing) = cache.iter_mut().find(|p| p.name == pack.name) {
+        *existing = pack;
+    } else {
+        let position = cache
+            .iter()
+            .position(|p| pack_sort_key(&p.name, &pack_ini::load(&root.join(&p.name))) > sort_key)
+            .unwrap_or(cache.len());
+        cache.insert(position, pack);
+    }
+    duplicate_titles::disambiguate(&mut cache);
 }
 
 /// Helper function to parse a single simfile, using a cache if available and valid.
@@ -440,12 +553,19 @@ fn load_song_from_file(path: &Path, fastload: bool, cachesongs: bool) -> Result<
                     let mut buffer = Vec::new();
                     if file.read_to_end(&mut buffer).is_ok() {
                         if let Ok((cached_song, _)) = bincode::decode_from_slice::<CachedSong, _>(&buffer, bincode::config::standard()) {
-                            if cached_song.source_hash == ch && cached_song.rssp_version == rssp::RSSP_VERSION {
+                            if cached_song.source_hash == ch
+                                && cached_song.rssp_version == rssp::RSSP_VERSION
+                                && cached_song.cache_revision == CACHE_REVISION
+                            {
                                 info!("Cache hit for: {:?}", path.file_name().unwrap_or_default());
-                                return Ok(cached_song.data.into());
+                                let mut song: SongData = cached_song.data.into();
+                                song.simfile_path = path.to_path_buf();
+                                return Ok(song);
                             } else {
                                 if cached_song.source_hash != ch {
                                     info!("Cache stale (content hash mismatch) for: {:?}", path.file_name().unwrap_or_default());
+                                } else if cached_song.cache_revision != CACHE_REVISION {
+                                    info!("Cache stale (cache format changed) for: {:?}", path.file_name().unwrap_or_default());
                                 } else {
                                     info!("Cache stale (rssp version mismatch) for: {:?}", path.file_name().unwrap_or_default());
                                 }
@@ -469,6 +589,7 @@ fn load_song_from_file(path: &Path, fastload: bool, cachesongs: bool) -> Result<
         if let (Some(cp), Some(ch)) = (cache_path, content_hash) {
             let serializable_data: SerializableSongData = (&song_data).into();
             let cached_song = CachedSong {
+                cache_revision: CACHE_REVISION,
                 rssp_version: rssp::RSSP_VERSION.to_string(),
                 source_hash: ch,
                 data: serializable_data,
@@ -490,9 +611,100 @@ fn load_song_from_file(path: &Path, fastload: bool, cachesongs: bool) -> Result<
 }
 
 
+/// The first video in #BGCHANGES that exists, with the beat it starts on.
+/// Entries are `beat=file=rate=...`; image changes and effects aren't played.
+fn first_video_bgchange(simfile_data: &[u8], simfile_dir: &Path) -> Option<(PathBuf, f32)> {
+    let text = String::from_utf8_lossy(simfile_data);
+    let start = text.find("#BGCHANGES:")? + "#BGCHANGES:".len();
+    let body = &text[start..];
+    let body = &body[..body.find(';').unwrap_or(body.len())];
+    body.split(',').find_map(|entry| {
+        let mut fields = entry.split('=');
+        let beat = fields.next()?.trim().parse::<f32>().ok()?;
+        let path = simfile_dir.join(fields.next()?.trim());
+        (video::is_video_path(&path) && path.is_file()).then_some((path, beat))
+    })
+}
+
+/// The value of `tag` (e.g. "#JACKET") in the raw simfile, if it's there and not empty.
+fn raw_tag<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    let start = text.find(&format!("{}:", tag))? + tag.len() + 1;
+    let body = &text[start..];
+    let value = body[..body.find(';').unwrap_or(body.len())].trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// What song select plays over the banner: #PREVIEWVID if it's a video that
+/// exists, else #JACKET if it's a video or an animated image. Still jackets
+/// aren't shown; the banner already is.
+fn find_preview(simfile_data: &[u8], simfile_dir: &Path) -> Option<PathBuf> {
+    let text = String::from_utf8_lossy(simfile_data);
+    let preview_video = raw_tag(&text, "#PREVIEWVID")
+        .map(|file| simfile_dir.join(file))
+        .filter(|path| video::is_video_path(path) && path.is_file());
+    preview_video.or_else(|| {
+        let jacket = simfile_dir.join(raw_tag(&text, "#JACKET")?);
+        let moves = video::is_video_path(&jacket) || crate::assets::is_animated_image(&jacket);
+        (moves && jacket.is_file()).then_some(jacket)
+    })
+}
+
+/// Header-only check, so a truncated or mislabeled image is caught without a full decode.
+fn image_is_readable(path: &Path) -> bool {
+    path.is_file() && image::image_dimensions(path).is_ok()
+}
+
+// Reported and measured lengths within this many seconds are considered the same.
+const LENGTH_TOLERANCE_SECONDS: f32 = 1.0;
+
+/// Replaces a wrong #MUSICLENGTH (or the parser's estimate) with the audible
+/// length of the music file, so trailing silence doesn't stretch the progress
+/// meter and the remaining-time display.
+fn corrected_length_seconds(reported: i32, analysis: &audio_analysis::TrackAnalysis, path: &Path) -> i32 {
+    let Some(audible) = analysis.audible_end_sec.or(analysis.duration_sec) else {
+        return reported;
+    };
+    if (audible - reported as f32).abs() <= LENGTH_TOLERANCE_SECONDS {
+        return reported;
+    }
+    let corrected = audible.ceil() as i32;
+    info!(
+        "Correcting music length for {:?}: reported {}s, audio is {}s ({:.1}s decoded).",
+        path.file_name().unwrap_or_default(),
+        reported,
+        corrected,
+        analysis.duration_sec.unwrap_or(audible)
+    );
+    corrected
+}
+
 /// The original parsing logic, now separated to be called on a cache miss.
+/// Simfiles don't declare an encoding, and many older Japanese packs are saved
+/// as Shift-JIS. Valid UTF-8 passes through untouched; anything else is guessed
+/// with chardetng and transcoded, so rssp only ever sees UTF-8.
+fn decode_simfile_bytes(bytes: Vec<u8>, path: &Path) -> Vec<u8> {
+    if std::str::from_utf8(&bytes).is_ok() {
+        return bytes;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(&bytes, true);
+    let encoding = detector.guess(None, false);
+    let (text, _, had_errors) = encoding.decode(&bytes);
+    if had_errors {
+        warn!(
+            "{:?} is not valid {}; some characters were replaced.",
+            path.file_name().unwrap_or_default(),
+            encoding.name()
+        );
+    } else {
+        info!("Decoded {:?} as {}.", path.file_name().unwrap_or_default(), encoding.name());
+    }
+    text.into_owned().into_bytes()
+}
+
 fn parse_and_process_song_file(path: &Path) -> Result<SongData, String> {
     let simfile_data = fs::read(path).map_err(|e| format!("Could not read file: {}", e))?;
+    let simfile_data = decode_simfile_bytes(simfile_data, path);
     let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
     let options = AnalysisOptions::default(); // Use default parsing options
 
@@ -544,6 +756,18 @@ fn parse_and_process_song_file(path: &Path) -> Result<SongData, String> {
         None
     };
 
+    // Videos play through `background_video`; the still is looked for as usual.
+    let mut background_video = first_video_bgchange(&simfile_data, simfile_dir);
+    if background_path_opt.as_deref().is_some_and(video::is_video_path) {
+        let path = background_path_opt.take().unwrap();
+        background_video.get_or_insert((path, 0.0));
+    }
+    let (background_video, background_video_beat) = match background_video {
+        Some((path, beat)) => (Some(path), beat),
+        None => (None, 0.0),
+    };
+    let preview_path = find_preview(&simfile_data, simfile_dir);
+
     if background_path_opt.is_none() {
         info!("'{}' - BG path is missing or empty, attempting autodetection.", summary.title_str);
         if let Ok(entries) = fs::read_dir(simfile_dir) {
@@ -595,25 +819,63 @@ fn parse_and_process_song_file(path: &Path) -> Result<SongData, String> {
         }
     }
 
+    let mut asset_issues = Vec::new();
+
     let banner_path = if !summary.banner_path.is_empty() {
         let p = simfile_dir.join(&summary.banner_path);
-        if p.exists() { Some(p) } else { None }
+        if image_is_readable(&p) {
+            Some(p)
+        } else {
+            asset_issues.push(AssetIssue::BrokenBanner);
+            None
+        }
     } else {
         None
     };
+    if let Some(bg) = background_path_opt.take() {
+        if image_is_readable(&bg) {
+            background_path_opt = Some(bg);
+        } else {
+            asset_issues.push(AssetIssue::BrokenBackground);
+        }
+    }
 
     let music_path = if !summary.music_path.is_empty() {
         Some(simfile_dir.join(summary.music_path))
     } else {
         None
     };
+    let music_analysis = match music_path.as_deref() {
+        Some(p) if p.is_file() => {
+            let analysis = audio_analysis::analyze(p);
+            if analysis.duration_sec.is_none() {
+                asset_issues.push(AssetIssue::UnreadableMusic);
+            }
+            analysis
+        }
+        _ => {
+            asset_issues.push(AssetIssue::MissingMusic);
+            audio_analysis::TrackAnalysis::default()
+        }
+    };
+    for issue in &asset_issues {
+        warn!("{:?}: {}", path.file_name().unwrap_or_default(), issue.message());
+    }
+    let total_length_seconds = corrected_length_seconds(summary.total_length, &music_analysis, path);
 
     Ok(SongData {
+        simfile_path: path.to_path_buf(),
         title: summary.title_str,
         subtitle: summary.subtitle_str,
         artist: summary.artist_str,
+        title_translit: summary.titletranslit_str,
+        subtitle_translit: summary.subtitletranslit_str,
+        artist_translit: summary.artisttranslit_str,
         banner_path, // Keep original logic for banner
         background_path: background_path_opt,
+        background_video,
+        background_video_beat,
+        preview_path,
         display_bpm: summary.display_bpm_str,
         offset: summary.offset as f32,
         sample_start: if summary.sample_start > 0.0 { Some(summary.sample_start as f32) } else { None },
@@ -627,7 +889,10 @@ fn parse_and_process_song_file(path: &Path) -> Result<SongData, String> {
         normalized_speeds: summary.normalized_speeds,
         normalized_scrolls: summary.normalized_scrolls,
         music_path,
-        total_length_seconds: summary.total_length,
+        total_length_seconds,
+        music_gain_db: music_analysis.gain_db,
+        asset_issues,
+        disambiguator: None,
         charts,
     })
 }
@@ -0,0 +1,227 @@
+use chrono::{Datelike, Local, Timelike};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+
+// Just enough ZIP to unpack song downloads and pack diagnostic bundles: stored
+// and deflated entries, no encryption, no ZIP64. Each entry is read on its own,
+// so large packs don't have to fit in memory.
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+const EOCD_MIN_SIZE: u64 = 22;
+// The end record may be followed by a comment of up to 64 KiB.
+const EOCD_SEARCH: u64 = EOCD_MIN_SIZE + u16::MAX as u64;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+// Version 2.0: deflate. Also the "needed to extract" version written.
+const ZIP_VERSION: u16 = 20;
+// General purpose flag bit 11: names are UTF-8.
+const FLAG_UTF8: u16 = 1 << 11;
+
+struct Entry {
+    name: String,
+    method: u16,
+    flags: u16,
+    compressed_size: u64,
+    size: u64,
+    local_header_offset: u64,
+}
+
+fn u16_at(b: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([b[i], b[i + 1]])
+}
+
+fn u32_at(b: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]])
+}
+
+fn read_entries(file: &mut File) -> Result<Vec<Entry>, String> {
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    if len < EOCD_MIN_SIZE {
+        return Err("not a ZIP file".into());
+    }
+    let tail_len = len.min(EOCD_SEARCH);
+    let mut tail = vec![0u8; tail_len as usize];
+    file.seek(SeekFrom::Start(len - tail_len)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut tail).map_err(|e| e.to_string())?;
+    let eocd = (0..=tail.len() - EOCD_MIN_SIZE as usize)
+        .rev()
+        .find(|&i| u32_at(&tail, i) == EOCD_SIGNATURE)
+        .ok_or("not a ZIP file")?;
+
+    let count = u16_at(&tail, eocd + 10) as usize;
+    let cd_size = u32_at(&tail, eocd + 12);
+    let cd_offset = u32_at(&tail, eocd + 16);
+    if count == u16::MAX as usize || cd_size == u32::MAX || cd_offset == u32::MAX {
+        return Err("ZIP64 archives aren't supported".into());
+    }
+
+    let mut cd = vec![0u8; cd_size as usize];
+    file.seek(SeekFrom::Start(cd_offset as u64)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut cd).map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::with_capacity(count);
+    let mut i = 0;
+    for _ in 0..count {
+        if i + 46 > cd.len() || u32_at(&cd, i) != CENTRAL_SIGNATURE {
+            return Err("corrupt ZIP central directory".into());
+        }
+        let name_len = u16_at(&cd, i + 28) as usize;
+        let extra_len = u16_at(&cd, i + 30) as usize;
+        let comment_len = u16_at(&cd, i + 32) as usize;
+        let name = cd.get(i + 46..i + 46 + name_len).ok_or("corrupt ZIP central directory")?;
+        entries.push(Entry {
+            name: String::from_utf8_lossy(name).replace('\\', "/"),
+            flags: u16_at(&cd, i + 8),
+            method: u16_at(&cd, i + 10),
+            compressed_size: u32_at(&cd, i + 20) as u64,
+            size: u32_at(&cd, i + 24) as u64,
+            local_header_offset: u32_at(&cd, i + 42) as u64,
+        });
+        i += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+// Entry names are untrusted; only plain relative components are allowed.
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name.trim_end_matches('/'));
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+fn read_entry(file: &mut File, entry: &Entry) -> Result<Vec<u8>, String> {
+    if entry.flags & 1 != 0 {
+        return Err(format!("'{}' is encrypted", entry.name));
+    }
+    let mut header = [0u8; 30];
+    file.seek(SeekFrom::Start(entry.local_header_offset)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut header).map_err(|e| e.to_string())?;
+    if u32_at(&header, 0) != LOCAL_SIGNATURE {
+        return Err(format!("corrupt local header for '{}'", entry.name));
+    }
+    let skip = u16_at(&header, 26) as i64 + u16_at(&header, 28) as i64;
+    file.seek(SeekFrom::Current(skip)).map_err(|e| e.to_string())?;
+
+    let mut data = vec![0u8; entry.compressed_size as usize];
+    file.read_exact(&mut data).map_err(|e| e.to_string())?;
+    match entry.method {
+        METHOD_STORED => Ok(data),
+        METHOD_DEFLATED => miniz_oxide::inflate::decompress_to_vec_with_limit(&data, entry.size as usize)
+            .map_err(|e| format!("'{}' is corrupt: {:?}", entry.name, e.status)),
+        other => Err(format!("'{}' uses unsupported compression method {}", entry.name, other)),
+    }
+}
+
+/// Unpacks every file in the archive at `zip_path` under `dest`. Returns how many files were written.
+pub fn extract(zip_path: &Path, dest: &Path) -> Result<usize, String> {
+    let mut file = File::open(zip_path).map_err(|e| e.to_string())?;
+    let entries = read_entries(&mut file)?;
+    let mut written = 0;
+    for entry in &entries {
+        let Some(relative) = safe_relative_path(&entry.name) else { continue };
+        let out_path = dest.join(relative);
+        if entry.name.ends_with('/') {
+            fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
+            continue;
+        }
+        let data = read_entry(&mut file, entry)?;
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&out_path, data).map_err(|e| format!("{}: {}", out_path.display(), e))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+// Local time as the MS-DOS time and date fields.
+fn dos_timestamp() -> (u16, u16) {
+    let now = Local::now();
+    let time = (now.hour() << 11 | now.minute() << 5 | now.second() / 2) as u16;
+    let date = ((now.year().clamp(1980, 2107) - 1980) as u32) << 9 | now.month() << 5 | now.day();
+    (time, date as u16)
+}
+
+/// Writes `files` (entry name, contents) to a new archive at `zip_path`, each
+/// deflated unless that would make it bigger.
+pub fn create(zip_path: &Path, files: &[(String, Vec<u8>)]) -> Result<(), String> {
+    let mut out = BufWriter::new(File::create(zip_path).map_err(|e| e.to_string())?);
+    let (time, date) = dos_timestamp();
+    let mut central = Vec::new();
+    let mut offset: u64 = 0;
+    for (name, data) in files {
+        let deflated = miniz_oxide::deflate::compress_to_vec(data, 6);
+        let (method, stored) = match deflated.len() < data.len() {
+            true => (METHOD_DEFLATED, deflated.as_slice()),
+            false => (METHOD_STORED, data.as_slice()),
+        };
+        if data.len() as u64 >= u32::MAX as u64 || offset >= u32::MAX as u64 {
+            return Err(format!("'{}' is too big for a ZIP without ZIP64", name));
+        }
+        let crc = crc32(data);
+        // The local header's fields, shared with the central directory entry.
+        let mut common = Vec::with_capacity(26);
+        common.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        common.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+        common.extend_from_slice(&method.to_le_bytes());
+        common.extend_from_slice(&time.to_le_bytes());
+        common.extend_from_slice(&date.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend_from_slice(&LOCAL_SIGNATURE.to_le_bytes());
+        local.extend_from_slice(&common);
+        local.extend_from_slice(name.as_bytes());
+        out.write_all(&local).and_then(|_| out.write_all(stored)).map_err(|e| e.to_string())?;
+
+        central.extend_from_slice(&CENTRAL_SIGNATURE.to_le_bytes());
+        central.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // made by
+        central.extend_from_slice(&common);
+        central.extend_from_slice(&[0u8; 10]); // comment length, disk, internal and external attributes
+        central.extend_from_slice(&(offset as u32).to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+        offset += (local.len() + stored.len()) as u64;
+    }
+    if files.len() >= u16::MAX as usize || offset >= u32::MAX as u64 {
+        return Err("too much for a ZIP without ZIP64".into());
+    }
+
+    let mut eocd = Vec::with_capacity(EOCD_MIN_SIZE as usize);
+    eocd.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+    eocd.extend_from_slice(&[0u8; 4]); // this disk, central directory disk
+    eocd.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    eocd.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    eocd.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    eocd.extend_from_slice(&(offset as u32).to_le_bytes());
+    eocd.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    out.write_all(&central)
+        .and_then(|_| out.write_all(&eocd))
+        .and_then(|_| out.flush())
+        .map_err(|e| e.to_string())
+}
@@ -1,17 +1,21 @@
 pub use super::scroll::ScrollSpeedSetting;
+use super::mods::{ChartMods, Turn};
 use configparser::ini::Ini;
 use log::{info, warn};
 use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Mutex;
 
 // --- Profile Data ---
-const PROFILE_DIR: &str = "save/profiles/00000000";
+pub(crate) const PROFILE_DIR: &str = "save/profiles/00000000";
 const PROFILE_INI_PATH: &str = "save/profiles/00000000/profile.ini";
 const GROOVESTATS_INI_PATH: &str = "save/profiles/00000000/groovestats.ini";
 const PROFILE_AVATAR_PATH: &str = "save/profiles/00000000/profile.png";
+const PLAY_STATS_INI_PATH: &str = "save/profiles/00000000/stats.ini";
+const STICKY_MODS_INI_PATH: &str = "save/profiles/00000000/sticky_mods.ini";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BackgroundFilter {
@@ -51,6 +55,50 @@ impl core::fmt::Display for BackgroundFilter {
     }
 }
 
+/// Column spacing multipliers offered on the options screen, in steps of 5%.
+pub const MIN_COLUMN_SPACING: f32 = 0.5;
+pub const MAX_COLUMN_SPACING: f32 = 1.5;
+pub const COLUMN_SPACING_STEP: f32 = 0.05;
+
+/// Where the judgment and combo are drawn during gameplay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JudgmentPosition {
+    /// In the middle of the notefield.
+    #[default]
+    Center,
+    /// Beside the notefield, clear of the arrows.
+    Side,
+    /// Just below the receptors.
+    UnderReceptors,
+}
+
+impl JudgmentPosition {
+    pub const ALL: [JudgmentPosition; 3] =
+        [JudgmentPosition::Center, JudgmentPosition::Side, JudgmentPosition::UnderReceptors];
+}
+
+impl FromStr for JudgmentPosition {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(' ', "").as_str() {
+            "center" => Ok(Self::Center),
+            "side" => Ok(Self::Side),
+            "underreceptors" => Ok(Self::UnderReceptors),
+            _ => Err(format!("'{}' is not a valid JudgmentPosition setting", s)),
+        }
+    }
+}
+
+impl core::fmt::Display for JudgmentPosition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Center => write!(f, "Center"),
+            Self::Side => write!(f, "Side"),
+            Self::UnderReceptors => write!(f, "Under Receptors"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Profile {
     pub display_name: String,
@@ -59,9 +107,21 @@ pub struct Profile {
     pub groovestats_is_pad_player: bool,
     pub groovestats_username: String,
     pub background_filter: BackgroundFilter,
+    pub judgment_position: JudgmentPosition,
+    /// Multiplier on the noteskin's column spacing, applied on top of mini.
+    pub column_spacing: f32,
+    /// Combo milestones, explosions and glows play at the music rate, in step
+    /// with the beat-synced visuals, instead of in real time.
+    pub rate_scaled_effects: bool,
+    /// FA+: Fantastics inside the white window (`BASE_WHITE_FANTASTIC_WINDOW`)
+    /// get their own explosion and judgment counter row. Scoring is unchanged.
+    pub fa_plus: bool,
     pub avatar_path: Option<PathBuf>,
     pub avatar_texture_key: Option<String>,
     pub scroll_speed: ScrollSpeedSetting,
+    /// Rate, mini and turn for charts without a sticky preset. Session only;
+    /// not written to profile.ini.
+    pub mods: ChartMods,
 }
 
 impl Default for Profile {
@@ -73,16 +133,37 @@ impl Default for Profile {
             groovestats_is_pad_player: false,
             groovestats_username: "".to_string(),
             background_filter: BackgroundFilter::default(),
+            judgment_position: JudgmentPosition::default(),
+            column_spacing: 1.0,
+            rate_scaled_effects: true,
+            fa_plus: false,
             avatar_path: None,
             avatar_texture_key: None,
             scroll_speed: ScrollSpeedSetting::default(),
+            mods: ChartMods::default(),
         }
     }
 }
 
+/// How often and how recently a single chart was played, keyed by chart hash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChartPlayStats {
+    pub play_count: u32,
+    /// Unix timestamp (seconds) of the last time the chart was played.
+    pub last_played: Option<i64>,
+}
+
 // Global static for the current profile.
 static PROFILE: Lazy<Mutex<Profile>> = Lazy::new(|| Mutex::new(Profile::default()));
 
+// Kept apart from `Profile` so cloning the profile stays cheap.
+static PLAY_STATS: Lazy<Mutex<HashMap<String, ChartPlayStats>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Per-chart modifier presets ("sticky mods"), keyed by chart hash.
+static STICKY_MODS: Lazy<Mutex<HashMap<String, ChartMods>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 /// Creates the default profile directory and .ini files if they don't exist.
 fn create_default_files() -> Result<(), std::io::Error> {
     info!(
@@ -110,6 +191,26 @@ fn create_default_files() -> Result<(), std::io::Error> {
             "BackgroundFilter",
             Some(default_profile.background_filter.to_string()),
         );
+        profile_conf.set(
+            "PlayerOptions",
+            "JudgmentPosition",
+            Some(default_profile.judgment_position.to_string()),
+        );
+        profile_conf.set(
+            "PlayerOptions",
+            "ColumnSpacing",
+            Some(column_spacing_text(default_profile.column_spacing)),
+        );
+        profile_conf.set(
+            "PlayerOptions",
+            "RateScaledEffects",
+            Some((if default_profile.rate_scaled_effects { "1" } else { "0" }).to_string()),
+        );
+        profile_conf.set(
+            "PlayerOptions",
+            "FAPlus",
+            Some((if default_profile.fa_plus { "1" } else { "0" }).to_string()),
+        );
         profile_conf.set(
             "PlayerOptions",
             "ScrollSpeed",
@@ -151,6 +252,26 @@ fn save_profile_ini() {
         "BackgroundFilter",
         Some(profile.background_filter.to_string()),
     );
+    conf.set(
+        "PlayerOptions",
+        "JudgmentPosition",
+        Some(profile.judgment_position.to_string()),
+    );
+    conf.set(
+        "PlayerOptions",
+        "ColumnSpacing",
+        Some(column_spacing_text(profile.column_spacing)),
+    );
+    conf.set(
+        "PlayerOptions",
+        "RateScaledEffects",
+        Some((if profile.rate_scaled_effects { "1" } else { "0" }).to_string()),
+    );
+    conf.set(
+        "PlayerOptions",
+        "FAPlus",
+        Some((if profile.fa_plus { "1" } else { "0" }).to_string()),
+    );
     conf.set(
         "PlayerOptions",
         "ScrollSpeed",
@@ -194,6 +315,160 @@ fn save_groovestats_ini() {
     }
 }
 
+/// Each chart gets its own section: `[<chart hash>]` with `PlayCount` and `LastPlayed`.
+fn play_stats_from_ini(conf: &Ini) -> HashMap<String, ChartPlayStats> {
+    let mut stats = HashMap::new();
+    for hash in conf.sections() {
+        let play_count = conf
+            .get(&hash, "PlayCount")
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(0);
+        let last_played = conf
+            .get(&hash, "LastPlayed")
+            .and_then(|v| v.parse::<i64>().ok());
+        if play_count > 0 || last_played.is_some() {
+            stats.insert(hash, ChartPlayStats { play_count, last_played });
+        }
+    }
+    stats
+}
+
+fn play_stats_to_ini(stats: &HashMap<String, ChartPlayStats>) -> Ini {
+    let mut conf = Ini::new();
+    for (hash, s) in stats.iter() {
+        conf.set(hash, "PlayCount", Some(s.play_count.to_string()));
+        if let Some(t) = s.last_played {
+            conf.set(hash, "LastPlayed", Some(t.to_string()));
+        }
+    }
+    conf
+}
+
+fn load_play_stats() {
+    let mut stats = PLAY_STATS.lock().unwrap();
+    stats.clear();
+    if !Path::new(PLAY_STATS_INI_PATH).exists() {
+        return;
+    }
+    let text = match fs::read_to_string(PLAY_STATS_INI_PATH) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Failed to load '{}': {}", PLAY_STATS_INI_PATH, e);
+            return;
+        }
+    };
+    let mut conf = Ini::new();
+    if let Err(e) = conf.read(text.clone()) {
+        warn!("Failed to load '{}': {}", PLAY_STATS_INI_PATH, e);
+        return;
+    }
+    *stats = play_stats_from_ini(&conf);
+    info!("Loaded play stats for {} charts.", stats.len());
+
+    // Plays append a section each (see `append_play_stats`); fold the repeats away.
+    let headers = text.lines().filter(|line| line.trim_start().starts_with('[')).count();
+    let compacted = (headers > conf.sections().len()).then(|| play_stats_to_ini(&stats).write(PLAY_STATS_INI_PATH));
+    if let Some(Err(e)) = compacted {
+        warn!("Failed to save {}: {}", PLAY_STATS_INI_PATH, e);
+    }
+}
+
+/// Appends one chart's stats to stats.ini rather than rewriting every chart's.
+/// A later section for the same chart overrides earlier ones when loading.
+fn append_play_stats(hash: &str, stats: &ChartPlayStats) {
+    // Led by a newline in case the file doesn't end with one.
+    let mut section = format!("\n[{}]\nplaycount={}\n", hash, stats.play_count);
+    if let Some(t) = stats.last_played {
+        section.push_str(&format!("lastplayed={}\n", t));
+    }
+    let appended = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(PLAY_STATS_INI_PATH)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, section.as_bytes()));
+    if let Err(e) = appended {
+        warn!("Failed to save {}: {}", PLAY_STATS_INI_PATH, e);
+    }
+}
+
+/// Each preset gets its own section: `[<chart hash>]` with `Rate`, `Mini` and `Turn`.
+fn sticky_mods_from_ini(conf: &Ini) -> HashMap<String, ChartMods> {
+    let defaults = ChartMods::default();
+    conf.sections()
+        .into_iter()
+        .map(|hash| {
+            let mods = ChartMods {
+                rate: conf.get(&hash, "Rate").and_then(|v| v.parse().ok()).unwrap_or(defaults.rate),
+                mini: conf.get(&hash, "Mini").and_then(|v| v.parse().ok()).unwrap_or(defaults.mini),
+                turn: conf.get(&hash, "Turn").and_then(|v| Turn::from_str(&v).ok()).unwrap_or(defaults.turn),
+            }
+            .clamped();
+            (hash, mods)
+        })
+        .collect()
+}
+
+fn sticky_mods_to_ini(presets: &HashMap<String, ChartMods>) -> Ini {
+    let mut conf = Ini::new();
+    for (hash, mods) in presets.iter() {
+        conf.set(hash, "Rate", Some(format!("{:.2}", mods.rate)));
+        conf.set(hash, "Mini", Some(format!("{:.2}", mods.mini)));
+        conf.set(hash, "Turn", Some(mods.turn.to_string()));
+    }
+    conf
+}
+
+fn load_sticky_mods() {
+    let mut presets = STICKY_MODS.lock().unwrap();
+    presets.clear();
+    if !Path::new(STICKY_MODS_INI_PATH).exists() {
+        return;
+    }
+    let mut conf = Ini::new();
+    if let Err(e) = conf.load(STICKY_MODS_INI_PATH) {
+        warn!("Failed to load '{}': {}", STICKY_MODS_INI_PATH, e);
+        return;
+    }
+    *presets = sticky_mods_from_ini(&conf);
+    info!("Loaded sticky mods for {} charts.", presets.len());
+}
+
+fn save_sticky_mods() {
+    let presets = STICKY_MODS.lock().unwrap();
+    if let Err(e) = sticky_mods_to_ini(&presets).write(STICKY_MODS_INI_PATH) {
+        warn!("Failed to save {}: {}", STICKY_MODS_INI_PATH, e);
+    }
+}
+
+/// Profile sync's way into the play stats: `merge` gets them as stats.ini
+/// contents and returns true if it changed anything, in which case they're
+/// reloaded and saved. The lock is held throughout, so a play recorded at the
+/// same time is never lost. Returns the stats as they now stand.
+pub(crate) fn merge_play_stats(merge: impl FnOnce(&mut Ini) -> bool) -> Ini {
+    let mut stats = PLAY_STATS.lock().unwrap();
+    let mut conf = play_stats_to_ini(&stats);
+    if merge(&mut conf) {
+        *stats = play_stats_from_ini(&conf);
+        if let Err(e) = conf.write(PLAY_STATS_INI_PATH) {
+            warn!("Failed to save {}: {}", PLAY_STATS_INI_PATH, e);
+        }
+    }
+    conf
+}
+
+/// Like [`merge_play_stats`], for sticky_mods.ini.
+pub(crate) fn merge_sticky_mods(merge: impl FnOnce(&mut Ini) -> bool) -> Ini {
+    let mut presets = STICKY_MODS.lock().unwrap();
+    let mut conf = sticky_mods_to_ini(&presets);
+    if merge(&mut conf) {
+        *presets = sticky_mods_from_ini(&conf);
+        if let Err(e) = conf.write(STICKY_MODS_INI_PATH) {
+            warn!("Failed to save {}: {}", STICKY_MODS_INI_PATH, e);
+        }
+    }
+    conf
+}
+
 pub fn load() {
     if !Path::new(PROFILE_INI_PATH).exists() || !Path::new(GROOVESTATS_INI_PATH).exists() {
         if let Err(e) = create_default_files() {
@@ -219,6 +494,23 @@ pub fn load() {
                 .get("PlayerOptions", "BackgroundFilter")
                 .and_then(|s| BackgroundFilter::from_str(&s).ok())
                 .unwrap_or(default_profile.background_filter);
+            profile.judgment_position = profile_conf
+                .get("PlayerOptions", "JudgmentPosition")
+                .and_then(|s| JudgmentPosition::from_str(&s).ok())
+                .unwrap_or(default_profile.judgment_position);
+            profile.column_spacing = profile_conf
+                .get("PlayerOptions", "ColumnSpacing")
+                .and_then(|s| s.trim().trim_end_matches('%').trim().parse::<f32>().ok())
+                .filter(|v| v.is_finite())
+                .map_or(default_profile.column_spacing, |percent| clamp_column_spacing(percent / 100.0));
+            profile.rate_scaled_effects = profile_conf
+                .get("PlayerOptions", "RateScaledEffects")
+                .and_then(|v| v.parse::<u8>().ok())
+                .map_or(default_profile.rate_scaled_effects, |v| v != 0);
+            profile.fa_plus = profile_conf
+                .get("PlayerOptions", "FAPlus")
+                .and_then(|v| v.parse::<u8>().ok())
+                .map_or(default_profile.fa_plus, |v| v != 0);
             profile.scroll_speed = profile_conf
                 .get("PlayerOptions", "ScrollSpeed")
                 .and_then(|s| ScrollSpeedSetting::from_str(&s).ok())
@@ -256,9 +548,14 @@ pub fn load() {
         } else {
             None
         };
-        profile.avatar_texture_key = None;
+        // Keep an already-uploaded avatar texture across reloads (e.g. after a restore or sync).
+        if profile.avatar_path.is_none() {
+            profile.avatar_texture_key = None;
+        }
     } // Lock is released here.
 
+    load_play_stats();
+    load_sticky_mods();
     save_profile_ini();
     save_groovestats_ini();
     info!("Profile configuration files updated with default values for any missing fields.");
@@ -284,3 +581,104 @@ pub fn update_scroll_speed(setting: ScrollSpeedSetting) {
     }
     save_profile_ini();
 }
+
+pub fn update_judgment_position(position: JudgmentPosition) {
+    {
+        let mut profile = PROFILE.lock().unwrap();
+        if profile.judgment_position == position {
+            return;
+        }
+        profile.judgment_position = position;
+    }
+    save_profile_ini();
+}
+
+pub fn update_column_spacing(spacing: f32) {
+    let spacing = clamp_column_spacing(spacing);
+    {
+        let mut profile = PROFILE.lock().unwrap();
+        if (profile.column_spacing - spacing).abs() < 0.001 {
+            return;
+        }
+        profile.column_spacing = spacing;
+    }
+    save_profile_ini();
+}
+
+pub fn update_rate_scaled_effects(enabled: bool) {
+    {
+        let mut profile = PROFILE.lock().unwrap();
+        if profile.rate_scaled_effects == enabled {
+            return;
+        }
+        profile.rate_scaled_effects = enabled;
+    }
+    save_profile_ini();
+}
+
+pub fn update_fa_plus(enabled: bool) {
+    {
+        let mut profile = PROFILE.lock().unwrap();
+        if profile.fa_plus == enabled {
+            return;
+        }
+        profile.fa_plus = enabled;
+    }
+    save_profile_ini();
+}
+
+/// Snaps a column spacing multiplier to the options screen's steps.
+pub fn clamp_column_spacing(spacing: f32) -> f32 {
+    ((spacing / COLUMN_SPACING_STEP).round() * COLUMN_SPACING_STEP).clamp(MIN_COLUMN_SPACING, MAX_COLUMN_SPACING)
+}
+
+/// "100%" for 1.0, as shown on the options screen and saved in profile.ini.
+pub fn column_spacing_text(spacing: f32) -> String {
+    format!("{}%", (spacing * 100.0).round() as i32)
+}
+
+/// Sets the rate, mini and turn used for charts without a sticky preset.
+pub fn update_mods(mods: ChartMods) {
+    PROFILE.lock().unwrap().mods = mods;
+}
+
+/// The chart's sticky preset, if one was saved.
+pub fn sticky_mods(chart_hash: &str) -> Option<ChartMods> {
+    STICKY_MODS.lock().unwrap().get(&chart_hash.to_lowercase()).copied()
+}
+
+/// Saves `mods` as the chart's sticky preset, or removes the preset with None.
+pub fn set_sticky_mods(chart_hash: &str, mods: Option<ChartMods>) {
+    {
+        let mut presets = STICKY_MODS.lock().unwrap();
+        let hash = chart_hash.to_lowercase();
+        let changed = match mods {
+            Some(mods) => presets.insert(hash, mods) != Some(mods),
+            None => presets.remove(&hash).is_some(),
+        };
+        if !changed {
+            return;
+        }
+    }
+    save_sticky_mods();
+}
+
+pub fn get_play_stats(chart_hash: &str) -> ChartPlayStats {
+    PLAY_STATS
+        .lock()
+        .unwrap()
+        .get(&chart_hash.to_lowercase())
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Counts one play of the chart and stamps it with the current time.
+pub fn record_play(chart_hash: &str) {
+    let hash = chart_hash.to_lowercase();
+    let mut stats = PLAY_STATS.lock().unwrap();
+    let entry = stats.entry(hash.clone()).or_default();
+    entry.play_count += 1;
+    entry.last_played = Some(chrono::Local::now().timestamp());
+    // Under the lock, so a concurrent `merge_play_stats` rewrite can't drop it.
+    append_play_stats(&hash, entry);
+}
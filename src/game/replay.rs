@@ -0,0 +1,67 @@
+use crate::game::profile::PROFILE_DIR;
+use crate::game::scores;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// One file per chart and rate, holding the personal best run.
+const REPLAYS_DIR: &str = "replays";
+
+/// How a run's score built up: ITG grade points after every change, keyed by
+/// music time. Enough to race against, not to re-simulate the inputs.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Replay {
+    pub score_percent: f64,
+    pub possible_grade_points: i32,
+    /// (music time, earned grade points), in time order.
+    pub points: Vec<(f32, i32)>,
+}
+
+impl Replay {
+    /// Grade points the run had earned by `music_time`.
+    pub fn points_at(&self, music_time: f32) -> i32 {
+        let i = self.points.partition_point(|&(t, _)| t <= music_time);
+        if i == 0 { 0 } else { self.points[i - 1].1 }
+    }
+}
+
+fn replay_path(chart_hash: &str, rate: f32) -> PathBuf {
+    Path::new(PROFILE_DIR)
+        .join(REPLAYS_DIR)
+        .join(format!("{}.json", scores::rate_key(chart_hash, rate)))
+}
+
+/// The stored best run for a chart at `rate`, if there is one.
+pub fn load(chart_hash: &str, rate: f32) -> Option<Replay> {
+    let path = replay_path(chart_hash, rate);
+    let text = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<Replay>(&text) {
+        Ok(replay) => {
+            info!("Loaded ghost replay for chart {} ({:.2}%).", chart_hash, replay.score_percent * 100.0);
+            Some(replay)
+        }
+        Err(e) => {
+            warn!("Ignoring malformed replay '{}': {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Replaces the chart's stored replay at `rate`. Called when a run sets a new
+/// personal best.
+pub fn save(chart_hash: &str, rate: f32, replay: &Replay) {
+    let path = replay_path(chart_hash, rate);
+    if let Some(dir) = path.parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            warn!("Failed to create '{}': {}", dir.display(), e);
+            return;
+        }
+    }
+    let result = serde_json::to_string(replay)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to save replay '{}': {}", path.display(), e);
+    }
+}
@@ -1,10 +1,13 @@
 use crate::core::network;
-use crate::game::profile::Profile;
+use crate::game::judgment::{self, JudgeGrade};
+use crate::game::profile::{Profile, PROFILE_DIR};
+use configparser::ini::Ini;
 use log::{info, warn};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::error::Error;
+use std::path::Path;
 use std::sync::Mutex;
 
 const API_URL: &str = "https://api.groovestats.com/player-leaderboards.php";
@@ -52,6 +55,147 @@ pub fn set_cached_score(chart_hash: String, score: CachedScore) {
     GRADE_CACHE.lock().unwrap().insert(chart_hash, score);
 }
 
+// --- Local Personal Bests ---
+
+const LOCAL_SCORES_INI: &str = "scores.ini";
+
+/// Ini keys for each judgment count, in `JudgeGrade` order.
+const JUDGMENT_KEYS: [(JudgeGrade, &str); 6] = [
+    (JudgeGrade::Fantastic, "W1"),
+    (JudgeGrade::Excellent, "W2"),
+    (JudgeGrade::Great, "W3"),
+    (JudgeGrade::Decent, "W4"),
+    (JudgeGrade::WayOff, "W5"),
+    (JudgeGrade::Miss, "Miss"),
+];
+
+/// The player's best passing score on a chart, with the judgments that made it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalBest {
+    pub score_percent: f64, // 0.0 to 1.0
+    pub judgment_counts: HashMap<JudgeGrade, u32>,
+    /// Music rate the score was played at.
+    pub rate: f32,
+    /// Unix timestamp (seconds) of the play that set it.
+    pub achieved: i64,
+}
+
+// Loaded from disk on first use.
+static LOCAL_BESTS: Lazy<Mutex<Option<HashMap<String, LocalBest>>>> = Lazy::new(|| Mutex::new(None));
+
+fn local_scores_path() -> std::path::PathBuf {
+    Path::new(PROFILE_DIR).join(LOCAL_SCORES_INI)
+}
+
+/// Where a chart's records at `rate` are kept: the hash alone at 1.0, else the
+/// hash and rate (`1a2b3c@1.50`), so a rated run never replaces a 1.0 best.
+pub fn rate_key(chart_hash: &str, rate: f32) -> String {
+    let hash = chart_hash.to_lowercase();
+    if (rate - 1.0).abs() < 0.005 { hash } else { format!("{}@{:.2}", hash, rate) }
+}
+
+/// One section per chart hash and rate (see `rate_key`): `Score`, `Rate` (1.0
+/// when absent), `Achieved` (a Unix timestamp, 0 when absent) plus a count per
+/// judgment. Older files keyed by hash alone are re-keyed by their `Rate`.
+fn local_bests_from_ini(conf: &Ini) -> HashMap<String, LocalBest> {
+    let mut bests = HashMap::new();
+    for hash in conf.sections() {
+        // Older files kept unrounded percents; settle them like a fresh score
+        // so a replay of the same result ties rather than beats it.
+        let Some(score_percent) = conf.get(&hash, "Score").and_then(|v| v.parse::<f64>().ok()).map(judgment::quantize_score_percent) else {
+            continue;
+        };
+        let judgment_counts = JUDGMENT_KEYS
+            .iter()
+            .map(|(grade, key)| (*grade, conf.get(&hash, key).and_then(|v| v.parse().ok()).unwrap_or(0)))
+            .collect();
+        let rate = conf.get(&hash, "Rate").and_then(|v| v.parse().ok()).unwrap_or(1.0);
+        let achieved = conf.get(&hash, "Achieved").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let key = if hash.contains('@') { hash.clone() } else { rate_key(&hash, rate) };
+        let best = LocalBest { score_percent, judgment_counts, rate, achieved };
+        if bests.get(&key).is_none_or(|b: &LocalBest| best.score_percent > b.score_percent) {
+            bests.insert(key, best);
+        }
+    }
+    bests
+}
+
+fn local_bests_to_ini(bests: &HashMap<String, LocalBest>) -> Ini {
+    let mut conf = Ini::new();
+    for (hash, best) in bests {
+        conf.set(hash, "Score", Some(format!("{:.6}", best.score_percent)));
+        conf.set(hash, "Rate", Some(format!("{:.2}", best.rate)));
+        conf.set(hash, "Achieved", Some(best.achieved.to_string()));
+        for (grade, key) in JUDGMENT_KEYS {
+            let count = best.judgment_counts.get(&grade).copied().unwrap_or(0);
+            conf.set(hash, key, Some(count.to_string()));
+        }
+    }
+    conf
+}
+
+fn load_local_bests() -> HashMap<String, LocalBest> {
+    let path = local_scores_path();
+    if !path.exists() {
+        return HashMap::new();
+    }
+    let mut conf = Ini::new();
+    if let Err(e) = conf.load(&path) {
+        warn!("Failed to load '{}': {}", path.display(), e);
+        return HashMap::new();
+    }
+    local_bests_from_ini(&conf)
+}
+
+fn save_local_bests(bests: &HashMap<String, LocalBest>) {
+    let path = local_scores_path();
+    if let Err(e) = local_bests_to_ini(bests).write(&path) {
+        warn!("Failed to save {}: {}", path.display(), e);
+    }
+}
+
+/// Profile sync's way into the local bests, like `profile::merge_play_stats`:
+/// `merge` edits them as scores.ini contents and returns true if it changed
+/// anything. Returns the bests as they now stand.
+pub(crate) fn merge_local_bests(merge: impl FnOnce(&mut Ini) -> bool) -> Ini {
+    let mut guard = LOCAL_BESTS.lock().unwrap();
+    let bests = guard.get_or_insert_with(load_local_bests);
+    let mut conf = local_bests_to_ini(bests);
+    if merge(&mut conf) {
+        *bests = local_bests_from_ini(&conf);
+        save_local_bests(bests);
+    }
+    conf
+}
+
+/// The stored best for a chart at `rate`; a best at another rate doesn't count.
+pub fn get_local_best(chart_hash: &str, rate: f32) -> Option<LocalBest> {
+    let mut guard = LOCAL_BESTS.lock().unwrap();
+    guard.get_or_insert_with(load_local_bests).get(&rate_key(chart_hash, rate)).cloned()
+}
+
+/// Compares `score` with the stored best for the chart at the same rate and keeps
+/// whichever is higher. Returns the best as it was *before* this call, so callers
+/// can show the improvement.
+pub fn submit_local_score(chart_hash: &str, score: LocalBest) -> Option<LocalBest> {
+    let mut guard = LOCAL_BESTS.lock().unwrap();
+    let bests = guard.get_or_insert_with(load_local_bests);
+    let key = rate_key(chart_hash, score.rate);
+    let previous = bests.get(&key).cloned();
+    if previous.as_ref().map_or(true, |p| score.score_percent > p.score_percent) {
+        info!(
+            "New personal best {:.2}% at {:.2}x on chart {} (was {:?})",
+            score.score_percent * 100.0,
+            score.rate,
+            chart_hash,
+            previous.as_ref().map(|p| p.score_percent * 100.0)
+        );
+        bests.insert(key, score);
+        save_local_bests(bests);
+    }
+    previous
+}
+
 // --- API Response Structs ---
 
 #[derive(Deserialize, Debug)]
@@ -132,7 +276,8 @@ pub fn fetch_and_store_grade(profile: Profile, chart_hash: String) -> Result<(),
         let grade = score_to_grade(score_data.score);
         let cached_score = CachedScore {
             grade,
-            score_percent: score_data.score / 10000.0,
+            // GrooveStats already sends hundredths of a percent.
+            score_percent: score_data.score.round() / judgment::SCORE_SCALE as f64,
         };
         set_cached_score(chart_hash, cached_score);
     } else {
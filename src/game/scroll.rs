@@ -134,6 +134,25 @@ impl ScrollSpeedSetting {
         }
     }
 
+    /// Same kind of modifier, slowed down so the song's reference BPM scrolls no
+    /// faster than `max_bpm`. Settings already under the cap are returned as is.
+    pub fn capped(self, max_bpm: f32, reference_bpm: f32) -> Self {
+        if self.effective_bpm(reference_bpm, reference_bpm) <= max_bpm {
+            return self;
+        }
+        match self {
+            ScrollSpeedSetting::CMod(_) => ScrollSpeedSetting::CMod(max_bpm),
+            ScrollSpeedSetting::MMod(_) => ScrollSpeedSetting::MMod(max_bpm),
+            ScrollSpeedSetting::XMod(_) => {
+                if reference_bpm > 0.0 {
+                    ScrollSpeedSetting::XMod(max_bpm / reference_bpm)
+                } else {
+                    self
+                }
+            }
+        }
+    }
+
     pub fn travel_time_seconds(
         self,
         draw_distance: f32,
@@ -1,15 +1,78 @@
 use crate::game::chart::ChartData;
+use crate::game::game_type::GameType;
 use once_cell::sync::Lazy;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+/// A problem with one of a song's files, found while scanning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetIssue {
+    MissingMusic,
+    UnreadableMusic,
+    BrokenBanner,
+    BrokenBackground,
+}
+
+impl AssetIssue {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::MissingMusic => "music file is missing",
+            Self::UnreadableMusic => "music file could not be decoded",
+            Self::BrokenBanner => "banner image is missing or corrupt",
+            Self::BrokenBackground => "background image is missing or corrupt",
+        }
+    }
+
+    /// Music problems make the song unplayable; image problems only cost visuals.
+    pub fn blocks_play(self) -> bool {
+        matches!(self, Self::MissingMusic | Self::UnreadableMusic)
+    }
+
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::MissingMusic),
+            1 => Some(Self::UnreadableMusic),
+            2 => Some(Self::BrokenBanner),
+            3 => Some(Self::BrokenBackground),
+            _ => None,
+        }
+    }
+}
+
+/// What follows a title that other songs share (or nearly share) so they can
+/// be told apart. Set after the scan, never cached.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Disambiguator {
+    Artist,
+    Pack(String),
+    ArtistAndPack(String),
+    /// Pack and song folder, for same-titled songs by one artist in one pack.
+    Folder(String),
+}
+
 #[derive(Clone, Debug)]
 pub struct SongData {
+    /// The .sm/.ssc file the song was loaded from.
+    pub simfile_path: PathBuf,
     pub title: String,
     pub subtitle: String,
     pub artist: String,
+    pub title_translit: String,
+    pub subtitle_translit: String,
+    pub artist_translit: String,
     pub banner_path: Option<PathBuf>,
     pub background_path: Option<PathBuf>,
+    /// First video in #BGCHANGES (or a video #BACKGROUND) and the beat it starts
+    /// on. `background_path` is then the still shown around it.
+    pub background_video: Option<PathBuf>,
+    pub background_video_beat: f32,
+    /// #PREVIEWVID, or a #JACKET that moves (a video, GIF or APNG). Song select
+    /// shows it, muted and looping, in place of the banner.
+    pub preview_path: Option<PathBuf>,
     pub music_path: Option<PathBuf>,
     pub display_bpm: String,
     pub offset: f32,
@@ -24,12 +87,23 @@ pub struct SongData {
     pub normalized_speeds: String,
     pub normalized_scrolls: String,
     pub total_length_seconds: i32,
+    /// ReplayGain-style correction for the music file, found during the cache build.
+    pub music_gain_db: Option<f32>,
+    /// Validation results from the scan; empty when every referenced file is usable.
+    pub asset_issues: Vec<AssetIssue>,
+    /// Set when another song's title matches this one's; see `parsing::duplicate_titles`.
+    pub disambiguator: Option<Disambiguator>,
     pub charts: Vec<ChartData>,
 }
 
 #[derive(Clone, Debug)]
 pub struct SongPack {
+    /// Folder name; identifies the pack (offsets, wheel state), never changes.
     pub name: String,
+    /// Name shown on the wheel, from Group.ini's DisplayTitle or the folder name.
+    pub display_name: String,
+    pub banner_path: Option<PathBuf>,
+    pub color: Option<[f32; 4]>,
     pub songs: Vec<Arc<SongData>>,
 }
 
@@ -45,7 +119,79 @@ pub(super) fn set_song_cache(packs: Vec<SongPack>) {
     *SONG_CACHE.lock().unwrap() = packs;
 }
 
+#[inline(always)]
+fn pick_translit<'a>(native: &'a str, translit: &'a str, prefer_translit: bool) -> &'a str {
+    if prefer_translit && !translit.trim().is_empty() { translit } else { native }
+}
+
 impl SongData {
+    /// The chart at `difficulty` (a `FILE_DIFFICULTY_NAMES` entry) played on `game_type`.
+    pub fn chart_for(&self, game_type: GameType, difficulty: &str) -> Option<&ChartData> {
+        self.charts.iter().find(|c| {
+            c.chart_type.eq_ignore_ascii_case(game_type.chart_type()) && c.difficulty.eq_ignore_ascii_case(difficulty)
+        })
+    }
+
+    /// Whether any chart is played on `game_type`.
+    pub fn has_charts_for(&self, game_type: GameType) -> bool {
+        self.charts.iter().any(|c| c.chart_type.eq_ignore_ascii_case(game_type.chart_type()))
+    }
+
+    /// First issue that keeps the song from being played, if any.
+    pub fn play_blocker(&self) -> Option<AssetIssue> {
+        self.asset_issues.iter().copied().find(|issue| issue.blocks_play())
+    }
+
+    /// Gain to play the music at; zero when normalization is off or the file wasn't scanned.
+    pub fn playback_gain_db(&self, normalize: bool) -> f32 {
+        if normalize { self.music_gain_db.unwrap_or(0.0) } else { 0.0 }
+    }
+
+    /// Title as it should be shown: the #TITLETRANSLIT when transliterations are
+    /// preferred and the simfile has one, the native #TITLE otherwise.
+    pub fn display_title(&self, prefer_translit: bool) -> &str {
+        pick_translit(&self.title, &self.title_translit, prefer_translit)
+    }
+
+    pub fn display_subtitle(&self, prefer_translit: bool) -> &str {
+        pick_translit(&self.subtitle, &self.subtitle_translit, prefer_translit)
+    }
+
+    pub fn display_artist(&self, prefer_translit: bool) -> &str {
+        pick_translit(&self.artist, &self.artist_translit, prefer_translit)
+    }
+
+    /// Title used for ordering and wheel index letters. Always the transliteration
+    /// when there is one, so native Japanese titles sort among the Latin ones.
+    pub fn sort_title(&self) -> &str {
+        self.display_title(true)
+    }
+
+    /// "Title Subtitle", or just the title when there is no subtitle.
+    pub fn display_full_title(&self, prefer_translit: bool) -> String {
+        let title = self.display_title(prefer_translit);
+        let subtitle = self.display_subtitle(prefer_translit);
+        if subtitle.trim().is_empty() {
+            title.to_string()
+        } else {
+            format!("{} {}", title, subtitle)
+        }
+    }
+
+    /// `label` (this song's title as shown) followed by what tells it apart from
+    /// songs with the same title, e.g. "MAX 300 (Omega)".
+    pub fn disambiguated(&self, label: &str, prefer_translit: bool) -> String {
+        match &self.disambiguator {
+            None => label.to_string(),
+            Some(Disambiguator::Artist) => format!("{} ({})", label, self.display_artist(prefer_translit)),
+            Some(Disambiguator::Pack(pack)) => format!("{} ({})", label, pack),
+            Some(Disambiguator::ArtistAndPack(pack)) => {
+                format!("{} ({}, {})", label, self.display_artist(prefer_translit), pack)
+            }
+            Some(Disambiguator::Folder(folder)) => format!("{} ({})", label, folder),
+        }
+    }
+
     /// Formats the display BPM for the UI, prioritizing #DISPLAYBPM and cleaning up the format
     /// to match ITGmania (e.g., "128" instead of "128.000000"). Falls back to the
     /// calculated min-max range if #DISPLAYBPM is absent or set to "*".
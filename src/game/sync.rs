@@ -0,0 +1,486 @@
+use crate::core::network;
+use crate::game::profile::{self, PROFILE_DIR};
+use crate::game::scores;
+use configparser::ini::Ini;
+use log::{info, warn};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// --- Sync Settings ---
+const SYNC_INI_PATH: &str = "save/sync.ini";
+const MANIFEST_NAME: &str = "manifest.ini";
+// Read before `PasswordCommand`; the password itself never goes in sync.ini.
+const PASSWORD_ENV: &str = "DEADSYNC_SYNC_PASSWORD";
+// How long quitting waits for the last sync before giving up on it.
+const EXIT_SYNC_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Runs a merge of the remote records into a record file's loaded data; see
+// `profile::merge_play_stats`.
+type RecordMerge = fn(&dyn Fn(&mut Ini) -> bool) -> Ini;
+
+/// How a synced file is reconciled with the remote copy.
+#[derive(Clone, Copy)]
+enum Merge {
+    /// Whichever side changed the file last wins, going by the manifest.
+    WholeFile,
+    /// One record per section, merged one by one through the loaded data.
+    /// Conflicting records are settled by the named timestamp key when both
+    /// have it, otherwise by which side changed the file last.
+    Records { stamp: Option<&'static str>, merge: RecordMerge },
+}
+
+fn merge_play_stats(f: &dyn Fn(&mut Ini) -> bool) -> Ini {
+    profile::merge_play_stats(f)
+}
+
+fn merge_sticky_mods(f: &dyn Fn(&mut Ini) -> bool) -> Ini {
+    profile::merge_sticky_mods(f)
+}
+
+fn merge_local_bests(f: &dyn Fn(&mut Ini) -> bool) -> Ini {
+    scores::merge_local_bests(f)
+}
+
+/// Files inside the profile directory that are mirrored to the remote endpoint.
+/// Records are only ever added or replaced, so a sticky preset removed on one
+/// machine comes back from the others.
+const SYNCED_FILES: &[(&str, Merge)] = &[
+    ("profile.ini", Merge::WholeFile),
+    ("groovestats.ini", Merge::WholeFile),
+    ("profile.png", Merge::WholeFile),
+    ("stats.ini", Merge::Records { stamp: Some("LastPlayed"), merge: merge_play_stats }),
+    ("scores.ini", Merge::Records { stamp: Some("Achieved"), merge: merge_local_bests }),
+    ("sticky_mods.ini", Merge::Records { stamp: None, merge: merge_sticky_mods }),
+];
+
+// One sync at a time; the exit sync waits for a startup sync still running.
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+// Set when a whole profile file was pulled; the app reloads the profile once
+// it's somewhere the profile can change under it.
+static PROFILE_RELOAD: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Backend {
+    /// PUT/GET against a WebDAV folder, with HTTP basic auth.
+    #[default]
+    WebDav,
+    /// An S3-compatible bucket (path-style URL), with SigV4-signed requests.
+    S3,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "webdav" => Ok(Self::WebDav),
+            "s3" => Ok(Self::S3),
+            other => Err(format!("'{}' is not a valid sync Backend setting", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SyncSettings {
+    enabled: bool,
+    backend: Backend,
+    /// The WebDAV folder, or `https://<endpoint>/<bucket>[/<prefix>]` for S3.
+    url: String,
+    /// WebDAV user name, or the S3 access key ID.
+    username: String,
+    /// WebDAV password, or the S3 secret key; from the environment or `PasswordCommand`.
+    password: String,
+    region: String,
+}
+
+fn create_default_sync_ini() -> Result<(), std::io::Error> {
+    info!("'{}' not found, creating with sync disabled.", SYNC_INI_PATH);
+    if let Some(parent) = Path::new(SYNC_INI_PATH).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut conf = Ini::new();
+    conf.set("ProfileSync", "Enabled", Some("0".to_string()));
+    conf.set("ProfileSync", "Backend", Some("WebDav".to_string()));
+    conf.set("ProfileSync", "Url", Some("".to_string()));
+    conf.set("ProfileSync", "Username", Some("".to_string()));
+    conf.set("ProfileSync", "PasswordCommand", Some("".to_string()));
+    conf.set("ProfileSync", "Region", Some("us-east-1".to_string()));
+    conf.write(SYNC_INI_PATH)
+}
+
+/// The password from `DEADSYNC_SYNC_PASSWORD`, else the first line printed by
+/// `PasswordCommand` (e.g. `pass show deadsync` or `secret-tool lookup ...`).
+fn resolve_password(command: &str) -> String {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return password;
+    }
+    let command = command.trim();
+    if command.is_empty() {
+        return String::new();
+    }
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    };
+    match output {
+        Ok(out) if out.status.success() => {
+            String::from_utf8_lossy(&out.stdout).lines().next().unwrap_or_default().to_string()
+        }
+        Ok(out) => {
+            warn!("Sync PasswordCommand exited with {}.", out.status);
+            String::new()
+        }
+        Err(e) => {
+            warn!("Failed to run sync PasswordCommand: {}", e);
+            String::new()
+        }
+    }
+}
+
+fn load_settings() -> SyncSettings {
+    if !Path::new(SYNC_INI_PATH).exists() {
+        if let Err(e) = create_default_sync_ini() {
+            warn!("Failed to create '{}': {}", SYNC_INI_PATH, e);
+        }
+        return SyncSettings::default();
+    }
+
+    let mut conf = Ini::new();
+    if let Err(e) = conf.load(SYNC_INI_PATH) {
+        warn!("Failed to load '{}': {}", SYNC_INI_PATH, e);
+        return SyncSettings::default();
+    }
+    // Older versions kept the password here in plain text; drop it.
+    if conf.remove_key("ProfileSync", "Password").is_some() {
+        warn!("Removed the plain-text Password from '{}'; set {} or PasswordCommand instead.", SYNC_INI_PATH, PASSWORD_ENV);
+        if let Err(e) = conf.write(SYNC_INI_PATH) {
+            warn!("Failed to save '{}': {}", SYNC_INI_PATH, e);
+        }
+    }
+    let enabled = conf
+        .get("ProfileSync", "Enabled")
+        .and_then(|v| v.parse::<u8>().ok())
+        .map_or(false, |v| v != 0);
+    SyncSettings {
+        enabled,
+        backend: conf
+            .get("ProfileSync", "Backend")
+            .and_then(|v| Backend::from_str(&v).ok())
+            .unwrap_or_default(),
+        url: conf
+            .get("ProfileSync", "Url")
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_string(),
+        username: conf.get("ProfileSync", "Username").unwrap_or_default(),
+        // Only run the command when it will be used.
+        password: if enabled {
+            resolve_password(&conf.get("ProfileSync", "PasswordCommand").unwrap_or_default())
+        } else {
+            String::new()
+        },
+        region: conf
+            .get("ProfileSync", "Region")
+            .filter(|r| !r.trim().is_empty())
+            .unwrap_or_else(|| "us-east-1".to_string()),
+    }
+}
+
+/* ------------------------------ HTTP helpers ------------------------------ */
+
+fn base64_encode(input: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        out.push(TABLE[(n >> 18) as usize & 63] as char);
+        out.push(TABLE[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 { TABLE[(n >> 6) as usize & 63] as char } else { '=' });
+        out.push(if chunk.len() > 2 { TABLE[n as usize & 63] as char } else { '=' });
+    }
+    out
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encodes a URL path the way SigV4 expects, keeping the slashes.
+fn uri_encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'/') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// (`scheme://host`, `host`, `/path`) of a URL.
+fn split_url(url: &str) -> (&str, &str, &str) {
+    let host_start = url.find("://").map_or(0, |i| i + 3);
+    let path_start = url[host_start..].find('/').map_or(url.len(), |i| host_start + i);
+    (&url[..path_start], &url[host_start..path_start], &url[path_start..])
+}
+
+/// Headers for an S3 request signed with AWS Signature Version 4. `url` must
+/// already have its path encoded.
+fn s3_headers(settings: &SyncSettings, method: &str, url: &str, body: &[u8]) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    use hmac_sha256::{Hash, HMAC};
+
+    let (_, host, path) = split_url(url);
+    let path = if path.is_empty() { "/" } else { path };
+    if host.is_empty() {
+        return Err(format!("'{}' is not a valid S3 URL", url).into());
+    }
+    let now = chrono::Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(&Hash::hash(body));
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method, path, host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, settings.region);
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex(&Hash::hash(canonical_request.as_bytes())));
+
+    let k_date = HMAC::mac(date.as_bytes(), format!("AWS4{}", settings.password).as_bytes());
+    let k_region = HMAC::mac(settings.region.as_bytes(), k_date);
+    let k_service = HMAC::mac(b"s3", k_region);
+    let k_signing = HMAC::mac(b"aws4_request", k_service);
+    let signature = hex(&HMAC::mac(string_to_sign.as_bytes(), k_signing));
+
+    Ok(vec![
+        ("x-amz-date".to_string(), amz_date),
+        ("x-amz-content-sha256".to_string(), payload_hash),
+        (
+            "Authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                settings.username, scope, signed_headers, signature
+            ),
+        ),
+    ])
+}
+
+fn auth_headers(settings: &SyncSettings, method: &str, url: &str, body: &[u8]) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    match settings.backend {
+        Backend::WebDav if settings.username.is_empty() => Ok(Vec::new()),
+        Backend::WebDav => {
+            let creds = format!("{}:{}", settings.username, settings.password);
+            Ok(vec![("Authorization".to_string(), format!("Basic {}", base64_encode(creds.as_bytes())))])
+        }
+        Backend::S3 => s3_headers(settings, method, url, body),
+    }
+}
+
+fn remote_url(settings: &SyncSettings, name: &str) -> String {
+    match settings.backend {
+        Backend::WebDav => format!("{}/{}", settings.url, name),
+        // The signature covers the encoded path, so send exactly that.
+        Backend::S3 => {
+            let (origin, _, path) = split_url(&settings.url);
+            format!("{}{}", origin, uri_encode_path(&format!("{}/{}", path, name)))
+        }
+    }
+}
+
+/// Downloads `<url>/<name>`. A 404 is not an error; it simply means the remote
+/// has never received that file.
+fn remote_get(settings: &SyncSettings, name: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let url = remote_url(settings, name);
+    let mut req = network::get_agent().get(&url);
+    for (key, value) in auth_headers(settings, "GET", &url, &[])? {
+        req = req.header(key, value);
+    }
+    match req.call() {
+        Ok(resp) => Ok(Some(resp.into_body().read_to_vec()?)),
+        Err(ureq::Error::StatusCode(404)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn remote_put(settings: &SyncSettings, name: &str, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let url = remote_url(settings, name);
+    let mut req = network::get_agent().put(&url);
+    for (key, value) in auth_headers(settings, "PUT", &url, data)? {
+        req = req.header(key, value);
+    }
+    req.send(data)?;
+    Ok(())
+}
+
+/* --------------------------------- sync ---------------------------------- */
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn local_modified(path: &Path) -> Option<u64> {
+    fs::metadata(path).and_then(|m| m.modified()).ok().map(unix_secs)
+}
+
+/// Writes through a temporary file, so a sync cut short at exit never leaves
+/// half a profile file behind.
+fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("sync-tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+fn parse_ini(bytes: &[u8]) -> Result<Ini, Box<dyn Error>> {
+    let mut conf = Ini::new();
+    conf.read(String::from_utf8_lossy(bytes).into_owned())?;
+    Ok(conf)
+}
+
+/// Copies every record of `remote` that `local` lacks, or that beats the local
+/// one: by `stamp` when both records carry it, else by `remote_newer`.
+/// Returns true if `local` changed.
+fn merge_records(local: &mut Ini, remote: &Ini, stamp: Option<&str>, remote_newer: bool) -> bool {
+    let Some(remote_map) = remote.get_map() else { return false };
+    let local_map = local.get_map().unwrap_or_default();
+    let mut changed = false;
+    for (section, remote_record) in remote_map {
+        let remote_wins = match local_map.get(&section) {
+            None => true,
+            Some(local_record) if *local_record == remote_record => false,
+            Some(_) => {
+                let stamps = stamp.and_then(|key| {
+                    let l = local.get(&section, key)?.parse::<i64>().ok()?;
+                    let r = remote.get(&section, key)?.parse::<i64>().ok()?;
+                    Some((l, r))
+                });
+                match stamps {
+                    Some((l, r)) if l != r => r > l,
+                    _ => remote_newer,
+                }
+            }
+        };
+        if remote_wins {
+            local.remove_section(&section);
+            for (key, value) in remote_record {
+                local.set(&section, &key, value);
+            }
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Reconciles the local profile with the remote endpoint. The remote manifest
+/// keeps each file's last-modified time as of its last push. Whole files go to
+/// whichever side changed them last; record files are merged record by record
+/// into the loaded data, so nothing recorded meanwhile is overwritten.
+fn sync_once(settings: &SyncSettings) -> Result<(), Box<dyn Error>> {
+    let mut manifest = Ini::new();
+    if let Some(bytes) = remote_get(settings, MANIFEST_NAME)? {
+        manifest = parse_ini(&bytes)?;
+    }
+    let mut manifest_dirty = false;
+    fs::create_dir_all(PROFILE_DIR)?;
+
+    for &(name, merge) in SYNCED_FILES {
+        let local_path = Path::new(PROFILE_DIR).join(name);
+        let local_m = local_modified(&local_path);
+        let remote_m = manifest.get("Files", name).and_then(|v| v.parse::<u64>().ok());
+
+        match merge {
+            Merge::WholeFile => match (local_m, remote_m) {
+                (l, Some(r)) if l.map_or(true, |l| r > l) => {
+                    let Some(data) = remote_get(settings, name)? else {
+                        warn!("Remote manifest lists '{}' but the file is missing.", name);
+                        continue;
+                    };
+                    write_atomically(&local_path, &data)?;
+                    // Stamp the local copy with the remote time so the next pass sees them as equal.
+                    fs::File::options()
+                        .write(true)
+                        .open(&local_path)?
+                        .set_modified(UNIX_EPOCH + Duration::from_secs(r))?;
+                    info!("Profile sync: pulled '{}'.", name);
+                    PROFILE_RELOAD.store(true, Ordering::Relaxed);
+                }
+                (Some(l), r) if r.map_or(true, |r| l > r) => {
+                    remote_put(settings, name, &fs::read(&local_path)?)?;
+                    manifest.set("Files", name, Some(l.to_string()));
+                    manifest_dirty = true;
+                    info!("Profile sync: pushed '{}'.", name);
+                }
+                _ => {}
+            },
+            Merge::Records { stamp, merge } => {
+                let remote = match remote_get(settings, name)? {
+                    Some(bytes) => parse_ini(&bytes)?,
+                    None => Ini::new(),
+                };
+                let remote_newer = remote_m.is_some_and(|r| local_m.is_none_or(|l| r > l));
+                let merged = merge(&|local| merge_records(local, &remote, stamp, remote_newer));
+                if merged.get_map().unwrap_or_default() != remote.get_map().unwrap_or_default() {
+                    remote_put(settings, name, merged.writes().as_bytes())?;
+                    let stamp = local_modified(&local_path).unwrap_or_else(|| unix_secs(SystemTime::now()));
+                    manifest.set("Files", name, Some(stamp.to_string()));
+                    manifest_dirty = true;
+                    info!("Profile sync: merged and pushed '{}'.", name);
+                }
+            }
+        }
+    }
+
+    if manifest_dirty {
+        remote_put(settings, MANIFEST_NAME, manifest.writes().as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Runs a profile sync if one is configured in `save/sync.ini`. Blocks on
+/// network I/O, so it only runs on the worker threads below.
+fn sync_now() {
+    let _guard = SYNC_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let settings = load_settings();
+    if !settings.enabled || settings.url.is_empty() {
+        return;
+    }
+    match sync_once(&settings) {
+        Ok(()) => info!("Profile sync complete."),
+        Err(e) => warn!("Profile sync with '{}' failed: {}", settings.url, e),
+    }
+}
+
+/// Syncs on a worker thread. Call after `profile::load`: records are merged
+/// into the loaded profile, not read from under it.
+pub fn start_background_sync() {
+    thread::spawn(sync_now);
+}
+
+/// Syncs once more before quitting, waiting at most `EXIT_SYNC_TIMEOUT`; an
+/// unreachable endpoint must not hold up closing the game.
+pub fn sync_before_exit() {
+    let (done_tx, done_rx) = mpsc::channel();
+    thread::spawn(move || {
+        sync_now();
+        let _ = done_tx.send(());
+    });
+    if done_rx.recv_timeout(EXIT_SYNC_TIMEOUT).is_err() {
+        warn!("Profile sync didn't finish within {:?}; quitting without it.", EXIT_SYNC_TIMEOUT);
+    }
+}
+
+/// True once after a sync pulled profile.ini, groovestats.ini or the avatar;
+/// the caller should then `profile::load()` again.
+pub fn take_profile_reload() -> bool {
+    PROFILE_RELOAD.swap(false, Ordering::Relaxed)
+}
@@ -7,13 +7,31 @@ mod game;
 mod assets;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    core::logging::init();
+    let benchmark = std::env::args().any(|arg| arg == "--benchmark");
+    // A second launch only passes its simfile argument on to the running window.
+    // Benchmarks run alongside whatever is open.
+    if !benchmark && !core::instance::claim(std::env::args_os().nth(1).map(std::path::PathBuf::from)) {
+        return Ok(());
+    }
+    let safe_mode = core::safe_mode::begin_launch();
     config::load();
+    if safe_mode {
+        config::apply_safe_mode();
+    }
+    game::backup::create_startup_backup(config::get().profile_backups);
     game::profile::load();
-    if let Err(e) = core::audio::init() {
+    if safe_mode {
+        core::audio::disable();
+    } else if let Err(e) = core::audio::init() {
         // The game can run without audio; log the error and continue.
         log::error!("Failed to initialize audio engine: {}", e);
     }
     core::network::init();
-    // env_logger is initialized in app::run()
-    app::run()
+    if config::get().check_for_updates {
+        core::updates::init();
+    }
+    core::ipc::init(config::get().event_server_port);
+    game::sync::start_background_sync();
+    app::run(benchmark)
 }
@@ -1,9 +1,11 @@
 use crate::act;
 use crate::core::space::*;
 use crate::screens::{Screen, ScreenAction};
+use crate::core::gfx::BlendMode;
 use crate::ui::actors::{Actor, SizeSpec};
+use crate::ui::layer;
 use crate::ui::color;
-use crate::ui::components::{heart_bg, pad_display, screen_bar};
+use crate::ui::components::{banner, density_graph, heart_bg, pad_display, screen_bar};
 use crate::ui::components::screen_bar::{AvatarParams, ScreenBarParams, ScreenBarPosition, ScreenBarTitlePlacement};
 use crate::core::space::widescale;
 
@@ -11,10 +13,12 @@ use crate::game::judgment::{self, JudgeGrade};
 use crate::screens::gameplay;
 use crate::game::song::SongData;
 use crate::game::chart::ChartData;
-use crate::game::scores;
+use crate::game::game_type::GameType;
+use crate::game::{replay, scores};
 use std::collections::HashMap;
 use std::sync::{Arc, LazyLock};
 use crate::game::scroll::ScrollSpeedSetting;
+use crate::game::mods::ChartMods;
 use crate::assets::AssetManager;
 use crate::ui::font;
 
@@ -36,6 +40,7 @@ pub struct ScoreInfo {
     pub score_percent: f64,
     pub grade: scores::Grade,
     pub speed_mod: ScrollSpeedSetting,
+    pub mods: ChartMods,
     pub hands_achieved: u32,
     pub holds_held: u32,
     pub holds_total: u32,
@@ -43,6 +48,18 @@ pub struct ScoreInfo {
     pub rolls_total: u32,
     pub mines_avoided: u32,
     pub mines_total: u32,
+    pub life_history: Vec<(f32, f32)>,
+    /// Music time the life graph spans, in seconds.
+    pub graph_duration: f32,
+    pub tournament_mode: bool,
+    /// Set when this play beat the stored personal best.
+    pub record: Option<RecordBreak>,
+}
+
+/// A new personal best, and what it replaced (None on a chart's first clear).
+#[derive(Clone, Debug)]
+pub struct RecordBreak {
+    pub previous: Option<scores::LocalBest>,
 }
 
 pub struct State {
@@ -50,7 +67,8 @@ pub struct State {
     bg: heart_bg::State,
     pub session_elapsed: f32, // To display the timer
     pub score_info: Option<ScoreInfo>,
-    pub density_graph_texture_key: String,
+    /// Time since the screen opened; drives the record-break animation.
+    elapsed: f32,
 }
 
 pub fn init(gameplay_results: Option<gameplay::State>) -> State {
@@ -69,6 +87,33 @@ pub fn init(gameplay_results: Option<gameplay::State>) -> State {
             scores::score_to_grade(score_percent * 10000.0)
         };
 
+        // Only cleared plays count toward the play count and last-played date.
+        if grade != scores::Grade::Failed {
+            profile::record_play(&gs.chart.short_hash);
+        }
+
+        // Look up the old best before it is overwritten, so the delta can be shown.
+        let record = if grade == scores::Grade::Failed {
+            None
+        } else {
+            let submitted = scores::LocalBest {
+                score_percent,
+                judgment_counts: gs.judgment_counts.clone(),
+                rate: gs.mods.rate,
+                achieved: Local::now().timestamp(),
+            };
+            let previous = scores::submit_local_score(&gs.chart.short_hash, submitted);
+            let is_record = previous.as_ref().map_or(true, |p| score_percent > p.score_percent);
+            if is_record {
+                replay::save(&gs.chart.short_hash, gs.mods.rate, &replay::Replay {
+                    score_percent,
+                    possible_grade_points: gs.possible_grade_points,
+                    points: gs.score_history.clone(),
+                });
+            }
+            is_record.then_some(RecordBreak { previous })
+        };
+
         ScoreInfo {
             song: gs.song.clone(),
             chart: gs.chart.clone(),
@@ -76,6 +121,7 @@ pub fn init(gameplay_results: Option<gameplay::State>) -> State {
             score_percent,
             grade,
             speed_mod: gs.scroll_speed,
+            mods: gs.mods,
             hands_achieved: gs.hands_achieved,
             holds_held: gs.holds_held,
             holds_total: gs.holds_total,
@@ -83,6 +129,10 @@ pub fn init(gameplay_results: Option<gameplay::State>) -> State {
             rolls_total: gs.rolls_total,
             mines_avoided: gs.mines_avoided,
             mines_total: gs.mines_total,
+            life_history: gs.life_history.clone(),
+            graph_duration: gs.music_end_time,
+            tournament_mode: gs.tournament_mode,
+            record,
         }
     });
 
@@ -91,7 +141,7 @@ pub fn init(gameplay_results: Option<gameplay::State>) -> State {
         bg: heart_bg::State::new(),
         session_elapsed: 0.0,
         score_info,
-        density_graph_texture_key: "__white".to_string(),
+        elapsed: 0.0,
     }
 }
 
@@ -104,16 +154,36 @@ pub fn handle_key_press(_state: &mut State, event: &KeyEvent) -> ScreenAction {
     ScreenAction::None
 }
 
-// This screen doesn't have any dynamic state updates yet, but we keep the function for consistency.
-pub fn update(_state: &mut State, _dt: f32) {
-    //
+pub fn update(state: &mut State, dt: f32) {
+    state.elapsed += dt;
+}
+
+// --- Record-break animation timing ---
+const RECORD_DELAY: f32 = 0.6;
+const RECORD_COUNT_UP: f32 = 0.8;
+
+/// 0 -> 1 over the count-up, after the initial delay, eased out.
+fn record_progress(elapsed: f32) -> f32 {
+    let t = ((elapsed - RECORD_DELAY) / RECORD_COUNT_UP).clamp(0.0, 1.0);
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Judgment delta text and whether the change is an improvement
+/// (more of the top three windows, fewer of the rest).
+fn judgment_delta(grade: JudgeGrade, now: u32, before: u32) -> Option<(String, bool)> {
+    if now == before {
+        return None;
+    }
+    let diff = now as i64 - before as i64;
+    let more_is_better = grade <= JudgeGrade::Great;
+    Some((format!("{:+}", diff), (diff > 0) == more_is_better))
 }
 
 pub fn in_transition() -> (Vec<Actor>, f32) {
     let actor = act!(quad:
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
-        diffuse(0.0, 0.0, 0.0, 1.0): z(1100):
+        diffuse(0.0, 0.0, 0.0, 1.0): z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -125,7 +195,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -172,6 +242,9 @@ static JUDGMENT_INFO: LazyLock<HashMap<JudgeGrade, JudgmentDisplayInfo>> = LazyL
     ])
 });
 
+const DELTA_BETTER: [f32; 4] = [0.4, 1.0, 0.4, 1.0];
+const DELTA_WORSE: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+
 /// Builds the entire P1 (left side) stats pane including judgments and radar counts.
 fn build_p1_stats_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     let Some(score_info) = &state.score_info else { return vec![]; };
@@ -244,6 +317,18 @@ fn build_p1_stats_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor
                     diffuse(color[0], color[1], color[2], color[3]): z(101)
                 ));
             }
+
+            // Change against the previous best, faded in with the record animation.
+            let before = score_info.record.as_ref()
+                .and_then(|r| r.previous.as_ref())
+                .map(|p| p.judgment_counts.get(grade).copied().unwrap_or(0));
+            if let Some((text, improved)) = before.and_then(|b| judgment_delta(*grade, count, b)) {
+                let c = if improved { DELTA_BETTER } else { DELTA_WORSE };
+                actors.push(act!(text: font("miso"): settext(text):
+                    align(0.0, 0.5): xy(number_base_x + 4.0, number_final_y): zoom(0.5):
+                    diffuse(c[0], c[1], c[2], record_progress(state.elapsed)): z(101)
+                ));
+            }
         }
         
         // --- RADAR LABELS & NUMBERS ---
@@ -330,6 +415,32 @@ fn build_p1_stats_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor
     actors
 }
 
+/// Step plot of life over the song, drawn over the density graph.
+fn build_life_line(si: &ScoreInfo, width: f32, height: f32) -> Actor {
+    let duration = si.graph_duration.max(1.0);
+    let to_xy = |t: f32, life: f32| [(t / duration).clamp(0.0, 1.0) * width, (1.0 - life) * height];
+
+    let mut points = Vec::with_capacity(si.life_history.len() * 2 + 1);
+    let mut last_life = 0.5;
+    for &(t, life) in &si.life_history {
+        // Hold the previous value until the change so the line steps like SL's.
+        if !points.is_empty() {
+            points.push(to_xy(t, last_life));
+        }
+        points.push(to_xy(t, life));
+        last_life = life;
+    }
+    points.push(to_xy(duration, last_life));
+
+    Actor::Polyline {
+        points,
+        colors: vec![[1.0, 1.0, 1.0, 0.8]],
+        thickness: 2.0,
+        z: 3,
+        blend: BlendMode::Alpha,
+    }
+}
+
 /// Builds the timing statistics pane for P2 (or P1 in single player).
 fn build_p2_timing_pane(_state: &State) -> Vec<Actor> {
     let pane_width = 300.0;
@@ -436,6 +547,7 @@ fn build_p2_timing_pane(_state: &State) -> Vec<Actor> {
         size: [SizeSpec::Px(pane_width), SizeSpec::Px(pane_height)],
         children,
         background: None,
+        clip: false,
         z: 101,
     }]
 }
@@ -457,9 +569,14 @@ fn build_modifiers_pane(state: &State) -> Vec<Actor> {
     // The original large background pane is at z=100. This text needs to be on top.
     let text_z = 101;
 
-    // Get the speed mod from state.score_info
-    let speed_mod_text = state.score_info.as_ref().unwrap().speed_mod.to_string();
-    let final_text = format!("{}, Overhead", speed_mod_text);
+    // Get the speed mod from state.score_info; a rate other than 1.0x goes first, as in SL.
+    let score_info = state.score_info.as_ref().unwrap();
+    let speed_mod_text = score_info.speed_mod.to_string();
+    let final_text = if (score_info.mods.rate - 1.0).abs() > 0.001 {
+        format!("{} Rate, {}, Overhead", score_info.mods.rate_text(), speed_mod_text)
+    } else {
+        format!("{}, Overhead", speed_mod_text)
+    };
 
     let modifier_text = act!(text:
         font("miso"):
@@ -494,6 +611,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         fg_color: [1.0; 4],
         left_text: None, center_text: None, right_text: None,
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets { profile_source: true, groovestats: true, session_time: false, clock: true },
     }));
 
     // Session Timer
@@ -550,22 +668,26 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 format!("banner{}.png", banner_num)
             });
 
-        let full_title = if score_info.song.subtitle.trim().is_empty() {
-            score_info.song.title.clone()
-        } else {
-            format!("{} {}", score_info.song.title, score_info.song.subtitle)
-        };
+        let full_title = score_info.song.display_full_title(crate::config::get().prefer_translit);
 
         let title_and_banner_frame = Actor::Frame {
             align: [0.5, 0.5],
             offset: [screen_center_x(), 46.0],
             size: [SizeSpec::Px(0.0), SizeSpec::Px(0.0)],
-            children: vec![
-                act!(sprite(banner_key): align(0.5, 0.5): xy(0.0, 66.0): setsize(418.0, 164.0): zoom(0.7): z(0)),
+            children: banner::build(banner::BannerParams {
+                texture_key: banner_key,
+                center: [0.0, 66.0],
+                zoom: 0.7,
+                z: 0,
+            })
+            .into_iter()
+            .chain([
                 act!(quad: align(0.5, 0.5): xy(0.0, 0.0): setsize(418.0, 25.0): zoom(0.7): diffuse(0.117, 0.157, 0.184, 1.0): z(1)),
                 act!(text: font("miso"): settext(full_title): align(0.5, 0.5): xy(0.0, 0.0): maxwidth(418.0 * 0.7): z(2)),
-            ],
+            ])
+            .collect(),
             background: None,
+            clip: false,
             z: 50,
         };
         actors.push(title_and_banner_frame);
@@ -590,11 +712,12 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             size: [SizeSpec::Px(0.0), SizeSpec::Px(0.0)],
             children: vec![
                 act!(quad: align(0.5, 0.5): xy(0.0, 0.0): setsize(418.0, 16.0): zoom(0.7): diffuse(0.117, 0.157, 0.184, 1.0): z(0) ),
-                act!(text: font("miso"): settext(score_info.song.artist.clone()): align(0.0, 0.5): xy(-145.0, 0.0): zoom(0.6): maxwidth(418.0 / 2.3): z(1) ),
+                act!(text: font("miso"): settext(score_info.song.display_artist(crate::config::get().prefer_translit).to_string()): align(0.0, 0.5): xy(-145.0, 0.0): zoom(0.6): maxwidth(418.0 / 2.3): z(1) ),
                 act!(text: font("miso"): settext(bpm_text): align(0.5, 0.5): xy(0.0, 0.0): zoom(0.6): maxwidth(418.0 / 0.875): z(1) ),
                 act!(text: font("miso"): settext(length_text): align(1.0, 0.5): xy(145.0, 0.0): zoom(0.6): z(1) ),
             ],
             background: None,
+            clip: false,
             z: 50,
         };
         actors.push(song_features_frame);
@@ -604,7 +727,13 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     let p1_frame_x = screen_center_x() - 155.0;
 
     // Letter Grade (0.4 for parity with individual pngs)
-    actors.push(act!(sprite("grades/grades 1x19.png"): align(0.5, 0.5): xy(p1_frame_x - 70.0, cy - 134.0): zoom(1.0): z(101): setstate(score_info.grade.to_sprite_state()) ));
+    // Grade reveal: the letter flips in edge-on, like Simply Love's.
+    actors.push(act!(sprite("grades/grades 1x19.png"):
+        align(0.5, 0.5): xy(p1_frame_x - 70.0, cy - 134.0): zoom(1.0): z(101):
+        setstate(score_info.grade.to_sprite_state()):
+        rotationy(90.0):
+        sleep(0.2): decelerate(0.5): rotationy(0.0)
+    ));
 
     // Difficulty Text and Meter Block
     {
@@ -661,6 +790,34 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     ));
 
 
+    // --- Record Break (beside the grade) ---
+    if let Some(record) = &score_info.record {
+        let progress = record_progress(state.elapsed);
+        let pulse = 0.75 + 0.25 * (state.elapsed * 6.0).sin();
+        actors.push(act!(text: font("wendy"): settext("NEW RECORD"):
+            align(1.0, 0.5): xy(p1_frame_x + 5.0, cy - 150.0): zoom(0.3):
+            diffuse(1.0, 1.0, pulse, progress): z(101)
+        ));
+        if let Some(previous) = &record.previous {
+            // Counts up from +0.00% as the animation plays.
+            let gain = (score_info.score_percent - previous.score_percent) * 100.0 * progress as f64;
+            actors.push(act!(text: font("miso"): settext(format!("{:+.2}%", gain)):
+                align(1.0, 0.5): xy(p1_frame_x + 5.0, cy - 130.0): zoom(0.8):
+                diffuse(DELTA_BETTER[0], DELTA_BETTER[1], DELTA_BETTER[2], progress): z(101)
+            ));
+            // Bests are per chart, whatever the rate; say so when the old one was at another.
+            let was_text = if (previous.rate - score_info.mods.rate).abs() > 0.001 {
+                format!("was {:.2}% at {:.2}x", previous.score_percent * 100.0, previous.rate)
+            } else {
+                format!("was {:.2}%", previous.score_percent * 100.0)
+            };
+            actors.push(act!(text: font("miso"): settext(was_text):
+                align(1.0, 0.5): xy(p1_frame_x + 5.0, cy - 115.0): zoom(0.55):
+                diffuse(1.0, 1.0, 1.0, 0.7 * progress): z(101)
+            ));
+        }
+    }
+
     // --- Player 1 Score Percentage Display ---
     {
         let score_frame_y = screen_center_y() - 26.0;
@@ -672,6 +829,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             offset: [p1_frame_x, score_frame_y],
             size: [SizeSpec::Px(0.0), SizeSpec::Px(0.0)],
             background: None,
+            clip: false,
             z: 101,
             children: vec![
                 act!(quad: align(0.0, 0.5): xy(-150.0, 0.0): setsize(158.5, 60.0): diffuse(score_bg_color[0], score_bg_color[1], score_bg_color[2], 1.0) ),
@@ -694,25 +852,26 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     {
         const GRAPH_WIDTH: f32 = 610.0;
         const GRAPH_HEIGHT: f32 = 64.0;
+        let graph_bg_color = color::rgba_hex("#101519");
 
         let frame_center_x = screen_center_x();
         let frame_center_y = screen_center_y() + 124.0;
         
-        let graph_frame = Actor::Frame {
+        let mut graph_frame = Actor::Frame {
             align: [0.5, 0.0], // Center-Top alignment for the main frame
             offset: [frame_center_x, frame_center_y],
             size: [SizeSpec::Px(GRAPH_WIDTH), SizeSpec::Px(GRAPH_HEIGHT)],
             z: 101,
             background: None,
+            clip: false,
             children: vec![
-                // The NPS histogram is positioned with its origin at the bottom-left of the frame,
-                // and then shifted to be centered horizontally.
-                // Lua: `addx(-GraphWidth/2):addy(GraphHeight)`
-                // This is equivalent to `align(0.0, 1.0)` (bottom-left) and `xy` at the center of the frame.
-                act!(sprite(state.density_graph_texture_key.clone()):
-                    align(0.0, 1.0): // bottom-left
-                    xy(0.0, GRAPH_HEIGHT): // position at the bottom-left of the frame
-                    setsize(GRAPH_WIDTH, GRAPH_HEIGHT): z(1)
+                // Graph background; the NPS histogram sits on top of it, filling the frame.
+                act!(quad:
+                    align(0.0, 0.0):
+                    xy(0.0, 0.0):
+                    setsize(GRAPH_WIDTH, GRAPH_HEIGHT):
+                    diffuse(graph_bg_color[0], graph_bg_color[1], graph_bg_color[2], 1.0):
+                    z(0)
                 ),
                 // The horizontal zero-line, centered vertically in the panel.
                 act!(quad:
@@ -724,6 +883,18 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 ),
             ],
         };
+        if let (Some(si), Actor::Frame { children, .. }) = (&state.score_info, &mut graph_frame) {
+            children.extend(density_graph::build(
+                &si.chart.measure_nps_vec,
+                si.chart.max_nps,
+                GRAPH_WIDTH,
+                GRAPH_HEIGHT,
+                color::rgba_hex("#26545B"),
+                color::rgba_hex("#361943"),
+                1,
+            ));
+            children.push(build_life_line(si, GRAPH_WIDTH, GRAPH_HEIGHT));
+        }
         actors.push(graph_frame);
     }
 
@@ -732,8 +903,12 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         let itg_text_x = screen_width() - widescale(55.0, 62.0);
         actors.push(act!(text: font("wendy"): settext("ITG"): align(1.0, 0.5): xy(itg_text_x, 15.0): zoom(widescale(0.5, 0.6)): z(121): diffuse(1.0, 1.0, 1.0, 1.0) ));
         let final_pad_zoom = 0.24 * widescale(0.435, 0.525);
-        actors.push(pad_display::build(pad_display::PadDisplayParams { center_x: screen_width() - widescale(35.0, 41.0), center_y: widescale(22.0, 23.5), zoom: final_pad_zoom, z: 121, is_active: true, }));
-        actors.push(pad_display::build(pad_display::PadDisplayParams { center_x: screen_width() - widescale(15.0, 17.0), center_y: widescale(22.0, 23.5), zoom: final_pad_zoom, z: 121, is_active: false, }));
+        // The pad the chart was played on, not necessarily the wheel's.
+        let game_type = state.score_info.as_ref()
+            .and_then(|si| GameType::for_chart_type(&si.chart.chart_type))
+            .unwrap_or(crate::config::get().game_type);
+        actors.push(pad_display::build(pad_display::PadDisplayParams { center_x: screen_width() - widescale(35.0, 41.0), center_y: widescale(22.0, 23.5), zoom: final_pad_zoom, z: 121, is_active: true, game_type }));
+        actors.push(pad_display::build(pad_display::PadDisplayParams { center_x: screen_width() - widescale(15.0, 17.0), center_y: widescale(22.0, 23.5), zoom: final_pad_zoom, z: 121, is_active: false, game_type }));
     }
 
     // 3. Bottom Bar
@@ -749,6 +924,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         fg_color: [1.0; 4],
         left_text: Some(&profile.display_name), center_text: None, right_text: None,
         left_avatar: footer_avatar,
+        widgets: screen_bar::ScreenBarWidgets::NONE,
     }));
 
      // --- Date/Time in footer (like ScreenEvaluation decorations) ---
@@ -766,5 +942,28 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         z(121) // a bit above the screen bar (z=120)
     ));
 
+    // --- Tournament watermark, so screenshots show the score was set under lockdown ---
+    if let Some(si) = state.score_info.as_ref().filter(|si| si.tournament_mode) {
+        actors.push(act!(text:
+            font("wendy"):
+            settext("TOURNAMENT"):
+            align(0.5, 0.5):
+            xy(screen_center_x(), screen_center_y()):
+            zoom(widescale(1.2, 1.5)):
+            diffuse(1.0, 1.0, 1.0, 0.08):
+            z(200)
+        ));
+        actors.push(act!(text:
+            font("miso"):
+            settext(format!("NO MODS  {}", si.chart.short_hash)):
+            align(0.5, 1.0):
+            xy(screen_center_x(), screen_height() - 24.0):
+            zoom(0.6):
+            horizalign(center):
+            diffuse(1.0, 1.0, 1.0, 0.6):
+            z(121)
+        ));
+    }
+
     actors
 }
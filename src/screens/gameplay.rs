@@ -1,5 +1,6 @@
 use crate::act;
 use crate::assets::{self, AssetManager};
+use crate::core::gfx::PostEffect;
 use crate::core::space::*;
 use crate::core::space::{is_wide, widescale};
 use crate::game::judgment;
@@ -7,23 +8,24 @@ use crate::game::judgment::JudgeGrade;
 use crate::game::note::HoldResult;
 use crate::game::note::NoteType;
 use crate::game::parsing::noteskin::{Quantization, SpriteSlot, NUM_QUANTIZATIONS};
-use crate::game::{profile, scroll::ScrollSpeedSetting};
+use crate::game::{profile::{self, JudgmentPosition}, scroll::ScrollSpeedSetting};
 use crate::ui::actors::{Actor, SizeSpec};
+use crate::ui::layer;
+use crate::ui::primitives::{self, ArcParams};
 use crate::ui::color;
+use crate::ui::components::banner;
 use crate::ui::components::screen_bar::{self, ScreenBarParams};
 use crate::ui::font;
 use log::warn;
-use std::array::from_fn;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, LazyLock, Mutex};
 
 pub use crate::game::gameplay::{handle_key_press, init, update, State};
-use crate::game::gameplay::active_hold_is_engaged;
+use crate::game::gameplay::{active_hold_is_engaged, fail_sequence_time, ghost_delta_percent, receptor_animation, Foot};
 use crate::game::gameplay::{
-    ComboMilestoneKind, COMBO_HUNDRED_MILESTONE_DURATION, COMBO_THOUSAND_MILESTONE_DURATION,
-    HOLD_JUDGMENT_TOTAL_DURATION, MINE_EXPLOSION_DURATION, RECEPTOR_GLOW_DURATION,
+    ComboMilestoneKind, QualityLevel, COMBO_HUNDRED_MILESTONE_DURATION, COMBO_THOUSAND_MILESTONE_DURATION,
     RECEPTOR_Y_OFFSET_FROM_CENTER, TRANSITION_IN_DURATION, TRANSITION_OUT_DURATION,
 };
 
@@ -33,18 +35,18 @@ use crate::game::gameplay::{
 const TARGET_ARROW_PIXEL_SIZE: f32 = 64.0; // Match Simply Love's on-screen arrow height
 const TARGET_EXPLOSION_PIXEL_SIZE: f32 = 125.0; // Simply Love tap explosions top out around 125px tall
 const HOLD_JUDGMENT_Y_OFFSET_FROM_CENTER: f32 = -90.0; // Mirrors Simply Love metrics for hold judgments
-const LOVE_HOLD_JUDGMENT_NATIVE_FRAME_HEIGHT: f32 = 140.0; // Each frame in Love 1x2 (doubleres).png is 140px tall
 const HOLD_JUDGMENT_FINAL_HEIGHT: f32 = 32.0; // Matches Simply Love's final on-screen size
 const HOLD_JUDGMENT_INITIAL_HEIGHT: f32 = HOLD_JUDGMENT_FINAL_HEIGHT * 0.8; // Mirrors 0.4->0.5 zoom ramp in metrics
-const HOLD_JUDGMENT_FINAL_ZOOM: f32 =
-    HOLD_JUDGMENT_FINAL_HEIGHT / LOVE_HOLD_JUDGMENT_NATIVE_FRAME_HEIGHT;
-const HOLD_JUDGMENT_INITIAL_ZOOM: f32 =
-    HOLD_JUDGMENT_INITIAL_HEIGHT / LOVE_HOLD_JUDGMENT_NATIVE_FRAME_HEIGHT;
 
 //const DANGER_THRESHOLD: f32 = 0.2; // For implementation of red/green flashing light
 
 // Visual Feedback
 const SHOW_COMBO_AT: u32 = 4; // From Simply Love metrics
+const JUDGMENT_Y_OFFSET_FROM_CENTER: f32 = -30.0; // Simply Love's centered judgment
+const COMBO_Y_OFFSET_FROM_CENTER: f32 = 30.0;
+const JUDGMENT_SIDE_X_OFFSET: f32 = 250.0; // From the playfield center, past its right edge
+const JUDGMENT_UNDER_RECEPTORS_Y: f32 = 80.0; // Below the receptors, clear of hold judgments
+const COMBO_UNDER_RECEPTORS_Y: f32 = 128.0;
 
 // Z-order layers for key gameplay visuals (higher draws on top)
 const Z_RECEPTOR: i32 = 100;
@@ -54,8 +56,10 @@ const Z_HOLD_EXPLOSION: i32 = 120;
 const Z_HOLD_GLOW: i32 = 130;
 const Z_MINE_EXPLOSION: i32 = 101;
 const Z_TAP_NOTE: i32 = 140;
-const MINE_CORE_SIZE_RATIO: f32 = 0.45;
-const MINE_FILL_LAYERS: usize = 32;
+
+// Beginner assist foot labels drawn over upcoming arrows.
+const FOOT_HINT_LEFT_COLOR: [f32; 3] = [0.45, 0.85, 1.0];
+const FOOT_HINT_RIGHT_COLOR: [f32; 3] = [1.0, 0.6, 0.35];
 const MINE_GRADIENT_SAMPLES: usize = 64;
 
 #[derive(Hash, PartialEq, Eq, Clone)]
@@ -72,10 +76,10 @@ static MINE_GRADIENT_CACHE: LazyLock<Mutex<MineGradientCache>> =
 
 #[derive(Clone, Debug)]
 struct MineFillState {
-    layers: [[f32; 4]; MINE_FILL_LAYERS],
+    layers: Vec<[f32; 4]>,
 }
 
-fn mine_fill_state(slot: &SpriteSlot, beat: f32) -> Option<MineFillState> {
+fn mine_fill_state(slot: &SpriteSlot, beat: f32, layer_count: usize) -> Option<MineFillState> {
     let colors = {
         let key = MineGradientKey {
             texture_key: slot.texture_key().to_string(),
@@ -107,13 +111,15 @@ fn mine_fill_state(slot: &SpriteSlot, beat: f32) -> Option<MineFillState> {
     let idx_float = phase * len as f32;
     let idx = (idx_float.floor() as usize) % len;
 
-    let layers = from_fn(|layer| {
-        let offset = layer % len;
-        let sample_index = (idx + len - offset) % len;
-        let mut color = colors[sample_index];
-        color[3] = 1.0;
-        color
-    });
+    let layers = (0..layer_count)
+        .map(|layer| {
+            let offset = layer % len;
+            let sample_index = (idx + len - offset) % len;
+            let mut color = colors[sample_index];
+            color[3] = 1.0;
+            color
+        })
+        .collect();
 
     Some(MineFillState { layers })
 }
@@ -236,13 +242,81 @@ fn load_mine_gradient_colors(slot: &SpriteSlot) -> Option<Vec<[f32; 4]>> {
     Some(samples)
 }
 
+// --- POST EFFECTS ---
+// After failing, the screen darkens in from the edges; an Immediate fail
+// also drains the color out while the song winds down.
+const FAIL_VIGNETTE_STRENGTH: f32 = 0.7;
+const FAIL_VIGNETTE_FADE: f32 = 1.5;
+const FAIL_DESATURATE_FADE: f32 = 1.2;
+
+pub fn post_effects(state: &State) -> Vec<PostEffect> {
+    let Some(fail_time) = state.fail_time else { return Vec::new() };
+    // The song clock stops during the Immediate fail sequence, so it keeps its own.
+    let since_fail = fail_sequence_time(state).unwrap_or(state.current_music_time - fail_time);
+    let t = (since_fail / FAIL_VIGNETTE_FADE).clamp(0.0, 1.0);
+    let mut effects = Vec::new();
+    if fail_sequence_time(state).is_some() {
+        effects.push(PostEffect::Desaturate { amount: (since_fail / FAIL_DESATURATE_FADE).clamp(0.0, 1.0) });
+    }
+    effects.push(PostEffect::Vignette { strength: FAIL_VIGNETTE_STRENGTH * t });
+    effects
+}
+
+// --- LIFE METER SHATTER ---
+// On an Immediate fail the empty meter breaks into a grid of shards that burst
+// out, spin, fall and fade.
+const SHATTER_COLS: usize = 8;
+const SHATTER_ROWS: usize = 2;
+const SHATTER_GRAVITY: f32 = 900.0;
+const SHATTER_DURATION: f32 = 1.4;
+
+// Stable per-shard randomness in 0..1.
+fn shard_random(index: usize, salt: u32) -> f32 {
+    let mut x = (index as u32).wrapping_mul(0x9E37_79B9) ^ salt.wrapping_mul(0x85EB_CA6B);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x2C1B_3C6D);
+    x ^= x >> 12;
+    (x & 0xFFFF) as f32 / 65535.0
+}
+
+fn build_life_meter_shards(center: [f32; 2], size: [f32; 2], t: f32) -> Vec<Actor> {
+    let alpha = (1.0 - t / SHATTER_DURATION).clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return Vec::new();
+    }
+    let shard_w = size[0] / SHATTER_COLS as f32;
+    let shard_h = size[1] / SHATTER_ROWS as f32;
+    let mut actors = Vec::with_capacity(SHATTER_COLS * SHATTER_ROWS);
+    for row in 0..SHATTER_ROWS {
+        for col in 0..SHATTER_COLS {
+            let i = row * SHATTER_COLS + col;
+            let home_x = center[0] - size[0] / 2.0 + (col as f32 + 0.5) * shard_w;
+            let home_y = center[1] - size[1] / 2.0 + (row as f32 + 0.5) * shard_h;
+            // Outward from the meter's center, a little upward, with some scatter.
+            let vx = (home_x - center[0]) * 2.5 + (shard_random(i, 1) - 0.5) * 160.0;
+            let vy = -120.0 - shard_random(i, 2) * 160.0;
+            let spin = (shard_random(i, 3) - 0.5) * 720.0;
+            let x = home_x + vx * t;
+            let y = home_y + vy * t + 0.5 * SHATTER_GRAVITY * t * t;
+            actors.push(act!(quad:
+                align(0.5, 0.5): xy(x, y):
+                zoomto(shard_w - 1.0, shard_h - 1.0):
+                rotationz(spin * t):
+                diffuse(1.0, 1.0, 1.0, alpha):
+                z(91)
+            ));
+        }
+    }
+    actors
+}
+
 // --- TRANSITIONS ---
 pub fn in_transition() -> (Vec<Actor>, f32) {
     let actor = act!(quad:
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 1.0):
-        z(1100):
+        z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -254,7 +328,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -266,9 +340,10 @@ fn build_background(state: &State) -> Actor {
     let sw = screen_width();
     let sh = screen_height();
     let screen_aspect = if sh > 0.0 { sw / sh } else { 16.0 / 9.0 };
+    let key = state.background_video_key.as_ref().unwrap_or(&state.background_texture_key);
 
     let (tex_w, tex_h) =
-        if let Some(meta) = crate::assets::texture_dims(&state.background_texture_key) {
+        if let Some(meta) = crate::assets::texture_dims(key) {
             (meta.w as f32, meta.h as f32)
         } else {
             (1.0, 1.0) // fallback, will just fill screen
@@ -278,14 +353,14 @@ fn build_background(state: &State) -> Actor {
 
     if screen_aspect > tex_aspect {
         // screen is wider, match width to cover
-        act!(sprite(state.background_texture_key.clone()):
+        act!(sprite(key.clone()):
             align(0.5, 0.5): xy(screen_center_x(), screen_center_y()):
             zoomtowidth(sw):
             z(-100)
         )
     } else {
         // screen is taller/equal, match height to cover
-        act!(sprite(state.background_texture_key.clone()):
+        act!(sprite(key.clone()):
             align(0.5, 0.5): xy(screen_center_x(), screen_center_y()):
             zoomtoheight(sh):
             z(-100)
@@ -379,14 +454,42 @@ fn format_game_time(s: f32, total_seconds: f32) -> String {
     }
 }
 
+/// Centers of the judgment sprite and the combo for the player's `JudgmentPosition`.
+/// Under the receptors they follow the notefield's mini zoom; beside it, the field's width.
+fn judgment_anchors(position: JudgmentPosition, playfield_center_x: f32, receptor_y: f32, field_zoom: f32, column_spacing: f32) -> ([f32; 2], [f32; 2]) {
+    match position {
+        JudgmentPosition::Center => (
+            [playfield_center_x, screen_center_y() + JUDGMENT_Y_OFFSET_FROM_CENTER],
+            [playfield_center_x, screen_center_y() + COMBO_Y_OFFSET_FROM_CENTER],
+        ),
+        JudgmentPosition::Side => {
+            // A wider field pushes the judgment out by as much as its right edge moved.
+            let x = playfield_center_x + JUDGMENT_SIDE_X_OFFSET + 2.0 * TARGET_ARROW_PIXEL_SIZE * (field_zoom * column_spacing - 1.0);
+            (
+                [x, screen_center_y() + JUDGMENT_Y_OFFSET_FROM_CENTER],
+                [x, screen_center_y() + COMBO_Y_OFFSET_FROM_CENTER],
+            )
+        }
+        JudgmentPosition::UnderReceptors => (
+            [playfield_center_x, receptor_y + JUDGMENT_UNDER_RECEPTORS_Y * field_zoom],
+            [playfield_center_x, receptor_y + COMBO_UNDER_RECEPTORS_Y * field_zoom],
+        ),
+    }
+}
+
 pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     let mut actors = Vec::new();
     let profile = profile::get();
 
     // --- Background and Filter ---
-    actors.push(build_background(state));
+    // An overlay window shows the desktop where the background would be.
+    let show_background = state.quality.shows_background() && !crate::config::get().overlay_window;
+    if show_background {
+        actors.push(build_background(state));
+    }
 
     let filter_alpha = match profile.background_filter {
+        _ if !show_background => 0.0,
         crate::game::profile::BackgroundFilter::Off => 0.0,
         crate::game::profile::BackgroundFilter::Dark => 0.5,
         crate::game::profile::BackgroundFilter::Darker => 0.75,
@@ -408,6 +511,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     let playfield_center_x = screen_center_x() - (clamped_width * 0.25);
 
     let receptor_y = screen_center_y() + RECEPTOR_Y_OFFSET_FROM_CENTER;
+    let (judgment_xy, combo_xy) =
+        judgment_anchors(profile.judgment_position, playfield_center_x, receptor_y, state.mods.field_zoom(), profile.column_spacing);
 
     // --- Banner (1:1 with Simply Love, including parent frame logic) ---
     if let Some(banner_path) = &state.song.banner_path {
@@ -435,32 +540,38 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         let banner_y = sidepane_center_y + (local_banner_y * banner_data_zoom);
         let final_zoom = 0.4 * banner_data_zoom;
 
-        actors.push(act!(sprite(banner_key):
-            align(0.5, 0.5): xy(banner_x, banner_y):
-            setsize(418.0, 164.0): zoom(final_zoom):
-            z(-50)
-        ));
+        actors.extend(banner::build(banner::BannerParams {
+            texture_key: banner_key,
+            center: [banner_x, banner_y],
+            zoom: final_zoom,
+            z: -50,
+        }));
     }
 
     if let Some(ns) = &state.noteskin {
+        // Mini shrinks the whole field around the receptors: sprites, columns and spacing.
+        let field_zoom = state.mods.field_zoom();
+        let arrow_spacing = ScrollSpeedSetting::ARROW_SPACING * field_zoom;
+        // Column spacing moves the columns apart without resizing the arrows.
+        let column_zoom = field_zoom * profile.column_spacing;
         let scale_sprite = |size: [i32; 2]| -> [f32; 2] {
             let width = size[0].max(0) as f32;
             let height = size[1].max(0) as f32;
             if height <= 0.0 || TARGET_ARROW_PIXEL_SIZE <= 0.0 {
-                [width, height]
+                [width * field_zoom, height * field_zoom]
             } else {
-                let scale = TARGET_ARROW_PIXEL_SIZE / height;
-                [width * scale, TARGET_ARROW_PIXEL_SIZE]
+                let scale = TARGET_ARROW_PIXEL_SIZE * field_zoom / height;
+                [width * scale, TARGET_ARROW_PIXEL_SIZE * field_zoom]
             }
         };
         let scale_explosion = |size: [i32; 2]| -> [f32; 2] {
             let width = size[0].max(0) as f32;
             let height = size[1].max(0) as f32;
             if height <= 0.0 || TARGET_EXPLOSION_PIXEL_SIZE <= 0.0 {
-                [width, height]
+                [width * field_zoom, height * field_zoom]
             } else {
-                let scale = TARGET_EXPLOSION_PIXEL_SIZE / height;
-                [width * scale, TARGET_EXPLOSION_PIXEL_SIZE]
+                let scale = TARGET_EXPLOSION_PIXEL_SIZE * field_zoom / height;
+                [width * scale, TARGET_EXPLOSION_PIXEL_SIZE * field_zoom]
             }
         };
         let current_time = state.current_music_time;
@@ -469,7 +580,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             match state.scroll_speed {
                 ScrollSpeedSetting::CMod(c_bpm) => {
                     // C-Mod is time-based. Visual scroll speed is constant and must ignore #SPEEDS.
-                    let pps = (c_bpm / 60.0) * ScrollSpeedSetting::ARROW_SPACING;
+                    let pps = (c_bpm / 60.0) * arrow_spacing;
 
                     // note_time correctly includes offsets from stops/delays.
                     let note_time = state.timing.get_time_for_beat(beat);
@@ -488,7 +599,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
 
                     let player_multiplier = state.scroll_speed.beat_multiplier(state.scroll_reference_bpm);
                     let final_multiplier = player_multiplier * speed_multiplier;
-                    receptor_y + (beat_diff_disp * ScrollSpeedSetting::ARROW_SPACING * final_multiplier)
+                    receptor_y + (beat_diff_disp * arrow_spacing * final_multiplier)
                 }
             }
         };
@@ -499,36 +610,40 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 .unwrap_or([TARGET_EXPLOSION_PIXEL_SIZE, TARGET_EXPLOSION_PIXEL_SIZE]);
 
             if base[1] <= 0.0 {
-                base
+                [base[0] * field_zoom, base[1] * field_zoom]
             } else {
-                let scale = TARGET_EXPLOSION_PIXEL_SIZE / base[1];
-                [base[0] * scale, TARGET_EXPLOSION_PIXEL_SIZE]
+                let scale = TARGET_EXPLOSION_PIXEL_SIZE * field_zoom / base[1];
+                [base[0] * scale, TARGET_EXPLOSION_PIXEL_SIZE * field_zoom]
             }
         };
 
         // Receptors + glow
-        for i in 0..4 {
-            let col_x_offset = ns.column_xs[i];
+        for i in 0..state.num_cols {
+            let col_x_offset = ns.column_xs[i] as f32 * column_zoom;
 
-            let bop_timer = state.receptor_bop_timers[i];
-            let bop_zoom = if bop_timer > 0.0 {
-                let t = (0.11 - bop_timer) / 0.11;
-                0.75 + (1.0 - 0.75) * t
-            } else {
-                1.0
-            };
+            let (receptor_zoom, receptor_tint) = state.receptor_commands[i]
+                .as_ref()
+                .map(|active| {
+                    let visual =
+                        receptor_animation(ns, active.data).state_at(active.elapsed());
+                    (visual.zoom, visual.diffuse)
+                })
+                .unwrap_or((1.0, [1.0; 4]));
 
             let receptor_slot = &ns.receptor_off[i];
             let receptor_frame =
-                receptor_slot.frame_index(state.total_elapsed_in_screen, state.current_beat);
+                receptor_slot.frame_index(state.visual_clock.time, state.visual_clock.beat);
             let receptor_uv = receptor_slot.uv_for_frame(receptor_frame);
             let receptor_size = scale_sprite(receptor_slot.size());
-            let receptor_color = ns.receptor_pulse.color_for_beat(state.current_beat);
+            let pulse_color = ns.receptor_pulse.color_for_beat(state.visual_clock.beat);
+            let receptor_color: [f32; 4] =
+                std::array::from_fn(|c| pulse_color[c] * receptor_tint[c]);
             actors.push(act!(sprite(receptor_slot.texture_key().to_string()):
                 align(0.5, 0.5):
-                xy(playfield_center_x + col_x_offset as f32, receptor_y):
+                material(receptor_slot.def.material):
+                xy(playfield_center_x + col_x_offset, receptor_y):
                 zoomto(receptor_size[0] as f32, receptor_size[1] as f32):
-                zoom(bop_zoom):
+                zoom(receptor_zoom):
                 diffuse(
                     receptor_color[0],
                     receptor_color[1],
@@ -545,7 +660,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 z(Z_RECEPTOR)
             ));
 
-            if let Some(hold_slot) = state.active_holds[i]
+            if let Some((active, hold_slot, animation)) = state.active_holds[i]
                 .as_ref()
                 .filter(|active| active_hold_is_engaged(active))
                 .and_then(|active| {
@@ -555,14 +670,23 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     } else {
                         &ns.hold
                     };
-                    visuals
+                    let slot = visuals
                         .explosion
                         .as_ref()
-                        .or_else(|| ns.hold.explosion.as_ref())
+                        .or_else(|| ns.hold.explosion.as_ref())?;
+                    Some((active, slot, visuals.explosion_animation.as_ref()))
                 })
             {
                 let hold_uv = hold_slot.uv_for_frame(0);
                 let hold_size = scale_explosion(hold_slot.size());
+                let visual = animation.map(|anim| anim.state_at(active.explosion_elapsed));
+                let hold_zoom = visual.map_or(1.0, |v| v.zoom);
+                let hold_diffuse = visual.map_or([1.0; 4], |v| v.diffuse);
+                // Roll shimmer and the like: the script's glowshift, drawn by the same sprite.
+                let hold_glow = visual
+                    .map(|v| v.glow)
+                    .filter(|_| state.quality.shows_glow())
+                    .unwrap_or([0.0; 4]);
                 let receptor_rotation = ns
                     .receptor_off
                     .get(i)
@@ -572,26 +696,30 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 let final_rotation = base_rotation + receptor_rotation;
                 actors.push(act!(sprite(hold_slot.texture_key().to_string()):
                     align(0.5, 0.5):
-                    xy(playfield_center_x + col_x_offset as f32, receptor_y):
+                    material(hold_slot.def.material):
+                    xy(playfield_center_x + col_x_offset, receptor_y):
                     zoomto(hold_size[0], hold_size[1]):
+                    zoom(hold_zoom):
                     rotationz(-final_rotation):
                     customtexturerect(hold_uv[0], hold_uv[1], hold_uv[2], hold_uv[3]):
+                    diffuse(hold_diffuse[0], hold_diffuse[1], hold_diffuse[2], hold_diffuse[3]):
+                    glow(hold_glow[0], hold_glow[1], hold_glow[2], hold_glow[3]):
                     blend(normal):
                     z(Z_HOLD_EXPLOSION)
                 ));
             }
 
-            let glow_timer = state.receptor_glow_timers[i];
-            if glow_timer > 0.0 {
+            if let Some(glow) = state.receptor_glows[i].as_ref().filter(|_| state.quality.shows_glow()) {
                 if let Some(glow_slot) = ns.receptor_glow.get(i).and_then(|slot| slot.as_ref()) {
                     let glow_frame =
-                        glow_slot.frame_index(state.total_elapsed_in_screen, state.current_beat);
+                        glow_slot.frame_index(state.visual_clock.time, state.visual_clock.beat);
                     let glow_uv = glow_slot.uv_for_frame(glow_frame);
                     let glow_size = glow_slot.size();
-                    let alpha = (glow_timer / RECEPTOR_GLOW_DURATION).powf(0.75);
+                    let alpha = 1.0 - glow.value();
                     actors.push(act!(sprite(glow_slot.texture_key().to_string()):
                         align(0.5, 0.5):
-                        xy(playfield_center_x + col_x_offset as f32, receptor_y):
+                        material(glow_slot.def.material):
+                        xy(playfield_center_x + col_x_offset, receptor_y):
                         zoomto(glow_size[0] as f32, glow_size[1] as f32):
                         rotationz(-glow_slot.def.rotation_deg as f32):
                         customtexturerect(glow_uv[0], glow_uv[1], glow_uv[2], glow_uv[3]):
@@ -604,21 +732,22 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         }
 
         // Tap explosions
-        for i in 0..4 {
+        for i in 0..state.num_cols {
             if let Some(active) = state.tap_explosions[i].as_ref() {
-                if let Some(explosion) = ns.tap_explosions.get(&active.window) {
-                    let col_x_offset = ns.column_xs[i];
-                    let anim_time = active.elapsed;
+                if let Some(explosion) = ns.tap_explosions.get(&active.data.window) {
+                    let col_x_offset = ns.column_xs[i] as f32 * column_zoom;
+                    let anim_time = active.elapsed();
                     let slot = &explosion.slot;
                     let beat_for_anim = if slot.source.is_beat_based() {
-                        (state.current_beat - active.start_beat).max(0.0)
+                        (state.visual_clock.beat - active.data.start_beat).max(0.0)
                     } else {
-                        state.current_beat
+                        state.visual_clock.beat
                     };
                     let frame = slot.frame_index(anim_time, beat_for_anim);
                     let uv = slot.uv_for_frame(frame);
                     let size = scale_explosion(slot.size());
-                    let visual = explosion.animation.state_at(active.elapsed);
+                    let visual = explosion.animation.state_at(active.elapsed());
+                    let glow = if state.quality.shows_glow() { visual.glow } else { [0.0; 4] };
                     let rotation_deg = ns
                         .receptor_off
                         .get(i)
@@ -627,7 +756,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
 
                     actors.push(act!(sprite(slot.texture_key().to_string()):
                         align(0.5, 0.5):
-                        xy(playfield_center_x + col_x_offset as f32, receptor_y):
+                        material(slot.def.material):
+                        xy(playfield_center_x + col_x_offset, receptor_y):
                         zoomto(size[0], size[1]):
                         zoom(visual.zoom):
                         customtexturerect(uv[0], uv[1], uv[2], uv[3]):
@@ -637,36 +767,19 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                             visual.diffuse[2],
                             visual.diffuse[3]
                         ):
+                        glow(glow[0], glow[1], glow[2], glow[3]):
                         rotationz(-(rotation_deg as f32)):
                         blend(normal):
                         z(101)
                     ));
-
-                    let glow = visual.glow;
-                    let glow_strength =
-                        glow[0].abs() + glow[1].abs() + glow[2].abs() + glow[3].abs();
-                    if glow_strength > f32::EPSILON {
-                        actors.push(act!(sprite(slot.texture_key().to_string()):
-                            align(0.5, 0.5):
-                            xy(playfield_center_x + col_x_offset as f32, receptor_y):
-                            zoomto(size[0], size[1]):
-                            zoom(visual.zoom):
-                            customtexturerect(uv[0], uv[1], uv[2], uv[3]):
-                            diffuse(glow[0], glow[1], glow[2], glow[3]):
-                            rotationz(-(rotation_deg as f32)):
-                            blend(add):
-                            z(101)
-                        ));
-                    }
                 }
             }
         }
 
         // Mine explosions
-        for i in 0..4 {
+        for i in 0..state.num_cols {
             if let Some(active) = state.mine_explosions[i].as_ref() {
-                let duration = MINE_EXPLOSION_DURATION.max(f32::EPSILON);
-                let progress = (active.elapsed / duration).clamp(0.0, 1.0);
+                let progress = active.progress();
                 let alpha = if progress < 0.5 {
                     1.0
                 } else {
@@ -679,7 +792,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 }
 
                 let rotation_progress = 180.0 * progress;
-                let col_x_offset = ns.column_xs[i];
+                let col_x_offset = ns.column_xs[i] as f32 * column_zoom;
                 let base_rotation = ns
                     .receptor_off
                     .get(i)
@@ -689,7 +802,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
 
                 actors.push(act!(sprite("hit_mine_explosion.png"):
                     align(0.5, 0.5):
-                    xy(playfield_center_x + col_x_offset as f32, receptor_y):
+                    xy(playfield_center_x + col_x_offset, receptor_y):
                     zoomto(mine_explosion_size[0], mine_explosion_size[1]):
                     rotationz(-final_rotation):
                     diffuse(1.0, 1.0, 1.0, alpha):
@@ -729,7 +842,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 continue;
             }
 
-            let col_x_offset = ns.column_xs[note.column];
+            let col_x_offset = ns.column_xs[note.column] as f32 * column_zoom;
             let active_state = state.active_holds[note.column]
                 .as_ref()
                 .filter(|h| h.note_index == note_index);
@@ -801,7 +914,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     let texture_width = texture_size[0].max(1) as f32;
                     let texture_height = texture_size[1].max(1) as f32;
                     if texture_width > std::f32::EPSILON && texture_height > std::f32::EPSILON {
-                        let body_width = TARGET_ARROW_PIXEL_SIZE;
+                        // Tightly spaced columns get thinner holds so they stay in their lane.
+                        let body_width = TARGET_ARROW_PIXEL_SIZE * field_zoom * profile.column_spacing.min(1.0);
                         let scale = body_width / texture_width;
                         let segment_height = (texture_height * scale).max(std::f32::EPSILON);
                         let body_uv = body_slot.uv_for_frame(0);
@@ -897,7 +1011,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
 
                                 actors.push(act!(sprite(body_slot.texture_key().to_string()):
                                     align(0.5, 0.5):
-                                    xy(playfield_center_x + col_x_offset as f32, segment_center):
+                                    material(body_slot.def.material):
+                                    xy(playfield_center_x + col_x_offset, segment_center):
                                     zoomto(body_width, segment_size):
                                     customtexturerect(u0, v0, u1, v1):
                                     diffuse(
@@ -936,7 +1051,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
 
                                 actors.push(act!(sprite(body_slot.texture_key().to_string()):
                                     align(0.5, 0.5):
-                                    xy(playfield_center_x + col_x_offset as f32, segment_center):
+                                    material(body_slot.def.material):
+                                    xy(playfield_center_x + col_x_offset, segment_center):
                                     zoomto(body_width, segment_size):
                                     customtexturerect(u0, v0, u1, v1):
                                     diffuse(
@@ -1008,7 +1124,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     if cap_height > std::f32::EPSILON {
                         actors.push(act!(sprite(cap_slot.texture_key().to_string()):
                             align(0.5, 0.5):
-                            xy(playfield_center_x + col_x_offset as f32, cap_center):
+                            material(cap_slot.def.material):
+                            xy(playfield_center_x + col_x_offset, cap_center):
                             zoomto(cap_width, cap_height):
                             customtexturerect(u0, v0, u1, v1):
                             diffuse(
@@ -1038,16 +1155,17 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                         _ => Quantization::Q192nd,
                     };
 
-                    let note_idx = (note.column % 4) * NUM_QUANTIZATIONS + quantization as usize;
+                    let note_idx = (note.column % state.num_cols) * NUM_QUANTIZATIONS + quantization as usize;
                     if let Some(note_slot) = ns.notes.get(note_idx) {
                         let frame = note_slot
-                            .frame_index(state.total_elapsed_in_screen, state.current_beat);
+                            .frame_index(state.visual_clock.time, state.visual_clock.beat);
                         let uv = note_slot.uv_for_frame(frame);
                         let size = scale_sprite(note_slot.size());
 
                         actors.push(act!(sprite(note_slot.texture_key().to_string()):
                             align(0.5, 0.5):
-                            xy(playfield_center_x + col_x_offset as f32, head_y):
+                            material(note_slot.def.material):
+                            xy(playfield_center_x + col_x_offset, head_y):
                             zoomto(size[0] as f32, size[1] as f32):
                             rotationz(-note_slot.def.rotation_deg as f32):
                             customtexturerect(uv[0], uv[1], uv[2], uv[3]):
@@ -1075,7 +1193,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     continue;
                 }
 
-                let col_x_offset = ns.column_xs[arrow.column];
+                let col_x_offset = ns.column_xs[arrow.column] as f32 * column_zoom;
 
                 if matches!(arrow.note_type, NoteType::Mine) {
                     let fill_slot = ns.mines.get(arrow.column).and_then(|slot| slot.as_ref());
@@ -1092,22 +1210,22 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                         .map(|slot| -slot.def.rotation_deg as f32)
                         .or_else(|| frame_slot.map(|slot| -slot.def.rotation_deg as f32))
                         .unwrap_or(0.0);
-                    let time = state.total_elapsed_in_screen;
-                    let beat = state.current_beat;
+                    let time = state.visual_clock.time;
+                    let beat = state.visual_clock.beat;
 
                     let circle_reference = frame_slot
                         .map(|slot| scale_sprite(slot.size()))
                         .or_else(|| fill_slot.map(|slot| scale_sprite(slot.size())))
-                        .unwrap_or([TARGET_ARROW_PIXEL_SIZE, TARGET_ARROW_PIXEL_SIZE]);
+                        .unwrap_or([TARGET_ARROW_PIXEL_SIZE * field_zoom, TARGET_ARROW_PIXEL_SIZE * field_zoom]);
 
                     if let Some(slot) = fill_slot {
-                        if let Some(fill_state) = mine_fill_state(slot, state.current_beat) {
-                            let width = circle_reference[0] * MINE_CORE_SIZE_RATIO;
-                            let height = circle_reference[1] * MINE_CORE_SIZE_RATIO;
+                        if let Some(fill_state) = mine_fill_state(slot, state.visual_clock.beat, state.quality.mine_fill_layers(ns.mine.fill_layers)) {
+                            let width = circle_reference[0] * ns.mine.core_ratio;
+                            let height = circle_reference[1] * ns.mine.core_ratio;
+                            let layer_count = fill_state.layers.len();
 
-                            for layer_idx in (0..MINE_FILL_LAYERS).rev() {
-                                let color = fill_state.layers[layer_idx];
-                                let scale = (layer_idx as f32 + 1.0) / MINE_FILL_LAYERS as f32;
+                            for (layer_idx, color) in fill_state.layers.iter().enumerate().rev() {
+                                let scale = (layer_idx as f32 + 1.0) / layer_count as f32;
                                 let layer_width = width * scale;
                                 let layer_height = height * scale;
 
@@ -1117,7 +1235,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
 
                                 actors.push(act!(sprite("circle.png"):
                                     align(0.5, 0.5):
-                                    xy(playfield_center_x + col_x_offset as f32, y_pos):
+                                    xy(playfield_center_x + col_x_offset, y_pos):
                                     zoomto(layer_width, layer_height):
                                     diffuse(color[0], color[1], color[2], 1.0):
                                     z(Z_TAP_NOTE - 2)
@@ -1129,11 +1247,12 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                             let size = scale_sprite(slot.size());
                             let width = size[0];
                             let height = size[1];
-                            let rotation = base_rotation - time * 45.0;
+                            let rotation = base_rotation + time * ns.mine.fill_spin;
 
                             actors.push(act!(sprite(slot.texture_key().to_string()):
                                 align(0.5, 0.5):
-                                xy(playfield_center_x + col_x_offset as f32, y_pos):
+                                material(slot.def.material):
+                                xy(playfield_center_x + col_x_offset, y_pos):
                                 zoomto(width, height):
                                 rotationz(rotation):
                                 customtexturerect(uv[0], uv[1], uv[2], uv[3]):
@@ -1147,11 +1266,12 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                         let frame = slot.frame_index(time, beat);
                         let uv = slot.uv_for_frame(frame);
                         let size = scale_sprite(slot.size());
-                        let rotation = base_rotation + time * 120.0;
+                        let rotation = base_rotation + time * ns.mine.frame_spin;
 
                         actors.push(act!(sprite(slot.texture_key().to_string()):
                             align(0.5, 0.5):
-                            xy(playfield_center_x + col_x_offset as f32, y_pos):
+                            material(slot.def.material):
+                            xy(playfield_center_x + col_x_offset, y_pos):
                             zoomto(size[0], size[1]):
                             rotationz(rotation):
                             customtexturerect(uv[0], uv[1], uv[2], uv[3]):
@@ -1173,30 +1293,45 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     _ => Quantization::Q192nd,
                 };
 
-                let note_idx = (arrow.column % 4) * NUM_QUANTIZATIONS + quantization as usize;
+                let note_idx = (arrow.column % state.num_cols) * NUM_QUANTIZATIONS + quantization as usize;
                 if let Some(note_slot) = ns.notes.get(note_idx) {
                     let note_frame =
-                        note_slot.frame_index(state.total_elapsed_in_screen, state.current_beat);
+                        note_slot.frame_index(state.visual_clock.time, state.visual_clock.beat);
                     let note_uv = note_slot.uv_for_frame(note_frame);
                     let note_size = scale_sprite(note_slot.size());
 
                     actors.push(act!(sprite(note_slot.texture_key().to_string()):
                         align(0.5, 0.5):
-                        xy(playfield_center_x + col_x_offset as f32, y_pos):
+                        material(note_slot.def.material):
+                        xy(playfield_center_x + col_x_offset, y_pos):
                         zoomto(note_size[0] as f32, note_size[1] as f32):
                         rotationz(-note_slot.def.rotation_deg as f32):
                         customtexturerect(note_uv[0], note_uv[1], note_uv[2], note_uv[3]):
                         z(Z_TAP_NOTE)
                     ));
                 }
+
+                if let Some(Some(foot)) = state.foot_hints.get(arrow.note_index) {
+                    let (label, tint) = match foot {
+                        Foot::Left => ("L", FOOT_HINT_LEFT_COLOR),
+                        Foot::Right => ("R", FOOT_HINT_RIGHT_COLOR),
+                    };
+                    actors.push(act!(text:
+                        font("wendy"): settext(label):
+                        align(0.5, 0.5):
+                        xy(playfield_center_x + col_x_offset, y_pos):
+                        zoom(0.35):
+                        diffuse(tint[0], tint[1], tint[2], 1.0):
+                        z(Z_TAP_NOTE + 1)
+                    ));
+                }
             }
         }
     }
 
     // Combo Milestone Explosions (100 / 1000 combo)
     if !state.combo_milestones.is_empty() {
-        let combo_center_x = playfield_center_x;
-        let combo_center_y = screen_center_y() + 30.0;
+        let [combo_center_x, combo_center_y] = combo_xy;
         let player_color = state.player_color;
 
         let ease_out_quad = |t: f32| -> f32 {
@@ -1205,9 +1340,9 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         };
 
         for milestone in &state.combo_milestones {
-            match milestone.kind {
+            match milestone.data {
                 ComboMilestoneKind::Hundred => {
-                    let elapsed = milestone.elapsed;
+                    let elapsed = milestone.elapsed();
                     let explosion_duration = 0.5_f32;
                     if elapsed <= explosion_duration {
                         let progress = (elapsed / explosion_duration).clamp(0.0, 1.0);
@@ -1272,7 +1407,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     }
                 }
                 ComboMilestoneKind::Thousand => {
-                    let elapsed = milestone.elapsed;
+                    let elapsed = milestone.elapsed();
                     if elapsed <= COMBO_THOUSAND_MILESTONE_DURATION {
                         let progress =
                             (elapsed / COMBO_THOUSAND_MILESTONE_DURATION).clamp(0.0, 1.0);
@@ -1306,7 +1441,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     if state.miss_combo >= SHOW_COMBO_AT {
         actors.push(act!(text:
             font("wendy_combo"): settext(state.miss_combo.to_string()):
-            align(0.5, 0.5): xy(playfield_center_x, screen_center_y() + 30.0):
+            align(0.5, 0.5): xy(combo_xy[0], combo_xy[1]):
             zoom(0.75): horizalign(center):
             diffuse(1.0, 0.0, 0.0, 1.0):
             z(90)
@@ -1323,22 +1458,14 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             ([1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 1.0])
         };
 
-        let effect_period = 0.8;
-        let t = (state.total_elapsed_in_screen / effect_period).fract();
-        let anim_t = ((t * 2.0 * std::f32::consts::PI).sin() + 1.0) / 2.0;
-
-        let final_color = [
-            color1[0] + (color2[0] - color1[0]) * anim_t,
-            color1[1] + (color2[1] - color1[1]) * anim_t,
-            color1[2] + (color2[2] - color1[2]) * anim_t,
-            1.0,
-        ];
-
         actors.push(act!(text:
             font("wendy_combo"): settext(state.combo.to_string()):
-            align(0.5, 0.5): xy(playfield_center_x, screen_center_y() + 30.0):
+            align(0.5, 0.5): xy(combo_xy[0], combo_xy[1]):
             zoom(0.75): horizalign(center):
-            diffuse(final_color[0], final_color[1], final_color[2], final_color[3]):
+            // Two beats a cycle on the song clock, so it keeps time at any rate.
+            diffuseshift(): effectclock(beat): effectperiod(2.0):
+            effectcolor1(color1[0], color1[1], color1[2], 1.0):
+            effectcolor2(color2[0], color2[1], color2[2], 1.0):
             z(90)
         ));
     }
@@ -1369,7 +1496,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             let linear_index = (frame_base * 2 + frame_offset) as u32;
 
             actors.push(act!(sprite("judgements/Love 2x7 (doubleres).png"):
-                align(0.5, 0.5): xy(playfield_center_x, screen_center_y() - 30.0):
+                align(0.5, 0.5): xy(judgment_xy[0], judgment_xy[1]):
                 z(200): zoomtoheight(76.0): setstate(linear_index): zoom(zoom)
             ));
         }
@@ -1381,20 +1508,17 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             continue;
         };
 
-        let elapsed = render_info.triggered_at.elapsed().as_secs_f32();
-        if elapsed >= HOLD_JUDGMENT_TOTAL_DURATION {
-            continue;
-        }
+        let elapsed = render_info.elapsed();
 
-        let zoom = if elapsed < 0.3 {
+        let height = if elapsed < 0.3 {
             let progress = (elapsed / 0.3).clamp(0.0, 1.0);
-            HOLD_JUDGMENT_INITIAL_ZOOM
-                + progress * (HOLD_JUDGMENT_FINAL_ZOOM - HOLD_JUDGMENT_INITIAL_ZOOM)
+            HOLD_JUDGMENT_INITIAL_HEIGHT
+                + progress * (HOLD_JUDGMENT_FINAL_HEIGHT - HOLD_JUDGMENT_INITIAL_HEIGHT)
         } else {
-            HOLD_JUDGMENT_FINAL_ZOOM
+            HOLD_JUDGMENT_FINAL_HEIGHT
         };
 
-        let frame_index = match render_info.result {
+        let frame_index = match render_info.data {
             HoldResult::Held => 0,
             HoldResult::LetGo => 1,
         } as u32;
@@ -1404,15 +1528,17 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             .as_ref()
             .and_then(|ns| ns.column_xs.get(column))
             .map(|&x| x as f32)
-            .unwrap_or_else(|| ((column as f32) - 1.5) * TARGET_ARROW_PIXEL_SIZE);
+            .unwrap_or_else(|| ((column as f32) - 1.5) * TARGET_ARROW_PIXEL_SIZE)
+            * state.mods.field_zoom()
+            * profile.column_spacing;
 
         actors.push(act!(sprite("hold_judgements/Love 1x2 (doubleres).png"):
             align(0.5, 0.5):
             xy(playfield_center_x + column_offset, hold_judgment_y):
             z(195):
             setstate(frame_index):
-            zoom(zoom):
-            diffusealpha(1.0)
+            zoomtoheight(height):
+            diffusealpha(1.0 - render_info.value())
         ));
     }
 
@@ -1443,6 +1569,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             ),
         ],
         background: None,
+        clip: false,
         z: 90,
     });
 
@@ -1466,6 +1593,43 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         zoom(0.5): horizalign(right): z(90)
     ));
 
+    // Ghost pacer: lead over (or gap to) the personal best at this point in the song.
+    if let Some(delta) = ghost_delta_percent(state) {
+        let (r, g, b) = if delta >= 0.0 { (0.4, 1.0, 0.4) } else { (1.0, 0.4, 0.4) };
+        actors.push(act!(text:
+            font("miso"): settext(format!("{:+.2}% vs PB", delta)):
+            align(1.0, 0.0): xy(score_x, score_y + 2.0):
+            zoom(0.6): horizalign(right): diffuse(r, g, b, 0.9): z(90)
+        ));
+    }
+
+    // Quiet note after an automatic quality downshift; fades over its last second.
+    if let Some(notice) = &state.quality_notice {
+        let text = match state.quality {
+            QualityLevel::Full => "",
+            QualityLevel::NoGlow => "Reduced effects to keep up",
+            QualityLevel::FlatMines => "Simplified mines to keep up",
+            QualityLevel::NoBackground => "Hid background to keep up",
+        };
+        let alpha = 0.6 * (notice.duration() - notice.elapsed()).clamp(0.0, 1.0);
+        actors.push(act!(text:
+            font("miso"): settext(text):
+            align(0.5, 1.0): xy(screen_center_x(), screen_height() - 8.0):
+            zoom(0.55): horizalign(center): diffuse(1.0, 1.0, 1.0, alpha): z(90)
+        ));
+    }
+
+    // Song start countdown: each prompt pops in over the field and fades over its last third.
+    if let Some((text, elapsed, duration)) = state.countdown.and_then(|c| c.prompt_at(state.current_music_time)) {
+        let pop = 1.0 + 0.3 * (1.0 - elapsed / 0.15).clamp(0.0, 1.0);
+        let alpha = ((duration - elapsed) / (duration / 3.0)).clamp(0.0, 1.0);
+        actors.push(act!(text:
+            font("wendy"): settext(text):
+            align(0.5, 0.5): xy(playfield_center_x, screen_center_y()):
+            zoom(0.9 * pop): horizalign(center): diffuse(1.0, 1.0, 1.0, alpha): z(95)
+        ));
+    }
+
     // Current BPM Display (1:1 with Simply Love)
     {
         let bpm_value = state.timing.get_bpm_for_beat(state.current_beat);
@@ -1499,7 +1663,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             zoom(bpm_final_zoom): horizalign(center): z(90)
         ));
 
-        let music_rate = 1.0_f32; // Placeholder until dynamic music rate support exists
+        let music_rate = state.mods.rate;
         let rate_text = if (music_rate - 1.0).abs() > 0.001 {
             format!("{music_rate:.2}x rate")
         } else {
@@ -1533,14 +1697,11 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             ));
         }
 
-        let full_title = if state.song.subtitle.trim().is_empty() {
-            state.song.title.clone()
-        } else {
-            format!("{} {}", state.song.title, state.song.subtitle)
-        };
+        let prefer_translit = crate::config::get().prefer_translit;
+        let full_title = state.song.disambiguated(&state.song.display_full_title(prefer_translit), prefer_translit);
         frame_children.push(act!(text:
             font("miso"): settext(full_title): align(0.5, 0.5): xy(w / 2.0, h / 2.0):
-            zoom(0.8): maxwidth(screen_width() / 2.5 - 10.0): horizalign(center): z(3)
+            zoom(0.8): maxwidth(screen_width() / 2.5 - 10.0): marquee(true): horizalign(center): z(3)
         ));
 
         actors.push(Actor::Frame {
@@ -1548,6 +1709,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             offset: [box_cx, box_cy],
             size: [SizeSpec::Px(w), SizeSpec::Px(h)],
             background: None,
+            clip: false,
             z: 90,
             children: frame_children,
         });
@@ -1560,47 +1722,52 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         let meter_cx = screen_center_x() - widescale(238.0, 288.0);
         let meter_cy = 20.0;
 
-        // Frames/border
-        actors.push(act!(quad: align(0.5, 0.5): xy(meter_cx, meter_cy): zoomto(w + 4.0, h + 4.0): diffuse(1.0, 1.0, 1.0, 1.0): z(90) ));
-        actors.push(act!(quad: align(0.5, 0.5): xy(meter_cx, meter_cy): zoomto(w, h): diffuse(0.0, 0.0, 0.0, 1.0): z(91) ));
-
-        // Latch-to-zero for rendering the very frame we die.
-        let dead = state.is_failing || state.life <= 0.0;
-        let life_for_render = if dead {
-            0.0
+        if let Some(t) = fail_sequence_time(state) {
+            actors.extend(build_life_meter_shards([meter_cx, meter_cy], [w + 4.0, h + 4.0], t));
         } else {
-            state.life.clamp(0.0, 1.0)
-        };
+            // Frames/border
+            actors.push(act!(quad: align(0.5, 0.5): xy(meter_cx, meter_cy): zoomto(w + 4.0, h + 4.0): diffuse(1.0, 1.0, 1.0, 1.0): z(90) ));
+            actors.push(act!(quad: align(0.5, 0.5): xy(meter_cx, meter_cy): zoomto(w, h): diffuse(0.0, 0.0, 0.0, 1.0): z(91) ));
+
+            // Latch-to-zero for rendering the very frame we die.
+            let dead = state.is_failing || state.life <= 0.0;
+            let life_for_render = if dead {
+                0.0
+            } else {
+                state.life.clamp(0.0, 1.0)
+            };
 
-        let is_hot = !dead && life_for_render >= 1.0;
-        let life_color = if is_hot {
-            [1.0, 1.0, 1.0, 1.0]
-        } else {
-            state.player_color
-        };
+            let is_hot = !dead && life_for_render >= 1.0;
+            let life_color = if is_hot {
+                [1.0, 1.0, 1.0, 1.0]
+            } else {
+                state.player_color
+            };
 
-        let filled_width = w * life_for_render;
-
-        // Never draw swoosh if dead OR nothing to fill.
-        if filled_width > 0.0 && !dead {
-            let bps = state.timing.get_bpm_for_beat(state.current_beat) / 60.0;
-            let swoosh_alpha = if is_hot { 1.0 } else { 0.2 };
-            actors.push(act!(sprite("swoosh.png"):
-                align(0.0, 0.5):
-                xy(meter_cx - w / 2.0, meter_cy):
-                zoomto(filled_width, h):
-                diffusealpha(swoosh_alpha):
-                texcoordvelocity(-(bps * 0.5), 0.0):
-                z(93)
-            ));
+            let filled_width = w * life_for_render;
+
+            // Never draw swoosh if dead OR nothing to fill.
+            if filled_width > 0.0 && !dead {
+                let swoosh_alpha = if is_hot { 1.0 } else { 0.2 };
+                // Half a texture per beat, on the song clock so it stays locked under rate mods.
+                actors.push(act!(sprite("swoosh.png"):
+                    align(0.0, 0.5):
+                    xy(meter_cx - w / 2.0, meter_cy):
+                    zoomto(filled_width, h):
+                    diffusealpha(swoosh_alpha):
+                    texcoordvelocity(-0.5, 0.0):
+                    effectclock(beat):
+                    z(93)
+                ));
 
-            actors.push(act!(quad:
-                align(0.0, 0.5):
-                xy(meter_cx - w / 2.0, meter_cy):
-                zoomto(filled_width, h):
-                diffuse(life_color[0], life_color[1], life_color[2], 1.0):
-                z(92)
-            ));
+                actors.push(act!(quad:
+                    align(0.0, 0.5):
+                    xy(meter_cx - w / 2.0, meter_cy):
+                    zoomto(filled_width, h):
+                    diffuse(life_color[0], life_color[1], life_color[2], 1.0):
+                    z(92)
+                ));
+            }
         }
     }
 
@@ -1614,6 +1781,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         center_text: None,
         right_text: None,
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets::NONE,
     }));
 
     actors.extend(build_side_pane(state, asset_manager));
@@ -1748,6 +1916,7 @@ fn build_holds_mines_rolls_pane(state: &State, asset_manager: &AssetManager) ->
         size: [SizeSpec::Px(0.0), SizeSpec::Px(0.0)],
         children,
         background: None,
+        clip: false,
         z: 70,
     });
     actors
@@ -1791,7 +1960,20 @@ fn build_side_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     const LABEL_DIGIT_STEP: f32 = 16.0;
     const NUMBER_TO_LABEL_GAP: f32 = 8.0;
     let base_numbers_local_x_offset = base_label_local_x_offset - NUMBER_TO_LABEL_GAP;
-    let row_height = 35.0;
+    // (label, color, count) per row. FA+ splits white Fantastics into a row of
+    // their own above the blue ones, squeezing the rows into the same space.
+    let mut rows: Vec<(&str, [f32; 4], u32)> = JUDGMENT_ORDER
+        .iter()
+        .map(|grade| {
+            let info = JUDGMENT_INFO.get(grade).unwrap();
+            (info.label, info.color, *state.judgment_counts.get(grade).unwrap_or(&0))
+        })
+        .collect();
+    if state.fa_plus {
+        rows[0].2 = rows[0].2.saturating_sub(state.white_fantastic_count);
+        rows.insert(0, ("FANTASTIC", color::rgba_hex(color::WHITE_FANTASTIC_HEX), state.white_fantastic_count));
+    }
+    let row_height = 35.0 * (JUDGMENT_ORDER.len() - 1) as f32 / (rows.len() - 1) as f32;
     let y_base = -280.0;
 
     asset_manager.with_fonts(|all_fonts| asset_manager.with_font("wendy_screenevaluation", |f| {
@@ -1805,14 +1987,10 @@ fn build_side_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         let numbers_local_x_offset = base_numbers_local_x_offset + (extra_digits * digit_local_width);
         let numbers_cx = final_judgments_center_x + (numbers_local_x_offset * final_text_base_zoom);
 
-        for (index, grade) in JUDGMENT_ORDER.iter().enumerate() {
-            let info = JUDGMENT_INFO.get(grade).unwrap();
-            let count = *state.judgment_counts.get(grade).unwrap_or(&0);
-
+        for (index, &(label, bright, count)) in rows.iter().enumerate() {
             let local_y = y_base + (index as f32 * row_height);
             let world_y = final_judgments_center_y + (local_y * final_text_base_zoom);
 
-            let bright = info.color;
             let dim = [bright[0]*0.35, bright[1]*0.35, bright[2]*0.35, bright[3]];
             let full_number_str = format!("{:0width$}", count, width = digits);
 
@@ -1836,7 +2014,7 @@ fn build_side_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             let label_zoom = final_text_base_zoom * 0.833;
 
             actors.push(act!(text:
-                font("miso"): settext(info.label): align(0.0, 0.5):
+                font("miso"): settext(label): align(0.0, 0.5):
                 xy(label_world_x, label_world_y): zoom(label_zoom):
                 maxwidth(72.0 * final_text_base_zoom): horizalign(left):
                 diffuse(bright[0], bright[1], bright[2], bright[3]):
@@ -1851,12 +2029,7 @@ fn build_side_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             let total_seconds = state.song.total_length_seconds.max(0) as f32;
             let total_time_str = format_game_time(total_seconds, total_seconds);
 
-            let remaining_seconds = if let Some(fail_time) = state.fail_time {
-                (total_seconds - fail_time.max(0.0)).max(0.0)
-            } else {
-                (total_seconds - state.current_music_time.max(0.0)).max(0.0)
-            };
-            let remaining_time_str = format_game_time(remaining_seconds, total_seconds);
+            let remaining_time_str = format_game_time(state.remaining_seconds as f32, total_seconds);
 
             let font_name = "miso";
             let text_zoom = banner_data_zoom * 0.833;
@@ -1900,6 +2073,20 @@ fn build_side_pane(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 zoom(text_zoom): z(71):
                 diffuse(white_color[0], white_color[1], white_color[2], white_color[3])
             ));
+
+            // --- Song Progress Ring, beside both rows ---
+            let ring_center = [numbers_left_x + label_offset + 72.0, (y_pos_total + y_pos_remaining) * 0.5];
+            let played = if total_seconds > 0.0 { 1.0 - state.remaining_seconds as f32 / total_seconds } else { 0.0 };
+            actors.push(primitives::arc(ArcParams {
+                center: ring_center, radius: 8.0, thickness: 3.0,
+                start_deg: 0.0, sweep_deg: 360.0,
+                color: [1.0, 1.0, 1.0, 0.2], z: 71,
+            }));
+            actors.push(primitives::arc(ArcParams {
+                center: ring_center, radius: 8.0, thickness: 3.0,
+                start_deg: 0.0, sweep_deg: 360.0 * played.clamp(0.0, 1.0),
+                color: remaining_color, z: 72,
+            }));
         }
     }));
 
@@ -1,6 +1,7 @@
 use crate::act;
 use crate::screens::{Screen, ScreenAction};
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use crate::ui::color;
 use crate::ui::components::logo::{self, LogoParams};
 use crate::ui::components::menu_list::{self};
@@ -9,6 +10,7 @@ use winit::event::{ElementState, KeyEvent};
 use winit::keyboard::{KeyCode, PhysicalKey};
 use crate::game::song::get_song_cache;
 use crate::core::network::{self, ConnectionStatus};
+use crate::core::updates;
 
 use crate::core::space::*;
 
@@ -63,6 +65,10 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
         },
         // Escape is now handled globally in app.rs but we can leave this for clarity
         PhysicalKey::Code(KeyCode::Escape) => ScreenAction::Exit,
+        PhysicalKey::Code(KeyCode::Backspace) => {
+            updates::dismiss_toast();
+            ScreenAction::None
+        }
         _ => {
             let delta: isize = match event.physical_key {
                 PhysicalKey::Code(KeyCode::ArrowUp) | PhysicalKey::Code(KeyCode::KeyW) => -1,
@@ -85,7 +91,7 @@ pub fn in_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 1.0):
-        z(1100):
+        z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -97,7 +103,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -187,6 +193,7 @@ pub fn get_actors(state: &State, alpha_multiplier: f32) -> Vec<Actor> {
         center_text: None,
         right_text: Some("PRESS START"),
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets::NONE,
         fg_color: footer_fg,
     }));
 
@@ -251,5 +258,48 @@ pub fn get_actors(state: &State, alpha_multiplier: f32) -> Vec<Actor> {
     }
     actors.extend(groovestats_actors);
 
+    // --- Update toast (bottom-right, above the footer) ---
+    if let Some(release) = updates::pending_toast() {
+        actors.extend(build_update_toast(&release, alpha_multiplier));
+    }
+
+    actors
+}
+
+fn build_update_toast(release: &updates::Release, alpha_multiplier: f32) -> Vec<Actor> {
+    const W: f32 = 320.0;
+    const PAD: f32 = 8.0;
+    const TITLE_H: f32 = 18.0;
+    const LINE_H: f32 = 14.0;
+    const MARGIN: f32 = 10.0;
+    const FOOTER_H: f32 = 32.0;
+
+    let mut lines: Vec<String> = release.changelog.iter().map(|l| format!("• {}", l)).collect();
+    lines.push("BACKSPACE: dismiss".to_string());
+    let h = PAD * 2.0 + TITLE_H + LINE_H * lines.len() as f32;
+    let left = screen_width() - MARGIN - W;
+    let top = screen_height() - FOOTER_H - MARGIN - h;
+    let a = alpha_multiplier;
+
+    let mut actors = vec![
+        act!(quad:
+            align(0.0, 0.0): xy(left, top): zoomto(W, h):
+            diffuse(0.0, 0.0, 0.0, 0.8 * a): z(210)
+        ),
+        act!(text:
+            align(0.0, 0.0): xy(left + PAD, top + PAD): zoom(0.8):
+            font("miso"): settext(format!("DeadSync {} is available", release.version)): horizalign(left):
+            diffuse(1.0, 1.0, 1.0, a): z(211)
+        ),
+    ];
+    let last = lines.len() - 1;
+    for (i, line) in lines.into_iter().enumerate() {
+        let shade = if i == last { 0.6 } else { 0.9 };
+        actors.push(act!(text:
+            align(0.0, 0.0): xy(left + PAD, top + PAD + TITLE_H + LINE_H * i as f32): zoom(0.6):
+            font("miso"): settext(line): horizalign(left):
+            diffuse(shade, shade, shade, a): z(211)
+        ));
+    }
     actors
 }
@@ -7,17 +7,24 @@ pub mod select_music;
 pub mod sandbox;
 pub mod evaluation;
 pub mod player_options;
+pub mod sync_test;
 use std::path::PathBuf;
 
-use crate::game::chart::ChartData;
 #[derive(Debug, Clone, PartialEq)]
 pub enum ScreenAction {
     None,
     Navigate(Screen),
     Exit,
     RequestBanner(Option<PathBuf>),
-    RequestDensityGraph(Option<ChartData>),
+    /// Play this video muted and looping over song select's banner, or stop it for None.
+    RequestPreviewVideo(Option<PathBuf>),
     FetchOnlineGrade(String),
+    /// Show a short message in the system message bar at the top of the screen.
+    ShowMessage(String),
+    /// Re-apply the present mode and frame limit from the config.
+    ApplyPresentSettings,
+    /// Move the window to the display mode, resolution and monitor in the config.
+    ApplyDisplayMode,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,4 +38,5 @@ pub enum Screen {
     Sandbox,
     Evaluation,
     PlayerOptions,
+    SyncTest,
 }
\ No newline at end of file
@@ -1,10 +1,19 @@
 use crate::act;
 use crate::core::space::*;
 use crate::screens::{Screen, ScreenAction};
+use crate::config;
 use crate::core::audio;
+use crate::core::display::{self, DisplayMode};
+use crate::core::gfx::{BackendType, HdrOutput, PresentMode, UpscaleFilter, MAX_FRAMES_IN_FLIGHT, MSAA_SAMPLE_CHOICES, RENDER_SCALE_CHOICES};
+use crate::core::gfx::frame_limiter::FRAME_LIMIT_CHOICES;
+use crate::core::led_marquee::{self, LedMarqueeOutput};
+use crate::game::{backup, profile};
+use log::info;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use crate::ui::color;
 use crate::ui::components::{heart_bg, screen_bar};
 use crate::ui::components::screen_bar::{ScreenBarPosition, ScreenBarTitlePlacement};
@@ -65,6 +74,20 @@ pub const ITEMS: &[Item] = &[
     Item { name: "Test Input",                      help: &["View live input state for debugging."] },
     Item { name: "Input Options",                   help: &["Debounce, menu buttons, coin mode…"] },
     Item { name: "Graphics/Sound Options",          help: &["Resolution, VSync, sound device…"] },
+    Item { name: "Display Mode",                    help: &["Windowed, borderless over the desktop, or exclusive", "fullscreen at the chosen resolution and refresh rate."] },
+    Item { name: "Resolution",                      help: &["Window size, and the exclusive fullscreen mode.", "Lists the modes the chosen monitor reports."] },
+    Item { name: "Refresh Rate",                    help: &["Exclusive fullscreen refresh rate.", "Highest takes the fastest the resolution offers."] },
+    Item { name: "Monitor",                         help: &["Which display fullscreen modes go to."] },
+    Item { name: "Video Renderer",                  help: &["Auto tries Vulkan, then OpenGL.", "Left/Right: switch. Applies on next launch."] },
+    Item { name: "Present Mode",                    help: &["FIFO waits for vblank; Immediate may tear", "but shows frames soonest. Auto follows VSync."] },
+    Item { name: "Frame Limit",                     help: &["Caps the frame rate for a steady cadence", "when the present mode doesn't wait for vblank."] },
+    Item { name: "Assist Tick Volume",              help: &["Loudness of the clap or metronome tick F1 turns", "on in gameplay, apart from the music."] },
+    Item { name: "A/V Sync Test",                   help: &["Flashes and clicks on every beat.", "Tune the global offset until they line up."] },
+    Item { name: "Frames In Flight",                help: &["Frames queued ahead of the display.", "1 is the lowest latency. Applies on next launch."] },
+    Item { name: "Anti-Aliasing",                   help: &["Smooths the edges of rotated and scaled sprites.", "Higher costs more GPU time. Applies on next launch."] },
+    Item { name: "Render Scale",                    help: &["Draws the game below window resolution and", "stretches it to fit. Lower helps weak GPUs."] },
+    Item { name: "Upscale Filter",                  help: &["How a lowered render scale is stretched:", "Linear is smooth, Nearest is sharp and blocky."] },
+    Item { name: "HDR Output",                      help: &["For HDR monitors; Vulkan only. HDR10 suits most", "displays, scRGB Windows. Applies on next launch."] },
     Item { name: "Visual Options",                  help: &["Judgment, combo, lifebar, etc."] },
     Item { name: "Arcade Options",                  help: &["Coin mode, premium, attract mode…"] },
     Item { name: "View Bookkeeping Data",           help: &["Audit play counts, coins, uptime."] },
@@ -73,8 +96,12 @@ pub const ITEMS: &[Item] = &[
     Item { name: "Network Options",                 help: &["Online features, matchmaking, latency…"] },
     Item { name: "Profiles",                        help: &["Create, select, and edit player profiles."] },
     Item { name: "Theme Options",                   help: &["UI skin, colorway, layout, accessibility."] },
+    Item { name: "Song Start",                      help: &["Countdown shows Ready? and 3-2-1-GO before the", "first step. Instant starts without it."] },
+    Item { name: "Song Titles",                     help: &["Native titles, or #TITLETRANSLIT when a song has one.", "Left/Right: switch. Sorting always uses transliterations."] },
     Item { name: "Data Management",                 help: &["Save data, screenshots, logs, cache."] },
+    Item { name: "Restore Profile Backup",          help: &["Left/Right: choose a backup. Start: restore it over", "current profiles, which are kept as a new backup."] },
     Item { name: "Service Options",                 help: &["Cabinet/service settings for operators."] },
+    Item { name: "LED Marquee",                     help: &["Sends Now Playing text to a marquee board over", "a serial port or a UDP broadcast during gameplay."] },
     Item { name: "Credits",                         help: &["Project contributors and licenses."] },
     Item { name: "Exit",                            help: &["Return to the main menu."] },
 ];
@@ -92,6 +119,326 @@ pub struct State {
     nav_key_held_direction: Option<NavDirection>,
     nav_key_held_since: Option<Instant>,
     nav_key_last_scrolled_at: Option<Instant>,
+    backups: Vec<PathBuf>,
+    backup_index: usize,
+    backup_status: Option<String>,
+    /// A display change waiting to be kept, and what it replaced.
+    display_confirm: Option<DisplayConfirm>,
+    /// The chosen backup is waiting for a second Start before it's restored.
+    restore_confirm: bool,
+}
+
+// A display change reverts unless kept within this long.
+const DISPLAY_CONFIRM_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The config's display settings, as a change to them can be undone.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct DisplaySettings {
+    mode: DisplayMode,
+    width: u32,
+    height: u32,
+    refresh_rate: u32,
+    monitor: u32,
+}
+
+impl DisplaySettings {
+    fn current() -> Self {
+        let cfg = config::get();
+        Self {
+            mode: cfg.display_mode,
+            width: cfg.display_width,
+            height: cfg.display_height,
+            refresh_rate: cfg.refresh_rate,
+            monitor: cfg.display_monitor,
+        }
+    }
+
+    fn restore(self) {
+        config::update_display_monitor(self.monitor);
+        config::update_display_resolution(self.width, self.height);
+        config::update_refresh_rate(self.refresh_rate);
+        config::update_display_mode(self.mode);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct DisplayConfirm {
+    previous: DisplaySettings,
+    deadline: Instant,
+}
+
+fn sync_test_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "A/V Sync Test").unwrap_or(usize::MAX)
+}
+
+fn restore_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Restore Profile Backup").unwrap_or(usize::MAX)
+}
+
+/// Makes a display change through `change` and applies it, but only until
+/// `DISPLAY_CONFIRM_TIMEOUT` passes without the player keeping it: a mode the
+/// monitor can't show would otherwise leave them with no way back.
+fn change_display(state: &mut State, change: impl FnOnce()) -> ScreenAction {
+    let before = DisplaySettings::current();
+    change();
+    if DisplaySettings::current() == before {
+        return ScreenAction::None;
+    }
+    audio::play_sfx("assets/sounds/change_value.ogg");
+    // Further changes before confirming still revert to the last kept settings.
+    let previous = state.display_confirm.map_or(before, |c| c.previous);
+    state.display_confirm = Some(DisplayConfirm { previous, deadline: Instant::now() + DISPLAY_CONFIRM_TIMEOUT });
+    ScreenAction::ApplyDisplayMode
+}
+
+// Restores the backup picked in the list. The replaced profiles become a new
+// backup, so the list is read again and the cursor follows the restored one.
+fn restore_chosen_backup(state: &mut State) {
+    let Some(path) = state.backups.get(state.backup_index).cloned() else {
+        return;
+    };
+    state.backup_status = Some(match backup::restore_backup(&path) {
+        Ok(kept) => {
+            profile::load();
+            audio::play_sfx("assets/sounds/start.ogg");
+            state.backups = backup::list_backups();
+            state.backup_index = state.backups.iter().position(|b| *b == path).unwrap_or(0);
+            match kept {
+                Some(kept) => format!("Restored {}; previous profiles kept as {}", backup::backup_label(&path), backup::backup_label(&kept)),
+                None => format!("Restored {}", backup::backup_label(&path)),
+            }
+        }
+        Err(e) => format!("Restore failed: {}", e),
+    });
+}
+
+fn revert_display(state: &mut State) -> ScreenAction {
+    let Some(confirm) = state.display_confirm.take() else {
+        return ScreenAction::None;
+    };
+    info!("Display change not kept; restoring the previous settings.");
+    confirm.previous.restore();
+    ScreenAction::ApplyDisplayMode
+}
+
+fn display_mode_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Display Mode").unwrap_or(usize::MAX)
+}
+
+const DISPLAY_MODE_CHOICES: [DisplayMode; 3] = [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Exclusive];
+
+fn cycle_display_mode(delta: isize) {
+    let n = DISPLAY_MODE_CHOICES.len() as isize;
+    let current = config::get().display_mode;
+    let idx = DISPLAY_MODE_CHOICES.iter().position(|&m| m == current).unwrap_or(0) as isize;
+    config::update_display_mode(DISPLAY_MODE_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn resolution_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Resolution").unwrap_or(usize::MAX)
+}
+
+/// Steps through the chosen monitor's resolutions; the refresh rate falls back
+/// to Highest when the new resolution doesn't offer it.
+fn cycle_resolution(delta: isize) {
+    let cfg = config::get();
+    let Some(monitor) = display::monitor_info(cfg.display_monitor) else { return; };
+    if monitor.modes.is_empty() { return; }
+    let n = monitor.modes.len() as isize;
+    let idx = monitor.modes.iter()
+        .position(|&(w, h, _)| (w, h) == (cfg.display_width, cfg.display_height))
+        .map_or(if delta > 0 { -1 } else { 0 }, |i| i as isize);
+    let (width, height, rates) = &monitor.modes[(idx + delta).rem_euclid(n) as usize];
+    config::update_display_resolution(*width, *height);
+    if !rates.contains(&cfg.refresh_rate) {
+        config::update_refresh_rate(0);
+    }
+}
+
+fn refresh_rate_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Refresh Rate").unwrap_or(usize::MAX)
+}
+
+fn cycle_refresh_rate(delta: isize) {
+    let cfg = config::get();
+    let mut choices = vec![0];
+    if let Some((_, _, rates)) = display::monitor_info(cfg.display_monitor)
+        .and_then(|m| m.modes.into_iter().find(|&(w, h, _)| (w, h) == (cfg.display_width, cfg.display_height)))
+    {
+        choices.extend(rates);
+    }
+    let n = choices.len() as isize;
+    let idx = choices.iter().position(|&hz| hz == cfg.refresh_rate).unwrap_or(0) as isize;
+    config::update_refresh_rate(choices[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn refresh_rate_label(hz: u32) -> String {
+    if hz == 0 { "Highest".to_string() } else { format!("{} Hz", hz) }
+}
+
+fn monitor_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Monitor").unwrap_or(usize::MAX)
+}
+
+fn cycle_monitor(delta: isize) {
+    let n = display::monitors().len() as isize + 1;
+    let current = (config::get().display_monitor as isize).min(n - 1);
+    config::update_display_monitor((current + delta).rem_euclid(n) as u32);
+}
+
+fn monitor_label(index: u32) -> String {
+    match (index, display::monitors().get((index as usize).wrapping_sub(1))) {
+        (0, _) => "Primary".to_string(),
+        (_, Some(monitor)) => format!("{}: {}", index, monitor.name),
+        (_, None) => format!("{} (not connected)", index),
+    }
+}
+
+fn video_renderer_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Video Renderer").unwrap_or(usize::MAX)
+}
+
+const VIDEO_RENDERER_CHOICES: [BackendType; 3] = [BackendType::Auto, BackendType::Vulkan, BackendType::OpenGL];
+
+fn cycle_video_renderer(delta: isize) {
+    let n = VIDEO_RENDERER_CHOICES.len() as isize;
+    let current = config::get().video_renderer;
+    let idx = VIDEO_RENDERER_CHOICES.iter().position(|&r| r == current).unwrap_or(0) as isize;
+    config::update_video_renderer(VIDEO_RENDERER_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn present_mode_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Present Mode").unwrap_or(usize::MAX)
+}
+
+const PRESENT_MODE_CHOICES: [PresentMode; 4] =
+    [PresentMode::Auto, PresentMode::Fifo, PresentMode::Mailbox, PresentMode::Immediate];
+
+fn cycle_present_mode(delta: isize) {
+    let n = PRESENT_MODE_CHOICES.len() as isize;
+    let current = config::get().present_mode;
+    let idx = PRESENT_MODE_CHOICES.iter().position(|&m| m == current).unwrap_or(0) as isize;
+    config::update_present_mode(PRESENT_MODE_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn frame_limit_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Frame Limit").unwrap_or(usize::MAX)
+}
+
+fn cycle_frame_limit(delta: isize) {
+    let n = FRAME_LIMIT_CHOICES.len() as isize;
+    let current = config::get().max_fps;
+    let idx = FRAME_LIMIT_CHOICES.iter().position(|&f| f == current).unwrap_or(0) as isize;
+    config::update_max_fps(FRAME_LIMIT_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn frame_limit_label(max_fps: u32) -> String {
+    if max_fps == 0 { "Uncapped".to_string() } else { format!("{} FPS", max_fps) }
+}
+
+fn assist_tick_volume_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Assist Tick Volume").unwrap_or(usize::MAX)
+}
+
+// Volume moves in steps of this many percent.
+const ASSIST_TICK_VOLUME_STEP: i32 = 10;
+
+fn step_assist_tick_volume(delta: i32) {
+    let volume = config::get().assist_tick_volume as i32 + delta * ASSIST_TICK_VOLUME_STEP;
+    config::update_assist_tick_volume(volume.clamp(0, 100) as u8);
+}
+
+fn frames_in_flight_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Frames In Flight").unwrap_or(usize::MAX)
+}
+
+fn cycle_frames_in_flight(delta: isize) {
+    let n = MAX_FRAMES_IN_FLIGHT as isize;
+    let current = config::get().frames_in_flight as isize - 1;
+    config::update_frames_in_flight(((current + delta).rem_euclid(n) + 1) as u8);
+}
+
+fn anti_aliasing_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Anti-Aliasing").unwrap_or(usize::MAX)
+}
+
+fn cycle_anti_aliasing(delta: isize) {
+    let n = MSAA_SAMPLE_CHOICES.len() as isize;
+    let current = config::get().msaa_samples;
+    let idx = MSAA_SAMPLE_CHOICES.iter().position(|&s| s == current).unwrap_or(0) as isize;
+    config::update_msaa_samples(MSAA_SAMPLE_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn anti_aliasing_label(samples: u32) -> String {
+    if samples <= 1 { "Off".to_string() } else { format!("{}x MSAA", samples) }
+}
+
+fn render_scale_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Render Scale").unwrap_or(usize::MAX)
+}
+
+fn cycle_render_scale(delta: isize) {
+    let n = RENDER_SCALE_CHOICES.len() as isize;
+    let current = config::get().render_scale;
+    let idx = RENDER_SCALE_CHOICES.iter().position(|&s| s == current).unwrap_or(n as usize - 1) as isize;
+    config::update_render_scale(RENDER_SCALE_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn render_scale_label(scale: u32) -> String {
+    if scale >= 100 { "Native".to_string() } else { format!("{}%", scale) }
+}
+
+fn upscale_filter_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Upscale Filter").unwrap_or(usize::MAX)
+}
+
+fn toggle_upscale_filter() {
+    config::update_upscale_filter(match config::get().upscale_filter {
+        UpscaleFilter::Linear => UpscaleFilter::Nearest,
+        UpscaleFilter::Nearest => UpscaleFilter::Linear,
+    });
+}
+
+fn hdr_output_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "HDR Output").unwrap_or(usize::MAX)
+}
+
+const HDR_OUTPUT_CHOICES: [HdrOutput; 3] = [HdrOutput::Off, HdrOutput::Hdr10, HdrOutput::ScRgb];
+
+fn cycle_hdr_output(delta: isize) {
+    let n = HDR_OUTPUT_CHOICES.len() as isize;
+    let current = config::get().hdr_output;
+    let idx = HDR_OUTPUT_CHOICES.iter().position(|&h| h == current).unwrap_or(0) as isize;
+    config::update_hdr_output(HDR_OUTPUT_CHOICES[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn song_start_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Song Start").unwrap_or(usize::MAX)
+}
+
+fn led_marquee_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "LED Marquee").unwrap_or(usize::MAX)
+}
+
+fn cycle_led_marquee(delta: isize) {
+    let choices: Vec<LedMarqueeOutput> = LedMarqueeOutput::choices().collect();
+    let n = choices.len() as isize;
+    let current = config::get().led_marquee;
+    let idx = choices.iter().position(|&o| o == current).unwrap_or(0) as isize;
+    config::update_led_marquee(choices[(idx + delta).rem_euclid(n) as usize]);
+}
+
+fn led_marquee_label(output: LedMarqueeOutput) -> String {
+    match output {
+        LedMarqueeOutput::Off => "Off".to_string(),
+        LedMarqueeOutput::Serial(n) if cfg!(windows) => format!("COM{}", n),
+        LedMarqueeOutput::Serial(n) => led_marquee::serial_path(n),
+        LedMarqueeOutput::Network => format!("UDP port {}", config::get().led_marquee_udp_port),
+    }
+}
+
+fn song_titles_item_index() -> usize {
+    ITEMS.iter().position(|i| i.name == "Song Titles").unwrap_or(usize::MAX)
 }
 
 pub fn init() -> State {
@@ -104,6 +451,11 @@ pub fn init() -> State {
         nav_key_held_direction: None,
         nav_key_held_since: None,
         nav_key_last_scrolled_at: None,
+        backups: backup::list_backups(),
+        backup_index: 0,
+        backup_status: None,
+        display_confirm: None,
+        restore_confirm: false,
     }
 }
 
@@ -112,7 +464,7 @@ pub fn in_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 1.0):
-        z(1100):
+        z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -124,7 +476,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -139,6 +491,34 @@ pub fn handle_key_press(state: &mut State, e: &KeyEvent) -> ScreenAction {
     if e.state == ElementState::Pressed {
         if e.repeat { return ScreenAction::None; } // We handle our own repeats in `update`
 
+        // A pending display change takes every key until it's kept or undone.
+        if state.display_confirm.is_some() {
+            match key_code {
+                KeyCode::Enter | KeyCode::NumpadEnter => {
+                    state.display_confirm = None;
+                    audio::play_sfx("assets/sounds/start.ogg");
+                }
+                KeyCode::Escape => return revert_display(state),
+                _ => {}
+            }
+            return ScreenAction::None;
+        }
+        // Likewise a restore waiting to be confirmed.
+        if state.restore_confirm {
+            match key_code {
+                KeyCode::Enter | KeyCode::NumpadEnter => {
+                    state.restore_confirm = false;
+                    restore_chosen_backup(state);
+                }
+                KeyCode::Escape => {
+                    state.restore_confirm = false;
+                    audio::play_sfx("assets/sounds/change_value.ogg");
+                }
+                _ => {}
+            }
+            return ScreenAction::None;
+        }
+
         match key_code {
             KeyCode::Escape => return ScreenAction::Navigate(Screen::Menu),
             KeyCode::ArrowUp | KeyCode::KeyW => {
@@ -157,11 +537,154 @@ pub fn handle_key_press(state: &mut State, e: &KeyEvent) -> ScreenAction {
                 state.nav_key_held_since = Some(Instant::now());
                 state.nav_key_last_scrolled_at = Some(Instant::now());
             }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == restore_item_index() => {
+                if !state.backups.is_empty() {
+                    let n = state.backups.len();
+                    state.backup_index = (state.backup_index + n - 1) % n;
+                    state.backup_status = None;
+                    audio::play_sfx("assets/sounds/change_value.ogg");
+                }
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == restore_item_index() => {
+                if !state.backups.is_empty() {
+                    state.backup_index = (state.backup_index + 1) % state.backups.len();
+                    state.backup_status = None;
+                    audio::play_sfx("assets/sounds/change_value.ogg");
+                }
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == display_mode_item_index() => {
+                return change_display(state, || cycle_display_mode(-1));
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == display_mode_item_index() => {
+                return change_display(state, || cycle_display_mode(1));
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == resolution_item_index() => {
+                return change_display(state, || cycle_resolution(-1));
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == resolution_item_index() => {
+                return change_display(state, || cycle_resolution(1));
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == refresh_rate_item_index() => {
+                return change_display(state, || cycle_refresh_rate(-1));
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == refresh_rate_item_index() => {
+                return change_display(state, || cycle_refresh_rate(1));
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == monitor_item_index() => {
+                return change_display(state, || cycle_monitor(-1));
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == monitor_item_index() => {
+                return change_display(state, || cycle_monitor(1));
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == video_renderer_item_index() => {
+                cycle_video_renderer(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == video_renderer_item_index() => {
+                cycle_video_renderer(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == present_mode_item_index() => {
+                cycle_present_mode(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == present_mode_item_index() => {
+                cycle_present_mode(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == frame_limit_item_index() => {
+                cycle_frame_limit(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == frame_limit_item_index() => {
+                cycle_frame_limit(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == assist_tick_volume_item_index() => {
+                step_assist_tick_volume(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == assist_tick_volume_item_index() => {
+                step_assist_tick_volume(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == frames_in_flight_item_index() => {
+                cycle_frames_in_flight(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == frames_in_flight_item_index() => {
+                cycle_frames_in_flight(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == anti_aliasing_item_index() => {
+                cycle_anti_aliasing(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == anti_aliasing_item_index() => {
+                cycle_anti_aliasing(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == render_scale_item_index() => {
+                cycle_render_scale(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == render_scale_item_index() => {
+                cycle_render_scale(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA | KeyCode::ArrowRight | KeyCode::KeyD
+                if state.selected == upscale_filter_item_index() =>
+            {
+                toggle_upscale_filter();
+                audio::play_sfx("assets/sounds/change_value.ogg");
+                return ScreenAction::ApplyPresentSettings;
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == hdr_output_item_index() => {
+                cycle_hdr_output(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == hdr_output_item_index() => {
+                cycle_hdr_output(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA | KeyCode::ArrowRight | KeyCode::KeyD
+                if state.selected == song_start_item_index() =>
+            {
+                config::update_song_countdown(!config::get().song_countdown);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA if state.selected == led_marquee_item_index() => {
+                cycle_led_marquee(-1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowRight | KeyCode::KeyD if state.selected == led_marquee_item_index() => {
+                cycle_led_marquee(1);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+            KeyCode::ArrowLeft | KeyCode::KeyA | KeyCode::ArrowRight | KeyCode::KeyD
+                if state.selected == song_titles_item_index() =>
+            {
+                config::update_prefer_translit(!config::get().prefer_translit);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
             KeyCode::Enter => {
                 // If the last item ("Exit") is selected, go back to main menu.
                 if total > 0 && state.selected == total - 1 {
                     return ScreenAction::Navigate(Screen::Menu);
                 }
+                if state.selected == sync_test_item_index() {
+                    return ScreenAction::Navigate(Screen::SyncTest);
+                }
+                if state.selected == restore_item_index() && !state.backups.is_empty() {
+                    state.restore_confirm = true;
+                    audio::play_sfx("assets/sounds/start.ogg");
+                }
             }
             _ => {}
         }
@@ -178,7 +701,7 @@ pub fn handle_key_press(state: &mut State, e: &KeyEvent) -> ScreenAction {
     ScreenAction::None
 }
 
-pub fn update(state: &mut State, _dt: f32) {
+pub fn update(state: &mut State, _dt: f32) -> ScreenAction {
     if let (Some(direction), Some(held_since), Some(last_scrolled_at)) =
         (state.nav_key_held_direction, state.nav_key_held_since, state.nav_key_last_scrolled_at)
     {
@@ -205,6 +728,11 @@ pub fn update(state: &mut State, _dt: f32) {
         audio::play_sfx("assets/sounds/change.ogg");
         state.prev_selected = state.selected;
     }
+
+    if state.display_confirm.is_some_and(|c| Instant::now() >= c.deadline) {
+        return revert_display(state);
+    }
+    ScreenAction::None
 }
 
 /* --------------------------------- layout -------------------------------- */
@@ -252,6 +780,9 @@ fn apply_alpha_to_actor(actor: &mut Actor, alpha: f32) {
     match actor {
         Actor::Sprite { tint, .. } => tint[3] *= alpha,
         Actor::Text { color, .. } => color[3] *= alpha,
+        Actor::Polyline { colors, .. } | Actor::Mesh { colors, .. } => {
+            colors.iter_mut().for_each(|c| c[3] *= alpha)
+        }
         Actor::Frame { background, children, .. } => {
             if let Some(actors::Background::Color(c)) = background {
                 c[3] *= alpha;
@@ -290,6 +821,7 @@ pub fn get_actors(state: &State, alpha_multiplier: f32) -> Vec<Actor> {
         center_text: None,
         right_text: None,
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets { groovestats: true, clock: true, ..screen_bar::ScreenBarWidgets::NONE },
         fg_color: FG,
     }));
 
@@ -467,6 +999,159 @@ pub fn get_actors(state: &State, alpha_multiplier: f32) -> Vec<Actor> {
         cursor_y += body_px + 8.0 * s;
     }
 
+    if sel == restore_item_index() {
+        cursor_y += 12.0 * s;
+        let chosen = match state.backups.get(state.backup_index) {
+            Some(path) => format!(
+                "< {} >  ({}/{})",
+                backup::backup_label(path),
+                state.backup_index + 1,
+                state.backups.len()
+            ),
+            None => "No backups found.".to_string(),
+        };
+        let mut lines = vec![chosen];
+        lines.extend(state.backup_status.clone());
+        for line in lines {
+            ui_actors.push(act!(text:
+                align(0.0, 0.0):
+                xy(desc_x + desc_pad_x + 12.0 * s, cursor_y):
+                zoomtoheight(body_px):
+                diffuse(1.0, 1.0, 1.0, 1.0):
+                font("miso"): settext(line):
+                horizalign(left)
+            ));
+            cursor_y += body_px + 8.0 * s;
+        }
+    }
+
+    if sel == video_renderer_item_index() {
+        cursor_y += 12.0 * s;
+        let chosen = config::get().video_renderer;
+        let mut lines = vec![format!("< {} >", chosen)];
+        if let Some(active) = config::active_video_renderer() {
+            lines.push(format!("Running: {}", active));
+        }
+        for line in lines {
+            ui_actors.push(act!(text:
+                align(0.0, 0.0):
+                xy(desc_x + desc_pad_x + 12.0 * s, cursor_y):
+                zoomtoheight(body_px):
+                diffuse(1.0, 1.0, 1.0, 1.0):
+                font("miso"): settext(line):
+                horizalign(left)
+            ));
+            cursor_y += body_px + 8.0 * s;
+        }
+    }
+
+    let latency_value = if sel == display_mode_item_index() {
+        Some(format!("< {} >", config::get().display_mode))
+    } else if sel == resolution_item_index() {
+        Some(format!("< {}x{} >", config::get().display_width, config::get().display_height))
+    } else if sel == refresh_rate_item_index() {
+        Some(format!("< {} >", refresh_rate_label(config::get().refresh_rate)))
+    } else if sel == monitor_item_index() {
+        Some(format!("< {} >", monitor_label(config::get().display_monitor)))
+    } else if sel == present_mode_item_index() {
+        Some(format!("< {} >", config::get().present_mode))
+    } else if sel == frame_limit_item_index() {
+        Some(format!("< {} >", frame_limit_label(config::get().max_fps)))
+    } else if sel == assist_tick_volume_item_index() {
+        Some(format!("< {}% >", config::get().assist_tick_volume))
+    } else if sel == frames_in_flight_item_index() {
+        Some(format!("< {} >", config::get().frames_in_flight))
+    } else if sel == anti_aliasing_item_index() {
+        Some(format!("< {} >", anti_aliasing_label(config::get().msaa_samples)))
+    } else if sel == render_scale_item_index() {
+        Some(format!("< {} >", render_scale_label(config::get().render_scale)))
+    } else if sel == upscale_filter_item_index() {
+        Some(format!("< {} >", config::get().upscale_filter))
+    } else if sel == hdr_output_item_index() {
+        Some(format!("< {} >", config::get().hdr_output))
+    } else if sel == song_start_item_index() {
+        Some((if config::get().song_countdown { "< Countdown >" } else { "< Instant >" }).to_string())
+    } else if sel == led_marquee_item_index() {
+        Some(format!("< {} >", led_marquee_label(config::get().led_marquee)))
+    } else {
+        None
+    };
+    if let Some(value) = latency_value {
+        cursor_y += 12.0 * s;
+        ui_actors.push(act!(text:
+            align(0.0, 0.0):
+            xy(desc_x + desc_pad_x + 12.0 * s, cursor_y):
+            zoomtoheight(body_px):
+            diffuse(1.0, 1.0, 1.0, 1.0):
+            font("miso"): settext(value):
+            horizalign(left)
+        ));
+        cursor_y += body_px + 8.0 * s;
+    }
+
+    if sel == song_titles_item_index() {
+        cursor_y += 12.0 * s;
+        let chosen = if config::get().prefer_translit { "< Transliterated >" } else { "< Native >" };
+        ui_actors.push(act!(text:
+            align(0.0, 0.0):
+            xy(desc_x + desc_pad_x + 12.0 * s, cursor_y):
+            zoomtoheight(body_px):
+            diffuse(1.0, 1.0, 1.0, 1.0):
+            font("miso"): settext(chosen):
+            horizalign(left)
+        ));
+    }
+
+    /* ------------------------ DISPLAY CHANGE PROMPT ------------------------ */
+    if let Some(confirm) = state.display_confirm {
+        let seconds_left = confirm.deadline.saturating_duration_since(Instant::now()).as_secs_f32().ceil();
+        ui_actors.push(act!(quad:
+            align(0.5, 0.5):
+            xy(screen_center_x(), screen_center_y()):
+            zoomto(screen_width(), screen_height()):
+            diffuse(0.0, 0.0, 0.0, 0.75):
+            z(layer::OVERLAY)
+        ));
+        ui_actors.push(act!(text:
+            font("miso"): settext("Keep these display settings?"):
+            align(0.5, 0.5): xy(screen_center_x(), screen_center_y() - 16.0):
+            horizalign(center): diffuse(1.0, 1.0, 1.0, 1.0):
+            z(layer::OVERLAY)
+        ));
+        ui_actors.push(act!(text:
+            font("miso"):
+            settext(format!("Press Enter to keep them, Escape to undo. Reverting in {} seconds.", seconds_left as u32)):
+            align(0.5, 0.5): xy(screen_center_x(), screen_center_y() + 16.0):
+            zoom(widescale(0.6, 0.75)): horizalign(center): diffuse(0.8, 0.8, 0.8, 1.0):
+            z(layer::OVERLAY)
+        ));
+    }
+
+    /* ------------------------- RESTORE BACKUP PROMPT ------------------------- */
+    if state.restore_confirm {
+        let label = state.backups.get(state.backup_index).map(|p| backup::backup_label(p)).unwrap_or_default();
+        ui_actors.push(act!(quad:
+            align(0.5, 0.5):
+            xy(screen_center_x(), screen_center_y()):
+            zoomto(screen_width(), screen_height()):
+            diffuse(0.0, 0.0, 0.0, 0.75):
+            z(layer::OVERLAY)
+        ));
+        ui_actors.push(act!(text:
+            font("miso"): settext(format!("Restore profiles from {}?", label)):
+            align(0.5, 0.5): xy(screen_center_x(), screen_center_y() - 16.0):
+            horizalign(center): diffuse(1.0, 1.0, 1.0, 1.0):
+            z(layer::OVERLAY)
+        ));
+        ui_actors.push(act!(text:
+            font("miso"):
+            settext("Press Enter to restore, Escape to cancel. The current profiles are kept as a backup."):
+            align(0.5, 0.5): xy(screen_center_x(), screen_center_y() + 16.0):
+            zoom(widescale(0.6, 0.75)): horizalign(center): diffuse(0.8, 0.8, 0.8, 1.0):
+            z(layer::OVERLAY)
+        ));
+    }
+
     for actor in &mut ui_actors {
         apply_alpha_to_actor(actor, alpha_multiplier);
     }
@@ -1,9 +1,12 @@
 use crate::act;
 use crate::core::audio;
 use crate::core::space::*;
+use crate::game::mods::{self, ChartMods, Turn};
+use crate::game::profile::{self, JudgmentPosition};
 use crate::game::song::SongData;
 use crate::screens::{Screen, ScreenAction};
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use crate::assets::AssetManager;
 use crate::ui::color;
 use crate::ui::components::heart_bg;
@@ -53,13 +56,63 @@ pub struct State {
     pub prev_selected_row: usize,
     pub active_color_index: i32,
     pub speed_mod: SpeedMod,
+    /// Rate, mini and turn as shown in the rows.
+    pub mods: ChartMods,
+    /// Whether `mods` are saved as the chart's sticky preset on the way out.
+    pub sticky: bool,
+    pub judgment_position: JudgmentPosition,
+    pub column_spacing: f32,
+    pub rate_scaled_effects: bool,
+    pub fa_plus: bool,
+    // The session's mods, kept while a sticky preset is on screen instead.
+    session_mods: ChartMods,
     bg: heart_bg::State,
     nav_key_held_direction: Option<NavDirection>,
     nav_key_held_since: Option<Instant>,
     nav_key_last_scrolled_at: Option<Instant>,
 }
 
-fn build_rows(song: &SongData, speed_mod: &SpeedMod, selected_difficulty_index: usize) -> Vec<Row> {
+fn rate_choices() -> Vec<String> {
+    let steps = ((mods::MAX_RATE - mods::MIN_RATE) / mods::RATE_STEP).round() as usize;
+    (0..=steps)
+        .map(|i| format!("{:.2}x", mods::MIN_RATE + i as f32 * mods::RATE_STEP))
+        .collect()
+}
+
+fn mini_choices() -> Vec<String> {
+    let steps = (1.0 / mods::MINI_STEP).round() as usize;
+    (0..=steps)
+        .map(|i| format!("{}%", (i as f32 * mods::MINI_STEP * 100.0).round() as i32))
+        .collect()
+}
+
+fn column_spacing_choices() -> Vec<String> {
+    let steps = ((profile::MAX_COLUMN_SPACING - profile::MIN_COLUMN_SPACING) / profile::COLUMN_SPACING_STEP).round() as usize;
+    (0..=steps)
+        .map(|i| profile::column_spacing_text(profile::MIN_COLUMN_SPACING + i as f32 * profile::COLUMN_SPACING_STEP))
+        .collect()
+}
+
+fn column_spacing_index(spacing: f32) -> usize {
+    ((spacing - profile::MIN_COLUMN_SPACING) / profile::COLUMN_SPACING_STEP).round().max(0.0) as usize
+}
+
+fn rate_index(rate: f32) -> usize {
+    ((rate - mods::MIN_RATE) / mods::RATE_STEP).round().max(0.0) as usize
+}
+
+fn mini_index(mini: f32) -> usize {
+    (mini / mods::MINI_STEP).round().max(0.0) as usize
+}
+
+fn build_rows(
+    song: &SongData,
+    speed_mod: &SpeedMod,
+    selected_difficulty_index: usize,
+    chart_mods: &ChartMods,
+    sticky: bool,
+    profile: &profile::Profile,
+) -> Vec<Row> {
     let speed_mod_value_str = match speed_mod.mod_type.as_str() {
         "X" => format!("{:.2}x", speed_mod.value),
         "C" => format!("C{}", speed_mod.value as i32),
@@ -67,21 +120,17 @@ fn build_rows(song: &SongData, speed_mod: &SpeedMod, selected_difficulty_index:
         _ => "".to_string(),
     };
 
-    // Build Stepchart choices from the song's dance-single charts, ordered Beginner..Challenge
+    // Build Stepchart choices from the song's charts for the game type, ordered Beginner..Challenge
     let mut stepchart_choices: Vec<String> = Vec::with_capacity(5);
     let mut stepchart_choice_indices: Vec<usize> = Vec::with_capacity(5);
     for (i, file_name) in crate::ui::color::FILE_DIFFICULTY_NAMES.iter().enumerate() {
-        if let Some(chart) = song
-            .charts
-            .iter()
-            .find(|c| c.chart_type.eq_ignore_ascii_case("dance-single") && c.difficulty.eq_ignore_ascii_case(file_name))
-        {
+        if let Some(chart) = song.chart_for(crate::config::get().game_type, file_name) {
             let display_name = crate::ui::color::DISPLAY_DIFFICULTY_NAMES[i];
             stepchart_choices.push(format!("{} {}", display_name, chart.meter));
             stepchart_choice_indices.push(i);
         }
     }
-    // Fallback if none found (defensive; SelectMusic filters to dance-single songs)
+    // Fallback if none found (defensive; SelectMusic filters to songs with charts for the game type)
     if stepchart_choices.is_empty() {
         stepchart_choices.push("(Current)".to_string());
         stepchart_choice_indices.push(selected_difficulty_index.min(crate::ui::color::FILE_DIFFICULTY_NAMES.len() - 1));
@@ -117,13 +166,28 @@ fn build_rows(song: &SongData, speed_mod: &SpeedMod, selected_difficulty_index:
         },
         Row {
             name: "Mini".to_string(),
-            choices: vec![
-                "0%".to_string(),
-            ],
-            selected_choice_index: 0,
+            choices: mini_choices(),
+            selected_choice_index: mini_index(chart_mods.mini),
             help: vec!["Change the size of your arrows.".to_string()],
             choice_difficulty_indices: None,
         },
+        Row {
+            name: "Turn".to_string(),
+            choices: Turn::ALL.iter().map(|t| t.to_string()).collect(),
+            selected_choice_index: Turn::ALL.iter().position(|t| *t == chart_mods.turn).unwrap_or(0),
+            help: vec!["Rearrange the columns of the chart.".to_string()],
+            choice_difficulty_indices: None,
+        },
+        Row {
+            name: "Column Spacing".to_string(),
+            choices: column_spacing_choices(),
+            selected_choice_index: column_spacing_index(profile.column_spacing),
+            help: vec![
+                "Spread the columns further apart or closer together.".to_string(),
+                "Unlike Mini, the arrows keep their size.".to_string(),
+            ],
+            choice_difficulty_indices: None,
+        },
         Row {
             name: "Perspective".to_string(),
             choices: vec![
@@ -159,6 +223,26 @@ fn build_rows(song: &SongData, speed_mod: &SpeedMod, selected_difficulty_index:
             ],
             choice_difficulty_indices: None,
         },
+        Row {
+            name: "Judgment Position".to_string(),
+            choices: JudgmentPosition::ALL.iter().map(|p| p.to_string()).collect(),
+            selected_choice_index: JudgmentPosition::ALL.iter().position(|p| *p == profile.judgment_position).unwrap_or(0),
+            help: vec![
+                "Where the judgment and combo appear: over the middle of the".to_string(),
+                "notefield, beside it, or just below the receptors.".to_string(),
+            ],
+            choice_difficulty_indices: None,
+        },
+        Row {
+            name: "FA+ Window".to_string(),
+            choices: vec!["Off".to_string(), "On".to_string()],
+            selected_choice_index: usize::from(profile.fa_plus),
+            help: vec![
+                "Split Fantastics into white (within 15ms) and blue, with their".to_string(),
+                "own explosion and counter row. Scoring doesn't change.".to_string(),
+            ],
+            choice_difficulty_indices: None,
+        },
         Row {
             name: "Visual Delay".to_string(),
             choices: vec!["0ms".to_string()],
@@ -171,11 +255,31 @@ fn build_rows(song: &SongData, speed_mod: &SpeedMod, selected_difficulty_index:
         },
         Row {
             name: "Music Rate".to_string(),
-            choices: vec!["1.00x".to_string()],
-            selected_choice_index: 0,
+            choices: rate_choices(),
+            selected_choice_index: rate_index(chart_mods.rate),
             help: vec!["Change the native speed of the music itself.".to_string()],
             choice_difficulty_indices: None,
         },
+        Row {
+            name: "Effect Speed".to_string(),
+            choices: vec!["Music Rate".to_string(), "Real Time".to_string()],
+            selected_choice_index: usize::from(!profile.rate_scaled_effects),
+            help: vec![
+                "Whether combo milestones and explosions speed up with the".to_string(),
+                "music rate, in step with the beat, or always play in real time.".to_string(),
+            ],
+            choice_difficulty_indices: None,
+        },
+        Row {
+            name: "Sticky Mods".to_string(),
+            choices: vec!["Off".to_string(), "This Chart".to_string()],
+            selected_choice_index: usize::from(sticky),
+            help: vec![
+                "Remember this chart's rate, mini and turn, and apply them every time it's played.".to_string(),
+                "Press Backspace on this screen to reset them.".to_string(),
+            ],
+            choice_difficulty_indices: None,
+        },
         Row {
             name: "Stepchart".to_string(),
             choices: stepchart_choices,
@@ -210,7 +314,11 @@ pub fn init(song: Arc<SongData>, chart_difficulty_index: usize, active_color_ind
         },
     };
 
-    let rows = build_rows(&song, &speed_mod, chart_difficulty_index);
+    let session_mods = profile.mods;
+    let preset = chart_hash(&song, chart_difficulty_index).and_then(|hash| profile::sticky_mods(&hash));
+    let chart_mods = preset.unwrap_or(session_mods);
+    let sticky = preset.is_some();
+    let rows = build_rows(&song, &speed_mod, chart_difficulty_index, &chart_mods, sticky, &profile);
 
     State {
         song,
@@ -220,6 +328,13 @@ pub fn init(song: Arc<SongData>, chart_difficulty_index: usize, active_color_ind
         prev_selected_row: 0,
         active_color_index,
         speed_mod,
+        mods: chart_mods,
+        sticky,
+        judgment_position: profile.judgment_position,
+        column_spacing: profile.column_spacing,
+        rate_scaled_effects: profile.rate_scaled_effects,
+        fa_plus: profile.fa_plus,
+        session_mods,
         bg: heart_bg::State::new(),
         nav_key_held_direction: None,
         nav_key_held_since: None,
@@ -227,12 +342,68 @@ pub fn init(song: Arc<SongData>, chart_difficulty_index: usize, active_color_ind
     }
 }
 
+// Hash of the game type's chart at the difficulty, which sticky presets are keyed by.
+pub(crate) fn chart_hash(song: &SongData, difficulty_index: usize) -> Option<String> {
+    let difficulty_name = crate::ui::color::FILE_DIFFICULTY_NAMES.get(difficulty_index)?;
+    song.chart_for(crate::config::get().game_type, difficulty_name).map(|c| c.short_hash.clone())
+}
+
+// Points the rate, mini, turn and sticky rows at the current values.
+fn sync_mod_rows(state: &mut State) {
+    let mods = state.mods;
+    for row in &mut state.rows {
+        let index = match row.name.as_str() {
+            "Mini" => mini_index(mods.mini),
+            "Turn" => Turn::ALL.iter().position(|t| *t == mods.turn).unwrap_or(0),
+            "Music Rate" => rate_index(mods.rate),
+            "Sticky Mods" => usize::from(state.sticky),
+            _ => continue,
+        };
+        row.selected_choice_index = index.min(row.choices.len().saturating_sub(1));
+    }
+}
+
+/// Stores the rate, mini and turn for the chart on screen: as its sticky preset
+/// when that's on, as the session's mods (dropping any preset) when it's off.
+pub fn save_mods(state: &State) {
+    let Some(hash) = chart_hash(&state.song, state.chart_difficulty_index) else { return };
+    if state.sticky {
+        profile::set_sticky_mods(&hash, Some(state.mods));
+        profile::update_mods(state.session_mods);
+    } else {
+        profile::set_sticky_mods(&hash, None);
+        profile::update_mods(state.mods);
+    }
+}
+
+// Another chart was picked: keep what was set for the last one, then bring up
+// the new chart's preset if it has one.
+fn switch_chart(state: &mut State, difficulty_index: usize) {
+    save_mods(state);
+    if !state.sticky {
+        state.session_mods = state.mods;
+    }
+    state.chart_difficulty_index = difficulty_index;
+    let preset = chart_hash(&state.song, difficulty_index).and_then(|hash| profile::sticky_mods(&hash));
+    state.sticky = preset.is_some();
+    state.mods = preset.unwrap_or(state.session_mods);
+    sync_mod_rows(state);
+}
+
+// Backspace: back to plain rate, mini and turn, and no preset for this chart.
+fn reset_mods(state: &mut State) {
+    state.mods = ChartMods::default();
+    state.sticky = false;
+    sync_mod_rows(state);
+    audio::play_sfx("assets/sounds/change_value.ogg");
+}
+
 pub fn in_transition() -> (Vec<Actor>, f32) {
     let actor = act!(quad:
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 1.0):
-        z(1100):
+        z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -244,7 +415,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -313,11 +484,29 @@ fn change_choice(state: &mut State, delta: isize) {
                 }
             } else if row.name == "Stepchart" {
                 // Update the state's difficulty index to match the newly selected choice
-                if let Some(diff_indices) = &row.choice_difficulty_indices {
-                    if let Some(&difficulty_idx) = diff_indices.get(row.selected_choice_index) {
-                        state.chart_difficulty_index = difficulty_idx;
-                    }
+                let difficulty_idx = row
+                    .choice_difficulty_indices
+                    .as_ref()
+                    .and_then(|diff_indices| diff_indices.get(row.selected_choice_index).copied());
+                if let Some(difficulty_idx) = difficulty_idx {
+                    switch_chart(state, difficulty_idx);
                 }
+            } else if row.name == "Mini" {
+                state.mods.mini = row.selected_choice_index as f32 * mods::MINI_STEP;
+            } else if row.name == "Turn" {
+                state.mods.turn = Turn::ALL[row.selected_choice_index];
+            } else if row.name == "Music Rate" {
+                state.mods.rate = mods::MIN_RATE + row.selected_choice_index as f32 * mods::RATE_STEP;
+            } else if row.name == "Column Spacing" {
+                state.column_spacing = profile::MIN_COLUMN_SPACING + row.selected_choice_index as f32 * profile::COLUMN_SPACING_STEP;
+            } else if row.name == "Judgment Position" {
+                state.judgment_position = JudgmentPosition::ALL[row.selected_choice_index];
+            } else if row.name == "FA+ Window" {
+                state.fa_plus = row.selected_choice_index == 1;
+            } else if row.name == "Effect Speed" {
+                state.rate_scaled_effects = row.selected_choice_index == 0;
+            } else if row.name == "Sticky Mods" {
+                state.sticky = row.selected_choice_index == 1;
             }
             audio::play_sfx("assets/sounds/change_value.ogg");
         }
@@ -339,6 +528,7 @@ pub fn handle_key_press(state: &mut State, e: &KeyEvent) -> ScreenAction {
 
         match key_code {
             KeyCode::Escape => return ScreenAction::Navigate(Screen::SelectMusic),
+            KeyCode::Backspace => reset_mods(state),
             KeyCode::ArrowUp | KeyCode::KeyW => {
                 if num_rows > 0 {
                     state.selected_row = (state.selected_row + num_rows - 1) % num_rows;
@@ -454,6 +644,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         center_text: None,
         right_text: None,
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets { session_time: true, clock: true, ..screen_bar::ScreenBarWidgets::NONE },
     }));
 
     // Speed Mod Helper Display (from overlay.lua)
@@ -471,6 +662,22 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         z(121)
     ));
 
+    // Sticky preset indicator, listing what the chart brings with it.
+    if state.sticky {
+        let sticky_text = format!(
+            "STICKY: {} {} {}",
+            state.mods.rate_text(),
+            state.mods.mini_text(),
+            state.mods.turn
+        );
+        actors.push(act!(text: font("miso"): settext(sticky_text):
+            align(1.0, 0.5): xy(screen_width() - widescale(13.0, 30.666), speed_mod_y): zoom(0.8):
+            diffuse(speed_color[0], speed_color[1], speed_color[2], 1.0):
+            horizalign(right):
+            z(121)
+        ));
+    }
+
     /* ---------- SHARED GEOMETRY (rows aligned to help box) ---------- */
     // Help Text Box (from underlay.lua) — define this first so rows can match its width/left.
     let help_box_h = 40.0;
@@ -574,6 +781,8 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         // Some rows should display all choices inline
         let show_all_choices_inline = row.name == "Perspective"
             || row.name == "Background Filter"
+            || row.name == "Judgment Position"
+            || row.name == "Effect Speed"
             || row.name == "Stepchart";
 
         if show_all_choices_inline {
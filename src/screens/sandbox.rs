@@ -1,7 +1,9 @@
 use crate::act;
+use crate::core::gfx::PostEffect;
 use crate::core::space::*;
 use crate::screens::{Screen, ScreenAction};
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use winit::event::{ElementState, KeyEvent};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
@@ -9,23 +11,42 @@ use winit::keyboard::{KeyCode, PhysicalKey};
 const TRANSITION_IN_DURATION: f32 = 0.4;
 const TRANSITION_OUT_DURATION: f32 = 0.4;
 
+// Post effect presets cycled with Space; the first is none.
+const POST_PRESETS: [&[PostEffect]; 4] = [
+    &[],
+    &[PostEffect::Vignette { strength: 0.8 }],
+    &[PostEffect::Crt { scanlines: 0.35, curvature: 0.08 }, PostEffect::Vignette { strength: 0.5 }],
+    &[PostEffect::Gamma { gamma: 1.6 }],
+];
+
 pub struct State {
     pub elapsed: f32,
+    pub post_preset: usize,
 }
 
 pub fn init() -> State {
-    State { elapsed: 0.0 }
+    State { elapsed: 0.0, post_preset: 0 }
 }
 
-pub fn handle_key_press(_state: &mut State, event: &KeyEvent) -> ScreenAction {
+pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
     if event.state == ElementState::Pressed {
-        if let PhysicalKey::Code(KeyCode::Escape) | PhysicalKey::Code(KeyCode::F4) = event.physical_key {
-            return ScreenAction::Navigate(Screen::Menu);
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::Escape) | PhysicalKey::Code(KeyCode::F4) => {
+                return ScreenAction::Navigate(Screen::Menu);
+            }
+            PhysicalKey::Code(KeyCode::Space) if !event.repeat => {
+                state.post_preset = (state.post_preset + 1) % POST_PRESETS.len();
+            }
+            _ => {}
         }
     }
     ScreenAction::None
 }
 
+pub fn post_effects(state: &State) -> Vec<PostEffect> {
+    POST_PRESETS[state.post_preset].to_vec()
+}
+
 pub fn update(state: &mut State, dt: f32) {
     state.elapsed += dt;
 }
@@ -34,7 +55,7 @@ pub fn in_transition() -> (Vec<Actor>, f32) {
     let actor = act!(quad:
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
-        diffuse(0.0, 0.0, 0.0, 1.0): z(1100):
+        diffuse(0.0, 0.0, 0.0, 1.0): z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -46,7 +67,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -63,6 +84,10 @@ pub fn get_actors(_state: &State) -> Vec<Actor> {
         align(0.5, 0.0): xy(screen_center_x(), 60.0):
         zoomtoheight(15.0): font("miso"): settext("Press ESC or F4 to return to Menu"): horizalign(center)
     ));
+    actors.push(act!(text:
+        align(0.5, 0.0): xy(screen_center_x(), 80.0):
+        zoomtoheight(15.0): font("miso"): settext("Press SPACE to cycle post effects"): horizalign(center)
+    ));
     //actors.push(act!(text:
     //    align(1.0, 1.0): xy(screen_width() - 10.0, screen_height() - 10.0):
     //    zoomtoheight(15.0): font("miso"): settext(format!("Elapsed: {:.2}", state.elapsed)): horizalign(right)
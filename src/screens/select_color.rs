@@ -2,6 +2,7 @@ use crate::act;
 use crate::core::space::*;
 use crate::screens::{Screen, ScreenAction};
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use crate::ui::color;
 use crate::ui::components::{heart_bg, screen_bar};
 use crate::ui::components::screen_bar::{ScreenBarPosition, ScreenBarTitlePlacement};
@@ -122,6 +123,9 @@ fn apply_alpha_to_actor(actor: &mut Actor, alpha: f32) {
     match actor {
         Actor::Sprite { tint, .. } => tint[3] *= alpha,
         Actor::Text { color, .. } => color[3] *= alpha,
+        Actor::Polyline { colors, .. } | Actor::Mesh { colors, .. } => {
+            colors.iter_mut().for_each(|c| c[3] *= alpha)
+        }
         Actor::Frame { background, children, .. } => {
             if let Some(actors::Background::Color(c)) = background {
                 c[3] *= alpha;
@@ -138,7 +142,7 @@ pub fn in_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 1.0):
-        z(1100):
+        z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -150,7 +154,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -202,6 +206,7 @@ pub fn get_actors(state: &State, alpha_multiplier: f32) -> Vec<Actor> {
         center_text: None,   // later: Some("01:23")
         right_text: None,    // later: Some("P1 • READY")
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets { clock: true, ..screen_bar::ScreenBarWidgets::NONE },
         fg_color: FG,
     }));
     ui_actors.push(screen_bar::build(screen_bar::ScreenBarParams {
@@ -213,6 +218,7 @@ pub fn get_actors(state: &State, alpha_multiplier: f32) -> Vec<Actor> {
         center_text: None,
         right_text: Some("NOT PRESENT"),
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets::NONE,
         fg_color: FG,
     }));
 
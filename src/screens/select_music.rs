@@ -1,11 +1,13 @@
 // src/screens/select_music.rs
 use crate::act;
 use crate::core::audio;
+use crate::core::video;
 use crate::core::space::*;
 use crate::screens::{Screen, ScreenAction};
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use crate::ui::color;
-use crate::ui::components::{heart_bg, pad_display, music_wheel};
+use crate::ui::components::{banner, density_graph, filter_overlay, heart_bg, pad_display, music_wheel, wheel_index};
 use crate::ui::components::screen_bar::{
     self, AvatarParams, ScreenBarParams, ScreenBarPosition, ScreenBarTitlePlacement,
 };
@@ -13,13 +15,13 @@ use crate::ui::actors::SizeSpec;
 use crate::core::space::is_wide;
 use crate::core::gamepad::{PadDir, PadButton};
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, LazyLock};
-use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use winit::event::{ElementState, KeyEvent};
 use winit::keyboard::{KeyCode, PhysicalKey};
 use crate::ui::font;
-use log::info;
+use log::{info, warn};
 use std::fs;
 
 // --- engine imports ---
@@ -29,16 +31,15 @@ use crate::assets::AssetManager;
 use crate::game::profile;
 use crate::game::scores;
 use crate::game::chart::ChartData;
+use crate::game::mods::{self, ChartMods};
+use crate::game::game_type::GameType;
+use crate::screens::player_options;
 
 
 /* ---------------------------- transitions ---------------------------- */
 const TRANSITION_IN_DURATION: f32 = 0.5;
 const TRANSITION_OUT_DURATION: f32 = 0.3;
 
-// --- THEME LAYOUT CONSTANTS (unscaled, native dimensions) ---
-const BANNER_NATIVE_WIDTH: f32 = 418.0;
-const BANNER_NATIVE_HEIGHT: f32 = 164.0;
-
 // --- Other UI Constants ---
 static UI_BOX_BG_COLOR: LazyLock<[f32; 4]> = LazyLock::new(|| color::rgba_hex("#1E282F"));
 const SELECTION_ANIMATION_CYCLE_DURATION: f32 = 1.0;
@@ -47,13 +48,171 @@ const NAV_INITIAL_HOLD_DELAY: Duration = Duration::from_millis(200);
 const NAV_REPEAT_SCROLL_INTERVAL: Duration = Duration::from_millis(40);
 const PREVIEW_DELAY_SECONDS: f32 = 0.25;
 const PREVIEW_FADE_OUT_SECONDS: f64 = 1.5;
+// Entries ahead of the wheel, in the direction it's turning, whose banners are
+// decoded early so fast scrolling lands on finished ones.
+const BANNER_PREFETCH_AHEAD: usize = 6;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum NavDirection { Left, Right }
 
+/// How the wheel is ordered. The played sorts list every played song under a
+/// single header, using the per-chart play stats saved in the profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortMode { Group, MostPlayed, RecentlyPlayed }
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Group => SortMode::MostPlayed,
+            SortMode::MostPlayed => SortMode::RecentlyPlayed,
+            SortMode::RecentlyPlayed => SortMode::Group,
+        }
+    }
+
+    fn header_name(self) -> &'static str {
+        match self {
+            SortMode::Group => "Group",
+            SortMode::MostPlayed => "Most Played",
+            SortMode::RecentlyPlayed => "Recently Played",
+        }
+    }
+}
+
+// Meters at or above this and BPMs at or above `FILTER_BPM_CAP` mean "no upper limit".
+const FILTER_METER_CAP: u32 = 20;
+const FILTER_BPM_CAP: u32 = 400;
+const FILTER_BPM_STEP: u32 = 10;
+const FILTER_ROWS: usize = 5;
+
+/// The last filter applied, kept for the rest of the session so it survives
+/// leaving the screen and the wheel being rebuilt.
+static SESSION_FILTER: Mutex<Option<SongFilter>> = Mutex::new(None);
+
+/// Limits on which songs the wheel shows, set from the F6 filter panel. A song
+/// passes if it has a chart of `steps_type` (the game type when unset) rated
+/// inside the meter range and its top BPM falls inside the BPM range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SongFilter {
+    steps_type: Option<GameType>,
+    meter_min: u32,
+    meter_max: u32,
+    bpm_min: u32,
+    bpm_max: u32,
+}
+
+impl SongFilter {
+    const NONE: SongFilter = SongFilter {
+        steps_type: None,
+        meter_min: 1,
+        meter_max: FILTER_METER_CAP,
+        bpm_min: 0,
+        bpm_max: FILTER_BPM_CAP,
+    };
+
+    fn is_active(&self) -> bool {
+        *self != Self::NONE
+    }
+
+    fn matches(&self, song: &SongData) -> bool {
+        let bpm = song.max_bpm.round().max(0.0) as u32;
+        if bpm < self.bpm_min || (self.bpm_max < FILTER_BPM_CAP && bpm > self.bpm_max) {
+            return false;
+        }
+        let chart_type = self.steps_type.unwrap_or(crate::config::get().game_type).chart_type();
+        song.charts.iter().any(|c| {
+            c.chart_type.eq_ignore_ascii_case(chart_type)
+                && !c.notes.is_empty()
+                && c.meter >= self.meter_min
+                && (self.meter_max >= FILTER_METER_CAP || c.meter <= self.meter_max)
+        })
+    }
+
+    /// Moves one setting a step, keeping each minimum at or below its maximum.
+    fn adjust(&mut self, row: usize, delta: i32) {
+        let step = |v: u32, by: u32, lo: u32, hi: u32| -> u32 {
+            if delta < 0 { v.saturating_sub(by).max(lo) } else { (v + by).min(hi) }
+        };
+        match row {
+            0 => {
+                let order: Vec<Option<GameType>> = std::iter::once(None).chain(GameType::ALL.into_iter().map(Some)).collect();
+                let i = order.iter().position(|t| *t == self.steps_type).unwrap_or(0) as i32;
+                self.steps_type = order[(i + delta).rem_euclid(order.len() as i32) as usize];
+            }
+            1 => {
+                self.meter_min = step(self.meter_min, 1, 1, FILTER_METER_CAP);
+                self.meter_max = self.meter_max.max(self.meter_min);
+            }
+            2 => {
+                self.meter_max = step(self.meter_max, 1, 1, FILTER_METER_CAP);
+                self.meter_min = self.meter_min.min(self.meter_max);
+            }
+            3 => {
+                self.bpm_min = step(self.bpm_min, FILTER_BPM_STEP, 0, FILTER_BPM_CAP);
+                self.bpm_max = self.bpm_max.max(self.bpm_min);
+            }
+            4 => {
+                self.bpm_max = step(self.bpm_max, FILTER_BPM_STEP, 0, FILTER_BPM_CAP);
+                self.bpm_min = self.bpm_min.min(self.bpm_max);
+            }
+            _ => {}
+        }
+    }
+
+    fn steps_type_label(&self) -> String {
+        self.steps_type.map_or_else(|| "Any".to_string(), |t| t.to_string())
+    }
+
+    fn meter_max_label(&self) -> String {
+        if self.meter_max >= FILTER_METER_CAP { format!("{}+", FILTER_METER_CAP) } else { self.meter_max.to_string() }
+    }
+
+    fn bpm_max_label(&self) -> String {
+        if self.bpm_max >= FILTER_BPM_CAP { format!("{}+", FILTER_BPM_CAP) } else { self.bpm_max.to_string() }
+    }
+
+    fn rows(&self) -> [(&'static str, String); FILTER_ROWS] {
+        [
+            ("Steps Type", self.steps_type_label()),
+            ("Min Meter", self.meter_min.to_string()),
+            ("Max Meter", self.meter_max_label()),
+            ("Min BPM", self.bpm_min.to_string()),
+            ("Max BPM", self.bpm_max_label()),
+        ]
+    }
+
+    /// A one-line description for the wheel while the filter is on.
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(t) = self.steps_type {
+            parts.push(t.to_string());
+        }
+        if self.meter_min > 1 || self.meter_max < FILTER_METER_CAP {
+            parts.push(format!("Meter {}-{}", self.meter_min, self.meter_max_label()));
+        }
+        if self.bpm_min > 0 || self.bpm_max < FILTER_BPM_CAP {
+            parts.push(format!("{}-{} BPM", self.bpm_min, self.bpm_max_label()));
+        }
+        parts.join(", ")
+    }
+}
+
+/// The filter panel while it's open: the settings being edited, which row has
+/// focus, and how many songs those settings would leave.
+struct FilterEdit {
+    draft: SongFilter,
+    row: usize,
+    matching_songs: usize,
+}
+
 #[derive(Clone, Debug)]
 pub enum MusicWheelEntry {
-    PackHeader { name: String, original_index: usize, banner_path: Option<PathBuf> },
+    PackHeader {
+        name: String,
+        display_name: String,
+        original_index: usize,
+        banner_path: Option<PathBuf>,
+        color: Option<[f32; 4]>,
+    },
     Song(Arc<SongData>),
 }
 
@@ -65,32 +224,84 @@ pub struct State {
     pub active_color_index: i32,
     pub selection_animation_timer: f32,
     pub current_banner_key: String,
-    pub current_graph_key: String,
+    /// Set once the selected song's preview video has a frame up; drawn instead
+    /// of the banner.
+    pub preview_video_key: Option<String>,
+    /// Seconds the preview video has been playing.
+    pub preview_video_time: f32,
+    /// Texture the density graph was drawn into, once it's ready; see `density_graph_key`.
+    pub density_graph_texture: Option<String>,
     pub session_elapsed: f32,
+    pub sort_mode: SortMode,
+    // Every song of the game type in group order; `group_entries` is this
+    // with the filter applied.
+    unfiltered_entries: Vec<MusicWheelEntry>,
+    group_entries: Vec<MusicWheelEntry>,
+    filter: SongFilter,
+    filter_edit: Option<FilterEdit>,
     all_entries: Vec<MusicWheelEntry>,
     expanded_pack_name: Option<String>,
     bg: heart_bg::State,
     last_requested_banner_path: Option<PathBuf>,
-    last_requested_chart_hash: Option<String>,
     active_chord_keys: HashSet<KeyCode>,
     last_difficulty_nav_key: Option<KeyCode>,
     last_difficulty_nav_time: Option<Instant>,
     nav_key_held_direction: Option<NavDirection>,
     nav_key_held_since: Option<Instant>,
     nav_key_last_scrolled_at: Option<Instant>,
+    index_modifier_held: bool,
     currently_playing_preview_path: Option<PathBuf>,
+    playing_preview_video: Option<PathBuf>,
+    // Rate the preview was started at; a change restarts it.
+    preview_rate: f32,
     prev_selected_index: usize,
     time_since_selection_change: f32,
     pub displayed_chart_data: Option<Arc<ChartData>>,
+    banner_prefetch: Option<Vec<PathBuf>>,
+}
+
+fn density_graph_panel_size() -> (f32, f32) {
+    (if is_wide() { 286.0 } else { 276.0 }, 64.0)
+}
+
+fn build_density_graph(chart: &ChartData, width: f32, height: f32) -> Option<Actor> {
+    density_graph::build(
+        &chart.measure_nps_vec,
+        chart.max_nps,
+        width,
+        height,
+        color::rgba_hex("#00B8CC"),
+        color::rgba_hex("#8200A1"),
+        0,
+    )
+}
+
+/// The pixel size of the density graph's texture: the panel at the window's density.
+pub fn density_graph_px_size() -> (u32, u32) {
+    let (w, h) = density_graph_panel_size();
+    let ppu = pixels_per_unit();
+    ((w * ppu).ceil().max(1.0) as u32, (h * ppu).ceil().max(1.0) as u32)
+}
+
+/// Texture key the displayed chart's density graph is drawn into, one per
+/// chart and pixel size; None while no chart is displayed.
+pub fn density_graph_key(state: &State) -> Option<String> {
+    let chart = state.displayed_chart_data.as_ref()?;
+    let (w, h) = density_graph_px_size();
+    Some(format!("__density_graph_{}_{}x{}", chart.short_hash, w, h))
+}
+
+/// The displayed chart's density graph over the whole screen space, which
+/// `Backend::draw_to_target` maps onto the texture.
+pub fn density_graph_screen(state: &State) -> Option<Actor> {
+    build_density_graph(state.displayed_chart_data.as_ref()?, screen_width(), screen_height())
 }
 
 /// Helper function to check if a specific difficulty index has a playable chart
 pub(crate) fn is_difficulty_playable(song: &Arc<SongData>, difficulty_index: usize) -> bool {
     if difficulty_index >= color::FILE_DIFFICULTY_NAMES.len() { return false; }
     let target_difficulty_name = color::FILE_DIFFICULTY_NAMES[difficulty_index];
-    song.charts.iter().any(|c| {
-        c.difficulty.eq_ignore_ascii_case(target_difficulty_name) && !c.notes.is_empty()
-    })
+    song.chart_for(crate::config::get().game_type, target_difficulty_name).is_some_and(|c| !c.notes.is_empty())
 }
 
 fn find_pack_banner(pack: &SongPack) -> Option<PathBuf> {
@@ -180,41 +391,344 @@ fn rebuild_displayed_entries(state: &mut State) {
     state.entries = new_entries;
 }
 
+/// Starts the selected song, or explains why it can't be played.
+fn start_song(song: &SongData) -> ScreenAction {
+    if let Some(issue) = song.play_blocker() {
+        warn!("Refusing to start '{}': {}", song.title, issue.message());
+        audio::play_sfx("assets/sounds/boom.ogg");
+        return ScreenAction::ShowMessage(format!("Can't play \"{}\": {}.", song.title, issue.message()));
+    }
+    info!("Selected song: '{}'. It has {} charts.", song.title, song.charts.len());
+    ScreenAction::Navigate(song_start_screen())
+}
+
+/// The selected chart's hash, the mods it would play with (its sticky preset or
+/// the session's), and whether they are the preset.
+fn selected_chart_mods(state: &State) -> Option<(String, ChartMods, bool)> {
+    let Some(MusicWheelEntry::Song(song)) = state.entries.get(state.selected_index) else { return None };
+    let hash = player_options::chart_hash(song, state.selected_difficulty_index)?;
+    let preset = profile::sticky_mods(&hash);
+    Some((hash, preset.unwrap_or(profile::get().mods), preset.is_some()))
+}
+
+/// Music rate the selected chart would play at; tournament mode locks it to 1.0x.
+fn selected_rate(state: &State) -> f32 {
+    if crate::config::get().tournament_mode {
+        return 1.0;
+    }
+    selected_chart_mods(state).map_or(1.0, |(_, mods, _)| mods.rate)
+}
+
+// -/= step the selected chart's rate by `RATE_STEP`, as Simply Love's rate code
+// does; a sticky preset keeps its own rate, other charts share the session's.
+fn change_rate(state: &mut State, steps: f32) {
+    if crate::config::get().tournament_mode {
+        return;
+    }
+    let Some((hash, current, sticky)) = selected_chart_mods(state) else { return };
+    let changed = ChartMods { rate: current.rate + steps * mods::RATE_STEP, ..current }.clamped();
+    if changed == current {
+        return;
+    }
+    if sticky {
+        profile::set_sticky_mods(&hash, Some(changed));
+    } else {
+        profile::update_mods(changed);
+    }
+    info!("Music rate set to {}.", changed.rate_text());
+    audio::play_sfx("assets/sounds/change_value.ogg");
+}
+
+/// Starting a song normally opens PlayerOptions; tournament mode locks the
+/// modifiers, so it skips straight to gameplay.
+fn song_start_screen() -> Screen {
+    if crate::config::get().tournament_mode { Screen::Gameplay } else { Screen::PlayerOptions }
+}
+
+/// Play stats summed over a song's charts for the game type: total plays and most recent play.
+pub(crate) fn song_play_stats(song: &SongData) -> profile::ChartPlayStats {
+    let chart_type = crate::config::get().game_type.chart_type();
+    song.charts
+        .iter()
+        .filter(|c| c.chart_type.eq_ignore_ascii_case(chart_type))
+        .map(|c| profile::get_play_stats(&c.short_hash))
+        .fold(profile::ChartPlayStats::default(), |acc, s| profile::ChartPlayStats {
+            play_count: acc.play_count + s.play_count,
+            last_played: acc.last_played.max(s.last_played),
+        })
+}
+
+/// Rebuilds the wheel for `state.sort_mode` and resets the selection to its top.
+fn apply_sort(state: &mut State) {
+    match state.sort_mode {
+        SortMode::Group => {
+            state.all_entries = state.group_entries.clone();
+            state.expanded_pack_name = None;
+        }
+        SortMode::MostPlayed | SortMode::RecentlyPlayed => {
+            let mut songs: Vec<(Arc<SongData>, profile::ChartPlayStats)> = state.group_entries
+                .iter()
+                .filter_map(|e| match e {
+                    MusicWheelEntry::Song(song) => Some((song.clone(), song_play_stats(song))),
+                    MusicWheelEntry::PackHeader { .. } => None,
+                })
+                .filter(|(_, stats)| stats.play_count > 0)
+                .collect();
+            // Stable sort, so ties keep their group order.
+            if state.sort_mode == SortMode::MostPlayed {
+                songs.sort_by(|(_, a), (_, b)| {
+                    b.play_count.cmp(&a.play_count).then(b.last_played.cmp(&a.last_played))
+                });
+            } else {
+                songs.sort_by(|(_, a), (_, b)| {
+                    b.last_played.cmp(&a.last_played).then(b.play_count.cmp(&a.play_count))
+                });
+            }
+
+            let name = state.sort_mode.header_name().to_string();
+            state.all_entries = Vec::with_capacity(songs.len() + 1);
+            state.all_entries.push(MusicWheelEntry::PackHeader {
+                name: name.clone(),
+                display_name: name.clone(),
+                original_index: usize::MAX,
+                banner_path: None,
+                color: None,
+            });
+            state.all_entries.extend(songs.into_iter().map(|(song, _)| MusicWheelEntry::Song(song)));
+            state.expanded_pack_name = Some(name);
+        }
+    }
+    rebuild_displayed_entries(state);
+    // Land on the first song when the sort has one, otherwise on the top entry.
+    state.selected_index = if state.entries.len() > 1 && state.sort_mode != SortMode::Group { 1 } else { 0 };
+    state.selection_animation_timer = 0.0;
+    state.time_since_selection_change = 0.0;
+}
+
+/// `entries` without the songs `filter` rejects, and without packs left empty.
+fn filter_entries(entries: &[MusicWheelEntry], filter: &SongFilter) -> Vec<MusicWheelEntry> {
+    if !filter.is_active() { return entries.to_vec(); }
+    let mut filtered = Vec::new();
+    let mut pending_header = None;
+    for entry in entries {
+        match entry {
+            MusicWheelEntry::PackHeader { .. } => pending_header = Some(entry),
+            MusicWheelEntry::Song(song) => {
+                if !filter.matches(song) { continue; }
+                if let Some(header) = pending_header.take() {
+                    filtered.push(header.clone());
+                }
+                filtered.push(entry.clone());
+            }
+        }
+    }
+    filtered
+}
+
+fn count_matching_songs(entries: &[MusicWheelEntry], filter: &SongFilter) -> usize {
+    entries.iter().filter(|e| matches!(e, MusicWheelEntry::Song(song) if filter.matches(song))).count()
+}
+
+/// Rebuilds the wheel with `filter` and remembers it for the rest of the session.
+fn apply_filter(state: &mut State, filter: SongFilter) {
+    state.filter = filter;
+    *SESSION_FILTER.lock().unwrap() = filter.is_active().then_some(filter);
+    state.group_entries = filter_entries(&state.unfiltered_entries, &filter);
+    if filter.is_active() {
+        info!("Filtering the music wheel to {}: {} songs.", filter.summary(), count_matching_songs(&state.group_entries, &filter));
+    } else {
+        info!("Cleared the music wheel filter.");
+    }
+    apply_sort(state);
+    state.prev_selected_index = state.selected_index;
+}
+
+pub fn is_filter_open(state: &State) -> bool {
+    state.filter_edit.is_some()
+}
+
+fn open_filter(state: &mut State) {
+    let draft = state.filter;
+    state.filter_edit = Some(FilterEdit {
+        draft,
+        row: 0,
+        matching_songs: count_matching_songs(&state.unfiltered_entries, &draft),
+    });
+    audio::play_sfx("assets/sounds/expand.ogg");
+}
+
+/// The pad's X button, standing in for F6 and Backspace: opens the filter
+/// panel, or clears the filter while it's open.
+pub fn handle_pad_filter_button(state: &mut State) {
+    if state.filter_edit.is_some() {
+        handle_filter_key(state, KeyCode::Backspace);
+    } else {
+        state.nav_key_held_direction = None;
+        state.nav_key_held_since = None;
+        state.nav_key_last_scrolled_at = None;
+        open_filter(state);
+    }
+}
+
+/// Input while the filter panel is open. Everything it doesn't use is swallowed
+/// so the wheel stays put underneath.
+fn handle_filter_key(state: &mut State, key_code: KeyCode) {
+    let Some(edit) = state.filter_edit.as_mut() else { return };
+    match key_code {
+        KeyCode::ArrowUp => {
+            edit.row = (edit.row + FILTER_ROWS - 1) % FILTER_ROWS;
+            audio::play_sfx("assets/sounds/prev_row.ogg");
+        }
+        KeyCode::ArrowDown => {
+            edit.row = (edit.row + 1) % FILTER_ROWS;
+            audio::play_sfx("assets/sounds/next_row.ogg");
+        }
+        KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+            let delta = if key_code == KeyCode::ArrowLeft { -1 } else { 1 };
+            let before = edit.draft;
+            edit.draft.adjust(edit.row, delta);
+            if edit.draft != before {
+                edit.matching_songs = count_matching_songs(&state.unfiltered_entries, &edit.draft);
+                audio::play_sfx("assets/sounds/change_value.ogg");
+            }
+        }
+        KeyCode::Enter => {
+            // Applying a filter nothing passes would leave an empty wheel.
+            if edit.matching_songs == 0 {
+                audio::play_sfx("assets/sounds/boom.ogg");
+                return;
+            }
+            let draft = edit.draft;
+            state.filter_edit = None;
+            apply_filter(state, draft);
+            audio::play_sfx("assets/sounds/start.ogg");
+        }
+        KeyCode::Backspace | KeyCode::Delete => {
+            state.filter_edit = None;
+            if state.filter.is_active() {
+                apply_filter(state, SongFilter::NONE);
+            }
+            audio::play_sfx("assets/sounds/expand.ogg");
+        }
+        KeyCode::Escape | KeyCode::F6 => {
+            state.filter_edit = None;
+            audio::play_sfx("assets/sounds/expand.ogg");
+        }
+        _ => {}
+    }
+}
+
+/// Selects the song loaded from `path` (its simfile or song folder), switching to
+/// group sort and opening its pack, and clearing the filter if it hides the song.
+/// Returns false if the song isn't on the wheel.
+pub fn select_song_by_path(state: &mut State, path: &Path) -> bool {
+    let wanted_dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    let Some(wanted_name) = wanted_dir.file_name() else { return false };
+    let wanted_dir = wanted_dir.canonicalize().unwrap_or_else(|_| wanted_dir.to_path_buf());
+
+    let mut pack_name = None;
+    let mut found = None;
+    for entry in &state.unfiltered_entries {
+        match entry {
+            MusicWheelEntry::PackHeader { name, .. } => pack_name = Some(name.clone()),
+            MusicWheelEntry::Song(song) => {
+                let Some(dir) = song.simfile_path.parent() else { continue };
+                // Only canonicalize folders that could match; there can be thousands.
+                if dir.file_name() == Some(wanted_name) && dir.canonicalize().is_ok_and(|d| d == wanted_dir) {
+                    found = pack_name.clone().map(|pack| (pack, song.clone()));
+                    break;
+                }
+            }
+        }
+    }
+    let Some((pack, song)) = found else { return false };
+
+    if !state.filter.matches(&song) {
+        apply_filter(state, SongFilter::NONE);
+    }
+    if state.sort_mode != SortMode::Group {
+        state.sort_mode = SortMode::Group;
+        apply_sort(state);
+    }
+    state.expanded_pack_name = Some(pack);
+    rebuild_displayed_entries(state);
+    let Some(index) = state.entries.iter().position(|e| matches!(e, MusicWheelEntry::Song(s) if Arc::ptr_eq(s, &song))) else {
+        return false;
+    };
+    state.selected_index = index;
+    state.selection_animation_timer = 0.0;
+    state.time_since_selection_change = 0.0;
+    info!("Jumped the wheel to '{}'.", song.title);
+    true
+}
+
+/// Moves the selection to the start of the next/previous index section.
+/// Jumping backwards from inside a section lands on that section's start first.
+fn jump_to_index_section(state: &mut State, forward: bool) {
+    let num_entries = state.entries.len();
+    if num_entries == 0 { return; }
+    let sections = wheel_index::build_sections(&state.entries, state.sort_mode);
+    let current_index = state.selected_index % num_entries;
+    let current = wheel_index::section_of(&sections, current_index);
+    let target = if forward {
+        (current + 1) % sections.len()
+    } else if sections[current].start < current_index {
+        current
+    } else {
+        (current + sections.len() - 1) % sections.len()
+    };
+    state.selected_index = sections[target].start;
+    state.selection_animation_timer = 0.0;
+    state.time_since_selection_change = 0.0;
+}
+
 pub fn init() -> State {
     info!("Initializing SelectMusic screen, reading from song cache...");
-    let mut all_entries = vec![];
+    let mut unfiltered_entries = vec![];
     let song_cache = get_song_cache();
     let mut total_filtered_songs = 0;
 
+    let game_type = crate::config::get().game_type;
     for (i, pack) in song_cache.iter().enumerate() {
-        // Filter songs for this pack to only include those with "dance-single" charts.
-        let single_dance_songs: Vec<Arc<SongData>> = pack.songs
+        // Filter songs for this pack to only include those with charts for the game type.
+        let playable_songs: Vec<Arc<SongData>> = pack.songs
             .iter()
-            .filter(|song| {
-                song.charts.iter().any(|chart| chart.chart_type.eq_ignore_ascii_case("dance-single"))
-            })
+            .filter(|song| song.has_charts_for(game_type))
             .cloned()
             .collect();
         
-        // Only add the pack header and its songs if there are any "dance-single" songs in it.
-        if !single_dance_songs.is_empty() {
-            all_entries.push(MusicWheelEntry::PackHeader {
+        // Only add the pack header and its songs if there are any playable songs in it.
+        if !playable_songs.is_empty() {
+            unfiltered_entries.push(MusicWheelEntry::PackHeader {
                 name: pack.name.clone(),
+                display_name: pack.display_name.clone(),
                 original_index: i,
-                banner_path: find_pack_banner(pack),
+                banner_path: pack.banner_path.clone().or_else(|| find_pack_banner(pack)),
+                color: pack.color,
             });
-            total_filtered_songs += single_dance_songs.len();
-            for song in single_dance_songs {
-                all_entries.push(MusicWheelEntry::Song(song));
+            total_filtered_songs += playable_songs.len();
+            for song in playable_songs {
+                unfiltered_entries.push(MusicWheelEntry::Song(song));
             }
         }
     }
     
     let total_songs_before_filter: usize = song_cache.iter().map(|p| p.songs.len()).sum();
-    info!("Read {} packs and {} total songs from cache. After filtering for dance-single, {} songs remain.", song_cache.len(), total_songs_before_filter, total_filtered_songs);
+    info!("Read {} packs and {} total songs from cache. After filtering for {}, {} songs remain.", song_cache.len(), total_songs_before_filter, game_type.chart_type(), total_filtered_songs);
+
+    let filter = SESSION_FILTER.lock().unwrap().unwrap_or(SongFilter::NONE);
+    let group_entries = filter_entries(&unfiltered_entries, &filter);
+    if filter.is_active() {
+        info!("Keeping this session's music wheel filter ({}).", filter.summary());
+    }
 
     let mut state = State {
-        all_entries,
+        all_entries: group_entries.clone(),
+        unfiltered_entries,
+        group_entries,
+        filter,
+        filter_edit: None,
+        sort_mode: SortMode::Group,
         entries: Vec::new(),
         selected_index: 0,
         selected_difficulty_index: 2,
@@ -225,19 +739,24 @@ pub fn init() -> State {
         bg: heart_bg::State::new(),
         last_requested_banner_path: None,
         current_banner_key: "banner1.png".to_string(),
-        last_requested_chart_hash: None,
-        current_graph_key: "__white".to_string(),
+        preview_video_key: None,
+        preview_video_time: 0.0,
+        density_graph_texture: None,
         active_chord_keys: HashSet::new(),
         last_difficulty_nav_key: None,
         last_difficulty_nav_time: None,
         nav_key_held_direction: None,
         nav_key_held_since: None,
         nav_key_last_scrolled_at: None,
+        index_modifier_held: false,
         currently_playing_preview_path: None,
+        playing_preview_video: None,
+        preview_rate: 1.0,
         session_elapsed: 0.0,
         prev_selected_index: 0,
         time_since_selection_change: 0.0,
         displayed_chart_data: None,
+        banner_prefetch: None,
     };
 
     rebuild_displayed_entries(&mut state);
@@ -253,6 +772,10 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
     let Some(PhysicalKey::Code(key_code)) = Some(event.physical_key) else { return ScreenAction::None; };
 
     if event.state == ElementState::Pressed {
+        if state.filter_edit.is_some() {
+            handle_filter_key(state, key_code);
+            return ScreenAction::None;
+        }
         if matches!(key_code, KeyCode::ArrowUp | KeyCode::ArrowDown) {
             state.active_chord_keys.insert(key_code);
         }
@@ -287,6 +810,15 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
             if combo_action_taken { return ScreenAction::None; }
 
             match key_code {
+                KeyCode::ShiftLeft | KeyCode::ShiftRight => {
+                    state.index_modifier_held = true;
+                }
+                KeyCode::ArrowRight | KeyCode::KeyD if state.index_modifier_held => {
+                    jump_to_index_section(state, true);
+                }
+                KeyCode::ArrowLeft | KeyCode::KeyA if state.index_modifier_held => {
+                    jump_to_index_section(state, false);
+                }
                 KeyCode::ArrowRight | KeyCode::KeyD => {
                     if num_entries > 0 {
                         state.selected_index = (state.selected_index + 1) % num_entries;
@@ -368,8 +900,7 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
                     if let Some(entry) = state.entries.get(state.selected_index).cloned() {
                         match entry {
                             MusicWheelEntry::Song(song) => {
-                                info!("Selected song: '{}'. It has {} charts.", song.title, song.charts.len());
-                                return ScreenAction::Navigate(Screen::PlayerOptions);
+                                return start_song(&song);
                             }
                             MusicWheelEntry::PackHeader { name, .. } => {
                                 audio::play_sfx("assets/sounds/expand.ogg");
@@ -389,6 +920,21 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
                         }
                     }
                 }
+                KeyCode::Tab => {
+                    state.sort_mode = state.sort_mode.next();
+                    info!("Sorting music wheel by {}.", state.sort_mode.header_name());
+                    apply_sort(state);
+                    state.prev_selected_index = state.selected_index;
+                    audio::play_sfx("assets/sounds/expand.ogg");
+                }
+                KeyCode::F6 => {
+                    state.nav_key_held_direction = None;
+                    state.nav_key_held_since = None;
+                    state.nav_key_last_scrolled_at = None;
+                    open_filter(state);
+                }
+                KeyCode::Minus | KeyCode::NumpadSubtract => change_rate(state, -1.0),
+                KeyCode::Equal | KeyCode::NumpadAdd => change_rate(state, 1.0),
                 KeyCode::Escape => return ScreenAction::Navigate(Screen::Menu),
                 _ => {}
             }
@@ -397,6 +943,9 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
         if matches!(key_code, KeyCode::ArrowUp | KeyCode::ArrowDown) {
             state.active_chord_keys.remove(&key_code);
         }
+        if matches!(key_code, KeyCode::ShiftLeft | KeyCode::ShiftRight) {
+            state.index_modifier_held = false;
+        }
         if matches!(key_code, KeyCode::ArrowLeft | KeyCode::KeyA | KeyCode::ArrowRight | KeyCode::KeyD) {
             state.nav_key_held_direction = None;
             state.nav_key_held_since = None;
@@ -409,6 +958,21 @@ pub fn handle_key_press(state: &mut State, event: &KeyEvent) -> ScreenAction {
 // Handle D-pad / left-stick as if arrow keys were used.
 pub fn handle_pad_dir(state: &mut State, dir: PadDir, pressed: bool) -> ScreenAction {
     use winit::keyboard::KeyCode;
+    if state.filter_edit.is_some() {
+        if pressed {
+            let key_code = match dir {
+                PadDir::Up => Some(KeyCode::ArrowUp),
+                PadDir::Down => Some(KeyCode::ArrowDown),
+                PadDir::Left => Some(KeyCode::ArrowLeft),
+                PadDir::Right => Some(KeyCode::ArrowRight),
+                PadDir::UpLeft | PadDir::UpRight => None,
+            };
+            if let Some(key_code) = key_code {
+                handle_filter_key(state, key_code);
+            }
+        }
+        return ScreenAction::None;
+    }
     let num_entries = state.entries.len();
 
     if pressed {
@@ -503,6 +1067,7 @@ pub fn handle_pad_dir(state: &mut State, dir: PadDir, pressed: bool) -> ScreenAc
                     state.active_chord_keys.insert(kc);
                 }
             }
+            PadDir::UpLeft | PadDir::UpRight => {}
         }
     } else {
         // releases
@@ -514,6 +1079,7 @@ pub fn handle_pad_dir(state: &mut State, dir: PadDir, pressed: bool) -> ScreenAc
                 state.nav_key_held_since = None;
                 state.nav_key_last_scrolled_at = None;
             }
+            PadDir::UpLeft | PadDir::UpRight => {}
         }
     }
     ScreenAction::None
@@ -523,6 +1089,14 @@ pub fn handle_pad_dir(state: &mut State, dir: PadDir, pressed: bool) -> ScreenAc
 // (F7/Y is handled in app.rs directly for the online-grade fetch.)
 pub fn handle_pad_button(state: &mut State, btn: PadButton, pressed: bool) -> ScreenAction {
     if !pressed { return ScreenAction::None; }
+    if state.filter_edit.is_some() {
+        match btn {
+            PadButton::Confirm => handle_filter_key(state, KeyCode::Enter),
+            PadButton::Back => handle_filter_key(state, KeyCode::Escape),
+            PadButton::F7 => {}
+        }
+        return ScreenAction::None;
+    }
     match btn {
         PadButton::Confirm => {
             if state.entries.is_empty() {
@@ -532,9 +1106,9 @@ pub fn handle_pad_button(state: &mut State, btn: PadButton, pressed: bool) -> Sc
 
             if let Some(entry) = state.entries.get(state.selected_index).cloned() {
                 match entry {
-                    MusicWheelEntry::Song(_song) => {
+                    MusicWheelEntry::Song(song) => {
                         // same as Enter on a song
-                        return ScreenAction::Navigate(Screen::PlayerOptions);
+                        return start_song(&song);
                     }
                     MusicWheelEntry::PackHeader { name, .. } => {
                         // toggle expand/collapse (same as Enter on pack)
@@ -568,8 +1142,42 @@ pub fn handle_pad_button(state: &mut State, btn: PadButton, pressed: bool) -> Sc
     }
 }
 
+fn entry_banner_path(entry: &MusicWheelEntry) -> Option<PathBuf> {
+    match entry {
+        MusicWheelEntry::Song(song) => song.banner_path.clone(),
+        MusicWheelEntry::PackHeader { banner_path, .. } => banner_path.clone(),
+    }
+}
+
+// Picks the banners just ahead of the selection in the direction the wheel moved
+// from `from`, the shorter way round.
+fn queue_banner_prefetch(state: &mut State, from: usize) {
+    let n = state.entries.len();
+    if n < 2 {
+        return;
+    }
+    let to = state.selected_index % n;
+    let forward = (to + n - from % n) % n <= n / 2;
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(BANNER_PREFETCH_AHEAD);
+    for step in 1..=BANNER_PREFETCH_AHEAD.min(n - 1) {
+        let i = if forward { (to + step) % n } else { (to + n - step) % n };
+        if let Some(path) = entry_banner_path(&state.entries[i]) {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    state.banner_prefetch = Some(paths);
+}
+
+/// Banners to decode ahead of the wheel, set whenever the selection moves.
+pub fn take_banner_prefetch(state: &mut State) -> Option<Vec<PathBuf>> {
+    state.banner_prefetch.take()
+}
+
 pub fn update(state: &mut State, dt: f32) -> ScreenAction {
     state.time_since_selection_change += dt;
+    state.preview_video_time += dt;
     state.selection_animation_timer = (state.selection_animation_timer + dt) % SELECTION_ANIMATION_CYCLE_DURATION;
 
     // Handle rapid scrolling when a navigation key is held down.
@@ -599,6 +1207,7 @@ pub fn update(state: &mut State, dt: f32) -> ScreenAction {
 
     if state.selected_index != state.prev_selected_index {
         audio::play_sfx("assets/sounds/change.ogg");
+        queue_banner_prefetch(state, state.prev_selected_index);
         state.prev_selected_index = state.selected_index;
         state.time_since_selection_change = 0.0;
 
@@ -635,7 +1244,11 @@ pub fn update(state: &mut State, dt: f32) -> ScreenAction {
     };
 
     // --- IMMEDIATE UPDATES (Banner) ---
-    let new_banner_path = selected_song.as_ref().and_then(|s| s.banner_path.clone()).or_else(|| selected_pack.and_then(|(_, path)| path));
+    // An animated jacket takes the banner's place; a preview video plays over it later.
+    let new_banner_path = selected_song
+        .as_ref()
+        .and_then(|s| s.preview_path.clone().filter(|p| !video::is_video_path(p)).or_else(|| s.banner_path.clone()))
+        .or_else(|| selected_pack.and_then(|(_, path)| path));
     if state.last_requested_banner_path != new_banner_path {
         state.last_requested_banner_path = new_banner_path.clone();
         return ScreenAction::RequestBanner(new_banner_path);
@@ -645,8 +1258,10 @@ pub fn update(state: &mut State, dt: f32) -> ScreenAction {
     if state.time_since_selection_change >= PREVIEW_DELAY_SECONDS {
         // Music Preview
         let music_path_for_preview = selected_song.as_ref().and_then(|s| s.music_path.clone());
-        if state.currently_playing_preview_path != music_path_for_preview {
+        let rate = selected_rate(state);
+        if state.currently_playing_preview_path != music_path_for_preview || state.preview_rate != rate {
             state.currently_playing_preview_path = music_path_for_preview;
+            state.preview_rate = rate;
             let mut played = false;
             if let Some(song) = &selected_song {
                 if let (Some(path), Some(start), Some(length)) = (&song.music_path, song.sample_start, song.sample_length) {
@@ -656,6 +1271,8 @@ pub fn update(state: &mut State, dt: f32) -> ScreenAction {
                             start_sec: start as f64,
                             length_sec: length as f64,
                             fade_out_sec: PREVIEW_FADE_OUT_SECONDS,
+                            gain_db: song.playback_gain_db(crate::config::get().normalize_music_volume),
+                            rate: rate as f64,
                             ..Default::default()
                         };
                         audio::play_music(path.clone(), cut, true);
@@ -671,31 +1288,45 @@ pub fn update(state: &mut State, dt: f32) -> ScreenAction {
         // Update displayed chart for UI and Graph
         let chart_to_display = selected_song.as_ref().and_then(|song| {
             let difficulty_name = color::FILE_DIFFICULTY_NAMES[state.selected_difficulty_index];
-            song.charts.iter().find(|c| c.difficulty.eq_ignore_ascii_case(difficulty_name)).cloned()
+            song.chart_for(crate::config::get().game_type, difficulty_name).cloned()
         });
-        state.displayed_chart_data = chart_to_display.clone().map(Arc::new);
-        
-        // Density Graph
-        let new_chart_hash = chart_to_display.as_ref().map(|c| c.short_hash.clone());
-        if state.last_requested_chart_hash != new_chart_hash {
-            state.last_requested_chart_hash = new_chart_hash;
-            return ScreenAction::RequestDensityGraph(chart_to_display);
+        state.displayed_chart_data = chart_to_display.map(Arc::new);
+
+        // Preview video, started with the music and looping muted until the selection moves.
+        let video_path = selected_song.as_ref().and_then(|s| s.preview_path.clone()).filter(|p| video::is_video_path(p));
+        if state.playing_preview_video != video_path {
+            state.playing_preview_video = video_path.clone();
+            state.preview_video_key = None;
+            state.preview_video_time = 0.0;
+            return ScreenAction::RequestPreviewVideo(video_path);
+        }
+    } else {
+        if state.currently_playing_preview_path.is_some() {
+            state.currently_playing_preview_path = None;
+            audio::stop_music();
+        }
+        if state.playing_preview_video.is_some() {
+            stop_preview_video(state);
+            return ScreenAction::RequestPreviewVideo(None);
         }
-
-    } else if state.currently_playing_preview_path.is_some() {
-        state.currently_playing_preview_path = None;
-        audio::stop_music();
     }
     
     ScreenAction::None
 }
 
+/// Forgets the playing preview video, so it starts over next time the song is
+/// landed on. The caller stops the video itself.
+pub fn stop_preview_video(state: &mut State) {
+    state.playing_preview_video = None;
+    state.preview_video_key = None;
+}
+
 pub fn in_transition() -> (Vec<Actor>, f32) {
     let actor = act!(quad:
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 1.0):
-        z(1100):
+        z(layer::OVERLAY):
         linear(TRANSITION_IN_DURATION): alpha(0.0):
         linear(0.0): visible(false)
     );
@@ -707,7 +1338,7 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
         align(0.0, 0.0): xy(0.0, 0.0):
         zoomto(screen_width(), screen_height()):
         diffuse(0.0, 0.0, 0.0, 0.0):
-        z(1200):
+        z(layer::FADE_OUT):
         linear(TRANSITION_OUT_DURATION): alpha(1.0)
     );
     (vec![actor], TRANSITION_OUT_DURATION)
@@ -718,8 +1349,6 @@ pub fn out_transition() -> (Vec<Actor>, f32) {
 pub fn trigger_immediate_refresh(state: &mut State) {
     // Ensure we exceed the preview delay so update() runs the delayed branch
     state.time_since_selection_change = PREVIEW_DELAY_SECONDS;
-    // Setting this to None forces a new density graph request for the selected chart
-    state.last_requested_chart_hash = None;
 }
 
 fn format_session_time(seconds_total: f32) -> String {
@@ -756,6 +1385,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         fg_color: [1.0; 4],
         left_text: None, center_text: None, right_text: None,
         left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets { profile_source: true, groovestats: true, session_time: false, clock: true },
     }));
     let footer_avatar = profile
         .avatar_texture_key
@@ -769,20 +1399,28 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         fg_color: [1.0; 4],
         left_text: Some(&profile.display_name), center_text: None, right_text: Some("PRESS START"),
         left_avatar: footer_avatar,
+        widgets: screen_bar::ScreenBarWidgets::NONE,
     }));
  
     // Calculate the color for the currently selected difficulty based on the active theme color
     let selected_difficulty_color_index = state.active_color_index - (4 - state.selected_difficulty_index) as i32;
     let selected_difficulty_color = color::simply_love_rgba(selected_difficulty_color_index);
 
-    // --- Build pack song counts for music wheel ---
+    // --- Build pack song counts for music wheel (what the filter leaves) ---
     let mut pack_song_counts = HashMap::new();
-    let song_cache = get_song_cache();
-    for pack in song_cache.iter() {
-        let count = pack.songs.iter().filter(|song| {
-            song.charts.iter().any(|chart| chart.chart_type.eq_ignore_ascii_case("dance-single"))
-        }).count();
-        pack_song_counts.insert(pack.name.clone(), count);
+    let mut counting_pack: Option<&String> = None;
+    for entry in &state.group_entries {
+        match entry {
+            MusicWheelEntry::PackHeader { name, .. } => {
+                pack_song_counts.insert(name.clone(), 0usize);
+                counting_pack = Some(name);
+            }
+            MusicWheelEntry::Song(_) => {
+                if let Some(count) = counting_pack.and_then(|name| pack_song_counts.get_mut(name)) {
+                    *count += 1;
+                }
+            }
+        }
     }
 
     // Session Timer, centered in the top bar.
@@ -823,6 +1461,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             zoom: final_pad_zoom,
             z: 121,
             is_active: true,
+            game_type: crate::config::get().game_type,
         }));
         // P2 Pad
         actors.push(pad_display::build(pad_display::PadDisplayParams {
@@ -831,6 +1470,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             zoom: final_pad_zoom,
             z: 121,
             is_active: false,
+            game_type: crate::config::get().game_type,
         }));
     }
 
@@ -841,13 +1481,12 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         (0.75,   screen_center_x() - 166.0, 96.0) // <- keep -166 like the Lua
     };
 
-    actors.push(act!(sprite(state.current_banner_key.clone()):
-        align(0.5, 0.5):                 // <- match SL (center)
-        xy(banner_cx, banner_cy):
-        setsize(BANNER_NATIVE_WIDTH, BANNER_NATIVE_HEIGHT):
-        zoom(banner_zoom):
-        z(51)
-    ));
+    actors.extend(banner::build(banner::BannerParams {
+        texture_key: state.preview_video_key.clone().unwrap_or_else(|| state.current_banner_key.clone()),
+        center: [banner_cx, banner_cy], // <- match SL (center)
+        zoom: banner_zoom,
+        z: 51,
+    }));
 
 // --- ARTIST / BPM / LENGTH INFO BOX (Verbatim Implementation) ---
     let (box_width, frame_x, frame_y) = if is_wide() {
@@ -861,17 +1500,24 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     let (artist_text, bpm_text, length_text) = if let Some(entry) = selected_entry {
         match entry {
             MusicWheelEntry::Song(song) => {
-                let minutes = song.total_length_seconds / 60;
-                let seconds = song.total_length_seconds % 60;
-                let formatted_bpm = song.formatted_display_bpm();
+                // At a rate, the length shrinks or stretches and the BPM says so.
+                let rate = selected_rate(state);
+                let length = (song.total_length_seconds as f32 / rate).round() as i32;
+                let minutes = length / 60;
+                let seconds = length % 60;
+                let formatted_bpm = if (rate - 1.0).abs() > 0.001 {
+                    format!("{} ({:.2}x)", song.formatted_display_bpm(), rate)
+                } else {
+                    song.formatted_display_bpm()
+                };
                 (
-                    song.artist.clone(),
+                    song.display_artist(crate::config::get().prefer_translit).to_string(),
                     formatted_bpm,
                     format!("{}:{:02}", minutes, seconds)
                 )
             }
             MusicWheelEntry::PackHeader { original_index, .. } => {
-                let total_length_sec = if let Some(pack) = song_cache.get(*original_index) {
+                let total_length_sec = if let Some(pack) = get_song_cache().get(*original_index) {
                     pack.songs.iter().map(|s| s.total_length_seconds as u64).sum()
                 } else {
                     0
@@ -894,6 +1540,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         offset: [frame_x, frame_y],
         size: [SizeSpec::Px(box_width), SizeSpec::Px(50.0)],
         background: None,
+        clip: false,
         z: 51,
         children: vec![
             // Background Quad
@@ -907,6 +1554,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                 offset: [-110.0, -6.0],
                 size: [SizeSpec::Fill, SizeSpec::Fill],
                 background: None,
+                clip: false,
                 z: 0,
                 children: vec![
                     // --- Artist ---
@@ -919,6 +1567,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
                     act!(text: font("miso"): settext(artist_text):
                         align(0.0, 0.0): xy(5.0, -11.0):
                         maxwidth(artist_max_w): // maxwidth is applied before final size calc
+                        marquee(true):          // scroll long artists instead of squishing
                         zoomtoheight(15.0):     // Enforce a consistent height for alignment
                         diffuse(value_color[0], value_color[1], value_color[2], value_color[3]):
                         z(52)
@@ -959,7 +1608,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     // --- Get data for the various info panes ---
     // IMMEDIATE data for things that update instantly (stats, artist, etc.)
     let immediate_chart_data = if let Some(MusicWheelEntry::Song(song)) = selected_entry {
-        song.charts.iter().find(|c| c.difficulty.eq_ignore_ascii_case(color::FILE_DIFFICULTY_NAMES[state.selected_difficulty_index])).cloned()
+        song.chart_for(crate::config::get().game_type, color::FILE_DIFFICULTY_NAMES[state.selected_difficulty_index]).cloned()
     } else {
         None
     };
@@ -1119,8 +1768,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     }
 
     // --- Density graph panel (SL 1:1, Player 1, top-left anchored) ---
-    let panel_w = if is_wide() { 286.0 } else { 276.0 };
-    let panel_h = 64.0;
+    let (panel_w, panel_h) = density_graph_panel_size();
     
     let mut graph_children: Vec<Actor> = Vec::new();
     
@@ -1132,14 +1780,18 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         diffuse(UI_BOX_BG_COLOR[0], UI_BOX_BG_COLOR[1], UI_BOX_BG_COLOR[2], UI_BOX_BG_COLOR[3])
     ));
     
-    // Only draw the graph sprite + labels + breakdown when we have delayed chart data to show
-    if state.displayed_chart_data.is_some() {
-        // Density graph image fills the panel
-        graph_children.push(act!(sprite(state.current_graph_key.clone()):
-            align(0.0, 0.0):
-            xy(0.0, 0.0):
-            setsize(panel_w, panel_h)
-        ));
+    // Only draw the graph + labels + breakdown when we have delayed chart data to show
+    if let Some(chart) = displayed_chart_data {
+        // Density graph fills the panel; drawn from its texture once that's ready.
+        let drawn = state.density_graph_texture.as_ref().filter(|&key| Some(key) == density_graph_key(state).as_ref());
+        match drawn {
+            Some(key) => graph_children.push(act!(sprite(key.clone()):
+                align(0.0, 0.0):
+                xy(0.0, 0.0):
+                setsize(panel_w, panel_h)
+            )),
+            None => graph_children.extend(build_density_graph(chart, panel_w, panel_h)),
+        }
     
         // Peak NPS text
         graph_children.push(act!(text: font("miso"): settext(peak_nps_text):
@@ -1172,6 +1824,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         ],
         size: [SizeSpec::Px(panel_w), SizeSpec::Px(panel_h)],
         background: None,
+        clip: false,
         z: 51,
         children: graph_children,
     };
@@ -1256,18 +1909,42 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
             zoom(text_zoom): z(121): diffuse(0.0, 0.0, 0.0, 1.0)
         ));
 
-        // Player High Score
-        actors.push(act!(text: font("miso"): settext(score_name):
+        // Player High Score: the local best at the rate the chart would play at.
+        let (best_name, best_percent) = immediate_chart_data
+            .as_ref()
+            .and_then(|chart| scores::get_local_best(&chart.short_hash, selected_rate(state)))
+            .map_or(("----".to_string(), "??.??%".to_string()), |best| {
+                (profile.player_initials.clone(), format!("{:.2}%", best.score_percent * 100.0))
+            });
+        actors.push(act!(text: font("miso"): settext(best_name):
             align(0.5, 0.5): // Centered, like default BitmapText in SM
             xy(pane_cx + cols_x[2] - (50.0 * text_zoom), pane_top + rows_y[1]):
             maxwidth(30.0): zoom(text_zoom): z(121): diffuse(0.0, 0.0, 0.0, 1.0)
         ));
-        actors.push(act!(text: font("miso"): settext(score_percent):
+        actors.push(act!(text: font("miso"): settext(best_percent):
             align(1.0, 0.5): // Right-aligned
             xy(pane_cx + cols_x[2] + (25.0 * text_zoom), pane_top + rows_y[1]):
             zoom(text_zoom): z(121): diffuse(0.0, 0.0, 0.0, 1.0)
         ));
 
+        // --- Play Count / Last Played (same slots as the score rows) ---
+        if let Some(chart) = &immediate_chart_data {
+            let stats = profile::get_play_stats(&chart.short_hash);
+            let last_played = stats.last_played
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                .map_or("--/--/--".to_string(), |d| d.with_timezone(&chrono::Local).format("%m/%d/%y").to_string());
+            actors.push(act!(text: font("miso"): settext(format!("{}x", stats.play_count)):
+                align(0.5, 0.5):
+                xy(pane_cx + cols_x[2] - (50.0 * text_zoom), pane_top + rows_y[2]):
+                maxwidth(30.0): zoom(text_zoom): z(121): diffuse(0.0, 0.0, 0.0, 1.0)
+            ));
+            actors.push(act!(text: font("miso"): settext(last_played):
+                align(1.0, 0.5):
+                xy(pane_cx + cols_x[2] + (25.0 * text_zoom), pane_top + rows_y[2]):
+                zoom(text_zoom): z(121): diffuse(0.0, 0.0, 0.0, 1.0)
+            ));
+        }
+
         // --- Difficulty Meter ---
         let meter_text = if let Some(MusicWheelEntry::Song(_)) = selected_entry {
             // It's a song, show meter or "?" if no chart exists for the difficulty
@@ -1401,7 +2078,7 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
     let mut meters: [Option<i32>; 5] = [None, None, None, None, None];
     if let Some(MusicWheelEntry::Song(song)) = state.entries.get(state.selected_index) {
         for (i, name) in color::FILE_DIFFICULTY_NAMES.iter().enumerate() {
-            if let Some(chart) = song.charts.iter().find(|c| c.difficulty.eq_ignore_ascii_case(name)) {
+            if let Some(chart) = song.chart_for(crate::config::get().game_type, name) {
                 meters[i] = Some(chart.meter as i32);
             }
         }
@@ -1453,6 +2130,16 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         selected_difficulty_index: state.selected_difficulty_index,
     }));
 
+    // --- Jump index sidebar (shown while the index modifier is held) ---
+    if state.index_modifier_held && !state.entries.is_empty() {
+        let sections = wheel_index::build_sections(&state.entries, state.sort_mode);
+        actors.extend(wheel_index::build(wheel_index::WheelIndexParams {
+            current_section: wheel_index::section_of(&sections, state.selected_index % state.entries.len()),
+            sections: &sections,
+            active_color_index: state.active_color_index,
+        }));
+    }
+
     // --- Pulsating Meter Arrow (P1) ---
     let arrow_x_base = screen_center_x() - 53.0;
     let arrow_zoom = 0.575;
@@ -1496,5 +2183,29 @@ pub fn get_actors(state: &State, asset_manager: &AssetManager) -> Vec<Actor> {
         z(122) // Above the difficulty display panel
     ));
 
+    // --- Active filter hint, just above the footer ---
+    if state.filter.is_active() {
+        actors.push(act!(text:
+            font("miso"):
+            settext(format!("Filter: {} (F6 or X to change)", state.filter.summary())):
+            align(0.5, 1.0):
+            xy(screen_center_x(), screen_height() - 36.0):
+            zoom(widescale(0.6, 0.7)):
+            horizalign(center):
+            diffuse(1.0, 1.0, 1.0, 0.9):
+            z(121)
+        ));
+    }
+
+    if let Some(edit) = &state.filter_edit {
+        let rows = edit.draft.rows();
+        actors.extend(filter_overlay::build(filter_overlay::FilterOverlayParams {
+            rows: &rows,
+            selected_row: edit.row,
+            matching_songs: edit.matching_songs,
+            active_color_index: state.active_color_index,
+        }));
+    }
+
     actors
 }
@@ -0,0 +1,173 @@
+use crate::act;
+use crate::config;
+use crate::core::audio;
+use crate::core::space::*;
+use crate::screens::{Screen, ScreenAction};
+use crate::ui::actors::Actor;
+use crate::ui::layer;
+use crate::ui::components::screen_bar::{self, ScreenBarPosition, ScreenBarTitlePlacement};
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+/* ---------------------------- transitions ---------------------------- */
+const TRANSITION_IN_DURATION: f32 = 0.4;
+const TRANSITION_OUT_DURATION: f32 = 0.4;
+
+/* ------------------------------- pattern ------------------------------ */
+/// One click and one flash per beat at 60 BPM.
+const BEAT_INTERVAL: f32 = 1.0;
+/// Quiet time before the first click, so the screen has finished fading in.
+const LEAD_IN: f32 = 1.0;
+/// How long the flash stays fully lit, in seconds (about two frames at 60 Hz).
+const FLASH_DURATION: f32 = 0.033;
+/// Clicks queued on the audio clock when the test starts; an hour of beats.
+const CLICK_COUNT: usize = 3600;
+
+const OFFSET_STEP: f32 = 0.001;
+const OFFSET_STEP_COARSE: f32 = 0.010;
+
+pub struct State {
+    pub elapsed: f32,
+    clicks_scheduled: bool,
+    coarse: bool,
+}
+
+pub fn init() -> State {
+    State { elapsed: 0.0, clicks_scheduled: false, coarse: false }
+}
+
+pub fn handle_key_press(state: &mut State, e: &KeyEvent) -> ScreenAction {
+    let PhysicalKey::Code(key_code) = e.physical_key else { return ScreenAction::None };
+    if matches!(key_code, KeyCode::ShiftLeft | KeyCode::ShiftRight) {
+        state.coarse = e.state == ElementState::Pressed;
+        return ScreenAction::None;
+    }
+    if e.state != ElementState::Pressed {
+        return ScreenAction::None;
+    }
+
+    let step = if state.coarse { OFFSET_STEP_COARSE } else { OFFSET_STEP };
+    match key_code {
+        KeyCode::Escape | KeyCode::Enter => {
+            audio::clear_clicks();
+            return ScreenAction::Navigate(Screen::Options);
+        }
+        KeyCode::ArrowLeft | KeyCode::KeyA => nudge_offset(-step),
+        KeyCode::ArrowRight | KeyCode::KeyD => nudge_offset(step),
+        _ => {}
+    }
+    ScreenAction::None
+}
+
+fn nudge_offset(delta: f32) {
+    let offset = config::get().global_offset_seconds + delta;
+    // Keep whole milliseconds so repeated nudges don't drift.
+    config::update_global_offset((offset * 1000.0).round() / 1000.0);
+}
+
+pub fn update(state: &mut State, dt: f32) {
+    // The clicks are placed to the sample by the mixer, so frame timing can't
+    // smear them; they start with the visual timeline.
+    if !std::mem::replace(&mut state.clicks_scheduled, true) {
+        let first = (LEAD_IN - state.elapsed) as f64;
+        let times = (0..CLICK_COUNT).map(|beat| first + beat as f64 * BEAT_INTERVAL as f64).collect();
+        audio::set_clicks(times, 1.0);
+    }
+    state.elapsed += dt;
+}
+
+/// Seconds since the last flash on the visual timeline. Visuals are shifted by
+/// the global offset exactly like gameplay notes: a beat at music time `t` is
+/// shown at `t - offset`.
+fn visual_phase(state: &State) -> Option<f32> {
+    let t = state.elapsed + config::get().global_offset_seconds - LEAD_IN;
+    (t >= 0.0).then(|| t.rem_euclid(BEAT_INTERVAL))
+}
+
+pub fn in_transition() -> (Vec<Actor>, f32) {
+    let actor = act!(quad:
+        align(0.0, 0.0): xy(0.0, 0.0):
+        zoomto(screen_width(), screen_height()):
+        diffuse(0.0, 0.0, 0.0, 1.0): z(layer::OVERLAY):
+        linear(TRANSITION_IN_DURATION): alpha(0.0):
+        linear(0.0): visible(false)
+    );
+    (vec![actor], TRANSITION_IN_DURATION)
+}
+
+pub fn out_transition() -> (Vec<Actor>, f32) {
+    let actor = act!(quad:
+        align(0.0, 0.0): xy(0.0, 0.0):
+        zoomto(screen_width(), screen_height()):
+        diffuse(0.0, 0.0, 0.0, 0.0):
+        z(layer::FADE_OUT):
+        linear(TRANSITION_OUT_DURATION): alpha(1.0)
+    );
+    (vec![actor], TRANSITION_OUT_DURATION)
+}
+
+pub fn get_actors(state: &State) -> Vec<Actor> {
+    let mut actors = Vec::with_capacity(8);
+    let (cx, cy) = (screen_center_x(), screen_center_y());
+
+    actors.push(act!(quad:
+        align(0.0, 0.0): xy(0.0, 0.0):
+        zoomto(screen_width(), screen_height()):
+        diffuse(0.0, 0.0, 0.0, 1.0):
+        z(0)
+    ));
+    actors.push(screen_bar::build(screen_bar::ScreenBarParams {
+        title: "A/V SYNC TEST",
+        title_placement: ScreenBarTitlePlacement::Left,
+        position: ScreenBarPosition::Top,
+        transparent: false,
+        left_text: None,
+        center_text: None,
+        right_text: None,
+        left_avatar: None,
+        widgets: screen_bar::ScreenBarWidgets::NONE,
+        fg_color: [1.0; 4],
+    }));
+
+    let phase = visual_phase(state);
+    let lit = phase.is_some_and(|p| p < FLASH_DURATION);
+    let flash_alpha = if lit { 1.0 } else { 0.08 };
+    actors.push(act!(quad:
+        align(0.5, 0.5): xy(cx, cy - 30.0):
+        zoomto(200.0, 200.0):
+        diffuse(1.0, 1.0, 1.0, flash_alpha):
+        z(10)
+    ));
+
+    // A marker sweeping across one beat makes early/late easier to judge than the flash alone.
+    let sweep_w = 400.0;
+    let marker_x = cx - 0.5 * sweep_w + phase.map_or(0.0, |p| p / BEAT_INTERVAL) * sweep_w;
+    actors.push(act!(quad:
+        align(0.5, 0.5): xy(cx, cy + 100.0):
+        zoomto(sweep_w, 2.0):
+        diffuse(1.0, 1.0, 1.0, 0.3):
+        z(10)
+    ));
+    actors.push(act!(quad:
+        align(0.5, 0.5): xy(marker_x, cy + 100.0):
+        zoomto(4.0, 16.0):
+        diffuse(1.0, 1.0, 1.0, 1.0):
+        z(11)
+    ));
+
+    let offset_ms = config::get().global_offset_seconds * 1000.0;
+    actors.push(act!(text:
+        align(0.5, 0.5): xy(cx, cy + 140.0):
+        zoom(0.9): font("miso"):
+        settext(format!("Global Offset: {:+.0} ms", offset_ms)):
+        horizalign(center): z(10)
+    ));
+    actors.push(act!(text:
+        align(0.5, 0.5): xy(cx, cy + 175.0):
+        zoom(0.7): font("miso"):
+        settext("The square should flash exactly on each click.\nLeft/Right: adjust by 1 ms (hold Shift for 10 ms). Enter/Esc: done."):
+        horizalign(center): diffuse(0.8, 0.8, 0.8, 1.0): z(10)
+    ));
+
+    actors
+}
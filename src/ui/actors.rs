@@ -1,4 +1,4 @@
-use crate::core::gfx::BlendMode;
+use crate::core::gfx::{BlendMode, Material};
 
 #[derive(Clone, Debug)]
 pub enum Background {
@@ -23,6 +23,77 @@ pub enum SizeSpec {
     Fill,
 }
 
+/// Which clock drives an actor's effects (e.g. `texcoordvelocity`), like SM's `effectclock`.
+/// `Beat` advances in beats and `Music` in song seconds, so both follow rate mods;
+/// outside gameplay there is no song position and they fall back to `Timer`.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EffectClock {
+    #[default]
+    Timer,
+    Beat,
+    Music,
+}
+
+/// StepMania's looping actor effects.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EffectMode {
+    #[default]
+    None,
+    /// Diffuse oscillates between `color1` and `color2`.
+    DiffuseShift,
+    /// Diffuse cycles through the hue wheel.
+    Rainbow,
+    /// Zoom oscillates between `magnitude[0]` and `magnitude[1]`.
+    Pulse,
+    /// Rotation swings by ±`magnitude` degrees (x, y, z).
+    Wag,
+    /// Rotation advances by `magnitude` degrees per clock unit (x, y, z).
+    Spin,
+}
+
+/// An actor effect, evaluated at draw time on the actor's effect clock.
+#[derive(Clone, Copy, Debug)]
+pub struct Effect {
+    pub mode: EffectMode,
+    /// Clock units per cycle.
+    pub period: f32,
+    /// Phase shift, in clock units.
+    pub offset: f32,
+    pub color1: [f32; 4],
+    pub color2: [f32; 4],
+    pub magnitude: [f32; 3],
+}
+
+impl Default for Effect {
+    fn default() -> Self {
+        Self {
+            mode: EffectMode::None,
+            period: 1.0,
+            offset: 0.0,
+            color1: [1.0; 4],
+            color2: [1.0; 4],
+            magnitude: [0.0; 3],
+        }
+    }
+}
+
+impl Effect {
+    /// Starts `mode` with SM's defaults for it (period, colors, magnitude).
+    pub fn start(mode: EffectMode) -> Self {
+        let base = Self { mode, ..Self::default() };
+        match mode {
+            EffectMode::None => Self::default(),
+            EffectMode::DiffuseShift => Self { color1: [0.0, 0.0, 0.0, 1.0], ..base },
+            EffectMode::Rainbow => Self { period: 2.0, ..base },
+            EffectMode::Pulse => Self { period: 2.0, magnitude: [0.5, 1.0, 1.0], ..base },
+            EffectMode::Wag => Self { period: 2.0, magnitude: [0.0, 0.0, 20.0], ..base },
+            EffectMode::Spin => Self { magnitude: [0.0, 0.0, 180.0], ..base },
+        }
+    }
+}
+
 /// A sprite can be sourced from either a texture or a solid color.
 /// For `Solid`, the final color is `tint` (no sampling).
 #[derive(Clone, Debug)]
@@ -40,6 +111,8 @@ pub enum Actor {
         size: [SizeSpec; 2],
         source: SpriteSource,
         tint: [f32; 4],
+        /// Per-corner multipliers on `tint` (TL, TR, BL, BR) for gradients, each in [0, 1].
+        corner_tint: [[f32; 4]; 4],
         z: i16,
         cell: Option<(u32, u32)>,
         grid: Option<(u32, u32)>,
@@ -56,11 +129,23 @@ pub enum Actor {
         fadetop: f32,
         fadebottom: f32,
         blend: BlendMode,
+        /// Rotation about the actor's horizontal axis, in degrees.
+        rot_x_deg: f32,
+        /// Rotation about the actor's vertical axis, in degrees.
+        rot_y_deg: f32,
         rot_z_deg: f32,
         texcoordvelocity: Option<[f32; 2]>,
+        effect_clock: EffectClock,
+        effect: Effect,
         animate: bool,
         state_delay: f32,
         scale: [f32; 2],
+        material: Material,
+        /// SM glow, drawn over the sprite in the same pass; zero alpha is none.
+        glow: [f32; 4],
+        /// Draws nothing, but clips the siblings after it (and their children) to
+        /// its quad, rotation included. For rectangles a clipping frame is cheaper.
+        mask_source: bool,
     },
 
     /// Text actor (BitmapText-like)
@@ -79,6 +164,13 @@ pub enum Actor {
         max_height: Option<f32>,
         max_w_pre_zoom: bool,
         max_h_pre_zoom: bool,
+        /// Scroll back and forth inside `max_width` when the text overflows it, instead of
+        /// squishing. Holds the seconds this text has been scrolling, so each actor starts
+        /// from its own beginning and restarts when its text changes.
+        marquee: Option<f32>,
+        effect_clock: EffectClock,
+        /// Text applies the color and zoom effects; rotation effects are sprite-only.
+        effect: Effect,
         blend: BlendMode,
     },
 
@@ -89,6 +181,28 @@ pub enum Actor {
         size: [SizeSpec; 2],
         children: Vec<Actor>,
         background: Option<Background>,
+        /// Clip children to this frame's rect (a scissor rect); nested clips intersect.
+        clip: bool,
+        z: i16,
+    },
+
+    /// Stroked line through `points` (parent top-left space), for graphs and plots.
+    /// One color per point; a shorter list repeats its last entry.
+    Polyline {
+        points: Vec<[f32; 2]>,
+        colors: Vec<[f32; 4]>,
+        thickness: f32,
         z: i16,
+        blend: BlendMode,
+    },
+
+    /// Filled triangles (parent top-left space); `indices` index `vertices` three
+    /// at a time. One color per vertex; a shorter list repeats its last entry.
+    Mesh {
+        vertices: Vec<[f32; 2]>,
+        indices: Vec<u16>,
+        colors: Vec<[f32; 4]>,
+        z: i16,
+        blend: BlendMode,
     },
 }
@@ -100,6 +100,8 @@ pub struct TweenState {
     pub visible: bool,
     pub flip_x: bool,
     pub flip_y: bool,
+    pub rot_x: f32, // degrees
+    pub rot_y: f32, // degrees
     pub rot_z: f32, // NEW: degrees
     pub crop_l: f32,
     pub crop_r: f32,
@@ -121,6 +123,8 @@ impl Default for TweenState {
             visible: true,
             flip_x: false,
             flip_y: false,
+            rot_x: 0.0,
+            rot_y: 0.0,
             rot_z: 0.0, // NEW
             fade_l: 0.0, fade_r: 0.0, fade_t: 0.0, fade_b: 0.0,
             crop_l: 0.0, crop_r: 0.0, crop_t: 0.0, crop_b: 0.0,
@@ -184,6 +188,8 @@ impl Step {
                         BuildOp::FadeR(t)   => { mix(&mut h, 27); mix_t(&mut h, t); }
                         BuildOp::FadeT(t)   => { mix(&mut h, 28); mix_t(&mut h, t); }
                         BuildOp::FadeB(t)   => { mix(&mut h, 29); mix_t(&mut h, t); }
+                        BuildOp::RotX(t)    => { mix(&mut h, 30); mix_t(&mut h, t); }
+                        BuildOp::RotY(t)    => { mix(&mut h, 31); mix_t(&mut h, t); }
                     }
                 }
             }
@@ -211,6 +217,8 @@ enum BuildOp {
     Visible(bool),
     FlipX(bool),
     FlipY(bool),
+    RotX(Target),
+    RotY(Target),
     RotZ(Target),
     CropL(Target),
     CropR(Target),
@@ -239,6 +247,8 @@ enum PreparedKind {
     Visible(bool),
     FlipX(bool),
     FlipY(bool),
+    RotX { from: f32, to: f32 },
+    RotY { from: f32, to: f32 },
     RotZ { from: f32, to: f32 },
     CropL { from: f32, to: f32 },
     CropR { from: f32, to: f32 },
@@ -266,6 +276,8 @@ impl OpPrepared {
             PreparedKind::Visible(v) => s.visible = v,
             PreparedKind::FlipX(v) => s.flip_x = v,
             PreparedKind::FlipY(v) => s.flip_y = v,
+            PreparedKind::RotX { from, to } => s.rot_x = from + (to - from) * a,
+            PreparedKind::RotY { from, to } => s.rot_y = from + (to - from) * a,
             PreparedKind::RotZ { from, to } => s.rot_z = from + (to - from) * a,
             PreparedKind::CropL { from, to } => s.crop_l = from + (to - from) * a,
             PreparedKind::CropR { from, to } => s.crop_r = from + (to - from) * a,
@@ -359,6 +371,14 @@ impl Segment {
                 BuildOp::FlipY(v) => {
                     self.prepared.push(OpPrepared { kind: PreparedKind::FlipY(v) });
                 }
+                BuildOp::RotX(t) => {
+                    let to = match t { Target::Abs(v) => v, Target::Rel(dv) => s.rot_x + dv };
+                    self.prepared.push(OpPrepared { kind: PreparedKind::RotX { from: s.rot_x, to } });
+                }
+                BuildOp::RotY(t) => {
+                    let to = match t { Target::Abs(v) => v, Target::Rel(dv) => s.rot_y + dv };
+                    self.prepared.push(OpPrepared { kind: PreparedKind::RotY { from: s.rot_y, to } });
+                }
                 BuildOp::RotZ(t) => {
                     let to = match t { Target::Abs(v) => v, Target::Rel(dv) => s.rot_z + dv };
                     self.prepared.push(OpPrepared { kind: PreparedKind::RotZ { from: s.rot_z, to } });
@@ -496,6 +516,10 @@ impl SegmentBuilder {
     // --- rotation (degrees) ---  NEW
     pub fn rotationz(mut self, deg: f32) -> Self { self.ops.push(BuildOp::RotZ(Target::Abs(deg))); self }
     pub fn addrotationz(mut self, ddeg: f32) -> Self { self.ops.push(BuildOp::RotZ(Target::Rel(ddeg))); self }
+    pub fn rotationx(mut self, deg: f32) -> Self { self.ops.push(BuildOp::RotX(Target::Abs(deg))); self }
+    pub fn addrotationx(mut self, ddeg: f32) -> Self { self.ops.push(BuildOp::RotX(Target::Rel(ddeg))); self }
+    pub fn rotationy(mut self, deg: f32) -> Self { self.ops.push(BuildOp::RotY(Target::Abs(deg))); self }
+    pub fn addrotationy(mut self, ddeg: f32) -> Self { self.ops.push(BuildOp::RotY(Target::Rel(ddeg))); self }
 
     pub fn cropleft(mut self, v: f32) -> Self { self.ops.push(BuildOp::CropL(Target::Abs(v))); self }
     pub fn cropright(mut self, v: f32) -> Self { self.ops.push(BuildOp::CropR(Target::Abs(v))); self }
@@ -629,3 +653,68 @@ impl TweenSeq {
         }
     }
 }
+
+/* ============================== ONE-SHOT ANIMS ============================== */
+
+/// A fire-and-forget animation: spawn it with a duration and curve, `tick` it
+/// each frame, and read `value`/`progress` when drawing. `data` carries
+/// whatever the effect needs to draw itself (a judgment, a noteskin window...).
+#[derive(Clone, Copy, Debug)]
+pub struct Anim<T = ()> {
+    pub data: T,
+    elapsed: f32,
+    duration: f32,
+    ease: Ease,
+}
+
+impl Anim<()> {
+    #[inline(always)]
+    pub fn new(duration: f32, ease: Ease) -> Self {
+        Self::with((), duration, ease)
+    }
+}
+
+impl<T> Anim<T> {
+    #[inline(always)]
+    pub fn with(data: T, duration: f32, ease: Ease) -> Self {
+        Self { data, elapsed: 0.0, duration: duration.max(0.0), ease }
+    }
+
+    #[inline(always)]
+    pub fn elapsed(&self) -> f32 { self.elapsed }
+
+    #[inline(always)]
+    pub fn duration(&self) -> f32 { self.duration }
+
+    /// Linear 0..1 through the animation; 1 once it's over.
+    #[inline(always)]
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 { 1.0 } else { (self.elapsed / self.duration).clamp(0.0, 1.0) }
+    }
+
+    /// `progress` run through the curve.
+    #[inline(always)]
+    pub fn value(&self) -> f32 { ease_apply(self.ease, self.progress()) }
+
+    #[inline(always)]
+    pub fn is_finished(&self) -> bool { self.elapsed >= self.duration }
+
+    #[inline(always)]
+    pub fn restart(&mut self) { self.elapsed = 0.0; }
+
+    /// Advances by `dt`; false once the animation has run its course.
+    #[inline(always)]
+    pub fn tick(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        !self.is_finished()
+    }
+}
+
+/// Ticks a fixed set of slots (one per column, say), emptying finished ones.
+pub fn tick_slots<T>(slots: &mut [Option<Anim<T>>], dt: f32) {
+    for slot in slots {
+        if slot.as_mut().is_some_and(|anim| !anim.tick(dt)) {
+            *slot = None;
+        }
+    }
+}
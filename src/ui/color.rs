@@ -80,6 +80,9 @@ pub const JUDGMENT_HEX: [&str; 6] = [
     "#FF3030", // Miss
 ];
 
+/// FA+ white Fantastic (`Profile::fa_plus`), shown above the blue Fantastic row.
+pub const WHITE_FANTASTIC_HEX: &str = "#FFFFFF";
+
 /// Difficulty names as they appear in simfiles. Used for parsing and lookups.
 pub const FILE_DIFFICULTY_NAMES: [&str; 5] = ["Beginner", "Easy", "Medium", "Hard", "Challenge"];
 /// Difficulty names as they should be displayed in the UI.
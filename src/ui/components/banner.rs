@@ -0,0 +1,132 @@
+use crate::act;
+use crate::assets;
+use crate::config;
+use crate::ui::actors::Actor;
+use std::str::FromStr;
+
+/// Native Simply Love banner box, in unscaled pixels.
+pub const BANNER_NATIVE_WIDTH: f32 = 418.0;
+pub const BANNER_NATIVE_HEIGHT: f32 = 164.0;
+
+/// Fraction of the visible banner width/height that fades out at a cropped edge.
+const CROP_EDGE_FADE: f32 = 0.04;
+
+/// How a banner whose aspect ratio differs from the banner box is fitted into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BannerFit {
+    /// Stretch to the box, ignoring aspect (StepMania's default behavior).
+    Stretch,
+    /// Fill the box, cropping the overflowing axis with a soft edge.
+    Crop,
+    /// Fit inside the box, padding the remainder with black bars.
+    Letterbox,
+}
+
+impl Default for BannerFit {
+    fn default() -> Self {
+        BannerFit::Crop
+    }
+}
+
+impl core::fmt::Display for BannerFit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Stretch => write!(f, "Stretch"),
+            Self::Crop => write!(f, "Crop"),
+            Self::Letterbox => write!(f, "Letterbox"),
+        }
+    }
+}
+
+impl FromStr for BannerFit {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stretch" => Ok(Self::Stretch),
+            "crop" => Ok(Self::Crop),
+            "letterbox" => Ok(Self::Letterbox),
+            _ => Err(format!("'{}' is not a valid banner fit mode", s)),
+        }
+    }
+}
+
+pub struct BannerParams {
+    pub texture_key: String,
+    pub center: [f32; 2],
+    pub zoom: f32,
+    pub z: i16,
+}
+
+/// Builds the banner sprite (and letterbox backing, if any) using the configured fit mode.
+pub fn build(p: BannerParams) -> Vec<Actor> {
+    let box_w = BANNER_NATIVE_WIDTH * p.zoom;
+    let box_h = BANNER_NATIVE_HEIGHT * p.zoom;
+    let [cx, cy] = p.center;
+
+    // Animated banners are sheets of frames; the aspect is that of one frame.
+    let (cols, rows) = assets::texture_animation(&p.texture_key).map_or((1, 1), |a| a.grid);
+    let src_aspect = assets::texture_dims(&p.texture_key)
+        .filter(|m| m.w > 0 && m.h > 0)
+        .map(|m| (m.w * rows) as f32 / (m.h * cols) as f32);
+    let box_aspect = BANNER_NATIVE_WIDTH / BANNER_NATIVE_HEIGHT;
+
+    let fit = match src_aspect {
+        // Close enough to the box aspect that stretching is invisible.
+        Some(a) if (a / box_aspect - 1.0).abs() > 0.01 => config::get().banner_fit,
+        _ => BannerFit::Stretch,
+    };
+    let src_aspect = src_aspect.unwrap_or(box_aspect);
+
+    match fit {
+        BannerFit::Stretch => vec![act!(sprite(p.texture_key):
+            align(0.5, 0.5):
+            xy(cx, cy):
+            setsize(box_w, box_h):
+            z(p.z)
+        )],
+        BannerFit::Crop => {
+            // Scale up until both axes cover the box, then crop the overflow evenly.
+            let (w, h) = if src_aspect > box_aspect {
+                (box_h * src_aspect, box_h)
+            } else {
+                (box_w, box_w / src_aspect)
+            };
+            let crop_x = (1.0 - box_w / w).max(0.0) * 0.5;
+            let crop_y = (1.0 - box_h / h).max(0.0) * 0.5;
+            let fade_x = if crop_x > 0.0 { crop_x + CROP_EDGE_FADE * (1.0 - 2.0 * crop_x) } else { 0.0 };
+            let fade_y = if crop_y > 0.0 { crop_y + CROP_EDGE_FADE * (1.0 - 2.0 * crop_y) } else { 0.0 };
+            vec![act!(sprite(p.texture_key):
+                align(0.5, 0.5):
+                xy(cx, cy):
+                setsize(w, h):
+                cropleft(crop_x): cropright(crop_x):
+                croptop(crop_y): cropbottom(crop_y):
+                fadeleft(fade_x): faderight(fade_x):
+                fadetop(fade_y): fadebottom(fade_y):
+                z(p.z)
+            )]
+        }
+        BannerFit::Letterbox => {
+            let (w, h) = if src_aspect > box_aspect {
+                (box_w, box_w / src_aspect)
+            } else {
+                (box_h * src_aspect, box_h)
+            };
+            vec![
+                act!(quad:
+                    align(0.5, 0.5):
+                    xy(cx, cy):
+                    zoomto(box_w, box_h):
+                    diffuse(0.0, 0.0, 0.0, 1.0):
+                    z(p.z)
+                ),
+                act!(sprite(p.texture_key):
+                    align(0.5, 0.5):
+                    xy(cx, cy):
+                    setsize(w, h):
+                    z(p.z)
+                ),
+            ]
+        }
+    }
+}
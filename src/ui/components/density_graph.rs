@@ -0,0 +1,53 @@
+use crate::core::gfx::BlendMode;
+use crate::ui::actors::Actor;
+
+// One vertex pair per sample plus the two edge pairs must fit in u16 indices.
+const MAX_SAMPLES: usize = (u16::MAX as usize / 2) - 2;
+
+/// Filled NPS-per-measure curve, `width` x `height` with its top-left at the
+/// parent origin. Colors run from `bottom_color` at zero to `top_color` at the
+/// chart's peak NPS. Returns `None` when the chart has nothing to plot.
+pub fn build(
+    measure_nps: &[f64],
+    max_nps: f64,
+    width: f32,
+    height: f32,
+    bottom_color: [f32; 4],
+    top_color: [f32; 4],
+    z: i16,
+) -> Option<Actor> {
+    if measure_nps.is_empty() || max_nps <= 0.0 || width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+    let samples = &measure_nps[..measure_nps.len().min(MAX_SAMPLES)];
+    let n = samples.len() as f32;
+    let lerp = |t: f32| -> [f32; 4] {
+        std::array::from_fn(|i| bottom_color[i] + (top_color[i] - bottom_color[i]) * t)
+    };
+
+    // Each measure is plotted at its center; the first/last values extend to the edges.
+    let first = samples[0];
+    let last = samples[samples.len() - 1];
+    let points = std::iter::once((0.0, first))
+        .chain(samples.iter().enumerate().map(|(i, &nps)| ((i as f32 + 0.5) / n * width, nps)))
+        .chain(std::iter::once((width, last)));
+
+    let mut vertices = Vec::with_capacity(2 * (samples.len() + 2));
+    let mut colors = Vec::with_capacity(vertices.capacity());
+    for (x, nps) in points {
+        let t = (nps / max_nps).clamp(0.0, 1.0) as f32;
+        vertices.push([x, height]);
+        colors.push(bottom_color);
+        vertices.push([x, height * (1.0 - t)]);
+        colors.push(lerp(t));
+    }
+
+    let columns = (vertices.len() / 2 - 1) as u16;
+    let mut indices = Vec::with_capacity(columns as usize * 6);
+    for c in 0..columns {
+        let (b0, t0, b1, t1) = (2 * c, 2 * c + 1, 2 * c + 2, 2 * c + 3);
+        indices.extend_from_slice(&[b0, t0, b1, b1, t0, t1]);
+    }
+
+    Some(Actor::Mesh { vertices, indices, colors, z, blend: BlendMode::Alpha })
+}
@@ -0,0 +1,44 @@
+use crate::act;
+use crate::core::space::*;
+use crate::ui::actors::Actor;
+use crate::ui::layer;
+
+const DIM_ALPHA: f32 = 0.75;
+const PROMPT: &str = "Quit DeadSync? The song in progress will end.";
+const HINT: &str = "Press Enter or close the window again to quit, Escape to keep playing.";
+
+/// Builds the "really quit?" prompt shown when the window is closed mid-song:
+/// the whole screen dimmed with the question centered over it.
+pub fn build() -> Vec<Actor> {
+    let dim = act!(quad:
+        align(0.5, 0.5):
+        xy(screen_center_x(), screen_center_y()):
+        zoomto(screen_width(), screen_height()):
+        diffuse(0.0, 0.0, 0.0, DIM_ALPHA):
+        z(layer::OVERLAY)
+    );
+
+    let prompt = act!(text:
+        font("miso"):
+        settext(PROMPT):
+        align(0.5, 0.5):
+        xy(screen_center_x(), screen_center_y() - 16.0):
+        zoom(1.0):
+        horizalign(center):
+        diffuse(1.0, 1.0, 1.0, 1.0):
+        z(layer::OVERLAY)
+    );
+
+    let hint = act!(text:
+        font("miso"):
+        settext(HINT):
+        align(0.5, 0.5):
+        xy(screen_center_x(), screen_center_y() + 16.0):
+        zoom(widescale(0.6, 0.75)):
+        horizalign(center):
+        diffuse(0.8, 0.8, 0.8, 1.0):
+        z(layer::OVERLAY)
+    );
+
+    vec![dim, prompt, hint]
+}
@@ -0,0 +1,105 @@
+use crate::act;
+use crate::core::space::*;
+use crate::ui::actors::Actor;
+use crate::ui::layer;
+use crate::ui::color;
+
+const DIM_ALPHA: f32 = 0.8;
+const ROW_SPACING: f32 = 28.0;
+const LABEL_X_OFFSET: f32 = -140.0;
+const VALUE_X_OFFSET: f32 = 140.0;
+const HINT: &str = "Up/Down pick a setting, Left/Right change it. Enter/Start applies, Backspace/X clears, Escape/Back cancels.";
+
+pub struct FilterOverlayParams<'a> {
+    /// One (label, value) pair per setting, top to bottom.
+    pub rows: &'a [(&'a str, String)],
+    pub selected_row: usize,
+    /// How many songs the settings as shown would leave on the wheel.
+    pub matching_songs: usize,
+    pub active_color_index: i32,
+}
+
+/// Builds the song select filter panel: the screen dimmed, one row per filter
+/// setting with the selected one in the theme color, and the match count below.
+pub fn build(p: FilterOverlayParams) -> Vec<Actor> {
+    let mut actors = Vec::with_capacity(4 + p.rows.len() * 2);
+    let cx = screen_center_x();
+    let top_y = screen_center_y() - (p.rows.len() as f32 * ROW_SPACING) / 2.0;
+    let highlight = color::simply_love_rgba(p.active_color_index);
+
+    actors.push(act!(quad:
+        align(0.5, 0.5):
+        xy(cx, screen_center_y()):
+        zoomto(screen_width(), screen_height()):
+        diffuse(0.0, 0.0, 0.0, DIM_ALPHA):
+        z(layer::OVERLAY)
+    ));
+
+    actors.push(act!(text:
+        font("wendy"):
+        settext("FILTER SONGS"):
+        align(0.5, 0.5):
+        xy(cx, top_y - 44.0):
+        zoom(widescale(0.4, 0.5)):
+        horizalign(center):
+        diffuse(1.0, 1.0, 1.0, 1.0):
+        z(layer::OVERLAY)
+    ));
+
+    for (i, (label, value)) in p.rows.iter().enumerate() {
+        let y = top_y + i as f32 * ROW_SPACING;
+        let rgba = if i == p.selected_row { highlight } else { [1.0, 1.0, 1.0, 1.0] };
+        actors.push(act!(text:
+            font("miso"):
+            settext(label.to_string()):
+            align(0.0, 0.5):
+            xy(cx + LABEL_X_OFFSET, y):
+            zoom(1.0):
+            horizalign(left):
+            diffuse(rgba[0], rgba[1], rgba[2], rgba[3]):
+            z(layer::OVERLAY)
+        ));
+        let value = if i == p.selected_row { format!("< {} >", value) } else { value.clone() };
+        actors.push(act!(text:
+            font("miso"):
+            settext(value):
+            align(1.0, 0.5):
+            xy(cx + VALUE_X_OFFSET, y):
+            zoom(1.0):
+            horizalign(right):
+            diffuse(rgba[0], rgba[1], rgba[2], rgba[3]):
+            z(layer::OVERLAY)
+        ));
+    }
+
+    let bottom_y = top_y + p.rows.len() as f32 * ROW_SPACING;
+    let count = match p.matching_songs {
+        0 => "No songs match".to_string(),
+        1 => "1 song matches".to_string(),
+        n => format!("{} songs match", n),
+    };
+    let count_rgba = if p.matching_songs == 0 { [1.0, 0.4, 0.4, 1.0] } else { [0.8, 0.8, 0.8, 1.0] };
+    actors.push(act!(text:
+        font("miso"):
+        settext(count):
+        align(0.5, 0.5):
+        xy(cx, bottom_y + 12.0):
+        zoom(0.9):
+        horizalign(center):
+        diffuse(count_rgba[0], count_rgba[1], count_rgba[2], count_rgba[3]):
+        z(layer::OVERLAY)
+    ));
+
+    actors.push(act!(text:
+        font("miso"):
+        settext(HINT):
+        align(0.5, 0.5):
+        xy(cx, bottom_y + 40.0):
+        zoom(widescale(0.6, 0.75)):
+        horizalign(center):
+        diffuse(0.8, 0.8, 0.8, 1.0):
+        z(layer::OVERLAY)
+    ));
+
+    actors
+}
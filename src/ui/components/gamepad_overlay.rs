@@ -1,6 +1,7 @@
 use crate::act;
 use crate::core::space::*;
 use crate::ui::actors::Actor;
+use crate::ui::layer;
 use crate::ui::color;
 
 // --- Constants to match StepMania's SystemMessage display ---
@@ -35,7 +36,7 @@ pub fn build(params: Params) -> Vec<Actor> {
         xy(screen_center_x(), 0.0):
         zoomto(screen_width(), final_bar_h): // Use calculated height
         diffuse(bg_color[0], bg_color[1], bg_color[2], 0.0):
-        z(1000): // High Z-order to be on top of other UI
+        z(layer::NOTICE):
 
         // Animation sequence
         linear(FADE_IN_DURATION): alpha(0.85):
@@ -50,7 +51,7 @@ pub fn build(params: Params) -> Vec<Actor> {
         xy(TEXT_MARGIN_X, TEXT_MARGIN_Y):
         zoom(text_zoom): // Apply the widescale zoom
         diffusealpha(0.0):
-        z(1001): // Above the background quad
+        z(layer::NOTICE): // Built after the background quad, so drawn over it
 
         // Animation sequence, synced with the background
         linear(FADE_IN_DURATION): alpha(1.0):
@@ -22,7 +22,7 @@ impl Default for LogoParams {
 /// Returns a `Vec<Actor>` to be included in a screen's actor list.
 pub fn build_logo(params: LogoParams) -> Vec<Actor> {
     // Get logo's native dimensions from the asset system, with a safe fallback.
-    let logo_dims = assets::texture_dims("logo.png").unwrap_or(assets::TexMeta { w: 1, h: 1 });
+    let logo_dims = assets::texture_dims("logo.png").unwrap_or(assets::TexMeta { w: 1, h: 1, density: 1 });
     let logo_aspect = if logo_dims.h > 0 { logo_dims.w as f32 / logo_dims.h as f32 } else { 1.0 };
     
     // Calculate the final display width of the logo based on the target height and true aspect ratio.
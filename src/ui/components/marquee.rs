@@ -0,0 +1,65 @@
+use crate::act;
+use crate::core::space::Metrics;
+use crate::ui::actors::Actor;
+use crate::ui::color;
+
+const MARGIN_X: f32 = 24.0;
+
+pub struct Params<'a> {
+    /// Song title and artist, or `None` when nothing is picked or playing.
+    pub now_playing: Option<(&'a str, &'a str)>,
+    pub accent_color_index: i32,
+}
+
+/// Now-playing actors for the marquee window, laid out in that window's own
+/// `metrics` rather than the main screen's.
+pub fn build(params: Params, metrics: &Metrics) -> Vec<Actor> {
+    let w = metrics.right - metrics.left;
+    let h = metrics.top - metrics.bottom;
+    let accent = color::simply_love_rgba(params.accent_color_index);
+
+    let mut actors = Vec::with_capacity(3);
+    actors.push(act!(quad:
+        align(0.0, 1.0):
+        xy(0.0, h):
+        zoomto(w, 6.0):
+        diffuse(accent[0], accent[1], accent[2], 1.0):
+        z(1)
+    ));
+
+    match params.now_playing {
+        Some((title, artist)) => {
+            actors.push(act!(text:
+                font("wendy"):
+                settext(title):
+                align(0.5, 1.0):
+                xy(0.5 * w, 0.5 * h):
+                maxwidth(w - 2.0 * MARGIN_X):
+                zoom(0.9):
+                diffuse(1.0, 1.0, 1.0, 1.0):
+                z(2)
+            ));
+            actors.push(act!(text:
+                font("miso"):
+                settext(artist):
+                align(0.5, 0.0):
+                xy(0.5 * w, 0.5 * h + 12.0):
+                maxwidth(w - 2.0 * MARGIN_X):
+                zoom(1.2):
+                diffuse(0.8, 0.8, 0.8, 1.0):
+                z(2)
+            ));
+        }
+        None => actors.push(act!(text:
+            font("wendy"):
+            settext("DeadSync"):
+            align(0.5, 0.5):
+            xy(0.5 * w, 0.5 * h):
+            zoom(0.9):
+            diffuse(1.0, 1.0, 1.0, 1.0):
+            z(2)
+        )),
+    }
+
+    actors
+}
@@ -1,8 +1,15 @@
 pub mod logo;
 pub mod screen_bar;
 pub mod stats_overlay;
+pub mod perf_overlay;
 pub mod menu_list;
 pub mod heart_bg;
 pub mod pad_display;
 pub mod music_wheel;
-pub mod gamepad_overlay;
\ No newline at end of file
+pub mod banner;
+pub mod gamepad_overlay;
+pub mod exit_overlay;
+pub mod filter_overlay;
+pub mod wheel_index;
+pub mod marquee;
+pub mod density_graph;
@@ -18,6 +18,10 @@ const NUM_WHEEL_ITEMS: usize = 17;
 const CENTER_WHEEL_SLOT_INDEX: usize = NUM_WHEEL_ITEMS / 2;
 const SELECTION_ANIMATION_CYCLE_DURATION: f32 = 1.0;
 
+// Warning glyph on songs whose files failed validation during the scan.
+const WARNING_BLOCKING_COLOR: [f32; 3] = [1.0, 0.25, 0.25];
+const WARNING_COSMETIC_COLOR: [f32; 3] = [1.0, 0.75, 0.2];
+
 // Helper from select_music.rs
 fn lerp_color(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
     [
@@ -73,6 +77,7 @@ pub fn build(p: MusicWheelParams) -> Vec<Actor> {
     let anim_t = (anim_t_unscaled.sin() + 1.0) / 2.0;
     
     let num_entries = p.entries.len();
+    let prefer_translit = crate::config::get().prefer_translit;
 
     if num_entries > 0 {
         for i_slot in 0..NUM_WHEEL_ITEMS {
@@ -91,14 +96,14 @@ pub fn build(p: MusicWheelParams) -> Vec<Actor> {
                         let base = col_music_wheel_box();
                         let sel  = col_selected_song_box();
                         let bg   = if is_selected_slot { lerp_color(base, sel, anim_t) } else { base };
-                        (false, bg, [1.0, 1.0, 1.0, 1.0], info.title.clone(), info.subtitle.clone(), None)
+                        (false, bg, [1.0, 1.0, 1.0, 1.0], info.disambiguated(info.display_title(prefer_translit), prefer_translit), info.display_subtitle(prefer_translit).to_string(), None)
                     }
-                    Some(MusicWheelEntry::PackHeader { name, original_index, .. }) => {
+                    Some(MusicWheelEntry::PackHeader { name, display_name, original_index, color: pack_color, .. }) => {
                         let base = col_pack_header_box();
                         let sel  = col_selected_pack_header_box();
                         let bg   = if is_selected_slot { lerp_color(base, sel, anim_t) } else { base };
-                        let c    = color::simply_love_rgba(*original_index as i32);
-                        (true, bg, [c[0], c[1], c[2], 1.0], name.clone(), String::new(), Some(name.clone()))
+                        let c    = pack_color.unwrap_or_else(|| color::simply_love_rgba(*original_index as i32));
+                        (true, bg, [c[0], c[1], c[2], 1.0], display_name.clone(), String::new(), Some(name.clone()))
                     }
                     _ => (false, col_music_wheel_box(), [1.0; 4], String::new(), String::new(), None),
                 };
@@ -207,7 +212,7 @@ pub fn build(p: MusicWheelParams) -> Vec<Actor> {
                     
                     let difficulty_name = crate::ui::color::FILE_DIFFICULTY_NAMES[difficulty_index_to_check];
 
-                    if let Some(chart) = info.charts.iter().find(|c| c.difficulty.eq_ignore_ascii_case(difficulty_name)) {
+                    if let Some(chart) = info.chart_for(crate::config::get().game_type, difficulty_name) {
                         if let Some(cached_score) = scores::get_cached_score(&chart.short_hash) {
                             if let Actor::Sprite { visible, cell, .. } = &mut grade_actor {
                                 *visible = true;
@@ -218,6 +223,22 @@ pub fn build(p: MusicWheelParams) -> Vec<Actor> {
                 }
 
                 slot_children.push(grade_actor);
+
+                // --- Asset warning glyph: red for unplayable songs, amber for missing art ---
+                if let Some(MusicWheelEntry::Song(info)) = p.entries.get(list_index) {
+                    if !info.asset_issues.is_empty() {
+                        let glyph_col = if info.play_blocker().is_some() { WARNING_BLOCKING_COLOR } else { WARNING_COSMETIC_COLOR };
+                        slot_children.push(act!(text:
+                            font("miso"):
+                            settext("!"):
+                            align(1.0, 0.5):
+                            xy(pack_count_x_local, half_item_h):
+                            zoom(1.0):
+                            diffuse(glyph_col[0], glyph_col[1], glyph_col[2], 1.0):
+                            z(3)
+                        ));
+                    }
+                }
             }
 
             // Container: left-anchored at SL highlight-left
@@ -226,6 +247,7 @@ pub fn build(p: MusicWheelParams) -> Vec<Actor> {
                 offset: [highlight_left_world, y_center_item],
                 size: [SizeSpec::Px(highlight_w), SizeSpec::Px(item_h_full)],
                 background: None,
+                clip: false,
                 z: 51,
                 children: slot_children,
             });
@@ -283,6 +305,7 @@ pub fn build(p: MusicWheelParams) -> Vec<Actor> {
                 offset: [highlight_left_world, y_center_item],
                 size: [SizeSpec::Px(highlight_w), SizeSpec::Px(item_h_full)],
                 background: None,
+                clip: false,
                 z: 51,
                 children: slot_children,
             });
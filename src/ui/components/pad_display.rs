@@ -1,14 +1,10 @@
 use crate::act;
+use crate::core::input::Lane;
+use crate::game::game_type::GameType;
 use crate::ui::actors::{Actor, SizeSpec};
 
 // This should match the native resolution of "rounded-square.png" from the theme (64x64).
 const PANEL_NATIVE_SIZE: f32 = 64.0;
-// Defines which panels are "active" for the dance-single layout.
-const DANCE_LAYOUT: [bool; 9] = [
-    false, true,  false,
-    true,  false, true,
-    false, true,  false,
-];
 // Defines the layout for an inactive player.
 const INACTIVE_LAYOUT: [bool; 9] = [
     false, false, false,
@@ -16,6 +12,27 @@ const INACTIVE_LAYOUT: [bool; 9] = [
     false, false, false,
 ];
 
+// Where each lane's panel sits in the 3x3 grid, row by row.
+fn grid_index(lane: Lane) -> usize {
+    match lane {
+        Lane::UpLeft => 0,
+        Lane::Up => 1,
+        Lane::UpRight => 2,
+        Lane::Left => 3,
+        Lane::Right => 5,
+        Lane::Down => 7,
+    }
+}
+
+// The panels `game_type` is played on, lit in the grid.
+fn layout_for(game_type: GameType) -> [bool; 9] {
+    let mut layout = INACTIVE_LAYOUT;
+    for &lane in game_type.panels() {
+        layout[grid_index(lane)] = true;
+    }
+    layout
+}
+
 // Colors for active and inactive panels, matching the default (non-dark) theme.
 const COLOR_USED: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
 const COLOR_UNUSED: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
@@ -26,6 +43,8 @@ pub struct PadDisplayParams {
     pub zoom: f32,
     pub z: i16,
     pub is_active: bool,
+    /// Whose panels are lit for an active player.
+    pub game_type: GameType,
 }
 
 /// Builds a 3x3 pad display actor, positioned and scaled as a group.
@@ -33,7 +52,7 @@ pub fn build(params: PadDisplayParams) -> Actor {
     let mut children = Vec::with_capacity(9);
 
     // Choose which layout to use based on whether the player is active.
-    let layout = if params.is_active { DANCE_LAYOUT } else { INACTIVE_LAYOUT };
+    let layout = if params.is_active { layout_for(params.game_type) } else { INACTIVE_LAYOUT };
 
     // This is the final size of one panel after zoom.
     let zoomed_panel_size = PANEL_NATIVE_SIZE * params.zoom;
@@ -70,6 +89,7 @@ pub fn build(params: PadDisplayParams) -> Actor {
         size: [SizeSpec::Px(0.0), SizeSpec::Px(0.0)], // Frame itself has no intrinsic size.
         children,
         background: None,
+        clip: false,
         z: params.z,
     }
 }
\ No newline at end of file
@@ -0,0 +1,35 @@
+use crate::core::gfx::FrameStats;
+use crate::ui::actors::Actor;
+use crate::act;
+
+/// Render statistics for the last sampled frame and CPU time per frame stage
+/// (`profiler::stage_ms`) — top-left, miso, white. GPU time only shows when
+/// the backend could measure it.
+pub fn build(stats: &FrameStats, fps: f32, stages: &[(&str, f32)]) -> Vec<Actor> {
+    const MARGIN_X: f32 = 16.0;
+    const MARGIN_Y: f32 = 16.0;
+
+    let frame_ms = if fps > 0.0 { 1000.0 / fps } else { 0.0 };
+    let gpu = match stats.gpu_time_ms {
+        Some(ms) => format!("{:.2} ms GPU", ms),
+        None => "GPU time n/a".to_string(),
+    };
+    let mut text = format!(
+        "{:.2} ms frame\n{}\n{} draw calls\n{} instances\n{} texture binds",
+        frame_ms, gpu, stats.draw_calls, stats.instances, stats.texture_binds
+    );
+    for (name, ms) in stages {
+        text.push_str(&format!("\n{:.2} ms {}", ms, name));
+    }
+
+    vec![act!(text:
+        align(0.0, 0.0):
+        xy(MARGIN_X, MARGIN_Y):
+        zoom(0.65):
+        diffuse(1.0, 1.0, 1.0, 1.0):
+        font("miso"):
+        settext(text):
+        horizalign(left):
+        z(200)
+    )]
+}
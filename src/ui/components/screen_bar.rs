@@ -3,6 +3,8 @@ use crate::act;
 use crate::core::space::*;
 use crate::core::space;
 use crate::ui::color;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
 
 // --- Constants ---
 const BAR_H: f32 = 32.0;
@@ -22,6 +24,52 @@ pub enum ScreenBarTitlePlacement {
     Center,
 }
 
+/// Status widgets drawn at the right end of the bar, right to left in field
+/// order. Values come from the shared `Status`; leave `right_text` empty when
+/// using them.
+#[derive(Clone, Copy, Default)]
+pub struct ScreenBarWidgets {
+    pub profile_source: bool,
+    pub groovestats: bool,
+    pub session_time: bool,
+    pub clock: bool,
+}
+
+impl ScreenBarWidgets {
+    pub const NONE: Self = Self { profile_source: false, groovestats: false, session_time: false, clock: false };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Connectivity {
+    #[default]
+    Pending,
+    Online,
+    Offline,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProfileSource {
+    #[default]
+    Local,
+    #[allow(dead_code)] // USB profiles aren't loaded yet
+    Usb,
+}
+
+/// What the widgets show, kept current by the app every frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Status {
+    /// Seconds since the first song of the session started, if one has.
+    pub session_elapsed: Option<f32>,
+    pub groovestats: Connectivity,
+    pub profile_source: ProfileSource,
+}
+
+static STATUS: Lazy<Mutex<Status>> = Lazy::new(|| Mutex::new(Status::default()));
+
+pub fn set_status(status: Status) {
+    *STATUS.lock().unwrap() = status;
+}
+
 #[derive(Clone, Copy)]
 pub struct AvatarParams<'a> {
     pub texture_key: &'a str,
@@ -41,6 +89,7 @@ pub struct ScreenBarParams<'a> {
     pub right_text: Option<&'a str>,
 
     pub left_avatar: Option<AvatarParams<'a>>,
+    pub widgets: ScreenBarWidgets,
 
     pub fg_color: [f32; 4], // text color
 }
@@ -161,12 +210,87 @@ pub fn build(params: ScreenBarParams) -> Actor {
         }
     }
 
+    build_widgets(&params, &mut children);
+
     Actor::Frame {
         align,
         offset,
         size:   [SizeSpec::Fill, SizeSpec::Px(BAR_H)],
         children,
         background,
+        clip: false,
         z: 120i16,
     }
 }
+
+// Widget slots are fixed-width so they don't shift as their text changes.
+const WIDGET_MARGIN_X: f32 = 12.0;
+const WIDGET_GAP: f32 = 14.0;
+const WIDGET_ZOOM: f32 = 0.6;
+const PROFILE_SLOT_W: f32 = 40.0;
+const GROOVESTATS_SLOT_W: f32 = 36.0;
+const SESSION_SLOT_W: f32 = 52.0;
+const STATUS_DOT_SIZE: f32 = 8.0;
+
+fn format_session_time(seconds_total: f32) -> String {
+    let seconds_total = seconds_total.max(0.0) as u64;
+    let hours = seconds_total / 3600;
+    let minutes = (seconds_total % 3600) / 60;
+    let seconds = seconds_total % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+fn build_widgets(params: &ScreenBarParams, children: &mut Vec<Actor>) {
+    let w = params.widgets;
+    if !(w.profile_source || w.groovestats || w.session_time || w.clock) {
+        return;
+    }
+    let status = *STATUS.lock().unwrap();
+    let fg = params.fg_color;
+    let y = 0.5 * BAR_H;
+    let mut right = screen_width() - WIDGET_MARGIN_X;
+
+    let push_text = |children: &mut Vec<Actor>, right: f32, text: String| {
+        children.push(act!(text:
+            align(1.0, 0.5): xy(right, y): zoom(WIDGET_ZOOM): z(2):
+            diffuse(fg[0], fg[1], fg[2], fg[3]):
+            font("miso"): settext(text): horizalign(right)
+        ));
+    };
+
+    if w.profile_source {
+        let label = match status.profile_source {
+            ProfileSource::Local => "LOCAL",
+            ProfileSource::Usb => "USB",
+        };
+        push_text(children, right, label.to_string());
+        right -= PROFILE_SLOT_W + WIDGET_GAP;
+    }
+    if w.groovestats {
+        let dot = match status.groovestats {
+            Connectivity::Pending => [0.6, 0.6, 0.6, 1.0],
+            Connectivity::Online => [0.3, 0.85, 0.35, 1.0],
+            Connectivity::Offline => [0.9, 0.25, 0.25, 1.0],
+        };
+        children.push(act!(sprite("circle.png"):
+            align(0.0, 0.5): xy(right - GROOVESTATS_SLOT_W, y):
+            setsize(STATUS_DOT_SIZE, STATUS_DOT_SIZE): z(2):
+            diffuse(dot[0], dot[1], dot[2], dot[3] * fg[3])
+        ));
+        push_text(children, right, "GS".to_string());
+        right -= GROOVESTATS_SLOT_W + WIDGET_GAP;
+    }
+    if w.session_time {
+        if let Some(elapsed) = status.session_elapsed {
+            push_text(children, right, format_session_time(elapsed));
+        }
+        right -= SESSION_SLOT_W + WIDGET_GAP;
+    }
+    if w.clock {
+        push_text(children, right, chrono::Local::now().format("%H:%M").to_string());
+    }
+}
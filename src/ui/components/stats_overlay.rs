@@ -3,20 +3,24 @@ use crate::ui::actors::Actor;
 use crate::act;
 use crate::core::space::*;
 
-/// Three-line stats: FPS, VPF, Backend — top-right, miso, white.
-pub fn build(backend: BackendType, fps: f32, vpf: u32) -> Vec<Actor> {
+/// Three-line stats: FPS, VPF, Backend — top-right, miso, white. A fourth line
+/// shows queue-to-display latency when the backend can measure it.
+pub fn build(backend: BackendType, fps: f32, vpf: u32, present_latency: Option<f32>) -> Vec<Actor> {
     const MARGIN_X: f32 = -16.0;
     const MARGIN_Y: f32 = 16.0;
 
     let w = screen_width();
 
     // 1. Combine all stat lines into a single string with newlines.
-    let stats_text = format!(
+    let mut stats_text = format!(
         "{:.0} FPS\n{} VPF\n{}",
         fps.max(0.0),
         vpf,
         backend.to_string()
     );
+    if let Some(latency) = present_latency {
+        stats_text.push_str(&format!("\n{:.1} ms queue-to-display", latency * 1000.0));
+    }
 
     // 2. Create a single text actor for the entire block.
     // The layout engine will handle the line breaks automatically.
@@ -0,0 +1,170 @@
+use crate::act;
+use crate::core::space::*;
+use crate::game::song::SongData;
+use crate::screens::select_music::{self, MusicWheelEntry, SortMode};
+use crate::ui::actors::Actor;
+use crate::ui::color;
+use crate::ui::primitives::{self, GradientDir, GradientParams, NineSliceParams};
+
+// --- Layout Constants ---
+const MAX_VISIBLE_LABELS: usize = 27;
+const LABEL_SPACING: f32 = 16.0;
+const LABEL_ZOOM: f32 = 0.6;
+const PANEL_W: f32 = 26.0;
+// Border insets of "rounded-square.png" (64x64), drawn at a quarter size.
+const PANEL_INSETS: [f32; 4] = [16.0, 16.0, 16.0, 16.0];
+const PANEL_BORDER_SCALE: f32 = 0.25;
+
+/// A contiguous run of wheel entries that share the same index label.
+#[derive(Clone, Debug)]
+pub struct IndexSection {
+    pub label: String,
+    pub start: usize,
+}
+
+/// Index label for a display string: its first letter, or "#" for anything
+/// that doesn't start with an ASCII letter (digits, symbols, CJK, ...).
+fn initial_label(s: &str) -> String {
+    match s.trim_start().chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase().to_string(),
+        _ => "#".to_string(),
+    }
+}
+
+/// Play-count bucket, coarse at the top so heavy favorites share a label.
+fn play_count_label(count: u32) -> String {
+    match count {
+        100.. => "100".to_string(),
+        50..=99 => "50".to_string(),
+        20..=49 => "20".to_string(),
+        10..=19 => "10".to_string(),
+        5..=9 => "5".to_string(),
+        n => n.to_string(),
+    }
+}
+
+/// How long ago `last_played` was: within a day, week, month, year, or older.
+fn age_label(last_played: Option<i64>, now: i64) -> String {
+    let days = last_played.map_or(i64::MAX, |t| (now - t).max(0) / 86_400);
+    match days {
+        0 => "1d",
+        1..=6 => "1w",
+        7..=30 => "1m",
+        31..=364 => "1y",
+        _ => "old",
+    }
+    .to_string()
+}
+
+/// A song's index label under `sort`: its title initial in group order, or the
+/// bucket of the stat the played sorts order by, so each section is contiguous.
+fn song_label(song: &SongData, sort: SortMode, now: i64) -> String {
+    match sort {
+        SortMode::Group => initial_label(song.sort_title()),
+        SortMode::MostPlayed => play_count_label(select_music::song_play_stats(song).play_count),
+        SortMode::RecentlyPlayed => age_label(select_music::song_play_stats(song).last_played, now),
+    }
+}
+
+/// Splits the displayed wheel into index sections. Pack headers are indexed by
+/// pack initial; songs are indexed per `song_label`, so an expanded pack
+/// contributes its own A-Z run between the surrounding pack headers.
+pub fn build_sections(entries: &[MusicWheelEntry], sort: SortMode) -> Vec<IndexSection> {
+    let now = chrono::Local::now().timestamp();
+    let mut sections: Vec<IndexSection> = Vec::new();
+    let mut last_key: Option<(bool, String)> = None;
+    for (i, entry) in entries.iter().enumerate() {
+        let key = match entry {
+            MusicWheelEntry::PackHeader { display_name, .. } => (true, initial_label(display_name)),
+            MusicWheelEntry::Song(song) => (false, song_label(song, sort, now)),
+        };
+        if last_key.as_ref() != Some(&key) {
+            sections.push(IndexSection { label: key.1.clone(), start: i });
+            last_key = Some(key);
+        }
+    }
+    sections
+}
+
+/// Index of the section containing `entry_index`.
+pub fn section_of(sections: &[IndexSection], entry_index: usize) -> usize {
+    sections
+        .iter()
+        .rposition(|s| s.start <= entry_index)
+        .unwrap_or(0)
+}
+
+pub struct WheelIndexParams<'a> {
+    pub sections: &'a [IndexSection],
+    pub current_section: usize,
+    pub active_color_index: i32,
+}
+
+pub fn build(p: WheelIndexParams) -> Vec<Actor> {
+    let mut actors = Vec::new();
+    let total = p.sections.len();
+    if total == 0 {
+        return actors;
+    }
+
+    // Keep the current label centered once the list is longer than the panel.
+    let visible = total.min(MAX_VISIBLE_LABELS);
+    let first = p
+        .current_section
+        .saturating_sub(visible / 2)
+        .min(total - visible);
+
+    let panel_h = visible as f32 * LABEL_SPACING + LABEL_SPACING;
+    let x = screen_width() - PANEL_W * 0.5 - 4.0;
+    let top = screen_center_y() - panel_h * 0.5;
+
+    actors.extend(primitives::nine_slice(NineSliceParams {
+        texture: "rounded-square.png",
+        xy: [x - PANEL_W * 0.5, top],
+        size: [PANEL_W, panel_h],
+        insets: PANEL_INSETS,
+        border_scale: PANEL_BORDER_SCALE,
+        tint: [0.0, 0.0, 0.0, 0.85],
+        z: 120,
+    }));
+    // Soft sheen fading down from the top edge of the panel.
+    actors.push(primitives::gradient_quad(GradientParams {
+        align: [0.5, 0.0],
+        xy: [x, top],
+        size: [PANEL_W - 4.0, LABEL_SPACING * 3.0],
+        from: [1.0, 1.0, 1.0, 0.12],
+        to: [1.0, 1.0, 1.0, 0.0],
+        dir: GradientDir::Vertical,
+        z: 120,
+    }));
+
+    let highlight = color::simply_love_rgba(p.active_color_index);
+    for (row, section) in p.sections[first..first + visible].iter().enumerate() {
+        let is_current = first + row == p.current_section;
+        let y = top + LABEL_SPACING * (row as f32 + 1.0);
+        if is_current {
+            actors.push(primitives::gradient_quad(GradientParams {
+                align: [0.5, 0.5],
+                xy: [x, y],
+                size: [PANEL_W - 4.0, LABEL_SPACING],
+                from: [highlight[0], highlight[1], highlight[2], 0.0],
+                to: [highlight[0], highlight[1], highlight[2], 0.4],
+                dir: GradientDir::Horizontal,
+                z: 120,
+            }));
+        }
+        let c = if is_current { highlight } else { [1.0, 1.0, 1.0, 0.6] };
+        actors.push(act!(text:
+            font("miso"):
+            settext(section.label.clone()):
+            align(0.5, 0.5):
+            xy(x, y):
+            zoom(if is_current { LABEL_ZOOM * 1.4 } else { LABEL_ZOOM }):
+            diffuse(c[0], c[1], c[2], c[3]):
+            z(121):
+            horizalign(center)
+        ));
+    }
+
+    actors
+}
@@ -8,15 +8,42 @@ use cgmath::{Deg, Matrix4, Vector2, Vector3};
 
 /* ======================= RENDERER SCREEN BUILDER ======================= */
 
+/// Current value of each `EffectClock` for this frame.
+#[derive(Clone, Copy, Debug)]
+pub struct EffectClocks {
+    /// Seconds since startup.
+    pub timer: f32,
+    /// Song beat.
+    pub beat: f32,
+    /// Song position in seconds.
+    pub music: f32,
+}
+
+impl EffectClocks {
+    /// Clocks for screens with no song position: everything runs on wall time.
+    pub fn timer_only(timer: f32) -> Self {
+        Self { timer, beat: timer, music: timer }
+    }
+
+    #[inline(always)]
+    fn get(&self, clock: actors::EffectClock) -> f32 {
+        match clock {
+            actors::EffectClock::Timer => self.timer,
+            actors::EffectClock::Beat => self.beat,
+            actors::EffectClock::Music => self.music,
+        }
+    }
+}
+
 #[inline(always)]
 pub fn build_screen(
     actors: &[actors::Actor],
     clear_color: [f32; 4],
     m: &Metrics,
     fonts: &std::collections::HashMap<&'static str, font::Font>,
-    total_elapsed: f32,
+    clocks: EffectClocks,
 ) -> RenderList {
-    let mut objects = Vec::with_capacity(estimate_object_count(actors, fonts));
+    let mut objects = Vec::with_capacity(estimate_object_count(actors));
     let mut order_counter: u32 = 0;
 
     let root_rect = SmRect {
@@ -26,8 +53,11 @@ pub fn build_screen(
         h: m.top - m.bottom,
     };
     let parent_z: i16 = 0;
+    let mut clips = ClipState { clip: None, next_mask: 0, new_mask: None };
+    let mut masked = Vec::new();
 
-    for actor in actors {
+    for (group, actor) in actors.iter().enumerate() {
+        let first = objects.len();
         build_actor_recursive(
             actor,
             root_rect,
@@ -36,49 +66,56 @@ pub fn build_screen(
             parent_z,
             &mut order_counter,
             &mut objects,
-            total_elapsed,
+            clocks,
+            &mut clips,
         );
+        for obj in &mut objects[first..] {
+            obj.group = group as u32;
+        }
+        if let Some(id) = clips.new_mask.take() {
+            masked.push((id, objects.len()));
+        }
+    }
+    assign_masks(&mut objects, &masked);
+
+    renderer::batch::sort(&mut objects);
+    if clips.next_mask > 0 {
+        objects = place_masks(objects);
     }
 
-    objects.sort_by_key(|o| (o.z, o.order));
+    RenderList { clear_color, objects, post_effects: Vec::new(), labels: None }
+}
 
-    RenderList { clear_color, objects }
+/// A debug label per top-level actor, in the order `build_screen` numbers their groups.
+pub fn group_labels(actors: &[actors::Actor]) -> Vec<String> {
+    actors
+        .iter()
+        .map(|actor| match actor {
+            actors::Actor::Sprite { source: actors::SpriteSource::Texture(key), .. } => format!("Sprite {}", key),
+            actors::Actor::Sprite { .. } => "Quad".to_string(),
+            actors::Actor::Text { font, .. } => format!("Text {}", font),
+            actors::Actor::Frame { children, .. } => format!("Frame ({} children)", children.len()),
+            actors::Actor::Polyline { .. } => "Polyline".to_string(),
+            actors::Actor::Mesh { .. } => "Mesh".to_string(),
+        })
+        .collect()
 }
 
 #[inline(always)]
-fn estimate_object_count(
-    actors: &[Actor],
-    fonts: &std::collections::HashMap<&'static str, font::Font>,
-) -> usize {
+fn estimate_object_count(actors: &[Actor]) -> usize {
     let mut stack: Vec<&Actor> = Vec::with_capacity(actors.len());
     stack.extend(actors.iter());
     let mut total = 0usize;
 
     while let Some(a) = stack.pop() {
         match a {
-            Actor::Sprite { visible, tint, .. } => {
-                if *visible && tint[3] > 0.0 {
+            Actor::Sprite { visible, tint, glow, .. } => {
+                if *visible && (tint[3] > 0.0 || glow[3] > 0.0) {
                     total += 1;
                 }
             }
-            Actor::Text { content, font, .. } => {
-                if let Some(fm) = fonts.get(font) {
-                    let fallback_font = fm.fallback_font_name.and_then(|name| fonts.get(name));
-                    total += content
-                        .chars()
-                        .filter(|&c| {
-                            if c == '\n' {
-                                return false;
-                            }
-                            let mapped = fm.glyph_map.contains_key(&c)
-                                || fallback_font.map_or(false, |f| f.glyph_map.contains_key(&c));
-                            mapped || fm.default_glyph.is_some()
-                        })
-                        .count();
-                } else {
-                    total += content.chars().filter(|&ch| ch != '\n').count();
-                }
-            }
+            // One `ObjectType::Text`; the glyph atlas expands it later.
+            Actor::Text { .. } => total += 1,
             Actor::Frame {
                 children, background, ..
             } => {
@@ -87,6 +124,7 @@ fn estimate_object_count(
                 }
                 stack.extend(children.iter());
             }
+            Actor::Polyline { .. } | Actor::Mesh { .. } => total += 1,
         }
     }
     total
@@ -102,6 +140,91 @@ struct SmRect {
     h: f32,
 }
 
+/// The clip rect currently in effect, from the innermost clipping frame, and
+/// the stencil ids handed out to mask sources.
+struct ClipState {
+    clip: Option<SmRect>,
+    next_mask: u8,
+    /// Set by a mask source for its frame to pick up.
+    new_mask: Option<u8>,
+}
+
+#[inline(always)]
+fn intersect_rect(a: SmRect, b: SmRect) -> SmRect {
+    let x0 = a.x.max(b.x);
+    let y0 = a.y.max(b.y);
+    let x1 = (a.x + a.w).min(b.x + b.w);
+    let y1 = (a.y + a.h).min(b.y + b.h);
+    SmRect { x: x0, y: y0, w: (x1 - x0).max(0.0), h: (y1 - y0).max(0.0) }
+}
+
+/// Makes `rect` (narrowed by any enclosing clip) the active clip. Returns it as a
+/// world-space scissor rect, plus the clip to restore afterwards.
+fn push_clip(clips: &mut ClipState, rect: SmRect, m: &Metrics) -> ([f32; 4], Option<SmRect>) {
+    let saved_clip = clips.clip;
+    let clip_rect = saved_clip.map_or(rect, |c| intersect_rect(c, rect));
+    clips.clip = Some(clip_rect);
+    let (center, size) = sm_rect_to_world_center_size(clip_rect, m);
+    let world = [
+        center.x - 0.5 * size.x,
+        center.y - 0.5 * size.y,
+        center.x + 0.5 * size.x,
+        center.y + 0.5 * size.y,
+    ];
+    (world, saved_clip)
+}
+
+/// Clips objects to `clip`, leaving ones already clipped by a nested frame alone
+/// (their rect is already narrowed by this one).
+#[inline(always)]
+fn assign_clip(objects: &mut [RenderObject], clip: [f32; 4]) {
+    for obj in objects {
+        if obj.clip.is_none() {
+            obj.clip = Some(clip);
+        }
+    }
+}
+
+/// Clips each `(id, start)` mask's objects, `objects[start..]`, to it. A later
+/// mask in the same frame replaces an earlier one, and objects already clipped
+/// by a nested frame's mask keep theirs.
+fn assign_masks(objects: &mut [RenderObject], masked: &[(u8, usize)]) {
+    for &(id, start) in masked.iter().rev() {
+        for obj in &mut objects[start..] {
+            if obj.mask == 0 && !matches!(obj.object_type, renderer::ObjectType::Mask { .. }) {
+                obj.mask = id;
+            }
+        }
+    }
+}
+
+/// Moves stencil masks out of z-order and writes each one right before the objects
+/// it clips. Masks can overlap, so a mask is written again whenever a different
+/// one was written since it was last used. Masks that no object references are dropped.
+fn place_masks(objects: Vec<RenderObject>) -> Vec<RenderObject> {
+    let mut masks: Vec<Option<RenderObject>> = vec![None; 256];
+    let mut rest = Vec::with_capacity(objects.len());
+    for obj in objects {
+        match obj.object_type {
+            renderer::ObjectType::Mask { id } => masks[id as usize] = Some(obj),
+            _ => rest.push(obj),
+        }
+    }
+
+    let mut out = Vec::with_capacity(rest.len() + 8);
+    let mut current: u8 = 0;
+    for obj in rest {
+        if obj.mask != 0 && obj.mask != current {
+            if let Some(mask) = &masks[obj.mask as usize] {
+                out.push(mask.clone());
+            }
+            current = obj.mask;
+        }
+        out.push(obj);
+    }
+    out
+}
+
 #[inline(always)]
 fn build_actor_recursive(
     actor: &actors::Actor,
@@ -111,7 +234,8 @@ fn build_actor_recursive(
     base_z: i16,
     order_counter: &mut u32,
     out: &mut Vec<RenderObject>,
-    total_elapsed: f32,
+    clocks: EffectClocks,
+    clips: &mut ClipState,
 ) {
     match actor {
         actors::Actor::Sprite {
@@ -120,6 +244,7 @@ fn build_actor_recursive(
             size,
             source,
             tint,
+            corner_tint,
             z,
             cell,
             grid,
@@ -136,12 +261,21 @@ fn build_actor_recursive(
             faderight,
             fadetop,
             fadebottom,
+            rot_x_deg,
+            rot_y_deg,
             rot_z_deg,
             texcoordvelocity,
+            effect_clock,
+            effect,
             animate,
             state_delay,
             scale,
+            material,
+            glow,
+            mask_source,
         } => {
+            let fx = sample_effect(effect, clocks.get(*effect_clock));
+            let scale = &[scale[0] * fx.zoom, scale[1] * fx.zoom];
             if !*visible {
                 return;
             }
@@ -155,7 +289,11 @@ fn build_actor_recursive(
             let mut chosen_grid = *grid;
 
             if !is_solid && uv_rect.is_none() {
-                let (cols, rows) = grid.unwrap_or_else(|| assets::parse_sprite_sheet_dims(texture_name));
+                // Animated GIF/APNG sheets carry their own layout and timing.
+                let animation = if grid.is_none() { assets::texture_animation(texture_name) } else { None };
+                let (cols, rows) = grid
+                    .or(animation.as_ref().map(|a| a.grid))
+                    .unwrap_or_else(|| assets::parse_sprite_sheet_dims(texture_name));
                 let total = cols.saturating_mul(rows).max(1);
 
                 let start_linear: u32 = match *cell {
@@ -169,10 +307,13 @@ fn build_actor_recursive(
                 };
 
                 if *animate && *state_delay > 0.0 && total > 1 {
-                    let steps = (total_elapsed / *state_delay).floor().max(0.0) as u32;
+                    let steps = (clocks.timer / *state_delay).floor().max(0.0) as u32;
                     let idx = (start_linear + (steps % total)) % total;
                     chosen_cell = Some((idx, u32::MAX));
                     chosen_grid = Some((cols, rows));
+                } else if let Some(animation) = animation.filter(|_| cell.is_none()) {
+                    chosen_cell = Some((animation.frame_at(clocks.timer) as u32, u32::MAX));
+                    chosen_grid = Some((cols, rows));
                 } else if chosen_cell.is_none() && total > 1 {
                     chosen_cell = Some((0, u32::MAX));
                     chosen_grid = Some((cols, rows));
@@ -198,7 +339,9 @@ fn build_actor_recursive(
                 m,
                 is_solid,
                 texture_name,
-                *tint,
+                // A mask covers its quad whatever its color.
+                if *mask_source { [1.0; 4] } else { mul_color(*tint, fx.tint) },
+                *corner_tint,
                 *uv_rect,
                 chosen_cell,
                 chosen_grid,
@@ -213,11 +356,26 @@ fn build_actor_recursive(
                 *fadetop,
                 *fadebottom,
                 *blend,
-                *rot_z_deg,
+                *material,
+                *glow,
+                [*rot_x_deg + fx.rot_deg[0], *rot_y_deg + fx.rot_deg[1], *rot_z_deg + fx.rot_deg[2]],
                 *texcoordvelocity,
-                total_elapsed,
+                clocks.get(*effect_clock),
             );
 
+            if *mask_source {
+                // Stencil ids are 8-bit; past 255 masks, content goes unclipped.
+                if clips.next_mask == u8::MAX {
+                    out.truncate(before);
+                    return;
+                }
+                clips.next_mask += 1;
+                clips.new_mask = Some(clips.next_mask);
+                for obj in &mut out[before..] {
+                    obj.object_type = renderer::ObjectType::Mask { id: clips.next_mask };
+                }
+            }
+
             let layer = base_z.saturating_add(*z);
             for i in before..out.len() {
                 out[i].z = layer;
@@ -245,15 +403,21 @@ fn build_actor_recursive(
             // NEW:
             max_w_pre_zoom,
             max_h_pre_zoom,
+            marquee,
+            effect_clock,
+            effect,
             blend,
         } => {
             if let Some(fm) = fonts.get(font) {
-                let mut objects = layout_text(
+                let fx = sample_effect(effect, clocks.get(*effect_clock));
+                let color = &mul_color(*color, fx.tint);
+                let (mut objects, marquee_window) = layout_text(
+                    font,
                     fm,
                     fonts,
                     content,
                     0.0,                 // _px_size unused
-                    *scale,
+                    [scale[0] * fx.zoom, scale[1] * fx.zoom],
                     *fit_width,
                     *fit_height,
                     *max_width,
@@ -266,8 +430,14 @@ fn build_actor_recursive(
                     *offset,
                     *align_text,
                     m,
+                    *marquee,
                 );
                 let layer = base_z.saturating_add(*z);
+                if let Some(window) = marquee_window {
+                    let (clip, saved_clip) = push_clip(clips, window, m);
+                    clips.clip = saved_clip;
+                    assign_clip(&mut objects, clip);
+                }
                 for obj in &mut objects {
                     obj.z = layer;
                     obj.order = {
@@ -276,8 +446,9 @@ fn build_actor_recursive(
                         o
                     };
                     obj.blend = *blend;
-                    let renderer::ObjectType::Sprite { tint, .. } = &mut obj.object_type;
-                    *tint = *color;
+                    if let renderer::ObjectType::Text { color: text_color, .. } = &mut obj.object_type {
+                        *text_color = *color;
+                    }
                 }
                 out.extend(objects);
             }
@@ -289,6 +460,7 @@ fn build_actor_recursive(
             size,
             children,
             background,
+            clip,
             z,
         } => {
             let rect = place_rect(parent, *align, *offset, *size);
@@ -305,6 +477,7 @@ fn build_actor_recursive(
                             true,
                             "__white",
                             *c,
+                            [[1.0; 4]; 4],
                             None,
                             None,
                             None,
@@ -319,9 +492,11 @@ fn build_actor_recursive(
                             0.0,
                             0.0,
                             BlendMode::Alpha,
-                            0.0,
+                            renderer::Material::Standard,
+                            [0.0; 4],
+                            [0.0; 3],
                             None,
-                            total_elapsed,
+                            clocks.timer,
                         );
                         for i in before..out.len() {
                             out[i].z = layer;
@@ -341,6 +516,7 @@ fn build_actor_recursive(
                             false,
                             tex,
                             [1.0; 4],
+                            [[1.0; 4]; 4],
                             None,
                             None,
                             None,
@@ -355,9 +531,11 @@ fn build_actor_recursive(
                             0.0,
                             0.0,
                             BlendMode::Alpha,
-                            0.0,
+                            renderer::Material::Standard,
+                            [0.0; 4],
+                            [0.0; 3],
                             None,
-                            total_elapsed,
+                            clocks.timer,
                         );
                         for i in before..out.len() {
                             out[i].z = layer;
@@ -371,6 +549,14 @@ fn build_actor_recursive(
                 }
             }
 
+            let clip_rect = if *clip {
+                Some(push_clip(clips, rect, m))
+            } else {
+                None
+            };
+
+            let before = out.len();
+            let mut masked = Vec::new();
             for child in children {
                 build_actor_recursive(
                     child,
@@ -380,15 +566,143 @@ fn build_actor_recursive(
                     layer,
                     order_counter,
                     out,
-                    total_elapsed,
+                    clocks,
+                    clips,
                 );
+                if let Some(id) = clips.new_mask.take() {
+                    masked.push((id, out.len()));
+                }
+            }
+            assign_masks(out, &masked);
+
+            if let Some((clip, saved_clip)) = clip_rect {
+                clips.clip = saved_clip;
+                assign_clip(&mut out[before..], clip);
+            }
+        }
+
+        actors::Actor::Polyline {
+            points,
+            colors,
+            thickness,
+            z,
+            blend,
+        } => {
+            if points.len() < 2 || *thickness <= 0.0 {
+                return;
+            }
+            // Points are parent-local SM coords; the backend maps them through the parent matrix.
+            out.push(renderer::RenderObject {
+                object_type: renderer::ObjectType::Polyline {
+                    points: points.clone(),
+                    colors: colors.clone(),
+                    thickness: *thickness,
+                },
+                transform: parent_matrix(parent, m),
+                blend: *blend,
+                z: base_z.saturating_add(*z),
+                order: {
+                    let o = *order_counter;
+                    *order_counter += 1;
+                    o
+                },
+                mask: 0,
+                clip: None,
+                group: 0,
+            });
+        }
+
+        actors::Actor::Mesh {
+            vertices,
+            indices,
+            colors,
+            z,
+            blend,
+        } => {
+            if indices.len() < 3 {
+                return;
             }
+            // Vertices are parent-local, like Polyline's points.
+            out.push(renderer::RenderObject {
+                object_type: renderer::ObjectType::Mesh {
+                    vertices: vertices.clone(),
+                    indices: indices.clone(),
+                    colors: colors.clone(),
+                },
+                transform: parent_matrix(parent, m),
+                blend: *blend,
+                z: base_z.saturating_add(*z),
+                order: {
+                    let o = *order_counter;
+                    *order_counter += 1;
+                    o
+                },
+                mask: 0,
+                clip: None,
+                group: 0,
+            });
         }
     }
 }
 
 /* ======================= LAYOUT HELPERS ======================= */
 
+/// What an actor effect contributes this frame.
+struct EffectSample {
+    tint: [f32; 4],
+    zoom: f32,
+    rot_deg: [f32; 3],
+}
+
+/// Evaluates `effect` at clock time `t`, following SM's Actor effect math.
+#[inline(always)]
+fn sample_effect(effect: &actors::Effect, t: f32) -> EffectSample {
+    use std::f32::consts::TAU;
+    let mut sample = EffectSample { tint: [1.0; 4], zoom: 1.0, rot_deg: [0.0; 3] };
+    if effect.mode == actors::EffectMode::None || effect.period <= 0.0 {
+        return sample;
+    }
+    let t = t + effect.offset;
+    let percent = (t / effect.period).rem_euclid(1.0);
+    let mag = effect.magnitude;
+    match effect.mode {
+        actors::EffectMode::None => {}
+        actors::EffectMode::DiffuseShift => {
+            // Starts on color1, reaches color2 half a period later.
+            let between = ((percent + 0.25) * TAU).sin() * 0.5 + 0.5;
+            for i in 0..4 {
+                sample.tint[i] = effect.color1[i] * between + effect.color2[i] * (1.0 - between);
+            }
+        }
+        actors::EffectMode::Rainbow => {
+            let a = percent * TAU;
+            sample.tint = [
+                a.cos() * 0.5 + 0.5,
+                (a + TAU / 3.0).cos() * 0.5 + 0.5,
+                (a + 2.0 * TAU / 3.0).cos() * 0.5 + 0.5,
+                1.0,
+            ];
+        }
+        actors::EffectMode::Pulse => {
+            let s = (percent * std::f32::consts::PI).sin();
+            sample.zoom = mag[0] + (mag[1] - mag[0]) * s;
+        }
+        actors::EffectMode::Wag => {
+            let s = (percent * TAU).sin();
+            sample.rot_deg = [mag[0] * s, mag[1] * s, mag[2] * s];
+        }
+        actors::EffectMode::Spin => {
+            sample.rot_deg = [(mag[0] * t) % 360.0, (mag[1] * t) % 360.0, (mag[2] * t) % 360.0];
+        }
+    }
+    sample
+}
+
+#[inline(always)]
+fn mul_color(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [a[0] * b[0], a[1] * b[1], a[2] * b[2], a[3] * b[3]]
+}
+
 #[inline(always)]
 fn resolve_sprite_size_like_sm(
     size: [SizeSpec; 2],
@@ -415,7 +729,7 @@ fn resolve_sprite_size_like_sm(
         let Some(meta) = assets::texture_dims(texture_name) else {
             return (0.0, 0.0);
         };
-        let (mut tw, mut th) = (meta.w as f32, meta.h as f32);
+        let (mut tw, mut th) = meta.logical_size();
         if let Some([u0, v0, u1, v1]) = uv {
             tw *= (u1 - u0).abs().max(1e-6);
             th *= (v1 - v0).abs().max(1e-6);
@@ -478,12 +792,12 @@ fn calculate_uvs(
     ct: f32,
     cb: f32,
     texcoordvelocity: Option<[f32; 2]>,
-    total_elapsed: f32,
+    effect_time: f32,
 ) -> ([f32; 2], [f32; 2]) {
     let (mut uv_scale, mut uv_offset) = if let Some([u0, v0, u1, v1]) = uv_rect {
-        let du = (u1 - u0).abs().max(1e-6);
-        let dv = (v1 - v0).abs().max(1e-6);
-        ([du, dv], [u0.min(u1), v0.min(v1)])
+        // A reversed rect samples mirrored, as in SM (and noteskin `mirror`).
+        let nonzero = |d: f32| if d.abs() < 1e-6 { 1e-6_f32.copysign(d) } else { d };
+        ([nonzero(u1 - u0), nonzero(v1 - v0)], [u0, v0])
     } else if let Some((cx, cy)) = cell {
         let (gc, gr) = grid.unwrap_or_else(|| assets::parse_sprite_sheet_dims(texture));
         let cols = gc.max(1);
@@ -522,8 +836,8 @@ fn calculate_uvs(
     }
 
     if let Some(vel) = texcoordvelocity {
-        uv_offset[0] += vel[0] * total_elapsed;
-        uv_offset[1] += vel[1] * total_elapsed;
+        uv_offset[0] += vel[0] * effect_time;
+        uv_offset[1] += vel[1] * effect_time;
     }
 
     (uv_scale, uv_offset)
@@ -537,6 +851,7 @@ fn push_sprite(
     is_solid: bool,
     texture_id: &str,
     tint: [f32; 4],
+    corner_tint: [[f32; 4]; 4],
     uv_rect: Option<[f32; 4]>,
     cell: Option<(u32, u32)>,
     grid: Option<(u32, u32)>,
@@ -551,11 +866,13 @@ fn push_sprite(
     fadetop: f32,
     fadebottom: f32,
     blend: BlendMode,
-    rot_z_deg: f32,
+    material: renderer::Material,
+    glow: [f32; 4],
+    rot_deg: [f32; 3],
     texcoordvelocity: Option<[f32; 2]>,
-    total_elapsed: f32,
+    effect_time: f32,
 ) {
-    if tint[3] <= 0.0 {
+    if tint[3] <= 0.0 && glow[3] <= 0.0 {
         return;
     }
 
@@ -592,7 +909,7 @@ fn push_sprite(
             ct,
             cb,
             texcoordvelocity,
-            total_elapsed,
+            effect_time,
         )
     };
 
@@ -606,14 +923,26 @@ fn push_sprite(
     let mut ft_eff = ((ft - ct).max(0.0) / sy_crop).clamp(0.0, 1.0);
     let mut fb_eff = ((fb - cb).max(0.0) / sy_crop).clamp(0.0, 1.0);
 
+    // SM flips mirror the quad itself, so corner colors follow the flip. Corner
+    // tints are multipliers in [0, 1]; Vulkan packs them as RGBA8, so clamp here
+    // for every backend to see the same values.
+    let mut corner_tint = corner_tint.map(|c| c.map(|v| v.clamp(0.0, 1.0)));
     if flip_x {
         std::mem::swap(&mut fl_eff, &mut fr_eff);
+        corner_tint.swap(0, 1);
+        corner_tint.swap(2, 3);
     }
     if flip_y {
         std::mem::swap(&mut ft_eff, &mut fb_eff);
+        corner_tint.swap(0, 2);
+        corner_tint.swap(1, 3);
     }
 
+    // SM order: translate, then rotate X, Y, Z, then scale the unit quad.
+    let [rot_x_deg, rot_y_deg, rot_z_deg] = rot_deg;
     let transform = Matrix4::from_translation(Vector3::new(center_x, center_y, 0.0))
+        * Matrix4::from_angle_x(Deg(rot_x_deg))
+        * Matrix4::from_angle_y(Deg(rot_y_deg))
         * Matrix4::from_angle_z(Deg(rot_z_deg))
         * Matrix4::from_nonuniform_scale(size_x, size_y, 1.0);
 
@@ -630,11 +959,17 @@ fn push_sprite(
             uv_scale,
             uv_offset,
             edge_fade: [fl_eff, fr_eff, ft_eff, fb_eff],
+            corner_tint,
+            material,
+            glow,
         },
         transform,
         blend,
         z: 0,
         order: 0,
+        mask: 0,
+        clip: None,
+        group: 0,
     });
 }
 
@@ -651,7 +986,7 @@ fn clamp_crop_fractions(l: f32, r: f32, t: f32, b: f32) -> (f32, f32, f32, f32)
 
 #[inline(always)]
 #[must_use]
-fn lrint_ties_even(v: f32) -> f32 {
+pub(crate) fn lrint_ties_even(v: f32) -> f32 {
     if !v.is_finite() {
         return 0.0;
     }
@@ -677,7 +1012,7 @@ fn lrint_ties_even(v: f32) -> f32 {
 
 #[inline(always)]
 #[must_use]
-fn quantize_up_even_i32(v: i32) -> i32 {
+pub(crate) fn quantize_up_even_i32(v: i32) -> i32 {
     if v <= 0 {
         0
     } else if (v & 1) != 0 {
@@ -688,6 +1023,7 @@ fn quantize_up_even_i32(v: i32) -> i32 {
 }
 
 fn layout_text(
+    font_name: &'static str,
     font: &font::Font,
     fonts: &std::collections::HashMap<&'static str, font::Font>,
     text: &str,
@@ -705,13 +1041,15 @@ fn layout_text(
     offset: [f32; 2],
     text_align: actors::TextAlign,
     m: &Metrics,
-) -> Vec<RenderObject> {
+    // Some(elapsed) scrolls overflowing text instead of applying max_width.
+    marquee_t: Option<f32>,
+) -> (Vec<RenderObject>, Option<SmRect>) {
     if text.is_empty() {
-        return vec![];
+        return (vec![], None);
     }
     let lines: Vec<&str> = text.lines().collect();
     if lines.is_empty() {
-        return vec![];
+        return (vec![], None);
     }
 
     // 1) Logical (integer) widths like SM: sum integer advances (default glyph if unmapped).
@@ -766,9 +1104,18 @@ fn layout_text(
     let denom_h_for_max = if max_h_pre_zoom { height_before_zoom } else { height_after_zoom };
 
     // 6) Compute per-axis extra downscale from max constraints
-    let max_s_w = max_width.map_or(1.0, |mw| {
+    let mut max_s_w = max_width.map_or(1.0, |mw| {
         if denom_w_for_max > mw { (mw / denom_w_for_max).max(0.0) } else { 1.0 }
     });
+    // Marquee keeps full width and scrolls it through the box the squished text would fill.
+    let marquee_window_w = match marquee_t {
+        Some(_) if max_s_w < 1.0 => {
+            let window_w = width_after_zoom * max_s_w;
+            max_s_w = 1.0;
+            Some(window_w)
+        }
+        _ => None,
+    };
     let max_s_h = max_height.map_or(1.0, |mh| {
         if denom_h_for_max > mh { (mh / denom_h_for_max).max(0.0) } else { 1.0 }
     });
@@ -777,7 +1124,7 @@ fn layout_text(
     let sx = scale[0] * fit_s * max_s_w;
     let sy = scale[1] * fit_s * max_s_h;
     if sx.abs() < 1e-6 || sy.abs() < 1e-6 {
-        return vec![];
+        return (vec![], None);
     }
 
     // 8) Pixel rounding/snapping
@@ -785,116 +1132,66 @@ fn layout_text(
     let block_h_px = block_h_logical * sy;
 
     // 9) Place the block, compute baseline (unchanged)
-    let block_left_sm = parent.x + offset[0] - align[0] * block_w_px;
+    let mut block_left_sm = parent.x + offset[0] - align[0] * block_w_px;
     let block_top_sm  = parent.y + offset[1] - align[1] * block_h_px;
+    let marquee_window = marquee_window_w.map(|window_w| {
+        let window_left = parent.x + offset[0] - align[0] * window_w;
+        let scroll = marquee_scroll(block_w_px - window_w, marquee_t.unwrap_or(0.0));
+        block_left_sm = window_left - scroll;
+        // Pad vertically so glyph overhang above/below the line box isn't clipped.
+        SmRect { x: window_left, y: block_top_sm - 0.5 * block_h_px, w: window_w, h: 2.0 * block_h_px }
+    });
     let block_center_x = block_left_sm + 0.5 * block_w_px;
     let block_center_y = block_top_sm  + 0.5 * block_h_px;
 
-    let mut pen_y_logical = lrint_ties_even(-(block_h_logical_i as f32) * 0.5) as i32;
-    let line_padding = font.line_spacing - font.height;
-
-    #[inline(always)]
-    fn start_x_logical(
-        align: actors::TextAlign,
-        block_w_logical: f32,
-        line_w_logical: f32,
-    ) -> i32 {
-        let align_value = match align {
-            actors::TextAlign::Left => 0.0,
-            actors::TextAlign::Center => 0.5,
-            actors::TextAlign::Right => 1.0,
-        };
-        let start = -0.5 * block_w_logical + align_value * (block_w_logical - line_w_logical);
-        lrint_ties_even(start) as i32
-    }
-
-    #[inline(always)]
-    fn advance_logical(glyph: &font::Glyph) -> i32 {
-        lrint_ties_even(glyph.advance) as i32
-    }
-
-    #[inline(always)]
-    fn logical_to_world(center: f32, logical: f32, scale: f32) -> f32 {
-        center + logical * scale
-    }
-
-    use std::collections::HashMap;
-    let mut dims_cache: HashMap<&str, (f32, f32)> = HashMap::new();
-
-    #[inline(always)]
-    fn atlas_dims<'a>(cache: &mut HashMap<&'a str, (f32, f32)>, key: &'a str) -> (f32, f32) {
-        if let Some(&d) = cache.get(key) {
-            return d;
-        }
-        let d = assets::texture_dims(key)
-            .map_or((1.0_f32, 1.0_f32), |meta| (meta.w as f32, meta.h as f32));
-        cache.insert(key, d);
-        d
-    }
-
-    let mut objects = Vec::new();
-
-    for (i, line) in lines.iter().enumerate() {
-        pen_y_logical += font.height;
-        let baseline_local_logical = pen_y_logical as f32;
-
-        let line_w_logical = logical_line_widths[i] as f32;
-        let mut pen_x_logical = start_x_logical(text_align, block_w_logical_even, line_w_logical);
-
-        for ch in line.chars() {
-            let glyph = font::find_glyph(font, ch, fonts);
-
-            let glyph = match glyph {
-                Some(g) => g,
-                None => continue, // no glyph and no default; skip entirely
-            };
+    let line_align = match text_align {
+        actors::TextAlign::Left => 0.0,
+        actors::TextAlign::Center => 0.5,
+        actors::TextAlign::Right => 1.0,
+    };
+    // Model space is the block in logical units around its center; the glyph atlas lays it out.
+    let text = RenderObject {
+        object_type: renderer::ObjectType::Text {
+            font: font_name,
+            text: text.to_string(),
+            size: cap_height,
+            color: [1.0; 4],
+            align: line_align,
+        },
+        transform: Matrix4::from_translation(Vector3::new(m.left + block_center_x, m.top - block_center_y, 0.0))
+            * Matrix4::from_nonuniform_scale(sx, sy, 1.0),
+        blend: BlendMode::Alpha,
+        z: 0,
+        order: 0,
+        mask: 0,
+        clip: None,
+        group: 0,
+    };
 
-            let quad_w = glyph.size[0] * sx;
-            let quad_h = glyph.size[1] * sy;
-
-            let draw_quad = !(ch == ' ' && font.glyph_map.get(&ch).is_none());
-
-            let quad_x_logical = pen_x_logical as f32 + glyph.offset[0];
-            let quad_y_logical = baseline_local_logical + glyph.offset[1];
-
-            if draw_quad && quad_w.abs() >= 1e-6 && quad_h.abs() >= 1e-6 {
-                let quad_x_sm = logical_to_world(block_center_x, quad_x_logical, sx);
-                let quad_y_sm = logical_to_world(block_center_y, quad_y_logical, sy);
-
-                let center_x = m.left + quad_x_sm + quad_w * 0.5;
-                let center_y = m.top  - (quad_y_sm + quad_h * 0.5);
-
-                let transform = Matrix4::from_translation(Vector3::new(center_x, center_y, 0.0))
-                    * Matrix4::from_nonuniform_scale(quad_w, quad_h, 1.0);
-
-                let (tex_w, tex_h) = atlas_dims(&mut dims_cache, &glyph.texture_key);
-                let uv_scale = [
-                    (glyph.tex_rect[2] - glyph.tex_rect[0]) / tex_w,
-                    (glyph.tex_rect[3] - glyph.tex_rect[1]) / tex_h,
-                ];
-                let uv_offset = [glyph.tex_rect[0] / tex_w, glyph.tex_rect[1] / tex_h];
-
-                objects.push(RenderObject {
-                    object_type: renderer::ObjectType::Sprite {
-                        texture_id: glyph.texture_key.clone(),
-                        tint: [1.0; 4],
-                        uv_scale,
-                        uv_offset,
-                        edge_fade: [0.0; 4],
-                    },
-                    transform,
-                    blend: BlendMode::Alpha,
-                    z: 0,
-                    order: 0,
-                });
-            }
+    (vec![text], marquee_window)
+}
 
-            pen_x_logical += advance_logical(glyph);
-        }
-        pen_y_logical += line_padding;
+/// Ping-pong scroll for marquee text overflowing its window by `overflow` px,
+/// holding at each end before turning around.
+#[inline(always)]
+fn marquee_scroll(overflow: f32, t: f32) -> f32 {
+    const HOLD_SECONDS: f32 = 1.5;
+    const SCROLL_SPEED: f32 = 30.0; // px per second
+    if overflow <= 0.0 {
+        return 0.0;
     }
+    let travel = overflow / SCROLL_SPEED;
+    let phase = t.max(0.0) % (2.0 * (HOLD_SECONDS + travel));
+    let forward = ((phase - HOLD_SECONDS) / travel).clamp(0.0, 1.0);
+    let back = ((phase - 2.0 * HOLD_SECONDS - travel) / travel).clamp(0.0, 1.0);
+    (forward - back) * overflow
+}
 
-    objects
+/// Maps parent-local SM coords (top-left origin, y down) to world space.
+#[inline(always)]
+fn parent_matrix(parent: SmRect, m: &Metrics) -> Matrix4<f32> {
+    Matrix4::from_translation(Vector3::new(m.left + parent.x, m.top - parent.y, 0.0))
+        * Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0)
 }
 
 #[inline(always)]
@@ -1,5 +1,5 @@
-use crate::core::gfx::BlendMode;
-use crate::ui::actors::{Actor, SizeSpec, SpriteSource, TextAlign};
+use crate::core::gfx::{BlendMode, Material};
+use crate::ui::actors::{Actor, Effect, EffectClock, EffectMode, SizeSpec, SpriteSource, TextAlign};
 use crate::ui::{anim, runtime};
 use std::borrow::Cow;
 
@@ -21,8 +21,13 @@ pub enum Mod<'a> {
     // draw order & color
     Z(i16),
     Tint([f32; 4]),
+    /// Per-corner tint; mask bits are TL=1, TR=2, BL=4, BR=8.
+    CornerTint(u8, [f32; 4]),
     Alpha(f32),
     Blend(BlendMode),
+    Material(Material),
+    Glow([f32; 4]),
+    MaskSource,
 
     // absolute size (pre-zoom) in SM TL space
     SizePx(f32, f32),
@@ -56,16 +61,30 @@ pub enum Mod<'a> {
 
     // texture scroll (kept)
     TexVel([f32; 2]),
+    EffectClock(EffectClock),
+
+    // looping effects (SM diffuseshift/rainbow/pulse/wag/spin + effect* params)
+    Effect(EffectMode),
+    EffectPeriod(f32),
+    EffectOffset(f32),
+    EffectColor1([f32; 4]),
+    EffectColor2([f32; 4]),
+    EffectMagnitude([f32; 3]),
 
     // text
     Font(&'static str),
     Content(std::borrow::Cow<'a, str>),
     TAlign(TextAlign),
+    Marquee(bool),
 
     // visibility + rotation
     Visible(bool),
     RotZ(f32),
     AddRotZ(f32),
+    RotX(f32),
+    AddRotX(f32),
+    RotY(f32),
+    AddRotY(f32),
 
     // ---- NEW: SM/ITG-compatible sprite controls ----
     /// `setstate(i)` — linear state index (row-major); grid inferred from filename `_CxR`.
@@ -83,6 +102,21 @@ pub enum Mod<'a> {
 
 /* ======================== SPRITE/QUAD CORE ======================== */
 
+/// Folds an effect mod; starting an effect resets its parameters to SM's defaults,
+/// so `effect*` mods must come after it.
+#[inline(always)]
+fn fold_effect(effect: &mut Effect, m: &Mod) {
+    match m {
+        Mod::Effect(mode)       => { *effect = Effect::start(*mode); }
+        Mod::EffectPeriod(p)    => { effect.period = *p; }
+        Mod::EffectOffset(o)    => { effect.offset = *o; }
+        Mod::EffectColor1(c)    => { effect.color1 = *c; }
+        Mod::EffectColor2(c)    => { effect.color2 = *c; }
+        Mod::EffectMagnitude(v) => { effect.magnitude = *v; }
+        _ => {}
+    }
+}
+
 #[inline(always)]
 fn build_sprite_like<'a>(
     source: SpriteSource,
@@ -95,16 +129,23 @@ fn build_sprite_like<'a>(
     let (mut x, mut y, mut w, mut h) = (0.0, 0.0, 0.0, 0.0);
     let (mut hx, mut vy) = (0.5, 0.5);
     let mut tint = [1.0, 1.0, 1.0, 1.0];
+    let mut corner_tint = [[1.0_f32; 4]; 4];
     let mut z: i16 = 0;
     let (mut vis, mut fx, mut fy) = (true, false, false);
     let (mut cl, mut cr, mut ct, mut cb) = (0.0, 0.0, 0.0, 0.0);
     let (mut fl, mut fr, mut ft, mut fb) = (0.0_f32, 0.0_f32, 0.0_f32, 0.0_f32);
     let mut blend = BlendMode::Alpha;
+    let mut material = Material::Standard;
+    let mut glow = [0.0_f32; 4];
+    let mut mask_source = false;
     let mut rot = 0.0_f32;
+    let (mut rot_x, mut rot_y) = (0.0_f32, 0.0_f32);
     let mut uv: Option<[f32; 4]> = None;
     let mut cell: Option<(u32, u32)> = None;
     let mut grid: Option<(u32, u32)> = None;
     let mut texv: Option<[f32; 2]> = None;
+    let mut effect_clock = EffectClock::Timer;
+    let mut effect = Effect::default();
     // animation
     let mut anim_enable = false;
     let mut state_delay = 0.1_f32;
@@ -128,8 +169,16 @@ fn build_sprite_like<'a>(
 
             Mod::Z(v) => { z = *v; }
             Mod::Tint(rgba) => { tint = *rgba; }
+            Mod::CornerTint(mask, rgba) => {
+                for (i, c) in corner_tint.iter_mut().enumerate() {
+                    if mask & (1 << i) != 0 { *c = *rgba; }
+                }
+            }
             Mod::Alpha(a) => { tint[3] = *a; }
             Mod::Blend(bm) => { blend = *bm; }
+            Mod::Material(mat) => { material = *mat; }
+            Mod::Glow(rgba) => { glow = *rgba; }
+            Mod::MaskSource => { mask_source = true; }
 
             Mod::SizePx(a, b) => { w = *a; h = *b; }
 
@@ -171,13 +220,22 @@ fn build_sprite_like<'a>(
             Mod::FadeBottom(v)  => { fb = *v; }
 
             Mod::TexVel(v)     => { texv = Some(*v); }
+            Mod::EffectClock(c) => { effect_clock = *c; }
+            Mod::Effect(_) | Mod::EffectPeriod(_) | Mod::EffectOffset(_)
+            | Mod::EffectColor1(_) | Mod::EffectColor2(_) | Mod::EffectMagnitude(_) => {
+                fold_effect(&mut effect, m);
+            }
 
             Mod::Visible(v) => { vis = *v; }
             Mod::RotZ(d)    => { rot = *d; }
             Mod::AddRotZ(dd)=> { rot += *dd; }
+            Mod::RotX(d)    => { rot_x = *d; }
+            Mod::AddRotX(dd)=> { rot_x += *dd; }
+            Mod::RotY(d)    => { rot_y = *d; }
+            Mod::AddRotY(dd)=> { rot_y += *dd; }
 
             // text-only mods ignored here
-            Mod::Font(_) | Mod::Content(_) | Mod::TAlign(_) | Mod::MaxWidth(_) | Mod::MaxHeight(_) => {}
+            Mod::Font(_) | Mod::Content(_) | Mod::TAlign(_) | Mod::Marquee(_) | Mod::MaxWidth(_) | Mod::MaxHeight(_) => {}
             Mod::Tween(steps) => { tw = Some(steps); }
             Mod::State(i) => {
                 cell = Some((*i, u32::MAX));
@@ -200,7 +258,7 @@ fn build_sprite_like<'a>(
         init.hx = hx; init.vy = vy;
         init.tint = tint;
         init.visible = vis; init.flip_x = fx; init.flip_y = fy;
-        init.rot_z = rot;
+        init.rot_x = rot_x; init.rot_y = rot_y; init.rot_z = rot;
         init.fade_l = fl; init.fade_r = fr; init.fade_t = ft; init.fade_b = fb;
         init.crop_l = cl; init.crop_r = cr; init.crop_t = ct; init.crop_b = cb;
         init.scale = [sx, sy];
@@ -222,7 +280,7 @@ fn build_sprite_like<'a>(
             mix(&mut h, f32b(init.x)); mix(&mut h, f32b(init.y));
             mix(&mut h, f32b(init.w)); mix(&mut h, f32b(init.h));
             mix(&mut h, f32b(init.hx)); mix(&mut h, f32b(init.vy));
-            mix(&mut h, f32b(init.rot_z));
+            mix(&mut h, f32b(init.rot_x)); mix(&mut h, f32b(init.rot_y)); mix(&mut h, f32b(init.rot_z));
             for c in init.tint { mix(&mut h, f32b(c)); }
             mix(&mut h, u64::from(init.visible));
             mix(&mut h, u64::from(init.flip_x));
@@ -243,7 +301,7 @@ fn build_sprite_like<'a>(
         x = s.x; y = s.y; w = s.w; h = s.h;
         hx = s.hx; vy = s.vy;
         tint = s.tint; vis = s.visible; fx = s.flip_x; fy = s.flip_y;
-        rot = s.rot_z;
+        rot_x = s.rot_x; rot_y = s.rot_y; rot = s.rot_z;
         fl = s.fade_l; fr = s.fade_r; ft = s.fade_t; fb = s.fade_b;
         cl = s.crop_l; cr = s.crop_r; ct = s.crop_t; cb = s.crop_b;
         sx = s.scale[0]; sy = s.scale[1];
@@ -268,6 +326,7 @@ fn build_sprite_like<'a>(
         size: [SizeSpec::Px(w), SizeSpec::Px(h)],
         source,
         tint,
+        corner_tint,
         z,
         cell,
         grid,
@@ -284,11 +343,18 @@ fn build_sprite_like<'a>(
         fadetop: ft,
         fadebottom: fb,
         blend,
+        rot_x_deg: rot_x,
+        rot_y_deg: rot_y,
         rot_z_deg: rot,
         texcoordvelocity: texv,
+        effect_clock,
+        effect,
         animate: anim_enable,
         state_delay,
         scale: scale_carry, // NEW
+        material,
+        glow,
+        mask_source,
     }
 }
 
@@ -312,6 +378,9 @@ pub fn text<'a>(mods: &[Mod<'a>], file: &'static str, line: u32, col: u32) -> Ac
     let mut font: &'static str = "miso";
     let mut content: Cow<'a, str> = Cow::Borrowed("");
     let mut talign = TextAlign::Left;
+    let mut marquee = false;
+    let mut effect_clock = EffectClock::Timer;
+    let mut effect = Effect::default();
     let mut z: i16 = 0;
 
     // zoom + optional fit targets
@@ -346,6 +415,12 @@ pub fn text<'a>(mods: &[Mod<'a>], file: &'static str, line: u32, col: u32) -> Ac
             Mod::Font(f)     => { font = *f; }
             Mod::Content(s)  => { content = s.clone(); }
             Mod::TAlign(a)   => { talign = *a; }
+            Mod::Marquee(v)  => { marquee = *v; }
+            Mod::EffectClock(c) => { effect_clock = *c; }
+            Mod::Effect(_) | Mod::EffectPeriod(_) | Mod::EffectOffset(_)
+            | Mod::EffectColor1(_) | Mod::EffectColor2(_) | Mod::EffectMagnitude(_) => {
+                fold_effect(&mut effect, m);
+            }
             Mod::Z(v)        => { z = *v; }
 
             // zooms — if they occur after a max* for that axis, mark pre-zoom clamp
@@ -396,6 +471,17 @@ pub fn text<'a>(mods: &[Mod<'a>], file: &'static str, line: u32, col: u32) -> Ac
         }
     }
 
+    // Hash of the text content: salts tween state so different text animates
+    // separately, and restarts a marquee when the text changes.
+    let content_hash = {
+        let mut h = 0xcbf29ce484222325u64;
+        for b in content.as_bytes() {
+            h ^= *b as u64;
+            h = h.wrapping_mul(0x100000001b3);
+        }
+        h
+    };
+
     if let Some(steps) = tw {
         let mut init = anim::TweenState::default();
         init.x = x;
@@ -403,18 +489,7 @@ pub fn text<'a>(mods: &[Mod<'a>], file: &'static str, line: u32, col: u32) -> Ac
         init.tint = color;
         init.scale = [sx, sy];
 
-        // Create a salt for the tween state based on the text content to ensure
-        // different text gets a different animation state.
-        let salt = {
-            let mut h = 0xcbf29ce484222325u64;
-            for b in content.as_bytes() {
-                h ^= *b as u64;
-                h = h.wrapping_mul(0x100000001b3);
-            }
-            h
-        };
-
-        let sid = runtime::site_id(file, line, col, salt);
+        let sid = runtime::site_id(file, line, col, content_hash);
         let s = runtime::materialize(sid, init, steps);
 
         // Apply tweened state
@@ -440,6 +515,9 @@ pub fn text<'a>(mods: &[Mod<'a>], file: &'static str, line: u32, col: u32) -> Ac
         max_height: max_h,
         max_w_pre_zoom,
         max_h_pre_zoom,
+        marquee: marquee.then(|| runtime::marquee_phase(runtime::site_id(file, line, col, 0), content_hash)),
+        effect_clock,
+        effect,
         blend,
     }
 }
@@ -630,6 +708,31 @@ macro_rules! __dsl_apply_one {
             $mods.push($crate::ui::dsl::Mod::Tint([($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
         }
     }};
+    // --- per-corner / per-edge color (SM diffuseupperleft, diffusetopedge, ...) ---
+    (diffuseupperleft ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b0001, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffuseupperright ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b0010, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffuselowerleft ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b0100, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffuselowerright ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b1000, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffusetopedge ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b0011, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffusebottomedge ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b1100, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffuseleftedge ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b0101, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (diffuserightedge ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::CornerTint(0b1010, [($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
     (alpha ($a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
         if let ::core::option::Option::Some(mut seg) = $cur.take() {
             seg = seg.alpha(($a) as f32);
@@ -791,6 +894,77 @@ macro_rules! __dsl_apply_one {
         else { $mods.push($crate::ui::dsl::Mod::AddRotZ(dd)); }
     }};
 
+    // --- rotationx / rotationy (degrees, about the actor's horizontal / vertical axis) ---
+    (rotationx ($deg:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        let d=($deg) as f32;
+        if let ::core::option::Option::Some(mut seg)=$cur.take(){ seg=seg.rotationx(d); $cur=::core::option::Option::Some(seg); }
+        else { $mods.push($crate::ui::dsl::Mod::RotX(d)); }
+    }};
+
+    (addrotationx ($ddeg:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        let dd=($ddeg) as f32;
+        if let ::core::option::Option::Some(mut seg)=$cur.take(){ seg=seg.addrotationx(dd); $cur=::core::option::Option::Some(seg); }
+        else { $mods.push($crate::ui::dsl::Mod::AddRotX(dd)); }
+    }};
+
+    (rotationy ($deg:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        let d=($deg) as f32;
+        if let ::core::option::Option::Some(mut seg)=$cur.take(){ seg=seg.rotationy(d); $cur=::core::option::Option::Some(seg); }
+        else { $mods.push($crate::ui::dsl::Mod::RotY(d)); }
+    }};
+
+    (addrotationy ($ddeg:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        let dd=($ddeg) as f32;
+        if let ::core::option::Option::Some(mut seg)=$cur.take(){ seg=seg.addrotationy(dd); $cur=::core::option::Option::Some(seg); }
+        else { $mods.push($crate::ui::dsl::Mod::AddRotY(dd)); }
+    }};
+
+    // looping effects; start one first, then override its effect* params
+    (diffuseshift () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Effect($crate::ui::actors::EffectMode::DiffuseShift));
+    }};
+    (rainbow () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Effect($crate::ui::actors::EffectMode::Rainbow));
+    }};
+    (pulse () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Effect($crate::ui::actors::EffectMode::Pulse));
+    }};
+    (wag () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Effect($crate::ui::actors::EffectMode::Wag));
+    }};
+    (spin () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Effect($crate::ui::actors::EffectMode::Spin));
+    }};
+    (stopeffect () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Effect($crate::ui::actors::EffectMode::None));
+    }};
+    (effectperiod ($p:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectPeriod(($p) as f32));
+    }};
+    (effectoffset ($o:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectOffset(($o) as f32));
+    }};
+    (effectcolor1 ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectColor1([($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (effectcolor2 ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectColor2([($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    (effectmagnitude ($x:expr,$y:expr,$z:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectMagnitude([($x) as f32,($y) as f32,($z) as f32]));
+    }};
+
+    // effect clock: timer (wall time), beat, music (song seconds)
+    (effectclock (timer) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectClock($crate::ui::actors::EffectClock::Timer));
+    }};
+    (effectclock (beat) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectClock($crate::ui::actors::EffectClock::Beat));
+    }};
+    (effectclock (music) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::EffectClock($crate::ui::actors::EffectClock::Music));
+    }};
+
     // blends: normal, add, multiply, subtract
     (blend (normal) $mods:ident $tw:ident $cur:ident $site:ident) => {{
         $mods.push($crate::ui::dsl::Mod::Blend($crate::core::gfx::BlendMode::Alpha));
@@ -805,6 +979,19 @@ macro_rules! __dsl_apply_one {
         $mods.push($crate::ui::dsl::Mod::Blend($crate::core::gfx::BlendMode::Subtract));
     }};
 
+    // sprite material (gfx::Material), e.g. material(Material::Grayscale { amount: 1.0 })
+    (material ($m:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Material($m));
+    }};
+    // SM glow color, drawn over the sprite by the same instance
+    (glow ($r:expr,$g:expr,$b:expr,$a:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Glow([($r) as f32,($g) as f32,($b) as f32,($a) as f32]));
+    }};
+    // clip the following siblings to this sprite's quad instead of drawing it
+    (masksource () $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::MaskSource);
+    }};
+
     // Text properties (SM-compatible)
     (font ($n:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{ $mods.push($crate::ui::dsl::Mod::Font($n)); }};
     (settext ($s:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
@@ -813,6 +1000,10 @@ macro_rules! __dsl_apply_one {
     (horizalign ($dir:ident) $mods:ident $tw:ident $cur:ident $site:ident) => {{
         $mods.push($crate::ui::dsl::Mod::TAlign($crate::__ui_textalign_from_ident!($dir)));
     }};
+    // scroll inside maxwidth instead of squishing
+    (marquee ($v:expr) $mods:ident $tw:ident $cur:ident $site:ident) => {{
+        $mods.push($crate::ui::dsl::Mod::Marquee(($v) as bool));
+    }};
 
     // unknown
     ($other:ident ( $($args:expr),* ) $mods:ident $tw:ident $cur:ident $site:ident) => {
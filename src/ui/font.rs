@@ -165,31 +165,6 @@ fn parse_line_entry_raw(raw: &str) -> Option<(u32, &str)> {
     Some((row, rhs))
 }
 
-#[inline(always)]
-fn is_doubleres_in_name(name: &str) -> bool {
-    let b = name.as_bytes();
-    // search for "doubleres" case-insensitively without allocation
-    for w in b.windows(9) {
-        #[inline(always)]
-        fn low(x: u8) -> u8 {
-            x | 0x20
-        }
-        if low(w[0]) == b'd'
-            && low(w[1]) == b'o'
-            && low(w[2]) == b'u'
-            && low(w[3]) == b'b'
-            && low(w[4]) == b'l'
-            && low(w[5]) == b'e'
-            && low(w[6]) == b'r'
-            && low(w[7]) == b'e'
-            && low(w[8]) == b's'
-        {
-            return true;
-        }
-    }
-    false
-}
-
 /// [section]->{key->val} (both section/key lowercased, value trimmed). Only std.
 /// Allocation-free per line (borrows &str).
 #[inline(always)]
@@ -643,19 +618,15 @@ pub fn parse(ini_path_str: &str) -> Result<FontLoadData, Box<dyn std::error::Err
         required_textures.push(tex_path.to_path_buf());
 
         let (num_frames_wide, num_frames_high) = assets::parse_sprite_sheet_dims(&texture_key);
-        let has_doubleres = is_doubleres_in_name(&texture_key);
+        let density = assets::parse_texture_density(&texture_key);
         let total_frames = (num_frames_wide * num_frames_high) as usize;
 
         let (base_tex_w, base_tex_h) =
             parse_base_res_from_filename(&texture_key).unwrap_or((tex_dims.0, tex_dims.1));
 
         // authored metrics parity w/ StepMania
-        let mut authored_tex_w = base_tex_w;
-        let mut authored_tex_h = base_tex_h;
-        if has_doubleres {
-            authored_tex_w = (authored_tex_w / 2).max(1);
-            authored_tex_h = (authored_tex_h / 2).max(1);
-        }
+        let authored_tex_w = (base_tex_w / density).max(1);
+        let authored_tex_h = (base_tex_h / density).max(1);
         let frame_w_i = (authored_tex_w / num_frames_wide) as i32;
         let frame_h_i = (authored_tex_h / num_frames_high) as i32;
 
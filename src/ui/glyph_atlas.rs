@@ -0,0 +1,250 @@
+//! Runtime glyph atlas behind `ObjectType::Text`.
+//! - Glyphs are copied out of the font sheets the first time they are drawn, so
+//!   a text run is one texture no matter how many pages or fallback fonts it spans
+//! - Sheets are decoded on demand and only a few are kept (the CJK/emoji pages are large)
+//! - When the atlas fills up mid-list, the rest of the list draws from the font
+//!   sheets; the atlas starts over before the next list, and glyphs still in use
+//!   get packed again
+//! - Only the region packed since the last upload is uploaded again
+
+use crate::assets;
+use crate::core::gfx::{Material, ObjectType, RenderList, RenderObject};
+use crate::ui::compose::{lrint_ties_even, quantize_up_even_i32};
+use crate::ui::font::{self, Font, Glyph};
+use cgmath::{Matrix4, Vector3};
+use image::RgbaImage;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Texture key the atlas is uploaded under.
+pub const GLYPH_ATLAS_KEY: &str = "__glyph_atlas";
+
+const ATLAS_SIZE: u32 = 2048;
+// Transparent border so linear filtering never pulls in a neighbouring glyph.
+const PADDING: u32 = 1;
+const MAX_CACHED_SHEETS: usize = 4;
+
+#[derive(Clone, Copy)]
+struct AtlasRect {
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+}
+
+pub struct GlyphAtlas {
+    image: RgbaImage,
+    sheet_paths: HashMap<String, PathBuf>,
+    // Decoded sheets, least recently used first.
+    sheets: Vec<(String, RgbaImage)>,
+    // (sheet key, source rect in sheet pixels) -> where it lives in the atlas.
+    placed: HashMap<(String, [u32; 4]), AtlasRect>,
+    cursor: (u32, u32),
+    shelf_h: u32,
+    // Out of room; starts over before the next list.
+    full: bool,
+    // `[x0, y0, x1, y1)` packed since the last upload.
+    dirty: Option<[u32; 4]>,
+}
+
+impl GlyphAtlas {
+    pub fn new() -> Self {
+        Self {
+            image: RgbaImage::new(ATLAS_SIZE, ATLAS_SIZE),
+            sheet_paths: HashMap::new(),
+            sheets: Vec::new(),
+            placed: HashMap::new(),
+            cursor: (0, 0),
+            shelf_h: 0,
+            full: false,
+            dirty: None,
+        }
+    }
+
+    /// Makes a font sheet (by its texture key) available for packing.
+    pub fn register_sheet(&mut self, key: String, path: PathBuf) {
+        self.sheet_paths.insert(key, path);
+    }
+
+    /// Has the next `take_upload` hand out the whole image again, for when the
+    /// texture it was uploaded to is gone.
+    pub fn invalidate(&mut self) {
+        self.dirty = Some([0, 0, ATLAS_SIZE, ATLAS_SIZE]);
+    }
+
+    /// The atlas image and the `[x, y, width, height]` region of it packed since
+    /// the last call, if any.
+    pub fn take_upload(&mut self) -> Option<(&RgbaImage, [u32; 4])> {
+        let [x0, y0, x1, y1] = self.dirty.take()?;
+        Some((&self.image, [x0, y0, x1 - x0, y1 - y0]))
+    }
+
+    /// Replaces every `ObjectType::Text` in the list with atlas sprites.
+    /// Returns whether the list had any text.
+    pub fn resolve(&mut self, render_list: &mut RenderList, fonts: &HashMap<&'static str, Font>) -> bool {
+        if !render_list.objects.iter().any(|o| matches!(o.object_type, ObjectType::Text { .. })) {
+            return false;
+        }
+        // Lists already drawn are done with the old glyphs; this one hasn't placed any yet.
+        if self.full {
+            info!("Glyph atlas full; starting over.");
+            self.image = RgbaImage::new(ATLAS_SIZE, ATLAS_SIZE);
+            self.placed.clear();
+            self.cursor = (0, 0);
+            self.shelf_h = 0;
+            self.full = false;
+        }
+        let objects = std::mem::take(&mut render_list.objects);
+        render_list.objects.reserve(objects.len());
+        for obj in objects {
+            if matches!(obj.object_type, ObjectType::Text { .. }) {
+                self.layout(&obj, fonts, &mut render_list.objects);
+            } else {
+                render_list.objects.push(obj);
+            }
+        }
+        true
+    }
+
+    /// Lays out one text object as glyph quads, as `compose` lays out text. Model
+    /// space is world units with the origin at the block's center; `align` places
+    /// each line within the block.
+    fn layout(&mut self, obj: &RenderObject, fonts: &HashMap<&'static str, Font>, out: &mut Vec<RenderObject>) {
+        let ObjectType::Text { font: font_name, text, size, color, align } = &obj.object_type else { return };
+        let Some(font) = fonts.get(font_name) else {
+            warn!("Text uses unknown font '{}'.", font_name);
+            return;
+        };
+        let cap_height = if font.height > 0 { font.height } else { font.line_spacing };
+        if cap_height <= 0 || *size <= 0.0 {
+            return;
+        }
+        let scale = size / cap_height as f32;
+
+        let lines: Vec<&str> = text.lines().collect();
+        let line_widths: Vec<i32> = lines.iter().map(|l| font::measure_line_width_logical(font, l, fonts)).collect();
+        let block_w = quantize_up_even_i32(line_widths.iter().copied().max().unwrap_or(0)) as f32;
+        let block_h = font.height + (lines.len().saturating_sub(1) as i32) * font.line_spacing;
+        let mut pen_y = lrint_ties_even(-(block_h as f32) * 0.5) as i32;
+
+        for (line, line_w) in lines.into_iter().zip(line_widths) {
+            pen_y += font.height;
+            let mut pen_x = lrint_ties_even(-0.5 * block_w + align * (block_w - line_w as f32)) as i32;
+
+            for ch in line.chars() {
+                let Some(glyph) = font::find_glyph(font, ch, fonts) else { continue };
+                let blank = ch == ' ' && !font.glyph_map.contains_key(&ch);
+                if !blank && glyph.size[0] > 0.0 && glyph.size[1] > 0.0 {
+                    // A glyph the atlas has no room for draws from its own sheet.
+                    let (texture_id, rect) = match self.place(glyph) {
+                        Some(rect) => (GLYPH_ATLAS_KEY.to_string(), Some(rect)),
+                        None => (glyph.texture_key.clone(), sheet_rect(glyph)),
+                    };
+                    if let Some(rect) = rect {
+                        let (w, h) = (glyph.size[0] * scale, glyph.size[1] * scale);
+                        let x = (pen_x as f32 + glyph.offset[0]) * scale + 0.5 * w;
+                        let y = -((pen_y as f32 + glyph.offset[1]) * scale + 0.5 * h);
+                        out.push(RenderObject {
+                            object_type: ObjectType::Sprite {
+                                texture_id,
+                                tint: *color,
+                                uv_scale: rect.uv_scale,
+                                uv_offset: rect.uv_offset,
+                                edge_fade: [0.0; 4],
+                                corner_tint: [[1.0; 4]; 4],
+                                material: Material::Standard,
+                                glow: [0.0; 4],
+                            },
+                            transform: obj.transform
+                                * Matrix4::from_translation(Vector3::new(x, y, 0.0))
+                                * Matrix4::from_nonuniform_scale(w, h, 1.0),
+                            blend: obj.blend,
+                            z: obj.z,
+                            order: obj.order,
+                            mask: obj.mask,
+                            clip: obj.clip,
+                            group: obj.group,
+                        });
+                    }
+                }
+                pen_x += lrint_ties_even(glyph.advance) as i32;
+            }
+            pen_y += font.line_spacing - font.height;
+        }
+    }
+
+    fn place(&mut self, glyph: &Glyph) -> Option<AtlasRect> {
+        let r = glyph.tex_rect.map(|v| v.round().max(0.0) as u32);
+        let key = (glyph.texture_key.clone(), r);
+        if let Some(rect) = self.placed.get(&key) {
+            return Some(*rect);
+        }
+
+        let (w, h) = (r[2].saturating_sub(r[0]), r[3].saturating_sub(r[1]));
+        if self.full || w == 0 || h == 0 || w + 2 * PADDING > ATLAS_SIZE || h + 2 * PADDING > ATLAS_SIZE {
+            return None;
+        }
+        if self.cursor.0 + w + 2 * PADDING > ATLAS_SIZE {
+            self.cursor = (0, self.cursor.1 + self.shelf_h);
+            self.shelf_h = 0;
+        }
+        // Glyphs placed earlier in this list are still drawn from where they are.
+        if self.cursor.1 + h + 2 * PADDING > ATLAS_SIZE {
+            self.full = true;
+            return None;
+        }
+
+        let sheet = self.sheet(&glyph.texture_key)?;
+        if r[2] > sheet.width() || r[3] > sheet.height() {
+            return None;
+        }
+        let pixels = image::imageops::crop_imm(sheet, r[0], r[1], w, h).to_image();
+        let (x, y) = (self.cursor.0 + PADDING, self.cursor.1 + PADDING);
+        image::imageops::replace(&mut self.image, &pixels, x as i64, y as i64);
+
+        // The padding goes up too, so stale texels from before a restart never bleed in.
+        let (x0, y0, x1, y1) = (self.cursor.0, self.cursor.1, x + w + PADDING, y + h + PADDING);
+        self.dirty = Some(self.dirty.map_or([x0, y0, x1, y1], |d| [d[0].min(x0), d[1].min(y0), d[2].max(x1), d[3].max(y1)]));
+        self.cursor.0 += w + 2 * PADDING;
+        self.shelf_h = self.shelf_h.max(h + 2 * PADDING);
+
+        let size = ATLAS_SIZE as f32;
+        let rect = AtlasRect {
+            uv_offset: [x as f32 / size, y as f32 / size],
+            uv_scale: [w as f32 / size, h as f32 / size],
+        };
+        self.placed.insert(key, rect);
+        Some(rect)
+    }
+
+    fn sheet(&mut self, key: &str) -> Option<&RgbaImage> {
+        if let Some(i) = self.sheets.iter().position(|(k, _)| k == key) {
+            let entry = self.sheets.remove(i);
+            self.sheets.push(entry);
+        } else {
+            let path = self.sheet_paths.get(key)?;
+            let image = match image::open(path) {
+                Ok(img) => img.to_rgba8(),
+                Err(e) => {
+                    warn!("Glyph atlas could not read '{}': {}", path.display(), e);
+                    return None;
+                }
+            };
+            if self.sheets.len() >= MAX_CACHED_SHEETS {
+                self.sheets.remove(0);
+            }
+            self.sheets.push((key.to_string(), image));
+        }
+        self.sheets.last().map(|(_, img)| img)
+    }
+}
+
+/// Where `glyph` lives in its own font sheet, for drawing it straight from there.
+fn sheet_rect(glyph: &Glyph) -> Option<AtlasRect> {
+    let meta = assets::texture_dims(&glyph.texture_key)?;
+    let (w, h) = (meta.w as f32, meta.h as f32);
+    let r = glyph.tex_rect;
+    Some(AtlasRect {
+        uv_offset: [r[0] / w, r[1] / h],
+        uv_scale: [(r[2] - r[0]) / w, (r[3] - r[1]) / h],
+    })
+}
@@ -0,0 +1,13 @@
+//! Named z layers for what's drawn over a whole screen, so overlays agree on
+//! what covers what instead of each picking a number.
+//!
+//! Inside a layer, later actors draw over earlier ones: `batch::sort` orders by
+//! z and then build order, and is stable. An overlay's text therefore sits on
+//! its own dimmed backdrop without a `+ 1`.
+
+/// Brief notices over the screen, like a gamepad connecting.
+pub const NOTICE: i16 = 1000;
+/// Screen-wide panels (the quit prompt, song filter) and the fade in.
+pub const OVERLAY: i16 = 1100;
+/// The fade out, over everything.
+pub const FADE_OUT: i16 = 1200;
@@ -5,4 +5,7 @@ pub mod color;
 pub mod dsl;
 pub mod anim;
 pub mod runtime;
-pub mod font;
\ No newline at end of file
+pub mod font;
+pub mod primitives;
+pub mod glyph_atlas;
+pub mod layer;
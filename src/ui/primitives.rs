@@ -0,0 +1,156 @@
+use crate::act;
+use crate::assets;
+use crate::core::gfx::BlendMode;
+use crate::ui::actors::Actor;
+
+/* ============================ GRADIENTS ============================ */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientDir {
+    /// `from` on the left edge, `to` on the right edge.
+    Horizontal,
+    /// `from` on the top edge, `to` on the bottom edge.
+    Vertical,
+}
+
+pub struct GradientParams {
+    pub align: [f32; 2],
+    pub xy: [f32; 2],
+    pub size: [f32; 2],
+    pub from: [f32; 4],
+    pub to: [f32; 4],
+    pub dir: GradientDir,
+    pub z: i16,
+}
+
+/// A solid quad whose color ramps linearly between two colors along one axis.
+pub fn gradient_quad(p: GradientParams) -> Actor {
+    let (f, t) = (p.from, p.to);
+    match p.dir {
+        GradientDir::Horizontal => act!(quad:
+            align(p.align[0], p.align[1]):
+            xy(p.xy[0], p.xy[1]):
+            zoomto(p.size[0], p.size[1]):
+            diffuseleftedge(f[0], f[1], f[2], f[3]):
+            diffuserightedge(t[0], t[1], t[2], t[3]):
+            z(p.z)
+        ),
+        GradientDir::Vertical => act!(quad:
+            align(p.align[0], p.align[1]):
+            xy(p.xy[0], p.xy[1]):
+            zoomto(p.size[0], p.size[1]):
+            diffusetopedge(f[0], f[1], f[2], f[3]):
+            diffusebottomedge(t[0], t[1], t[2], t[3]):
+            z(p.z)
+        ),
+    }
+}
+
+/* ============================ NINE-SLICE ============================ */
+
+pub struct NineSliceParams<'a> {
+    pub texture: &'a str,
+    /// Top-left corner of the panel.
+    pub xy: [f32; 2],
+    pub size: [f32; 2],
+    /// Border insets in source texture pixels: (left, right, top, bottom).
+    pub insets: [f32; 4],
+    /// Scale applied to the borders when drawn; the center stretches to fill.
+    pub border_scale: f32,
+    pub tint: [f32; 4],
+    pub z: i16,
+}
+
+/// Splits a texture into a 3x3 grid and draws it so the corners keep their size,
+/// the edges stretch along one axis, and the center stretches along both.
+pub fn nine_slice(p: NineSliceParams) -> Vec<Actor> {
+    let Some(meta) = assets::texture_dims(p.texture) else {
+        return Vec::new();
+    };
+    // Insets are in logical pixels, so high-density art slices the same way.
+    let (tw, th) = meta.logical_size();
+    let [il, ir, it, ib] = p.insets;
+
+    // Destination border sizes, shrunk proportionally if the panel is too small.
+    let mut dl = il * p.border_scale;
+    let mut dr = ir * p.border_scale;
+    let mut dt = it * p.border_scale;
+    let mut db = ib * p.border_scale;
+    let fit_x = if dl + dr > p.size[0] && dl + dr > 0.0 { p.size[0] / (dl + dr) } else { 1.0 };
+    let fit_y = if dt + db > p.size[1] && dt + db > 0.0 { p.size[1] / (dt + db) } else { 1.0 };
+    dl *= fit_x; dr *= fit_x;
+    dt *= fit_y; db *= fit_y;
+
+    let xs = [p.xy[0], p.xy[0] + dl, p.xy[0] + p.size[0] - dr, p.xy[0] + p.size[0]];
+    let ys = [p.xy[1], p.xy[1] + dt, p.xy[1] + p.size[1] - db, p.xy[1] + p.size[1]];
+    let us = [0.0, il / tw, 1.0 - ir / tw, 1.0];
+    let vs = [0.0, it / th, 1.0 - ib / th, 1.0];
+
+    let c = p.tint;
+    let mut actors = Vec::with_capacity(9);
+    for row in 0..3 {
+        for col in 0..3 {
+            let w = xs[col + 1] - xs[col];
+            let h = ys[row + 1] - ys[row];
+            if w <= 0.0 || h <= 0.0 {
+                continue;
+            }
+            actors.push(act!(sprite(p.texture):
+                align(0.0, 0.0):
+                xy(xs[col], ys[row]):
+                zoomto(w, h):
+                customtexturerect(us[col], vs[row], us[col + 1], vs[row + 1]):
+                diffuse(c[0], c[1], c[2], c[3]):
+                z(p.z)
+            ));
+        }
+    }
+    actors
+}
+
+/* ============================ ARCS / RINGS ============================ */
+
+/// Upper bound on generated segments so huge rings stay cheap.
+const ARC_MAX_SEGMENTS: u32 = 128;
+
+pub struct ArcParams {
+    pub center: [f32; 2],
+    /// Radius to the middle of the stroke.
+    pub radius: f32,
+    pub thickness: f32,
+    /// Degrees clockwise from 12 o'clock.
+    pub start_deg: f32,
+    /// Degrees clockwise from `start_deg`; 360 draws a full ring.
+    pub sweep_deg: f32,
+    pub color: [f32; 4],
+    pub z: i16,
+}
+
+/// An arc (or full ring) as one triangle strip between its inner and outer
+/// edges, for timers, progress rings and spinners. Neighboring segments share
+/// an edge, so translucent rings blend evenly.
+pub fn arc(p: ArcParams) -> Actor {
+    let sweep = p.sweep_deg.clamp(-360.0, 360.0);
+    let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+    if sweep != 0.0 && p.radius > 0.0 && p.thickness > 0.0 {
+        // Roughly one segment per 4px of outer circumference, but never fewer than 8.
+        let outer = p.radius + 0.5 * p.thickness;
+        let inner = (p.radius - 0.5 * p.thickness).max(0.0);
+        let arc_len = outer * sweep.abs().to_radians();
+        let segments = ((arc_len / 4.0).ceil() as u32).clamp(8, ARC_MAX_SEGMENTS);
+        let step = sweep / segments as f32;
+
+        vertices.reserve(2 * (segments as usize + 1));
+        for i in 0..=segments {
+            let (s, c) = (p.start_deg + step * i as f32).to_radians().sin_cos();
+            vertices.push([p.center[0] + inner * s, p.center[1] - inner * c]);
+            vertices.push([p.center[0] + outer * s, p.center[1] - outer * c]);
+        }
+        indices.reserve(6 * segments as usize);
+        for i in 0..segments as u16 {
+            let (a, b, c, d) = (2 * i, 2 * i + 1, 2 * i + 2, 2 * i + 3);
+            indices.extend_from_slice(&[a, b, d, a, d, c]);
+        }
+    }
+    Actor::Mesh { vertices, indices, colors: vec![p.color], z: p.z, blend: BlendMode::Alpha }
+}
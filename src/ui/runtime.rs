@@ -7,9 +7,17 @@ struct Entry {
     last_seen_frame: u64,
 }
 
+/// Scroll clock for one marquee text; restarts when its content changes.
+struct MarqueeEntry {
+    content: u64,
+    elapsed: f32,
+    last_seen_frame: u64,
+}
+
 #[derive(Default)]
 struct Registry {
     map: HashMap<u64, Entry>,
+    marquees: HashMap<u64, MarqueeEntry>,
     frame: u64,
 }
 
@@ -26,10 +34,14 @@ pub fn tick(dt: f32) {
         for e in r.map.values_mut() {
             e.seq.update(dt);
         }
+        for e in r.marquees.values_mut() {
+            e.elapsed += dt;
+        }
 
         let cur = r.frame;
         // Drop anything not seen last frame (one-frame grace is usually enough).
         r.map.retain(|_, e| e.last_seen_frame + 1 >= cur);
+        r.marquees.retain(|_, e| e.last_seen_frame + 1 >= cur);
     });
 }
 
@@ -51,6 +63,23 @@ pub fn materialize(id: u64, initial: TweenState, steps: &[Step]) -> TweenState {
     })
 }
 
+/// Seconds the marquee text at this site has been scrolling. The clock starts
+/// on first sight and restarts whenever `content` (a hash of the text) changes.
+pub fn marquee_phase(id: u64, content: u64) -> f32 {
+    REG.with(|r| {
+        let mut r = r.borrow_mut();
+        let frame = r.frame;
+
+        let ent = r.marquees.entry(id).or_insert(MarqueeEntry { content, elapsed: 0.0, last_seen_frame: frame });
+        if ent.content != content {
+            ent.content = content;
+            ent.elapsed = 0.0;
+        }
+        ent.last_seen_frame = frame;
+        ent.elapsed
+    })
+}
+
 /// Stable-ish id for a macro callsite, with an optional per-instance discriminator.
 pub fn site_id(file: &'static str, line: u32, col: u32, extra: u64) -> u64 {
     // FNV-1a 64
@@ -64,5 +93,9 @@ pub fn site_id(file: &'static str, line: u32, col: u32, extra: u64) -> u64 {
 
 // Optional manual clear (e.g., on screen swaps if desired).
 pub fn clear_all() {
-    REG.with(|r| r.borrow_mut().map.clear());
+    REG.with(|r| {
+        let mut r = r.borrow_mut();
+        r.map.clear();
+        r.marquees.clear();
+    });
 }